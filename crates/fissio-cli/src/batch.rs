@@ -0,0 +1,168 @@
+//! Running a pipeline over a JSONL file of inputs.
+//!
+//! Each input line is either a JSON string or an object with an `input` and
+//! an optional `id`:
+//!
+//! ```json
+//! {"id": "ticket-1042", "input": "My invoice was charged twice"}
+//! ```
+//!
+//! One result line is written per input, in input order, with the output,
+//! token usage, and latency. A failed input gets an `error` and doesn't stop
+//! the batch.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use fissio_config::PipelineConfig;
+use fissio_engine::{EmptyReason, EngineOutput, PipelineEngine};
+use fissio_llm::StreamChunk;
+use fissio_monitor::{InMemoryCollector, MetricsCollector};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{EngineArgs, EngineTemplate};
+
+/// Arguments for `fissio batch`.
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Pipeline definition (JSON).
+    pipeline: PathBuf,
+    /// Inputs, one JSON value per line; read from stdin when omitted.
+    #[arg(long)]
+    input: Option<PathBuf>,
+    /// Write results here instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Number of inputs run at once.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    concurrency: u16,
+    #[command(flatten)]
+    engine: EngineArgs,
+}
+
+/// One input line.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchInput {
+    Text(String),
+    Item {
+        #[serde(default)]
+        id: Option<String>,
+        input: String,
+    },
+}
+
+/// One result line.
+#[derive(Debug, Default, Serialize)]
+struct BatchResult {
+    /// 1-based line number of the input.
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    empty_reason: Option<EmptyReason>,
+    input_tokens: u64,
+    output_tokens: u64,
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Runs `fissio batch`.
+pub async fn run(args: BatchArgs) -> Result<ExitCode> {
+    let config = PipelineConfig::from_file(&args.pipeline)?;
+    let template = args.engine.template(config)?;
+
+    let content = match &args.input {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("failed to read inputs '{}'", path.display()))?,
+        None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content).context("failed to read inputs from stdin")?;
+            content
+        }
+    };
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(io::BufWriter::new(
+            fs::File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?,
+        )),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let mut results = stream::iter(lines)
+        .map(|(n, line)| run_line(&template, n + 1, line))
+        .buffered(args.concurrency as usize);
+
+    let (mut total, mut failed) = (0, 0);
+    while let Some(result) = results.next().await {
+        total += 1;
+        if let Some(error) = &result.error {
+            failed += 1;
+            eprintln!("line {}: {}", result.line, error);
+        }
+        writeln!(out, "{}", serde_json::to_string(&result)?)?;
+        out.flush()?;
+    }
+
+    eprintln!("{} inputs, {} failed", total, failed);
+    Ok(if failed > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+async fn run_line(template: &EngineTemplate, line: usize, text: &str) -> BatchResult {
+    let mut result = BatchResult { line, ..Default::default() };
+    let input = match serde_json::from_str(text) {
+        Ok(BatchInput::Text(input)) => input,
+        Ok(BatchInput::Item { id, input }) => {
+            result.id = id;
+            input
+        }
+        Err(e) => {
+            result.error = Some(format!("invalid input: {}", e));
+            return result;
+        }
+    };
+
+    let collector = Arc::new(InMemoryCollector::new(template.pipeline_id()));
+    let engine = template.build().with_pretty_logs(false).with_collector(collector.clone());
+    let started = Instant::now();
+
+    if let Err(e) = execute(&engine, &input, &mut result).await {
+        result.error = Some(e.to_string());
+    }
+
+    let metrics = collector.flush();
+    result.input_tokens += metrics.total_input_tokens as u64;
+    result.output_tokens += metrics.total_output_tokens as u64;
+    result.elapsed_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+async fn execute(engine: &PipelineEngine, input: &str, result: &mut BatchResult) -> Result<()> {
+    match engine.execute_stream(input, &[]).await? {
+        EngineOutput::Stream(mut stream) => {
+            while let Some(chunk) = stream.next().await {
+                match chunk? {
+                    StreamChunk::Content(text) => result.output.push_str(&text),
+                    StreamChunk::Usage { input_tokens, output_tokens } => {
+                        result.input_tokens += input_tokens as u64;
+                        result.output_tokens += output_tokens as u64;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        EngineOutput::Complete(text) => result.output = text,
+        EngineOutput::Empty(reason) => result.empty_reason = Some(reason),
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,315 @@
+//! Dataset-backed regression gate.
+//!
+//! Runs every case of a JSONL dataset through a pipeline, scores the outputs
+//! against each case's expectations, and compares the aggregate scores with a
+//! stored baseline. Exits with a failure code when any delta exceeds its
+//! threshold so prompt changes can be gated in CI.
+//!
+//! Dataset format (one JSON object per line):
+//!
+//! ```json
+//! {"id": "refund", "input": "I want my money back", "expect": {"contains": ["refund"]}}
+//! ```
+//!
+//! A case may stub individual tools with `mock_tools`; unlisted tools run live:
+//!
+//! ```json
+//! {"input": "Latest Rust release?", "mock_tools": {"web_search": "Rust 1.84 was released on 2025-01-09."}}
+//! ```
+//!
+//! Cases take the same assertions as a [`fissio_eval::EvalSuite`], including
+//! `regex` and an LLM `judge` rubric. A suite file can be passed instead of a
+//! dataset. Without `--baseline`, the run fails when any case fails.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use fissio_config::PipelineConfig;
+use fissio_eval::{CaseResult, EvalCase, EvalSuite, Evaluator, Scores};
+use fissio_llm::Cassette;
+use serde::Serialize;
+
+use crate::models::model_config;
+
+/// Arguments for `fissio eval`.
+#[derive(Debug, Args)]
+pub struct EvalArgs {
+    /// Eval suite (JSON) to run instead of a dataset.
+    #[arg(conflicts_with = "dataset")]
+    suite: Option<PathBuf>,
+    /// Pipeline definition (JSON).
+    #[arg(long)]
+    pipeline: PathBuf,
+    /// Test cases, one JSON object per line.
+    #[arg(long, required_unless_present = "suite")]
+    dataset: Option<PathBuf>,
+    /// Baseline scores to compare against.
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Write this run's scores to the baseline file instead of comparing.
+    #[arg(long, requires = "baseline")]
+    update_baseline: bool,
+    /// Model used for nodes that don't pin one.
+    #[arg(long, env = "FISSIO_MODEL", default_value = "gpt-4o-mini")]
+    model: String,
+    /// API base URL for the model (e.g. an Ollama `/v1` endpoint).
+    #[arg(long, env = "FISSIO_API_BASE")]
+    api_base: Option<String>,
+    /// Model that grades `judge` rubrics; defaults to `--model`.
+    #[arg(long)]
+    judge_model: Option<String>,
+    /// Abort the run once this many tokens (input + output) are consumed.
+    #[arg(long)]
+    token_budget: Option<u64>,
+    /// Maximum allowed absolute drop in pass rate (0.0–1.0).
+    #[arg(long, default_value_t = 0.0)]
+    max_pass_rate_drop: f64,
+    /// Maximum allowed relative increase in average latency (0.5 = +50%).
+    #[arg(long, default_value_t = 0.5)]
+    max_latency_increase: f64,
+    /// Maximum allowed relative increase in total token usage.
+    #[arg(long, default_value_t = 0.2)]
+    max_token_increase: f64,
+    /// Print the report as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+    /// Record every LLM interaction to this cassette file.
+    #[arg(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Serve LLM responses from this cassette file instead of calling providers.
+    #[arg(long)]
+    replay: Option<PathBuf>,
+}
+
+/// Full report printed at the end of a run.
+#[derive(Debug, Serialize)]
+struct Report {
+    scores: Scores,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline: Option<Scores>,
+    violations: Vec<String>,
+    results: Vec<CaseResult>,
+}
+
+/// Thresholds a run must stay within relative to the baseline.
+#[derive(Debug, Clone, Copy)]
+struct Thresholds {
+    max_pass_rate_drop: f64,
+    max_latency_increase: f64,
+    max_token_increase: f64,
+}
+
+/// Runs `fissio eval`.
+pub async fn run(args: EvalArgs) -> Result<ExitCode> {
+    let config = PipelineConfig::from_file(&args.pipeline)?;
+    let (suite, source) = match (&args.suite, &args.dataset) {
+        (Some(path), _) => (EvalSuite::from_file(path)?, path),
+        (None, Some(path)) => (EvalSuite { name: None, cases: load_dataset(path)? }, path),
+        (None, None) => bail!("either a suite file or --dataset is required"),
+    };
+    if suite.cases.is_empty() {
+        bail!("'{}' contains no cases", source.display());
+    }
+
+    let model = model_config(&args.model, args.api_base.clone());
+    let mut evaluator = Evaluator::new(config, vec![model.clone()], model);
+    if let Some(judge) = &args.judge_model {
+        evaluator = evaluator.with_judge(model_config(judge, args.api_base.clone()));
+    }
+    if let Some(budget) = args.token_budget {
+        evaluator = evaluator.with_token_budget(budget);
+    }
+    let cassette = match (&args.record, &args.replay) {
+        (Some(path), _) => Some(Arc::new(Cassette::record(path))),
+        (None, Some(path)) => Some(Arc::new(Cassette::replay(path)?)),
+        (None, None) => None,
+    };
+    if let Some(cassette) = cassette {
+        evaluator = evaluator.with_cassette(cassette);
+    }
+
+    let report = evaluator.run(&suite).await?;
+    let (scores, results) = (report.scores, report.results);
+
+    let Some(baseline_path) = &args.baseline else {
+        let failed = results.iter().filter(|r| !r.passed).count();
+        let violations = match failed {
+            0 => vec![],
+            n => vec![format!("{} of {} cases failed", n, results.len())],
+        };
+        print_report(&Report { scores, baseline: None, violations, results }, args.json)?;
+        return Ok(if failed > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+    };
+
+    if args.update_baseline {
+        let json = serde_json::to_string_pretty(&scores)?;
+        fs::write(baseline_path, json)
+            .with_context(|| format!("failed to write baseline '{}'", baseline_path.display()))?;
+        print_report(&Report { scores, baseline: None, violations: vec![], results }, args.json)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let baseline_json = fs::read_to_string(baseline_path)
+        .with_context(|| format!("failed to read baseline '{}'", baseline_path.display()))?;
+    let baseline: Scores = serde_json::from_str(&baseline_json)
+        .with_context(|| format!("failed to parse baseline '{}'", baseline_path.display()))?;
+
+    let thresholds = Thresholds {
+        max_pass_rate_drop: args.max_pass_rate_drop,
+        max_latency_increase: args.max_latency_increase,
+        max_token_increase: args.max_token_increase,
+    };
+    let violations = compare(&scores, &baseline, thresholds);
+    let failed = !violations.is_empty();
+
+    print_report(&Report { scores, baseline: Some(baseline), violations, results }, args.json)?;
+
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+/// Reads a JSONL dataset, skipping blank lines.
+fn load_dataset(path: &Path) -> Result<Vec<EvalCase>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read dataset '{}'", path.display()))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: invalid case", path.display(), n + 1))
+        })
+        .collect()
+}
+
+/// Returns a description of every threshold the current scores violate.
+fn compare(current: &Scores, baseline: &Scores, thresholds: Thresholds) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    let drop = baseline.pass_rate - current.pass_rate;
+    if drop > thresholds.max_pass_rate_drop {
+        violations.push(format!(
+            "pass rate dropped {:.1}% ({:.1}% -> {:.1}%), allowed {:.1}%",
+            drop * 100.0,
+            baseline.pass_rate * 100.0,
+            current.pass_rate * 100.0,
+            thresholds.max_pass_rate_drop * 100.0
+        ));
+    }
+
+    if let Some(increase) = relative_increase(baseline.avg_latency_ms, current.avg_latency_ms) {
+        if increase > thresholds.max_latency_increase {
+            violations.push(format!(
+                "average latency increased {:.1}% ({:.0}ms -> {:.0}ms), allowed {:.1}%",
+                increase * 100.0,
+                baseline.avg_latency_ms,
+                current.avg_latency_ms,
+                thresholds.max_latency_increase * 100.0
+            ));
+        }
+    }
+
+    if let Some(increase) = relative_increase(baseline.total_tokens as f64, current.total_tokens as f64) {
+        if increase > thresholds.max_token_increase {
+            violations.push(format!(
+                "token usage increased {:.1}% ({} -> {}), allowed {:.1}%",
+                increase * 100.0,
+                baseline.total_tokens,
+                current.total_tokens,
+                thresholds.max_token_increase * 100.0
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Relative change from `base` to `current`, or `None` when there is no base to compare.
+fn relative_increase(base: f64, current: f64) -> Option<f64> {
+    if base <= 0.0 {
+        return None;
+    }
+    Some((current - base) / base)
+}
+
+fn print_report(report: &Report, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+        return Ok(());
+    }
+
+    for r in &report.results {
+        let status = if r.passed { "PASS" } else { "FAIL" };
+        println!("{} {} ({}ms, {} tokens)", status, r.id, r.elapsed_ms, r.tokens);
+        for f in &r.failures {
+            println!("     - {}", f);
+        }
+    }
+
+    let s = &report.scores;
+    println!();
+    println!(
+        "cases: {}  pass rate: {:.1}%  score: {:.2}  avg latency: {:.0}ms  tokens: {}",
+        s.cases,
+        s.pass_rate * 100.0,
+        s.avg_score,
+        s.avg_latency_ms,
+        s.total_tokens
+    );
+
+    if let Some(b) = &report.baseline {
+        println!(
+            "base:  {}  pass rate: {:.1}%  score: {:.2}  avg latency: {:.0}ms  tokens: {}",
+            b.cases,
+            b.pass_rate * 100.0,
+            b.avg_score,
+            b.avg_latency_ms,
+            b.total_tokens
+        );
+    }
+
+    if report.violations.is_empty() {
+        println!("\nOK");
+    } else {
+        println!("\nREGRESSION:");
+        for v in &report.violations {
+            println!("  - {}", v);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(pass_rate: f64, avg_latency_ms: f64, total_tokens: u64) -> Scores {
+        Scores { cases: 10, pass_rate, avg_score: pass_rate, avg_latency_ms, total_tokens }
+    }
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        max_pass_rate_drop: 0.05,
+        max_latency_increase: 0.5,
+        max_token_increase: 0.2,
+    };
+
+    #[test]
+    fn test_compare_within_thresholds() {
+        let baseline = scores(0.9, 1000.0, 5000);
+        let current = scores(0.88, 1200.0, 5500);
+        assert!(compare(&current, &baseline, THRESHOLDS).is_empty());
+    }
+
+    #[test]
+    fn test_compare_flags_regressions() {
+        let baseline = scores(0.9, 1000.0, 5000);
+        let current = scores(0.7, 2000.0, 8000);
+        assert_eq!(compare(&current, &baseline, THRESHOLDS).len(), 3);
+    }
+}
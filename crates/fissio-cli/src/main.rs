@@ -0,0 +1,74 @@
+//! Command-line entry point for running fissio pipelines without the HTTP server.
+//!
+//! Subcommands:
+//!
+//! - `fissio run` — Run a pipeline once, streaming the response to stdout
+//! - `fissio batch` — Run a pipeline over a JSONL file of inputs
+//! - `fissio validate` — Check pipeline definitions
+//! - `fissio plan` — Show what a run would execute, without calling any LLM
+//! - `fissio eval` — Dataset-backed regression gate for CI
+
+mod batch;
+mod eval;
+mod models;
+mod plan;
+mod run;
+mod validate;
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "fissio", version, about = "Pipeline-first agent framework CLI")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs a pipeline on one input and streams the response to stdout.
+    Run(run::RunArgs),
+    /// Runs a pipeline over a JSONL file of inputs, writing one result per line.
+    Batch(batch::BatchArgs),
+    /// Checks pipeline definitions for errors.
+    Validate(validate::ValidateArgs),
+    /// Shows the stages, models, and tools a run would use.
+    Plan(plan::PlanArgs),
+    /// Runs a pipeline over a dataset and gates on metric deltas against a baseline.
+    Eval(eval::EvalArgs),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "warn".parse().unwrap()),
+        )
+        .compact()
+        .init();
+
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Run(args) => run::run(args).await,
+        Command::Batch(args) => batch::run(args).await,
+        Command::Validate(args) => validate::run(args),
+        Command::Plan(args) => plan::run(args),
+        Command::Eval(args) => eval::run(args).await,
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Model and pipeline options shared by the commands that build an engine.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use fissio_config::{PipelineConfig, PresetRegistry};
+use fissio_core::ModelConfig;
+use fissio_engine::PipelineEngine;
+
+/// Options for the commands that run or plan a pipeline.
+#[derive(Debug, Args)]
+pub struct EngineArgs {
+    /// Model used for nodes that don't pin one.
+    #[arg(long, env = "FISSIO_MODEL", default_value = "gpt-4o-mini")]
+    pub model: String,
+    /// API base URL for the models (e.g. an Ollama `/v1` endpoint).
+    #[arg(long, env = "FISSIO_API_BASE")]
+    pub api_base: Option<String>,
+    /// Runs a node on another model, as `node=model`; repeatable.
+    #[arg(long = "node-model", value_name = "NODE=MODEL")]
+    pub node_models: Vec<String>,
+    /// Directory of pipeline JSON files that `Pipeline` nodes may reference.
+    #[arg(long)]
+    pub pipelines: Option<PathBuf>,
+}
+
+impl EngineArgs {
+    /// Builds an engine for `config`.
+    pub fn engine(&self, config: PipelineConfig) -> Result<PipelineEngine> {
+        Ok(self.template(config)?.build())
+    }
+
+    /// Resolves models and sub-pipelines once, for commands that build an
+    /// engine per input. Every model the pipeline or a `--node-model` names
+    /// is registered so pinned models aren't swapped for the default.
+    pub fn template(&self, config: PipelineConfig) -> Result<EngineTemplate> {
+        let node_models = self
+            .node_models
+            .iter()
+            .map(|pair| {
+                pair.split_once('=')
+                    .map(|(node, model)| (node.trim().to_string(), model.trim().to_string()))
+                    .ok_or_else(|| anyhow!("invalid --node-model '{}', expected node=model", pair))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let pipelines = match &self.pipelines {
+            Some(dir) => PresetRegistry::load_from_dir(dir)
+                .with_context(|| format!("failed to load pipelines from '{}'", dir.display()))?
+                .list()
+                .into_iter()
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut ids = BTreeSet::from([self.model.clone()]);
+        ids.extend(node_models.values().cloned());
+        for node in pipelines.iter().chain([&config]).flat_map(|p| &p.nodes) {
+            ids.extend(node.model.iter().chain(&node.fallback_models).cloned());
+        }
+        let models = ids.iter().map(|id| model_config(id, self.api_base.clone())).collect();
+        let default_model = model_config(&self.model, self.api_base.clone());
+
+        Ok(EngineTemplate { config, models, default_model, node_models, pipelines })
+    }
+}
+
+/// A pipeline with its models and sub-pipelines resolved, ready to build engines from.
+pub struct EngineTemplate {
+    config: PipelineConfig,
+    models: Vec<ModelConfig>,
+    default_model: ModelConfig,
+    node_models: HashMap<String, String>,
+    pipelines: Vec<PipelineConfig>,
+}
+
+impl EngineTemplate {
+    pub fn pipeline_id(&self) -> &str {
+        &self.config.id
+    }
+
+    pub fn build(&self) -> PipelineEngine {
+        PipelineEngine::new(
+            self.config.clone(),
+            self.models.clone(),
+            self.default_model.clone(),
+            self.node_models.clone(),
+        )
+        .with_pipelines(self.pipelines.clone())
+    }
+}
+
+/// A model config for `model`, served from `api_base` if given.
+pub fn model_config(model: &str, api_base: Option<String>) -> ModelConfig {
+    ModelConfig {
+        id: model.to_string(),
+        name: model.to_string(),
+        model: model.to_string(),
+        api_base,
+        mirrors: Vec::new(),
+        provider: None,
+        rate_limit: None,
+        azure: None,
+        region: None,
+        context_window: None,
+        pricing: None,
+    }
+}
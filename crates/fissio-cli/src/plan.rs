@@ -0,0 +1,84 @@
+//! Dry runs: what a pipeline would execute, without calling any LLM.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::Args;
+use fissio_config::PipelineConfig;
+use fissio_engine::{ExecutionPlan, StageKind, ToolStatus};
+
+use crate::models::EngineArgs;
+
+/// Arguments for `fissio plan`.
+#[derive(Debug, Args)]
+pub struct PlanArgs {
+    /// Pipeline definition (JSON).
+    pipeline: PathBuf,
+    #[command(flatten)]
+    engine: EngineArgs,
+    /// Print the plan as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Runs `fissio plan`.
+pub fn run(args: PlanArgs) -> Result<ExitCode> {
+    let config = PipelineConfig::from_file(&args.pipeline)?;
+    let plan = args.engine.engine(config)?.plan();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        print_plan(&plan, 0);
+    }
+
+    Ok(if plan.is_runnable() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn print_plan(plan: &ExecutionPlan, depth: usize) {
+    let indent = "  ".repeat(depth);
+
+    for (i, stage) in plan.stages.iter().enumerate() {
+        let kind = match stage.kind {
+            StageKind::Sequential => "sequential",
+            StageKind::Parallel => "parallel",
+            StageKind::Map => "map",
+            StageKind::Streaming => "streaming",
+        };
+        let limit = stage.max_concurrency.map(|n| format!(" (max {})", n)).unwrap_or_default();
+        println!("{}stage {}: {}{} {}", indent, i + 1, kind, limit, stage.nodes.join(", "));
+    }
+
+    for node in &plan.nodes {
+        let model = node.model.as_deref().unwrap_or("-");
+        let overridden = if node.overridden { " (override)" } else { "" };
+        let conditional = if node.conditional { " [conditional]" } else { "" };
+        println!("{}{} {}: {}{}{}", indent, node.node_type, node.id, model, overridden, conditional);
+        if !node.fallback_models.is_empty() {
+            println!("{}  fallbacks: {}", indent, node.fallback_models.join(", "));
+        }
+        for tool in &node.tools {
+            let status = match tool.status {
+                ToolStatus::Available => "available",
+                ToolStatus::RequiresApproval => "requires approval",
+                ToolStatus::Denied => "denied",
+                ToolStatus::Unknown => "unknown",
+            };
+            println!("{}  tool {}: {}", indent, tool.name, status);
+        }
+        if let Some(error) = &node.error {
+            println!("{}  error: {}", indent, error);
+        }
+        if let Some(sub) = &node.sub_pipeline {
+            print_plan(sub, depth + 2);
+        }
+    }
+
+    if !plan.unreachable.is_empty() {
+        println!("{}unreachable: {}", indent, plan.unreachable.join(", "));
+    }
+    for warning in &plan.warnings {
+        println!("{}warning: {}", indent, warning);
+    }
+}
@@ -0,0 +1,140 @@
+//! Running a pipeline once from the command line.
+//!
+//! The response streams to stdout as it is generated, and tool activity goes
+//! to stderr, so the output can be piped. With `--json`, a single JSON object
+//! with the response, token usage, and per-node metrics is printed at the end.
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use fissio_config::PipelineConfig;
+use fissio_core::Citation;
+use fissio_engine::{EmptyReason, EngineOutput};
+use fissio_llm::StreamChunk;
+use fissio_monitor::{InMemoryCollector, MetricsCollector, NodeMetrics};
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::models::EngineArgs;
+
+/// Arguments for `fissio run`.
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Pipeline definition (JSON).
+    pipeline: PathBuf,
+    /// Message to run the pipeline on; read from stdin when omitted.
+    #[arg(long)]
+    input: Option<String>,
+    #[command(flatten)]
+    engine: EngineArgs,
+    /// Print one JSON object with the response and metrics instead of streaming text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// What `fissio run --json` prints.
+#[derive(Debug, Serialize)]
+struct RunReport {
+    pipeline_id: String,
+    output: String,
+    /// Why the pipeline returned nothing, when it didn't.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    empty_reason: Option<EmptyReason>,
+    input_tokens: u64,
+    output_tokens: u64,
+    elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    citations: Vec<Citation>,
+    nodes: Vec<NodeMetrics>,
+}
+
+/// Runs `fissio run`.
+pub async fn run(args: RunArgs) -> Result<ExitCode> {
+    let config = PipelineConfig::from_file(&args.pipeline)?;
+    let input = match args.input {
+        Some(input) => input,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).context("failed to read input from stdin")?;
+            input
+        }
+    };
+
+    let pipeline_id = config.id.clone();
+    let collector = Arc::new(InMemoryCollector::new(&pipeline_id));
+    let engine = args.engine.engine(config)?.with_pretty_logs(false).with_collector(collector.clone());
+
+    let started = Instant::now();
+    let mut report = RunReport {
+        pipeline_id,
+        output: String::new(),
+        empty_reason: None,
+        input_tokens: 0,
+        output_tokens: 0,
+        elapsed_ms: 0,
+        citations: Vec::new(),
+        nodes: Vec::new(),
+    };
+    let mut stdout = io::stdout().lock();
+
+    match engine.execute_stream(&input, &[]).await? {
+        EngineOutput::Stream(mut stream) => {
+            while let Some(chunk) = stream.next().await {
+                match chunk? {
+                    StreamChunk::Content(text) => {
+                        if !args.json {
+                            write!(stdout, "{}", text)?;
+                            stdout.flush()?;
+                        }
+                        report.output.push_str(&text);
+                    }
+                    StreamChunk::Usage { input_tokens, output_tokens } => {
+                        report.input_tokens += input_tokens as u64;
+                        report.output_tokens += output_tokens as u64;
+                    }
+                    StreamChunk::Progress(status) if !args.json => eprintln!("{}", status),
+                    StreamChunk::ToolCallStarted { name, .. } if !args.json => eprintln!("Running {}…", name),
+                    StreamChunk::Citation(citation) => report.citations.push(citation),
+                    _ => {}
+                }
+            }
+        }
+        EngineOutput::Complete(text) => {
+            if !args.json {
+                write!(stdout, "{}", text)?;
+            }
+            report.output = text;
+        }
+        EngineOutput::Empty(reason) => report.empty_reason = Some(reason),
+        _ => {}
+    }
+
+    let metrics = collector.flush();
+    report.input_tokens += metrics.total_input_tokens as u64;
+    report.output_tokens += metrics.total_output_tokens as u64;
+    report.elapsed_ms = started.elapsed().as_millis() as u64;
+    report.nodes = metrics.node_metrics;
+    let empty = report.output.trim().is_empty();
+
+    if args.json {
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&report)?)?;
+    } else {
+        if !report.output.ends_with('\n') && !empty {
+            writeln!(stdout)?;
+        }
+        for citation in &report.citations {
+            let source = citation.url.as_deref().or(citation.title.as_deref()).unwrap_or(&citation.source_id);
+            writeln!(stdout, "[{}] {}", citation.index, source)?;
+        }
+        if empty {
+            eprintln!("{}", report.empty_reason.unwrap_or(EmptyReason::EmptyResult).message());
+        }
+    }
+
+    Ok(if empty { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
@@ -0,0 +1,53 @@
+//! Checking pipeline definitions without running them.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::Args;
+use fissio_config::PipelineConfig;
+use serde::Serialize;
+
+/// Arguments for `fissio validate`.
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Pipeline definitions (JSON) to check.
+    #[arg(required = true)]
+    pipelines: Vec<PathBuf>,
+    /// Print the results as JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Validation {
+    path: PathBuf,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Runs `fissio validate`.
+pub fn run(args: ValidateArgs) -> Result<ExitCode> {
+    let results: Vec<Validation> = args
+        .pipelines
+        .into_iter()
+        .map(|path| {
+            let error = PipelineConfig::from_file(&path).and_then(|config| config.validate()).err();
+            Validation { path, valid: error.is_none(), error: error.map(|e| e.to_string()) }
+        })
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for r in &results {
+            match &r.error {
+                None => println!("ok    {}", r.path.display()),
+                Some(e) => println!("error {}: {}", r.path.display(), e),
+            }
+        }
+    }
+
+    Ok(if results.iter().all(|r| r.valid) { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
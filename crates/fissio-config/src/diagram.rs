@@ -0,0 +1,162 @@
+//! Rendering pipeline graphs as Graphviz DOT or Mermaid flowcharts.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{EdgeType, NodeType, PipelineConfig};
+
+/// How a node type is drawn: DOT shape, Mermaid shape brackets, and fill color.
+struct NodeStyle {
+    dot_shape: &'static str,
+    mermaid: (&'static str, &'static str),
+    fill: &'static str,
+}
+
+fn node_style(node_type: NodeType) -> NodeStyle {
+    let (dot_shape, mermaid, fill) = match node_type {
+        NodeType::Llm => ("box", ("[", "]"), "#dbeafe"),
+        NodeType::Worker => ("box3d", ("[[", "]]"), "#dcfce7"),
+        NodeType::Router => ("diamond", ("{", "}"), "#fef3c7"),
+        NodeType::Gate => ("hexagon", ("{{", "}}"), "#fee2e2"),
+        NodeType::Evaluator => ("octagon", ("{{", "}}"), "#fce7f3"),
+        NodeType::Aggregator | NodeType::Synthesizer => ("invtrapezium", ("[\\", "/]"), "#ede9fe"),
+        NodeType::Orchestrator | NodeType::Coordinator => ("component", ("[[", "]]"), "#ffedd5"),
+        NodeType::Map => ("parallelogram", ("[/", "/]"), "#ccfbf1"),
+        NodeType::Pipeline => ("folder", ("[[", "]]"), "#e5e7eb"),
+        NodeType::Retriever => ("cylinder", ("[(", ")]"), "#e0f2fe"),
+        NodeType::Grounding => ("note", (">", "]"), "#fef9c3"),
+    };
+    NodeStyle { dot_shape, mermaid, fill }
+}
+
+impl PipelineConfig {
+    /// Renders the graph in Graphviz DOT, e.g. for `dot -Tsvg`.
+    ///
+    /// Node shapes and colors follow the node type. Parallel edges are bold,
+    /// conditional edges dashed, and dynamic edges dotted.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph {} {{", dot_quote(&self.id));
+        out.push_str("  rankdir=LR;\n");
+        out.push_str("  node [style=filled, fontname=\"Helvetica\"];\n");
+        out.push_str("  input [shape=circle, fillcolor=\"#f3f4f6\"];\n");
+        out.push_str("  output [shape=doublecircle, fillcolor=\"#f3f4f6\"];\n");
+        for node in &self.nodes {
+            let style = node_style(node.node_type);
+            let _ = writeln!(
+                out,
+                "  {} [label={}, shape={}, fillcolor=\"{}\"];",
+                dot_quote(&node.id),
+                dot_quote(&self.node_label(&node.id, "\n")),
+                style.dot_shape,
+                style.fill,
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = match edge.edge_type {
+                EdgeType::Direct => Vec::new(),
+                EdgeType::Parallel => vec!["style=bold".to_string()],
+                EdgeType::Conditional => vec!["style=dashed".to_string()],
+                EdgeType::Dynamic => vec!["style=dotted".to_string()],
+            };
+            if let Some(label) = edge_label(edge.edge_type, edge.max_concurrency) {
+                attrs.push(format!("label={}", dot_quote(&label)));
+            }
+            let attrs = if attrs.is_empty() { String::new() } else { format!(" [{}]", attrs.join(", ")) };
+            for from in edge.from.as_vec() {
+                for to in edge.to.as_vec() {
+                    let _ = writeln!(out, "  {} -> {}{};", dot_quote(from), dot_quote(to), attrs);
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as a Mermaid flowchart, which GitHub renders in
+    /// markdown files and pull requests.
+    ///
+    /// Node shapes and colors follow the node type. Parallel edges are thick,
+    /// and conditional and dynamic edges dotted.
+    pub fn to_mermaid(&self) -> String {
+        // Node IDs may clash with Mermaid keywords (`end`), so nodes get
+        // generated keys and show their ID in the label
+        let mut keys: HashMap<&str, String> = HashMap::new();
+        let mut next_key = (0..).map(|i| format!("n{}", i));
+        let mut out = String::from("flowchart LR\n");
+        out.push_str("  input((input))\n");
+        out.push_str("  output(((output)))\n");
+        keys.insert("input", "input".into());
+        keys.insert("output", "output".into());
+
+        let mut classes: Vec<NodeType> = Vec::new();
+        for node in &self.nodes {
+            let key = next_key.next().unwrap_or_default();
+            let (open, close) = node_style(node.node_type).mermaid;
+            let label = mermaid_escape(&self.node_label(&node.id, "<br/>"));
+            let _ = writeln!(out, "  {}{}\"{}\"{}:::{}", key, open, label, close, node.node_type);
+            if !classes.contains(&node.node_type) {
+                classes.push(node.node_type);
+            }
+            keys.entry(node.id.as_str()).or_insert(key);
+        }
+        // Edges may name nodes the config lacks; draw those plainly
+        for edge in &self.edges {
+            for id in edge.from.as_vec().into_iter().chain(edge.to.as_vec()) {
+                if !keys.contains_key(id) {
+                    let key = next_key.next().unwrap_or_default();
+                    let _ = writeln!(out, "  {}[\"{}\"]", key, mermaid_escape(id));
+                    keys.insert(id, key);
+                }
+            }
+        }
+
+        for edge in &self.edges {
+            let arrow = match edge.edge_type {
+                EdgeType::Direct => "-->",
+                EdgeType::Parallel => "==>",
+                EdgeType::Conditional | EdgeType::Dynamic => "-.->",
+            };
+            let label = edge_label(edge.edge_type, edge.max_concurrency)
+                .map(|l| format!("|\"{}\"|", mermaid_escape(&l)))
+                .unwrap_or_default();
+            for from in edge.from.as_vec() {
+                for to in edge.to.as_vec() {
+                    let _ = writeln!(out, "  {} {}{} {}", keys[from], arrow, label, keys[to]);
+                }
+            }
+        }
+
+        for node_type in classes {
+            let _ = writeln!(out, "  classDef {} fill:{},stroke:#374151", node_type, node_style(node_type).fill);
+        }
+        out
+    }
+
+    /// A node's ID, type, and model, one per line.
+    fn node_label(&self, id: &str, line_break: &str) -> String {
+        let Some(node) = self.nodes.iter().find(|n| n.id == id) else {
+            return id.to_string();
+        };
+        match &node.model {
+            Some(model) => format!("{}{}{} · {}", id, line_break, node.node_type, model),
+            None => format!("{}{}{}", id, line_break, node.node_type),
+        }
+    }
+}
+
+fn edge_label(edge_type: EdgeType, max_concurrency: Option<usize>) -> Option<String> {
+    match (edge_type, max_concurrency) {
+        (EdgeType::Parallel, Some(limit)) => Some(format!("max {}", limit)),
+        (EdgeType::Dynamic, _) => Some("dynamic".into()),
+        _ => None,
+    }
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
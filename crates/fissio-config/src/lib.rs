@@ -7,7 +7,11 @@
 //! - [`NodeConfig`] — Configuration for individual pipeline nodes
 //! - [`EdgeConfig`] — Connections between nodes with routing behavior
 //! - [`NodeType`] and [`EdgeType`] — Available node and edge types
+//! - [`FailurePolicy`] — What a parallel edge does when one of its targets fails
 //! - [`PresetRegistry`] — Load pipeline presets from JSON files
+//! - [`ResidencyPolicy`] — Providers and regions a pipeline's models may use
+//! - [`ModelLifecycle`] — Loading models before a run and unloading them after
+//! - [`SecretResolver`] — Filling `${VAR}` placeholders from allowlisted environment variables
 //!
 //! # Loading from JSON
 //!
@@ -17,6 +21,11 @@
 //! let config = PipelineConfig::from_file("pipeline.json")?;
 //! ```
 //!
+//! # Diagrams
+//!
+//! [`PipelineConfig::to_dot`] and [`PipelineConfig::to_mermaid`] render the
+//! graph for Graphviz or Mermaid, for documenting pipelines outside the editor.
+//!
 //! # Builder API
 //!
 //! ```rust
@@ -34,9 +43,19 @@
 //!
 //! assert_eq!(config.nodes.len(), 1);
 //! assert_eq!(config.edges.len(), 2);
+//! assert!(config.validate().is_ok());
 //! ```
 
-use std::collections::HashMap;
+mod diagram;
+mod lifecycle;
+mod residency;
+mod secrets;
+
+pub use lifecycle::{ModelLifecycle, UnloadPolicy};
+pub use residency::ResidencyPolicy;
+pub use secrets::SecretResolver;
+
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::str::FromStr;
@@ -45,6 +64,7 @@ use serde::{Deserialize, Serialize};
 
 /// Errors that can occur when loading or parsing configurations.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum ConfigError {
     /// Failed to read a configuration file.
     #[error("Failed to read config file '{path}': {source}")]
@@ -75,6 +95,20 @@ pub enum ConfigError {
         pipeline_id: String,
         node_id: String,
     },
+
+    /// A `${VAR}` placeholder could not be filled in.
+    #[error("Cannot resolve ${{{name}}}: {message}")]
+    Secret {
+        name: String,
+        message: String,
+    },
+
+    /// Config written by a newer fissio than this one.
+    #[error("Config schema version {found} is newer than the supported version {supported}")]
+    UnsupportedSchema {
+        found: u32,
+        supported: u32,
+    },
 }
 
 impl ConfigError {
@@ -107,8 +141,13 @@ impl ConfigError {
 /// | `Evaluator` | Quality scoring |
 /// | `Synthesizer` | Synthesizes inputs |
 /// | `Coordinator` | Distributes to workers |
+/// | `Pipeline` | Runs another pipeline as a nested step |
+/// | `Map` | Runs downstream nodes once per list item |
+/// | `Retriever` | Searches a document collection for its input |
+/// | `Grounding` | Checks an answer against its retrieved sources |
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum NodeType {
     /// Simple LLM call with a system prompt.
     Llm,
@@ -128,6 +167,14 @@ pub enum NodeType {
     Synthesizer,
     /// Evaluates quality of outputs.
     Evaluator,
+    /// Runs another pipeline (referenced by `config.pipeline_id`) as a nested step.
+    Pipeline,
+    /// Splits a list input and runs downstream nodes once per item.
+    Map,
+    /// Searches a document collection (`config.collection`) for its input.
+    Retriever,
+    /// Checks its input answer against the output of `config.sources`.
+    Grounding,
 }
 
 impl FromStr for NodeType {
@@ -144,6 +191,10 @@ impl FromStr for NodeType {
             "worker" => Ok(Self::Worker),
             "synthesizer" => Ok(Self::Synthesizer),
             "evaluator" => Ok(Self::Evaluator),
+            "pipeline" => Ok(Self::Pipeline),
+            "map" => Ok(Self::Map),
+            "retriever" => Ok(Self::Retriever),
+            "grounding" => Ok(Self::Grounding),
             _ => Err(()),
         }
     }
@@ -161,6 +212,10 @@ impl std::fmt::Display for NodeType {
             Self::Worker => "worker",
             Self::Synthesizer => "synthesizer",
             Self::Evaluator => "evaluator",
+            Self::Pipeline => "pipeline",
+            Self::Map => "map",
+            Self::Retriever => "retriever",
+            Self::Grounding => "grounding",
         };
         write!(f, "{}", s)
     }
@@ -169,7 +224,7 @@ impl std::fmt::Display for NodeType {
 impl NodeType {
     /// Returns `true` if this node type makes an LLM call.
     pub fn requires_llm(&self) -> bool {
-        matches!(self, NodeType::Llm | NodeType::Worker)
+        matches!(self, NodeType::Llm | NodeType::Worker | NodeType::Grounding)
     }
 
     /// Returns `true` if this node type performs routing decisions.
@@ -190,6 +245,10 @@ impl NodeType {
             NodeType::Synthesizer => "Synthesizing",
             NodeType::Worker => "Worker executing",
             NodeType::Evaluator => "Evaluating",
+            NodeType::Pipeline => "Running sub-pipeline",
+            NodeType::Map => "Splitting items",
+            NodeType::Retriever => "Retrieving",
+            NodeType::Grounding => "Checking grounding",
         }
     }
 }
@@ -204,6 +263,7 @@ impl NodeType {
 /// | `Dynamic` | Orchestrator dynamically selects targets |
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum EdgeType {
     /// Sequential execution (default).
     #[default]
@@ -241,6 +301,34 @@ impl std::fmt::Display for EdgeType {
     }
 }
 
+/// What happens when a target of a parallel edge fails.
+///
+/// | Policy | Description |
+/// |--------|-------------|
+/// | `FailFast` | The run fails with the node's error (default) |
+/// | `SkipFailed` | The node is skipped; fan-in nodes merge the other branches |
+/// | `SubstituteDefault` | The edge's `default_output` stands in for the node's output |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FailurePolicy {
+    #[default]
+    FailFast,
+    SkipFailed,
+    SubstituteDefault,
+}
+
+impl std::fmt::Display for FailurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::FailFast => "fail_fast",
+            Self::SkipFailed => "skip_failed",
+            Self::SubstituteDefault => "substitute_default",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// Configuration for a single node in a pipeline.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeConfig {
@@ -252,6 +340,9 @@ pub struct NodeConfig {
     /// Optional model ID to use for this node (overrides default).
     #[serde(default)]
     pub model: Option<String>,
+    /// Model IDs to retry the node with, in order, when its model fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_models: Vec<String>,
     /// Additional configuration (node-type specific).
     #[serde(default)]
     pub config: serde_json::Value,
@@ -266,6 +357,18 @@ pub struct NodeConfig {
     pub observe: Option<fissio_monitor::ObserveConfig>,
 }
 
+impl NodeConfig {
+    /// Returns the referenced pipeline ID for `Pipeline` nodes (`config.pipeline_id`).
+    pub fn pipeline_ref(&self) -> Option<&str> {
+        self.config.get("pipeline_id").and_then(|v| v.as_str())
+    }
+
+    /// Returns the JSON Schema an Llm or Worker node's output must match (`config.output_schema`).
+    pub fn output_schema(&self) -> Option<&serde_json::Value> {
+        self.config.get("output_schema").filter(|v| v.is_object())
+    }
+}
+
 fn default_observe() -> Option<fissio_monitor::ObserveConfig> {
     Some(fissio_monitor::ObserveConfig::new())
 }
@@ -280,6 +383,16 @@ pub struct EdgeConfig {
     /// How this edge should be traversed.
     #[serde(default)]
     pub edge_type: EdgeType,
+    /// Maximum targets of a parallel edge running at once (overrides the pipeline limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    /// What a parallel edge does when one of its targets fails.
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    /// Output recorded for a failed target under [`FailurePolicy::SubstituteDefault`]
+    /// (empty if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_output: Option<String>,
 }
 
 /// An edge endpoint: either a single node ID or multiple node IDs.
@@ -373,6 +486,10 @@ impl From<EdgeEndpoint> for serde_json::Value {
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineConfig {
+    /// Version of the config format, [`SCHEMA_VERSION`] for configs written
+    /// by this release. Configs without one predate versioning and are version 1.
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     /// Unique identifier for this pipeline.
     pub id: String,
     /// Human-readable name.
@@ -384,6 +501,27 @@ pub struct PipelineConfig {
     pub nodes: Vec<NodeConfig>,
     /// The edges connecting nodes.
     pub edges: Vec<EdgeConfig>,
+    /// Maximum nodes a parallel edge runs at once (unlimited if unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    /// Providers and regions this pipeline's models may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residency: Option<ResidencyPolicy>,
+    /// Per-model warm-up and unload policies, keyed by model ID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_lifecycle: HashMap<String, ModelLifecycle>,
+}
+
+/// Current version of the [`PipelineConfig`] format.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades from each older schema version to the next: `MIGRATIONS[i]`
+/// takes a version `i + 1` config to version `i + 2`.
+const MIGRATIONS: &[fn(&mut serde_json::Value)] = &[];
+const _: () = assert!(MIGRATIONS.len() == SCHEMA_VERSION as usize - 1);
+
+fn default_schema_version() -> u32 {
+    1
 }
 
 impl PipelineConfig {
@@ -400,15 +538,73 @@ impl PipelineConfig {
         Self::from_json(&content)
     }
 
-    /// Parses a pipeline configuration from a JSON string.
+    /// Parses a pipeline configuration from a JSON string, upgrading older
+    /// schema versions.
     pub fn from_json(json: &str) -> Result<Self, ConfigError> {
-        Ok(serde_json::from_str(json)?)
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// Parses a pipeline configuration from JSON, upgrading older schema versions.
+    pub fn from_value(mut value: serde_json::Value) -> Result<Self, ConfigError> {
+        Self::migrate(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Upgrades a config (or anything embedding one, like an export bundle)
+    /// to [`SCHEMA_VERSION`] in place. Fails for configs from a newer release.
+    pub fn migrate(value: &mut serde_json::Value) -> Result<(), ConfigError> {
+        let found = match value.get("schema_version") {
+            Some(v) => v.as_u64().and_then(|v| u32::try_from(v).ok()).unwrap_or(0),
+            None => default_schema_version(),
+        };
+        if found > SCHEMA_VERSION {
+            return Err(ConfigError::UnsupportedSchema { found, supported: SCHEMA_VERSION });
+        }
+        let from = found.max(1) as usize;
+        for migration in &MIGRATIONS[from - 1..] {
+            migration(value);
+        }
+        if let Some(object) = value.as_object_mut() {
+            object.insert("schema_version".into(), SCHEMA_VERSION.into());
+        }
+        Ok(())
     }
 
     /// Serializes this configuration to a JSON string.
     pub fn to_json(&self) -> Result<String, ConfigError> {
         Ok(serde_json::to_string_pretty(self)?)
     }
+
+    /// Returns true if an edge leads to `output`, without which runs return nothing.
+    pub fn has_output_edge(&self) -> bool {
+        self.edges.iter().any(|e| matches!(&e.to, EdgeEndpoint::Single(s) if s == "output"))
+    }
+
+    /// Checks the graph for mistakes that would otherwise only show up when it
+    /// runs: duplicate node IDs, edges naming unknown nodes, and no edge to `output`.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id.as_str()) {
+                return Err(ConfigError::validation(&self.id, format!("duplicate node id '{}'", node.id)));
+            }
+        }
+
+        for edge in &self.edges {
+            let mut endpoints = edge.from.as_vec().into_iter().chain(edge.to.as_vec());
+            if let Some(unknown) = endpoints.find(|id| !matches!(*id, "input" | "output") && !ids.contains(id)) {
+                return Err(ConfigError::NodeNotFound {
+                    pipeline_id: self.id.clone(),
+                    node_id: unknown.to_string(),
+                });
+            }
+        }
+
+        if !self.has_output_edge() {
+            return Err(ConfigError::validation(&self.id, "no edge leads to 'output', so runs would return nothing"));
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -425,6 +621,9 @@ pub struct PipelineBuilder {
     description: String,
     nodes: Vec<NodeConfig>,
     edges: Vec<EdgeConfig>,
+    max_concurrency: Option<usize>,
+    residency: Option<ResidencyPolicy>,
+    model_lifecycle: HashMap<String, ModelLifecycle>,
 }
 
 impl PipelineBuilder {
@@ -436,6 +635,9 @@ impl PipelineBuilder {
             description: String::new(),
             nodes: Vec::new(),
             edges: Vec::new(),
+            max_concurrency: None,
+            residency: None,
+            model_lifecycle: HashMap::new(),
         }
     }
 
@@ -445,6 +647,24 @@ impl PipelineBuilder {
         self
     }
 
+    /// Limits how many nodes a parallel edge runs at once.
+    pub fn max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit);
+        self
+    }
+
+    /// Restricts the providers and regions the pipeline's models may use.
+    pub fn residency(mut self, policy: ResidencyPolicy) -> Self {
+        self.residency = Some(policy);
+        self
+    }
+
+    /// Sets when a model is loaded and unloaded around runs of the pipeline.
+    pub fn model_lifecycle(mut self, model_id: impl Into<String>, lifecycle: ModelLifecycle) -> Self {
+        self.model_lifecycle.insert(model_id.into(), lifecycle);
+        self
+    }
+
     /// Starts building a new node with the given ID and type.
     pub fn node(self, id: impl Into<String>, node_type: NodeType) -> NodeBuilder {
         NodeBuilder::new(self, id.into(), node_type)
@@ -456,6 +676,9 @@ impl PipelineBuilder {
             from: EdgeEndpoint::Single(from.into()),
             to: EdgeEndpoint::Single(to.into()),
             edge_type: EdgeType::Direct,
+            max_concurrency: None,
+            on_failure: FailurePolicy::FailFast,
+            default_output: None,
         });
         self
     }
@@ -471,6 +694,9 @@ impl PipelineBuilder {
             from: EdgeEndpoint::Single(from.into()),
             to: EdgeEndpoint::Single(to.into()),
             edge_type,
+            max_concurrency: None,
+            on_failure: FailurePolicy::FailFast,
+            default_output: None,
         });
         self
     }
@@ -481,16 +707,52 @@ impl PipelineBuilder {
             from: EdgeEndpoint::Single(from.into()),
             to: EdgeEndpoint::Multiple(to.iter().map(|s| s.to_string()).collect()),
             edge_type: EdgeType::Parallel,
+            max_concurrency: None,
+            on_failure: FailurePolicy::FailFast,
+            default_output: None,
         });
         self
     }
 
+    /// Adds a parallel edge that runs at most `limit` targets at once.
+    pub fn parallel_edge_limited(mut self, from: impl Into<String>, to: &[&str], limit: usize) -> Self {
+        self.edges.push(EdgeConfig {
+            from: EdgeEndpoint::Single(from.into()),
+            to: EdgeEndpoint::Multiple(to.iter().map(|s| s.to_string()).collect()),
+            edge_type: EdgeType::Parallel,
+            max_concurrency: Some(limit),
+            on_failure: FailurePolicy::FailFast,
+            default_output: None,
+        });
+        self
+    }
+
+    /// Sets what the last added edge does when one of its targets fails.
+    pub fn on_failure(mut self, policy: FailurePolicy) -> Self {
+        if let Some(edge) = self.edges.last_mut() {
+            edge.on_failure = policy;
+        }
+        self
+    }
+
+    /// Substitutes `output` for targets of the last added edge that fail.
+    pub fn substitute_on_failure(mut self, output: impl Into<String>) -> Self {
+        if let Some(edge) = self.edges.last_mut() {
+            edge.on_failure = FailurePolicy::SubstituteDefault;
+            edge.default_output = Some(output.into());
+        }
+        self
+    }
+
     /// Adds a conditional edge (for routers).
     pub fn conditional_edge(mut self, from: impl Into<String>, to: &[&str]) -> Self {
         self.edges.push(EdgeConfig {
             from: EdgeEndpoint::Single(from.into()),
             to: EdgeEndpoint::Multiple(to.iter().map(|s| s.to_string()).collect()),
             edge_type: EdgeType::Conditional,
+            max_concurrency: None,
+            on_failure: FailurePolicy::FailFast,
+            default_output: None,
         });
         self
     }
@@ -498,11 +760,15 @@ impl PipelineBuilder {
     /// Builds the final [`PipelineConfig`].
     pub fn build(self) -> PipelineConfig {
         PipelineConfig {
+            schema_version: SCHEMA_VERSION,
             id: self.id,
             name: self.name,
             description: self.description,
             nodes: self.nodes,
             edges: self.edges,
+            max_concurrency: self.max_concurrency,
+            residency: self.residency,
+            model_lifecycle: self.model_lifecycle,
         }
     }
 
@@ -522,6 +788,7 @@ pub struct NodeBuilder {
     id: String,
     node_type: NodeType,
     model: Option<String>,
+    fallback_models: Vec<String>,
     prompt: Option<String>,
     tools: Vec<String>,
     config: serde_json::Value,
@@ -535,6 +802,7 @@ impl NodeBuilder {
             id,
             node_type,
             model: None,
+            fallback_models: Vec::new(),
             prompt: None,
             tools: Vec::new(),
             config: serde_json::Value::Null,
@@ -548,6 +816,16 @@ impl NodeBuilder {
         self
     }
 
+    /// Sets the models to retry this node with, in order, when its model fails.
+    pub fn fallback_models<I, S>(mut self, models: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fallback_models = models.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Sets the system prompt for this node.
     pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
         self.prompt = Some(prompt.into());
@@ -570,6 +848,24 @@ impl NodeBuilder {
         self
     }
 
+    /// Sets the pipeline this node runs (for `NodeType::Pipeline` nodes).
+    pub fn pipeline_ref(mut self, pipeline_id: impl Into<String>) -> Self {
+        if !self.config.is_object() {
+            self.config = serde_json::json!({});
+        }
+        self.config["pipeline_id"] = serde_json::Value::String(pipeline_id.into());
+        self
+    }
+
+    /// Requires this node's output to be JSON matching `schema`.
+    pub fn output_schema(mut self, schema: serde_json::Value) -> Self {
+        if !self.config.is_object() {
+            self.config = serde_json::json!({});
+        }
+        self.config["output_schema"] = schema;
+        self
+    }
+
     /// Enables observability with default settings.
     pub fn observe(mut self) -> Self {
         self.observe = Some(fissio_monitor::ObserveConfig::new());
@@ -588,6 +884,7 @@ impl NodeBuilder {
             id: self.id,
             node_type: self.node_type,
             model: self.model,
+            fallback_models: self.fallback_models,
             prompt: self.prompt,
             tools: self.tools,
             config: self.config,
@@ -637,7 +934,7 @@ impl PresetRegistry {
             if path.extension().is_some_and(|ext| ext == "json") {
                 let content = fs::read_to_string(&path)
                     .map_err(|e| ConfigError::io(path.display().to_string(), e))?;
-                let config: PipelineConfig = serde_json::from_str(&content)?;
+                let config = PipelineConfig::from_json(&content)?;
                 registry.presets.insert(config.id.clone(), config);
             }
         }
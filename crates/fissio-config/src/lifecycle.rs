@@ -0,0 +1,46 @@
+//! When a pipeline's models are loaded before a run and unloaded after it.
+
+use serde::{Deserialize, Serialize};
+
+/// Load and unload policy for one model, keyed by model ID under a pipeline's
+/// `model_lifecycle`. Only local (Ollama) models are loaded and unloaded.
+///
+/// ```json
+/// { "model_lifecycle": { "ollama-llama3-1-8b": { "prewarm": true, "unload": "when_idle" } } }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelLifecycle {
+    /// Loads the model before the run starts, so the first node using it doesn't wait.
+    #[serde(default)]
+    pub prewarm: bool,
+    /// What happens to the model after the run.
+    #[serde(default)]
+    pub unload: UnloadPolicy,
+}
+
+impl ModelLifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn prewarm(mut self) -> Self {
+        self.prewarm = true;
+        self
+    }
+
+    pub fn unload_when_idle(mut self) -> Self {
+        self.unload = UnloadPolicy::WhenIdle;
+        self
+    }
+}
+
+/// Whether a model is unloaded after a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnloadPolicy {
+    /// Leave the model loaded.
+    #[default]
+    Keep,
+    /// Unload the model to free memory once no run is using it.
+    WhenIdle,
+}
@@ -0,0 +1,87 @@
+//! Restrictions on which providers and regions a pipeline's data may be sent to.
+
+use serde::{Deserialize, Serialize};
+
+/// Providers and hosting regions a pipeline's models may use.
+///
+/// Provider names are `openai`, `anthropic`, `azure`, `ollama`, or a custom
+/// provider's scheme. Regions are whatever the deployment labels its models
+/// with, such as `eu`, `us`, or `local`. Names compare case-insensitively.
+///
+/// ```json
+/// { "residency": { "allow_regions": ["eu", "local"], "deny_providers": ["openai"] } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResidencyPolicy {
+    /// When set, only models from these providers may be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_providers: Option<Vec<String>>,
+    /// Providers no model may come from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_providers: Vec<String>,
+    /// When set, only models hosted in these regions may be used; models
+    /// without a region are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_regions: Option<Vec<String>>,
+}
+
+impl ResidencyPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts models to the given providers.
+    pub fn allow_providers<I, S>(mut self, providers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_providers = Some(providers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Refuses models from a provider.
+    pub fn deny_provider(mut self, provider: impl Into<String>) -> Self {
+        self.deny_providers.push(provider.into());
+        self
+    }
+
+    /// Restricts models to the given hosting regions.
+    pub fn allow_regions<I, S>(mut self, regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_regions = Some(regions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns true if the policy restricts nothing.
+    pub fn is_empty(&self) -> bool {
+        self.allow_providers.is_none() && self.deny_providers.is_empty() && self.allow_regions.is_none()
+    }
+
+    /// Checks a model's provider and region, returning why it is refused.
+    pub fn check(&self, provider: &str, region: Option<&str>) -> Result<(), String> {
+        let listed = |names: &[String], name: &str| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+        if listed(&self.deny_providers, provider) {
+            return Err(format!("provider '{}' is denied", provider));
+        }
+        if let Some(allowed) = &self.allow_providers {
+            if !listed(allowed, provider) {
+                return Err(format!("provider '{}' is not in the allowed providers {:?}", provider, allowed));
+            }
+        }
+        if let Some(allowed) = &self.allow_regions {
+            match region {
+                Some(region) if listed(allowed, region) => {}
+                Some(region) => {
+                    return Err(format!("region '{}' is not in the allowed regions {:?}", region, allowed));
+                }
+                None => return Err(format!("its region is unknown and only {:?} are allowed", allowed)),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+//! `${VAR}` placeholders in pipeline configs, filled in from the environment.
+
+use std::collections::HashMap;
+
+use crate::{ConfigError, PipelineConfig};
+
+/// Fills `${VAR}` placeholders in a pipeline's prompts and node `config`
+/// values, such as HTTP tool headers or an `api_base`, so endpoints and keys
+/// stay out of committed pipeline JSON.
+///
+/// Only allowlisted variables are read, so a pipeline can't pull arbitrary
+/// secrets out of the environment it runs in. An entry ending in `*` allows
+/// every variable with that prefix. `$${` writes a literal `${`.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use fissio_config::{NodeType, PipelineConfig, SecretResolver};
+///
+/// let mut config = PipelineConfig::builder("support", "Support")
+///     .node("agent", NodeType::Llm)
+///         .prompt("You answer for ${COMPANY_NAME}.")
+///         .done()
+///     .edge("input", "agent")
+///     .edge("agent", "output")
+///     .build();
+///
+/// let resolver = SecretResolver::new(["COMPANY_*"])
+///     .with_vars(HashMap::from([("COMPANY_NAME".to_string(), "Acme".to_string())]));
+/// resolver.resolve(&mut config)?;
+/// assert_eq!(config.nodes[0].prompt.as_deref(), Some("You answer for Acme."));
+///
+/// assert!(resolver.resolve_str("${HOME}").is_err());
+/// # Ok::<(), fissio_config::ConfigError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SecretResolver {
+    allowed: Vec<String>,
+    vars: Option<HashMap<String, String>>,
+}
+
+impl SecretResolver {
+    /// Creates a resolver that reads the allowed variables from the environment.
+    pub fn new<I, S>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { allowed: allowed.into_iter().map(Into::into).collect(), vars: None }
+    }
+
+    /// Reads variables from `vars` instead of the environment.
+    pub fn with_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = Some(vars);
+        self
+    }
+
+    /// Returns true if placeholders may read the variable `name`.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        self.allowed.iter().any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => allowed == name,
+        })
+    }
+
+    /// Fills the placeholders in one string.
+    pub fn resolve_str(&self, text: &str) -> Result<String, ConfigError> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let Some(len) = rest[start..].find('}') else { break };
+            out.push_str(&rest[..start]);
+            out.push_str(&self.lookup(&rest[start + 2..start + len])?);
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Fills the placeholders in every node's prompt and `config`.
+    pub fn resolve(&self, config: &mut PipelineConfig) -> Result<(), ConfigError> {
+        for node in &mut config.nodes {
+            if let Some(prompt) = &node.prompt {
+                node.prompt = Some(self.resolve_str(prompt)?);
+            }
+            self.resolve_value(&mut node.config)?;
+        }
+        Ok(())
+    }
+
+    fn resolve_value(&self, value: &mut serde_json::Value) -> Result<(), ConfigError> {
+        match value {
+            serde_json::Value::String(s) => *s = self.resolve_str(s)?,
+            serde_json::Value::Array(items) => items.iter_mut().try_for_each(|v| self.resolve_value(v))?,
+            serde_json::Value::Object(map) => map.values_mut().try_for_each(|v| self.resolve_value(v))?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, ConfigError> {
+        let unresolved = |message: &str| ConfigError::Secret { name: name.to_string(), message: message.to_string() };
+        if !self.is_allowed(name) {
+            return Err(unresolved("not on the allowlist"));
+        }
+        let value = match &self.vars {
+            Some(vars) => vars.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        };
+        value.ok_or_else(|| unresolved("not set"))
+    }
+}
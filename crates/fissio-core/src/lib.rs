@@ -4,8 +4,13 @@
 //!
 //! - [`AgentError`] — Error type for pipeline and LLM operations
 //! - [`Message`] and [`MessageRole`] — Conversation message types
-//! - [`ModelConfig`] — LLM model configuration
+//! - [`ModelConfig`] and [`Provider`] — LLM model configuration
+//! - [`RateLimitConfig`] — Per-provider request and token limits
+//! - [`ModelPricing`] — Per-token prices for estimating a run's cost
+//! - [`AzureOpenAiConfig`] — Azure OpenAI deployment settings
 //! - [`ToolCall`], [`ToolResult`], [`ToolSchema`] — Tool interaction types
+//! - [`OutputSchema`] — JSON Schema for structured LLM output
+//! - [`Citation`] — A response's reference to a retrieved source
 //!
 //! # Example
 //!
@@ -22,6 +27,13 @@
 //!     name: "GPT-4".to_string(),
 //!     model: "gpt-4-turbo".to_string(),
 //!     api_base: None,
+//!     mirrors: Vec::new(),
+//!     provider: None,
+//!     rate_limit: None,
+//!     azure: None,
+//!     region: None,
+//!     context_window: None,
+//!     pricing: None,
 //! };
 //! ```
 
@@ -30,6 +42,7 @@ use thiserror::Error;
 
 /// Errors that can occur during pipeline execution or LLM operations.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum AgentError {
     /// LLM API request failed.
     #[error("LLM request failed: {0}")]
@@ -58,6 +71,54 @@ pub enum AgentError {
     /// WebSocket communication error.
     #[error("WebSocket error: {0}")]
     WebSocket(String),
+
+    /// A pipeline node failed; `source` is why.
+    #[error("Node '{node}' failed: {source}")]
+    NodeFailed {
+        node: String,
+        #[source]
+        source: Box<AgentError>,
+    },
+}
+
+impl AgentError {
+    /// Attributes an error to the node at path `node`, unless a nested node already claims it.
+    pub fn node_failed(node: impl Into<String>, error: AgentError) -> Self {
+        match error {
+            AgentError::NodeFailed { .. } => error,
+            source => AgentError::NodeFailed { node: node.into(), source: Box::new(source) },
+        }
+    }
+
+    /// Path of the pipeline node that failed, if the error came from one.
+    pub fn node(&self) -> Option<&str> {
+        match self {
+            AgentError::NodeFailed { node, .. } => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without the failing node.
+    pub fn root(&self) -> &AgentError {
+        match self {
+            AgentError::NodeFailed { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+
+    /// Stable snake_case name of the kind of error, for clients to branch on.
+    pub fn code(&self) -> &'static str {
+        match self.root() {
+            AgentError::LlmError(_) => "llm_error",
+            AgentError::ParseError(_) => "parse_error",
+            AgentError::WorkerFailed(_) => "worker_failed",
+            AgentError::ExternalApi(_) => "external_api",
+            AgentError::MaxRetriesExceeded => "max_retries_exceeded",
+            AgentError::UnknownWorker(_) => "unknown_worker",
+            AgentError::WebSocket(_) => "websocket",
+            AgentError::NodeFailed { .. } => "node_failed",
+        }
+    }
 }
 
 impl From<serde_json::Error> for AgentError {
@@ -120,6 +181,121 @@ pub struct ModelConfig {
     pub model: String,
     /// Optional API base URL for self-hosted or alternative endpoints.
     pub api_base: Option<String>,
+    /// Other API base URLs serving the same model, such as further Ollama hosts.
+    /// Engines spread concurrent calls across these and `api_base`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// Provider to route requests to. When unset, it is inferred from the model name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    /// Optional request and token limits shared by every call to this model's provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Azure OpenAI deployment to call instead of the OpenAI API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azure: Option<AzureOpenAiConfig>,
+    /// Where the model is hosted, e.g. `eu`, `us`, or `local`; checked by residency policies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Tokens the model accepts per request, prompt and response together.
+    /// When set, a `ContextManager` summarizes or drops older history to fit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    /// Prices for the model's tokens; runs using it report an estimated cost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// Model name prefixes served by Anthropic when no provider is set.
+const ANTHROPIC_PREFIXES: &[&str] = &["claude-"];
+
+impl ModelConfig {
+    /// Name of the provider requests for this model go to: `openai`, `anthropic`,
+    /// `azure`, `ollama`, or a custom provider's scheme.
+    ///
+    /// Follows the same rules as client construction: an Azure deployment, then
+    /// an explicit `provider`, then a `<scheme>://` prefix or the model name.
+    pub fn provider_name(&self) -> String {
+        if self.azure.is_some() {
+            return "azure".into();
+        }
+        match &self.provider {
+            Some(Provider::OpenAI) => "openai".into(),
+            Some(Provider::Anthropic) => "anthropic".into(),
+            Some(Provider::Ollama) => "ollama".into(),
+            Some(Provider::Custom(scheme)) => scheme.clone(),
+            None => match self.model.split_once("://") {
+                Some((scheme, _)) => scheme.to_string(),
+                None if ANTHROPIC_PREFIXES.iter().any(|p| self.model.starts_with(p)) => "anthropic".into(),
+                None => "openai".into(),
+            },
+        }
+    }
+}
+
+/// LLM backend a model is served by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// OpenAI API, or Azure OpenAI when the model has an `azure` section.
+    OpenAI,
+    /// Anthropic Messages API.
+    Anthropic,
+    /// Ollama's OpenAI-compatible endpoint at `api_base` (default `http://localhost:11434/v1`).
+    Ollama,
+    /// A provider registered under this scheme in the LLM provider registry.
+    Custom(String),
+}
+
+/// Azure OpenAI deployment settings.
+///
+/// Requests go to `{endpoint}/openai/deployments/{deployment}` with the
+/// `api-key` header; the key is read from `AZURE_OPENAI_API_KEY`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AzureOpenAiConfig {
+    /// Resource endpoint, e.g. `https://my-resource.openai.azure.com`.
+    pub endpoint: String,
+    /// Deployment name of the model in the Azure resource.
+    pub deployment: String,
+    /// REST API version, e.g. `2024-10-21`.
+    pub api_version: String,
+}
+
+/// Per-minute request and token limits for an LLM provider.
+///
+/// Unset fields are unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Maximum requests per minute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Maximum tokens (input + output) per minute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// Model pricing for cost estimation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Cost per 1K input tokens in USD.
+    pub input_per_1k: f64,
+    /// Cost per 1K output tokens in USD.
+    pub output_per_1k: f64,
+}
+
+impl ModelPricing {
+    pub fn new(input_per_1k: f64, output_per_1k: f64) -> Self {
+        Self {
+            input_per_1k,
+            output_per_1k,
+        }
+    }
+
+    /// Estimate cost for given token counts.
+    pub fn estimate(&self, input_tokens: u32, output_tokens: u32) -> f64 {
+        (input_tokens as f64 / 1000.0) * self.input_per_1k
+            + (output_tokens as f64 / 1000.0) * self.output_per_1k
+    }
 }
 
 // ============================================================================
@@ -163,3 +339,55 @@ pub struct ToolSchema {
     pub parameters: serde_json::Value,
 }
 
+/// A JSON Schema an LLM response must conform to.
+///
+/// OpenAI enforces it natively through its `json_schema` response format;
+/// Anthropic is made to answer by calling a tool whose input is the schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputSchema {
+    /// Name sent to the provider: letters, digits, `_` and `-`, at most 64 characters.
+    pub name: String,
+    /// JSON Schema object describing the response.
+    pub schema: serde_json::Value,
+    /// Asks OpenAI to guarantee the schema. Strict schemas must list every
+    /// property in `required` and set `additionalProperties: false`.
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+}
+
+impl OutputSchema {
+    /// Creates a strict schema.
+    pub fn new(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self { name: name.into(), schema, strict: true }
+    }
+
+    /// Sets whether OpenAI enforces the schema exactly.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+/// A `[n]` marker in a response that refers to a retrieved source.
+///
+/// Sent alongside streamed text so a frontend can render the marker as a
+/// footnote without matching the answer against source chunks itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// The number in the marker; sources are numbered from 1.
+    pub index: usize,
+    /// Character offset of the marker's `[` in the full response text.
+    pub offset: usize,
+    /// ID of the cited chunk.
+    pub source_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The cited chunk's text.
+    pub snippet: String,
+}
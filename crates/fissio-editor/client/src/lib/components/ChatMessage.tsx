@@ -1,33 +1,40 @@
-import { Show, createMemo } from 'solid-js';
+import { For, Show, createMemo } from 'solid-js';
 import { marked } from 'marked';
-import type { WsMetadata } from '../types';
+import type { Citation, WsMetadata } from '../types';
 
 type Props = {
   user: 'User' | 'Bot';
   msg: string;
   streaming?: boolean;
   metadata?: WsMetadata;
+  citations?: Citation[];
 };
 
 marked.setOptions({ breaks: true, gfm: true });
 
 const formatMetadata = (m: WsMetadata): string => {
   const secs = (m.elapsed_ms / 1000).toFixed(1);
+  const cost = m.estimated_cost_usd !== undefined ? ` · ~$${m.estimated_cost_usd.toFixed(4)}` : '';
 
   if (m.tokens_per_sec !== undefined) {
-    return `${secs}s · ${m.tokens_per_sec.toFixed(1)} tok/s · ${m.output_tokens} tokens`;
+    return `${secs}s · ${m.tokens_per_sec.toFixed(1)} tok/s · ${m.output_tokens} tokens${cost}`;
   }
 
   if (m.input_tokens > 0 || m.output_tokens > 0) {
-    return `${secs}s · ${m.input_tokens}/${m.output_tokens} tokens`;
+    return `${secs}s · ${m.input_tokens}/${m.output_tokens} tokens${cost}`;
   }
 
-  return `${secs}s`;
+  return `${secs}s${cost}`;
 };
 
 export default function ChatMessage(props: Props) {
   const showMetadata = () => props.user === 'Bot' && props.metadata && !props.streaming;
   const html = createMemo(() => marked.parse(props.msg) as string);
+  // One footnote per source, in marker order
+  const footnotes = createMemo(() => {
+    const seen = new Set<number>();
+    return (props.citations ?? []).filter((c) => !seen.has(c.index) && seen.add(c.index));
+  });
 
   return (
     <div
@@ -39,6 +46,19 @@ export default function ChatMessage(props: Props) {
       }}
     >
       <div innerHTML={html()} />
+      <Show when={footnotes().length > 0}>
+        <ol class="citations">
+          <For each={footnotes()}>
+            {(c) => (
+              <li value={c.index} title={c.snippet}>
+                <Show when={c.url} fallback={c.title ?? c.source_id}>
+                  <a href={c.url} target="_blank" rel="noreferrer">{c.title ?? c.url}</a>
+                </Show>
+              </li>
+            )}
+          </For>
+        </ol>
+      </Show>
       <Show when={showMetadata()}>
         <div class="metadata">{formatMetadata(props.metadata!)}</div>
       </Show>
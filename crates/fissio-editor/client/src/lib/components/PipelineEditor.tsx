@@ -36,7 +36,7 @@ type Props = {
   onSave?: (config: PipelineInfo) => void;
 };
 
-const NODE_TYPES = ['llm', 'worker', 'coordinator', 'aggregator', 'orchestrator', 'synthesizer', 'router', 'gate', 'evaluator'];
+const NODE_TYPES = ['llm', 'worker', 'coordinator', 'aggregator', 'orchestrator', 'synthesizer', 'router', 'gate', 'evaluator', 'pipeline', 'map', 'retriever', 'grounding'];
 const EDGE_TYPES = ['direct', 'conditional', 'dynamic', 'feedback'];
 
 const NODE_COLORS: Record<string, string> = {
@@ -49,6 +49,8 @@ const NODE_COLORS: Record<string, string> = {
   aggregator: '#ec4899',
   coordinator: '#06b6d4',
   evaluator: '#eab308',
+  pipeline: '#14b8a6',
+  map: '#a855f7',
   input: '#6b7280',
   output: '#6b7280'
 };
@@ -331,6 +333,16 @@ export default function PipelineEditor(props: Props) {
     props.onUpdate({ ...props.config, nodes });
   };
 
+  const updateNodeConfig = (nodeId: string, key: string, value: string | number | Record<string, unknown> | null) => {
+    const nodes = props.config.nodes.map(n => {
+      if (n.id !== nodeId) return n;
+      const config = { ...(n.config || {}) };
+      if (value === null) delete config[key]; else config[key] = value;
+      return { ...n, config: Object.keys(config).length > 0 ? config : undefined };
+    });
+    props.onUpdate({ ...props.config, nodes });
+  };
+
   const toggleNodeTool = (nodeId: string, toolName: string) => {
     const nodes = props.config.nodes.map(n => {
       if (n.id !== nodeId) return n;
@@ -547,8 +559,13 @@ export default function PipelineEditor(props: Props) {
                 <Show when={(props.availableTools?.length ?? 0) > 0} fallback={<p class="no-tools-msg">No tools available. Set TAVILY_API_KEY to enable web search.</p>}>
                   <For each={props.availableTools}>{(tool) => (
                     <div class="tool-item">
-                      <strong>{tool.name}</strong>
+                      <strong>{tool.metadata?.namespace ? `${tool.metadata.namespace}/${tool.name}` : tool.name}</strong>
                       <p class="tool-desc">{tool.description}</p>
+                      <Show when={tool.metadata}>{(meta) => (
+                        <span class="tool-meta">
+                          {[...(meta().tags ?? []), meta().cost !== 'free' ? `${meta().cost} cost` : null, meta().requires_approval ? 'requires approval' : null, meta().rate_limit_per_minute ? `${meta().rate_limit_per_minute}/min` : null].filter(Boolean).join(' · ')}
+                        </span>
+                      )}</Show>
                       <span class="tool-usage">Used by: {props.config.nodes.filter(n => n.tools?.includes(tool.name)).map(n => n.id).join(', ') || 'none'}</span>
                     </div>
                   )}</For>
@@ -599,6 +616,57 @@ export default function PipelineEditor(props: Props) {
                         <For each={props.models}>{(m) => <option value={m.id}>{m.name}</option>}</For>
                       </select>
                     </label>
+                    <Show when={node().node_type === 'pipeline'}>
+                      <label>
+                        <span>Pipeline ID</span>
+                        <input type="text" value={(node().config?.pipeline_id as string) || ''} onChange={(e) => updateNodeConfig(node().id, 'pipeline_id', e.currentTarget.value || null)} />
+                      </label>
+                    </Show>
+                    <Show when={node().node_type === 'map'}>
+                      <label>
+                        <span>Concurrency</span>
+                        <input type="number" min="1" value={(node().config?.concurrency as number) || ''} placeholder="4" onChange={(e) => updateNodeConfig(node().id, 'concurrency', e.currentTarget.value ? Number(e.currentTarget.value) : null)} />
+                      </label>
+                    </Show>
+                    <Show when={node().node_type === 'retriever'}>
+                      <label>
+                        <span>Collection</span>
+                        <input type="text" value={(node().config?.collection as string) || ''} onChange={(e) => updateNodeConfig(node().id, 'collection', e.currentTarget.value || null)} />
+                      </label>
+                      <label>
+                        <span>Top K</span>
+                        <input type="number" min="1" value={(node().config?.top_k as number) || ''} placeholder="5" onChange={(e) => updateNodeConfig(node().id, 'top_k', e.currentTarget.value ? Number(e.currentTarget.value) : null)} />
+                      </label>
+                      <label>
+                        <span>Search</span>
+                        <select value={(node().config?.search as string) || 'vector'} onChange={(e) => updateNodeConfig(node().id, 'search', e.currentTarget.value)}>
+                          <option value="vector">Vector</option>
+                          <option value="keyword">Keyword</option>
+                          <option value="hybrid">Hybrid</option>
+                        </select>
+                      </label>
+                      <label>
+                        <span>Query Rewrites</span>
+                        <input type="number" min="1" max="5" value={((node().config?.multi_query as { queries?: number } | undefined)?.queries) ?? ''} placeholder="Off" onChange={(e) => updateNodeConfig(node().id, 'multi_query', e.currentTarget.value ? { queries: Number(e.currentTarget.value) } : null)} />
+                      </label>
+                    </Show>
+                    <Show when={node().node_type === 'grounding'}>
+                      <label>
+                        <span>Sources Node</span>
+                        <input type="text" value={(node().config?.sources as string) || ''} onChange={(e) => updateNodeConfig(node().id, 'sources', e.currentTarget.value || null)} />
+                      </label>
+                      <label>
+                        <span>Threshold</span>
+                        <input type="number" min="0" max="1" step="0.05" value={(node().config?.threshold as number) ?? ''} placeholder="0.7" onChange={(e) => updateNodeConfig(node().id, 'threshold', e.currentTarget.value ? Number(e.currentTarget.value) : null)} />
+                      </label>
+                      <label>
+                        <span>On Unsupported</span>
+                        <select value={(node().config?.on_unsupported as string) || 'flag'} onChange={(e) => updateNodeConfig(node().id, 'on_unsupported', e.currentTarget.value)}>
+                          <option value="flag">Flag</option>
+                          <option value="block">Block</option>
+                        </select>
+                      </label>
+                    </Show>
                     <label>
                       <span>Prompt</span>
                       <textarea value={node().prompt || ''} onInput={(e) => updateNodeField(node().id, 'prompt', e.currentTarget.value || null)} rows="6" />
@@ -1,7 +1,24 @@
 import { createSignal, createEffect } from 'solid-js';
-import type { ChatMsg, HistoryMessage, ModelConfig, PipelineInfo, RuntimePipelineConfig, WsMetadata, ToolSchema } from '../types';
+import type { ChatMsg, Citation, HistoryMessage, ModelConfig, PipelineInfo, RuntimePipelineConfig, WsMetadata, ToolSchema } from '../types';
 
 const API_BASE = 'http://localhost:8000';
+const MAX_RESUME_ATTEMPTS = 5;
+const RESUME_DELAY_MS = 500;
+
+/** Progress of a streaming chat run, used to resume it after a dropped connection. */
+interface RunState {
+  runId: string | null;
+  lastEventId: string | null;
+  ended: boolean;
+}
+
+/** A tool call waiting for the user to approve or deny it. */
+export interface PendingApproval {
+  approval_id: string;
+  node_id: string;
+  tool: string;
+  arguments: unknown;
+}
 
 function createChatStore() {
   const [messages, setMessages] = createSignal<ChatMsg[]>([
@@ -10,6 +27,8 @@ function createChatStore() {
   const [isConnected, setIsConnected] = createSignal(false);
   const [isStreaming, setIsStreaming] = createSignal(false);
   const [isThinking, setIsThinking] = createSignal(false);
+  const [progress, setProgress] = createSignal<string | null>(null);
+  const [pendingApproval, setPendingApproval] = createSignal<PendingApproval | null>(null);
   const [models, setModels] = createSignal<ModelConfig[]>([]);
   const [selectedModel, setSelectedModel] = createSignal<string>('');
   const [templates, setTemplates] = createSignal<PipelineInfo[]>([]);
@@ -147,7 +166,11 @@ Always include edges from "input" to the first node(s) and from final node(s) to
         setPipelines(data.configs);
       }
 
-      fetchTools();
+      if (data.tools) {
+        setAvailableTools(data.tools);
+      } else {
+        fetchTools();
+      }
     } catch (e) {
       console.error('[init] Connection error:', e);
       setIsConnected(false);
@@ -156,20 +179,30 @@ Always include edges from "input" to the first node(s) and from final node(s) to
 
   function handleStreamChunk(chunk: string) {
     setIsThinking(false);
+    setProgress(null);
     const msgs = messages();
     const last = msgs[msgs.length - 1];
 
     if (last?.user === 'Bot' && last.streaming) {
-      setMessages([...msgs.slice(0, -1), { user: 'Bot', msg: last.msg + chunk, streaming: true }]);
+      setMessages([...msgs.slice(0, -1), { ...last, msg: last.msg + chunk }]);
       return;
     }
     setIsStreaming(true);
     setMessages([...msgs, { user: 'Bot', msg: chunk, streaming: true }]);
   }
 
+  function handleCitation(citation: Citation) {
+    const msgs = messages();
+    const last = msgs[msgs.length - 1];
+    if (last?.user !== 'Bot' || !last.streaming) return;
+    setMessages([...msgs.slice(0, -1), { ...last, citations: [...(last.citations ?? []), citation] }]);
+  }
+
   function handleStreamEnd(metadata?: WsMetadata) {
     setIsStreaming(false);
     setIsThinking(false);
+    setProgress(null);
+    setPendingApproval(null);
     const msgs = messages();
     const last = msgs[msgs.length - 1];
     if (!last?.streaming) return;
@@ -207,13 +240,18 @@ Always include edges from "input" to the first node(s) and from final node(s) to
         type: n.node_type,
         model: n.model,
         prompt: n.prompt,
-        tools: n.tools
+        tools: n.tools,
+        config: n.config
       })),
       edges: config.edges.map((e) => ({
         from: e.from,
         to: e.to,
-        edge_type: e.edge_type
-      }))
+        edge_type: e.edge_type,
+        max_concurrency: e.max_concurrency
+      })),
+      max_concurrency: config.max_concurrency,
+      residency: config.residency,
+      model_lifecycle: config.model_lifecycle
     };
   }
 
@@ -236,9 +274,11 @@ Always include edges from "input" to the first node(s) and from final node(s) to
     const config = pipelineConfig();
     const mode = composeMode();
 
+    // Lets the request be retried without running twice
     const payload: Record<string, unknown> = {
       message: text,
-      model_id: selectedModel()
+      model_id: selectedModel(),
+      idempotency_key: crypto.randomUUID()
     };
 
     // In compose mode, send history and custom system prompt
@@ -258,12 +298,22 @@ Always include edges from "input" to the first node(s) and from final node(s) to
     abortController?.abort();
     abortController = new AbortController();
 
+    const signal = abortController.signal;
+    const run: RunState = { runId: null, lastEventId: null, ended: false };
+
     try {
-      const res = await fetch(`${API_BASE}/chat`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(payload),
-        signal: abortController.signal
+      const post = () =>
+        fetch(`${API_BASE}/chat`, {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify(payload),
+          signal
+        });
+      const res = await post().catch(async (e) => {
+        if ((e as Error).name === 'AbortError') throw e;
+        console.warn('[chat] Request failed, retrying:', e);
+        await new Promise((resolve) => setTimeout(resolve, RESUME_DELAY_MS));
+        return post();
       });
 
       if (!res.ok || !res.body) {
@@ -273,33 +323,32 @@ Always include edges from "input" to the first node(s) and from final node(s) to
         return;
       }
 
-      const reader = res.body.getReader();
-      const decoder = new TextDecoder();
-      let buffer = '';
-
-      while (true) {
-        const { done, value } = await reader.read();
-        if (done) break;
-
-        buffer += decoder.decode(value, { stream: true });
-        const lines = buffer.split('\n');
-        buffer = lines.pop() || '';
-
-        for (const line of lines) {
-          if (line.startsWith('data: ')) {
-            try {
-              const data = JSON.parse(line.slice(6));
-              if (data.type === 'stream') {
-                handleStreamChunk(data.content);
-              } else if (data.type === 'end') {
-                handleStreamEnd(data.metadata);
-              }
-            } catch (e) {
-              console.warn('[sse] Failed to parse:', line, e);
-            }
-          }
+      await readEvents(res.body, run).catch((e) => {
+        if ((e as Error).name === 'AbortError') throw e;
+        console.warn('[sse] Connection lost:', e);
+      });
+
+      // The run continues server-side; pick up where the stream left off
+      for (let attempt = 1; !run.ended && run.runId && attempt <= MAX_RESUME_ATTEMPTS; attempt++) {
+        await new Promise((resolve) => setTimeout(resolve, RESUME_DELAY_MS * attempt));
+        console.log(`[sse] Resuming run ${run.runId} after event ${run.lastEventId} (attempt ${attempt})`);
+        const headers: Record<string, string> = {};
+        if (run.lastEventId !== null) headers['Last-Event-ID'] = run.lastEventId;
+        try {
+          const resumed = await fetch(`${API_BASE}/chat/runs/${run.runId}/events`, { headers, signal });
+          if (resumed.status === 404) break;
+          if (resumed.ok && resumed.body) await readEvents(resumed.body, run);
+        } catch (e) {
+          if ((e as Error).name === 'AbortError') throw e;
+          console.warn('[sse] Resume failed:', e);
         }
       }
+
+      if (!run.ended) {
+        setIsThinking(false);
+        handleStreamChunk('Error: Connection lost.');
+        handleStreamEnd();
+      }
     } catch (e) {
       if ((e as Error).name !== 'AbortError') {
         console.error('[chat] Request failed:', e);
@@ -310,6 +359,73 @@ Always include edges from "input" to the first node(s) and from final node(s) to
     }
   }
 
+  /** Reads SSE events from a chat stream, tracking the run ID and last event ID for resuming. */
+  async function readEvents(body: ReadableStream<Uint8Array>, run: RunState) {
+    const reader = body.getReader();
+    const decoder = new TextDecoder();
+    let buffer = '';
+
+    while (true) {
+      const { done, value } = await reader.read();
+      if (done) break;
+
+      buffer += decoder.decode(value, { stream: true });
+      const lines = buffer.split('\n');
+      buffer = lines.pop() || '';
+
+      for (const line of lines) {
+        if (line.startsWith('id: ')) {
+          run.lastEventId = line.slice(4);
+        } else if (line.startsWith('data: ')) {
+          try {
+            const data = JSON.parse(line.slice(6));
+            if (data.type === 'run') {
+              run.runId = data.run_id;
+            } else if (data.type === 'stream') {
+              handleStreamChunk(data.content);
+            } else if (data.type === 'progress') {
+              setProgress(data.message);
+            } else if (data.type === 'tool_call_started') {
+              setProgress(`Running ${data.name}…`);
+            } else if (data.type === 'tool_call_finished') {
+              setProgress(null);
+            } else if (data.type === 'approval') {
+              setPendingApproval(data);
+            } else if (data.type === 'citation') {
+              handleCitation(data);
+            } else if (data.type === 'warning') {
+              handleStreamChunk(`Warning: ${data.message}`);
+            } else if (data.type === 'error') {
+              const where = data.node_id ? ` (${data.node_id})` : '';
+              handleStreamChunk(`Error${where}: ${data.detail ?? data.message}`);
+            } else if (data.type === 'end') {
+              run.ended = true;
+              handleStreamEnd(data.metadata);
+            }
+          } catch (e) {
+            console.warn('[sse] Failed to parse:', line, e);
+          }
+        }
+      }
+    }
+  }
+
+  async function respondToApproval(approved: boolean) {
+    const pending = pendingApproval();
+    if (!pending) return;
+    setPendingApproval(null);
+    try {
+      const res = await fetch(`${API_BASE}/chat/approvals/${encodeURIComponent(pending.approval_id)}`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ approved })
+      });
+      if (!res.ok) console.warn('[approval] Response not accepted:', res.status);
+    } catch (e) {
+      console.error('[approval] Failed to respond:', e);
+    }
+  }
+
   function updateNode(nodeId: string, updates: Partial<{ prompt: string; model: string | null; node_type: string }>) {
     const config = pipelineConfig();
     if (!config) return;
@@ -364,7 +480,10 @@ Always include edges from "input" to the first node(s) and from final node(s) to
       description: config.description,
       nodes: config.nodes,
       edges: config.edges,
-      layout: config.layout
+      layout: config.layout,
+      max_concurrency: config.max_concurrency,
+      residency: config.residency,
+      model_lifecycle: config.model_lifecycle
     };
     console.log('[save] Sending save request:', config.id, config.name);
     console.log('[save] nodes with positions:', config.nodes.map(n => ({ id: n.id, x: n.x, y: n.y })));
@@ -434,7 +553,8 @@ Always include edges from "input" to the first node(s) and from final node(s) to
         method: 'DELETE'
       });
       if (res.ok) {
-        console.log('[model] Unloaded model:', modelId);
+        const { deferred } = await res.json();
+        console.log(deferred ? '[model] Unload deferred until runs finish:' : '[model] Unloaded model:', modelId);
       }
     } catch (e) {
       console.error('[model] Unload failed:', e);
@@ -490,6 +610,9 @@ What would you like to build?`
     isConnected,
     isStreaming,
     isThinking,
+    progress,
+    pendingApproval,
+    respondToApproval,
     models,
     selectedModel,
     setSelectedModel,
@@ -1,7 +1,17 @@
+export type ToolMetadata = {
+	namespace?: string;
+	tags?: string[];
+	cost: 'free' | 'low' | 'medium' | 'high';
+	requires_approval: boolean;
+	rate_limit_per_minute?: number;
+	side_effects: boolean;
+};
+
 export type ToolSchema = {
 	name: string;
 	description: string;
 	parameters: Record<string, unknown>;
+	metadata?: ToolMetadata;
 };
 
 export type WsMetadata = {
@@ -12,6 +22,7 @@ export type WsMetadata = {
 	prompt_eval_ms?: number;
 	eval_ms?: number;
 	tokens_per_sec?: number;
+	estimated_cost_usd?: number;
 };
 
 export type ModelConfig = {
@@ -27,6 +38,7 @@ export type NodeInfo = {
 	model: string | null;
 	prompt: string | null;
 	tools?: string[];
+	config?: Record<string, unknown>;
 	x?: number;
 	y?: number;
 };
@@ -35,6 +47,20 @@ export type EdgeInfo = {
 	from: string | string[];
 	to: string | string[];
 	edge_type?: string;
+	max_concurrency?: number;
+};
+
+/** Providers and hosting regions a pipeline's models may use. */
+export type ResidencyPolicy = {
+	allow_providers?: string[];
+	deny_providers?: string[];
+	allow_regions?: string[];
+};
+
+/** When a model is loaded before a run and unloaded after it. */
+export type ModelLifecycle = {
+	prewarm?: boolean;
+	unload?: 'keep' | 'when_idle';
 };
 
 export type PipelineInfo = {
@@ -44,6 +70,9 @@ export type PipelineInfo = {
 	nodes: NodeInfo[];
 	edges: EdgeInfo[];
 	layout?: Record<string, { x: number; y: number }>; // positions for input/output virtual nodes
+	max_concurrency?: number;
+	residency?: ResidencyPolicy;
+	model_lifecycle?: Record<string, ModelLifecycle>;
 };
 
 export type RuntimeNodeConfig = {
@@ -52,12 +81,14 @@ export type RuntimeNodeConfig = {
 	model?: string | null;
 	prompt?: string | null;
 	tools?: string[];
+	config?: Record<string, unknown>;
 };
 
 export type RuntimeEdgeConfig = {
 	from: string | string[];
 	to: string | string[];
 	edge_type?: string;
+	max_concurrency?: number;
 };
 
 export type RuntimePipelineConfig = {
@@ -65,6 +96,19 @@ export type RuntimePipelineConfig = {
 	name?: string;
 	nodes: RuntimeNodeConfig[];
 	edges: RuntimeEdgeConfig[];
+	max_concurrency?: number;
+	residency?: ResidencyPolicy;
+	model_lifecycle?: Record<string, ModelLifecycle>;
+};
+
+/** A `[n]` marker in a response and the retrieved source it refers to. */
+export type Citation = {
+	index: number;
+	offset: number;
+	source_id: string;
+	title?: string;
+	url?: string;
+	snippet: string;
 };
 
 export type ChatMsg = {
@@ -72,6 +116,7 @@ export type ChatMsg = {
 	msg: string;
 	streaming?: boolean;
 	metadata?: WsMetadata;
+	citations?: Citation[];
 };
 
 export type HistoryMessage = {
@@ -164,6 +164,7 @@ export default function Chat() {
                 msg={message.msg}
                 streaming={message.streaming}
                 metadata={message.metadata}
+                citations={message.citations}
               />
             )}
           </For>
@@ -174,10 +175,28 @@ export default function Chat() {
                 <span />
                 <span />
               </span>
+              <Show when={chat.progress()}>
+                <span class="thinking-status">{chat.progress()}</span>
+              </Show>
             </div>
           </Show>
         </div>
 
+        <Show when={chat.pendingApproval()}>
+          {(approval) => (
+            <div class="approval-prompt">
+              <div class="approval-text">
+                <strong>{approval().node_id}</strong> wants to run <code>{approval().tool}</code>
+                <pre>{JSON.stringify(approval().arguments, null, 2)}</pre>
+              </div>
+              <div class="compose-preview-actions">
+                <button class="btn-save" onClick={() => chat.respondToApproval(true)}>Approve</button>
+                <button class="btn-cancel" onClick={() => chat.respondToApproval(false)}>Deny</button>
+              </div>
+            </div>
+          )}
+        </Show>
+
         <Show when={chat.composeMode() === 'composing'}>
           <div class="compose-indicator">
             <span class="compose-badge">COMPOSE MODE</span>
@@ -15,6 +15,7 @@ interface TraceRecord {
   total_input_tokens: number;
   total_output_tokens: number;
   total_tool_calls: number;
+  total_cost_usd: number | null;
   status: string;
 }
 
@@ -31,6 +32,18 @@ interface SpanRecord {
   output_tokens: number;
   tool_call_count: number;
   iteration_count: number;
+  offset_ms: number;
+  duration_ms: number;
+  tool_calls: ToolCallRecord[];
+}
+
+interface ToolCallRecord {
+  call_id: string;
+  tool_name: string;
+  arguments: unknown;
+  result: string;
+  elapsed_ms: number;
+  start_time: number;
 }
 
 interface MetricsSummary {
@@ -39,6 +52,7 @@ interface MetricsSummary {
   total_output_tokens: number;
   total_tool_calls: number;
   avg_latency_ms: number;
+  total_cost_usd: number;
 }
 
 const fetchTraces = async (): Promise<TraceRecord[]> => {
@@ -67,6 +81,11 @@ const formatDuration = (ms: number): string => {
   return `${(ms / 1000).toFixed(2)}s`;
 };
 
+const formatCost = (usd: number | null): string => {
+  if (usd === null) return '—';
+  return usd < 0.01 && usd > 0 ? '<$0.01' : `$${usd.toFixed(2)}`;
+};
+
 const truncate = (str: string, len: number): string => {
   return str.length > len ? str.slice(0, len) + '...' : str;
 };
@@ -112,6 +131,13 @@ export default function Observe() {
       width: '80px',
       render: (row) => (row.total_input_tokens + row.total_output_tokens).toLocaleString()
     },
+    {
+      key: 'cost',
+      header: 'Est. Cost',
+      align: 'right',
+      width: '80px',
+      render: (row) => formatCost(row.total_cost_usd)
+    },
     {
       key: 'latency',
       header: 'Latency',
@@ -164,6 +190,10 @@ export default function Observe() {
                 <div class="metric-card-label">Avg Latency</div>
                 <div class="metric-card-value">{formatDuration(m().avg_latency_ms)}</div>
               </div>
+              <div class="metric-card">
+                <div class="metric-card-label">Est. Cost</div>
+                <div class="metric-card-value">{formatCost(m().total_cost_usd)}</div>
+              </div>
             </div>
           )}
         </Show>
@@ -222,16 +252,37 @@ export default function Observe() {
                               <span class="span-node-type">{span.node_type}</span>
                             </div>
                             <div class="span-metrics">
+                              <span>+{formatDuration(span.offset_ms)}</span>
                               <span>Tokens: {span.input_tokens + span.output_tokens}</span>
-                              <span>Time: {formatDuration(span.end_time - span.start_time)}</span>
+                              <span>Time: {formatDuration(span.duration_ms)}</span>
                               <span>Tools: {span.tool_call_count}</span>
                             </div>
+                            <Show when={detail().trace.total_elapsed_ms > 0}>
+                              <div class="span-bar-track">
+                                <div
+                                  class="span-bar"
+                                  style={{
+                                    left: `${(100 * span.offset_ms) / detail().trace.total_elapsed_ms}%`,
+                                    width: `${Math.max((100 * span.duration_ms) / detail().trace.total_elapsed_ms, 0.5)}%`,
+                                  }}
+                                />
+                              </div>
+                            </Show>
                             <Show when={span.input}>
                               <div class="span-io">In: {truncate(span.input, 80)}</div>
                             </Show>
                             <Show when={span.output}>
                               <div class="span-io">Out: {truncate(span.output, 80)}</div>
                             </Show>
+                            <For each={span.tool_calls}>
+                              {(call) => (
+                                <div class="span-tool-call">
+                                  <span class="span-tool-name">{call.tool_name}</span>
+                                  <span>{formatDuration(call.elapsed_ms)}</span>
+                                  <span class="span-io">{truncate(call.result, 80)}</span>
+                                </div>
+                              )}
+                            </For>
                           </div>
                         )}
                       </For>
@@ -35,6 +35,9 @@ async fn static_handler(axum::extract::Path(path): axum::extract::Path<String>)
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::X_FRAME_OPTIONS, "DENY")
+        .header(header::STRICT_TRANSPORT_SECURITY, "max-age=31536000; includeSubDomains")
         .body(Body::from(content.data.into_owned()))
         .unwrap()
 }
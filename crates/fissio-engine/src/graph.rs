@@ -0,0 +1,437 @@
+//! The pipeline graph, compiled once per engine for scheduling.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use fissio_config::{EdgeConfig, PipelineConfig};
+
+/// A pipeline's nodes and edges indexed for execution.
+///
+/// Built once when the engine is created, so runs look nodes and edges up by
+/// ID instead of scanning the config. Edges that lead back to a node already
+/// on the path from `input` (e.g. an evaluator feeding its generator) are
+/// back edges: they carry data but don't order execution, so every other
+/// edge forms a DAG.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionGraph {
+    /// Node IDs in config order.
+    order: Vec<String>,
+    /// Position of each node in the config's `nodes`.
+    index: HashMap<String, usize>,
+    /// Edge indices by source node, including `input`.
+    outgoing: HashMap<String, Vec<usize>>,
+    /// Edge indices by target node, including `output`.
+    incoming: HashMap<String, Vec<usize>>,
+    /// Upstream nodes (and `input`) a node waits for, over forward edges.
+    parents: HashMap<String, Vec<String>>,
+    /// Downstream nodes over forward edges, without `output`.
+    children: HashMap<String, Vec<String>>,
+    back_edges: HashSet<(String, String)>,
+    /// Parents a node needs before it may start, from its `config.quorum`.
+    quorum: HashMap<String, usize>,
+    /// Nodes reachable from `input`, batched so each node comes after all its parents.
+    levels: Vec<Vec<String>>,
+}
+
+impl ExecutionGraph {
+    /// Compiles the config's nodes and edges.
+    pub fn compile(config: &PipelineConfig) -> Self {
+        let mut graph = Self {
+            order: config.nodes.iter().map(|n| n.id.clone()).collect(),
+            ..Self::default()
+        };
+        for (i, node) in config.nodes.iter().enumerate() {
+            graph.index.entry(node.id.clone()).or_insert(i);
+            if let Some(quorum) = node.config.get("quorum").and_then(|v| v.as_u64()).filter(|&n| n > 0) {
+                graph.quorum.insert(node.id.clone(), quorum as usize);
+            }
+        }
+        for (i, edge) in config.edges.iter().enumerate() {
+            for from in edge.from.as_vec() {
+                graph.outgoing.entry(from.to_string()).or_default().push(i);
+            }
+            for to in edge.to.as_vec() {
+                graph.incoming.entry(to.to_string()).or_default().push(i);
+            }
+        }
+
+        let (back_edges, reachable) = graph.walk_from_input(&config.edges);
+        graph.back_edges = back_edges;
+        for edge in &config.edges {
+            // Unreachable nodes never run, so nothing waits for them
+            for from in edge.from.as_vec().into_iter().filter(|from| reachable.contains(*from)) {
+                for to in edge.to.as_vec() {
+                    if to == "output" || !graph.index.contains_key(to) || graph.is_back_edge(from, to) {
+                        continue;
+                    }
+                    push_unique(graph.parents.entry(to.to_string()).or_default(), from);
+                    push_unique(graph.children.entry(from.to_string()).or_default(), to);
+                }
+            }
+        }
+        graph.levels = graph.level_batches();
+        graph
+    }
+
+    /// Walks the graph depth-first from `input` in edge order, returning the
+    /// edges that close a cycle and the nodes reached (with `input`).
+    fn walk_from_input(&self, edges: &[EdgeConfig]) -> (HashSet<(String, String)>, HashSet<String>) {
+        let successors = |id: &str| -> Vec<String> {
+            self.outgoing
+                .get(id)
+                .into_iter()
+                .flatten()
+                .flat_map(|&i| edges[i].to.as_vec())
+                .filter(|to| self.index.contains_key(*to))
+                .map(String::from)
+                .collect()
+        };
+
+        let mut back_edges = HashSet::new();
+        let mut finished: HashSet<String> = HashSet::new();
+        let mut on_path: HashSet<String> = HashSet::from(["input".to_string()]);
+        let mut stack: Vec<(String, Vec<String>)> = vec![("input".to_string(), successors("input"))];
+        while let Some((id, pending)) = stack.last_mut() {
+            let Some(next) = (!pending.is_empty()).then(|| pending.remove(0)) else {
+                on_path.remove(id.as_str());
+                finished.insert(id.clone());
+                stack.pop();
+                continue;
+            };
+            if on_path.contains(&next) {
+                back_edges.insert((id.clone(), next));
+            } else if !finished.contains(&next) {
+                on_path.insert(next.clone());
+                let successors = successors(&next);
+                stack.push((next, successors));
+            }
+        }
+        (back_edges, finished)
+    }
+
+    /// Kahn's algorithm over forward edges, starting from `input`'s children.
+    fn level_batches(&self) -> Vec<Vec<String>> {
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+        for id in self.children("input") {
+            if self.parents(id).iter().all(|p| p == "input") {
+                queue.push_back((id.as_str(), 0));
+            }
+        }
+        for id in &self.order {
+            let waiting = self.parents(id).iter().filter(|p| *p != "input").count();
+            remaining.insert(id.as_str(), waiting);
+        }
+
+        let mut levels: Vec<Vec<String>> = Vec::new();
+        let mut placed = HashSet::new();
+        while let Some((id, level)) = queue.pop_front() {
+            if !placed.insert(id) {
+                continue;
+            }
+            if levels.len() <= level {
+                levels.resize(level + 1, Vec::new());
+            }
+            levels[level].push(id.to_string());
+            for child in self.children(id) {
+                let Some(waiting) = remaining.get_mut(child.as_str()) else { continue };
+                *waiting = waiting.saturating_sub(1);
+                if *waiting == 0 {
+                    queue.push_back((child.as_str(), level + 1));
+                }
+            }
+        }
+        levels
+    }
+
+    /// Node IDs in config order.
+    pub fn nodes(&self) -> &[String] {
+        &self.order
+    }
+
+    /// Position of a node in the config's `nodes`.
+    pub fn node_index(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    /// Indices into the config's `edges` of the edges leaving `id`.
+    pub fn outgoing(&self, id: &str) -> &[usize] {
+        self.outgoing.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Indices into the config's `edges` of the edges entering `id`.
+    pub fn incoming(&self, id: &str) -> &[usize] {
+        self.incoming.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes, and `input`, that `id` runs after.
+    pub fn parents(&self, id: &str) -> &[String] {
+        self.parents.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes that run after `id`.
+    pub fn children(&self, id: &str) -> &[String] {
+        self.children.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of nodes, and `input`, that `id` waits for.
+    pub fn indegree(&self, id: &str) -> usize {
+        self.parents(id).len()
+    }
+
+    /// Parents that must finish before `id` may start, when fewer than all.
+    pub fn quorum(&self, id: &str) -> Option<usize> {
+        self.quorum.get(id).copied().filter(|&q| q < self.indegree(id))
+    }
+
+    /// True when the edge from `from` to `to` closes a cycle.
+    pub fn is_back_edge(&self, from: &str, to: &str) -> bool {
+        self.back_edges.contains(&(from.to_string(), to.to_string()))
+    }
+
+    /// Nodes reachable from `input` in batches: every node comes after all
+    /// its parents, and the nodes of a batch don't depend on each other.
+    pub fn levels(&self) -> &[Vec<String>] {
+        &self.levels
+    }
+}
+
+fn push_unique(ids: &mut Vec<String>, id: &str) {
+    if !ids.iter().any(|existing| existing == id) {
+        ids.push(id.to_string());
+    }
+}
+
+/// Tracks which nodes of a run have finished, been skipped, or routed.
+///
+/// A node is ready once every parent has settled, or once its quorum of
+/// parents has finished. It runs if at least one parent finished and, for a
+/// router parent, chose it; otherwise it is skipped, and so are the nodes
+/// that only it leads to.
+pub(crate) struct Frontier<'g> {
+    graph: &'g ExecutionGraph,
+    done: HashSet<String>,
+    skipped: HashSet<String>,
+    routes: HashMap<String, Vec<String>>,
+}
+
+impl<'g> Frontier<'g> {
+    pub fn new(graph: &'g ExecutionGraph) -> Self {
+        Self { graph, done: HashSet::from(["input".to_string()]), skipped: HashSet::new(), routes: HashMap::new() }
+    }
+
+    /// Records that `id` finished; a router's `next_nodes` limit which children run.
+    pub fn complete(&mut self, id: &str, next_nodes: Vec<String>) {
+        self.done.insert(id.to_string());
+        if !next_nodes.is_empty() {
+            self.routes.insert(id.to_string(), next_nodes);
+        }
+    }
+
+    /// Records that `id` failed and the run goes on without it, as if no
+    /// parent had led to it.
+    pub fn skip(&mut self, id: &str) {
+        self.skipped.insert(id.to_string());
+    }
+
+    /// True when `parent` finished and, if it routed, chose `child`.
+    pub fn taken(&self, parent: &str, child: &str) -> bool {
+        self.done.contains(parent) && self.routes.get(parent).is_none_or(|chosen| chosen.iter().any(|c| c == child))
+    }
+
+    /// True when some parent of `id` leads to it.
+    pub fn reached(&self, id: &str) -> bool {
+        self.graph.parents(id).iter().any(|p| self.taken(p, id))
+    }
+
+    /// Nodes whose quorum of parents has finished, in config order, except
+    /// `held` and those in `started`. They may start while other parents
+    /// are still running.
+    pub fn quorum_ready(&self, held: Option<&str>, started: &HashSet<String>) -> Vec<String> {
+        self.graph
+            .nodes()
+            .iter()
+            .filter(|id| {
+                !self.done.contains(*id) && !self.skipped.contains(*id) && held != Some(id.as_str()) && !started.contains(*id)
+            })
+            .filter(|id| {
+                let taken = self.graph.parents(id).iter().filter(|p| self.taken(p, id)).count();
+                self.graph.quorum(id).is_some_and(|q| taken >= q)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the next nodes to run, in config order, skipping those no
+    /// parent leads to. Empty once the run has nothing left to do.
+    ///
+    /// `held` is never returned: the streaming terminal runs after the rest.
+    pub fn next_batch(&mut self, held: Option<&str>) -> Vec<String> {
+        loop {
+            let mut batch = Vec::new();
+            let mut skipped_any = false;
+            for id in self.graph.nodes() {
+                if self.done.contains(id) || self.skipped.contains(id) || held == Some(id.as_str()) {
+                    continue;
+                }
+                let parents = self.graph.parents(id);
+                let settled = parents.iter().all(|p| self.done.contains(p) || self.skipped.contains(p));
+                let taken = parents.iter().filter(|p| self.taken(p, id)).count();
+                let quorum_met = self.graph.quorum(id).is_some_and(|q| taken >= q);
+                if parents.is_empty() || !(settled || quorum_met) {
+                    continue;
+                }
+                if taken > 0 {
+                    batch.push(id.clone());
+                } else {
+                    self.skipped.insert(id.clone());
+                    skipped_any = true;
+                }
+            }
+            if !batch.is_empty() || !skipped_any {
+                return batch;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn compile(nodes: serde_json::Value, edges: serde_json::Value) -> ExecutionGraph {
+        let config: PipelineConfig = serde_json::from_value(json!({ "id": "test", "name": "Test", "nodes": nodes, "edges": edges })).unwrap();
+        ExecutionGraph::compile(&config)
+    }
+
+    #[test]
+    fn test_node_waits_for_every_parent() {
+        let graph = compile(
+            json!([{ "id": "a", "type": "llm" }, { "id": "b", "type": "llm" }, { "id": "c", "type": "aggregator" }]),
+            json!([
+                { "from": "input", "to": ["a", "b"], "edge_type": "parallel" },
+                { "from": ["a", "b"], "to": "c" },
+                { "from": "c", "to": "output" },
+            ]),
+        );
+        assert_eq!(graph.levels(), [vec!["a", "b"], vec!["c"]]);
+        assert_eq!(graph.indegree("c"), 2);
+
+        let mut frontier = Frontier::new(&graph);
+        assert_eq!(frontier.next_batch(None), ["a", "b"]);
+        frontier.complete("a", Vec::new());
+        assert!(!frontier.next_batch(None).contains(&"c".to_string()));
+        frontier.complete("b", Vec::new());
+        assert_eq!(frontier.next_batch(None), ["c"]);
+        assert!(frontier.next_batch(Some("c")).is_empty());
+    }
+
+    #[test]
+    fn test_quorum_readies_node_before_every_parent_finishes() {
+        let graph = compile(
+            json!([
+                { "id": "a", "type": "llm" },
+                { "id": "b", "type": "llm" },
+                { "id": "c", "type": "llm" },
+                { "id": "agg", "type": "aggregator", "config": { "quorum": 2 } },
+            ]),
+            json!([
+                { "from": "input", "to": ["a", "b", "c"], "edge_type": "parallel" },
+                { "from": ["a", "b", "c"], "to": "agg" },
+                { "from": "agg", "to": "output" },
+            ]),
+        );
+        assert_eq!(graph.quorum("agg"), Some(2));
+
+        let mut frontier = Frontier::new(&graph);
+        let started: HashSet<String> = frontier.next_batch(None).into_iter().collect();
+        frontier.complete("a", Vec::new());
+        assert!(frontier.quorum_ready(None, &started).is_empty());
+        frontier.complete("b", Vec::new());
+        assert_eq!(frontier.quorum_ready(None, &started), ["agg"]);
+        assert!(frontier.quorum_ready(Some("agg"), &started).is_empty());
+    }
+
+    #[test]
+    fn test_router_skips_branches_it_did_not_choose() {
+        let graph = compile(
+            json!([{ "id": "router", "type": "router" }, { "id": "x", "type": "llm" }, { "id": "y", "type": "llm" }, { "id": "after_x", "type": "llm" }]),
+            json!([
+                { "from": "input", "to": "router" },
+                { "from": "router", "to": ["x", "y"], "edge_type": "conditional" },
+                { "from": "x", "to": "after_x" },
+                { "from": ["y", "after_x"], "to": "output" },
+            ]),
+        );
+
+        let mut frontier = Frontier::new(&graph);
+        assert_eq!(frontier.next_batch(None), ["router"]);
+        frontier.complete("router", vec!["y".to_string()]);
+        assert!(frontier.taken("router", "y"));
+        assert!(!frontier.taken("router", "x"));
+        // `x` is skipped, and so is `after_x`, which only `x` leads to
+        assert_eq!(frontier.next_batch(None), ["y"]);
+        frontier.complete("y", Vec::new());
+        assert!(frontier.next_batch(None).is_empty());
+        assert!(!frontier.reached("after_x"));
+    }
+
+    #[test]
+    fn test_failed_node_skips_nodes_only_it_leads_to() {
+        let graph = compile(
+            json!([{ "id": "a", "type": "llm" }, { "id": "b", "type": "llm" }, { "id": "after_a", "type": "llm" }, { "id": "join", "type": "aggregator" }]),
+            json!([
+                { "from": "input", "to": ["a", "b"], "edge_type": "parallel" },
+                { "from": "a", "to": "after_a" },
+                { "from": ["a", "b"], "to": "join" },
+                { "from": ["after_a", "join"], "to": "output" },
+            ]),
+        );
+
+        let mut frontier = Frontier::new(&graph);
+        assert_eq!(frontier.next_batch(None), ["a", "b"]);
+        frontier.skip("a");
+        frontier.complete("b", Vec::new());
+        // `join` still runs on `b`; `after_a` has no parent left
+        assert_eq!(frontier.next_batch(None), ["join"]);
+        assert!(!frontier.reached("after_a"));
+    }
+
+    #[test]
+    fn test_back_edge_does_not_order_execution() {
+        let graph = compile(
+            json!([{ "id": "generator", "type": "llm" }, { "id": "evaluator", "type": "evaluator" }]),
+            json!([
+                { "from": "input", "to": "generator" },
+                { "from": "generator", "to": "evaluator" },
+                { "from": "evaluator", "to": "generator" },
+                { "from": "evaluator", "to": "output" },
+            ]),
+        );
+        assert!(graph.is_back_edge("evaluator", "generator"));
+        assert!(!graph.is_back_edge("generator", "evaluator"));
+        assert_eq!(graph.parents("generator"), ["input"]);
+        assert_eq!(graph.levels(), [vec!["generator"], vec!["evaluator"]]);
+
+        let mut frontier = Frontier::new(&graph);
+        assert_eq!(frontier.next_batch(None), ["generator"]);
+        frontier.complete("generator", Vec::new());
+        assert_eq!(frontier.next_batch(None), ["evaluator"]);
+        frontier.complete("evaluator", Vec::new());
+        assert!(frontier.next_batch(None).is_empty());
+    }
+
+    #[test]
+    fn test_unreachable_node_is_never_batched() {
+        let graph = compile(
+            json!([{ "id": "a", "type": "llm" }, { "id": "orphan", "type": "llm" }]),
+            json!([
+                { "from": "input", "to": "a" },
+                { "from": "orphan", "to": "a" },
+                { "from": "a", "to": "output" },
+            ]),
+        );
+        assert_eq!(graph.parents("a"), ["input"]);
+        assert_eq!(graph.levels(), [vec!["a"]]);
+    }
+}
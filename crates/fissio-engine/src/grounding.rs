@@ -0,0 +1,111 @@
+//! Grounding checks: whether an answer's claims are supported by its sources.
+
+use serde::Deserialize;
+
+const DEFAULT_THRESHOLD: f64 = 0.7;
+
+const DEFAULT_INSTRUCTIONS: &str = "You verify answers against their sources. Split the answer into its \
+    factual claims and decide for each whether the sources state or directly imply it. Claims that go \
+    beyond the sources are unsupported, even if they are true.";
+
+const BLOCKED_MESSAGE: &str = "I couldn't verify this answer against the available sources, so I'm not able to give it.";
+
+/// What a Grounding node does with an answer scoring below its threshold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum GroundingAction {
+    /// Pass the answer through with a note listing the unsupported claims.
+    #[default]
+    Flag,
+    /// Replace the answer with `blocked_message`.
+    Block,
+}
+
+/// A Grounding node's `config`.
+///
+/// ```json
+/// { "sources": "docs", "threshold": 0.8, "on_unsupported": "block" }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct GroundingConfig {
+    /// ID of the node whose output holds the sources, usually a Retriever.
+    pub sources: String,
+    /// Minimum share of supported claims for the answer to pass.
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    #[serde(default)]
+    pub on_unsupported: GroundingAction,
+    /// Output when a blocked answer is replaced.
+    #[serde(default)]
+    pub blocked_message: Option<String>,
+}
+
+fn default_threshold() -> f64 {
+    DEFAULT_THRESHOLD
+}
+
+#[derive(Debug, Deserialize)]
+struct Claim {
+    claim: String,
+    supported: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Verdict {
+    #[serde(default)]
+    claims: Vec<Claim>,
+}
+
+/// The result of checking an answer.
+#[derive(Debug, Clone)]
+pub(crate) struct Grounding {
+    /// Share of claims supported by the sources, from 0.0 to 1.0.
+    pub score: f64,
+    pub unsupported: Vec<String>,
+    /// False if the checker's response could not be read; the score is then 0.
+    pub parsed: bool,
+}
+
+/// Builds the checker's system prompt, with the node's prompt replacing the default instructions.
+pub(crate) fn system_prompt(prompt: Option<&str>) -> String {
+    format!(
+        "{}\n\nIMPORTANT: Respond with ONLY a JSON object, no explanation:\n\
+        {{\"claims\": [{{\"claim\": \"<claim from the answer>\", \"supported\": true}}]}}",
+        prompt.unwrap_or(DEFAULT_INSTRUCTIONS)
+    )
+}
+
+pub(crate) fn user_message(sources: &str, answer: &str) -> String {
+    format!("Sources:\n{}\n\nAnswer:\n{}", sources, answer)
+}
+
+/// Reads the checker's response. An answer with no factual claims scores 1.0.
+pub(crate) fn parse_verdict(response: &str) -> Grounding {
+    let Ok(verdict) = serde_json::from_str::<Verdict>(crate::unfence(response)) else {
+        return Grounding { score: 0.0, unsupported: vec![], parsed: false };
+    };
+    let total = verdict.claims.len();
+    let unsupported: Vec<String> = verdict.claims.into_iter().filter(|c| !c.supported).map(|c| c.claim).collect();
+    let score = match total {
+        0 => 1.0,
+        n => (n - unsupported.len()) as f64 / n as f64,
+    };
+    Grounding { score, unsupported, parsed: true }
+}
+
+/// Returns the node's output: the answer if it passes, else the answer flagged or blocked.
+pub(crate) fn apply(config: &GroundingConfig, answer: &str, grounding: &Grounding) -> String {
+    if grounding.parsed && grounding.score >= config.threshold {
+        return answer.to_string();
+    }
+    match config.on_unsupported {
+        GroundingAction::Block => config.blocked_message.clone().unwrap_or_else(|| BLOCKED_MESSAGE.to_string()),
+        GroundingAction::Flag if grounding.unsupported.is_empty() => {
+            format!("{}\n\nNote: this answer could not be verified against the sources.", answer)
+        }
+        GroundingAction::Flag => {
+            let claims: Vec<String> = grounding.unsupported.iter().map(|c| format!("- {}", c)).collect();
+            format!("{}\n\nNote: these statements are not supported by the sources:\n{}", answer, claims.join("\n"))
+        }
+    }
+}
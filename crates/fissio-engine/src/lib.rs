@@ -4,8 +4,12 @@
 //!
 //! - [`PipelineEngine`] — Executes pipeline configurations
 //! - [`ModelResolver`] — Resolves model IDs to configurations
-//! - [`EngineOutput`] — Stream or complete response from execution
+//! - [`EngineOutput`] — Stream or complete response from execution, or why there is none
 //! - [`NodeInput`] / [`NodeOutput`] — Data flowing through nodes
+//! - [`VramScheduler`] — Keeps parallel nodes within a GPU memory budget for Ollama models
+//! - [`ExecutionGraph`] — The pipeline's nodes and edges indexed and batched for scheduling
+//! - [`ExecutionPlan`] — What a run would execute, from [`PipelineEngine::plan`], without calling any LLM
+//! - [`PipelineTool`] — A pipeline exposed as a tool, so a Worker can call a whole sub-agent
 //!
 //! # Quick Start
 //!
@@ -27,38 +31,116 @@
 //! match result {
 //!     EngineOutput::Stream(stream) => { /* consume stream */ }
 //!     EngineOutput::Complete(text) => println!("{}", text),
+//!     EngineOutput::Empty(reason) => eprintln!("{}", reason.message()),
 //! }
 //! ```
 //!
 //! # Execution Model
 //!
-//! The engine traverses the pipeline DAG starting from `input` edges:
+//! The engine compiles the pipeline into an [`ExecutionGraph`] once, then runs
+//! it in batches from `input`: a node runs once every node feeding it has
+//! finished or been routed around, so a node with several parents waits for
+//! all of them. Edges back to an earlier node don't order execution.
 //!
 //! 1. **Sequential** (Direct edges) — Nodes execute one after another
-//! 2. **Parallel** (Parallel edges) — Nodes execute concurrently via `tokio::join_all`
+//! 2. **Parallel** (Parallel edges) — Nodes execute concurrently, throttled by the
+//!    edge's or pipeline's `max_concurrency` when set; a node with `config.quorum`
+//!    starts as soon as that many of its parents have finished
 //! 3. **Conditional** (Router nodes) — LLM classifies input to choose path
+//! 4. **Nested** (Pipeline nodes) — Runs a registered pipeline as a single step;
+//!    nested nodes are traced under prefixed paths such as `research/summarizer`
+//! 5. **Map** (Map nodes) — Splits a list and runs each downstream node once per
+//!    item (bounded by `config.concurrency`), collecting outputs into a JSON array
+//! 6. **Retrieval** (Retriever nodes) — Searches a collection through the engine's
+//!    [`Retriever`] and outputs numbered chunks as context for the next node
+//! 7. **Grounding** (Grounding nodes) — Checks their input answer against the output
+//!    of `config.sources`, flagging or blocking unsupported claims and recording a
+//!    groundedness score on the node's metrics and span
 //!
 //! # Agentic Tool Loops
 //!
 //! Worker nodes with tools configured run an agentic loop:
 //! 1. Send message + tool schemas to LLM
-//! 2. If LLM returns tool calls, execute them
-//! 3. Send results back to LLM
+//! 2. If LLM returns tool calls, execute them (concurrently, up to 4 at a time)
+//! 3. Send results back to LLM; a call whose arguments don't match the tool's
+//!    parameter schema isn't executed, and its result lists the violations instead
 //! 4. Repeat until LLM returns final content (max 10 iterations)
+//!
+//! Tools whose metadata declares `side_effects` run at most once per run key
+//! when the engine has a [`SideEffectLedger`]: executing the run again replays
+//! their recorded results instead of, say, sending the same email twice.
+//!
+//! A [`PipelineTool`] in the registry makes a whole pipeline one of those
+//! tools: an orchestrating Worker calls `research_agent` with an `input`, the
+//! pipeline runs on its own engine, and its output is the tool's result.
+//!
+//! When a Worker is the only node feeding `output`, its loop runs last and is
+//! returned as [`EngineOutput::Stream`]: `ToolCallStarted` and `ToolCallFinished`
+//! chunks around each tool call, then the final content.
+//!
+//! # Structured Output
+//!
+//! An Llm or Worker node with `config.output_schema` outputs JSON matching that
+//! schema, or fails. Without tools the provider enforces the schema (see
+//! [`UnifiedLlmClient::chat_structured`]); with tools the schema is added to the
+//! prompt and the loop's final answer is checked against it. Set
+//! `config.strict_schema: false` for schemas OpenAI's strict mode rejects.
+//!
+//! # Prompt Variables
+//!
+//! `{{now}}` in a node's prompt becomes the current date and time, in the
+//! node's `config.timezone`, else the engine's (see [`PipelineEngine::with_timezone`]),
+//! so models don't answer date-sensitive questions from their training cutoff.
+//!
+//! When the node feeding `output` takes a Retriever node's output as input, each
+//! `[n]` marker in its answer is followed by a `Citation` chunk for that source.
+//! A complete answer that cites sources is returned as a stream for the same reason.
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use fissio_config::{EdgeConfig, EdgeEndpoint, EdgeType, NodeConfig, NodeType, PipelineConfig};
-use fissio_core::{AgentError, ModelConfig};
-use fissio_llm::{ChatResponse, LlmMetrics, LlmStream, ToolCall, ToolSchema, UnifiedLlmClient};
-use fissio_tools::ToolRegistry;
-use async_recursion::async_recursion;
-use futures::future::join_all;
-use fissio_monitor::{MetricsCollector, NodeMetrics};
-use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use fissio_config::{EdgeConfig, EdgeEndpoint, EdgeType, FailurePolicy, NodeConfig, NodeType, PipelineConfig, ResidencyPolicy};
+use fissio_core::{AgentError, ModelConfig, ModelPricing, OutputSchema};
+use fissio_llm::{
+    parse_structured, with_schema_instructions, Cassette, ChatResponse, HostBalancer, HostLease, LlmCache, LlmMetrics, LlmStream,
+    StreamChunk, ToolCall, ToolSchema, UnifiedLlmClient,
+};
+use fissio_tools::{HostAllowlist, HttpTool, HttpToolSpec, Timezone, ToolExecutionPolicy, ToolRegistry};
+use futures::future::{join_all, BoxFuture};
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use fissio_monitor::{CaptureMode, MetricsCollector, NodeMetrics, ToolCallRecord};
+use fissio_rag::{
+    find_citations, format_context, CitationScanner, Memory, MemoryReadTool, MemoryWriteTool, RetrievalConfig, Retriever,
+    SearchResult,
+};
+use graph::Frontier;
+use grounding::GroundingConfig;
+use logging::{banner, warn_line, NodeLog};
+use post_process::PostProcessSteps;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, RwLock, Semaphore};
+use tracing::field::{display, Empty};
+use tracing::{debug, info, info_span, warn, Instrument, Span};
+
+mod graph;
+mod grounding;
+mod logging;
+mod pipeline_tool;
+mod plan;
+mod post_process;
+mod side_effects;
+mod tool_policy;
+mod validation;
+mod vram;
+
+pub use graph::ExecutionGraph;
+pub use pipeline_tool::PipelineTool;
+pub use plan::{ExecutionPlan, PlanStage, PlannedNode, PlannedTool, StageKind, ToolStatus};
+pub use side_effects::{Claim, InMemoryLedger, SideEffectLedger, SqliteLedger};
+pub use tool_policy::{ApprovalRequest, ApprovalResponse, ToolApprover, ToolDecision, ToolPolicy};
+pub use vram::{VramPermit, VramScheduler};
 
 /// Input data passed to a node during execution.
 ///
@@ -84,6 +166,9 @@ pub struct NodeOutput {
     pub content: String,
     /// Target nodes for routing (only set by Router nodes).
     pub next_nodes: Vec<String>,
+    /// Usage summed over every LLM call the node made, including each
+    /// iteration of its tool loop.
+    pub metrics: ExecutionMetrics,
 }
 
 /// Accumulated metrics from node execution.
@@ -100,40 +185,203 @@ pub struct ExecutionMetrics {
     pub tool_call_count: u32,
     /// Number of agentic loop iterations.
     pub iteration_count: u32,
+    /// Groundedness score, set by Grounding nodes.
+    pub groundedness: Option<f64>,
+    /// Provider request ids of the LLM calls, in order.
+    pub request_ids: Vec<String>,
+    /// Tool calls with their arguments, results, and timing, in call order.
+    pub tool_calls: Vec<ToolCallRecord>,
 }
 
 impl ExecutionMetrics {
     fn accumulate(&mut self, metrics: &LlmMetrics) {
         self.input_tokens += metrics.input_tokens;
         self.output_tokens += metrics.output_tokens;
+        self.request_ids.extend(metrics.request_id.clone());
+    }
+}
+
+/// Records a node's metrics and span to the engine's collector.
+struct NodeRecorder {
+    collector: Arc<dyn MetricsCollector>,
+    node_path: String,
+    node_type: String,
+    model: Option<String>,
+    pricing: Option<ModelPricing>,
+    capture: CaptureMode,
+}
+
+impl NodeRecorder {
+    fn record(&self, input: &str, output: &str, exec_metrics: &ExecutionMetrics, start_time_ms: i64, end_time_ms: i64) {
+        let node_metrics = NodeMetrics {
+            node_id: self.node_path.clone(),
+            model: self.model.clone(),
+            input_tokens: exec_metrics.input_tokens,
+            output_tokens: exec_metrics.output_tokens,
+            elapsed_ms: (end_time_ms - start_time_ms).max(0) as u64,
+            tool_call_count: exec_metrics.tool_call_count,
+            iteration_count: exec_metrics.iteration_count,
+            estimated_cost_usd: self.pricing.map(|p| p.estimate(exec_metrics.input_tokens, exec_metrics.output_tokens)),
+            groundedness: exec_metrics.groundedness,
+            provider_request_ids: exec_metrics.request_ids.clone(),
+            tool_calls: exec_metrics
+                .tool_calls
+                .iter()
+                .map(|call| ToolCallRecord {
+                    arguments: match self.capture {
+                        CaptureMode::Full => call.arguments.clone(),
+                        _ => self.capture.apply(&call.arguments.to_string()).into(),
+                    },
+                    result: self.capture.apply(&call.result),
+                    ..call.clone()
+                })
+                .collect(),
+            error: None,
+        };
+        self.collector.record(node_metrics.clone());
+        self.collector.record_span(
+            &self.node_path,
+            &self.node_type,
+            start_time_ms,
+            end_time_ms,
+            &self.capture.apply(input),
+            &self.capture.apply(output),
+            &node_metrics,
+        );
+    }
+
+    /// Records a node that failed, with the error and no output.
+    fn record_failure(&self, input: &str, error: &AgentError, start_time_ms: i64, end_time_ms: i64) {
+        let node_metrics = NodeMetrics {
+            model: self.model.clone(),
+            elapsed_ms: (end_time_ms - start_time_ms).max(0) as u64,
+            error: Some(error.to_string()),
+            ..NodeMetrics::new(self.node_path.clone())
+        };
+        self.collector.record(node_metrics.clone());
+        self.collector.record_span(
+            &self.node_path,
+            &self.node_type,
+            start_time_ms,
+            end_time_ms,
+            &self.capture.apply(input),
+            "",
+            &node_metrics,
+        );
+    }
+}
+
+/// Span for a node's execution; the `llm` and `tool` spans of its calls nest under it.
+fn node_span(node_path: &str, node: &NodeConfig, model: &ModelConfig) -> Span {
+    info_span!(
+        "node",
+        otel.name = %format!("node {}", node_path),
+        node.id = %node_path,
+        node.type = %node.node_type,
+        gen_ai.request.model = %model.model,
+        gen_ai.usage.input_tokens = Empty,
+        gen_ai.usage.output_tokens = Empty,
+        tool_calls = Empty,
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+    )
+}
+
+/// Adds a node's usage, or why it failed, to its span.
+fn record_node_span(span: &Span, result: Result<&ExecutionMetrics, &AgentError>) {
+    match result {
+        Ok(metrics) => {
+            span.record("gen_ai.usage.input_tokens", metrics.input_tokens);
+            span.record("gen_ai.usage.output_tokens", metrics.output_tokens);
+            span.record("tool_calls", metrics.tool_call_count);
+        }
+        Err(e) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("otel.status_message", display(e));
+        }
     }
 }
 
 /// Result of pipeline execution.
 ///
-/// Depending on pipeline structure, execution may return a stream
-/// for real-time output or a complete response string.
+/// Pipelines whose only terminal node is a Worker return a stream so tool
+/// activity is visible as it happens; others return the complete response.
+#[non_exhaustive]
 pub enum EngineOutput {
     /// Streaming response for real-time output.
     Stream(LlmStream),
     /// Complete response after pipeline finishes.
     Complete(String),
+    /// The pipeline finished without producing a response.
+    Empty(EmptyReason),
+}
+
+/// Why a pipeline finished without producing a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum EmptyReason {
+    /// No edge leads to `output`; the pipeline is not run.
+    NoOutputEdge,
+    /// No node feeding `output` ran, e.g. a router chose a branch that ends elsewhere.
+    OutputNotReached,
+    /// The node feeding `output` returned an empty string.
+    EmptyResult,
+}
+
+impl EmptyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmptyReason::NoOutputEdge => "no_output_edge",
+            EmptyReason::OutputNotReached => "output_not_reached",
+            EmptyReason::EmptyResult => "empty_result",
+        }
+    }
+
+    /// A sentence explaining the reason to the person who ran the pipeline.
+    pub fn message(&self) -> &'static str {
+        match self {
+            EmptyReason::NoOutputEdge => "The pipeline has no edge to output, so it has nothing to return.",
+            EmptyReason::OutputNotReached => "No node connected to output ran, so the pipeline returned nothing.",
+            EmptyReason::EmptyResult => "The node connected to output returned an empty response.",
+        }
+    }
 }
 
 /// Resolves model IDs to their configurations.
 ///
 /// Used by the engine to look up model configs for nodes that specify
 /// a model ID. Falls back to a default model when no match is found.
+#[derive(Clone)]
 pub struct ModelResolver {
     models: HashMap<String, Arc<ModelConfig>>,
     default_model: Arc<ModelConfig>,
+    fallbacks: Vec<String>,
+    unhealthy: HashSet<String>,
 }
 
 impl ModelResolver {
     /// Creates a resolver with available models and a default fallback.
     pub fn new(models: Vec<ModelConfig>, default: ModelConfig) -> Self {
         let map = models.into_iter().map(|m| (m.id.clone(), Arc::new(m))).collect();
-        Self { models: map, default_model: Arc::new(default) }
+        Self { models: map, default_model: Arc::new(default), fallbacks: Vec::new(), unhealthy: HashSet::new() }
+    }
+
+    /// Sets the fallback chain for nodes that don't list their own `fallback_models`.
+    pub fn with_fallbacks(mut self, model_ids: Vec<String>) -> Self {
+        self.fallbacks = model_ids;
+        self
+    }
+
+    /// Marks models as failing health checks, so fallback chains pass over them.
+    pub fn with_unhealthy(mut self, model_ids: HashSet<String>) -> Self {
+        self.unhealthy = model_ids;
+        self
+    }
+
+    /// True when the model is marked as failing health checks.
+    pub fn is_unhealthy(&self, model_id: &str) -> bool {
+        self.unhealthy.contains(model_id)
     }
 
     /// Resolves a model ID to its config, or returns the default.
@@ -144,6 +392,50 @@ impl ModelResolver {
             .cloned()
             .unwrap_or_else(|| Arc::clone(&self.default_model))
     }
+
+    /// Resolves a model ID like [`resolve`](Self::resolve), keeping to `policies`.
+    ///
+    /// A requested model the policies refuse is an error, never swapped for
+    /// another. Without a known ID, the default is used if it complies, else the
+    /// first complying model by ID. Errors say why no model could be used.
+    pub fn resolve_within(&self, model_id: Option<&str>, policies: &[ResidencyPolicy]) -> Result<Arc<ModelConfig>, String> {
+        let check = |model: &ModelConfig| {
+            policies.iter().try_for_each(|p| p.check(&model.provider_name(), model.region.as_deref()))
+        };
+
+        if let Some(model) = model_id.and_then(|id| self.models.get(id)) {
+            return check(model)
+                .map(|_| Arc::clone(model))
+                .map_err(|reason| format!("model '{}' is not allowed: {}", model.id, reason));
+        }
+        let Err(reason) = check(&self.default_model) else {
+            return Ok(Arc::clone(&self.default_model));
+        };
+        let mut compliant: Vec<&Arc<ModelConfig>> = self.models.values().filter(|m| check(m).is_ok()).collect();
+        compliant.sort_by(|a, b| a.id.cmp(&b.id));
+        compliant.first().map(|m| Arc::clone(m)).ok_or_else(|| {
+            format!("no available model satisfies the residency policy (default '{}': {})", self.default_model.id, reason)
+        })
+    }
+
+    /// Resolves the models to retry with after `primary` fails: `model_ids`,
+    /// or the resolver's own chain when empty, in order.
+    ///
+    /// Unknown IDs, `primary` itself, unhealthy models, and models `policies`
+    /// refuse are left out, so a fallback never sends data somewhere the
+    /// primary couldn't.
+    pub fn resolve_fallbacks(&self, model_ids: &[String], primary: &str, policies: &[ResidencyPolicy]) -> Vec<Arc<ModelConfig>> {
+        let chain = if model_ids.is_empty() { &self.fallbacks } else { model_ids };
+        let mut resolved: Vec<Arc<ModelConfig>> = Vec::new();
+        for model in chain.iter().filter_map(|id| self.models.get(id)) {
+            let allowed = policies.iter().all(|p| p.check(&model.provider_name(), model.region.as_deref()).is_ok());
+            let usable = allowed && model.id != primary && !self.is_unhealthy(&model.id);
+            if usable && !resolved.iter().any(|m| m.id == model.id) {
+                resolved.push(Arc::clone(model));
+            }
+        }
+        resolved
+    }
 }
 
 /// Core pipeline execution engine.
@@ -163,6 +455,23 @@ pub struct PipelineEngine {
     node_overrides: HashMap<String, String>,
     tool_registry: Arc<ToolRegistry>,
     collector: Option<Arc<dyn MetricsCollector>>,
+    llm_cache: Option<Arc<dyn LlmCache>>,
+    cassette: Option<Arc<Cassette>>,
+    default_capture: CaptureMode,
+    retriever: Option<Arc<Retriever>>,
+    memory: Option<(Arc<dyn Memory>, String)>,
+    tool_access: ToolPolicy,
+    approver: Option<Arc<dyn ToolApprover>>,
+    ledger: Option<(Arc<dyn SideEffectLedger>, String)>,
+    residency: Vec<ResidencyPolicy>,
+    local_hosts: Option<HostAllowlist>,
+    vram: Option<Arc<VramScheduler>>,
+    pipelines: Arc<HashMap<String, PipelineConfig>>,
+    pretty_logs: bool,
+    timezone: Timezone,
+    graph: ExecutionGraph,
+    path_prefix: String,
+    ancestors: Vec<String>,
 }
 
 impl PipelineEngine {
@@ -174,11 +483,28 @@ impl PipelineEngine {
         node_overrides: HashMap<String, String>,
     ) -> Self {
         Self {
+            graph: ExecutionGraph::compile(&config),
             config,
             resolver: ModelResolver::new(models, default_model),
             node_overrides,
             tool_registry: Arc::new(ToolRegistry::with_defaults()),
             collector: None,
+            llm_cache: None,
+            cassette: None,
+            default_capture: CaptureMode::default(),
+            retriever: None,
+            memory: None,
+            tool_access: ToolPolicy::default(),
+            approver: None,
+            ledger: None,
+            residency: Vec::new(),
+            local_hosts: None,
+            vram: None,
+            pipelines: Arc::new(HashMap::new()),
+            pretty_logs: true,
+            timezone: Timezone::from_env(),
+            path_prefix: String::new(),
+            ancestors: Vec::new(),
         }
     }
 
@@ -191,11 +517,28 @@ impl PipelineEngine {
         tool_registry: ToolRegistry,
     ) -> Self {
         Self {
+            graph: ExecutionGraph::compile(&config),
             config,
             resolver: ModelResolver::new(models, default_model),
             node_overrides,
             tool_registry: Arc::new(tool_registry),
             collector: None,
+            llm_cache: None,
+            cassette: None,
+            default_capture: CaptureMode::default(),
+            retriever: None,
+            memory: None,
+            tool_access: ToolPolicy::default(),
+            approver: None,
+            ledger: None,
+            residency: Vec::new(),
+            local_hosts: None,
+            vram: None,
+            pipelines: Arc::new(HashMap::new()),
+            pretty_logs: true,
+            timezone: Timezone::from_env(),
+            path_prefix: String::new(),
+            ancestors: Vec::new(),
         }
     }
 
@@ -205,25 +548,353 @@ impl PipelineEngine {
         self
     }
 
-    /// Gets the model to use for a node, considering overrides.
+    /// Serves repeated LLM requests from `cache` instead of calling the provider.
+    pub fn with_llm_cache(mut self, cache: Arc<dyn LlmCache>) -> Self {
+        self.llm_cache = Some(cache);
+        self
+    }
+
+    /// Records LLM interactions to, or replays them from, `cassette`.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Sets how much node input and output is logged and traced for nodes
+    /// whose `observe.capture` is unset. Defaults to [`CaptureMode::Full`].
+    pub fn with_default_capture(mut self, capture: CaptureMode) -> Self {
+        self.default_capture = capture;
+        self
+    }
+
+    /// Sets the retriever that `Retriever` nodes search.
+    pub fn with_retriever(mut self, retriever: Arc<Retriever>) -> Self {
+        self.retriever = Some(retriever);
+        self
+    }
+
+    /// Gives the pipeline long-term memory in `scope`, usually a user or session ID.
+    ///
+    /// Registers the `memory_read` and `memory_write` tools for that scope, and
+    /// appends the memories most relevant to each Llm and Worker node's input to
+    /// its prompt: `config.memory_recall` of them (default 5; 0 turns it off).
+    pub fn with_memory(mut self, memory: Arc<dyn Memory>, scope: impl Into<String>) -> Self {
+        let scope = scope.into();
+        let registry = Arc::make_mut(&mut self.tool_registry);
+        registry.register(MemoryReadTool::new(Arc::clone(&memory), scope.clone()));
+        registry.register(MemoryWriteTool::new(Arc::clone(&memory), scope.clone()));
+        self.memory = Some((memory, scope));
+        self
+    }
+
+    /// Sets the tool access policy applied to every node, on top of each node's `config.tool_access`.
+    pub fn with_tool_policy(mut self, policy: ToolPolicy) -> Self {
+        self.tool_access = policy;
+        self
+    }
+
+    /// Sets the models nodes retry with, in order, when their model fails and
+    /// they don't list `fallback_models` of their own.
+    pub fn with_fallback_models(mut self, model_ids: Vec<String>) -> Self {
+        self.resolver = self.resolver.with_fallbacks(model_ids);
+        self
+    }
+
+    /// Marks models as failing health checks. A node whose model is marked
+    /// runs on its first healthy fallback instead, if it has one.
+    pub fn with_unhealthy_models(mut self, model_ids: HashSet<String>) -> Self {
+        self.resolver = self.resolver.with_unhealthy(model_ids);
+        self
+    }
+
+    /// Adds a residency policy (e.g. a tenant's) that every node's model must
+    /// satisfy, on top of the pipeline's own `residency`.
+    pub fn with_residency(mut self, policy: ResidencyPolicy) -> Self {
+        self.residency.push(policy);
+        self
+    }
+
+    /// Keeps every request on the machine or network: nodes may only use models
+    /// whose region is `local` (Ollama), `web_search` is refused, and `fetch_url`,
+    /// `transcribe_audio` downloads, and HTTP tools may only reach `hosts`.
+    pub fn with_local_only(mut self, hosts: HostAllowlist) -> Self {
+        self.residency.push(ResidencyPolicy::new().allow_regions(["local"]));
+        self.local_hosts = Some(hosts);
+        self
+    }
+
+    /// Makes nodes using Ollama models wait for GPU memory instead of loading
+    /// more models than fit. Share one scheduler across engines using the same GPU.
+    pub fn with_vram_scheduler(mut self, scheduler: Arc<VramScheduler>) -> Self {
+        self.vram = Some(scheduler);
+        self
+    }
+
+    /// Sets who approves tool calls that the policy marks `require_approval`.
+    /// Without an approver, such calls are refused.
+    pub fn with_approver(mut self, approver: Arc<dyn ToolApprover>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Runs tools with side effects at most once for `run_key`, recording their
+    /// results in `ledger`. Executing a pipeline again with the same key and
+    /// ledger, as when resuming a run that failed partway, reuses the result of
+    /// each such call made before with the same node, tool, and arguments.
+    pub fn with_side_effect_ledger(mut self, ledger: Arc<dyn SideEffectLedger>, run_key: impl Into<String>) -> Self {
+        self.ledger = Some((ledger, run_key.into()));
+        self
+    }
+
+    /// Registers pipelines that `Pipeline` nodes may reference by ID.
+    pub fn with_pipelines(mut self, pipelines: impl IntoIterator<Item = PipelineConfig>) -> Self {
+        self.pipelines = Arc::new(pipelines.into_iter().map(|p| (p.id.clone(), p)).collect());
+        self
+    }
+
+    /// Logs box-framed banners for reading in a terminal (the default), or with
+    /// `false`, one event per pipeline and node with `pipeline_id`, `node_id`,
+    /// `step`, `duration_ms`, and token counts as fields, for log aggregation.
+    pub fn with_pretty_logs(mut self, pretty: bool) -> Self {
+        self.pretty_logs = pretty;
+        self
+    }
+
+    /// Sets the timezone of `{{now}}` in prompts of nodes without `config.timezone`.
+    /// Defaults to `FISSIO_TIMEZONE`, else UTC.
+    pub fn with_timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Creates the engine for a sub-pipeline node, sharing models, tools, collector, cache, and cassette.
+    ///
+    /// Node overrides keyed as `"<node_id>/<inner_id>"` are forwarded with the prefix stripped.
+    fn nested(&self, node_id: &str, config: PipelineConfig) -> PipelineEngine {
+        let prefix = format!("{}/", node_id);
+        let node_overrides = self.node_overrides
+            .iter()
+            .filter_map(|(k, v)| k.strip_prefix(&prefix).map(|inner| (inner.to_string(), v.clone())))
+            .collect();
+        let mut ancestors = self.ancestors.clone();
+        ancestors.push(self.config.id.clone());
+        let mut residency = self.residency.clone();
+        residency.extend(self.config.residency.clone());
+
+        PipelineEngine {
+            graph: ExecutionGraph::compile(&config),
+            config,
+            resolver: self.resolver.clone(),
+            node_overrides,
+            tool_registry: Arc::clone(&self.tool_registry),
+            collector: self.collector.clone(),
+            llm_cache: self.llm_cache.clone(),
+            cassette: self.cassette.clone(),
+            default_capture: self.default_capture,
+            retriever: self.retriever.clone(),
+            memory: self.memory.clone(),
+            tool_access: self.tool_access.clone(),
+            approver: self.approver.clone(),
+            ledger: self.ledger.clone(),
+            residency,
+            local_hosts: self.local_hosts.clone(),
+            vram: self.vram.clone(),
+            pipelines: Arc::clone(&self.pipelines),
+            pretty_logs: self.pretty_logs,
+            timezone: self.timezone,
+            path_prefix: format!("{}{}", self.path_prefix, prefix),
+            ancestors,
+        }
+    }
+
+    /// The compiled graph this engine schedules nodes by.
+    pub fn graph(&self) -> &ExecutionGraph {
+        &self.graph
+    }
+
+    /// Returns the node ID qualified with its sub-pipeline path (e.g. `research/summarizer`).
+    fn node_path(&self, node_id: &str) -> String {
+        format!("{}{}", self.path_prefix, node_id)
+    }
+
+    /// Returns the capture mode for a node, falling back to the engine default.
+    fn capture_for(&self, node: &NodeConfig) -> CaptureMode {
+        node.observe.as_ref().and_then(|o| o.capture).unwrap_or(self.default_capture)
+    }
+
+    /// Returns the node's prompt with `{{now}}` filled in.
+    fn node_prompt(&self, node: &NodeConfig) -> Option<String> {
+        let prompt = node.prompt.as_ref()?;
+        if !prompt.contains(NOW_VARIABLE) {
+            return Some(prompt.clone());
+        }
+        let timezone = match node.config.get("timezone").and_then(|v| v.as_str()) {
+            None => self.timezone,
+            Some(name) => name.parse().unwrap_or_else(|e| {
+                warn_line!(self.pretty_logs, "Ignoring timezone on {}: {}", self.node_path(&node.id), e);
+                self.timezone
+            }),
+        };
+        Some(prompt.replace(NOW_VARIABLE, &timezone.describe_now()))
+    }
+
+    /// Returns the node's prompt with its recalled memories appended, for Llm
+    /// and Worker nodes of an engine with memory.
+    async fn prompt_with_memories(&self, node: &NodeConfig, input: &str) -> Option<String> {
+        let prompt = self.node_prompt(node);
+        let Some((memory, scope)) = &self.memory else {
+            return prompt;
+        };
+        let limit = node
+            .config
+            .get("memory_recall")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MEMORY_RECALL, |n| n as usize);
+        if limit == 0 || !matches!(node.node_type, NodeType::Llm | NodeType::Worker) {
+            return prompt;
+        }
+        let memories = match memory.search(scope, input, limit).await {
+            Ok(memories) if !memories.is_empty() => memories,
+            Ok(_) => return prompt,
+            Err(e) => {
+                warn_line!(self.pretty_logs, "Memory recall failed for {}: {}", self.node_path(&node.id), e);
+                return prompt;
+            }
+        };
+        banner!(self.pretty_logs, "║     Memories: {}", memories.len());
+        let notes = memories.iter().map(|m| format!("- {}: {}", m.key, m.value)).collect::<Vec<_>>().join("\n");
+        let recalled = format!("What you remember from earlier conversations:\n{}", notes);
+        Some(match prompt {
+            Some(prompt) => format!("{}\n\n{}", prompt, recalled),
+            None => recalled,
+        })
+    }
+
+    /// Returns the node's `config.tool_policy`, falling back to the registry default.
+    fn tool_policy_for(&self, node: &NodeConfig) -> ToolExecutionPolicy {
+        let Some(value) = node.config.get("tool_policy") else {
+            return self.tool_registry.policy().clone();
+        };
+        serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+            warn_line!(self.pretty_logs, "Ignoring invalid tool_policy on {}: {}", self.node_path(&node.id), e);
+            self.tool_registry.policy().clone()
+        })
+    }
+
+    /// Returns the node's `config.tool_post_process` steps, keyed by tool name.
+    fn post_process_for(&self, node: &NodeConfig) -> PostProcessSteps {
+        let Some(value) = node.config.get("tool_post_process") else {
+            return PostProcessSteps::new();
+        };
+        post_process::parse_steps(value).unwrap_or_else(|e| {
+            warn_line!(self.pretty_logs, "Ignoring invalid tool_post_process on {}: {}", self.node_path(&node.id), e);
+            PostProcessSteps::new()
+        })
+    }
+
+    /// Returns the tool registry for a node, adding the HTTP tools declared in
+    /// its `config.http_tools`.
+    fn registry_for(&self, node: &NodeConfig) -> Arc<ToolRegistry> {
+        let Some(value) = node.config.get("http_tools") else {
+            return Arc::clone(&self.tool_registry);
+        };
+        match serde_json::from_value::<Vec<HttpToolSpec>>(value.clone()) {
+            Ok(specs) => {
+                let mut registry = (*self.tool_registry).clone();
+                for spec in specs {
+                    match &self.local_hosts {
+                        Some(hosts) => registry.register(HttpTool::new(spec).with_allowed_hosts(hosts.clone())),
+                        None => registry.register(HttpTool::new(spec)),
+                    }
+                }
+                Arc::new(registry)
+            }
+            Err(e) => {
+                warn_line!(self.pretty_logs, "Ignoring invalid http_tools on {}: {}", self.node_path(&node.id), e);
+                Arc::clone(&self.tool_registry)
+            }
+        }
+    }
+
+    /// Builds the tool context for a node: the engine's access policy combined
+    /// with the node's `config.tool_access`, its execution policy, and its
+    /// post-processing steps.
+    fn tool_context(&self, node: &NodeConfig) -> ToolContext {
+        let node_path = self.node_path(&node.id);
+        let access = match node.config.get("tool_access") {
+            None => self.tool_access.clone(),
+            Some(value) => match serde_json::from_value::<ToolPolicy>(value.clone()) {
+                Ok(node_access) => self.tool_access.and(&node_access),
+                Err(e) => {
+                    // Fail closed: a typo in a deny list must not allow everything
+                    warn_line!(self.pretty_logs, "Invalid tool_access on {}, denying all tools: {}", node_path, e);
+                    ToolPolicy::new().allow_only(Vec::<String>::new())
+                }
+            },
+        };
+        let access = match &self.local_hosts {
+            Some(_) => access.and(&ToolPolicy::new().deny("web_search")),
+            None => access,
+        };
+        ToolContext {
+            registry: self.registry_for(node),
+            execution: self.tool_policy_for(node),
+            post_process: self.post_process_for(node),
+            access,
+            approver: self.approver.clone(),
+            ledger: self.ledger.clone(),
+            allowed_hosts: self.local_hosts.clone(),
+            node_path,
+            pretty_logs: self.pretty_logs,
+        }
+    }
+
+    /// Gets the model to use for a node, considering overrides, residency
+    /// policies, and model health.
     /// Returns Arc for cheap cloning in parallel execution.
-    fn get_node_model(&self, node: &NodeConfig) -> Arc<ModelConfig> {
+    fn get_node_model(&self, node: &NodeConfig) -> Result<Arc<ModelConfig>, AgentError> {
         let model_id = self.node_overrides
             .get(&node.id)
             .or(node.model.as_ref());
-        self.resolver.resolve(model_id.map(|s| s.as_str()))
+        let model = self.resolver.resolve_within(model_id.map(|s| s.as_str()), &self.residency_policies()).map_err(|reason| {
+            AgentError::WorkerFailed(format!("Node '{}' has no usable model: {}", self.node_path(&node.id), reason))
+        })?;
+        if self.resolver.is_unhealthy(&model.id) {
+            if let Some(fallback) = self.get_fallback_models(node, &model).into_iter().next() {
+                return Ok(fallback);
+            }
+        }
+        Ok(model)
+    }
+
+    /// Gets the models to retry a node with after `primary` fails, keeping to
+    /// the same residency policies.
+    fn get_fallback_models(&self, node: &NodeConfig, primary: &ModelConfig) -> Vec<Arc<ModelConfig>> {
+        // Sub-pipelines pick models per node; their nodes fall back themselves
+        if node.node_type == NodeType::Pipeline {
+            return Vec::new();
+        }
+        self.resolver.resolve_fallbacks(&node.fallback_models, &primary.id, &self.residency_policies())
+    }
+
+    /// The engine's residency policies and the pipeline's.
+    fn residency_policies(&self) -> Vec<ResidencyPolicy> {
+        self.residency.iter().chain(&self.config.residency).cloned().collect()
     }
 
     /// Finds a node by ID.
     fn get_node(&self, id: &str) -> Option<&NodeConfig> {
-        self.config.nodes.iter().find(|n| n.id == id)
+        self.graph.node_index(id).map(|i| &self.config.nodes[i])
     }
 
     /// Gets all edges originating from a node.
     fn get_outgoing_edges(&self, node_id: &str) -> Vec<&EdgeConfig> {
-        self.config.edges.iter().filter(|e| {
-            e.from.as_vec().contains(&node_id)
-        }).collect()
+        self.graph.outgoing(node_id).iter().map(|&i| &self.config.edges[i]).collect()
+    }
+
+    /// Gets all edges leading to a node, in config order.
+    fn get_incoming_edges(&self, node_id: &str) -> impl Iterator<Item = &EdgeConfig> {
+        self.graph.incoming(node_id).iter().map(|&i| &self.config.edges[i])
     }
 
     /// Gets all target node IDs from outgoing edges (for router decisions).
@@ -236,205 +907,456 @@ impl PipelineEngine {
     }
 
     /// Executes the pipeline and returns the result.
-    pub async fn execute_stream(
+    ///
+    /// Runs in a `pipeline` span with a `node` span per node, under which the
+    /// `llm` and `tool` spans of the node's calls nest.
+    ///
+    /// The future is boxed, which keeps it `Send` although `Pipeline` nodes
+    /// run engines of their own, so it can be spawned or run inside a tool.
+    pub fn execute_stream<'a>(
+        &'a self,
+        user_input: &'a str,
+        history: &'a [fissio_core::Message],
+    ) -> BoxFuture<'a, Result<EngineOutput, AgentError>> {
+        let span = info_span!("pipeline", pipeline.id = %self.config.id, pipeline.name = %self.config.name);
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let result = self.run_graph(user_input, history, started).await;
+                if let Err(e) = &result {
+                    if !self.pretty_logs {
+                        warn!(pipeline_id = %self.config.id, duration_ms = started.elapsed().as_millis() as u64, error = %e, "Pipeline failed");
+                    }
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+
+    /// Runs the pipeline for [`execute_stream`](Self::execute_stream), which logs a failure.
+    async fn run_graph(
         &self,
         user_input: &str,
         history: &[fissio_core::Message],
+        started: Instant,
     ) -> Result<EngineOutput, AgentError> {
-        info!("╔══════════════════════════════════════════════════════════════");
-        info!("║ PIPELINE: {}", self.config.name);
-        info!("║ Input: {}...", user_input.chars().take(50).collect::<String>());
-        info!("╠══════════════════════════════════════════════════════════════");
+        if self.pretty_logs {
+            info!("╔══════════════════════════════════════════════════════════════");
+            if self.path_prefix.is_empty() {
+                info!("║ PIPELINE: {}", self.config.name);
+            } else {
+                info!("║ SUB-PIPELINE: {} ({})", self.config.name, self.path_prefix.trim_end_matches('/'));
+            }
+            // The banner never shows more than a preview; full text goes to the debug node logs.
+            let banner_capture = match self.default_capture {
+                CaptureMode::Off => CaptureMode::Off,
+                _ => CaptureMode::Preview,
+            };
+            info!("║ Input: {}", banner_capture.apply(user_input));
+            info!("╠══════════════════════════════════════════════════════════════");
 
-        if !self.node_overrides.is_empty() {
-            info!("║ Node model overrides: {:?}", self.node_overrides);
+            if !self.node_overrides.is_empty() {
+                info!("║ Node model overrides: {:?}", self.node_overrides);
+            }
+        } else {
+            info!(pipeline_id = %self.config.id, pipeline_name = %self.config.name, "Pipeline started");
+        }
+
+        // Refuse before any node runs, so no data goes out when a later node can't comply
+        for node in &self.config.nodes {
+            self.get_node_model(node)?;
+        }
+
+        if let Err(e) = self.config.validate() {
+            warn_line!(self.pretty_logs, "{}", e);
+        }
+        // Nothing would reach the caller, so don't spend the tokens
+        if !self.config.has_output_edge() {
+            self.log_finished(started, "skipped", "Pipeline skipped (no output edge)".to_string());
+            return Ok(EngineOutput::Empty(EmptyReason::NoOutputEdge));
         }
 
         let context = Arc::new(RwLock::new(HashMap::<String, String>::new()));
         context.write().await.insert("input".to_string(), user_input.to_string());
 
-        let mut executed: HashSet<String> = HashSet::new();
         let step = Arc::new(RwLock::new(0usize));
 
-        // Find starting edges (from "input")
-        let start_edges: Vec<&EdgeConfig> = self.config.edges.iter()
-            .filter(|e| matches!(&e.from, EdgeEndpoint::Single(s) if s == "input"))
-            .collect();
+        // The streaming terminal node runs after the rest of the graph
+        let terminal = self.streaming_terminal();
+        let held = terminal.map(|n| n.id.as_str());
+        let mut frontier = Frontier::new(&self.graph);
+        loop {
+            let batch = frontier.next_batch(held);
+            if batch.is_empty() {
+                break;
+            }
+            for stage in self.stages(&batch, &frontier) {
+                let finished = match stage.kind {
+                    StageKind::Parallel => {
+                        // Completes each node in the frontier as it finishes
+                        self.execute_parallel(&stage.nodes, stage.max_concurrency, &batch, held, &mut frontier, &context, history, &step).await?;
+                        Vec::new()
+                    }
+                    StageKind::Map => {
+                        let target = &stage.nodes[0];
+                        let map_id = self.map_parent(target, &frontier).unwrap_or_default().to_string();
+                        self.execute_map(&map_id, target, &context, history, &step).await?;
+                        vec![(target.clone(), Vec::new())]
+                    }
+                    _ => self.execute_sequential(&stage.nodes, &context, history, &step).await?,
+                };
+                for (id, next_nodes) in finished {
+                    frontier.complete(&id, next_nodes);
+                }
+            }
+        }
 
-        for start_edge in start_edges {
-            self.process_edge(start_edge, &context, &mut executed, history, &step).await?;
+        if let Some(node) = terminal {
+            if frontier.reached(&node.id) {
+                let sources = self.cited_sources(&node.id, &context).await;
+                let stream = with_citations(self.stream_terminal_node(node, &context, &step).await?, sources);
+                self.log_finished(started, "streaming", format!("Pipeline complete (streaming {})", self.node_path(&node.id)));
+                return Ok(EngineOutput::Stream(stream));
+            }
         }
 
         // Find output
         let ctx = context.read().await;
-        for edge in &self.config.edges {
+        for edge in self.graph.incoming("output").iter().map(|&i| &self.config.edges[i]) {
             if !matches!(&edge.to, EdgeEndpoint::Single(s) if s == "output") {
                 continue;
             }
 
             let from_nodes = edge.from.as_vec();
-            let output = from_nodes.iter()
+            let Some((from, output)) = from_nodes.iter()
                 .rev()
-                .find_map(|id| ctx.get(*id))
-                .cloned()
-                .unwrap_or_default();
+                .find_map(|id| ctx.get(*id).map(|text| (*id, text.clone())))
+            else {
+                self.log_finished(started, "output_not_reached", "Pipeline complete (output not reached)".to_string());
+                return Ok(EngineOutput::Empty(EmptyReason::OutputNotReached));
+            };
+            drop(ctx);
+
+            if output.trim().is_empty() {
+                self.log_finished(started, "empty_result", format!("Pipeline complete (empty result from {})", self.node_path(from)));
+                return Ok(EngineOutput::Empty(EmptyReason::EmptyResult));
+            }
 
-            info!("║ Pipeline complete");
+            self.log_finished(started, "complete", "Pipeline complete".to_string());
+            let citations = find_citations(&output, &self.cited_sources(from, &context).await);
+            if citations.is_empty() {
+                return Ok(EngineOutput::Complete(output));
+            }
+            let chunks = std::iter::once(StreamChunk::Content(output))
+                .chain(citations.into_iter().map(StreamChunk::Citation))
+                .map(Ok::<_, AgentError>);
+            return Ok(EngineOutput::Stream(Box::pin(futures::stream::iter(chunks))));
+        }
+
+        self.log_finished(started, "no_output_edge", "Pipeline complete (no output edge found)".to_string());
+        Ok(EngineOutput::Empty(EmptyReason::NoOutputEdge))
+    }
+
+    /// Ends the run's log: closes the banner with `summary`, or emits a
+    /// `Pipeline finished` event with `outcome` and the run's duration.
+    fn log_finished(&self, started: Instant, outcome: &str, summary: String) {
+        if self.pretty_logs {
+            info!("║ {}", summary);
             info!("╚══════════════════════════════════════════════════════════════");
-            return Ok(EngineOutput::Complete(output));
+        } else {
+            info!(pipeline_id = %self.config.id, outcome, duration_ms = started.elapsed().as_millis() as u64, "Pipeline finished");
+        }
+    }
+
+    /// Returns the Worker node that alone feeds `output`, if there is one.
+    ///
+    /// Its agentic loop is streamed to the caller rather than buffered, so tool
+    /// activity is visible while it runs.
+    fn streaming_terminal(&self) -> Option<&NodeConfig> {
+        let mut output_edges = self.graph
+            .incoming("output")
+            .iter()
+            .map(|&i| &self.config.edges[i])
+            .filter(|e| matches!(&e.to, EdgeEndpoint::Single(s) if s == "output"));
+        let edge = output_edges.next()?;
+        if output_edges.next().is_some() {
+            return None;
         }
 
-        info!("║ Pipeline complete (no output edge found)");
-        info!("╚══════════════════════════════════════════════════════════════");
-        Ok(EngineOutput::Complete(String::new()))
+        let EdgeEndpoint::Single(id) = &edge.from else { return None };
+        let node = self.get_node(id)?;
+        let streams = node.node_type == NodeType::Worker && node.output_schema().is_none();
+        (streams && self.get_outgoing_targets(id).is_empty()).then_some(node)
     }
 
-    /// Processes an edge, executing target nodes based on edge type.
-    #[async_recursion]
-    async fn process_edge(
+    /// Starts the terminal Worker node and returns its output as a stream.
+    ///
+    /// Metrics and the span are recorded once the stream's agentic loop finishes.
+    async fn stream_terminal_node(
         &self,
-        edge: &EdgeConfig,
+        node: &NodeConfig,
         context: &Arc<RwLock<HashMap<String, String>>>,
-        executed: &mut HashSet<String>,
-        history: &[fissio_core::Message],
         step: &Arc<RwLock<usize>>,
-    ) -> Result<(), AgentError> {
-        let target_ids = edge.to.as_vec();
+    ) -> Result<LlmStream, AgentError> {
+        let input = self.get_input_for_node(&node.id, context).await;
+        let model = self.get_node_model(node)?;
+        let current_step = {
+            let mut s = step.write().await;
+            *s += 1;
+            *s
+        };
+
+        if self.pretty_logs {
+            info!("╠──────────────────────────────────────────────────────────────");
+            info!("║ [{}] NODE: {} ({:?}, streaming)", current_step, self.node_path(&node.id), node.node_type);
+            info!("║     Model: {}", model.name);
+            if !node.tools.is_empty() {
+                info!("║     Tools: {:?}", node.tools);
+            }
+            debug!("║     Input: {}", self.capture_for(node).apply(&input));
+        }
+        let log = self.node_log(node, &model, current_step);
+        if let Some(log) = &log {
+            log.started();
+        }
+        let recorder = self.recorder(node, &model);
+
+        let vram = self.vram_permit(&model).await;
+        let started = Instant::now();
+        let start_time_ms = now_ms();
+        let tool_context = self.tool_context(node);
+        let tools = tool_context.registry.resolve(&node.tools);
+        let prompt = self.prompt_with_memories(node, &input).await;
+        let node_path = self.node_path(&node.id);
+        let span = node_span(&node_path, node, &model);
+        let client = self.llm_client(&model);
+        // The loop's task is spawned inside the node span, so its calls nest under it
+        let stream = span.in_scope(|| {
+            stream_node_with_tools(client, prompt, input.clone(), tools, tool_context, move |result| {
+                // The permit lives in this callback, so it is released when the loop ends either way
+                drop(vram);
+                if let Some(log) = log {
+                    log.finished(started.elapsed(), result.map(|(_, metrics)| metrics));
+                }
+                if let (Some(recorder), Ok((content, metrics))) = (recorder, result) {
+                    recorder.record(&input, content, metrics, start_time_ms, now_ms());
+                }
+            })
+        });
+        Ok(Box::pin(stream.map(move |chunk| chunk.map_err(|e| AgentError::node_failed(node_path.clone(), e)))))
+    }
 
-        if target_ids.len() == 1 && target_ids[0] == "output" {
-            return Ok(());
+    /// Splits a batch of ready nodes into stages: the targets of one parallel
+    /// edge run together, a Map node's targets run once per item, and the
+    /// rest run one at a time.
+    fn stages(&self, batch: &[String], frontier: &Frontier) -> Vec<PlanStage> {
+        let mut stages: Vec<PlanStage> = Vec::new();
+        // Edge index -> position of its stage
+        let mut parallel: HashMap<usize, usize> = HashMap::new();
+
+        for id in batch {
+            if let Some(map_id) = self.map_parent(id, frontier) {
+                let concurrency = self.get_node(map_id).map(|n| self.map_concurrency(n));
+                stages.push(PlanStage { kind: StageKind::Map, nodes: vec![id.clone()], max_concurrency: concurrency });
+                continue;
+            }
+
+            let Some(edge_idx) = self.parallel_edge(id, frontier) else {
+                stages.push(PlanStage { kind: StageKind::Sequential, nodes: vec![id.clone()], max_concurrency: None });
+                continue;
+            };
+            match parallel.get(&edge_idx) {
+                Some(&pos) => stages[pos].nodes.push(id.clone()),
+                None => {
+                    parallel.insert(edge_idx, stages.len());
+                    let limit = self.config.edges[edge_idx].max_concurrency.or(self.config.max_concurrency);
+                    stages.push(PlanStage { kind: StageKind::Parallel, nodes: vec![id.clone()], max_concurrency: limit });
+                }
+            }
         }
+        stages
+    }
 
-        if edge.edge_type == EdgeType::Parallel {
-            return self.execute_parallel(target_ids, context, executed, history, step).await;
+    /// Returns the index of the parallel edge `id` was reached over, if any.
+    fn parallel_edge(&self, id: &str, frontier: &Frontier) -> Option<usize> {
+        self.graph.incoming(id).iter().copied().find(|&i| {
+            let edge = &self.config.edges[i];
+            edge.edge_type == EdgeType::Parallel
+                && edge.from.as_vec().iter().any(|from| !self.graph.is_back_edge(from, id) && frontier.taken(from, id))
+        })
+    }
+
+    /// Applies the failure policy of the parallel `edge` to a target that
+    /// failed with `error`.
+    ///
+    /// Returns true when the default output was substituted, so the node
+    /// counts as finished, and false when it should be skipped.
+    async fn recover(
+        &self,
+        node_id: &str,
+        error: AgentError,
+        edge: Option<&EdgeConfig>,
+        context: &Arc<RwLock<HashMap<String, String>>>,
+    ) -> Result<bool, AgentError> {
+        let policy = edge.map(|e| e.on_failure).unwrap_or_default();
+        match policy {
+            FailurePolicy::SkipFailed => {
+                warn_line!(self.pretty_logs, "Skipping failed node {}: {}", self.node_path(node_id), error);
+                Ok(false)
+            }
+            FailurePolicy::SubstituteDefault => {
+                warn_line!(self.pretty_logs, "Substituting default output for failed node {}: {}", self.node_path(node_id), error);
+                let output = edge.and_then(|e| e.default_output.clone()).unwrap_or_default();
+                context.write().await.insert(node_id.to_string(), output);
+                Ok(true)
+            }
+            _ => Err(error),
         }
+    }
 
-        self.execute_sequential(target_ids, context, executed, history, step).await
+    /// Returns the finished Map node feeding `id`, whose items it runs once each.
+    fn map_parent<'a>(&'a self, id: &str, frontier: &Frontier) -> Option<&'a str> {
+        self.graph
+            .parents(id)
+            .iter()
+            .find(|p| frontier.taken(p, id) && self.get_node(p).is_some_and(|n| n.node_type == NodeType::Map))
+            .map(String::as_str)
     }
 
-    /// Executes multiple nodes concurrently using `tokio::join_all`.
+    /// Executes multiple nodes concurrently.
     ///
     /// Each node runs independently with its own model and input context.
-    /// Results are collected and stored in the shared context map.
-    /// Router node decisions are tracked to filter subsequent edge processing.
+    /// When `limit` is set, a semaphore caps how many nodes run at once.
+    /// Each node is stored and completed in `frontier` as soon as it
+    /// finishes, so a node whose quorum is met then starts right away
+    /// instead of waiting for the slowest target. A failed node goes through
+    /// the failure policy of the parallel edge it was reached over, so one
+    /// failed branch doesn't discard the others.
+    ///
+    /// `pending` is the rest of the batch, which starts in its own stage, and
+    /// `held` the streaming terminal, which starts last.
+    #[allow(clippy::too_many_arguments)]
     async fn execute_parallel(
         &self,
-        target_ids: Vec<&str>,
+        target_ids: &[String],
+        limit: Option<usize>,
+        pending: &[String],
+        held: Option<&str>,
+        frontier: &mut Frontier<'_>,
         context: &Arc<RwLock<HashMap<String, String>>>,
-        executed: &mut HashSet<String>,
         history: &[fissio_core::Message],
         step: &Arc<RwLock<usize>>,
     ) -> Result<(), AgentError> {
-        info!("╠══════════════════════════════════════════════════════════════");
-        info!("║ PARALLEL EXECUTION: {:?}", target_ids);
+        banner!(self.pretty_logs, "╠══════════════════════════════════════════════════════════════");
+        banner!(self.pretty_logs, "║ PARALLEL EXECUTION: {:?}", target_ids);
+        if let Some(n) = limit {
+            banner!(self.pretty_logs, "║ Max concurrency: {}", n);
+        }
 
-        // Gather node data
-        let mut node_data = Vec::new();
-        for id in target_ids.iter().filter(|&id| !executed.contains(*id)) {
+        let semaphore = limit.map(|n| Arc::new(Semaphore::new(n.max(1))));
+        let mut running = FuturesUnordered::new();
+        for id in target_ids {
             let Some(node) = self.get_node(id) else { continue };
-            let input = self.get_input_for_node(id, context).await;
-            let model = self.get_node_model(node).clone();
-            let outgoing_targets = self.get_outgoing_targets(id);
-            node_data.push((node.id.clone(), node.node_type, model, node.prompt.clone(), node.tools.clone(), input, outgoing_targets, node.observe.clone()));
+            let model = self.get_node_model(node)?;
+            let edge = self.parallel_edge(id, frontier);
+            running.push(self.parallel_node(node, model, edge, semaphore.clone(), context, history, step));
         }
 
-        // Execute in parallel
-        let tool_registry = Arc::clone(&self.tool_registry);
-        let collector = self.collector.clone();
-        let futures: Vec<_> = node_data.into_iter()
-            .map(|(node_id, node_type, model, prompt, tools, input, outgoing_targets, observe)| {
-                let step = Arc::clone(step);
-                let registry = Arc::clone(&tool_registry);
-                let collector = collector.clone();
-                async move {
-                    let current_step = {
-                        let mut s = step.write().await;
-                        *s += 1;
-                        *s
-                    };
-                    let start = std::time::Instant::now();
-                    let start_time_ms = now_ms();
-                    let result = execute_node(&node_id, node_type, &model, prompt.as_deref(), &input, &tools, &registry, current_step, &outgoing_targets).await;
-                    let elapsed_ms = start.elapsed().as_millis() as u64;
-                    let end_time_ms = now_ms();
-
-                    // Record metrics and span if observe is enabled and execution succeeded
-                    let should_record = result.is_ok()
-                        && observe.as_ref().is_some_and(|o| o.enabled)
-                        && collector.is_some();
-                    if should_record {
-                        let (ref output, ref exec_metrics) = result.as_ref().unwrap();
-                        let node_metrics = NodeMetrics {
-                            node_id: node_id.clone(),
-                            input_tokens: exec_metrics.input_tokens,
-                            output_tokens: exec_metrics.output_tokens,
-                            elapsed_ms,
-                            tool_call_count: exec_metrics.tool_call_count,
-                            iteration_count: exec_metrics.iteration_count,
-                            estimated_cost_usd: None,
-                        };
-                        let coll = collector.as_ref().unwrap();
-                        coll.record(node_metrics.clone());
-                        coll.record_span(
-                            &node_id,
-                            &node_type.to_string(),
-                            start_time_ms,
-                            end_time_ms,
-                            &input,
-                            &output.content,
-                            &node_metrics,
-                        );
+        let mut started: HashSet<String> = pending.iter().cloned().collect();
+        while let Some((node_id, edge, result)) = running.next().await {
+            match result {
+                Ok(output) => {
+                    context.write().await.insert(node_id.clone(), output.content);
+                    frontier.complete(&node_id, output.next_nodes);
+                }
+                Err(e) => {
+                    if self.recover(&node_id, e, edge.map(|i| &self.config.edges[i]), context).await? {
+                        frontier.complete(&node_id, Vec::new());
+                    } else {
+                        frontier.skip(&node_id);
                     }
-
-                    // Map result to extract just the NodeOutput for compatibility
-                    (node_id, result.map(|(output, _)| output))
                 }
-            })
-            .collect();
-
-        let results = join_all(futures).await;
+            }
 
-        // Store results and track router decisions
-        let mut router_decisions: HashMap<String, Vec<String>> = HashMap::new();
-        for (node_id, result) in results {
-            let output = result?;
-            context.write().await.insert(node_id.clone(), output.content);
-            if !output.next_nodes.is_empty() {
-                router_decisions.insert(node_id.clone(), output.next_nodes);
+            for id in frontier.quorum_ready(held, &started) {
+                // A Map node's targets run once per item, in their own stage
+                if self.map_parent(&id, frontier).is_some() {
+                    continue;
+                }
+                let Some(node) = self.get_node(&id) else { continue };
+                banner!(self.pretty_logs, "║ QUORUM MET: {}", self.node_path(&id));
+                let model = self.get_node_model(node)?;
+                let edge = self.parallel_edge(&id, frontier);
+                // Counted against the stage's max_concurrency like the nodes it started with
+                running.push(self.parallel_node(node, model, edge, semaphore.clone(), context, history, step));
+                started.insert(id);
             }
-            executed.insert(node_id);
         }
 
-        info!("║ PARALLEL EXECUTION COMPLETE");
-        info!("╠══════════════════════════════════════════════════════════════");
+        banner!(self.pretty_logs, "║ PARALLEL EXECUTION COMPLETE");
+        banner!(self.pretty_logs, "╠══════════════════════════════════════════════════════════════");
 
-        // Process outgoing edges
-        for node_id in target_ids {
-            let router_targets = router_decisions.get(node_id).map(|v| v.as_slice()).unwrap_or(&[]);
-            self.process_outgoing_edges(node_id, router_targets, context, executed, history, step).await?;
+        Ok(())
+    }
+
+    /// Runs and records one node of a parallel stage, once `semaphore` has a
+    /// permit. Returns the node's ID and the parallel edge it was reached
+    /// over with its output or error.
+    #[allow(clippy::too_many_arguments)]
+    async fn parallel_node(
+        &self,
+        node: &NodeConfig,
+        model: Arc<ModelConfig>,
+        edge: Option<usize>,
+        semaphore: Option<Arc<Semaphore>>,
+        context: &Arc<RwLock<HashMap<String, String>>>,
+        history: &[fissio_core::Message],
+        step: &Arc<RwLock<usize>>,
+    ) -> (String, Option<usize>, Result<NodeOutput, AgentError>) {
+        let _permit = match &semaphore {
+            Some(s) => s.acquire().await.ok(),
+            None => None,
+        };
+        let input = self.get_input_for_node(&node.id, context).await;
+        let outgoing_targets = self.get_outgoing_targets(&node.id);
+        let current_step = {
+            let mut s = step.write().await;
+            *s += 1;
+            *s
+        };
+        let start_time_ms = now_ms();
+        let result = self.run_node(node, &model, &input, history, context, current_step, &outgoing_targets).await;
+        let end_time_ms = now_ms();
+
+        match &result {
+            Ok((output, used)) => {
+                self.record_node(node, used, &input, &output.content, &output.metrics, start_time_ms, end_time_ms);
+            }
+            Err(e) => {
+                if let Some(recorder) = self.recorder(node, &model) {
+                    recorder.record_failure(&input, e, start_time_ms, end_time_ms);
+                }
+            }
         }
 
-        Ok(())
+        (node.id.clone(), edge, result.map(|(output, _)| output))
     }
 
     /// Executes nodes one at a time in order.
     ///
     /// Each node receives input from previously executed nodes via the context map.
     /// Errors in any node abort execution and propagate up.
-    /// Router decisions are applied to filter which outgoing edges to follow.
+    /// Returns each node with the targets it routed to, if it is a router.
     async fn execute_sequential(
         &self,
-        target_ids: Vec<&str>,
+        target_ids: &[String],
         context: &Arc<RwLock<HashMap<String, String>>>,
-        executed: &mut HashSet<String>,
         history: &[fissio_core::Message],
         step: &Arc<RwLock<usize>>,
-    ) -> Result<(), AgentError> {
+    ) -> Result<Vec<(String, Vec<String>)>, AgentError> {
+        let mut finished = Vec::with_capacity(target_ids.len());
         for node_id in target_ids {
-            if executed.contains(node_id) || node_id == "output" {
-                continue;
-            }
-
             let Some(node) = self.get_node(node_id) else { continue };
             let input = self.get_input_for_node(node_id, context).await;
             let outgoing_targets = self.get_outgoing_targets(node_id);
@@ -445,101 +1367,498 @@ impl PipelineEngine {
                 *s
             };
 
-            let model = self.get_node_model(node);
-            let start = std::time::Instant::now();
+            let model = self.get_node_model(node)?;
             let start_time_ms = now_ms();
-            let (output, exec_metrics) = execute_node(node_id, node.node_type, &model, node.prompt.as_deref(), &input, &node.tools, &self.tool_registry, current_step, &outgoing_targets).await?;
-            let elapsed_ms = start.elapsed().as_millis() as u64;
+            let (output, used) = self.run_node(node, &model, &input, history, context, current_step, &outgoing_targets).await?;
             let end_time_ms = now_ms();
 
-            // Record metrics and span if observe is enabled
-            let should_record = node.observe.as_ref().is_some_and(|o| o.enabled) && self.collector.is_some();
-            if should_record {
-                let node_metrics = NodeMetrics {
-                    node_id: node_id.to_string(),
-                    input_tokens: exec_metrics.input_tokens,
-                    output_tokens: exec_metrics.output_tokens,
-                    elapsed_ms,
-                    tool_call_count: exec_metrics.tool_call_count,
-                    iteration_count: exec_metrics.iteration_count,
-                    estimated_cost_usd: None,
-                };
-                let collector = self.collector.as_ref().unwrap();
-                collector.record(node_metrics.clone());
-                collector.record_span(
-                    node_id,
-                    &node.node_type.to_string(),
-                    start_time_ms,
-                    end_time_ms,
-                    &input,
-                    &output.content,
-                    &node_metrics,
-                );
-            }
+            self.record_node(node, &used, &input, &output.content, &output.metrics, start_time_ms, end_time_ms);
 
             context.write().await.insert(node_id.to_string(), output.content.clone());
-            executed.insert(node_id.to_string());
+            finished.push((node_id.clone(), output.next_nodes));
+        }
+
+        Ok(finished)
+    }
 
-            // Process outgoing edges - filter by router decision if applicable
-            self.process_outgoing_edges(node_id, &output.next_nodes, context, executed, history, step).await?;
+    /// Executes a node, retrying with its fallback models when the model
+    /// fails or exceeds the node's `config.timeout_ms`.
+    ///
+    /// Returns the model that produced the output along with it.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_node(
+        &self,
+        node: &NodeConfig,
+        model: &Arc<ModelConfig>,
+        input: &str,
+        history: &[fissio_core::Message],
+        context: &Arc<RwLock<HashMap<String, String>>>,
+        step: usize,
+        outgoing_targets: &[String],
+    ) -> Result<(NodeOutput, Arc<ModelConfig>), AgentError> {
+        let timeout = node.config.get("timeout_ms").and_then(|v| v.as_u64()).map(Duration::from_millis);
+        let mut fallbacks = self.get_fallback_models(node, model).into_iter();
+        let mut model = Arc::clone(model);
+        loop {
+            let attempt = self.attempt_node(node, &model, input, history, context, step, outgoing_targets);
+            let result = match timeout {
+                Some(limit) => tokio::time::timeout(limit, attempt).await.unwrap_or_else(|_| {
+                    Err(AgentError::LlmError(format!("Node '{}' timed out after {}ms", self.node_path(&node.id), limit.as_millis())))
+                }),
+                None => attempt.await,
+            };
+            let error = match result {
+                Ok(output) => return Ok((output, model)),
+                Err(e) => e,
+            };
+            let Some(next) = fallbacks.next() else {
+                return Err(AgentError::node_failed(self.node_path(&node.id), error));
+            };
+            warn_line!(self.pretty_logs, "{} failed on {}, falling back to {}: {}", self.node_path(&node.id), model.id, next.id, error);
+            model = next;
         }
+    }
 
-        Ok(())
+    /// Executes a node once on `model`, dispatching `Pipeline` nodes to a nested engine.
+    #[allow(clippy::too_many_arguments)]
+    async fn attempt_node(
+        &self,
+        node: &NodeConfig,
+        model: &ModelConfig,
+        input: &str,
+        history: &[fissio_core::Message],
+        context: &Arc<RwLock<HashMap<String, String>>>,
+        step: usize,
+        outgoing_targets: &[String],
+    ) -> Result<NodeOutput, AgentError> {
+        let log = self.node_log(node, model, step);
+        if let Some(log) = &log {
+            log.started();
+        }
+        let started = Instant::now();
+        let span = node_span(&self.node_path(&node.id), node, model);
+        let result = async {
+            if node.node_type == NodeType::Pipeline {
+                return self.execute_sub_pipeline(node, input, history, step).await;
+            }
+            let _vram = self.vram_permit(model).await;
+            let lease = HostBalancer::global().acquire(model);
+            let model = lease.as_ref().map_or(model, HostLease::model);
+            if node.node_type == NodeType::Retriever {
+                return self.execute_retriever(node, model, input, context, step).await;
+            }
+            if node.node_type == NodeType::Grounding {
+                return self.execute_grounding(node, model, input, context, step).await;
+            }
+            let client = self.llm_client(model);
+            let tool_context = self.tool_context(node);
+            // Expands selections such as `web/*` against the node's registry
+            let tools = tool_context.registry.resolve(&node.tools);
+            let prompt = self.prompt_with_memories(node, input).await;
+            let schema = output_schema(node);
+            execute_node(&self.node_path(&node.id), node.node_type, model, &client, prompt.as_deref(), input, &tools, &tool_context, schema.as_ref(), step, outgoing_targets, self.capture_for(node)).await
+        }
+        .instrument(span.clone())
+        .await;
+        record_node_span(&span, result.as_ref().map(|output| &output.metrics));
+        if let Some(log) = log {
+            log.finished(started.elapsed(), result.as_ref().map(|output| &output.metrics));
+        }
+        result
     }
 
-    /// Gets the input text for a node from its incoming edges.
-    async fn get_input_for_node(&self, node_id: &str, context: &Arc<RwLock<HashMap<String, String>>>) -> String {
-        let ctx = context.read().await;
+    /// Returns the node's structured log fields, or `None` with pretty logs on.
+    fn node_log(&self, node: &NodeConfig, model: &ModelConfig, step: usize) -> Option<NodeLog> {
+        (!self.pretty_logs).then(|| NodeLog {
+            pipeline_id: self.config.id.clone(),
+            node_id: self.node_path(&node.id),
+            node_type: node.node_type,
+            step,
+            model: model.model.clone(),
+        })
+    }
 
-        for edge in &self.config.edges {
-            if !edge.to.as_vec().contains(&node_id) {
-                continue;
+    /// Waits for GPU memory for the model, if the engine schedules it.
+    async fn vram_permit(&self, model: &ModelConfig) -> Option<VramPermit> {
+        match &self.vram {
+            Some(scheduler) => scheduler.acquire(model).await,
+            None => None,
+        }
+    }
+
+    /// Creates an LLM client for a model, attaching the engine's cache and cassette.
+    fn llm_client(&self, model: &ModelConfig) -> UnifiedLlmClient {
+        let mut client = UnifiedLlmClient::from_config(model);
+        if let Some(cache) = &self.llm_cache {
+            client = client.with_cache(Arc::clone(cache));
+        }
+        if let Some(cassette) = &self.cassette {
+            client = client.with_cassette(Arc::clone(cassette));
+        }
+        client
+    }
+
+    /// Runs the pipeline referenced by a `Pipeline` node with the node's input.
+    ///
+    /// Nested node metrics and spans are recorded by the shared collector under
+    /// prefixed paths, so the node itself reports no token usage of its own.
+    async fn execute_sub_pipeline(
+        &self,
+        node: &NodeConfig,
+        input: &str,
+        history: &[fissio_core::Message],
+        step: usize,
+    ) -> Result<NodeOutput, AgentError> {
+        let path = self.node_path(&node.id);
+        let config = self.sub_pipeline_config(node)?;
+
+        banner!(self.pretty_logs, "╠──────────────────────────────────────────────────────────────");
+        banner!(self.pretty_logs, "║ [{}] NODE: {} (Pipeline → {})", step, path, config.id);
+
+        let engine = self.nested(&node.id, config);
+        let content = match engine.execute_stream(input, history).await? {
+            EngineOutput::Complete(text) => text,
+            EngineOutput::Stream(stream) => collect_stream(stream).await?,
+            EngineOutput::Empty(reason) => {
+                warn_line!(self.pretty_logs, "Pipeline node '{}' returned nothing: {}", path, reason.message());
+                String::new()
             }
+        };
 
-            let inputs: Vec<String> = edge.from.as_vec()
-                .iter()
-                .filter_map(|id| ctx.get(*id).cloned())
-                .collect();
+        Ok(NodeOutput { content, next_nodes: vec![], metrics: ExecutionMetrics::default() })
+    }
 
-            if !inputs.is_empty() {
-                return inputs.join("\n\n---\n\n");
+    /// Returns the registered pipeline a `Pipeline` node references, refusing
+    /// one that is already running above it.
+    fn sub_pipeline_config(&self, node: &NodeConfig) -> Result<PipelineConfig, AgentError> {
+        let path = self.node_path(&node.id);
+        let pipeline_id = node.pipeline_ref().ok_or_else(|| {
+            AgentError::WorkerFailed(format!("Pipeline node '{}' is missing config.pipeline_id", path))
+        })?;
+
+        if pipeline_id == self.config.id || self.ancestors.iter().any(|a| a == pipeline_id) {
+            return Err(AgentError::WorkerFailed(format!(
+                "Pipeline node '{}' recursively references '{}'", path, pipeline_id
+            )));
+        }
+
+        self.pipelines.get(pipeline_id).cloned().ok_or_else(|| {
+            AgentError::WorkerFailed(format!("Pipeline node '{}' references unknown pipeline '{}'", path, pipeline_id))
+        })
+    }
+
+    /// Runs a `Retriever` node: searches `config.collection` for the node's input
+    /// and outputs the matching chunks as numbered context.
+    ///
+    /// With `config.multi_query`, the node's model first writes reformulations of
+    /// the input to search for as well.
+    async fn execute_retriever(
+        &self,
+        node: &NodeConfig,
+        model: &ModelConfig,
+        input: &str,
+        context: &Arc<RwLock<HashMap<String, String>>>,
+        step: usize,
+    ) -> Result<NodeOutput, AgentError> {
+        let path = self.node_path(&node.id);
+        let retriever = self.retriever.as_ref().ok_or_else(|| {
+            AgentError::WorkerFailed(format!("Retriever node '{}' requires PipelineEngine::with_retriever", path))
+        })?;
+        let config: RetrievalConfig = serde_json::from_value(node.config.clone()).map_err(|e| {
+            AgentError::WorkerFailed(format!("Retriever node '{}' has invalid config: {}", path, e))
+        })?;
+
+        banner!(self.pretty_logs, "╠──────────────────────────────────────────────────────────────");
+        banner!(self.pretty_logs, "║ [{}] NODE: {} (Retriever → {}, {:?})", step, path, config.collection, config.search);
+
+        let mut metrics = ExecutionMetrics::default();
+        let variants = match &config.multi_query {
+            Some(multi_query) => {
+                banner!(self.pretty_logs, "║     Model: {}", model.name);
+                match self.llm_client(model).chat(&multi_query.prompt(), input).await {
+                    Ok(response) => {
+                        metrics.accumulate(&response.metrics);
+                        metrics.iteration_count = 1;
+                        let variants = multi_query.parse(&response.content, input);
+                        banner!(self.pretty_logs, "║     → Searching {} reformulations: {:?}", variants.len(), variants);
+                        variants
+                    }
+                    Err(e) => {
+                        warn_line!(self.pretty_logs, "Query expansion failed, searching the input only: {}", e);
+                        Vec::new()
+                    }
+                }
             }
+            None => Vec::new(),
+        };
+
+        let results = retriever.retrieve_expanded(input, &variants, &config).await.map_err(|e| {
+            AgentError::WorkerFailed(format!("Retriever node '{}' failed: {}", path, e))
+        })?;
+        banner!(self.pretty_logs, "║     ✓ Retrieved {} chunks", results.len());
+
+        if let Ok(sources) = serde_json::to_string(&results) {
+            context.write().await.insert(sources_key(&node.id), sources);
         }
 
-        ctx.get("input").cloned().unwrap_or_default()
+        Ok(NodeOutput { content: format_context(&results), next_nodes: vec![], metrics })
     }
 
-    /// Processes outgoing edges for a node, filtering by router decisions if applicable.
-    async fn process_outgoing_edges(
+    /// Runs a `Grounding` node: asks the model whether each claim in the node's
+    /// input is supported by the output of `config.sources`, then passes, flags,
+    /// or blocks the answer.
+    async fn execute_grounding(
         &self,
-        node_id: &str,
-        router_targets: &[String],
+        node: &NodeConfig,
+        model: &ModelConfig,
+        input: &str,
+        context: &Arc<RwLock<HashMap<String, String>>>,
+        step: usize,
+    ) -> Result<NodeOutput, AgentError> {
+        let path = self.node_path(&node.id);
+        let config: GroundingConfig = serde_json::from_value(node.config.clone()).map_err(|e| {
+            AgentError::WorkerFailed(format!("Grounding node '{}' has invalid config: {}", path, e))
+        })?;
+        let sources = context.read().await.get(&config.sources).cloned().ok_or_else(|| {
+            AgentError::WorkerFailed(format!("Grounding node '{}' has no output from sources node '{}'", path, config.sources))
+        })?;
+
+        banner!(self.pretty_logs, "╠──────────────────────────────────────────────────────────────");
+        banner!(self.pretty_logs, "║ [{}] NODE: {} (Grounding ← {})", step, path, config.sources);
+        banner!(self.pretty_logs, "║     Model: {}", model.name);
+
+        let client = self.llm_client(model);
+        let system_prompt = grounding::system_prompt(self.node_prompt(node).as_deref());
+        let response = client.chat(&system_prompt, &grounding::user_message(&sources, input)).await?;
+        let verdict = grounding::parse_verdict(&response.content);
+
+        if !verdict.parsed {
+            warn_line!(self.pretty_logs, "Could not read grounding verdict, treating answer as unsupported");
+        }
+        banner!(self.pretty_logs, "║     ✓ Groundedness {:.2} (threshold {:.2}), {} unsupported claims", verdict.score, config.threshold, verdict.unsupported.len());
+
+        let mut metrics = ExecutionMetrics::default();
+        metrics.accumulate(&response.metrics);
+        metrics.iteration_count = 1;
+        metrics.groundedness = Some(verdict.score);
+
+        let content = grounding::apply(&config, input, &verdict);
+        Ok(NodeOutput { content, next_nodes: vec![], metrics })
+    }
+
+    /// Records metrics and a span for a node if observability is enabled for it.
+    #[allow(clippy::too_many_arguments)]
+    fn record_node(
+        &self,
+        node: &NodeConfig,
+        model: &ModelConfig,
+        input: &str,
+        output: &str,
+        exec_metrics: &ExecutionMetrics,
+        start_time_ms: i64,
+        end_time_ms: i64,
+    ) {
+        if let Some(recorder) = self.recorder(node, model) {
+            recorder.record(input, output, exec_metrics, start_time_ms, end_time_ms);
+        }
+    }
+
+    /// Returns a recorder for a node, or `None` if observability is off for it.
+    fn recorder(&self, node: &NodeConfig, model: &ModelConfig) -> Option<NodeRecorder> {
+        let collector = self.collector.clone()?;
+        if !node.observe.as_ref().is_some_and(|o| o.enabled) {
+            return None;
+        }
+
+        let expands_queries = node.node_type == NodeType::Retriever && node.config.get("multi_query").is_some();
+        let calls_llm = node.node_type.requires_llm() || node.node_type.is_router() || expands_queries;
+        Some(NodeRecorder {
+            collector,
+            node_path: self.node_path(&node.id),
+            node_type: node.node_type.to_string(),
+            model: calls_llm.then(|| model.model.clone()),
+            pricing: model.pricing.filter(|_| calls_llm),
+            capture: self.capture_for(node),
+        })
+    }
+
+    /// Returns the chunks retrieved by the first Retriever node feeding `node_id`,
+    /// which its `[n]` markers refer to.
+    async fn cited_sources(&self, node_id: &str, context: &Arc<RwLock<HashMap<String, String>>>) -> Vec<SearchResult> {
+        let ctx = context.read().await;
+        self.get_incoming_edges(node_id)
+            .flat_map(|e| e.from.as_vec())
+            .find_map(|from| ctx.get(&sources_key(from)))
+            .and_then(|sources| serde_json::from_str(sources).ok())
+            .unwrap_or_default()
+    }
+
+    /// Gets the input text for a node: the outputs of its parents that have
+    /// finished, in the order their edges are declared, or the user input.
+    ///
+    /// A Router's output is only its routing decision, so the nodes it routes
+    /// to are given the Router's own input instead.
+    async fn get_input_for_node(&self, node_id: &str, context: &Arc<RwLock<HashMap<String, String>>>) -> String {
+        self.input_from(node_id, &*context.read().await)
+    }
+
+    /// [`Self::get_input_for_node`] over an already-locked context.
+    fn input_from(&self, node_id: &str, ctx: &HashMap<String, String>) -> String {
+        let inputs: Vec<String> = self.graph
+            .parents(node_id)
+            .iter()
+            .filter(|parent| ctx.contains_key(parent.as_str()))
+            .map(|parent| match self.get_node(parent) {
+                Some(node) if node.node_type.is_router() => self.input_from(parent, ctx),
+                _ => ctx[parent.as_str()].clone(),
+            })
+            .collect();
+
+        if inputs.is_empty() {
+            return ctx.get("input").cloned().unwrap_or_default();
+        }
+        inputs.join("\n\n---\n\n")
+    }
+
+    /// Runs a Map node's downstream node once per item.
+    ///
+    /// Items come from the Map node's output (a JSON array of strings). Per-item runs
+    /// are bounded by `config.concurrency` and traced as `<node>[<index>]`; outputs are
+    /// collected in item order into a JSON array stored as the downstream node's output.
+    async fn execute_map(
+        &self,
+        map_id: &str,
+        target_id: &str,
         context: &Arc<RwLock<HashMap<String, String>>>,
-        executed: &mut HashSet<String>,
         history: &[fissio_core::Message],
         step: &Arc<RwLock<usize>>,
     ) -> Result<(), AgentError> {
-        let edges_to_process: Vec<_> = self.get_outgoing_edges(node_id)
-            .into_iter()
-            .filter(|edge| {
-                let targets = edge.to.as_vec();
-                let none_executed = !targets.iter().any(|t: &&str| executed.contains(*t));
-                let matches_router = router_targets.is_empty() ||
-                    targets.iter().any(|t: &&str| router_targets.contains(&t.to_string()));
-                none_executed && matches_router
+        let (Some(map_node), Some(node)) = (self.get_node(map_id), self.get_node(target_id)) else { return Ok(()) };
+        let items: Vec<String> = {
+            let ctx = context.read().await;
+            ctx.get(map_id)
+                .and_then(|s| serde_json::from_str(s).ok())
+                .unwrap_or_default()
+        };
+        let concurrency = self.map_concurrency(map_node);
+        let model = self.get_node_model(node)?;
+        let outgoing_targets = self.get_outgoing_targets(target_id);
+
+        banner!(self.pretty_logs, "╠══════════════════════════════════════════════════════════════");
+        banner!(self.pretty_logs, "║ MAP: {} → {} ({} items, concurrency {})", map_id, target_id, items.len(), concurrency);
+
+        // Boxed as `Send` up front; the compiler can't prove it through the closure's borrows
+        let futures: Vec<BoxFuture<'_, Result<String, AgentError>>> = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let step = Arc::clone(step);
+                let item_node = NodeConfig { id: format!("{}[{}]", node.id, i), ..node.clone() };
+                let model = Arc::clone(&model);
+                let outgoing_targets = &outgoing_targets;
+                Box::pin(async move {
+                    let current_step = {
+                        let mut s = step.write().await;
+                        *s += 1;
+                        *s
+                    };
+                    let start_time_ms = now_ms();
+                    let (output, used) = self.run_node(&item_node, &model, item, history, context, current_step, outgoing_targets).await?;
+                    let end_time_ms = now_ms();
+                    self.record_node(&item_node, &used, item, &output.content, &output.metrics, start_time_ms, end_time_ms);
+                    Ok(output.content)
+                }) as BoxFuture<'_, _>
             })
             .collect();
+        let results: Vec<Result<String, AgentError>> = futures::stream::iter(futures).buffered(concurrency).collect().await;
 
-        for next_edge in edges_to_process {
-            self.process_edge(next_edge, context, executed, history, step).await?;
-        }
+        let outputs = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+        let collected = serde_json::to_string(&outputs)?;
+
+        banner!(self.pretty_logs, "║ MAP COMPLETE: {} ({} results)", target_id, outputs.len());
+
+        context.write().await.insert(target_id.to_string(), collected);
         Ok(())
     }
+
+    /// Per-item concurrency of a Map node: its `config.concurrency`, else the
+    /// pipeline's `max_concurrency`, else [`DEFAULT_MAP_CONCURRENCY`].
+    fn map_concurrency(&self, map_node: &NodeConfig) -> usize {
+        map_node.config
+            .get("concurrency")
+            .and_then(|v| v.as_u64())
+            .map(|n| n.max(1) as usize)
+            .or(self.config.max_concurrency)
+            .unwrap_or(DEFAULT_MAP_CONCURRENCY)
+            .max(1)
+    }
 }
 
 /// Maximum number of tool call iterations to prevent infinite loops.
 const MAX_TOOL_ITERATIONS: usize = 10;
 
+/// Maximum number of tool calls from one LLM turn that run at the same time.
+const MAX_PARALLEL_TOOL_CALLS: usize = 4;
+
+/// Characters of a tool call's arguments or result shown in its stream events.
+const TOOL_PREVIEW_CHARS: usize = 200;
+
+/// Per-item concurrency for Map nodes without `config.concurrency` or a pipeline `max_concurrency`.
+const DEFAULT_MAP_CONCURRENCY: usize = 4;
+
+/// Replaced with the current date and time in node prompts.
+const NOW_VARIABLE: &str = "{{now}}";
+
+/// Memories added to Llm and Worker prompts for nodes without `config.memory_recall`.
+const DEFAULT_MEMORY_RECALL: usize = 5;
+
+/// The schema a node's `config.output_schema` requires, named after the node.
+fn output_schema(node: &NodeConfig) -> Option<OutputSchema> {
+    let schema = node.output_schema()?.clone();
+    // Providers accept only `[a-zA-Z0-9_-]` in the name, up to 64 characters
+    let name: String = node.id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .take(64)
+        .collect();
+    let strict = node.config.get("strict_schema").and_then(|v| v.as_bool()).unwrap_or(true);
+    Some(OutputSchema::new(name, schema).with_strict(strict))
+}
+
+/// Context key under which a Retriever node's results are kept, as JSON, for citations.
+fn sources_key(node_id: &str) -> String {
+    format!("{}#sources", node_id)
+}
+
+/// Follows each content chunk with a `Citation` chunk for every `[n]` marker it completes.
+fn with_citations(stream: LlmStream, sources: Vec<SearchResult>) -> LlmStream {
+    if sources.is_empty() {
+        return stream;
+    }
+    let mut scanner = CitationScanner::new(sources);
+    Box::pin(stream.flat_map(move |chunk| {
+        let chunks = match chunk {
+            Ok(StreamChunk::Content(text)) => {
+                let citations = scanner.push(&text);
+                std::iter::once(StreamChunk::Content(text))
+                    .chain(citations.into_iter().map(StreamChunk::Citation))
+                    .map(Ok)
+                    .collect()
+            }
+            other => vec![other],
+        };
+        futures::stream::iter(chunks)
+    }))
+}
+
+/// The first [`TOOL_PREVIEW_CHARS`] characters of `text`, with an ellipsis if cut.
+fn preview(text: &str) -> String {
+    match text.char_indices().nth(TOOL_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
 /// Returns current time in milliseconds since UNIX epoch.
 fn now_ms() -> i64 {
     SystemTime::now()
@@ -548,58 +1867,124 @@ fn now_ms() -> i64 {
         .unwrap_or(0)
 }
 
-/// Executes a single node and returns its output along with execution metrics.
+/// Drains a stream into its concatenated text content.
+async fn collect_stream(mut stream: LlmStream) -> Result<String, AgentError> {
+    let mut content = String::new();
+    while let Some(chunk) = stream.next().await {
+        if let StreamChunk::Content(text) = chunk? {
+            content.push_str(&text);
+        }
+    }
+    Ok(content)
+}
+
+/// Executes a single node and returns its output, including its execution metrics.
 /// If the node has tools configured, runs an agentic loop until the LLM produces final output.
 /// For Router nodes, executes an LLM call to determine routing and returns the target in next_nodes.
+#[allow(clippy::too_many_arguments)]
 async fn execute_node(
     node_id: &str,
     node_type: NodeType,
     model: &ModelConfig,
+    client: &UnifiedLlmClient,
     prompt: Option<&str>,
     input: &str,
     tools: &[String],
-    tool_registry: &ToolRegistry,
+    tool_context: &ToolContext,
+    output_schema: Option<&OutputSchema>,
     step: usize,
     outgoing_targets: &[String],
-) -> Result<(NodeOutput, ExecutionMetrics), AgentError> {
-    info!("╠──────────────────────────────────────────────────────────────");
-    info!("║ [{}] NODE: {} ({:?})", step, node_id, node_type);
-    info!("║     Model: {}", model.name);
-    if !tools.is_empty() {
-        info!("║     Tools: {:?}", tools);
+    capture: CaptureMode,
+) -> Result<NodeOutput, AgentError> {
+    let pretty = tool_context.pretty_logs;
+    if pretty {
+        info!("╠──────────────────────────────────────────────────────────────");
+        info!("║ [{}] NODE: {} ({:?})", step, node_id, node_type);
+        info!("║     Model: {}", model.name);
+        if !tools.is_empty() {
+            info!("║     Tools: {:?}", tools);
+        }
+        debug!("║     Input: {}", capture.apply(input));
     }
-    debug!("║     Input: {}...", input.chars().take(100).collect::<String>());
 
-    let start = std::time::Instant::now();
-    info!("║     → {}", node_type.action_label());
+    let start = Instant::now();
+    banner!(pretty, "║     → {}", node_type.action_label());
 
     // Router node: execute LLM to classify and determine routing target
     if node_type.is_router() {
-        let (content, next_nodes, metrics) = execute_router(model, prompt, input, outgoing_targets).await?;
-        info!("║     ✓ Completed in {:?}, routed to: {:?}", start.elapsed(), next_nodes);
-        return Ok((NodeOutput { content, next_nodes }, metrics));
+        let (content, next_nodes, metrics) = execute_router(client, prompt, input, outgoing_targets, pretty).await?;
+        if pretty {
+            info!("║     ✓ Completed in {:?}, routed to: {:?}", start.elapsed(), next_nodes);
+            debug!("║     Output: {}", capture.apply(&content));
+        }
+        return Ok(NodeOutput { content, next_nodes, metrics });
     }
 
     let (content, metrics) = if node_type.requires_llm() {
-        execute_node_with_tools(model, prompt, input, tools, tool_registry).await?
+        match output_schema {
+            Some(schema) => execute_structured(client, prompt, input, tools, tool_context, schema).await?,
+            None => execute_node_with_tools(client, prompt, input, tools, tool_context, None).await?,
+        }
+    } else if node_type == NodeType::Map {
+        let items = split_items(input);
+        banner!(pretty, "║     Items: {}", items.len());
+        let content = serde_json::to_string(&items)?;
+        (content, ExecutionMetrics::default())
     } else {
         (input.to_string(), ExecutionMetrics::default())
     };
 
-    info!("║     ✓ Completed in {:?}", start.elapsed());
+    if pretty {
+        info!("║     ✓ Completed in {:?}", start.elapsed());
+        debug!("║     Output: {}", capture.apply(&content));
+    }
 
-    Ok((NodeOutput { content, next_nodes: vec![] }, metrics))
+    Ok(NodeOutput { content, next_nodes: vec![], metrics })
+}
+
+/// Splits Map node input into items.
+///
+/// Accepts a JSON array (optionally inside a markdown code fence); non-string
+/// elements are kept as their JSON text. Anything else is split into non-empty lines.
+fn split_items(input: &str) -> Vec<String> {
+    let trimmed = input.trim();
+    if let Ok(serde_json::Value::Array(values)) = serde_json::from_str(unfence(trimmed)) {
+        return values
+            .into_iter()
+            .map(|v| match v {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect();
+    }
+
+    trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Strips a markdown code fence (` ```json ` or ` ``` `) from around LLM output.
+fn unfence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|s| s.strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(trimmed)
 }
 
 /// Executes a Router node: LLM classifies input and returns the target node(s) with metrics.
 async fn execute_router(
-    model: &ModelConfig,
+    client: &UnifiedLlmClient,
     prompt: Option<&str>,
     input: &str,
     outgoing_targets: &[String],
+    pretty: bool,
 ) -> Result<(String, Vec<String>, ExecutionMetrics), AgentError> {
-    let client = UnifiedLlmClient::new(&model.model, model.api_base.as_deref());
-
     // Build routing prompt
     let targets_list = outgoing_targets.join(", ");
     let routing_prompt = format!(
@@ -613,7 +1998,11 @@ async fn execute_router(
     let response = client.chat(&routing_prompt, input).await?;
     let decision = response.content.trim().to_lowercase();
 
-    info!("║     Router decision: '{}'", decision);
+    if pretty {
+        info!("║     Router decision: '{}'", decision);
+    } else {
+        info!(decision = %decision, "Router decision");
+    }
 
     // Match decision to available targets (case-insensitive, exact match only)
     let matched = outgoing_targets
@@ -625,7 +2014,7 @@ async fn execute_router(
     let next_nodes = match matched {
         Some(target) => vec![target],
         None => {
-            warn!("║     ⚠ No exact match for '{}' in {:?}, defaulting to first", decision, outgoing_targets);
+            warn_line!(pretty, "No exact match for '{}' in {:?}, defaulting to first", decision, outgoing_targets);
             outgoing_targets.first().map(|t| vec![t.clone()]).unwrap_or_default()
         }
     };
@@ -642,26 +2031,29 @@ async fn execute_router(
 /// If no tools are configured, performs a simple chat completion.
 /// With tools, runs an iterative loop:
 /// 1. Send message + tool schemas to LLM
-/// 2. If LLM returns tool calls, execute them via the registry
+/// 2. If LLM returns tool calls, execute them concurrently via the registry
 /// 3. Send tool results back to LLM
 /// 4. Repeat until LLM returns final content (max 10 iterations)
 ///
-/// Returns the content and accumulated execution metrics.
+/// Returns the content and accumulated execution metrics. With `progress`, a
+/// [`StreamChunk::ToolCallStarted`] is sent as each tool starts and a
+/// [`StreamChunk::ToolCallFinished`] when it returns.
 async fn execute_node_with_tools(
-    model: &ModelConfig,
+    client: &UnifiedLlmClient,
     prompt: Option<&str>,
     input: &str,
     tools: &[String],
-    tool_registry: &ToolRegistry,
+    tool_context: &ToolContext,
+    progress: Option<&ProgressSender>,
 ) -> Result<(String, ExecutionMetrics), AgentError> {
-    let client = UnifiedLlmClient::new(&model.model, model.api_base.as_deref());
     let system_prompt = prompt.unwrap_or("");
+    let pretty = tool_context.pretty_logs;
     let mut metrics = ExecutionMetrics::default();
 
     // No tools configured - simple chat
     if tools.is_empty() {
         let response = client.chat(system_prompt, input).await?;
-        info!("║     ← Response: {} chars", response.content.len());
+        banner!(pretty, "║     ← Response: {} chars", response.content.len());
         metrics.accumulate(&response.metrics);
         metrics.iteration_count = 1;
         return Ok((response.content, metrics));
@@ -671,7 +2063,7 @@ async fn execute_node_with_tools(
     let tool_schemas: Vec<ToolSchema> = tools
         .iter()
         .filter_map(|name| {
-            tool_registry.get(name).map(|t| ToolSchema {
+            tool_context.registry.get(name).map(|t| ToolSchema {
                 name: t.name().to_string(),
                 description: t.description().to_string(),
                 parameters: t.parameters(),
@@ -680,14 +2072,14 @@ async fn execute_node_with_tools(
         .collect();
 
     if tool_schemas.is_empty() {
-        warn!("║     ⚠ No valid tools found in registry for: {:?}", tools);
+        warn_line!(pretty, "No valid tools found in registry for: {:?}", tools);
         let response = client.chat(system_prompt, input).await?;
         metrics.accumulate(&response.metrics);
         metrics.iteration_count = 1;
         return Ok((response.content, metrics));
     }
 
-    info!("║     → Starting agentic loop with {} tools", tool_schemas.len());
+    banner!(pretty, "║     → Starting agentic loop with {} tools", tool_schemas.len());
 
     // Agentic loop
     let mut messages = vec![UnifiedLlmClient::user_message(input)?];
@@ -696,7 +2088,7 @@ async fn execute_node_with_tools(
     loop {
         metrics.iteration_count += 1;
         if metrics.iteration_count > MAX_TOOL_ITERATIONS as u32 {
-            warn!("║     ⚠ Max tool iterations ({}) reached", MAX_TOOL_ITERATIONS);
+            warn_line!(pretty, "Max tool iterations ({}) reached", MAX_TOOL_ITERATIONS);
             return Err(AgentError::LlmError(format!(
                 "Max tool iterations ({}) exceeded",
                 MAX_TOOL_ITERATIONS
@@ -714,7 +2106,8 @@ async fn execute_node_with_tools(
 
         match response {
             ChatResponse::Content(llm_response) => {
-                info!(
+                banner!(
+                    pretty,
                     "║     ← Final response: {} chars (after {} iterations)",
                     llm_response.content.len(),
                     metrics.iteration_count
@@ -724,23 +2117,70 @@ async fn execute_node_with_tools(
             }
             ChatResponse::ToolCalls { calls, metrics: llm_metrics } => {
                 metrics.accumulate(&llm_metrics);
-                info!(
+                banner!(
+                    pretty,
                     "║     ← Tool calls: {:?}",
                     calls.iter().map(|c| &c.name).collect::<Vec<_>>()
                 );
 
-                for call in &calls {
-                    let tool = tool_registry.get(&call.name).ok_or_else(|| {
-                        AgentError::LlmError(format!("Tool not found: {}", call.name))
-                    })?;
-
-                    info!("║       → Executing tool: {}", call.name);
-                    let result = tool.execute(call.arguments.clone()).await.map_err(|e| {
-                        AgentError::LlmError(format!("Tool execution failed: {}", e))
-                    })?;
+                // Independent calls run concurrently; join_all keeps results in call order
+                let semaphore = Semaphore::new(MAX_PARALLEL_TOOL_CALLS);
+                let results = join_all(calls.iter().map(|call| {
+                    let semaphore = &semaphore;
+                    async move {
+                        let start_time = now_ms();
+                        if let Some(invalid) = tool_context.invalid_arguments(call) {
+                            return Ok((invalid, Vec::new(), start_time, now_ms()));
+                        }
+                        // Checked before taking a permit, so a call awaiting approval doesn't block others
+                        if let Some(refusal) = tool_context.refusal_for(call).await {
+                            return Ok((refusal, Vec::new(), start_time, now_ms()));
+                        }
+                        let _permit = semaphore.acquire().await.ok();
+
+                        banner!(pretty, "║       → Executing tool: {}", call.name);
+                        if let Some(tx) = progress {
+                            let _ = tx.send(Ok(StreamChunk::ToolCallStarted {
+                                call_id: call.id.clone(),
+                                name: call.name.clone(),
+                                args_preview: preview(&call.arguments.to_string()),
+                            }));
+                        }
+                        let (result, usage) = tool_context.execute(call, client).await?;
+
+                        let end_time = now_ms();
+                        if let Some(tx) = progress {
+                            let _ = tx.send(Ok(StreamChunk::ToolCallFinished {
+                                call_id: call.id.clone(),
+                                name: call.name.clone(),
+                                duration_ms: (end_time - start_time).max(0) as u64,
+                                result_preview: preview(&result),
+                            }));
+                        }
+                        if pretty {
+                            info!("║       ← Tool result ({}): {} chars", call.name, result.len());
+                        } else {
+                            info!(tool = %call.name, duration_ms = (end_time - start_time).max(0), result_chars = result.len(), "Tool call finished");
+                        }
+                        Ok::<_, AgentError>((result, usage, start_time, end_time))
+                    }
+                }))
+                .await;
 
-                    info!("║       ← Tool result: {} chars", result.len());
+                for (call, result) in calls.iter().zip(results) {
+                    let (result, usage, start_time, end_time) = result?;
                     metrics.tool_call_count += 1;
+                    // Summaries made by post-processing count toward the node's usage
+                    usage.iter().for_each(|m| metrics.accumulate(m));
+                    metrics.tool_calls.push(ToolCallRecord {
+                        call_id: call.id.clone(),
+                        span_id: String::new(),
+                        tool_name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                        result: result.clone(),
+                        elapsed_ms: (end_time - start_time).max(0) as u64,
+                        start_time,
+                    });
 
                     // Add tool result to messages
                     messages.push(UnifiedLlmClient::tool_result_message(&call.id, &result)?);
@@ -752,3 +2192,407 @@ async fn execute_node_with_tools(
         }
     }
 }
+
+/// Executes an LLM node whose output must match `schema`.
+///
+/// Without tools the provider's structured output is used; with tools the
+/// loop's final answer is checked instead. The output is the compact JSON.
+async fn execute_structured(
+    client: &UnifiedLlmClient,
+    prompt: Option<&str>,
+    input: &str,
+    tools: &[String],
+    tool_context: &ToolContext,
+    schema: &OutputSchema,
+) -> Result<(String, ExecutionMetrics), AgentError> {
+    let (content, metrics) = if tools.is_empty() {
+        let response = client.chat_structured(prompt.unwrap_or(""), input, schema).await?;
+        let mut metrics = ExecutionMetrics::default();
+        metrics.accumulate(&response.metrics);
+        metrics.iteration_count = 1;
+        (response.content, metrics)
+    } else {
+        let prompt = with_schema_instructions(prompt.unwrap_or(""), schema);
+        execute_node_with_tools(client, Some(&prompt), input, tools, tool_context, None).await?
+    };
+    let value: serde_json::Value = parse_structured(&content, schema)?;
+    banner!(tool_context.pretty_logs, "║     ✓ Output matches schema {}", schema.name);
+    Ok((value.to_string(), metrics))
+}
+
+type ProgressSender = mpsc::UnboundedSender<Result<StreamChunk, AgentError>>;
+
+/// A node's tools and the policies applied to its tool calls.
+struct ToolContext {
+    registry: Arc<ToolRegistry>,
+    execution: ToolExecutionPolicy,
+    post_process: PostProcessSteps,
+    access: ToolPolicy,
+    approver: Option<Arc<dyn ToolApprover>>,
+    /// Where calls with side effects are recorded, and the run's key in it.
+    ledger: Option<(Arc<dyn SideEffectLedger>, String)>,
+    /// Hosts `fetch_url` may reach, in local-only mode.
+    allowed_hosts: Option<HostAllowlist>,
+    node_path: String,
+    pretty_logs: bool,
+}
+
+impl ToolContext {
+    /// Returns a refusal for the LLM if the access policy denies the call or
+    /// approval is required and not given; waits for the approver if needed.
+    async fn refusal_for(&self, call: &ToolCall) -> Option<String> {
+        let reason = match (self.host_refusal(call), self.decide(&call.name)) {
+            (Some(reason), _) => reason,
+            (None, ToolDecision::Allow) => return None,
+            (None, ToolDecision::Deny) => "this tool is not allowed here".to_string(),
+            (None, ToolDecision::RequireApproval) => match &self.approver {
+                None => "this tool requires approval and no approver is available".to_string(),
+                Some(approver) => {
+                    banner!(self.pretty_logs, "║       ⏸ Awaiting approval: {}", call.name);
+                    let request = ApprovalRequest {
+                        node_id: self.node_path.clone(),
+                        tool: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                    };
+                    match approver.approve(&request).await {
+                        ApprovalResponse::Approved => return None,
+                        ApprovalResponse::Denied(reason) => reason,
+                    }
+                }
+            },
+        };
+        warn_line!(self.pretty_logs, "Tool call refused: {} ({})", call.name, reason);
+        Some(tool_policy::refusal(&call.name, &reason))
+    }
+
+    /// Returns an error for the LLM if the call's arguments don't match the
+    /// tool's parameter schema. Unknown tools are left to [`execute`](Self::execute).
+    fn invalid_arguments(&self, call: &ToolCall) -> Option<String> {
+        let tool = self.registry.get(&call.name)?;
+        let violations = validation::check_arguments(&call.name, &tool.parameters(), &call.arguments).err()?;
+        warn_line!(self.pretty_logs, "Invalid arguments for {}: {} violation(s)", call.name, violations.len());
+        Some(validation::invalid_arguments(&call.name, &violations))
+    }
+
+    /// Checks a tool against the access policy; tools whose metadata asks for
+    /// approval need it even where the policy allows them.
+    fn decide(&self, tool: &str) -> ToolDecision {
+        match self.access.decide(tool) {
+            ToolDecision::Allow if self.registry.metadata(tool).is_some_and(|m| m.requires_approval) => {
+                ToolDecision::RequireApproval
+            }
+            decision => decision,
+        }
+    }
+
+    /// Refuses a `fetch_url` or `transcribe_audio` download from a host outside the local-only allowlist.
+    fn host_refusal(&self, call: &ToolCall) -> Option<String> {
+        let hosts = self.allowed_hosts.as_ref().filter(|_| matches!(call.name.as_str(), "fetch_url" | "transcribe_audio"))?;
+        let url = match call.arguments.get("url").and_then(|v| v.as_str()) {
+            Some(url) => url,
+            // transcribe_audio may read a file under the sandbox root instead
+            None if call.name == "transcribe_audio" => return None,
+            None => "",
+        };
+        hosts.check(url).err().map(|e| e.to_string())
+    }
+
+    /// Runs the call, post-processes a successful result, then applies the
+    /// execution policy. Returns the result and the usage of any summaries.
+    ///
+    /// A call with side effects that the ledger has already recorded returns
+    /// the recorded result without running, and one that another execution
+    /// has claimed and not finished doesn't run at all.
+    #[tracing::instrument(name = "tool", skip_all, fields(tool.name = %call.name, tool.replayed = Empty, otel.status_code = Empty))]
+    async fn execute(&self, call: &ToolCall, client: &UnifiedLlmClient) -> Result<(String, Vec<LlmMetrics>), AgentError> {
+        let tool = self
+            .registry
+            .get(&call.name)
+            .ok_or_else(|| AgentError::LlmError(format!("Tool not found: {}", call.name)))?;
+        let claimed = match &self.ledger {
+            Some((ledger, run_key)) if self.registry.metadata(&call.name).is_some_and(|m| m.side_effects) => {
+                let key = side_effects::ledger_key(run_key, &self.node_path, call);
+                match ledger.claim(&key).await? {
+                    Claim::Acquired => Some((ledger, key)),
+                    Claim::Recorded(recorded) => {
+                        banner!(self.pretty_logs, "║       ↺ {} already ran in this run, reusing its result", call.name);
+                        Span::current().record("tool.replayed", true);
+                        return Ok((recorded, Vec::new()));
+                    }
+                    Claim::Pending => {
+                        banner!(self.pretty_logs, "║       ↺ {} already started in this run, not repeating it", call.name);
+                        Span::current().record("tool.replayed", true);
+                        let note = format!(
+                            "{} was already called with these arguments in this run and has no result yet; it was not run again.",
+                            call.name
+                        );
+                        return Ok((note, Vec::new()));
+                    }
+                }
+            }
+            _ => None,
+        };
+        let result = self.execution.run(tool.as_ref(), call.arguments.clone()).await;
+        // A call that failed may not have taken effect, so it may run again
+        if let (Some((ledger, key)), Err(_)) = (&claimed, &result) {
+            ledger.release(key).await?;
+        }
+        let (result, usage) = match (result, self.post_process.get(&call.name)) {
+            (Ok(output), Some(steps)) => {
+                let (output, usage) = post_process::apply(steps, call, output, client, self.pretty_logs).await;
+                (Ok(output), usage)
+            }
+            (result, _) => (result, Vec::new()),
+        };
+        let output = self.execution.finish(result).map_err(|e| {
+            Span::current().record("otel.status_code", "ERROR");
+            AgentError::LlmError(format!("Tool execution failed: {}", e))
+        })?;
+        if let Some((ledger, key)) = claimed {
+            ledger.record(&key, &output).await?;
+        }
+        Ok((output, usage))
+    }
+}
+
+/// Streaming variant of [`execute_node_with_tools`] for a terminal Worker node.
+///
+/// Runs the agentic loop in a background task. The stream yields tool call
+/// events as each tool starts and finishes, then the final content and the loop's cumulative
+/// usage. `on_finish` receives the content and metrics, or the error, when
+/// the loop ends.
+fn stream_node_with_tools(
+    client: UnifiedLlmClient,
+    prompt: Option<String>,
+    input: String,
+    tools: Vec<String>,
+    tool_context: ToolContext,
+    on_finish: impl FnOnce(Result<(&str, &ExecutionMetrics), &AgentError>) + Send + 'static,
+) -> LlmStream {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let result = execute_node_with_tools(&client, prompt.as_deref(), &input, &tools, &tool_context, Some(&tx)).await;
+        record_node_span(&Span::current(), result.as_ref().map(|(_, metrics)| metrics));
+        on_finish(result.as_ref().map(|(content, metrics)| (content.as_str(), metrics)));
+        match result {
+            Ok((content, metrics)) => {
+                banner!(tool_context.pretty_logs, "║     ✓ Streamed in {:?}", start.elapsed());
+                let _ = tx.send(Ok(StreamChunk::Content(content)));
+                let _ = tx.send(Ok(StreamChunk::Usage {
+                    input_tokens: metrics.input_tokens,
+                    output_tokens: metrics.output_tokens,
+                }));
+            }
+            Err(e) => {
+                if tool_context.pretty_logs {
+                    warn!("║     ✗ Streaming node failed: {}", e);
+                }
+                let _ = tx.send(Err(e));
+            }
+        }
+    }
+    .instrument(Span::current()));
+
+    Box::pin(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use async_trait::async_trait;
+    use fissio_llm::MockProvider;
+    use fissio_tools::{Tool, ToolError};
+    use serde_json::json;
+
+    /// Returns once the mock has been asked to combine answers, or reports a timeout.
+    struct WaitForCombine(Arc<MockProvider>);
+
+    #[async_trait]
+    impl Tool for WaitForCombine {
+        fn name(&self) -> &str {
+            "wait_for_combine"
+        }
+
+        fn description(&self) -> &str {
+            "Waits until the answers are being combined"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            json!({ "type": "object", "properties": {} })
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<String, ToolError> {
+            for _ in 0..200 {
+                if self.0.requests().iter().any(|r| r.system_prompt.contains("Combine")) {
+                    return Ok("combine started".to_string());
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            Ok("timed out".to_string())
+        }
+    }
+
+    /// Counts its calls, a side effect; `n` must be a positive integer.
+    struct Counter(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Tool for Counter {
+        fn name(&self) -> &str {
+            "count"
+        }
+
+        fn description(&self) -> &str {
+            "Counts to n"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            json!({ "type": "object", "properties": { "n": { "type": "integer", "minimum": 1 } }, "required": ["n"] })
+        }
+
+        fn metadata(&self) -> fissio_tools::ToolMetadata {
+            fissio_tools::ToolMetadata::new().with_side_effects(true)
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<String, ToolError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok("counted".to_string())
+        }
+    }
+
+    async fn run(engine: &PipelineEngine, input: &str) -> String {
+        match engine.execute_stream(input, &[]).await.unwrap() {
+            EngineOutput::Complete(text) => text,
+            EngineOutput::Stream(stream) => collect_stream(stream).await.unwrap(),
+            EngineOutput::Empty(reason) => panic!("empty output: {}", reason.message()),
+        }
+    }
+
+    /// A worker that calls `count` with `arguments`, then answers, counting into `calls`.
+    fn counter_engine(scheme: &str, arguments: serde_json::Value, calls: &Arc<AtomicUsize>) -> (PipelineEngine, Arc<MockProvider>) {
+        let mock = MockProvider::new().tool_call("Count", "count", arguments).reply("Count", "done").install(scheme);
+        let model = MockProvider::model(scheme, "count-model");
+        let config = PipelineConfig::from_value(json!({
+            "id": "counter",
+            "name": "Counter",
+            "nodes": [{ "id": "worker", "type": "worker", "prompt": "Count as asked.", "tools": ["count"] }],
+            "edges": [
+                { "from": "input", "to": "worker" },
+                { "from": "worker", "to": "output" },
+            ],
+        }))
+        .unwrap();
+        let mut tools = ToolRegistry::new();
+        tools.register(Counter(Arc::clone(calls)));
+        (PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), tools), mock)
+    }
+
+    /// Runs the counting worker once; returns what the tool ran and the mock saw.
+    async fn call_counter(scheme: &str, arguments: serde_json::Value) -> (usize, Vec<fissio_llm::MockRequest>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (engine, mock) = counter_engine(scheme, arguments, &calls);
+        assert_eq!(run(&engine, "count please").await, "done");
+        (calls.load(Ordering::SeqCst), mock.requests())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tool_arguments_are_returned_without_executing() {
+        let (calls, requests) = call_counter("invalid-args-mock", json!({ "n": "three" })).await;
+        assert_eq!(calls, 0);
+        // The worker got the violations back as the tool's result
+        assert!(requests.iter().any(|r| r.input.contains("invalid_arguments") && r.input.contains("/n")));
+    }
+
+    #[tokio::test]
+    async fn test_valid_tool_arguments_execute() {
+        let (calls, requests) = call_counter("valid-args-mock", json!({ "n": 3 })).await;
+        assert_eq!(calls, 1);
+        assert!(requests.iter().any(|r| r.input.contains("counted")));
+    }
+
+    #[tokio::test]
+    async fn test_side_effects_run_once_per_run_key() {
+        let ledger: Arc<dyn SideEffectLedger> = Arc::new(InMemoryLedger::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        for run_key in ["run-1", "run-1", "run-2"] {
+            let (engine, _) = counter_engine("side-effects-mock", json!({ "n": 1 }), &calls);
+            let engine = engine.with_side_effect_ledger(Arc::clone(&ledger), run_key);
+            assert_eq!(run(&engine, "count please").await, "done");
+        }
+        // The repeated run reused the first one's result
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_router_targets_get_the_routers_input() {
+        let mock = MockProvider::new()
+            .reply("Refund", "refund issued")
+            .reply("routing classifier", "refunds")
+            .install("router-input-mock");
+        let model = MockProvider::model("router-input-mock", "router-model");
+        let config = PipelineConfig::from_value(json!({
+            "id": "routing",
+            "name": "Routing",
+            "nodes": [
+                { "id": "router", "type": "router", "prompt": "Route refunds and questions." },
+                { "id": "refunds", "type": "llm", "prompt": "Refund the order." },
+                { "id": "questions", "type": "llm", "prompt": "Answer the question." },
+            ],
+            "edges": [
+                { "from": "input", "to": "router" },
+                { "from": "router", "to": ["refunds", "questions"], "edge_type": "conditional" },
+                { "from": ["refunds", "questions"], "to": "output" },
+            ],
+        }))
+        .unwrap();
+        let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), ToolRegistry::new());
+
+        assert_eq!(run(&engine, "please refund order 42").await, "refund issued");
+
+        let requests = mock.requests();
+        let refund = requests.iter().find(|r| r.system_prompt.contains("Refund")).unwrap();
+        assert_eq!(refund.input, "please refund order 42");
+    }
+
+    #[tokio::test]
+    async fn test_quorum_node_starts_before_slowest_parent_finishes() {
+        let mock = MockProvider::new()
+            .reply("Combine", "combined")
+            .tool_call("You wait", "wait_for_combine", json!({}))
+            .reply("You wait", "slow answer")
+            .install("quorum-mock");
+        let model = MockProvider::model("quorum-mock", "quorum-model");
+        let config = PipelineConfig::from_value(json!({
+            "id": "quorum",
+            "name": "Quorum",
+            "nodes": [
+                { "id": "a", "type": "llm", "prompt": "Answer briefly." },
+                { "id": "b", "type": "llm", "prompt": "Answer in detail." },
+                { "id": "slow", "type": "worker", "prompt": "You wait before answering.", "tools": ["wait_for_combine"] },
+                { "id": "agg", "type": "llm", "prompt": "Combine the answers.", "config": { "quorum": 2 } },
+            ],
+            "edges": [
+                { "from": "input", "to": ["a", "b", "slow"], "edge_type": "parallel" },
+                { "from": ["a", "b", "slow"], "to": "agg" },
+                { "from": "agg", "to": "output" },
+            ],
+        }))
+        .unwrap();
+        let mut tools = ToolRegistry::new();
+        tools.register(WaitForCombine(Arc::clone(&mock)));
+        let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), tools);
+
+        assert_eq!(run(&engine, "question").await, "combined");
+
+        let requests = mock.requests();
+        // The slow worker's tool saw the aggregator start while it was still running
+        assert!(requests.iter().any(|r| r.system_prompt.contains("You wait") && r.input.contains("combine started")));
+        let combine = requests.iter().find(|r| r.system_prompt.contains("Combine")).unwrap();
+        assert!(!combine.input.contains("slow answer"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Engine progress logs.
+//!
+//! With pretty logs on (the default), the engine draws a box-framed banner
+//! per run for reading in a terminal. With them off, it emits one event as
+//! each pipeline and node starts and finishes, with the details as tracing
+//! fields, for JSON log shipping.
+
+use std::time::Duration;
+
+use fissio_config::NodeType;
+use fissio_core::AgentError;
+use tracing::{info, warn};
+
+use crate::ExecutionMetrics;
+
+/// Logs a banner line when pretty logs are on.
+macro_rules! banner {
+    ($pretty:expr, $($arg:tt)+) => {
+        if $pretty {
+            tracing::info!($($arg)+);
+        }
+    };
+}
+
+/// Logs a warning: inside the banner when pretty logs are on, as a plain
+/// message otherwise.
+macro_rules! warn_line {
+    ($pretty:expr, $($arg:tt)+) => {
+        if $pretty {
+            tracing::warn!("║     ⚠ {}", format_args!($($arg)+));
+        } else {
+            tracing::warn!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use {banner, warn_line};
+
+/// Identifies a node in its structured log events.
+pub(crate) struct NodeLog {
+    pub pipeline_id: String,
+    pub node_id: String,
+    pub node_type: NodeType,
+    pub step: usize,
+    pub model: String,
+}
+
+impl NodeLog {
+    pub fn started(&self) {
+        info!(
+            pipeline_id = %self.pipeline_id,
+            node_id = %self.node_id,
+            node_type = %self.node_type,
+            step = self.step,
+            model = %self.model,
+            "Node started"
+        );
+    }
+
+    pub fn finished(&self, elapsed: Duration, result: Result<&ExecutionMetrics, &AgentError>) {
+        let duration_ms = elapsed.as_millis() as u64;
+        match result {
+            Ok(metrics) => info!(
+                pipeline_id = %self.pipeline_id,
+                node_id = %self.node_id,
+                node_type = %self.node_type,
+                step = self.step,
+                model = %self.model,
+                duration_ms,
+                input_tokens = metrics.input_tokens,
+                output_tokens = metrics.output_tokens,
+                tool_calls = metrics.tool_call_count,
+                "Node finished"
+            ),
+            Err(e) => warn!(
+                pipeline_id = %self.pipeline_id,
+                node_id = %self.node_id,
+                node_type = %self.node_type,
+                step = self.step,
+                model = %self.model,
+                duration_ms,
+                error = %e,
+                "Node failed"
+            ),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Pipelines as tools, so a Worker node can delegate to a whole sub-agent
+//! through function calling.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use fissio_config::PresetRegistry;
+use fissio_core::ModelConfig;
+use fissio_tools::{CostHint, Tool, ToolArgs, ToolError, ToolMetadata};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{collect_stream, EngineOutput, PipelineEngine};
+
+#[derive(Deserialize, ToolArgs)]
+struct PipelineArgs {
+    /// The task or question for the agent, with any context it needs; it sees nothing else of the conversation
+    input: String,
+}
+
+/// A tool that runs a pipeline with the call's `input` and returns its output.
+///
+/// Named after the pipeline's ID and described by its description unless
+/// [`with_name`](Self::with_name) and [`with_description`](Self::with_description)
+/// say otherwise. The pipeline runs on its own engine, with that engine's
+/// models, tools, and collector, and starts with no history.
+pub struct PipelineTool {
+    name: String,
+    description: String,
+    engine: Arc<PipelineEngine>,
+}
+
+impl PipelineTool {
+    pub fn new(engine: PipelineEngine) -> Self {
+        let config = &engine.config;
+        let description = if config.description.is_empty() {
+            format!("Runs the {} agent", config.name)
+        } else {
+            config.description.clone()
+        };
+        Self { name: config.id.clone(), description, engine: Arc::new(engine) }
+    }
+
+    /// Wraps the preset `id`, run with the default tools; `None` if there is no such preset.
+    pub fn from_preset(presets: &PresetRegistry, id: &str, models: Vec<ModelConfig>, default_model: ModelConfig) -> Option<Self> {
+        let config = presets.get(id)?.clone();
+        Some(Self::new(PipelineEngine::new(config, models, default_model, HashMap::new())))
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for PipelineTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        PipelineArgs::parameters()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("agents").with_cost(CostHint::High)
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let args = PipelineArgs::parse(args)?;
+        let failed = |e: fissio_core::AgentError| ToolError::ExecutionFailed(e.to_string());
+        match self.engine.execute_stream(&args.input, &[]).await.map_err(failed)? {
+            EngineOutput::Complete(text) => Ok(text),
+            EngineOutput::Stream(stream) => collect_stream(stream).await.map_err(failed),
+            EngineOutput::Empty(reason) => Err(ToolError::ExecutionFailed(reason.message().to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fissio_config::PipelineConfig;
+    use fissio_llm::MockProvider;
+    use fissio_tools::ToolRegistry;
+    use serde_json::json;
+
+    fn pipeline(value: Value) -> PipelineConfig {
+        PipelineConfig::from_value(value).unwrap()
+    }
+
+    fn researcher(model: &ModelConfig) -> PipelineTool {
+        let config = pipeline(json!({
+            "id": "researcher",
+            "name": "Researcher",
+            "description": "Researches a topic",
+            "nodes": [{ "id": "research", "type": "llm", "prompt": "Research the topic." }],
+            "edges": [
+                { "from": "input", "to": "research" },
+                { "from": "research", "to": "output" },
+            ],
+        }));
+        PipelineTool::new(PipelineEngine::new(config, vec![model.clone()], model.clone(), HashMap::new()))
+    }
+
+    #[tokio::test]
+    async fn test_worker_delegates_to_registered_pipeline() {
+        let mock = MockProvider::new()
+            .reply("Research the topic", "findings about rust")
+            .tool_call("Delegate", "researcher", json!({ "input": "rust" }))
+            .reply("Delegate", "summary")
+            .install("pipeline-tool-mock");
+        let model = MockProvider::model("pipeline-tool-mock", "delegate-model");
+
+        let tool = researcher(&model);
+        assert_eq!(tool.name(), "researcher");
+        assert_eq!(tool.description(), "Researches a topic");
+        assert_eq!(tool.parameters()["required"], json!(["input"]));
+
+        let mut tools = ToolRegistry::new();
+        tools.register(tool);
+        let config = pipeline(json!({
+            "id": "delegator",
+            "name": "Delegator",
+            "nodes": [{ "id": "lead", "type": "worker", "prompt": "Delegate the research.", "tools": ["researcher"] }],
+            "edges": [
+                { "from": "input", "to": "lead" },
+                { "from": "lead", "to": "output" },
+            ],
+        }));
+        let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), tools);
+
+        let output = match engine.execute_stream("look into rust", &[]).await.unwrap() {
+            EngineOutput::Complete(text) => text,
+            EngineOutput::Stream(stream) => collect_stream(stream).await.unwrap(),
+            EngineOutput::Empty(reason) => panic!("empty output: {}", reason.message()),
+        };
+        assert_eq!(output, "summary");
+
+        let requests = mock.requests();
+        // The sub-pipeline ran on the call's input, and its output came back as the tool result
+        assert!(requests.iter().any(|r| r.system_prompt.contains("Research the topic") && r.input == "rust"));
+        assert!(requests.iter().any(|r| r.system_prompt.contains("Delegate") && r.input.contains("findings about rust")));
+    }
+
+    #[tokio::test]
+    async fn test_execute_can_be_spawned() {
+        MockProvider::new().reply("Research the topic", "spawned findings").install("pipeline-tool-spawn-mock");
+        let tool = researcher(&MockProvider::model("pipeline-tool-spawn-mock", "spawn-model"));
+
+        let result = tokio::spawn(async move { tool.execute(json!({ "input": "rust" })).await }).await.unwrap();
+        assert_eq!(result.unwrap(), "spawned findings");
+    }
+
+    #[tokio::test]
+    async fn test_missing_input_is_invalid() {
+        let tool = researcher(&MockProvider::model("unused", "unused")).with_name("research");
+        assert_eq!(tool.name(), "research");
+        assert!(matches!(tool.execute(json!({})).await, Err(ToolError::InvalidArguments(_))));
+    }
+}
@@ -0,0 +1,205 @@
+//! Dry runs: what a pipeline would execute, without calling any LLM.
+
+use std::collections::HashSet;
+
+use fissio_config::NodeType;
+use serde::Serialize;
+
+use crate::graph::Frontier;
+use crate::{PipelineEngine, ToolDecision};
+
+/// What [`PipelineEngine::plan`] expects a run to do.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionPlan {
+    pub pipeline_id: String,
+    /// Stages in the order they would start.
+    pub stages: Vec<PlanStage>,
+    /// Every node that would run, in stage order.
+    pub nodes: Vec<PlannedNode>,
+    /// Nodes that can't run: no path from `input` leads to them.
+    pub unreachable: Vec<String>,
+    /// Config validation errors and other problems the run would log or fail on.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl ExecutionPlan {
+    /// True when no node has a problem that would fail the run.
+    pub fn is_runnable(&self) -> bool {
+        self.nodes.iter().all(|n| n.error.is_none())
+    }
+}
+
+/// Nodes that start together.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanStage {
+    pub kind: StageKind,
+    pub nodes: Vec<String>,
+    /// Cap on nodes (or Map items) running at once, when there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    Sequential,
+    Parallel,
+    /// Runs once per item of the Map node before it.
+    Map,
+    /// The Worker feeding `output`, streamed to the caller after everything else.
+    Streaming,
+}
+
+/// A node as it would run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedNode {
+    pub id: String,
+    pub node_type: NodeType,
+    /// ID of the model the node would use, after overrides and residency policies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// True when a node override picked the model.
+    pub overridden: bool,
+    /// Models the node would retry with, in order, if its model failed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fallback_models: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<PlannedTool>,
+    /// True when the node only runs if a router chooses its branch.
+    pub conditional: bool,
+    /// Parents that must finish before the node starts, when fewer than all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quorum: Option<usize>,
+    /// Plan of the pipeline a `Pipeline` node runs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_pipeline: Option<Box<ExecutionPlan>>,
+    /// Why the node would fail, e.g. no model satisfies residency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A tool the node lists and whether it could call it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedTool {
+    pub name: String,
+    pub status: ToolStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    Available,
+    /// Calls wait for an approver and are refused without one.
+    RequiresApproval,
+    /// The tool access policy refuses calls.
+    Denied,
+    /// No tool by this name is registered.
+    Unknown,
+}
+
+impl PipelineEngine {
+    /// Schedules the pipeline the way [`execute_stream`](Self::execute_stream)
+    /// would, without calling any LLM or tool.
+    ///
+    /// Router choices can't be known ahead, so every branch is planned and
+    /// the nodes behind a router are marked `conditional`.
+    pub fn plan(&self) -> ExecutionPlan {
+        let terminal = self.streaming_terminal();
+        let mut frontier = Frontier::new(&self.graph);
+        let mut stages: Vec<PlanStage> = Vec::new();
+        let mut conditional: HashSet<String> = HashSet::new();
+        let mut mark_conditional = |id: &str| {
+            let behind_router = self.graph.parents(id).iter().any(|p| {
+                conditional.contains(p) || self.get_node(p).is_some_and(|n| n.node_type.is_router())
+            });
+            if behind_router {
+                conditional.insert(id.to_string());
+            }
+        };
+
+        if self.config.has_output_edge() {
+            loop {
+                let batch = frontier.next_batch(terminal.map(|n| n.id.as_str()));
+                if batch.is_empty() {
+                    break;
+                }
+                batch.iter().for_each(|id| mark_conditional(id));
+                stages.extend(self.stages(&batch, &frontier));
+                for id in batch {
+                    frontier.complete(&id, Vec::new());
+                }
+            }
+            if let Some(node) = terminal.filter(|n| frontier.reached(&n.id)) {
+                mark_conditional(&node.id);
+                stages.push(PlanStage { kind: StageKind::Streaming, nodes: vec![node.id.clone()], max_concurrency: None });
+            }
+        }
+
+        let mut warnings = Vec::new();
+        if let Err(e) = self.config.validate() {
+            warnings.push(e.to_string());
+        }
+        if !self.config.has_output_edge() {
+            warnings.push("no edge leads to output, so the run is skipped".to_string());
+        }
+
+        let planned: HashSet<&String> = stages.iter().flat_map(|s| &s.nodes).collect();
+        let nodes = stages
+            .iter()
+            .flat_map(|s| &s.nodes)
+            .map(|id| self.plan_node(id, conditional.contains(id)))
+            .collect();
+        let unreachable = self.graph.nodes().iter().filter(|id| !planned.contains(id)).cloned().collect();
+
+        ExecutionPlan { pipeline_id: self.config.id.clone(), stages, nodes, unreachable, warnings }
+    }
+
+    fn plan_node(&self, id: &str, conditional: bool) -> PlannedNode {
+        let node = self.get_node(id).expect("planned nodes exist");
+        let (model, fallback_models, mut error) = match self.get_node_model(node) {
+            Ok(model) => {
+                let fallbacks = self.get_fallback_models(node, &model).iter().map(|m| m.id.clone()).collect();
+                (Some(model.id.clone()), fallbacks, None)
+            }
+            Err(e) => (None, Vec::new(), Some(e.to_string())),
+        };
+
+        let context = self.tool_context(node);
+        let tools = context
+            .registry
+            .resolve(&node.tools)
+            .into_iter()
+            .map(|name| {
+                let status = match context.decide(&name) {
+                    _ if !context.registry.has(&name) => ToolStatus::Unknown,
+                    ToolDecision::Allow => ToolStatus::Available,
+                    ToolDecision::RequireApproval => ToolStatus::RequiresApproval,
+                    ToolDecision::Deny => ToolStatus::Denied,
+                };
+                PlannedTool { name, status }
+            })
+            .collect();
+
+        let mut sub_pipeline = None;
+        if node.node_type == NodeType::Pipeline {
+            match self.sub_pipeline_config(node) {
+                Ok(config) => sub_pipeline = Some(Box::new(self.nested(&node.id, config).plan())),
+                Err(e) => error = error.or(Some(e.to_string())),
+            }
+        }
+
+        PlannedNode {
+            id: id.to_string(),
+            node_type: node.node_type,
+            model,
+            overridden: self.node_overrides.contains_key(id),
+            fallback_models,
+            tools,
+            conditional,
+            quorum: self.graph.quorum(id),
+            sub_pipeline,
+            error,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Post-processing of tool output before it goes back to a Worker's LLM.
+
+use std::collections::HashMap;
+
+use fissio_llm::{LlmMetrics, ToolCall, UnifiedLlmClient};
+use fissio_tools::{extract_html, select_json, truncate_middle, PostProcessor};
+use serde_json::Value;
+
+use crate::logging::{banner, warn_line};
+
+/// Most characters of a tool result sent to the summarizer.
+const SUMMARIZE_INPUT_CHARS: usize = 48_000;
+
+const SUMMARIZE_PROMPT: &str = "You condense tool results for an assistant working on a task. Summarize the \
+    result below, keeping the facts, figures, names, dates, and URLs the assistant may need and dropping \
+    navigation, boilerplate, and repetition. Reply with the summary only.";
+
+/// A node's `config.tool_post_process`: the steps applied to each tool's output.
+pub(crate) type PostProcessSteps = HashMap<String, Vec<PostProcessor>>;
+
+/// Parses `tool_post_process`, where each tool maps to one step or a list of steps.
+pub(crate) fn parse_steps(value: &Value) -> Result<PostProcessSteps, String> {
+    let Value::Object(map) = value else {
+        return Err("expected an object of tool names to steps".to_string());
+    };
+    map.iter()
+        .map(|(tool, steps)| {
+            let steps: Vec<PostProcessor> = match steps {
+                Value::String(step) => vec![step.parse()?],
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().ok_or_else(|| format!("steps for {} must be strings", tool))?.parse())
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(format!("steps for {} must be a string or a list of strings", tool)),
+            };
+            Ok((tool.clone(), steps))
+        })
+        .collect()
+}
+
+/// Applies `steps` to a call's output, returning the result and the usage of
+/// any summaries made.
+///
+/// A step that fails is skipped with a warning, leaving the output it was given.
+pub(crate) async fn apply(
+    steps: &[PostProcessor],
+    call: &ToolCall,
+    output: String,
+    client: &UnifiedLlmClient,
+    pretty_logs: bool,
+) -> (String, Vec<LlmMetrics>) {
+    let mut output = output;
+    let mut usage = Vec::new();
+    for step in steps {
+        let result = match step {
+            PostProcessor::Summarize => summarize(call, &output, client).await.map(|(summary, metrics)| {
+                usage.push(metrics);
+                summary
+            }),
+            PostProcessor::Extract(selector) => extract_html(&output, selector).map_err(|e| e.to_string()),
+            PostProcessor::JsonPath(path) => select_json(&output, path).map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(processed) => {
+                banner!(pretty_logs, "║       ⚙ {} on {}: {} → {} chars", step, call.name, output.len(), processed.len());
+                output = processed;
+            }
+            Err(e) => warn_line!(pretty_logs, "Skipping {} on {}: {}", step, call.name, e),
+        }
+    }
+    (output, usage)
+}
+
+async fn summarize(call: &ToolCall, output: &str, client: &UnifiedLlmClient) -> Result<(String, LlmMetrics), String> {
+    let input = format!(
+        "Tool: {}\nArguments: {}\n\nResult:\n{}",
+        call.name,
+        call.arguments,
+        truncate_middle(output, SUMMARIZE_INPUT_CHARS)
+    );
+    let response = client.chat(SUMMARIZE_PROMPT, &input).await.map_err(|e| e.to_string())?;
+    if response.content.trim().is_empty() {
+        return Err("the summary was empty".to_string());
+    }
+    Ok((response.content, response.metrics))
+}
@@ -0,0 +1,222 @@
+//! At-most-once execution of tools with side effects.
+//!
+//! Tools whose [`ToolMetadata::side_effects`](fissio_tools::ToolMetadata::side_effects)
+//! is set (sending email, creating issues) can't be safely repeated. With a
+//! [`SideEffectLedger`] and a run key from
+//! [`PipelineEngine::with_side_effect_ledger`](crate::PipelineEngine::with_side_effect_ledger),
+//! each such call's result is recorded, and when the same run executes again,
+//! for example after a crash or a retried request, a call with the same node,
+//! tool, and arguments returns the recorded result instead of running.
+//!
+//! Calls are claimed before they run, so of two identical calls, in one turn
+//! or in two executions at once, only one runs. A call claimed without a
+//! result, because it is still running or its process stopped, isn't run again.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fissio_core::AgentError;
+use fissio_llm::ToolCall;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// What claiming a call in the ledger found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Claim {
+    /// The call hasn't run; the claimant runs it, then records or releases it.
+    Acquired,
+    /// The call already ran and returned this result.
+    Recorded(String),
+    /// The call was claimed and has no result yet: it is still running, or
+    /// the process running it stopped. Whether it took effect is unknown.
+    Pending,
+}
+
+/// Results of tool calls with side effects, by run, node, tool, and arguments.
+#[async_trait]
+pub trait SideEffectLedger: Send + Sync {
+    /// Claims a call before it runs. Of concurrent claims on one key, exactly
+    /// one is [`Claim::Acquired`].
+    async fn claim(&self, key: &str) -> Result<Claim, AgentError>;
+
+    /// Records the result of a claimed call that ran.
+    async fn record(&self, key: &str, result: &str) -> Result<(), AgentError>;
+
+    /// Drops the claim of a call that failed, so it may run again.
+    async fn release(&self, key: &str) -> Result<(), AgentError>;
+}
+
+/// Ledger kept in process memory, for runs retried within one process.
+#[derive(Default)]
+pub struct InMemoryLedger {
+    entries: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl InMemoryLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SideEffectLedger for InMemoryLedger {
+    async fn claim(&self, key: &str) -> Result<Claim, AgentError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(match entries.get(key) {
+            Some(Some(result)) => Claim::Recorded(result.clone()),
+            Some(None) => Claim::Pending,
+            None => {
+                entries.insert(key.to_string(), None);
+                Claim::Acquired
+            }
+        })
+    }
+
+    async fn record(&self, key: &str, result: &str) -> Result<(), AgentError> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), Some(result.to_string()));
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), AgentError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.get(key).is_some_and(Option::is_none) {
+            entries.remove(key);
+        }
+        Ok(())
+    }
+}
+
+/// Ledger kept in SQLite, so a run executed again after a restart doesn't
+/// repeat the calls it already made.
+pub struct SqliteLedger {
+    conn: Mutex<Connection>,
+    ttl: Option<Duration>,
+}
+
+impl SqliteLedger {
+    /// Opens (or creates) a ledger database at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, AgentError> {
+        let conn = Connection::open(path).map_err(ledger_err)?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS side_effects (
+                key TEXT PRIMARY KEY,
+                result TEXT,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_side_effects_created ON side_effects(created_at);
+            "#,
+        )
+        .map_err(ledger_err)?;
+        Ok(Self { conn: Mutex::new(conn), ttl: None })
+    }
+
+    /// Forgets calls `ttl` after they were claimed, so a run executed again
+    /// after that repeats them. Without a TTL, entries are kept for good.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+#[async_trait]
+impl SideEffectLedger for SqliteLedger {
+    async fn claim(&self, key: &str) -> Result<Claim, AgentError> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(ttl) = self.ttl {
+            conn.execute(
+                "DELETE FROM side_effects WHERE created_at <= strftime('%s', 'now') - ?1",
+                params![ttl.as_secs() as i64],
+            )
+            .map_err(ledger_err)?;
+        }
+        let inserted = conn
+            .execute("INSERT OR IGNORE INTO side_effects (key) VALUES (?1)", params![key])
+            .map_err(ledger_err)?;
+        if inserted == 1 {
+            return Ok(Claim::Acquired);
+        }
+        let result: Option<Option<String>> = conn
+            .query_row("SELECT result FROM side_effects WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(ledger_err)?;
+        Ok(result.flatten().map_or(Claim::Pending, Claim::Recorded))
+    }
+
+    async fn record(&self, key: &str, result: &str) -> Result<(), AgentError> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute("UPDATE side_effects SET result = ?2 WHERE key = ?1", params![key, result])
+            .map_err(ledger_err)?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), AgentError> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute("DELETE FROM side_effects WHERE key = ?1 AND result IS NULL", params![key])
+            .map_err(ledger_err)?;
+        Ok(())
+    }
+}
+
+fn ledger_err(e: rusqlite::Error) -> AgentError {
+    AgentError::ExternalApi(format!("side effect ledger error: {}", e))
+}
+
+/// Ledger key of a call made by the node at `node_path` in the run `run_key`.
+///
+/// Arguments are keyed by their JSON, whose object keys serialize sorted, so
+/// the same arguments in another order match.
+pub(crate) fn ledger_key(run_key: &str, node_path: &str, call: &ToolCall) -> String {
+    format!("{}/{}/{}/{}", run_key, node_path, call.name, call.arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("fissio-ledger-{}-{}.db", name, std::process::id()))
+    }
+
+    async fn claim_record_release(ledger: &dyn SideEffectLedger) {
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Acquired);
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Pending);
+        ledger.release("run/node/send_email/{}").await.unwrap();
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Acquired);
+        ledger.record("run/node/send_email/{}", "sent").await.unwrap();
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Recorded("sent".into()));
+        // A recorded call stays recorded
+        ledger.release("run/node/send_email/{}").await.unwrap();
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Recorded("sent".into()));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_ledger_claims() {
+        claim_record_release(&InMemoryLedger::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_ledger_claims_and_survives_reopening() {
+        let path = temp_db("reopen");
+        claim_record_release(&SqliteLedger::new(&path).unwrap()).await;
+
+        let reopened = SqliteLedger::new(&path).unwrap();
+        assert_eq!(reopened.claim("run/node/send_email/{}").await.unwrap(), Claim::Recorded("sent".into()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_ledger_forgets_expired_calls() {
+        let path = temp_db("ttl");
+        let ledger = SqliteLedger::new(&path).unwrap().with_ttl(Duration::ZERO);
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Acquired);
+        ledger.record("run/node/send_email/{}", "sent").await.unwrap();
+        assert_eq!(ledger.claim("run/node/send_email/{}").await.unwrap(), Claim::Acquired);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
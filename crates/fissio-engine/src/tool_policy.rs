@@ -0,0 +1,119 @@
+//! Which tools a node may call, and which calls need a human's approval.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Allow, deny, and approval rules for tool calls.
+///
+/// Set for every node with [`PipelineEngine::with_tool_policy`](crate::PipelineEngine::with_tool_policy)
+/// and per node under `config.tool_access`; a call must pass both.
+///
+/// ```json
+/// { "tool_access": { "deny": ["send_email"], "require_approval": ["run_command"] } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// When set, only these tools may be called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    /// Tools that may never be called.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Tools whose calls wait for a [`ToolApprover`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub require_approval: Vec<String>,
+}
+
+/// Outcome of checking a tool call against a [`ToolPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolDecision {
+    Allow,
+    Deny,
+    RequireApproval,
+}
+
+impl ToolPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts calls to the given tools.
+    pub fn allow_only<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn deny(mut self, tool: impl Into<String>) -> Self {
+        self.deny.push(tool.into());
+        self
+    }
+
+    pub fn require_approval(mut self, tool: impl Into<String>) -> Self {
+        self.require_approval.push(tool.into());
+        self
+    }
+
+    pub fn decide(&self, tool: &str) -> ToolDecision {
+        let listed = |names: &[String]| names.iter().any(|n| n == tool);
+        if listed(&self.deny) || self.allow.as_deref().is_some_and(|allow| !listed(allow)) {
+            ToolDecision::Deny
+        } else if listed(&self.require_approval) {
+            ToolDecision::RequireApproval
+        } else {
+            ToolDecision::Allow
+        }
+    }
+
+    /// Combines two policies so a call must satisfy both.
+    pub fn and(&self, other: &ToolPolicy) -> ToolPolicy {
+        let allow = match (&self.allow, &other.allow) {
+            (Some(a), Some(b)) => Some(a.iter().filter(|t| b.contains(t)).cloned().collect()),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        let union = |a: &[String], b: &[String]| {
+            let mut all = a.to_vec();
+            all.extend(b.iter().filter(|t| !a.contains(t)).cloned());
+            all
+        };
+        ToolPolicy {
+            allow,
+            deny: union(&self.deny, &other.deny),
+            require_approval: union(&self.require_approval, &other.require_approval),
+        }
+    }
+}
+
+/// A tool call waiting for approval.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    /// Path of the node making the call, e.g. `research/searcher`.
+    pub node_id: String,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+}
+
+/// A human's answer to an [`ApprovalRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalResponse {
+    Approved,
+    /// Denied, with a reason passed on to the LLM.
+    Denied(String),
+}
+
+/// Asks a human whether a tool call may run.
+///
+/// The agentic loop waits for the answer, so implementations should time out
+/// and deny rather than wait forever.
+#[async_trait]
+pub trait ToolApprover: Send + Sync {
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalResponse;
+}
+
+/// Tool result returned to the LLM in place of a refused call.
+pub(crate) fn refusal(tool: &str, reason: &str) -> String {
+    serde_json::json!({ "error": "tool_call_denied", "tool": tool, "reason": reason }).to_string()
+}
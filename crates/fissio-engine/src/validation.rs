@@ -0,0 +1,91 @@
+//! Checking tool call arguments against the tool's parameter schema.
+//!
+//! A call whose arguments don't match isn't executed; the LLM gets each
+//! violation back as the tool's result and can correct the call.
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+
+/// One way the arguments break the schema.
+#[derive(Debug, Serialize)]
+pub(crate) struct Violation {
+    /// JSON pointer to the offending value; empty for the arguments as a whole.
+    path: String,
+    message: String,
+}
+
+/// Checks `arguments` against `schema`, returning every violation.
+///
+/// A schema the validator can't compile is logged and treated as accepting
+/// anything, so a tool with a sloppy schema still runs.
+pub(crate) fn check_arguments(tool: &str, schema: &Value, arguments: &Value) -> Result<(), Vec<Violation>> {
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(validator) => validator,
+        Err(e) => {
+            debug!("Not validating {} arguments, its schema is invalid: {}", tool, e);
+            return Ok(());
+        }
+    };
+    let violations: Vec<Violation> = validator
+        .iter_errors(arguments)
+        .map(|e| Violation { path: e.instance_path.to_string(), message: e.to_string() })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Tool result returned to the LLM in place of a call with invalid arguments.
+pub(crate) fn invalid_arguments(tool: &str, violations: &[Violation]) -> String {
+    serde_json::json!({
+        "error": "invalid_arguments",
+        "tool": tool,
+        "violations": violations,
+        "hint": "Fix the arguments to match the tool's parameter schema and call it again.",
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "days": { "type": "integer", "minimum": 1 },
+            },
+            "required": ["city"],
+        })
+    }
+
+    #[test]
+    fn test_valid_arguments_pass() {
+        assert!(check_arguments("weather", &schema(), &json!({ "city": "Lisbon" })).is_ok());
+        assert!(check_arguments("weather", &schema(), &json!({ "city": "Lisbon", "days": 3 })).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_arguments_report_every_violation() {
+        let violations = check_arguments("weather", &schema(), &json!({ "days": 0 })).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|v| v.path.is_empty() && v.message.contains("city")));
+        assert!(violations.iter().any(|v| v.path == "/days"));
+
+        let result: Value = serde_json::from_str(&invalid_arguments("weather", &violations)).unwrap();
+        assert_eq!(result["error"], "invalid_arguments");
+        assert_eq!(result["violations"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_uncompilable_schema_accepts_anything() {
+        let schema = json!({ "type": "no-such-type" });
+        assert!(check_arguments("sloppy", &schema, &json!({ "anything": true })).is_ok());
+    }
+}
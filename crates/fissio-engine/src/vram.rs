@@ -0,0 +1,144 @@
+//! Keeps parallel nodes from loading more local models than fit in GPU memory.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use fissio_core::{AgentError, ModelConfig, Provider};
+use fissio_llm::{loaded_models, model_sizes};
+use tokio::sync::Notify;
+use tracing::info;
+
+/// Schedules calls to Ollama models within a GPU memory budget.
+///
+/// Ollama evicts a loaded model to make room for another, so parallel nodes
+/// using local models that don't fit together load and unload them over and
+/// over. Before calling an Ollama model, a node waits until the model fits
+/// beside the models other nodes are using; idle models are assumed to be
+/// evicted to make room.
+///
+/// Sizes come from Ollama. A model not yet loaded is counted at its weights'
+/// size, which leaves out the context cache, so set the budget below the GPU's
+/// memory. A model larger than the whole budget runs alone.
+pub struct VramScheduler {
+    budget_bytes: u64,
+    sizes: HashMap<String, u64>,
+    resident: Mutex<HashMap<String, Resident>>,
+    released: Notify,
+}
+
+/// A model counted as loaded.
+struct Resident {
+    bytes: u64,
+    /// Nodes currently calling it.
+    users: usize,
+}
+
+impl VramScheduler {
+    /// Creates a scheduler for models of the given sizes in bytes, keyed by Ollama model name.
+    pub fn new(budget_bytes: u64, sizes: HashMap<String, u64>) -> Self {
+        Self {
+            budget_bytes,
+            sizes,
+            resident: Mutex::new(HashMap::new()),
+            released: Notify::new(),
+        }
+    }
+
+    /// Reads model sizes and the currently loaded models from Ollama.
+    pub async fn from_ollama(ollama_host: &str, budget_bytes: u64) -> Result<Self, AgentError> {
+        let mut sizes = model_sizes(ollama_host).await?;
+        let loaded = loaded_models(ollama_host).await?;
+
+        let mut resident = HashMap::new();
+        for model in loaded {
+            // A loaded model's size includes its context cache, so prefer it
+            sizes.insert(model.name.clone(), model.size);
+            resident.insert(model.name, Resident { bytes: model.size, users: 0 });
+        }
+        info!("VRAM scheduler: {} models, {} loaded, budget {} MiB", sizes.len(), resident.len(), budget_bytes >> 20);
+
+        Ok(Self {
+            budget_bytes,
+            sizes,
+            resident: Mutex::new(resident),
+            released: Notify::new(),
+        })
+    }
+
+    pub fn budget_bytes(&self) -> u64 {
+        self.budget_bytes
+    }
+
+    /// Waits until `model` fits in the budget and holds its place until the
+    /// permit is dropped. Returns `None` at once for models that aren't
+    /// scheduled: non-Ollama models and models of unknown size.
+    pub async fn acquire(self: &Arc<Self>, model: &ModelConfig) -> Option<VramPermit> {
+        if model.provider != Some(Provider::Ollama) {
+            return None;
+        }
+        let size = *self.sizes.get(&model.model)?;
+
+        let mut waited = false;
+        loop {
+            // Register for wakeups before checking, so a release in between isn't missed
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+
+            if self.try_admit(&model.model, size) {
+                if waited {
+                    info!(model = %model.model, "GPU memory available");
+                }
+                return Some(VramPermit { scheduler: Arc::clone(self), model: model.model.clone() });
+            }
+            if !waited {
+                info!(model = %model.model, "Waiting for GPU memory");
+                waited = true;
+            }
+            released.await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Resident>> {
+        self.resident.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn try_admit(&self, name: &str, size: u64) -> bool {
+        let mut resident = self.lock();
+        if let Some(model) = resident.get_mut(name) {
+            model.users += 1;
+            return true;
+        }
+
+        let busy = resident.values().any(|m| m.users > 0);
+        let in_use: u64 = resident.values().filter(|m| m.users > 0).map(|m| m.bytes).sum();
+        if busy && in_use + size > self.budget_bytes {
+            return false;
+        }
+        let total: u64 = resident.values().map(|m| m.bytes).sum();
+        if total + size > self.budget_bytes {
+            resident.retain(|_, m| m.users > 0);
+        }
+        resident.insert(name.to_string(), Resident { bytes: size, users: 1 });
+        true
+    }
+
+    fn release(&self, name: &str) {
+        if let Some(model) = self.lock().get_mut(name) {
+            model.users = model.users.saturating_sub(1);
+        }
+        self.released.notify_waiters();
+    }
+}
+
+/// A node's claim on GPU memory for its model, released when dropped.
+pub struct VramPermit {
+    scheduler: Arc<VramScheduler>,
+    model: String,
+}
+
+impl Drop for VramPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.model);
+    }
+}
@@ -0,0 +1,238 @@
+//! Running one input through two pipeline variants side by side.
+
+use std::collections::HashMap;
+
+use fissio_config::PipelineConfig;
+use fissio_core::ModelConfig;
+use fissio_engine::PipelineEngine;
+use fissio_llm::UnifiedLlmClient;
+use fissio_tools::ToolRegistry;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::judge;
+use crate::runner::{execute, Execution};
+use crate::EngineSetup;
+
+/// One side of a comparison: a pipeline and the models it runs on.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub label: String,
+    pub config: PipelineConfig,
+    /// Model for nodes that don't pin one; the comparer's default if unset.
+    pub default_model: Option<ModelConfig>,
+    /// Models to run nodes on instead of their configs', keyed by node ID.
+    pub node_overrides: HashMap<String, String>,
+}
+
+impl Variant {
+    pub fn new(label: impl Into<String>, config: PipelineConfig) -> Self {
+        Self { label: label.into(), config, default_model: None, node_overrides: HashMap::new() }
+    }
+
+    pub fn with_default_model(mut self, model: ModelConfig) -> Self {
+        self.default_model = Some(model);
+        self
+    }
+
+    pub fn with_node_overrides(mut self, overrides: HashMap<String, String>) -> Self {
+        self.node_overrides = overrides;
+        self
+    }
+}
+
+/// What one variant produced and what it took.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantResult {
+    pub label: String,
+    pub pipeline_id: String,
+    pub output: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Which variant the judge preferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Winner {
+    A,
+    B,
+    Tie,
+}
+
+/// The judge's pick and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Verdict {
+    pub winner: Winner,
+    pub reason: String,
+}
+
+/// Both variants' results for one input, with the judge's verdict if one was asked for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub input: String,
+    pub a: VariantResult,
+    pub b: VariantResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    /// Why no verdict was reached when a judge was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_error: Option<String>,
+}
+
+/// Runs the same input through two pipeline variants concurrently and
+/// optionally asks an LLM judge which answer is better.
+///
+/// ```rust,ignore
+/// let a = Variant::new("gpt", config.clone()).with_node_overrides(gpt_models);
+/// let b = Variant::new("claude", config).with_node_overrides(claude_models);
+/// let report = Comparer::new(models, default_model)
+///     .with_judge(judge_model)
+///     .compare(&a, &b, "Summarize our refund policy")
+///     .await;
+/// println!("{:?}: {}", report.verdict.unwrap().winner, report.a.elapsed_ms);
+/// ```
+pub struct Comparer {
+    models: Vec<ModelConfig>,
+    default_model: ModelConfig,
+    tools: ToolRegistry,
+    pipelines: Vec<PipelineConfig>,
+    judge: Option<ModelConfig>,
+    criteria: Option<String>,
+    setup: Option<EngineSetup>,
+}
+
+impl Comparer {
+    /// Creates a comparer running variants with `models`, using `default_model`
+    /// for variants that don't set one.
+    pub fn new(models: Vec<ModelConfig>, default_model: ModelConfig) -> Self {
+        Self {
+            models,
+            default_model,
+            tools: ToolRegistry::with_defaults(),
+            pipelines: Vec::new(),
+            judge: None,
+            criteria: None,
+            setup: None,
+        }
+    }
+
+    /// Replaces the default tool registry.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Registers pipelines that `Pipeline` nodes may reference by ID.
+    pub fn with_pipelines(mut self, pipelines: Vec<PipelineConfig>) -> Self {
+        self.pipelines = pipelines;
+        self
+    }
+
+    /// Asks `model` to pick the better answer. Without a judge, reports have no verdict.
+    pub fn with_judge(mut self, model: ModelConfig) -> Self {
+        self.judge = Some(model);
+        self
+    }
+
+    /// Tells the judge what makes an answer better, e.g. "cites a source".
+    pub fn with_criteria(mut self, criteria: impl Into<String>) -> Self {
+        self.criteria = Some(criteria.into());
+        self
+    }
+
+    /// Applies `setup` to the engine built for each variant.
+    pub fn with_engine_setup(mut self, setup: EngineSetup) -> Self {
+        self.setup = Some(setup);
+        self
+    }
+
+    /// Runs `input` through both variants and judges the answers.
+    ///
+    /// A variant that fails is reported with its error; the judge is only
+    /// asked when both succeed.
+    pub async fn compare(&self, a: &Variant, b: &Variant, input: &str) -> ComparisonReport {
+        let (run_a, run_b) = futures::join!(self.run(a, input), self.run(b, input));
+        let (a, b) = (result(a, run_a), result(b, run_b));
+
+        let (mut verdict, mut judge_error) = (None, None);
+        if let (Some(model), None, None) = (&self.judge, &a.error, &b.error) {
+            let client = UnifiedLlmClient::from_config(model);
+            match judge::pick(&client, self.criteria.as_deref(), input, &a.output, &b.output).await {
+                Ok((picked, _)) => verdict = Some(picked),
+                Err(e) => {
+                    warn!("Comparison judge failed: {}", e);
+                    judge_error = Some(e);
+                }
+            }
+        }
+
+        ComparisonReport { input: input.to_string(), a, b, verdict, judge_error }
+    }
+
+    async fn run(&self, variant: &Variant, input: &str) -> Execution {
+        let mut engine = PipelineEngine::with_tools(
+            variant.config.clone(),
+            self.models.clone(),
+            variant.default_model.clone().unwrap_or_else(|| self.default_model.clone()),
+            variant.node_overrides.clone(),
+            self.tools.clone(),
+        )
+        .with_pipelines(self.pipelines.clone());
+        if let Some(setup) = &self.setup {
+            engine = setup(engine);
+        }
+        execute(engine, &variant.config.id, input).await
+    }
+}
+
+fn result(variant: &Variant, run: Execution) -> VariantResult {
+    let (output, error) = match run.output {
+        Ok(output) => (output, None),
+        Err(e) => {
+            warn!("Variant {} failed: {}", variant.label, e);
+            (String::new(), Some(e.to_string()))
+        }
+    };
+    VariantResult {
+        label: variant.label.clone(),
+        pipeline_id: variant.config.id.clone(),
+        output,
+        error,
+        elapsed_ms: run.elapsed_ms,
+        input_tokens: run.input_tokens,
+        output_tokens: run.output_tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_serializes_winner_lowercase() {
+        let side = |label: &str| VariantResult {
+            label: label.into(),
+            pipeline_id: "support".into(),
+            output: "ok".into(),
+            error: None,
+            elapsed_ms: 10,
+            input_tokens: 5,
+            output_tokens: 2,
+        };
+        let report = ComparisonReport {
+            input: "hi".into(),
+            a: side("a"),
+            b: side("b"),
+            verdict: Some(Verdict { winner: Winner::Tie, reason: "Same answer".into() }),
+            judge_error: None,
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["verdict"]["winner"], "tie");
+        assert!(json["a"].get("error").is_none());
+        assert!(json.get("judge_error").is_none());
+    }
+}
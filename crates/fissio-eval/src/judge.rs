@@ -0,0 +1,113 @@
+//! Grading outputs against a rubric with an LLM judge.
+
+use fissio_llm::{LlmMetrics, UnifiedLlmClient};
+use serde::Deserialize;
+
+use crate::{JudgeRubric, Verdict, Winner};
+
+const JUDGE_PROMPT: &str = "You grade answers produced by an AI assistant. You are given the user's input, \
+    the assistant's answer, and a rubric describing a good answer. Score how well the answer meets the rubric \
+    from 1 (not at all) to 10 (fully). Judge only against the rubric. Reply with JSON only: \
+    {\"score\": <1-10>, \"reason\": \"<one sentence>\"}";
+
+const COMPARE_PROMPT: &str = "You compare two answers produced by AI assistants for the same input. You are \
+    given the user's input, answer A, answer B, and optionally the criteria to judge by. Decide which answer \
+    is better, or call a tie when neither is clearly better. Ignore which answer comes first and how long \
+    each is. Reply with JSON only: {\"winner\": \"A\" | \"B\" | \"tie\", \"reason\": \"<one sentence>\"}";
+
+/// A judge's grade for one output.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Judgement {
+    pub score: u8,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct RawJudgement {
+    score: f64,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Asks the judge to grade `output`, returning the grade and the call's usage.
+pub(crate) async fn grade(
+    client: &UnifiedLlmClient,
+    rubric: &JudgeRubric,
+    input: &str,
+    output: &str,
+) -> Result<(Judgement, LlmMetrics), String> {
+    let message = format!("Rubric:\n{}\n\nInput:\n{}\n\nAnswer:\n{}", rubric.rubric, input, output);
+    let response = client.chat(JUDGE_PROMPT, &message).await.map_err(|e| e.to_string())?;
+    let judgement = parse(&response.content).ok_or_else(|| format!("unreadable judge reply: {}", response.content.trim()))?;
+    Ok((judgement, response.metrics))
+}
+
+/// Asks the judge which of two outputs is better, returning its verdict and the call's usage.
+pub(crate) async fn pick(
+    client: &UnifiedLlmClient,
+    criteria: Option<&str>,
+    input: &str,
+    output_a: &str,
+    output_b: &str,
+) -> Result<(Verdict, LlmMetrics), String> {
+    let mut message = format!("Input:\n{}\n\nAnswer A:\n{}\n\nAnswer B:\n{}", input, output_a, output_b);
+    if let Some(criteria) = criteria {
+        message = format!("Criteria:\n{}\n\n{}", criteria, message);
+    }
+    let response = client.chat(COMPARE_PROMPT, &message).await.map_err(|e| e.to_string())?;
+    let verdict =
+        parse_verdict(&response.content).ok_or_else(|| format!("unreadable judge reply: {}", response.content.trim()))?;
+    Ok((verdict, response.metrics))
+}
+
+/// Reads the JSON object in a judge's reply, ignoring text or a code fence around it.
+fn parse(reply: &str) -> Option<Judgement> {
+    let raw: RawJudgement = json_object(reply)?;
+    Some(Judgement { score: raw.score.round().clamp(1.0, 10.0) as u8, reason: raw.reason })
+}
+
+fn parse_verdict(reply: &str) -> Option<Verdict> {
+    let raw: RawVerdict = json_object(reply)?;
+    let winner = match raw.winner.trim().to_ascii_lowercase().as_str() {
+        "a" => Winner::A,
+        "b" => Winner::B,
+        "tie" => Winner::Tie,
+        _ => return None,
+    };
+    Some(Verdict { winner, reason: raw.reason })
+}
+
+#[derive(Deserialize)]
+struct RawVerdict {
+    winner: String,
+    #[serde(default)]
+    reason: String,
+}
+
+fn json_object<T: serde::de::DeserializeOwned>(reply: &str) -> Option<T> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reads_fenced_reply() {
+        let reply = "```json\n{\"score\": 8.4, \"reason\": \"Mentions the refund window\"}\n```";
+        assert_eq!(parse(reply), Some(Judgement { score: 8, reason: "Mentions the refund window".into() }));
+        assert_eq!(parse("{\"score\": 14}").unwrap().score, 10);
+        assert_eq!(parse("Looks good to me"), None);
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        let verdict = parse_verdict("{\"winner\": \"b\", \"reason\": \"Cites the policy\"}").unwrap();
+        assert_eq!(verdict.winner, Winner::B);
+        assert_eq!(verdict.reason, "Cites the policy");
+        assert_eq!(parse_verdict("{\"winner\": \"Tie\"}").unwrap().winner, Winner::Tie);
+        assert_eq!(parse_verdict("{\"winner\": \"both\"}"), None);
+    }
+}
@@ -0,0 +1,74 @@
+//! Golden-test evaluation for fissio pipelines.
+//!
+//! - [`EvalSuite`] — Cases as JSON: an input and the assertions its output must pass
+//! - [`Expectations`] — `contains`, `not_contains`, `regex`, `min_length`, and an LLM `judge` rubric
+//! - [`Evaluator`] — Runs a suite against a pipeline and model set
+//! - [`EvalReport`] / [`Scores`] — Per-case results and the run's pass rate, score, latency, and tokens
+//! - [`Comparer`] — Runs one input through two [`Variant`]s side by side, with an optional judge picking a winner
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fissio_eval::{EvalSuite, Evaluator};
+//!
+//! let suite = EvalSuite::from_file("evals/support.json")?;
+//! let report = Evaluator::new(config, models, default_model).run(&suite).await?;
+//! for result in report.results.iter().filter(|r| !r.passed) {
+//!     println!("{}: {:?}", result.id, result.failures);
+//! }
+//! ```
+//!
+//! # Scoring
+//!
+//! A case passes when every assertion does. Its score is the share of its
+//! assertions that passed, so a prompt change that breaks one check of five
+//! shows as 0.8 rather than a plain failure. A `judge` rubric counts as one
+//! assertion: the judge model grades the output from 1 to 10 and it passes
+//! at `min_score` (default 7) or above.
+
+mod compare;
+mod judge;
+mod runner;
+mod suite;
+
+use thiserror::Error;
+
+pub use compare::{Comparer, ComparisonReport, Variant, VariantResult, Verdict, Winner};
+pub use runner::{CaseResult, EngineSetup, EvalReport, Evaluator, Scores};
+pub use suite::{EvalCase, EvalSuite, Expectations, JudgeRubric, DEFAULT_MIN_JUDGE_SCORE};
+
+/// Errors from loading or running a suite.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EvalError {
+    /// Failed to read a suite file.
+    #[error("Failed to read suite '{path}': {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Failed to parse a suite.
+    #[error("Failed to parse suite: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// The suite parsed but can't be run.
+    #[error("Invalid suite: {0}")]
+    InvalidSuite(String),
+
+    /// The run used more tokens than its budget allows.
+    #[error("Token budget exhausted: {used} tokens used after {completed}/{total} cases (budget {budget})")]
+    TokenBudget {
+        used: u64,
+        budget: u64,
+        completed: usize,
+        total: usize,
+    },
+}
+
+impl EvalError {
+    pub(crate) fn io(path: String, source: std::io::Error) -> Self {
+        Self::Io { path, source }
+    }
+}
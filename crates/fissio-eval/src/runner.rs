@@ -0,0 +1,364 @@
+//! Running a suite through a pipeline and scoring the results.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use fissio_config::PipelineConfig;
+use fissio_core::{AgentError, ModelConfig};
+use fissio_engine::{EngineOutput, PipelineEngine};
+use fissio_llm::{Cassette, LlmStream, StreamChunk, UnifiedLlmClient};
+use fissio_monitor::{InMemoryCollector, MetricsCollector};
+use fissio_tools::ToolRegistry;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::judge;
+use crate::{EvalCase, EvalError, EvalSuite};
+
+/// Adjusts the engine built for each case, e.g. to apply a tool policy.
+pub type EngineSetup = Arc<dyn Fn(PipelineEngine) -> PipelineEngine + Send + Sync>;
+
+/// Outcome of running one case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub passed: bool,
+    /// Share of the case's assertions that passed, from 0.0 to 1.0.
+    pub score: f64,
+    pub failures: Vec<String>,
+    pub output: String,
+    /// The judge's score out of 10, for cases with a rubric.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_score: Option<u8>,
+    pub elapsed_ms: u64,
+    /// Tokens the pipeline used; the judge's are not counted.
+    pub tokens: u64,
+}
+
+/// Aggregate scores for a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scores {
+    pub cases: usize,
+    pub pass_rate: f64,
+    /// Mean of the cases' scores.
+    #[serde(default)]
+    pub avg_score: f64,
+    pub avg_latency_ms: f64,
+    pub total_tokens: u64,
+}
+
+impl Scores {
+    /// Aggregates case results.
+    pub fn from_results(results: &[CaseResult]) -> Self {
+        let cases = results.len();
+        let mean = |total: f64| if cases == 0 { 0.0 } else { total / cases as f64 };
+        Self {
+            cases,
+            pass_rate: mean(results.iter().filter(|r| r.passed).count() as f64),
+            avg_score: mean(results.iter().map(|r| r.score).sum()),
+            avg_latency_ms: mean(results.iter().map(|r| r.elapsed_ms as f64).sum()),
+            total_tokens: results.iter().map(|r| r.tokens).sum(),
+        }
+    }
+}
+
+/// Scores and per-case results of a suite run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suite: Option<String>,
+    pub pipeline_id: String,
+    pub scores: Scores,
+    pub results: Vec<CaseResult>,
+}
+
+/// Runs suites against one pipeline and model set.
+///
+/// Each case gets a fresh engine, so cases don't share state, with the case's
+/// `mock_tools` registered over the evaluator's tools.
+///
+/// ```rust,ignore
+/// let report = Evaluator::new(config, models, default_model)
+///     .with_judge(judge_model)
+///     .run(&EvalSuite::from_file("evals/support.json")?)
+///     .await?;
+/// println!("pass rate {:.0}%", report.scores.pass_rate * 100.0);
+/// ```
+pub struct Evaluator {
+    config: PipelineConfig,
+    models: Vec<ModelConfig>,
+    default_model: ModelConfig,
+    node_overrides: HashMap<String, String>,
+    tools: ToolRegistry,
+    pipelines: Vec<PipelineConfig>,
+    cassette: Option<Arc<Cassette>>,
+    judge: Option<ModelConfig>,
+    token_budget: Option<u64>,
+    setup: Option<EngineSetup>,
+}
+
+impl Evaluator {
+    /// Creates an evaluator running `config` with `models`, using `default_model`
+    /// for nodes that don't pin one.
+    pub fn new(config: PipelineConfig, models: Vec<ModelConfig>, default_model: ModelConfig) -> Self {
+        Self {
+            config,
+            models,
+            default_model,
+            node_overrides: HashMap::new(),
+            tools: ToolRegistry::with_defaults(),
+            pipelines: Vec::new(),
+            cassette: None,
+            judge: None,
+            token_budget: None,
+            setup: None,
+        }
+    }
+
+    /// Runs nodes on other models than their configs name, keyed by node ID.
+    pub fn with_node_overrides(mut self, overrides: HashMap<String, String>) -> Self {
+        self.node_overrides = overrides;
+        self
+    }
+
+    /// Replaces the default tool registry.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Registers pipelines that `Pipeline` nodes may reference by ID.
+    pub fn with_pipelines(mut self, pipelines: Vec<PipelineConfig>) -> Self {
+        self.pipelines = pipelines;
+        self
+    }
+
+    /// Records LLM interactions to, or replays them from, `cassette`.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Sets the model that grades `judge` rubrics; defaults to the default model.
+    pub fn with_judge(mut self, model: ModelConfig) -> Self {
+        self.judge = Some(model);
+        self
+    }
+
+    /// Stops the run with [`EvalError::TokenBudget`] once the pipeline has used
+    /// more than `tokens` (input + output).
+    pub fn with_token_budget(mut self, tokens: u64) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Applies `setup` to the engine built for each case.
+    pub fn with_engine_setup(mut self, setup: EngineSetup) -> Self {
+        self.setup = Some(setup);
+        self
+    }
+
+    /// Runs every case of the suite in order and scores the results.
+    pub async fn run(&self, suite: &EvalSuite) -> Result<EvalReport, EvalError> {
+        suite.validate()?;
+        let mut results = Vec::with_capacity(suite.cases.len());
+        let mut total_tokens = 0u64;
+
+        for (idx, case) in suite.cases.iter().enumerate() {
+            let result = self.run_case(case, idx).await;
+            total_tokens += result.tokens;
+            results.push(result);
+
+            if let Some(budget) = self.token_budget {
+                if total_tokens > budget {
+                    return Err(EvalError::TokenBudget {
+                        used: total_tokens,
+                        budget,
+                        completed: idx + 1,
+                        total: suite.cases.len(),
+                    });
+                }
+            }
+        }
+
+        Ok(EvalReport {
+            suite: suite.name.clone(),
+            pipeline_id: self.config.id.clone(),
+            scores: Scores::from_results(&results),
+            results,
+        })
+    }
+
+    /// Executes one case and scores its output; `idx` names cases without an ID.
+    /// Engine errors fail every assertion.
+    pub async fn run_case(&self, case: &EvalCase, idx: usize) -> CaseResult {
+        let id = case.id_or(idx);
+        let mut tools = self.tools.clone();
+        for (name, mock) in &case.mock_tools {
+            tools.mock(name, mock.clone());
+        }
+        let mut engine = PipelineEngine::with_tools(
+            self.config.clone(),
+            self.models.clone(),
+            self.default_model.clone(),
+            self.node_overrides.clone(),
+            tools,
+        )
+        .with_pipelines(self.pipelines.clone());
+        if let Some(cassette) = &self.cassette {
+            engine = engine.with_cassette(Arc::clone(cassette));
+        }
+        if let Some(setup) = &self.setup {
+            engine = setup(engine);
+        }
+
+        let run = execute(engine, &self.config.id, &case.input).await;
+        let (elapsed_ms, tokens) = (run.elapsed_ms, run.total_tokens());
+        let output = match run.output {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("Case {} failed: {}", id, e);
+                return CaseResult {
+                    id,
+                    passed: false,
+                    score: 0.0,
+                    failures: vec![format!("engine error: {}", e)],
+                    output: String::new(),
+                    judge_score: None,
+                    elapsed_ms,
+                    tokens,
+                };
+            }
+        };
+
+        let mut failures = case.expect.check(&output);
+        let mut judge_score = None;
+        if let Some(rubric) = &case.expect.judge {
+            let client = UnifiedLlmClient::from_config(self.judge.as_ref().unwrap_or(&self.default_model));
+            match judge::grade(&client, rubric, &case.input, &output).await {
+                Ok((judgement, _)) => {
+                    judge_score = Some(judgement.score);
+                    if judgement.score < rubric.min_score {
+                        failures.push(format!(
+                            "judge scored {}/10 (needs {}): {}",
+                            judgement.score, rubric.min_score, judgement.reason
+                        ));
+                    }
+                }
+                Err(e) => failures.push(format!("judge failed: {}", e)),
+            }
+        }
+
+        let assertions = case.expect.len();
+        CaseResult {
+            id,
+            passed: failures.is_empty(),
+            score: match assertions {
+                0 => 1.0,
+                n => n.saturating_sub(failures.len()) as f64 / n as f64,
+            },
+            failures,
+            output,
+            judge_score,
+            elapsed_ms,
+            tokens,
+        }
+    }
+}
+
+/// One pipeline run's output, or why it failed, and what it took.
+pub(crate) struct Execution {
+    pub output: Result<String, AgentError>,
+    pub elapsed_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl Execution {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Runs `input` through `engine`, collecting the output and token usage.
+/// An empty run's output is empty, so it's graded like any other answer.
+pub(crate) async fn execute(engine: PipelineEngine, pipeline_id: &str, input: &str) -> Execution {
+    let collector = Arc::new(InMemoryCollector::new(pipeline_id));
+    let engine = engine.with_collector(collector.clone());
+
+    let start = Instant::now();
+    let mut usage = (0, 0);
+    let output = match engine.execute_stream(input, &[]).await {
+        Ok(EngineOutput::Complete(text)) => Ok(text),
+        Ok(EngineOutput::Stream(stream)) => Ok(drain_stream(stream, &mut usage).await),
+        Ok(_) => Ok(String::new()),
+        Err(e) => Err(e),
+    };
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    let recorded = collector.flush();
+    Execution {
+        output,
+        elapsed_ms,
+        input_tokens: recorded.total_input_tokens as u64 + usage.0,
+        output_tokens: recorded.total_output_tokens as u64 + usage.1,
+    }
+}
+
+/// Collects a streaming output into a string, setting `usage` to the
+/// (input, output) tokens it reports.
+async fn drain_stream(mut stream: LlmStream, usage: &mut (u64, u64)) -> String {
+    let mut output = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(StreamChunk::Content(text)) => output.push_str(&text),
+            Ok(StreamChunk::Usage { input_tokens, output_tokens }) => {
+                *usage = (input_tokens as u64, output_tokens as u64);
+            }
+            // Progress and citations aren't part of the response text
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Stream error: {}", e);
+                break;
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(passed: bool, score: f64, elapsed_ms: u64) -> CaseResult {
+        CaseResult {
+            id: "case".into(),
+            passed,
+            score,
+            failures: Vec::new(),
+            output: String::new(),
+            judge_score: None,
+            elapsed_ms,
+            tokens: 100,
+        }
+    }
+
+    #[test]
+    fn test_scores_from_results() {
+        let scores = Scores::from_results(&[result(true, 1.0, 100), result(false, 0.5, 300)]);
+        assert_eq!(scores.cases, 2);
+        assert_eq!(scores.pass_rate, 0.5);
+        assert_eq!(scores.avg_score, 0.75);
+        assert_eq!(scores.avg_latency_ms, 200.0);
+        assert_eq!(scores.total_tokens, 200);
+
+        // Baselines written before `avg_score` existed still load
+        let old: Scores =
+            serde_json::from_str(r#"{"cases": 1, "pass_rate": 1.0, "avg_latency_ms": 10.0, "total_tokens": 5}"#).unwrap();
+        assert_eq!(old.avg_score, 0.0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Test suites: cases and the assertions their outputs must pass.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use fissio_tools::ToolMock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::EvalError;
+
+/// Minimum judge score (out of 10) a case passes with when its rubric sets none.
+pub const DEFAULT_MIN_JUDGE_SCORE: u8 = 7;
+
+/// A named set of cases, stored as JSON:
+///
+/// ```json
+/// {
+///   "name": "support-regressions",
+///   "cases": [
+///     {"id": "refund", "input": "I want my money back", "expect": {"contains": ["refund"]}},
+///     {"input": "Where is my order?", "expect": {"regex": ["\\b\\d{5,}\\b"], "judge": {"rubric": "Asks for the order number politely"}}}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EvalSuite {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalSuite {
+    /// Loads a suite from a JSON file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, EvalError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| EvalError::io(path.display().to_string(), e))?;
+        Self::from_json(&content)
+    }
+
+    /// Parses a suite from JSON and checks its assertions.
+    pub fn from_json(json: &str) -> Result<Self, EvalError> {
+        let suite: Self = serde_json::from_str(json)?;
+        suite.validate()?;
+        Ok(suite)
+    }
+
+    /// Checks that the suite has cases and that every regex compiles.
+    pub fn validate(&self) -> Result<(), EvalError> {
+        if self.cases.is_empty() {
+            return Err(EvalError::InvalidSuite("the suite has no cases".into()));
+        }
+        for (idx, case) in self.cases.iter().enumerate() {
+            for pattern in &case.expect.regex {
+                Regex::new(pattern).map_err(|e| {
+                    EvalError::InvalidSuite(format!("case '{}' has an invalid regex: {}", case.id_or(idx), e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One input to run through the pipeline and what its output must satisfy.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub input: String,
+    #[serde(default)]
+    pub expect: Expectations,
+    /// Tools answered from fixtures instead of running, keyed by tool name;
+    /// unlisted tools run live.
+    #[serde(default)]
+    pub mock_tools: HashMap<String, ToolMock>,
+}
+
+impl EvalCase {
+    /// The case's ID, or `case-<n>` from its 0-based position.
+    pub fn id_or(&self, idx: usize) -> String {
+        self.id.clone().unwrap_or_else(|| format!("case-{}", idx + 1))
+    }
+}
+
+/// Assertions applied to a case's output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expectations {
+    /// Substrings that must appear (case-insensitive).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<String>,
+    /// Substrings that must not appear (case-insensitive).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_contains: Vec<String>,
+    /// Patterns the output must match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regex: Vec<String>,
+    /// Minimum output length in characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    /// A rubric an LLM judge scores the output against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<JudgeRubric>,
+}
+
+impl Expectations {
+    /// Number of assertions, counting the judge's as one.
+    pub fn len(&self) -> usize {
+        self.contains.len()
+            + self.not_contains.len()
+            + self.regex.len()
+            + usize::from(self.min_length.is_some())
+            + usize::from(self.judge.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the failed assertions for an output, leaving out the judge's.
+    pub fn check(&self, output: &str) -> Vec<String> {
+        let lower = output.to_lowercase();
+        let mut failures = Vec::new();
+
+        for needle in &self.contains {
+            if !lower.contains(&needle.to_lowercase()) {
+                failures.push(format!("missing '{}'", needle));
+            }
+        }
+        for needle in &self.not_contains {
+            if lower.contains(&needle.to_lowercase()) {
+                failures.push(format!("unexpected '{}'", needle));
+            }
+        }
+        for pattern in &self.regex {
+            match Regex::new(pattern) {
+                Ok(re) if re.is_match(output) => {}
+                Ok(_) => failures.push(format!("no match for /{}/", pattern)),
+                Err(e) => failures.push(format!("invalid regex /{}/: {}", pattern, e)),
+            }
+        }
+        if let Some(min) = self.min_length {
+            let len = output.chars().count();
+            if len < min {
+                failures.push(format!("output length {} < {}", len, min));
+            }
+        }
+
+        failures
+    }
+}
+
+/// What an LLM judge grades an output on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JudgeRubric {
+    /// What a good answer does, in plain language.
+    pub rubric: String,
+    /// Lowest passing score out of 10 (default 7).
+    #[serde(default = "default_min_score")]
+    pub min_score: u8,
+}
+
+fn default_min_score() -> u8 {
+    DEFAULT_MIN_JUDGE_SCORE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expectations_check() {
+        let expect = Expectations {
+            contains: vec!["Refund".into()],
+            not_contains: vec!["sorry".into()],
+            regex: vec![r"\d+ days".into()],
+            min_length: Some(5),
+            judge: None,
+        };
+        assert!(expect.check("Your refund arrives in 5 days").is_empty());
+        assert_eq!(expect.check("Sorry, no").len(), 3);
+        assert_eq!(expect.len(), 4);
+    }
+
+    #[test]
+    fn test_suite_rejects_invalid_regex() {
+        let suite = EvalSuite::from_json(
+            r#"{"cases": [{"input": "hi", "expect": {"judge": {"rubric": "Greets back"}}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(suite.cases[0].expect.judge.as_ref().unwrap().min_score, DEFAULT_MIN_JUDGE_SCORE);
+
+        let invalid = EvalSuite::from_json(r#"{"cases": [{"id": "x", "input": "hi", "expect": {"regex": ["("]}}]}"#);
+        assert!(matches!(invalid, Err(EvalError::InvalidSuite(_))));
+        assert!(matches!(EvalSuite::from_json(r#"{"cases": []}"#), Err(EvalError::InvalidSuite(_))));
+    }
+
+    #[test]
+    fn test_case_with_mock_tools() {
+        let case: EvalCase = serde_json::from_str(
+            r#"{"input": "hi", "mock_tools": {"web_search": "fixture", "fetch_url": [{"error": "offline"}]}}"#,
+        )
+        .unwrap();
+        assert!(matches!(case.mock_tools["web_search"], ToolMock::Output(ref s) if s == "fixture"));
+        assert!(matches!(case.mock_tools["fetch_url"], ToolMock::Responses(ref r) if r.len() == 1));
+        assert_eq!(case.id_or(2), "case-3");
+    }
+}
@@ -1,24 +1,32 @@
 //! Anthropic Claude API client with streaming and tool support.
 
-use fissio_core::{AgentError, Message, ToolCall, ToolSchema};
+use fissio_core::{AgentError, Message, OutputSchema, ToolCall, ToolSchema};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::{error, info};
 
 use crate::client::ChatResponse;
+use crate::headers::ResponseHeaders;
 use crate::{LlmMetrics, LlmResponse, LlmStream, StreamChunk};
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
+const MAX_TOKENS: u32 = 8192;
 
 /// Checks HTTP response status and returns an error if not successful.
+///
+/// Errors quote the request id so they can be traced with Anthropic support.
 async fn check_response(response: reqwest::Response) -> Result<reqwest::Response, AgentError> {
     if !response.status().is_success() {
         let status = response.status();
+        let headers = ResponseHeaders::from_headers(response.headers());
         let body = response.text().await.unwrap_or_default();
         return Err(AgentError::LlmError(format!(
-            "Anthropic API error {}: {}",
-            status, body
+            "Anthropic API error {}{}: {}",
+            status,
+            headers.describe(),
+            body
         )));
     }
     Ok(response)
@@ -37,11 +45,16 @@ struct AnthropicRequest {
     system: String,
     messages: Vec<AnthropicMessage>,
     stream: bool,
+    /// Extended thinking, e.g. `{"type": "enabled", "budget_tokens": 4096}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize)]
 struct ContentBlockDelta {
     text: Option<String>,
+    /// Set on `thinking_delta`s when extended thinking is on.
+    thinking: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -93,6 +106,9 @@ struct AnthropicRequestWithTools {
     system: String,
     messages: Vec<AnthropicMessageWithContent>,
     tools: Vec<AnthropicTool>,
+    /// Forces a particular tool, e.g. `{"type": "tool", "name": "..."}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
 }
 
 /// Message with content blocks (for tool conversations).
@@ -149,10 +165,13 @@ pub struct AnthropicClient {
     client: Client,
     model: String,
     api_key: String,
+    thinking_budget: Option<u32>,
 }
 
 impl AnthropicClient {
     /// Creates a new Anthropic client.
+    ///
+    /// Streamed chats think first when `ANTHROPIC_THINKING_BUDGET` sets a token budget.
     pub fn new(model: &str) -> Self {
         let api_key = std::env::var("ANTHROPIC_API_KEY").unwrap_or_default();
         tracing::info!(
@@ -164,9 +183,17 @@ impl AnthropicClient {
             client: Client::new(),
             model: model.to_string(),
             api_key,
+            thinking_budget: std::env::var("ANTHROPIC_THINKING_BUDGET").ok().and_then(|v| v.parse().ok()),
         }
     }
 
+    /// Lets streamed chats think with up to `budget_tokens` (at least 1024)
+    /// before answering; the thinking is streamed as [`StreamChunk::Reasoning`].
+    pub fn with_thinking(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = Some(budget_tokens);
+        self
+    }
+
     /// Creates a request builder with standard Anthropic headers.
     fn request(&self) -> reqwest::RequestBuilder {
         self.client
@@ -182,13 +209,14 @@ impl AnthropicClient {
 
         let request = AnthropicRequest {
             model: self.model.clone(),
-            max_tokens: 8192,
+            max_tokens: MAX_TOKENS,
             system: system_prompt.to_string(),
             messages: vec![AnthropicMessage {
                 role: "user",
                 content: user_input.to_string(),
             }],
             stream: false,
+            thinking: None,
         };
 
         let response = self
@@ -199,6 +227,7 @@ impl AnthropicClient {
             .map_err(|e| AgentError::LlmError(e.to_string()))?;
 
         let response = check_response(response).await?;
+        let headers = ResponseHeaders::from_headers(response.headers());
 
         let resp: NonStreamResponse = response
             .json()
@@ -213,7 +242,9 @@ impl AnthropicClient {
                 input_tokens: resp.usage.input_tokens.unwrap_or(0),
                 output_tokens: resp.usage.output_tokens.unwrap_or(0),
                 elapsed_ms: start.elapsed().as_millis() as u64,
-            },
+                ..Default::default()
+            }
+            .with_headers(headers),
         })
     }
 
@@ -239,12 +270,14 @@ impl AnthropicClient {
             content: user_input.to_string(),
         });
 
+        // The budget counts toward max_tokens, so the answer keeps its usual room
         let request = AnthropicRequest {
             model: self.model.clone(),
-            max_tokens: 8192,
+            max_tokens: MAX_TOKENS + self.thinking_budget.unwrap_or(0),
             system: system_prompt.to_string(),
             messages,
             stream: true,
+            thinking: self.thinking_budget.map(|budget| json!({ "type": "enabled", "budget_tokens": budget })),
         };
 
         let response = self
@@ -297,6 +330,9 @@ impl AnthropicClient {
                             match event.event_type.as_str() {
                                 "content_block_delta" => {
                                     if let Some(delta) = event.delta {
+                                        if let Some(thinking) = delta.thinking {
+                                            parsed_chunks.push(Ok(StreamChunk::Reasoning(thinking)));
+                                        }
                                         if let Some(text) = delta.text {
                                             parsed_chunks.push(Ok(StreamChunk::Content(text)));
                                         }
@@ -353,10 +389,11 @@ impl AnthropicClient {
 
         let request = AnthropicRequestWithTools {
             model: self.model.clone(),
-            max_tokens: 8192,
+            max_tokens: MAX_TOKENS,
             system: system_prompt.to_string(),
             messages,
             tools: anthropic_tools,
+            tool_choice: None,
         };
 
         let response = self
@@ -367,6 +404,7 @@ impl AnthropicClient {
             .map_err(|e| AgentError::LlmError(e.to_string()))?;
 
         let response = check_response(response).await?;
+        let headers = ResponseHeaders::from_headers(response.headers());
 
         let resp: ToolResponse = response
             .json()
@@ -378,7 +416,9 @@ impl AnthropicClient {
             input_tokens: resp.usage.input_tokens.unwrap_or(0),
             output_tokens: resp.usage.output_tokens.unwrap_or(0),
             elapsed_ms,
-        };
+            ..Default::default()
+        }
+        .with_headers(headers);
 
         // Check if response contains tool_use blocks
         let tool_calls: Vec<ToolCall> = resp
@@ -429,6 +469,67 @@ impl AnthropicClient {
 
         Ok(ChatResponse::Content(LlmResponse { content, metrics }))
     }
+
+    /// Sends a chat request whose answer must match `schema`.
+    ///
+    /// Anthropic has no JSON response format, so the schema is offered as the
+    /// input of a tool the model is forced to call; the call's input is
+    /// returned as the response's JSON text.
+    pub async fn chat_json_schema(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        let start = std::time::Instant::now();
+
+        let request = AnthropicRequestWithTools {
+            model: self.model.clone(),
+            max_tokens: MAX_TOKENS,
+            system: system_prompt.to_string(),
+            messages: vec![AnthropicMessageWithContent::user(user_input)],
+            tools: vec![AnthropicTool {
+                name: schema.name.clone(),
+                description: "Respond by calling this tool with your answer.".to_string(),
+                input_schema: schema.schema.clone(),
+            }],
+            tool_choice: Some(json!({ "type": "tool", "name": schema.name })),
+        };
+
+        let response = self
+            .request()
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+
+        let response = check_response(response).await?;
+        let headers = ResponseHeaders::from_headers(response.headers());
+
+        let resp: ToolResponse = response
+            .json()
+            .await
+            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+
+        let input = resp
+            .content
+            .into_iter()
+            .find_map(|block| match block {
+                ToolResponseBlock::ToolUse { input, .. } => Some(input),
+                _ => None,
+            })
+            .ok_or_else(|| AgentError::ParseError(format!("Anthropic did not call the {} tool", schema.name)))?;
+
+        let metrics = LlmMetrics {
+            input_tokens: resp.usage.input_tokens.unwrap_or(0),
+            output_tokens: resp.usage.output_tokens.unwrap_or(0),
+            elapsed_ms: start.elapsed().as_millis() as u64,
+            ..Default::default()
+        }
+        .with_headers(headers);
+
+        Ok(LlmResponse { content: input.to_string(), metrics })
+    }
 }
 
 // === Public helper functions for tool conversations ===
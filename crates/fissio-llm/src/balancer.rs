@@ -0,0 +1,90 @@
+//! Spreading calls to a model across the hosts that serve it.
+//!
+//! A model with [`mirrors`](fissio_core::ModelConfig::mirrors) is served from
+//! its `api_base` and each mirror. [`HostBalancer::acquire`] picks the host
+//! with the fewest calls in flight, taking turns among hosts that tie, so
+//! parallel nodes on a multi-GPU setup run side by side instead of queueing
+//! on one Ollama server.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use fissio_core::ModelConfig;
+use tracing::debug;
+
+static GLOBAL: OnceLock<HostBalancer> = OnceLock::new();
+
+/// Counts calls in flight per host and hands out the least busy one.
+#[derive(Debug, Default)]
+pub struct HostBalancer {
+    in_flight: Mutex<HashMap<String, usize>>,
+    turn: AtomicUsize,
+}
+
+impl HostBalancer {
+    /// Creates a balancer with nothing in flight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide balancer, shared by every engine.
+    pub fn global() -> &'static HostBalancer {
+        GLOBAL.get_or_init(HostBalancer::new)
+    }
+
+    /// Picks a host for a call to `model`, or `None` if it has no mirrors.
+    ///
+    /// The host counts as busy until the returned lease is dropped.
+    pub fn acquire(&self, model: &ModelConfig) -> Option<HostLease<'_>> {
+        if model.mirrors.is_empty() {
+            return None;
+        }
+        let hosts: Vec<&String> = model.api_base.iter().chain(&model.mirrors).collect();
+        let start = self.turn.fetch_add(1, Ordering::Relaxed);
+
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        let host = (0..hosts.len())
+            .map(|i| hosts[(start + i) % hosts.len()])
+            .min_by_key(|host| in_flight.get(host.as_str()).copied().unwrap_or(0))?
+            .clone();
+        *in_flight.entry(host.clone()).or_default() += 1;
+        debug!("Sending {} to {}", model.id, host);
+
+        let model = ModelConfig { api_base: Some(host.clone()), mirrors: Vec::new(), ..model.clone() };
+        Some(HostLease { balancer: self, host, model })
+    }
+
+    /// Calls currently in flight to `host`.
+    pub fn in_flight(&self, host: &str) -> usize {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).get(host).copied().unwrap_or(0)
+    }
+}
+
+/// A call's claim on a host, released on drop.
+#[derive(Debug)]
+pub struct HostLease<'a> {
+    balancer: &'a HostBalancer,
+    host: String,
+    model: ModelConfig,
+}
+
+impl HostLease<'_> {
+    /// The model config with `api_base` set to the chosen host.
+    pub fn model(&self) -> &ModelConfig {
+        &self.model
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+}
+
+impl Drop for HostLease<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.balancer.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = in_flight.get_mut(&self.host) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Response caching for non-streaming LLM calls.
+//!
+//! Identical requests (same model, endpoint, system prompt, messages, and tool
+//! schemas) return the stored response instead of calling the provider. Meant
+//! for development and tests, where pipelines are re-run with the same inputs.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use fissio_core::{AgentError, ToolSchema};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Storage backend for cached LLM responses.
+///
+/// Values are serialized responses; keys come from [`cache_key`].
+#[async_trait]
+pub trait LlmCache: Send + Sync {
+    /// Returns the cached value for `key`, if any.
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores `value` under `key`, replacing any previous entry.
+    async fn put(&self, key: &str, value: String);
+}
+
+/// Process-local cache, cleared on restart.
+#[derive(Default)]
+pub struct InMemoryLlmCache {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl InMemoryLlmCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LlmCache for InMemoryLlmCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    async fn put(&self, key: &str, value: String) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// SQLite-backed cache that persists across runs.
+pub struct SqliteLlmCache {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteLlmCache {
+    /// Opens (or creates) a cache database at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, AgentError> {
+        let conn = Connection::open(path).map_err(cache_err)?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS llm_cache (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+            "#,
+        )
+        .map_err(cache_err)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+#[async_trait]
+impl LlmCache for SqliteLlmCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let conn = self.conn.lock().ok()?;
+        conn.query_row("SELECT value FROM llm_cache WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    async fn put(&self, key: &str, value: String) {
+        if let Ok(conn) = self.conn.lock() {
+            let result = conn.execute(
+                "INSERT OR REPLACE INTO llm_cache (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            );
+            if let Err(e) = result {
+                tracing::warn!("Failed to write LLM cache entry: {}", e);
+            }
+        }
+    }
+}
+
+fn cache_err(e: rusqlite::Error) -> AgentError {
+    AgentError::ExternalApi(format!("LLM cache error: {}", e))
+}
+
+#[derive(Serialize)]
+struct KeyMaterial<'a, M: Serialize> {
+    model: &'a str,
+    api_base: Option<&'a str>,
+    system_prompt: &'a str,
+    messages: M,
+    tools: &'a [ToolSchema],
+}
+
+/// Builds a cache key for a request.
+///
+/// The key is the model name followed by a stable 64-bit FNV-1a hash of the
+/// full request, so it stays valid across processes and Rust versions.
+pub fn cache_key<M: Serialize>(
+    model: &str,
+    api_base: Option<&str>,
+    system_prompt: &str,
+    messages: M,
+    tools: &[ToolSchema],
+) -> String {
+    let material = KeyMaterial { model, api_base, system_prompt, messages, tools };
+    let bytes = serde_json::to_vec(&material).unwrap_or_default();
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{}:{:016x}", model, hash)
+}
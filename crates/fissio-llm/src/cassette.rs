@@ -0,0 +1,219 @@
+//! Record/replay of LLM interactions for deterministic pipeline runs.
+//!
+//! In record mode every request/response pair is appended to a JSON cassette
+//! file. In replay mode responses are served from the cassette instead of the
+//! provider, so CI and demos can re-run expensive agentic pipelines exactly.
+//!
+//! Requests are matched by kind and request key (see [`cache_key`](crate::cache_key)),
+//! not by position, so parallel nodes may replay in any order. Identical
+//! requests replay their recorded responses in the order they were recorded.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use fissio_core::{AgentError, Citation};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::StreamChunk;
+
+/// Whether a cassette captures or serves responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Which client method produced an interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CallKind {
+    Chat,
+    ChatStream,
+    ChatWithTools,
+    ChatStructured,
+}
+
+/// A streamed chunk as stored in a cassette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum RecordedChunk {
+    Content { text: String },
+    Reasoning { text: String },
+    Usage { input_tokens: u32, output_tokens: u32 },
+    Progress { text: String },
+    ToolCallStarted { call_id: String, name: String, args_preview: String },
+    ToolCallFinished { call_id: String, name: String, duration_ms: u64, result_preview: String },
+    Citation(Citation),
+}
+
+impl From<&StreamChunk> for RecordedChunk {
+    fn from(chunk: &StreamChunk) -> Self {
+        match chunk {
+            StreamChunk::Content(text) => RecordedChunk::Content { text: text.clone() },
+            StreamChunk::Reasoning(text) => RecordedChunk::Reasoning { text: text.clone() },
+            StreamChunk::Usage { input_tokens, output_tokens } => RecordedChunk::Usage {
+                input_tokens: *input_tokens,
+                output_tokens: *output_tokens,
+            },
+            StreamChunk::Progress(text) => RecordedChunk::Progress { text: text.clone() },
+            StreamChunk::ToolCallStarted { call_id, name, args_preview } => RecordedChunk::ToolCallStarted {
+                call_id: call_id.clone(),
+                name: name.clone(),
+                args_preview: args_preview.clone(),
+            },
+            StreamChunk::ToolCallFinished { call_id, name, duration_ms, result_preview } => {
+                RecordedChunk::ToolCallFinished {
+                    call_id: call_id.clone(),
+                    name: name.clone(),
+                    duration_ms: *duration_ms,
+                    result_preview: result_preview.clone(),
+                }
+            }
+            StreamChunk::Citation(citation) => RecordedChunk::Citation(citation.clone()),
+        }
+    }
+}
+
+impl From<RecordedChunk> for StreamChunk {
+    fn from(chunk: RecordedChunk) -> Self {
+        match chunk {
+            RecordedChunk::Content { text } => StreamChunk::Content(text),
+            RecordedChunk::Reasoning { text } => StreamChunk::Reasoning(text),
+            RecordedChunk::Usage { input_tokens, output_tokens } => {
+                StreamChunk::Usage { input_tokens, output_tokens }
+            }
+            RecordedChunk::Progress { text } => StreamChunk::Progress(text),
+            RecordedChunk::ToolCallStarted { call_id, name, args_preview } => {
+                StreamChunk::ToolCallStarted { call_id, name, args_preview }
+            }
+            RecordedChunk::ToolCallFinished { call_id, name, duration_ms, result_preview } => {
+                StreamChunk::ToolCallFinished { call_id, name, duration_ms, result_preview }
+            }
+            RecordedChunk::Citation(citation) => StreamChunk::Citation(citation),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    kind: CallKind,
+    key: String,
+    model: String,
+    /// The request as sent, kept for readability; matching uses `key`.
+    request: serde_json::Value,
+    response: serde_json::Value,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    replayed: Vec<bool>,
+}
+
+/// A file of recorded LLM interactions.
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Starts an empty recording that is written to `path` after every interaction.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: path.into(),
+            state: Mutex::new(CassetteState { interactions: Vec::new(), replayed: Vec::new() }),
+        }
+    }
+
+    /// Loads a recording to serve responses from.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, AgentError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| {
+            AgentError::LlmError(format!("failed to read cassette {}: {}", path.display(), e))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&content)?;
+        let replayed = vec![false; file.interactions.len()];
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path: path.to_path_buf(),
+            state: Mutex::new(CassetteState { interactions: file.interactions, replayed }),
+        })
+    }
+
+    /// Returns whether this cassette records or replays.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns the cassette file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the next unreplayed response recorded for `kind` and `key`.
+    pub(crate) fn take<T: DeserializeOwned>(&self, kind: CallKind, key: &str) -> Result<T, AgentError> {
+        let mut state = self.lock();
+        let CassetteState { interactions, replayed } = &mut *state;
+        let index = (0..interactions.len())
+            .find(|&i| !replayed[i] && interactions[i].kind == kind && interactions[i].key == key)
+            .ok_or_else(|| {
+                AgentError::LlmError(format!(
+                    "no recorded {:?} response for request {} in cassette {}",
+                    kind,
+                    key,
+                    self.path.display()
+                ))
+            })?;
+        replayed[index] = true;
+        debug!("Replaying {:?} response for {}", kind, key);
+        Ok(serde_json::from_value(interactions[index].response.clone())?)
+    }
+
+    /// Appends an interaction and rewrites the cassette file.
+    pub(crate) fn push(
+        &self,
+        kind: CallKind,
+        key: &str,
+        model: &str,
+        request: serde_json::Value,
+        response: &impl Serialize,
+    ) {
+        let response = match serde_json::to_value(response) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Failed to serialize response for cassette: {}", e);
+                return;
+            }
+        };
+
+        let mut state = self.lock();
+        state.interactions.push(Interaction {
+            kind,
+            key: key.to_string(),
+            model: model.to_string(),
+            request,
+            response,
+        });
+
+        let file = CassetteFile { interactions: state.interactions.clone() };
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&self.path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            warn!("Failed to write cassette {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Fault injection for resilience testing.
+//!
+//! Compiled only with the `chaos` feature. When enabled, provider calls made
+//! through [`UnifiedLlmClient`](crate::UnifiedLlmClient) randomly fail the way
+//! real providers do, at configurable rates, so retry, fallback, and
+//! partial-failure handling can be exercised before it is relied on:
+//!
+//! - Timeouts — the request hangs for `timeout`, then fails
+//! - 429s — the request fails immediately with a rate limit error
+//! - Malformed tool arguments — tool calls arrive with unparseable arguments
+//! - Stream truncation — a stream ends with an error after a few chunks
+//!
+//! Cassette replays and cache hits never reach the provider and are not affected.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fissio_core::AgentError;
+use futures::StreamExt;
+use tracing::{info, warn};
+
+use crate::{ChatResponse, LlmStream, StreamChunk};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most content chunks a truncated stream delivers before failing.
+const MAX_CHUNKS_BEFORE_TRUNCATION: u64 = 8;
+
+static GLOBAL: OnceLock<Option<FaultInjector>> = OnceLock::new();
+
+/// Probability (0.0–1.0) of each fault per provider call.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosConfig {
+    pub timeout_rate: f64,
+    pub rate_limit_rate: f64,
+    pub malformed_tool_args_rate: f64,
+    pub stream_truncation_rate: f64,
+    /// How long an injected timeout hangs before failing.
+    pub timeout: Duration,
+    /// Seed for reproducible fault sequences; random when unset.
+    pub seed: Option<u64>,
+}
+
+impl ChaosConfig {
+    /// Reads `CHAOS_TIMEOUT_RATE`, `CHAOS_RATE_LIMIT_RATE`, `CHAOS_MALFORMED_TOOL_ARGS_RATE`,
+    /// `CHAOS_STREAM_TRUNCATION_RATE`, `CHAOS_TIMEOUT_MS`, and `CHAOS_SEED`.
+    ///
+    /// Returns `None` when every rate is zero.
+    pub fn from_env() -> Option<Self> {
+        let rate = |var: &str| {
+            std::env::var(var)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .map_or(0.0, |r| r.clamp(0.0, 1.0))
+        };
+        let config = Self {
+            timeout_rate: rate("CHAOS_TIMEOUT_RATE"),
+            rate_limit_rate: rate("CHAOS_RATE_LIMIT_RATE"),
+            malformed_tool_args_rate: rate("CHAOS_MALFORMED_TOOL_ARGS_RATE"),
+            stream_truncation_rate: rate("CHAOS_STREAM_TRUNCATION_RATE"),
+            timeout: std::env::var("CHAOS_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+            seed: std::env::var("CHAOS_SEED").ok().and_then(|v| v.parse().ok()),
+        };
+        config.is_active().then_some(config)
+    }
+
+    fn is_active(&self) -> bool {
+        self.timeout_rate > 0.0
+            || self.rate_limit_rate > 0.0
+            || self.malformed_tool_args_rate > 0.0
+            || self.stream_truncation_rate > 0.0
+    }
+}
+
+/// Decides which calls fail, using a seeded xorshift generator.
+pub struct FaultInjector {
+    config: ChaosConfig,
+    state: Mutex<u64>,
+}
+
+impl FaultInjector {
+    /// Creates an injector for `config`.
+    pub fn new(config: ChaosConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64)
+        });
+        // xorshift never leaves zero
+        Self { config, state: Mutex::new(seed | 1) }
+    }
+
+    /// Installs the process-wide injector used by every client.
+    ///
+    /// Must be called before the first provider call; otherwise the
+    /// environment configuration is used.
+    pub fn install(config: ChaosConfig) {
+        if GLOBAL.set(Some(FaultInjector::new(config))).is_err() {
+            warn!("Fault injection already initialized, ignoring new config");
+        }
+    }
+
+    /// Returns the process-wide injector, configured from the environment on first use.
+    pub(crate) fn global() -> Option<&'static FaultInjector> {
+        GLOBAL
+            .get_or_init(|| {
+                let config = ChaosConfig::from_env()?;
+                warn!("Fault injection enabled: {:?}", config);
+                Some(FaultInjector::new(config))
+            })
+            .as_ref()
+    }
+
+    fn next(&self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0 && (self.next() as f64 / u64::MAX as f64) < rate
+    }
+
+    /// Fails the request with an injected timeout or 429, or lets it through.
+    pub(crate) async fn before_request(&self, model: &str) -> Result<(), AgentError> {
+        if self.roll(self.config.timeout_rate) {
+            info!("Chaos: injecting timeout for {}", model);
+            tokio::time::sleep(self.config.timeout).await;
+            return Err(AgentError::LlmError("injected fault: request timed out".into()));
+        }
+        if self.roll(self.config.rate_limit_rate) {
+            info!("Chaos: injecting 429 for {}", model);
+            return Err(AgentError::LlmError(
+                "injected fault: 429 Too Many Requests: rate limit exceeded".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replaces the arguments of returned tool calls with the value an unparseable payload yields.
+    pub(crate) fn corrupt_tool_calls(&self, response: &mut ChatResponse) {
+        let ChatResponse::ToolCalls { calls, .. } = response else {
+            return;
+        };
+        for call in calls {
+            if self.roll(self.config.malformed_tool_args_rate) {
+                info!("Chaos: corrupting arguments of tool call {}", call.name);
+                call.arguments = serde_json::Value::Null;
+            }
+        }
+    }
+
+    /// Possibly cuts the stream short after a few chunks, ending it with an error.
+    pub(crate) fn truncate(&self, stream: LlmStream) -> LlmStream {
+        if !self.roll(self.config.stream_truncation_rate) {
+            return stream;
+        }
+        let keep = (self.next() % (MAX_CHUNKS_BEFORE_TRUNCATION + 1)) as usize;
+        info!("Chaos: truncating stream after {} chunks", keep);
+        let cut = futures::stream::once(async {
+            Err::<StreamChunk, _>(AgentError::LlmError("injected fault: stream truncated".into()))
+        });
+        Box::pin(
+            stream
+                .filter(|chunk| futures::future::ready(matches!(chunk, Ok(StreamChunk::Content(_)))))
+                .take(keep)
+                .chain(cut),
+        )
+    }
+}
@@ -1,53 +1,87 @@
 //! OpenAI-compatible chat client with streaming support.
 //!
-//! Works with OpenAI API and any compatible endpoint (including Ollama's /v1 endpoint).
+//! Works with OpenAI API and any compatible endpoint (including Ollama's /v1 endpoint),
+//! as well as Azure OpenAI deployments.
 //! Supports regular chat, streaming, structured JSON output, and tool calling.
 
 use std::pin::Pin;
-use std::time::Instant;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-use fissio_core::{AgentError, Message, MessageRole, ToolCall, ToolSchema};
+use fissio_core::{
+    AgentError, AzureOpenAiConfig, Citation, Message, MessageRole, ModelConfig, OutputSchema, ToolCall, ToolSchema,
+};
 use async_openai::{
-    config::OpenAIConfig,
+    config::{AzureConfig, Config, OpenAIConfig},
     types::{
         ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessageArgs,
         ChatCompletionRequestUserMessageArgs, ChatCompletionStreamOptions,
-        ChatCompletionTool, ChatCompletionToolType, CreateChatCompletionRequestArgs,
-        CreateChatCompletionResponse, FunctionObject, ResponseFormat,
+        ChatCompletionTool, ChatCompletionToolType,
+        CreateChatCompletionRequest, CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
+        FunctionObject, ResponseFormat, ResponseFormatJsonSchema,
     },
     Client,
 };
 use futures::Stream;
 use serde::de::DeserializeOwned;
-use tracing::{debug, info};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::headers::{retry_after, RateLimitStatus, ResponseHeaders};
 
 /// A chunk from a streaming LLM response.
+#[non_exhaustive]
 pub enum StreamChunk {
     Content(String),
+    /// Reasoning the model streamed before its answer; not part of the response text.
+    Reasoning(String),
     Usage { input_tokens: u32, output_tokens: u32 },
+    /// Status line such as tool activity; not part of the response text.
+    Progress(String),
+    /// A tool call in an agentic loop started; `args_preview` is the start of its arguments.
+    ToolCallStarted { call_id: String, name: String, args_preview: String },
+    /// A tool call finished; `result_preview` is the start of its result.
+    ToolCallFinished { call_id: String, name: String, duration_ms: u64, result_preview: String },
+    /// A source cited by the content streamed so far; not part of the response text.
+    Citation(Citation),
 }
 
 /// A stream of LLM response chunks.
 pub type LlmStream = Pin<Box<dyn Stream<Item = Result<StreamChunk, AgentError>> + Send>>;
 
 /// Token usage and timing metrics from an LLM call.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LlmMetrics {
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub elapsed_ms: u64,
+    /// The provider's id for the request, to quote in support tickets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Quota the provider reported left after the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitStatus>,
+}
+
+impl LlmMetrics {
+    /// Adds what the provider reported in the response headers.
+    pub fn with_headers(mut self, headers: ResponseHeaders) -> Self {
+        self.request_id = headers.request_id;
+        self.rate_limit = headers.rate_limit;
+        self
+    }
 }
 
 /// Complete response from an LLM call.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmResponse {
     pub content: String,
     pub metrics: LlmMetrics,
 }
 
 /// Response from an LLM that may include tool calls.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ChatResponse {
     Content(LlmResponse),
     ToolCalls { calls: Vec<ToolCall>, metrics: LlmMetrics },
@@ -80,7 +114,11 @@ fn build_messages(
 }
 
 /// Extracts content and metrics from a completion response.
-fn extract_response(response: CreateChatCompletionResponse, elapsed_ms: u64) -> Result<LlmResponse, AgentError> {
+fn extract_response(
+    response: CreateChatCompletionResponse,
+    headers: ResponseHeaders,
+    elapsed_ms: u64,
+) -> Result<LlmResponse, AgentError> {
     let content = response
         .choices
         .into_iter()
@@ -100,13 +138,186 @@ fn extract_response(response: CreateChatCompletionResponse, elapsed_ms: u64) ->
 
     Ok(LlmResponse {
         content,
-        metrics: LlmMetrics { input_tokens, output_tokens, elapsed_ms },
+        metrics: LlmMetrics { input_tokens, output_tokens, elapsed_ms, ..Default::default() }.with_headers(headers),
     })
 }
 
+/// Environment variable holding the Azure OpenAI API key.
+const AZURE_API_KEY_VAR: &str = "AZURE_OPENAI_API_KEY";
+
+/// The underlying SDK client, which is typed by its config.
+enum ApiClient {
+    OpenAI(Client<OpenAIConfig>),
+    Azure(Client<AzureConfig>),
+}
+
+impl ApiClient {
+    /// Sends a chat completion request, returning the response with what its headers reported.
+    async fn create(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> Result<(CreateChatCompletionResponse, ResponseHeaders), AgentError> {
+        match self {
+            ApiClient::OpenAI(client) => post_chat_completion(client.config(), &request).await,
+            ApiClient::Azure(client) => post_chat_completion(client.config(), &request).await,
+        }
+    }
+
+    /// Sends a streaming chat completion request.
+    async fn create_stream(&self, request: CreateChatCompletionRequest) -> Result<LlmStream, AgentError> {
+        match self {
+            ApiClient::OpenAI(client) => post_chat_completion_stream(client.config(), &request).await,
+            ApiClient::Azure(client) => post_chat_completion_stream(client.config(), &request).await,
+        }
+    }
+}
+
+/// Attempts made at a request the provider answers with 429 before giving up.
+const RATE_LIMITED_ATTEMPTS: u32 = 5;
+
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Posts a chat completion request with the SDK's endpoint and credentials.
+///
+/// The SDK doesn't expose response headers, so the request is sent here to
+/// read the request id and rate limit headers. Like the SDK, a 429 is retried
+/// after the wait the provider asks for, unless the account is out of quota.
+async fn post_chat_completion<C: Config>(
+    config: &C,
+    request: &CreateChatCompletionRequest,
+) -> Result<(CreateChatCompletionResponse, ResponseHeaders), AgentError> {
+    let (response, headers) = send_chat_completion(config, request).await?;
+    let body = response.json().await.map_err(llm_err)?;
+    Ok((body, headers))
+}
+
+/// A chunk of a streamed chat completion.
+///
+/// Parsed here rather than with the SDK's types, which drop the reasoning
+/// that OpenAI-compatible servers (vLLM, DeepSeek, Ollama, OpenRouter) send
+/// as `reasoning_content` or `reasoning`.
+#[derive(Deserialize)]
+struct CompletionChunk {
+    #[serde(default)]
+    choices: Vec<CompletionChunkChoice>,
+    usage: Option<CompletionChunkUsage>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChunkChoice {
+    delta: CompletionDelta,
+}
+
+#[derive(Deserialize)]
+struct CompletionDelta {
+    content: Option<String>,
+    #[serde(alias = "reasoning")]
+    reasoning_content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChunkUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+impl CompletionChunk {
+    fn into_stream_chunks(self) -> Vec<StreamChunk> {
+        let mut chunks = Vec::new();
+        if let Some(delta) = self.choices.into_iter().next().map(|c| c.delta) {
+            chunks.extend(delta.reasoning_content.filter(|r| !r.is_empty()).map(StreamChunk::Reasoning));
+            chunks.extend(delta.content.filter(|c| !c.is_empty()).map(StreamChunk::Content));
+        }
+        if let Some(usage) = self.usage {
+            chunks.push(StreamChunk::Usage { input_tokens: usage.prompt_tokens, output_tokens: usage.completion_tokens });
+        }
+        chunks
+    }
+}
+
+/// Posts a streaming chat completion request and parses its server-sent events.
+async fn post_chat_completion_stream<C: Config>(
+    config: &C,
+    request: &CreateChatCompletionRequest,
+) -> Result<LlmStream, AgentError> {
+    use futures::StreamExt;
+
+    let (response, _) = send_chat_completion(config, request).await?;
+    // Keep incomplete lines in the buffer until the rest arrives
+    let mapped = response
+        .bytes_stream()
+        .scan(String::new(), |buffer, result| {
+            let chunks: Vec<Result<StreamChunk, AgentError>> = match result {
+                Err(e) => vec![Err(llm_err(e))],
+                Ok(bytes) => {
+                    buffer.push_str(&String::from_utf8_lossy(&bytes));
+                    let mut parsed = Vec::new();
+                    while let Some(newline) = buffer.find('\n') {
+                        let line = buffer[..newline].trim().to_string();
+                        *buffer = buffer[newline + 1..].to_string();
+                        let Some(json) = line.strip_prefix("data:").map(str::trim) else {
+                            continue;
+                        };
+                        if json == "[DONE]" {
+                            continue;
+                        }
+                        match serde_json::from_str::<CompletionChunk>(json) {
+                            Ok(chunk) => parsed.extend(chunk.into_stream_chunks().into_iter().map(Ok)),
+                            Err(e) => warn!("Failed to parse completion chunk: {} - {}", e, json),
+                        }
+                    }
+                    parsed
+                }
+            };
+            futures::future::ready(Some(chunks))
+        })
+        .flat_map(futures::stream::iter);
+
+    Ok(Box::pin(mapped))
+}
+
+/// Sends a chat completion request and returns the successful response.
+async fn send_chat_completion<C: Config>(
+    config: &C,
+    request: &CreateChatCompletionRequest,
+) -> Result<(reqwest::Response, ResponseHeaders), AgentError> {
+    let mut attempt = 1;
+    loop {
+        let response = http_client()
+            .post(config.url("/chat/completions"))
+            .query(&config.query())
+            .headers(config.headers())
+            .json(request)
+            .send()
+            .await
+            .map_err(llm_err)?;
+        let status = response.status();
+        let headers = ResponseHeaders::from_headers(response.headers());
+        if status.is_success() {
+            return Ok((response, headers));
+        }
+
+        let wait = retry_after(response.headers()).unwrap_or(Duration::from_secs(1 << attempt.min(5)));
+        let body = response.text().await.unwrap_or_default();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            && attempt < RATE_LIMITED_ATTEMPTS
+            && !body.contains("insufficient_quota")
+        {
+            warn!("Rate limited{}, retrying in {}ms", headers.describe(), wait.as_millis());
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+            continue;
+        }
+        return Err(AgentError::LlmError(format!("OpenAI API error {}{}: {}", status, headers.describe(), body)));
+    }
+}
+
 /// Client for OpenAI-compatible chat completion APIs.
 pub struct LlmClient {
-    client: Client<OpenAIConfig>,
+    client: ApiClient,
     default_model: String,
 }
 
@@ -121,11 +332,35 @@ impl LlmClient {
         };
 
         Self {
-            client: Client::with_config(config),
+            client: ApiClient::OpenAI(Client::with_config(config)),
+            default_model: model.to_string(),
+        }
+    }
+
+    /// Creates a client for an Azure OpenAI deployment.
+    ///
+    /// The API key is read from `AZURE_OPENAI_API_KEY`.
+    pub fn azure(model: &str, azure: &AzureOpenAiConfig) -> Self {
+        let config = AzureConfig::new()
+            .with_api_base(&azure.endpoint)
+            .with_deployment_id(&azure.deployment)
+            .with_api_version(&azure.api_version)
+            .with_api_key(std::env::var(AZURE_API_KEY_VAR).unwrap_or_default());
+
+        Self {
+            client: ApiClient::Azure(Client::with_config(config)),
             default_model: model.to_string(),
         }
     }
 
+    /// Creates a client for a model config, using Azure when it has an `azure` section.
+    pub fn from_config(model: &ModelConfig) -> Self {
+        match &model.azure {
+            Some(azure) => Self::azure(&model.model, azure),
+            None => Self::new(&model.model, model.api_base.as_deref()),
+        }
+    }
+
     /// Sends a chat request and returns the complete response.
     pub async fn chat(&self, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError> {
         let start = Instant::now();
@@ -137,8 +372,8 @@ impl LlmClient {
             .build()
             .map_err(llm_err)?;
 
-        let response = self.client.chat().create(request).await.map_err(llm_err)?;
-        extract_response(response, start.elapsed().as_millis() as u64)
+        let (response, headers) = self.client.create(request).await?;
+        extract_response(response, headers, start.elapsed().as_millis() as u64)
     }
 
     /// Sends a chat request with tools and returns content or tool calls.
@@ -181,7 +416,7 @@ impl LlmClient {
         }
 
         let request = request_builder.build().map_err(llm_err)?;
-        let response = self.client.chat().create(request).await.map_err(llm_err)?;
+        let (response, headers) = self.client.create(request).await?;
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
         let (input_tokens, output_tokens) = response
@@ -190,7 +425,7 @@ impl LlmClient {
             .map(|u| (u.prompt_tokens, u.completion_tokens))
             .unwrap_or((0, 0));
 
-        let metrics = LlmMetrics { input_tokens, output_tokens, elapsed_ms };
+        let metrics = LlmMetrics { input_tokens, output_tokens, elapsed_ms, ..Default::default() }.with_headers(headers);
 
         let choice = response
             .choices
@@ -269,8 +504,6 @@ impl LlmClient {
         history: &[Message],
         user_input: &str,
     ) -> Result<LlmStream, AgentError> {
-        use futures::StreamExt;
-
         let mut messages = vec![
             ChatCompletionRequestMessage::System(
                 ChatCompletionRequestSystemMessageArgs::default()
@@ -307,30 +540,13 @@ impl LlmClient {
 
         let request = CreateChatCompletionRequestArgs::default()
             .model(&self.default_model)
+            .stream(true)
             .stream_options(ChatCompletionStreamOptions { include_usage: true })
             .messages(messages)
             .build()
             .map_err(llm_err)?;
 
-        let stream = self.client.chat().create_stream(request).await.map_err(llm_err)?;
-
-        let mapped = stream.filter_map(|result| async move {
-            match result {
-                Ok(response) => {
-                    if let Some(usage) = response.usage {
-                        return Some(Ok(StreamChunk::Usage {
-                            input_tokens: usage.prompt_tokens,
-                            output_tokens: usage.completion_tokens,
-                        }));
-                    }
-                    let chunk = response.choices.first()?.delta.content.clone()?;
-                    Some(Ok(StreamChunk::Content(chunk)))
-                }
-                Err(e) => Some(Err(AgentError::LlmError(e.to_string()))),
-            }
-        });
-
-        Ok(Box::pin(mapped))
+        self.client.create_stream(request).await
     }
 
     /// Sends a chat request expecting a JSON response, parses into the given type.
@@ -349,8 +565,8 @@ impl LlmClient {
             .build()
             .map_err(llm_err)?;
 
-        let response = self.client.chat().create(request).await.map_err(llm_err)?;
-        let llm_response = extract_response(response, start.elapsed().as_millis() as u64)?;
+        let (response, headers) = self.client.create(request).await?;
+        let llm_response = extract_response(response, headers, start.elapsed().as_millis() as u64)?;
 
         debug!("Structured response: {}", llm_response.content);
 
@@ -360,4 +576,36 @@ impl LlmClient {
 
         Ok((parsed, llm_response.metrics))
     }
+
+    /// Sends a chat request with a `json_schema` response format, so the
+    /// model answers with JSON matching `schema`. Returns the raw JSON text.
+    pub async fn chat_json_schema(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        let start = Instant::now();
+        let messages = build_messages(system_prompt, user_input)?;
+
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(&self.default_model)
+            .response_format(ResponseFormat::JsonSchema {
+                json_schema: ResponseFormatJsonSchema {
+                    description: None,
+                    name: schema.name.clone(),
+                    schema: Some(schema.schema.clone()),
+                    strict: Some(schema.strict),
+                },
+            })
+            .messages(messages)
+            .build()
+            .map_err(llm_err)?;
+
+        let (response, headers) = self.client.create(request).await?;
+        let llm_response = extract_response(response, headers, start.elapsed().as_millis() as u64)?;
+
+        debug!("Structured response ({}): {}", schema.name, llm_response.content);
+        Ok(llm_response)
+    }
 }
@@ -0,0 +1,194 @@
+//! Fitting conversation history into a model's context window.
+
+use fissio_core::{Message, MessageRole, ModelConfig};
+use tracing::{info, warn};
+
+use crate::{LlmMetrics, UnifiedLlmClient};
+
+/// Tokens each chat message costs beyond its content, for the role and framing.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Tokens left free for the response by default.
+const DEFAULT_RESPONSE_RESERVE: usize = 4096;
+/// Most tokens set aside for the summary of older turns.
+const MAX_SUMMARY_TOKENS: usize = 1024;
+
+const SUMMARY_PROMPT: &str = "You compress conversations so they can be continued with less context. Summarize \
+    the conversation below between a user and an assistant. Keep what the user asked for, decisions made, facts \
+    and figures established, names, and open questions; drop pleasantries and repetition. Write it as notes in \
+    the third person. Reply with the summary only.";
+
+/// Estimates how many tokens `text` encodes to.
+///
+/// Splits text roughly the way BPE tokenizers pre-tokenize it: a run of ASCII
+/// letters is one token per eight characters (most words are one token), a run
+/// of digits one per three, a line break one, and any other character one.
+/// Spaces fold into the following word. Close to tiktoken's counts for English
+/// prose and code, and an overestimate for other scripts.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() {
+            let mut len = 1;
+            while chars.next_if(char::is_ascii_alphabetic).is_some() {
+                len += 1;
+            }
+            tokens += 1 + len / 8;
+        } else if c.is_ascii_digit() {
+            let mut len: usize = 1;
+            while chars.next_if(char::is_ascii_digit).is_some() {
+                len += 1;
+            }
+            tokens += len.div_ceil(3);
+        } else if c == '\n' {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            tokens += 1;
+        } else if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens
+}
+
+/// Estimates the tokens a list of chat messages takes, framing included.
+pub fn estimate_message_tokens(messages: &[Message]) -> usize {
+    messages.iter().map(|m| estimate_tokens(&m.content) + MESSAGE_OVERHEAD_TOKENS).sum()
+}
+
+/// History that fits the model's context window.
+#[derive(Debug, Clone)]
+pub struct FittedContext {
+    /// The system prompt, followed by the summary of older turns if one was made.
+    pub system_prompt: String,
+    /// The turns kept verbatim.
+    pub history: Vec<Message>,
+    /// Messages from the start of the history that were summarized or dropped.
+    pub compacted: usize,
+    /// The model that wrote the summary in `system_prompt` and its usage;
+    /// `None` if nothing was summarized or the compacted messages were dropped.
+    pub summary: Option<(String, LlmMetrics)>,
+}
+
+/// Keeps a conversation within the model's [`context_window`](ModelConfig::context_window).
+///
+/// When the system prompt, history, and new message would leave less than the
+/// response reserve, the oldest turns are summarized and the summary appended
+/// to the system prompt; the most recent turns that fit are kept verbatim and
+/// start on a user message. Summaries are written by the summarizer model, a
+/// cheap one ideally, or by the conversation's own model if none is set. If
+/// summarizing fails, the oldest turns are dropped instead.
+pub struct ContextManager {
+    summarizer: Option<UnifiedLlmClient>,
+    response_reserve: usize,
+}
+
+impl ContextManager {
+    pub fn new() -> Self {
+        Self { summarizer: None, response_reserve: DEFAULT_RESPONSE_RESERVE }
+    }
+
+    /// Summarizes older turns with this client instead of the conversation's model.
+    pub fn with_summarizer(mut self, client: UnifiedLlmClient) -> Self {
+        self.summarizer = Some(client);
+        self
+    }
+
+    /// Tokens of the window left free for the response (default 4096).
+    pub fn with_response_reserve(mut self, tokens: usize) -> Self {
+        self.response_reserve = tokens;
+        self
+    }
+
+    /// Fits `history` into `model`'s context window alongside `system_prompt` and `message`.
+    ///
+    /// Returns the history unchanged if the model has no `context_window` or it already fits.
+    pub async fn fit(&self, model: &ModelConfig, system_prompt: &str, history: &[Message], message: &str) -> FittedContext {
+        let unchanged = FittedContext {
+            system_prompt: system_prompt.to_string(),
+            history: history.to_vec(),
+            compacted: 0,
+            summary: None,
+        };
+        let Some(window) = model.context_window else {
+            return unchanged;
+        };
+        let budget = (window as usize).saturating_sub(self.response_reserve);
+        let fixed = estimate_tokens(system_prompt) + estimate_tokens(message) + 2 * MESSAGE_OVERHEAD_TOKENS;
+        if fixed + estimate_message_tokens(history) <= budget {
+            return unchanged;
+        }
+
+        let summary_budget = (budget / 4).min(MAX_SUMMARY_TOKENS);
+        let split = recent_start(history, budget.saturating_sub(fixed + summary_budget));
+        if split == 0 {
+            return unchanged;
+        }
+        let (older, recent) = history.split_at(split);
+
+        let own_model;
+        let client = match &self.summarizer {
+            Some(client) => client,
+            None => {
+                own_model = UnifiedLlmClient::from_config(model);
+                &own_model
+            }
+        };
+        match summarize(client, older).await {
+            Ok((summary, metrics)) => {
+                info!("Summarized {} of {} history messages for {}", older.len(), history.len(), model.id);
+                FittedContext {
+                    system_prompt: format!("{}\n\nSummary of the earlier conversation:\n{}", system_prompt, summary),
+                    history: recent.to_vec(),
+                    compacted: older.len(),
+                    summary: Some((client.model().to_string(), metrics)),
+                }
+            }
+            Err(e) => {
+                warn!("Dropping {} history messages for {}; summary failed: {}", older.len(), model.id, e);
+                FittedContext {
+                    system_prompt: system_prompt.to_string(),
+                    history: recent.to_vec(),
+                    compacted: older.len(),
+                    summary: None,
+                }
+            }
+        }
+    }
+}
+
+impl Default for ContextManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Index of the first message kept: the most recent messages within `budget`
+/// tokens, starting on a user message.
+fn recent_start(history: &[Message], budget: usize) -> usize {
+    let mut used = 0;
+    let mut start = history.len();
+    for (i, message) in history.iter().enumerate().rev() {
+        used += estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS;
+        if used > budget {
+            break;
+        }
+        start = i;
+    }
+    while start < history.len() && history[start].role != MessageRole::User {
+        start += 1;
+    }
+    start
+}
+
+async fn summarize(client: &UnifiedLlmClient, messages: &[Message]) -> Result<(String, LlmMetrics), String> {
+    let transcript = messages
+        .iter()
+        .map(|m| format!("{}: {}", m.role.as_str(), m.content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let response = client.chat(SUMMARY_PROMPT, &transcript).await.map_err(|e| e.to_string())?;
+    if response.content.trim().is_empty() {
+        return Err("the summary was empty".to_string());
+    }
+    Ok((response.content, response.metrics))
+}
@@ -0,0 +1,199 @@
+//! Text embeddings from OpenAI-compatible and Ollama endpoints.
+
+use fissio_core::AgentError;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::headers::ResponseHeaders;
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Texts sent per request when the client doesn't set a batch size.
+pub const DEFAULT_EMBEDDING_BATCH: usize = 64;
+
+/// The API an [`EmbeddingClient`] calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// `POST {base}/embeddings`, as served by OpenAI, vLLM, and LM Studio.
+    OpenAi,
+    /// Ollama's native `POST {host}/api/embed`.
+    Ollama,
+}
+
+/// Turns text into embedding vectors.
+///
+/// ```rust,ignore
+/// use fissio_llm::EmbeddingClient;
+///
+/// let client = EmbeddingClient::openai("text-embedding-3-small");
+/// let vectors = client.embed(&["What is Rust?".to_string()]).await?;
+///
+/// let local = EmbeddingClient::ollama("http://localhost:11434", "nomic-embed-text");
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmbeddingClient {
+    provider: EmbeddingProvider,
+    model: String,
+    base_url: String,
+    api_key: Option<String>,
+    dimensions: Option<usize>,
+    batch_size: usize,
+    http: Client,
+}
+
+#[derive(Serialize)]
+struct OpenAiRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    encoding_format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl EmbeddingClient {
+    /// Creates a client for OpenAI's embeddings API, authenticated with `OPENAI_API_KEY`.
+    pub fn openai(model: impl Into<String>) -> Self {
+        let mut client = Self::new(EmbeddingProvider::OpenAi, OPENAI_BASE_URL, model);
+        client.api_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty());
+        client
+    }
+
+    /// Creates a client for an Ollama instance at `host`, e.g. `http://localhost:11434`.
+    pub fn ollama(host: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::new(EmbeddingProvider::Ollama, host, model)
+    }
+
+    fn new(provider: EmbeddingProvider, base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            dimensions: None,
+            batch_size: DEFAULT_EMBEDDING_BATCH,
+            http: Client::new(),
+        }
+    }
+
+    /// Sends requests to another base URL, such as a vLLM server's `/v1`.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Asks for shortened vectors; supported by OpenAI's `text-embedding-3` models.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets how many texts go in one request.
+    pub fn with_batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    pub fn provider(&self) -> EmbeddingProvider {
+        self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Embeds each text, returning vectors in the same order.
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AgentError> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            let embedded = match self.provider {
+                EmbeddingProvider::OpenAi => self.embed_openai(batch).await?,
+                EmbeddingProvider::Ollama => self.embed_ollama(batch).await?,
+            };
+            if embedded.len() != batch.len() {
+                return Err(AgentError::LlmError(format!(
+                    "Embedding request returned {} vectors for {} texts",
+                    embedded.len(),
+                    batch.len()
+                )));
+            }
+            vectors.extend(embedded);
+        }
+        Ok(vectors)
+    }
+
+    async fn embed_openai(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, AgentError> {
+        let body = OpenAiRequest {
+            model: &self.model,
+            input: batch,
+            encoding_format: "float",
+            dimensions: self.dimensions,
+        };
+        let mut request = self.http.post(format!("{}/embeddings", self.base_url)).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let mut response: OpenAiResponse = send(request).await?;
+        // The API may return embeddings out of order
+        response.data.sort_by_key(|d| d.index);
+        Ok(response.data.into_iter().map(|d| d.embedding).collect())
+    }
+
+    async fn embed_ollama(&self, batch: &[String]) -> Result<Vec<Vec<f32>>, AgentError> {
+        let body = OllamaRequest { model: &self.model, input: batch };
+        let request = self.http.post(format!("{}/api/embed", self.base_url)).json(&body);
+        let response: OllamaResponse = send(request).await?;
+        Ok(response.embeddings)
+    }
+}
+
+/// Sends an embedding request and parses a successful response.
+async fn send<T: serde::de::DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, AgentError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AgentError::LlmError(format!("Embedding request failed: {}", e)))?;
+
+    let status = response.status();
+    let headers = ResponseHeaders::from_headers(response.headers());
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AgentError::LlmError(format!(
+            "Embedding request failed{}: {} - {}",
+            headers.describe(),
+            status,
+            body
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AgentError::LlmError(format!("Failed to parse embedding response: {}", e)))
+}
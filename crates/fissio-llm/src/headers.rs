@@ -0,0 +1,151 @@
+//! Request ids and rate limit quotas that providers report in response headers.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Headers carrying the provider's request id: OpenAI, Anthropic, then Azure.
+const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "request-id", "anthropic-request-id", "apim-request-id"];
+
+/// Quota a provider reported left in its rate limit window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Milliseconds until the request quota resets.
+    pub requests_reset_ms: Option<u64>,
+    /// Milliseconds until the token quota resets.
+    pub tokens_reset_ms: Option<u64>,
+}
+
+impl RateLimitStatus {
+    /// Reads OpenAI's `x-ratelimit-*` or Anthropic's `anthropic-ratelimit-*`
+    /// headers. Returns `None` when the response has neither.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let text = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+        let number = |name: &str| text(name).and_then(|v| v.parse().ok());
+
+        let status = if text("x-ratelimit-remaining-requests").is_some() || text("x-ratelimit-remaining-tokens").is_some() {
+            Self {
+                remaining_requests: number("x-ratelimit-remaining-requests"),
+                remaining_tokens: number("x-ratelimit-remaining-tokens"),
+                requests_reset_ms: text("x-ratelimit-reset-requests").and_then(parse_duration_ms),
+                tokens_reset_ms: text("x-ratelimit-reset-tokens").and_then(parse_duration_ms),
+            }
+        } else {
+            Self {
+                remaining_requests: number("anthropic-ratelimit-requests-remaining"),
+                remaining_tokens: number("anthropic-ratelimit-tokens-remaining"),
+                requests_reset_ms: text("anthropic-ratelimit-requests-reset").and_then(ms_until),
+                tokens_reset_ms: text("anthropic-ratelimit-tokens-reset").and_then(ms_until),
+            }
+        };
+        (status != Self::default()).then_some(status)
+    }
+}
+
+/// What a provider reported about one request in its response headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseHeaders {
+    /// The provider's id for the request, to quote in support tickets.
+    pub request_id: Option<String>,
+    pub rate_limit: Option<RateLimitStatus>,
+}
+
+impl ResponseHeaders {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| headers.get(*name)?.to_str().ok())
+            .map(String::from);
+        Self { request_id, rate_limit: RateLimitStatus::from_headers(headers) }
+    }
+
+    /// Formats the request id for an error message, e.g. ` (request req_123)`.
+    pub(crate) fn describe(&self) -> String {
+        match &self.request_id {
+            Some(id) => format!(" (request {})", id),
+            None => String::new(),
+        }
+    }
+}
+
+/// How long a 429 response asks the caller to wait, from `retry-after` in seconds.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds: f64 = headers.get("retry-after")?.to_str().ok()?.trim().parse().ok()?;
+    (seconds >= 0.0).then(|| Duration::from_secs_f64(seconds))
+}
+
+/// Parses OpenAI's reset durations such as `1s`, `6m0s`, `20ms`, or `1h2m3.5s`.
+fn parse_duration_ms(text: &str) -> Option<u64> {
+    let mut total = 0.0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let value: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let scale = match &rest[..unit] {
+            "ms" => 1.0,
+            "s" => 1_000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            _ => return None,
+        };
+        total += value * scale;
+        rest = &rest[unit..];
+    }
+    Some(total.round() as u64)
+}
+
+/// Milliseconds from now until an RFC 3339 time such as
+/// `2024-05-01T12:00:30Z`, or 0 if it has passed.
+fn ms_until(timestamp: &str) -> Option<u64> {
+    let at = parse_rfc3339_ms(timestamp)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_millis() as i64;
+    Some((at - now).max(0) as u64)
+}
+
+/// Parses an RFC 3339 timestamp into milliseconds since the Unix epoch.
+fn parse_rfc3339_ms(timestamp: &str) -> Option<i64> {
+    let field = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !matches!(timestamp.as_bytes().get(10), Some(b'T' | b't' | b' ')) {
+        return None;
+    }
+
+    let mut rest = &timestamp[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+        let padded = format!("{:0<3}", &fraction[..digits.min(3)]);
+        millis = padded.parse().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset_minutes = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let hours: i64 = rest.get(1..3)?.parse().ok()?;
+            let minutes: i64 = rest.get(4..6)?.parse().ok()?;
+            sign * (hours * 60 + minutes)
+        }
+    };
+
+    // Days since the epoch from a civil date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_minutes * 60;
+    Some(seconds * 1_000 + millis)
+}
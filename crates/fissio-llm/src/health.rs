@@ -0,0 +1,75 @@
+//! Lightweight reachability checks for cloud LLM providers.
+
+use std::time::Duration;
+
+use fissio_core::AgentError;
+use reqwest::Client;
+use serde::Serialize;
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const ANTHROPIC_MODELS_URL: &str = "https://api.anthropic.com/v1/models";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A cloud provider reachable with an API key from the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudProvider {
+    OpenAI,
+    Anthropic,
+}
+
+impl CloudProvider {
+    /// All supported cloud providers.
+    pub const ALL: [CloudProvider; 2] = [CloudProvider::OpenAI, CloudProvider::Anthropic];
+
+    /// Environment variable holding this provider's API key.
+    pub fn api_key_var(&self) -> &'static str {
+        match self {
+            CloudProvider::OpenAI => "OPENAI_API_KEY",
+            CloudProvider::Anthropic => "ANTHROPIC_API_KEY",
+        }
+    }
+
+    /// Returns the API key if one is configured.
+    pub fn api_key(&self) -> Option<String> {
+        std::env::var(self.api_key_var()).ok().filter(|k| !k.is_empty())
+    }
+
+    /// Providers with an API key configured.
+    pub fn configured() -> Vec<CloudProvider> {
+        Self::ALL.into_iter().filter(|p| p.api_key().is_some()).collect()
+    }
+
+    /// Checks that the provider is reachable and accepts the configured key.
+    ///
+    /// Lists models, which is free and does not consume tokens.
+    pub async fn check(&self) -> Result<(), AgentError> {
+        let key = self.api_key().ok_or_else(|| {
+            AgentError::ExternalApi(format!("{} is not set", self.api_key_var()))
+        })?;
+
+        let request = match self {
+            CloudProvider::OpenAI => Client::new().get(OPENAI_MODELS_URL).bearer_auth(key),
+            CloudProvider::Anthropic => Client::new()
+                .get(ANTHROPIC_MODELS_URL)
+                .header("x-api-key", key)
+                .header("anthropic-version", ANTHROPIC_VERSION),
+        };
+
+        let response = request
+            .timeout(HEALTH_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AgentError::ExternalApi(format!("{:?} health check failed: {}", self, e)))?;
+
+        if !response.status().is_success() {
+            return Err(AgentError::ExternalApi(format!(
+                "{:?} health check returned {}",
+                self,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
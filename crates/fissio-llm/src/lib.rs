@@ -5,6 +5,10 @@
 //! - [`UnifiedLlmClient`] — Recommended: auto-routes to correct provider
 //! - [`LlmClient`] — OpenAI-compatible client (also works with Ollama)
 //! - [`AnthropicClient`] — Claude models via Anthropic API
+//! - [`LlmProvider`] — Trait for custom backends, registered in a [`ProviderRegistry`]
+//! - [`EmbeddingClient`] — Text embeddings from OpenAI-compatible or Ollama endpoints
+//! - [`SpeechClient`] — Text-to-speech through OpenAI-compatible speech endpoints
+//! - `MockProvider` — Scripted replies for tests and offline examples (`testing` feature)
 //!
 //! # Quick Start
 //!
@@ -34,10 +38,109 @@
 //!         StreamChunk::Usage { input_tokens, output_tokens } => {
 //!             println!("\nTokens: {}/{}", input_tokens, output_tokens);
 //!         }
+//!         StreamChunk::Progress(status) => eprintln!("[{}]", status),
+//!         StreamChunk::Reasoning(thought) => eprint!("{}", thought),
+//!         _ => {}
 //!     }
 //! }
 //! ```
 //!
+//! # Rate Limiting
+//!
+//! Clients built with [`UnifiedLlmClient::from_config`] honor the model's
+//! `rate_limit`. Limits are shared process-wide per provider, so concurrent
+//! nodes wait for capacity instead of receiving 429s. With the `redis`
+//! feature, [`set_rate_limit_backend`] can share the windows across processes.
+//!
+//! ```rust,ignore
+//! use fissio_llm::{RateLimitConfig, UnifiedLlmClient};
+//!
+//! let client = UnifiedLlmClient::new("gpt-4", None).with_rate_limit(RateLimitConfig {
+//!     requests_per_minute: Some(500),
+//!     tokens_per_minute: Some(200_000),
+//! });
+//! ```
+//!
+//! # Caching
+//!
+//! Attach an [`LlmCache`] to reuse responses for identical non-streaming requests:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use fissio_llm::{SqliteLlmCache, UnifiedLlmClient};
+//!
+//! let cache = Arc::new(SqliteLlmCache::new("data/llm_cache.db")?);
+//! let client = UnifiedLlmClient::new("gpt-4", None).with_cache(cache);
+//! ```
+//!
+//! # Record and Replay
+//!
+//! A [`Cassette`] captures every request/response pair to a JSON file, or
+//! serves recorded responses without calling any provider:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use fissio_llm::{Cassette, UnifiedLlmClient};
+//!
+//! let recording = Arc::new(Cassette::record("cassettes/run.json"));
+//! let client = UnifiedLlmClient::new("gpt-4", None).with_cassette(recording);
+//!
+//! let replay = Arc::new(Cassette::replay("cassettes/run.json")?);
+//! let client = UnifiedLlmClient::new("gpt-4", None).with_cassette(replay);
+//! ```
+//!
+//! # Context Windows
+//!
+//! A [`ContextManager`] keeps history within a model's `context_window`,
+//! summarizing older turns with a cheap model when the conversation outgrows it:
+//!
+//! ```rust,ignore
+//! use fissio_llm::{ContextManager, UnifiedLlmClient};
+//!
+//! let context = ContextManager::new().with_summarizer(UnifiedLlmClient::new("gpt-4o-mini", None));
+//! let fitted = context.fit(&model, system_prompt, &history, message).await;
+//! let stream = client.chat_stream(&fitted.system_prompt, &fitted.history, message).await?;
+//! ```
+//!
+//! # Custom Providers
+//!
+//! Implement [`LlmProvider`] and register it under a scheme; models named
+//! `<scheme>://<model>` are then routed to it:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use fissio_llm::{OpenAiProvider, ProviderRegistry, UnifiedLlmClient};
+//!
+//! // A vLLM server speaks the OpenAI protocol
+//! let vllm = OpenAiProvider::new(Some("http://vllm:8000/v1"));
+//! ProviderRegistry::global().register("vllm", Arc::new(vllm));
+//! let client = UnifiedLlmClient::new("vllm://meta-llama/Llama-3.1-8B-Instruct", None);
+//! ```
+//!
+//! # Structured Output
+//!
+//! [`UnifiedLlmClient::structured`] asks for JSON matching a schema, using
+//! OpenAI's `json_schema` response format (strict by default) or a forced
+//! tool call on Anthropic, and checks the answer before deserializing it:
+//!
+//! ```rust,ignore
+//! use fissio_llm::{OutputSchema, UnifiedLlmClient};
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Ticket { category: String, urgent: bool }
+//!
+//! let schema = OutputSchema::new("ticket", serde_json::json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "category": { "type": "string" },
+//!         "urgent": { "type": "boolean" }
+//!     },
+//!     "required": ["category", "urgent"],
+//!     "additionalProperties": false
+//! }));
+//! let (ticket, metrics) = client.structured::<Ticket>(system, "My invoice was charged twice", &schema).await?;
+//! ```
+//!
 //! # Tool Calling
 //!
 //! ```rust,ignore
@@ -64,12 +167,57 @@
 //! ```
 
 mod anthropic;
+mod balancer;
+mod cache;
+mod cassette;
+#[cfg(feature = "chaos")]
+mod chaos;
 mod client;
+mod context;
+mod embeddings;
+mod headers;
+mod health;
+#[cfg(feature = "testing")]
+mod mock;
+mod observer;
 mod ollama;
+mod provider;
+mod rate_limit;
+#[cfg(feature = "redis")]
+mod redis_rate_limit;
+mod speech;
+mod structured;
 mod unified;
+mod usage;
 
 pub use anthropic::AnthropicClient;
+pub use balancer::{HostBalancer, HostLease};
+pub use cache::{cache_key, InMemoryLlmCache, LlmCache, SqliteLlmCache};
+pub use cassette::{Cassette, CassetteMode};
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosConfig, FaultInjector};
 pub use client::{ChatResponse, LlmClient, LlmMetrics, LlmResponse, LlmStream, StreamChunk};
-pub use fissio_core::{ToolCall, ToolResult, ToolSchema};
-pub use ollama::{discover_models, unload_model, OllamaClient, OllamaMetrics, OllamaMetricsCollector};
+pub use context::{estimate_message_tokens, estimate_tokens, ContextManager, FittedContext};
+pub use embeddings::{EmbeddingClient, EmbeddingProvider, DEFAULT_EMBEDDING_BATCH};
+pub use fissio_core::{
+    AzureOpenAiConfig, Citation, OutputSchema, Provider, RateLimitConfig, ToolCall, ToolResult, ToolSchema,
+};
+pub use headers::{RateLimitStatus, ResponseHeaders};
+pub use health::CloudProvider;
+#[cfg(feature = "testing")]
+pub use mock::{MockProvider, MockRequest};
+pub use observer::{set_call_observer, CallObserver};
+pub use ollama::{
+    discover_models, discover_models_on, load_model, loaded_models, model_sizes, unload_model, OllamaClient, OllamaLoadedModel, OllamaMetrics,
+    OllamaMetricsCollector,
+};
+pub use provider::{AnthropicProvider, LlmProvider, OpenAiProvider, ProviderRegistry};
+pub use rate_limit::{
+    set_rate_limit_backend, LocalRateLimitBackend, RateLimitBackend, RateLimiter, RatePermit,
+};
+#[cfg(feature = "redis")]
+pub use redis_rate_limit::RedisRateLimitBackend;
+pub use speech::{AudioFormat, SpeechClient, DEFAULT_SPEECH_MODEL, DEFAULT_VOICE, MAX_SPEECH_CHARS};
+pub use structured::{parse_structured, with_schema_instructions};
 pub use unified::UnifiedLlmClient;
+pub use usage::{fetch_openai_usage, ProviderUsage};
@@ -0,0 +1,241 @@
+//! Scripted provider for tests and offline examples (`testing` feature).
+
+use std::sync::{Arc, Mutex};
+
+use async_openai::types::{
+    ChatCompletionRequestMessage, ChatCompletionRequestToolMessageContent, ChatCompletionRequestUserMessageContent,
+    ChatCompletionRequestUserMessageContentPart,
+};
+use async_trait::async_trait;
+use fissio_core::{AgentError, Message, ModelConfig, Provider, ToolCall, ToolSchema};
+use futures::stream;
+use serde_json::Value;
+
+use crate::client::{ChatResponse, LlmMetrics, LlmResponse, LlmStream, StreamChunk};
+use crate::provider::{LlmProvider, ProviderRegistry};
+
+/// Reply sent when no rule matches.
+const DEFAULT_FALLBACK: &str = "Mock response.";
+
+/// A request the [`MockProvider`] received.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub model: String,
+    pub system_prompt: String,
+    /// The user input, followed by any tool results in a tool conversation.
+    pub input: String,
+}
+
+enum MockReply {
+    Text(String),
+    ToolCall { name: String, arguments: Value },
+}
+
+struct MockRule {
+    pattern: String,
+    reply: MockReply,
+}
+
+/// An [`LlmProvider`] that answers from a script instead of calling a model.
+///
+/// Rules are checked in order against the system prompt and input; the first
+/// whose pattern appears in either decides the reply. A tool call rule only
+/// applies to tool requests that have no tool results yet, so a worker calls
+/// the tool once and the next matching text rule gives its final answer.
+///
+/// ```rust,ignore
+/// use fissio_llm::MockProvider;
+///
+/// let mock = MockProvider::new()
+///     .tool_call("research assistant", "fetch_url", json!({ "url": "https://example.com" }))
+///     .reply("Summarize", "Rust is a systems language.")
+///     .install("mock");
+/// let model = MockProvider::model("mock", "fast");
+/// // run a pipeline with `model`, then inspect `mock.requests()`
+/// ```
+pub struct MockProvider {
+    rules: Vec<MockRule>,
+    fallback: String,
+    requests: Mutex<Vec<MockRequest>>,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self { rules: Vec::new(), fallback: DEFAULT_FALLBACK.to_string(), requests: Mutex::new(Vec::new()) }
+    }
+
+    /// Replies with `text` when the system prompt or input contains `pattern`.
+    pub fn reply(mut self, pattern: impl Into<String>, text: impl Into<String>) -> Self {
+        self.rules.push(MockRule { pattern: pattern.into(), reply: MockReply::Text(text.into()) });
+        self
+    }
+
+    /// Requests a call to `tool` when a tool request contains `pattern`.
+    pub fn tool_call(mut self, pattern: impl Into<String>, tool: impl Into<String>, arguments: Value) -> Self {
+        let reply = MockReply::ToolCall { name: tool.into(), arguments };
+        self.rules.push(MockRule { pattern: pattern.into(), reply });
+        self
+    }
+
+    /// Sets the reply used when no rule matches.
+    pub fn with_fallback(mut self, text: impl Into<String>) -> Self {
+        self.fallback = text.into();
+        self
+    }
+
+    /// Registers the provider in the global [`ProviderRegistry`] under `scheme`.
+    pub fn install(self, scheme: &str) -> Arc<Self> {
+        let provider = Arc::new(self);
+        ProviderRegistry::global().register(scheme, provider.clone());
+        provider
+    }
+
+    /// A model config routed to the provider installed under `scheme`.
+    pub fn model(scheme: &str, name: &str) -> ModelConfig {
+        ModelConfig {
+            id: name.to_string(),
+            name: name.to_string(),
+            model: name.to_string(),
+            api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::Custom(scheme.to_string())),
+            rate_limit: None,
+            azure: None,
+            region: None,
+            context_window: None,
+            pricing: None,
+        }
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn record(&self, model: &str, system_prompt: &str, input: &str) -> usize {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        requests.push(MockRequest {
+            model: model.to_string(),
+            system_prompt: system_prompt.to_string(),
+            input: input.to_string(),
+        });
+        requests.len()
+    }
+
+    fn matching(&self, system_prompt: &str, input: &str, tools: bool) -> Option<&MockReply> {
+        self.rules
+            .iter()
+            .filter(|rule| tools || matches!(rule.reply, MockReply::Text(_)))
+            .find(|rule| system_prompt.contains(&rule.pattern) || input.contains(&rule.pattern))
+            .map(|rule| &rule.reply)
+    }
+
+    fn text(&self, system_prompt: &str, input: &str) -> String {
+        match self.matching(system_prompt, input, false) {
+            Some(MockReply::Text(text)) => text.clone(),
+            _ => self.fallback.clone(),
+        }
+    }
+}
+
+/// Counts words as a stand-in for tokens.
+fn metrics(prompt: &str, reply: &str) -> LlmMetrics {
+    LlmMetrics {
+        input_tokens: prompt.split_whitespace().count() as u32,
+        output_tokens: reply.split_whitespace().count() as u32,
+        ..Default::default()
+    }
+}
+
+/// Joins the last user message with the tool results after it.
+fn conversation_input(messages: &[ChatCompletionRequestMessage]) -> (String, bool) {
+    let mut parts = Vec::new();
+    let mut has_results = false;
+    for message in messages {
+        match message {
+            ChatCompletionRequestMessage::User(user) => {
+                parts.clear();
+                parts.push(match &user.content {
+                    ChatCompletionRequestUserMessageContent::Text(text) => text.clone(),
+                    ChatCompletionRequestUserMessageContent::Array(content) => content
+                        .iter()
+                        .filter_map(|part| match part {
+                            ChatCompletionRequestUserMessageContentPart::Text(t) => Some(t.text.clone()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                });
+            }
+            ChatCompletionRequestMessage::Tool(tool) => {
+                has_results = true;
+                if let ChatCompletionRequestToolMessageContent::Text(text) = &tool.content {
+                    parts.push(text.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    (parts.join("\n"), has_results)
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn chat(&self, model: &str, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError> {
+        self.record(model, system_prompt, user_input);
+        let content = self.text(system_prompt, user_input);
+        Ok(LlmResponse { metrics: metrics(user_input, &content), content })
+    }
+
+    async fn chat_stream(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        _history: &[Message],
+        user_input: &str,
+    ) -> Result<LlmStream, AgentError> {
+        self.record(model, system_prompt, user_input);
+        let content = self.text(system_prompt, user_input);
+        let usage = metrics(user_input, &content);
+        let mut chunks: Vec<Result<StreamChunk, AgentError>> =
+            content.split_inclusive(' ').map(|word| Ok(StreamChunk::Content(word.to_string()))).collect();
+        chunks.push(Ok(StreamChunk::Usage { input_tokens: usage.input_tokens, output_tokens: usage.output_tokens }));
+        Ok(Box::pin(stream::iter(chunks)))
+    }
+
+    async fn chat_with_tools(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        messages: &[ChatCompletionRequestMessage],
+        _tools: &[ToolSchema],
+        _pending_tool_calls: Option<&[ToolCall]>,
+    ) -> Result<ChatResponse, AgentError> {
+        let (input, has_results) = conversation_input(messages);
+        let count = self.record(model, system_prompt, &input);
+        match self.matching(system_prompt, &input, !has_results) {
+            Some(MockReply::ToolCall { name, arguments }) => {
+                let call = ToolCall { id: format!("call_{}", count), name: name.clone(), arguments: arguments.clone() };
+                Ok(ChatResponse::ToolCalls { calls: vec![call], metrics: metrics(&input, "") })
+            }
+            Some(MockReply::Text(text)) => Ok(ChatResponse::Content(LlmResponse {
+                metrics: metrics(&input, text),
+                content: text.clone(),
+            })),
+            None => Ok(ChatResponse::Content(LlmResponse {
+                metrics: metrics(&input, &self.fallback),
+                content: self.fallback.clone(),
+            })),
+        }
+    }
+}
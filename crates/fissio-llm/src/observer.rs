@@ -0,0 +1,32 @@
+//! Process-wide hook for counting provider calls, e.g. for a metrics exporter.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use fissio_core::AgentError;
+use tracing::warn;
+
+static OBSERVER: OnceLock<Arc<dyn CallObserver>> = OnceLock::new();
+
+/// Receives every request a [`UnifiedLlmClient`](crate::UnifiedLlmClient) sends to a provider.
+///
+/// Cache hits and cassette replays are not requests and are not reported. For
+/// streams, `elapsed` and `error` cover opening the stream, not reading it.
+pub trait CallObserver: Send + Sync {
+    fn on_call(&self, provider: &str, model: &str, elapsed: Duration, error: Option<&AgentError>);
+}
+
+/// Installs the process-wide call observer.
+///
+/// Later calls are ignored, like [`set_rate_limit_backend`](crate::set_rate_limit_backend).
+pub fn set_call_observer(observer: Arc<dyn CallObserver>) {
+    if OBSERVER.set(observer).is_err() {
+        warn!("Call observer already initialized, ignoring replacement");
+    }
+}
+
+pub(crate) fn report<T>(provider: &str, model: &str, elapsed: Duration, result: &Result<T, AgentError>) {
+    if let Some(observer) = OBSERVER.get() {
+        observer.on_call(provider, model, elapsed, result.as_ref().err());
+    }
+}
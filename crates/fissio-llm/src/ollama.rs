@@ -3,14 +3,15 @@
 //! Uses Ollama's native /api/chat endpoint (not OpenAI-compatible) to access
 //! detailed performance metrics like tokens/sec, eval time, and load duration.
 
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 
-use fissio_core::{AgentError, Message, ModelConfig};
+use fissio_core::{AgentError, Message, ModelConfig, Provider};
 use futures::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::StreamChunk;
 
@@ -24,6 +25,27 @@ pub struct OllamaTagsResponse {
 #[derive(Debug, Deserialize)]
 pub struct OllamaModelInfo {
     pub name: String,
+    /// Size of the model's weights in bytes.
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Response from Ollama's /api/ps endpoint.
+#[derive(Debug, Deserialize)]
+pub struct OllamaPsResponse {
+    pub models: Vec<OllamaLoadedModel>,
+}
+
+/// A model Ollama currently has in memory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaLoadedModel {
+    pub name: String,
+    /// Total memory the loaded model uses, in bytes.
+    #[serde(default)]
+    pub size: u64,
+    /// Part of `size` held in GPU memory.
+    #[serde(default)]
+    pub size_vram: u64,
 }
 
 /// Discovers available models from an Ollama instance.
@@ -54,6 +76,13 @@ pub async fn discover_models(ollama_host: &str) -> Result<Vec<ModelConfig>, Agen
                 name: display_name,
                 model: m.name,
                 api_base: Some(format!("{}/v1", ollama_host.trim_end_matches('/'))),
+                mirrors: Vec::new(),
+                provider: Some(Provider::Ollama),
+                rate_limit: None,
+                azure: None,
+                region: Some("local".into()),
+                context_window: None,
+                pricing: None,
             }
         })
         .collect();
@@ -62,6 +91,98 @@ pub async fn discover_models(ollama_host: &str) -> Result<Vec<ModelConfig>, Agen
     Ok(models)
 }
 
+/// Discovers the models on several Ollama instances and merges them.
+///
+/// A model on more than one host is listed once, served from the first host
+/// that has it with the others as [`mirrors`](ModelConfig::mirrors). Hosts
+/// that can't be reached are skipped with a warning; it fails only if none
+/// can be.
+pub async fn discover_models_on(ollama_hosts: &[String]) -> Result<Vec<ModelConfig>, AgentError> {
+    let results = futures::future::join_all(ollama_hosts.iter().map(|host| discover_models(host))).await;
+
+    let mut merged: Vec<ModelConfig> = Vec::new();
+    let (mut reached, mut last_error) = (0, None);
+    for (host, result) in ollama_hosts.iter().zip(results) {
+        let models = match result {
+            Ok(models) => {
+                reached += 1;
+                models
+            }
+            Err(e) => {
+                warn!("Skipping Ollama host {}: {}", host, e);
+                last_error = Some(e);
+                continue;
+            }
+        };
+        for model in models {
+            match merged.iter_mut().find(|m| m.id == model.id) {
+                Some(existing) => existing.mirrors.extend(model.api_base),
+                None => merged.push(model),
+            }
+        }
+    }
+    match last_error {
+        Some(e) if reached == 0 => Err(e),
+        _ => Ok(merged),
+    }
+}
+
+/// Returns the size in bytes of each model Ollama has, keyed by model name.
+pub async fn model_sizes(ollama_host: &str) -> Result<HashMap<String, u64>, AgentError> {
+    let url = format!("{}/api/tags", ollama_host.trim_end_matches('/'));
+    let tags: OllamaTagsResponse = get_json(&url).await?;
+    Ok(tags.models.into_iter().map(|m| (m.name, m.size)).collect())
+}
+
+/// Returns the models Ollama currently has loaded.
+pub async fn loaded_models(ollama_host: &str) -> Result<Vec<OllamaLoadedModel>, AgentError> {
+    let url = format!("{}/api/ps", ollama_host.trim_end_matches('/'));
+    let ps: OllamaPsResponse = get_json(&url).await?;
+    Ok(ps.models)
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, AgentError> {
+    Client::new()
+        .get(url)
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await
+        .map_err(|e| AgentError::LlmError(format!("Ollama request to {} failed: {}", url, e)))?
+        .json()
+        .await
+        .map_err(|e| AgentError::LlmError(format!("Failed to parse Ollama response: {}", e)))
+}
+
+/// Loads a model into Ollama's memory without generating anything.
+///
+/// `keep_alive` is how long Ollama keeps it loaded while idle; `None` uses
+/// Ollama's default.
+pub async fn load_model(
+    ollama_host: &str,
+    model_name: &str,
+    keep_alive: Option<std::time::Duration>,
+) -> Result<(), AgentError> {
+    let url = format!("{}/api/generate", ollama_host.trim_end_matches('/'));
+    let mut body = serde_json::json!({ "model": model_name });
+    if let Some(keep_alive) = keep_alive {
+        body["keep_alive"] = keep_alive.as_secs().into();
+    }
+
+    let response = Client::new()
+        .post(&url)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(300))
+        .send()
+        .await
+        .map_err(|e| AgentError::LlmError(format!("Failed to load model: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AgentError::LlmError(format!("Failed to load model {}: HTTP {}", model_name, response.status())));
+    }
+
+    info!("Loaded model: {}", model_name);
+    Ok(())
+}
+
 /// Unloads a model from Ollama's memory.
 pub async fn unload_model(ollama_host: &str, model_name: &str) -> Result<(), AgentError> {
     let client = Client::new();
@@ -180,6 +301,9 @@ struct OllamaChatResponse {
 #[derive(Debug, Deserialize)]
 struct OllamaResponseMessage {
     content: String,
+    /// Reasoning from thinking models, streamed before the content.
+    #[serde(default)]
+    thinking: String,
 }
 
 /// Client for Ollama's native API with detailed metrics support.
@@ -321,6 +445,9 @@ impl OllamaClient {
                         }
 
                         if let Some(msg) = resp.message {
+                            if !msg.thinking.is_empty() {
+                                return Some(Ok(StreamChunk::Reasoning(msg.thinking)));
+                            }
                             if !msg.content.is_empty() {
                                 return Some(Ok(StreamChunk::Content(msg.content)));
                             }
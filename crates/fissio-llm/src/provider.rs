@@ -0,0 +1,326 @@
+//! Provider backends behind [`UnifiedLlmClient`](crate::UnifiedLlmClient).
+//!
+//! Every backend implements [`LlmProvider`]. OpenAI-compatible endpoints and
+//! Anthropic are built in; other backends (internal gateways, vLLM, a
+//! llama.cpp server) can be registered in the [`ProviderRegistry`] under a
+//! scheme and selected by prefixing a model name with `<scheme>://`:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use fissio_llm::{ProviderRegistry, UnifiedLlmClient};
+//!
+//! ProviderRegistry::global().register("gateway", Arc::new(MyGatewayProvider::new()));
+//! let client = UnifiedLlmClient::new("gateway://gpt-4o", None);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use async_openai::types::ChatCompletionRequestMessage;
+use async_trait::async_trait;
+use fissio_core::{AgentError, AzureOpenAiConfig, Message, OutputSchema, Provider, ToolCall, ToolSchema};
+
+use crate::anthropic::{AnthropicClient, AnthropicToolMessage};
+use crate::client::{ChatResponse, LlmClient, LlmResponse, LlmStream};
+use crate::structured::with_schema_instructions;
+
+/// Separates a registered provider scheme from the model name.
+const SCHEME_SEPARATOR: &str = "://";
+
+/// Ollama's OpenAI-compatible endpoint when a model doesn't set `api_base`.
+const DEFAULT_OLLAMA_API_BASE: &str = "http://localhost:11434/v1";
+
+static GLOBAL: OnceLock<ProviderRegistry> = OnceLock::new();
+
+/// A chat completion backend.
+///
+/// Tool conversations use OpenAI-format messages; backends with a different
+/// wire format convert them, using `pending_tool_calls` to rebuild the
+/// assistant turn that requested the tools.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short identifier, used as the rate limit key when there is no endpoint.
+    fn name(&self) -> &str;
+
+    /// The endpoint requests go to, if not the provider default.
+    fn endpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Sends a non-streaming chat request.
+    async fn chat(&self, model: &str, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError>;
+
+    /// Sends a chat request with history and returns a stream of chunks.
+    async fn chat_stream(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        history: &[Message],
+        user_input: &str,
+    ) -> Result<LlmStream, AgentError>;
+
+    /// Sends a chat request with tools and returns content or tool calls.
+    async fn chat_with_tools(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        messages: &[ChatCompletionRequestMessage],
+        tools: &[ToolSchema],
+        pending_tool_calls: Option<&[ToolCall]>,
+    ) -> Result<ChatResponse, AgentError>;
+
+    /// Sends a chat request whose answer must be JSON matching `schema`.
+    ///
+    /// Backends without native structured output get the schema in the
+    /// system prompt; the caller validates the response either way.
+    async fn chat_structured(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        self.chat(model, &with_schema_instructions(system_prompt, schema), user_input).await
+    }
+}
+
+/// OpenAI, Azure OpenAI, or any OpenAI-compatible endpoint (including Ollama's `/v1`).
+pub struct OpenAiProvider {
+    api_base: Option<String>,
+    azure: Option<AzureOpenAiConfig>,
+}
+
+impl OpenAiProvider {
+    /// Targets the OpenAI API, or a compatible endpoint at `api_base`.
+    pub fn new(api_base: Option<&str>) -> Self {
+        Self { api_base: api_base.map(String::from), azure: None }
+    }
+
+    /// Targets an Azure OpenAI deployment.
+    pub fn azure(azure: AzureOpenAiConfig) -> Self {
+        Self { api_base: None, azure: Some(azure) }
+    }
+
+    fn client(&self, model: &str) -> LlmClient {
+        match &self.azure {
+            Some(azure) => LlmClient::azure(model, azure),
+            None => LlmClient::new(model, self.api_base.as_deref()),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        if self.azure.is_some() {
+            "azure"
+        } else {
+            "openai"
+        }
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        match &self.azure {
+            Some(azure) => Some(format!("{}/{}", azure.endpoint, azure.deployment)),
+            None => self.api_base.clone(),
+        }
+    }
+
+    async fn chat(&self, model: &str, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError> {
+        self.client(model).chat(system_prompt, user_input).await
+    }
+
+    async fn chat_stream(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        history: &[Message],
+        user_input: &str,
+    ) -> Result<LlmStream, AgentError> {
+        self.client(model).chat_stream(system_prompt, history, user_input).await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        messages: &[ChatCompletionRequestMessage],
+        tools: &[ToolSchema],
+        _pending_tool_calls: Option<&[ToolCall]>,
+    ) -> Result<ChatResponse, AgentError> {
+        self.client(model).chat_with_tools(system_prompt, messages, tools).await
+    }
+
+    async fn chat_structured(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        self.client(model).chat_json_schema(system_prompt, user_input, schema).await
+    }
+}
+
+/// Anthropic's Messages API, keyed by `ANTHROPIC_API_KEY`.
+pub struct AnthropicProvider;
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn chat(&self, model: &str, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError> {
+        AnthropicClient::new(model).chat(system_prompt, user_input).await
+    }
+
+    async fn chat_stream(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        history: &[Message],
+        user_input: &str,
+    ) -> Result<LlmStream, AgentError> {
+        AnthropicClient::new(model).chat_stream(system_prompt, history, user_input).await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        messages: &[ChatCompletionRequestMessage],
+        tools: &[ToolSchema],
+        pending_tool_calls: Option<&[ToolCall]>,
+    ) -> Result<ChatResponse, AgentError> {
+        let messages = to_anthropic_messages(messages, pending_tool_calls)?;
+        AnthropicClient::new(model).chat_with_tools(system_prompt, messages, tools).await
+    }
+
+    async fn chat_structured(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        AnthropicClient::new(model).chat_json_schema(system_prompt, user_input, schema).await
+    }
+}
+
+/// Custom providers by scheme.
+#[derive(Default)]
+pub struct ProviderRegistry {
+    providers: RwLock<HashMap<String, Arc<dyn LlmProvider>>>,
+}
+
+impl ProviderRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process-wide registry consulted by [`UnifiedLlmClient::new`](crate::UnifiedLlmClient::new).
+    pub fn global() -> &'static ProviderRegistry {
+        GLOBAL.get_or_init(ProviderRegistry::new)
+    }
+
+    /// Registers `provider` for models named `<scheme>://<model>`.
+    ///
+    /// If a provider with the same scheme already exists, it will be replaced.
+    pub fn register(&self, scheme: &str, provider: Arc<dyn LlmProvider>) {
+        self.providers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(scheme.to_string(), provider);
+    }
+
+    /// Gets a provider by scheme.
+    pub fn get(&self, scheme: &str) -> Option<Arc<dyn LlmProvider>> {
+        self.providers.read().unwrap_or_else(|e| e.into_inner()).get(scheme).cloned()
+    }
+
+    /// Returns the provider for an explicit [`Provider`], or `None` if a custom one isn't registered.
+    pub fn for_provider(&self, provider: &Provider, api_base: Option<&str>) -> Option<Arc<dyn LlmProvider>> {
+        match provider {
+            Provider::OpenAI => Some(Arc::new(OpenAiProvider::new(api_base))),
+            Provider::Anthropic => Some(Arc::new(AnthropicProvider)),
+            Provider::Ollama => Some(Arc::new(OpenAiProvider::new(Some(
+                api_base.unwrap_or(DEFAULT_OLLAMA_API_BASE),
+            )))),
+            Provider::Custom(scheme) => self.get(scheme),
+        }
+    }
+
+    /// Splits a `<scheme>://<model>` name into its registered provider and the bare model name.
+    ///
+    /// Returns `None` when the name has no prefix or the scheme is not registered.
+    pub fn resolve<'a>(&self, model: &'a str) -> Option<(Arc<dyn LlmProvider>, &'a str)> {
+        let (scheme, name) = model.split_once(SCHEME_SEPARATOR)?;
+        Some((self.get(scheme)?, name))
+    }
+}
+
+/// Converts OpenAI-format messages to Anthropic format.
+fn to_anthropic_messages(
+    messages: &[ChatCompletionRequestMessage],
+    pending_tool_calls: Option<&[ToolCall]>,
+) -> Result<Vec<AnthropicToolMessage>, AgentError> {
+    let mut result = Vec::new();
+    let mut tool_results: Vec<(String, String)> = Vec::new();
+
+    for msg in messages {
+        match msg {
+            ChatCompletionRequestMessage::User(user_msg) => {
+                // Flush any pending tool results first
+                if !tool_results.is_empty() {
+                    // Add assistant message with tool_use blocks before tool results
+                    if let Some(calls) = pending_tool_calls {
+                        result.push(AnthropicToolMessage::assistant_tool_use(calls));
+                    }
+                    result.push(AnthropicToolMessage::tool_results(&tool_results));
+                    tool_results.clear();
+                }
+
+                // Extract text content
+                let text = match &user_msg.content {
+                    async_openai::types::ChatCompletionRequestUserMessageContent::Text(t) => t.clone(),
+                    async_openai::types::ChatCompletionRequestUserMessageContent::Array(parts) => {
+                        parts.iter().filter_map(|p| {
+                            if let async_openai::types::ChatCompletionRequestUserMessageContentPart::Text(t) = p {
+                                Some(t.text.clone())
+                            } else {
+                                None
+                            }
+                        }).collect::<Vec<_>>().join("\n")
+                    }
+                };
+                result.push(AnthropicToolMessage::user(&text));
+            }
+            ChatCompletionRequestMessage::Tool(tool_msg) => {
+                // Collect tool results to batch them
+                let id = tool_msg.tool_call_id.clone();
+                let content = match &tool_msg.content {
+                    async_openai::types::ChatCompletionRequestToolMessageContent::Text(t) => t.clone(),
+                    async_openai::types::ChatCompletionRequestToolMessageContent::Array(parts) => {
+                        parts.iter().map(|async_openai::types::ChatCompletionRequestToolMessageContentPart::Text(t)| {
+                            t.text.clone()
+                        }).collect::<Vec<_>>().join("\n")
+                    }
+                };
+                tool_results.push((id, content));
+            }
+            _ => {} // Skip system and other message types
+        }
+    }
+
+    // Flush any remaining tool results
+    if !tool_results.is_empty() {
+        if let Some(calls) = pending_tool_calls {
+            result.push(AnthropicToolMessage::assistant_tool_use(calls));
+        }
+        result.push(AnthropicToolMessage::tool_results(&tool_results));
+    }
+
+    Ok(result)
+}
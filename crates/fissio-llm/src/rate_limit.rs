@@ -0,0 +1,271 @@
+//! Client-side rate limiting shared by every request to the same provider.
+//!
+//! Each provider key tracks a sliding one-minute window of requests and
+//! tokens. Callers wait until the window has room, so parallel nodes queue up
+//! instead of tripping provider 429s.
+//!
+//! Window state lives in a [`RateLimitBackend`]. The default keeps it in
+//! process memory; replicas sharing one provider account can install a shared
+//! backend (such as the Redis backend behind the `redis` feature) with
+//! [`set_rate_limit_backend`] so the budget is enforced across all of them.
+//!
+//! Providers also report the quota left in their own window in response
+//! headers. A limiter told of it through [`RateLimiter::observe`] holds back
+//! requests the reported quota can't cover until the provider's window resets.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use fissio_core::RateLimitConfig;
+use tracing::{debug, warn};
+
+use crate::headers::RateLimitStatus;
+
+pub(crate) const WINDOW: Duration = Duration::from_secs(60);
+
+/// Rough characters-per-token ratio used to estimate prompt size before sending.
+const CHARS_PER_TOKEN: usize = 4;
+
+static BACKEND: OnceLock<Arc<dyn RateLimitBackend>> = OnceLock::new();
+
+/// The latest quota each provider reported, by limiter key. Kept in process
+/// even with a shared backend, since every replica sees the headers itself.
+static REPORTED: OnceLock<Mutex<HashMap<String, Reported>>> = OnceLock::new();
+
+/// A provider's [`RateLimitStatus`] and when it was received.
+struct Reported {
+    status: RateLimitStatus,
+    at: Instant,
+}
+
+impl Reported {
+    /// Returns how long until the provider's quota covers a request of
+    /// `tokens`, or `None` if it does now or the reset time is unknown.
+    fn wait_time(&self, now: Instant, tokens: u32) -> Option<Duration> {
+        let until_reset = |reset_ms: Option<u64>| {
+            let reset = self.at + Duration::from_millis(reset_ms?);
+            Some(reset.saturating_duration_since(now)).filter(|wait| !wait.is_zero())
+        };
+        let requests = match self.status.remaining_requests {
+            Some(0) => until_reset(self.status.requests_reset_ms),
+            _ => None,
+        };
+        let tokens = match self.status.remaining_tokens {
+            Some(remaining) if remaining < tokens as u64 => until_reset(self.status.tokens_reset_ms),
+            _ => None,
+        };
+        requests.max(tokens)
+    }
+
+    /// Counts a request against the reported quota until the next report replaces it.
+    fn spend(&mut self, tokens: u32) {
+        let status = &mut self.status;
+        status.remaining_requests = status.remaining_requests.map(|n| n.saturating_sub(1));
+        status.remaining_tokens = status.remaining_tokens.map(|n| n.saturating_sub(tokens as u64));
+    }
+}
+
+fn reported() -> std::sync::MutexGuard<'static, HashMap<String, Reported>> {
+    REPORTED.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Storage for rate limit windows, keyed by provider.
+#[async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    /// Reserves room for a request of `tokens` under `key`.
+    ///
+    /// Returns a reservation id on success, or how long to wait before trying again.
+    async fn try_reserve(
+        &self,
+        key: &str,
+        limits: RateLimitConfig,
+        tokens: u32,
+    ) -> Result<String, Duration>;
+
+    /// Replaces the token count of a reservation with the actual usage.
+    fn settle(&self, key: &str, reservation: &str, actual_tokens: u32);
+}
+
+/// Installs the process-wide backend used by [`RateLimiter::shared`].
+///
+/// Must be called before the first rate-limited request; later calls are ignored.
+pub fn set_rate_limit_backend(backend: Arc<dyn RateLimitBackend>) {
+    if BACKEND.set(backend).is_err() {
+        warn!("Rate limit backend already initialized, ignoring replacement");
+    }
+}
+
+fn backend() -> Arc<dyn RateLimitBackend> {
+    BACKEND.get_or_init(|| Arc::new(LocalRateLimitBackend::new())).clone()
+}
+
+struct Entry {
+    id: u64,
+    at: Instant,
+    tokens: u32,
+}
+
+#[derive(Default)]
+struct WindowState {
+    entries: VecDeque<Entry>,
+    next_id: u64,
+}
+
+impl WindowState {
+    fn prune(&mut self, now: Instant) {
+        while self.entries.front().is_some_and(|e| now.duration_since(e.at) >= WINDOW) {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Returns how long to wait before a request of `tokens` fits, or `None` if it fits now.
+    ///
+    /// A request larger than the whole token budget is let through once the window is empty.
+    fn wait_time(&self, limits: RateLimitConfig, now: Instant, tokens: u32) -> Option<Duration> {
+        let used: u64 = self.entries.iter().map(|e| e.tokens as u64).sum();
+        let requests_full = limits
+            .requests_per_minute
+            .is_some_and(|rpm| self.entries.len() >= rpm as usize);
+        let tokens_full = limits
+            .tokens_per_minute
+            .is_some_and(|tpm| used > 0 && used + tokens as u64 > tpm as u64);
+
+        if !requests_full && !tokens_full {
+            return None;
+        }
+        let oldest = self.entries.front()?;
+        Some((oldest.at + WINDOW).saturating_duration_since(now))
+    }
+}
+
+/// In-process windows, one per key.
+#[derive(Default)]
+pub struct LocalRateLimitBackend {
+    windows: Mutex<HashMap<String, WindowState>>,
+}
+
+impl LocalRateLimitBackend {
+    /// Creates a backend with no recorded requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, WindowState>> {
+        self.windows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for LocalRateLimitBackend {
+    async fn try_reserve(
+        &self,
+        key: &str,
+        limits: RateLimitConfig,
+        tokens: u32,
+    ) -> Result<String, Duration> {
+        let mut windows = self.lock();
+        let state = windows.entry(key.to_string()).or_default();
+        let now = Instant::now();
+        state.prune(now);
+        if let Some(wait) = state.wait_time(limits, now, tokens) {
+            return Err(wait);
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.entries.push_back(Entry { id, at: now, tokens });
+        Ok(id.to_string())
+    }
+
+    fn settle(&self, key: &str, reservation: &str, actual_tokens: u32) {
+        let Ok(id) = reservation.parse::<u64>() else { return };
+        let mut windows = self.lock();
+        if let Some(entry) = windows
+            .get_mut(key)
+            .and_then(|state| state.entries.iter_mut().find(|e| e.id == id))
+        {
+            entry.tokens = actual_tokens;
+        }
+    }
+}
+
+/// Sliding-window limiter for requests and tokens per minute.
+pub struct RateLimiter {
+    key: String,
+    limits: RateLimitConfig,
+    backend: Arc<dyn RateLimitBackend>,
+}
+
+impl RateLimiter {
+    /// Creates a standalone limiter with its own in-process window.
+    pub fn new(limits: RateLimitConfig) -> Self {
+        Self {
+            key: String::new(),
+            limits,
+            backend: Arc::new(LocalRateLimitBackend::new()),
+        }
+    }
+
+    /// Returns a limiter for `key` backed by the process-wide backend.
+    ///
+    /// Every limiter with the same key shares one window. Each checks the
+    /// window against its own `limits`, so models configured with a tighter
+    /// budget wait sooner than others on the same provider.
+    pub fn shared(key: &str, limits: RateLimitConfig) -> Arc<RateLimiter> {
+        Arc::new(RateLimiter { key: key.to_string(), limits, backend: backend() })
+    }
+
+    /// Waits until a request estimated at `estimated_tokens` fits in the window, then reserves it.
+    pub async fn acquire(self: &Arc<Self>, estimated_tokens: u32) -> RatePermit {
+        loop {
+            if let Some(wait) = self.reported_wait(estimated_tokens) {
+                debug!("Provider quota exhausted, waiting {}ms", wait.as_millis());
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+            match self.backend.try_reserve(&self.key, self.limits, estimated_tokens).await {
+                Ok(reservation) => {
+                    if let Some(entry) = reported().get_mut(&self.key) {
+                        entry.spend(estimated_tokens);
+                    }
+                    return RatePermit { limiter: self.clone(), reservation };
+                }
+                Err(wait) => {
+                    debug!("Rate limit reached, waiting {}ms", wait.as_millis());
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+
+    /// Records the quota the provider reported in a response's headers.
+    pub fn observe(&self, status: RateLimitStatus) {
+        reported().insert(self.key.clone(), Reported { status, at: Instant::now() });
+    }
+
+    /// Returns how long until the provider's last reported quota covers a request of `tokens`.
+    fn reported_wait(&self, tokens: u32) -> Option<Duration> {
+        reported().get(&self.key)?.wait_time(Instant::now(), tokens)
+    }
+}
+
+/// A reserved slot in a [`RateLimiter`] window.
+pub struct RatePermit {
+    limiter: Arc<RateLimiter>,
+    reservation: String,
+}
+
+impl RatePermit {
+    /// Replaces the estimated token count with the actual usage reported by the provider.
+    pub fn settle(&self, actual_tokens: u32) {
+        self.limiter
+            .backend
+            .settle(&self.limiter.key, &self.reservation, actual_tokens);
+    }
+}
+
+/// Estimates the token count of a prompt from its length in characters.
+pub(crate) fn estimate_tokens(chars: usize) -> u32 {
+    (chars / CHARS_PER_TOKEN).try_into().unwrap_or(u32::MAX)
+}
@@ -0,0 +1,137 @@
+//! Redis-backed rate limit windows shared by every replica.
+//!
+//! Each key is a sorted set of reservations scored by their start time in
+//! milliseconds, with members of the form `{id}:{tokens}`. Reservation and
+//! settlement run as Lua scripts so concurrent replicas never over-commit the
+//! window, and timestamps come from the Redis server clock.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fissio_core::{AgentError, RateLimitConfig};
+use redis::aio::ConnectionManager;
+use redis::Script;
+use tracing::warn;
+
+use crate::rate_limit::{RateLimitBackend, WINDOW};
+
+const KEY_PREFIX: &str = "fissio:ratelimit:";
+
+const RESERVE_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+local window = tonumber(ARGV[5])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+local entries = redis.call('ZRANGE', KEYS[1], 0, -1, 'WITHSCORES')
+local count = #entries / 2
+local used = 0
+for i = 1, #entries, 2 do
+  used = used + tonumber(string.match(entries[i], ':(%d+)$'))
+end
+local rpm = tonumber(ARGV[1])
+local tpm = tonumber(ARGV[2])
+local tokens = tonumber(ARGV[3])
+if (rpm > 0 and count >= rpm) or (tpm > 0 and used > 0 and used + tokens > tpm) then
+  return math.max(1, tonumber(entries[2]) + window - now)
+end
+redis.call('ZADD', KEYS[1], now, ARGV[4])
+redis.call('PEXPIRE', KEYS[1], window)
+return -1
+"#;
+
+const SETTLE_SCRIPT: &str = r#"
+local score = redis.call('ZSCORE', KEYS[1], ARGV[1])
+if score then
+  redis.call('ZREM', KEYS[1], ARGV[1])
+  redis.call('ZADD', KEYS[1], score, ARGV[2])
+end
+return 0
+"#;
+
+/// Rate limit windows stored in Redis.
+///
+/// Redis errors fail open: the request proceeds unthrottled and a warning is
+/// logged, so a Redis outage never blocks LLM calls.
+pub struct RedisRateLimitBackend {
+    conn: ConnectionManager,
+    reserve: Script,
+    settle: Script,
+    nonce: u64,
+    next_id: AtomicU64,
+}
+
+impl RedisRateLimitBackend {
+    /// Connects to the Redis server at `url` (e.g. `redis://localhost:6379`).
+    pub async fn connect(url: &str) -> Result<Self, AgentError> {
+        let client = redis::Client::open(url).map_err(redis_err)?;
+        let conn = ConnectionManager::new(client).await.map_err(redis_err)?;
+        Ok(Self {
+            conn,
+            reserve: Script::new(RESERVE_SCRIPT),
+            settle: Script::new(SETTLE_SCRIPT),
+            nonce: RandomState::new().hash_one(std::process::id()),
+            next_id: AtomicU64::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for RedisRateLimitBackend {
+    async fn try_reserve(
+        &self,
+        key: &str,
+        limits: RateLimitConfig,
+        tokens: u32,
+    ) -> Result<String, Duration> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let member = format!("{:016x}-{}:{}", self.nonce, id, tokens);
+        let result: redis::RedisResult<i64> = self
+            .reserve
+            .key(format!("{}{}", KEY_PREFIX, key))
+            .arg(limits.requests_per_minute.unwrap_or(0))
+            .arg(limits.tokens_per_minute.unwrap_or(0))
+            .arg(tokens)
+            .arg(&member)
+            .arg(WINDOW.as_millis() as u64)
+            .invoke_async(&mut self.conn.clone())
+            .await;
+
+        match result {
+            Ok(wait) if wait >= 0 => Err(Duration::from_millis(wait as u64)),
+            Ok(_) => Ok(member),
+            Err(e) => {
+                warn!("Redis rate limit check failed, proceeding unthrottled: {}", e);
+                Ok(String::new())
+            }
+        }
+    }
+
+    fn settle(&self, key: &str, reservation: &str, actual_tokens: u32) {
+        let Some((id, _)) = reservation.rsplit_once(':') else { return };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else { return };
+
+        let script = self.settle.clone();
+        let key = format!("{}{}", KEY_PREFIX, key);
+        let reservation = reservation.to_string();
+        let settled = format!("{}:{}", id, actual_tokens);
+        let mut conn = self.conn.clone();
+        handle.spawn(async move {
+            let result: redis::RedisResult<i64> = script
+                .key(key)
+                .arg(reservation)
+                .arg(settled)
+                .invoke_async(&mut conn)
+                .await;
+            if let Err(e) = result {
+                warn!("Failed to settle Redis rate limit reservation: {}", e);
+            }
+        });
+    }
+}
+
+fn redis_err(e: redis::RedisError) -> AgentError {
+    AgentError::ExternalApi(format!("Redis error: {}", e))
+}
@@ -0,0 +1,221 @@
+//! Text-to-speech through OpenAI's `audio/speech` API or a compatible server.
+
+use fissio_core::AgentError;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::headers::ResponseHeaders;
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Speech model used when none is given.
+pub const DEFAULT_SPEECH_MODEL: &str = "tts-1";
+
+/// Voice used when none is given.
+pub const DEFAULT_VOICE: &str = "alloy";
+
+/// Longest text the API accepts in one request; longer text is split.
+pub const MAX_SPEECH_CHARS: usize = 4096;
+
+/// Encoding of synthesized audio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+    /// Raw 24 kHz 16-bit mono samples.
+    Pcm,
+}
+
+impl AudioFormat {
+    /// The `response_format` value sent to the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Pcm => "pcm",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Pcm => "audio/pcm",
+        }
+    }
+
+    /// Whether audio from several requests plays back as one clip when concatenated.
+    fn concatenates(&self) -> bool {
+        !matches!(self, AudioFormat::Flac | AudioFormat::Wav)
+    }
+}
+
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'static str,
+}
+
+/// Turns text into spoken audio.
+///
+/// ```rust,ignore
+/// use fissio_llm::{AudioFormat, SpeechClient};
+///
+/// let client = SpeechClient::openai("tts-1").with_voice("nova");
+/// let mp3 = client.synthesize("Your order has shipped.").await?;
+///
+/// let local = SpeechClient::openai("kokoro").with_base_url("http://localhost:8880/v1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpeechClient {
+    model: String,
+    voice: String,
+    format: AudioFormat,
+    base_url: String,
+    api_key: Option<String>,
+    http: Client,
+}
+
+impl SpeechClient {
+    /// Creates a client for OpenAI's speech API, authenticated with `OPENAI_API_KEY`.
+    pub fn openai(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            voice: DEFAULT_VOICE.to_string(),
+            format: AudioFormat::default(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            api_key: std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty()),
+            http: Client::new(),
+        }
+    }
+
+    /// Sends requests to another base URL, such as a local speech server's `/v1`.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = voice.into();
+        self
+    }
+
+    pub fn with_format(mut self, format: AudioFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn voice(&self) -> &str {
+        &self.voice
+    }
+
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Synthesizes `text` as audio in the client's format.
+    ///
+    /// Text over [`MAX_SPEECH_CHARS`] is split between sentences and the parts
+    /// are joined, which FLAC and WAV can't be; those fail on long text instead.
+    pub async fn synthesize(&self, text: &str) -> Result<Vec<u8>, AgentError> {
+        let parts = split_text(text.trim(), MAX_SPEECH_CHARS);
+        if parts.is_empty() {
+            return Err(AgentError::LlmError("Nothing to synthesize".into()));
+        }
+        if parts.len() > 1 && !self.format.concatenates() {
+            return Err(AgentError::LlmError(format!(
+                "Text of {} characters is too long for one {} clip; the limit is {}",
+                text.chars().count(),
+                self.format.as_str(),
+                MAX_SPEECH_CHARS
+            )));
+        }
+
+        let mut audio = Vec::new();
+        for part in &parts {
+            audio.extend(self.synthesize_part(part).await?);
+        }
+        debug!("Synthesized {} characters to {} bytes of {}", text.len(), audio.len(), self.format.as_str());
+        Ok(audio)
+    }
+
+    async fn synthesize_part(&self, input: &str) -> Result<Vec<u8>, AgentError> {
+        let body = SpeechRequest { model: &self.model, input, voice: &self.voice, response_format: self.format.as_str() };
+        let mut request = self.http.post(format!("{}/audio/speech", self.base_url)).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AgentError::LlmError(format!("Speech request failed: {}", e)))?;
+
+        let status = response.status();
+        let headers = ResponseHeaders::from_headers(response.headers());
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AgentError::LlmError(format!(
+                "Speech request failed{}: {} - {}",
+                headers.describe(),
+                status,
+                body
+            )));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AgentError::LlmError(format!("Failed to read speech response: {}", e)))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Splits text into parts of at most `max` characters, preferring to break
+/// after a sentence, then at whitespace.
+fn split_text(text: &str, max: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some((limit, _)) = rest.char_indices().nth(max) else {
+            parts.push(rest);
+            break;
+        };
+        let window = &rest[..limit];
+        let cut = window
+            .rfind(['.', '!', '?', '\n'])
+            .map(|i| i + 1)
+            .or_else(|| window.rfind(char::is_whitespace))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        parts.push(rest[..cut].trim());
+        rest = rest[cut..].trim_start();
+    }
+    parts.retain(|p| !p.is_empty());
+    parts
+}
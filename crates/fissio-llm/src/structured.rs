@@ -0,0 +1,65 @@
+//! Structured output: responses that conform to a JSON Schema.
+//!
+//! OpenAI-compatible endpoints receive the schema as a `json_schema` response
+//! format, and Anthropic is forced to call a tool whose input is the schema.
+//! Other providers get the schema in the system prompt. Whichever way the
+//! response was produced, [`parse_structured`] checks it against the schema
+//! before deserializing it.
+
+use fissio_core::{AgentError, OutputSchema};
+use serde::de::DeserializeOwned;
+
+/// Appends an instruction to answer with JSON matching `schema` to a system prompt.
+///
+/// Used for providers without native structured output, and for tool loops
+/// whose final answer must match a schema.
+pub fn with_schema_instructions(system_prompt: &str, schema: &OutputSchema) -> String {
+    let instructions = format!(
+        "Respond with only a JSON value that matches this JSON Schema, with no other text:\n{}",
+        schema.schema
+    );
+    if system_prompt.is_empty() {
+        return instructions;
+    }
+    format!("{}\n\n{}", system_prompt, instructions)
+}
+
+/// Parses a response as JSON, checks it against `schema`, and deserializes it.
+///
+/// A markdown code fence around the JSON is ignored. Every schema violation
+/// is listed in the error.
+pub fn parse_structured<T: DeserializeOwned>(content: &str, schema: &OutputSchema) -> Result<T, AgentError> {
+    let value: serde_json::Value = serde_json::from_str(unfence(content)).map_err(|e| {
+        AgentError::ParseError(format!("{} response is not JSON: {} - content: {}", schema.name, e, content))
+    })?;
+
+    let validator = jsonschema::validator_for(&schema.schema)
+        .map_err(|e| AgentError::ParseError(format!("invalid output schema '{}': {}", schema.name, e)))?;
+    let violations: Vec<String> = validator
+        .iter_errors(&value)
+        .map(|e| match e.instance_path.to_string() {
+            path if path.is_empty() => e.to_string(),
+            path => format!("{}: {}", path, e),
+        })
+        .collect();
+    if !violations.is_empty() {
+        return Err(AgentError::ParseError(format!(
+            "{} response does not match its schema: {}",
+            schema.name,
+            violations.join("; ")
+        )));
+    }
+
+    serde_json::from_value(value).map_err(|e| AgentError::ParseError(format!("Failed to parse {}: {}", schema.name, e)))
+}
+
+/// Strips a markdown code fence from around a JSON response.
+fn unfence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|s| s.strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(trimmed)
+}
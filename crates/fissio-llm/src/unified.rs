@@ -1,77 +1,404 @@
 //! Unified LLM client that routes to the appropriate provider based on model name.
+//!
+//! Adds rate limiting, caching, and cassette record/replay on top of any [`LlmProvider`].
 
-use fissio_core::{AgentError, Message, ToolCall, ToolSchema};
-use async_openai::types::ChatCompletionRequestMessage;
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::anthropic::{AnthropicClient, AnthropicToolMessage};
-use crate::client::{ChatResponse, LlmClient};
-use crate::{LlmResponse, LlmStream};
+use fissio_core::{
+    AgentError, AzureOpenAiConfig, Message, ModelConfig, OutputSchema, RateLimitConfig, ToolCall, ToolSchema,
+};
+use async_openai::types::ChatCompletionRequestMessage;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use tracing::field::Empty;
+use tracing::{debug, warn, Span};
 
-/// Provider type determined from model name.
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum ProviderType {
-    OpenAI,
-    Anthropic,
-}
+use crate::cache::{cache_key, LlmCache};
+use crate::cassette::{CallKind, Cassette, CassetteMode, RecordedChunk};
+use crate::client::{ChatResponse, LlmClient, LlmMetrics};
+use crate::observer;
+use crate::provider::{AnthropicProvider, LlmProvider, OpenAiProvider, ProviderRegistry};
+use crate::rate_limit::{estimate_tokens, RateLimiter, RatePermit};
+use crate::structured::parse_structured;
+use crate::{LlmResponse, LlmStream, StreamChunk};
 
 /// Model prefixes that map to Anthropic provider.
 /// Add new prefixes here to support additional Anthropic models.
 const ANTHROPIC_PREFIXES: &[&str] = &["claude-"];
 
-/// Detects provider from model name using prefix matching.
-fn detect_provider(model: &str) -> ProviderType {
-    let is_anthropic = ANTHROPIC_PREFIXES.iter().any(|prefix| model.starts_with(prefix));
-    if is_anthropic { ProviderType::Anthropic } else { ProviderType::OpenAI }
+/// Picks a built-in provider from the model name using prefix matching.
+fn detect_provider(model: &str, api_base: Option<&str>) -> Arc<dyn LlmProvider> {
+    if ANTHROPIC_PREFIXES.iter().any(|prefix| model.starts_with(prefix)) {
+        Arc::new(AnthropicProvider)
+    } else {
+        Arc::new(OpenAiProvider::new(api_base))
+    }
 }
 
-/// Unified client that routes requests to OpenAI or Anthropic based on model name.
+/// Returns the name a tool is sent to providers under.
+///
+/// OpenAI and Anthropic only accept `[a-zA-Z0-9_-]` in function names, so a
+/// namespaced tool such as `github.create_issue` goes out as `github__create_issue`.
+fn wire_name(name: &str) -> String {
+    name.replace('.', "__")
+}
+
+/// Unified client that routes requests to OpenAI, Anthropic, or a registered provider.
 pub struct UnifiedLlmClient {
     model: String,
-    provider: ProviderType,
-    api_base: Option<String>,
+    provider: Arc<dyn LlmProvider>,
+    limiter: Option<Arc<RateLimiter>>,
+    cache: Option<Arc<dyn LlmCache>>,
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl UnifiedLlmClient {
     /// Creates a new unified client, detecting provider from model name.
+    ///
+    /// Names of the form `<scheme>://<model>` use the provider registered for
+    /// `scheme` in [`ProviderRegistry::global`].
     pub fn new(model: &str, api_base: Option<&str>) -> Self {
+        match ProviderRegistry::global().resolve(model) {
+            Some((provider, name)) => Self::with_provider(name, provider),
+            None => Self::with_provider(model, detect_provider(model, api_base)),
+        }
+    }
+
+    /// The model name sent to the provider.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Creates a client that sends every request for `model` to `provider`.
+    pub fn with_provider(model: &str, provider: Arc<dyn LlmProvider>) -> Self {
         Self {
             model: model.to_string(),
-            provider: detect_provider(model),
-            api_base: api_base.map(String::from),
+            provider,
+            limiter: None,
+            cache: None,
+            cassette: None,
+        }
+    }
+
+    /// Creates a client for a model config, applying its rate limit if set.
+    ///
+    /// An explicit `provider` takes precedence over inferring it from the model name.
+    pub fn from_config(model: &ModelConfig) -> Self {
+        let explicit = model.provider.as_ref().and_then(|provider| {
+            let resolved = ProviderRegistry::global().for_provider(provider, model.api_base.as_deref());
+            if resolved.is_none() {
+                warn!("Provider {:?} for model {} is not registered, inferring from name", provider, model.id);
+            }
+            resolved
+        });
+        let mut client = match explicit {
+            Some(provider) => Self::with_provider(&model.model, provider),
+            None => Self::new(&model.model, model.api_base.as_deref()),
+        };
+        if let Some(azure) = &model.azure {
+            client = client.with_azure(azure.clone());
+        }
+        match model.rate_limit {
+            Some(limits) => client.with_rate_limit(limits),
+            None => client,
+        }
+    }
+
+    /// Sends OpenAI-protocol requests to an Azure OpenAI deployment instead of the OpenAI API.
+    ///
+    /// Call before [`with_rate_limit`](Self::with_rate_limit) so the deployment gets its own limiter.
+    pub fn with_azure(mut self, azure: AzureOpenAiConfig) -> Self {
+        self.provider = Arc::new(OpenAiProvider::azure(azure));
+        self
+    }
+
+    /// Throttles requests through the limiter shared by every client for the same provider.
+    pub fn with_rate_limit(mut self, limits: RateLimitConfig) -> Self {
+        self.limiter = Some(RateLimiter::shared(&self.rate_limit_key(), limits));
+        self
+    }
+
+    /// Serves repeated `chat` and `chat_with_tools` requests from `cache`.
+    ///
+    /// Cache hits report zero tokens since nothing is sent to the provider.
+    pub fn with_cache(mut self, cache: Arc<dyn LlmCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Records every interaction to, or replays every response from, `cassette`.
+    ///
+    /// In replay mode no request reaches the provider; the cache and rate limit are bypassed.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Builds the request key, or `None` when neither a cache nor a cassette needs it.
+    fn request_key<M: Serialize>(&self, system_prompt: &str, messages: M, tools: &[ToolSchema]) -> Option<String> {
+        if self.cache.is_none() && self.cassette.is_none() {
+            return None;
+        }
+        Some(cache_key(&self.model, self.provider.endpoint().as_deref(), system_prompt, messages, tools))
+    }
+
+    /// Returns the cassette when it is replaying.
+    fn replaying(&self) -> Option<&Cassette> {
+        self.cassette.as_deref().filter(|c| c.mode() == CassetteMode::Replay)
+    }
+
+    /// Appends an interaction to the cassette when it is recording.
+    fn record(&self, kind: CallKind, key: Option<&str>, request: serde_json::Value, response: &impl Serialize) {
+        let Some(cassette) = self.cassette.as_deref().filter(|c| c.mode() == CassetteMode::Record) else {
+            return;
+        };
+        if let Some(key) = key {
+            cassette.push(kind, key, &self.model, request, response);
+        }
+    }
+
+    async fn cache_get<T: DeserializeOwned>(&self, key: Option<&str>) -> Option<T> {
+        let (cache, key) = (self.cache.as_ref()?, key?);
+        let value = serde_json::from_str(&cache.get(key).await?).ok()?;
+        debug!("LLM cache hit for {}", key);
+        Some(value)
+    }
+
+    async fn cache_put<T: Serialize>(&self, key: Option<&str>, value: &T) {
+        let (Some(cache), Some(key)) = (&self.cache, key) else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_string(value) {
+            cache.put(key, json).await;
+        }
+    }
+
+    /// Identifies the provider endpoint whose limits this client shares.
+    fn rate_limit_key(&self) -> String {
+        self.provider.endpoint().unwrap_or_else(|| self.provider.name().to_string())
+    }
+
+    /// Waits for room in the rate limit window, if one is configured.
+    async fn throttle(&self, prompt_chars: usize) -> Option<RatePermit> {
+        match &self.limiter {
+            Some(limiter) => Some(limiter.acquire(estimate_tokens(prompt_chars)).await),
+            None => None,
+        }
+    }
+
+    /// Passes the quota the provider reported on to the rate limiter.
+    fn observe(&self, metrics: &LlmMetrics) {
+        if let (Some(limiter), Some(status)) = (&self.limiter, metrics.rate_limit) {
+            limiter.observe(status);
+        }
+    }
+
+    /// Reports a provider request to the call observer and marks the current span if it failed.
+    fn finish_call<T>(&self, started: Instant, result: &Result<T, AgentError>) {
+        observer::report(self.provider.name(), &self.model, started.elapsed(), result);
+        if result.is_err() {
+            Span::current().record("otel.status_code", "ERROR");
         }
     }
 
     /// Sends a non-streaming chat request and returns the complete response.
+    #[tracing::instrument(
+        name = "llm",
+        skip_all,
+        fields(
+            gen_ai.system = %self.provider.name(),
+            gen_ai.request.model = %self.model,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            otel.status_code = Empty,
+        )
+    )]
     pub async fn chat(&self, system_prompt: &str, user_input: &str) -> Result<LlmResponse, AgentError> {
-        match self.provider {
-            ProviderType::OpenAI => {
-                let client = LlmClient::new(&self.model, self.api_base.as_deref());
-                client.chat(system_prompt, user_input).await
-            }
-            ProviderType::Anthropic => {
-                let client = AnthropicClient::new(&self.model);
-                client.chat(system_prompt, user_input).await
-            }
+        let key = self.request_key(system_prompt, user_input, &[]);
+        if let (Some(cassette), Some(key)) = (self.replaying(), key.as_deref()) {
+            return cassette.take(CallKind::Chat, key);
+        }
+        if let Some(mut hit) = self.cache_get::<LlmResponse>(key.as_deref()).await {
+            hit.metrics = LlmMetrics::default();
+            return Ok(hit);
+        }
+
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            chaos.before_request(&self.model).await?;
+        }
+        let permit = self.throttle(system_prompt.len() + user_input.len()).await;
+        let started = Instant::now();
+        let response = self.provider.chat(&self.model, system_prompt, user_input).await;
+        self.finish_call(started, &response);
+        let response = response?;
+        if let Some(permit) = permit {
+            permit.settle(response.metrics.input_tokens + response.metrics.output_tokens);
+        }
+        self.observe(&response.metrics);
+        record_usage(&Span::current(), response.metrics.input_tokens, response.metrics.output_tokens);
+        self.cache_put(key.as_deref(), &response).await;
+        self.record(
+            CallKind::Chat,
+            key.as_deref(),
+            json!({ "system_prompt": system_prompt, "user_input": user_input }),
+            &response,
+        );
+        Ok(response)
+    }
+
+    /// Sends a chat request whose answer must be JSON matching `schema`, and
+    /// returns the response after checking it against the schema.
+    ///
+    /// OpenAI-compatible providers use a `json_schema` response format and
+    /// Anthropic a forced tool call; other providers get the schema in the
+    /// system prompt.
+    #[tracing::instrument(
+        name = "llm",
+        skip_all,
+        fields(
+            gen_ai.system = %self.provider.name(),
+            gen_ai.request.model = %self.model,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            otel.status_code = Empty,
+        )
+    )]
+    pub async fn chat_structured(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<LlmResponse, AgentError> {
+        let key = self.request_key(system_prompt, (user_input, schema), &[]);
+        if let (Some(cassette), Some(key)) = (self.replaying(), key.as_deref()) {
+            return cassette.take(CallKind::ChatStructured, key);
+        }
+        if let Some(mut hit) = self.cache_get::<LlmResponse>(key.as_deref()).await {
+            hit.metrics = LlmMetrics::default();
+            return Ok(hit);
+        }
+
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            chaos.before_request(&self.model).await?;
         }
+        let permit = self.throttle(system_prompt.len() + user_input.len()).await;
+        let started = Instant::now();
+        let response = self.provider.chat_structured(&self.model, system_prompt, user_input, schema).await;
+        self.finish_call(started, &response);
+        let response = response?;
+        if let Some(permit) = permit {
+            permit.settle(response.metrics.input_tokens + response.metrics.output_tokens);
+        }
+        self.observe(&response.metrics);
+        record_usage(&Span::current(), response.metrics.input_tokens, response.metrics.output_tokens);
+        parse_structured::<serde_json::Value>(&response.content, schema)?;
+        self.cache_put(key.as_deref(), &response).await;
+        self.record(
+            CallKind::ChatStructured,
+            key.as_deref(),
+            json!({ "system_prompt": system_prompt, "user_input": user_input, "schema": schema }),
+            &response,
+        );
+        Ok(response)
+    }
+
+    /// Like [`chat_structured`](Self::chat_structured), deserializing the response into `T`.
+    pub async fn structured<T: DeserializeOwned>(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        schema: &OutputSchema,
+    ) -> Result<(T, LlmMetrics), AgentError> {
+        let response = self.chat_structured(system_prompt, user_input, schema).await?;
+        let parsed = parse_structured(&response.content, schema)?;
+        Ok((parsed, response.metrics))
     }
 
     /// Sends a chat request with history and returns a stream of chunks.
+    ///
+    /// The call's `llm` span stays open until the stream is dropped.
+    #[tracing::instrument(
+        name = "llm",
+        skip_all,
+        fields(
+            gen_ai.system = %self.provider.name(),
+            gen_ai.request.model = %self.model,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            otel.status_code = Empty,
+        )
+    )]
     pub async fn chat_stream(
         &self,
         system_prompt: &str,
         history: &[Message],
         user_input: &str,
     ) -> Result<LlmStream, AgentError> {
-        match self.provider {
-            ProviderType::OpenAI => {
-                let client = LlmClient::new(&self.model, self.api_base.as_deref());
-                client.chat_stream(system_prompt, history, user_input).await
-            }
-            ProviderType::Anthropic => {
-                let client = AnthropicClient::new(&self.model);
-                client.chat_stream(system_prompt, history, user_input).await
+        let key = self.request_key(system_prompt, (history, user_input), &[]);
+        if let (Some(cassette), Some(key)) = (self.replaying(), key.as_deref()) {
+            let chunks: Vec<RecordedChunk> = cassette.take(CallKind::ChatStream, key)?;
+            return Ok(Box::pin(futures::stream::iter(
+                chunks.into_iter().map(|c| Ok::<_, AgentError>(StreamChunk::from(c))),
+            )));
+        }
+
+        let history_chars: usize = history.iter().map(|m| m.content.len()).sum();
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            chaos.before_request(&self.model).await?;
+        }
+        let permit = self.throttle(system_prompt.len() + history_chars + user_input.len()).await;
+        let started = Instant::now();
+        let stream = self.provider.chat_stream(&self.model, system_prompt, history, user_input).await;
+        self.finish_call(started, &stream);
+        let mut stream = stream?;
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            stream = chaos.truncate(stream);
+        }
+
+        let span = Span::current();
+        stream = Box::pin(stream.inspect(move |chunk| {
+            if let Ok(StreamChunk::Usage { input_tokens, output_tokens }) = chunk {
+                record_usage(&span, *input_tokens, *output_tokens);
             }
+        }));
+
+        if let Some(permit) = permit {
+            stream = Box::pin(stream.inspect(move |chunk| {
+                if let Ok(StreamChunk::Usage { input_tokens, output_tokens }) = chunk {
+                    permit.settle(input_tokens + output_tokens);
+                }
+            }));
         }
+
+        let recorder = self.cassette.clone().filter(|c| c.mode() == CassetteMode::Record);
+        if let (Some(cassette), Some(key)) = (recorder, key) {
+            let request = json!({ "system_prompt": system_prompt, "history": history, "user_input": user_input });
+            let model = self.model.clone();
+            let chunks = Arc::new(std::sync::Mutex::new(Vec::<RecordedChunk>::new()));
+            let collected = Arc::clone(&chunks);
+            let finish = futures::stream::once(async move {
+                let chunks = std::mem::take(&mut *chunks.lock().unwrap_or_else(|e| e.into_inner()));
+                cassette.push(CallKind::ChatStream, &key, &model, request, &chunks);
+            })
+            .filter_map(|()| async { None::<Result<StreamChunk, AgentError>> });
+            stream = Box::pin(
+                stream
+                    .inspect(move |chunk| {
+                        if let Ok(chunk) = chunk {
+                            collected.lock().unwrap_or_else(|e| e.into_inner()).push(chunk.into());
+                        }
+                    })
+                    .chain(finish),
+            );
+        }
+        Ok(stream)
     }
 
     /// Sends a chat request with tools.
@@ -79,6 +406,17 @@ impl UnifiedLlmClient {
     ///
     /// For multi-turn tool conversations, pass `pending_tool_calls` with the
     /// tool calls from the previous response that are being fulfilled.
+    #[tracing::instrument(
+        name = "llm",
+        skip_all,
+        fields(
+            gen_ai.system = %self.provider.name(),
+            gen_ai.request.model = %self.model,
+            gen_ai.usage.input_tokens = Empty,
+            gen_ai.usage.output_tokens = Empty,
+            otel.status_code = Empty,
+        )
+    )]
     pub async fn chat_with_tools(
         &self,
         system_prompt: &str,
@@ -86,82 +424,66 @@ impl UnifiedLlmClient {
         tools: &[ToolSchema],
         pending_tool_calls: Option<&[ToolCall]>,
     ) -> Result<ChatResponse, AgentError> {
-        match self.provider {
-            ProviderType::OpenAI => {
-                let client = LlmClient::new(&self.model, self.api_base.as_deref());
-                client.chat_with_tools(system_prompt, messages, tools).await
-            }
-            ProviderType::Anthropic => {
-                let client = AnthropicClient::new(&self.model);
-                let anthropic_messages = self.convert_to_anthropic_messages(messages, pending_tool_calls)?;
-                client.chat_with_tools(system_prompt, anthropic_messages, tools).await
+        let key = self.request_key(system_prompt, messages, tools);
+        if let (Some(cassette), Some(key)) = (self.replaying(), key.as_deref()) {
+            return cassette.take(CallKind::ChatWithTools, key);
+        }
+        if let Some(mut hit) = self.cache_get::<ChatResponse>(key.as_deref()).await {
+            match &mut hit {
+                ChatResponse::Content(r) => r.metrics = LlmMetrics::default(),
+                ChatResponse::ToolCalls { metrics, .. } => *metrics = LlmMetrics::default(),
             }
+            return Ok(hit);
         }
-    }
 
-    /// Converts OpenAI-format messages to Anthropic format.
-    fn convert_to_anthropic_messages(
-        &self,
-        messages: &[ChatCompletionRequestMessage],
-        pending_tool_calls: Option<&[ToolCall]>,
-    ) -> Result<Vec<AnthropicToolMessage>, AgentError> {
-        let mut result = Vec::new();
-        let mut tool_results: Vec<(String, String)> = Vec::new();
-
-        for msg in messages {
-            match msg {
-                ChatCompletionRequestMessage::User(user_msg) => {
-                    // Flush any pending tool results first
-                    if !tool_results.is_empty() {
-                        // Add assistant message with tool_use blocks before tool results
-                        if let Some(calls) = pending_tool_calls {
-                            result.push(AnthropicToolMessage::assistant_tool_use(calls));
-                        }
-                        result.push(AnthropicToolMessage::tool_results(&tool_results));
-                        tool_results.clear();
-                    }
-
-                    // Extract text content
-                    let text = match &user_msg.content {
-                        async_openai::types::ChatCompletionRequestUserMessageContent::Text(t) => t.clone(),
-                        async_openai::types::ChatCompletionRequestUserMessageContent::Array(parts) => {
-                            parts.iter().filter_map(|p| {
-                                if let async_openai::types::ChatCompletionRequestUserMessageContentPart::Text(t) = p {
-                                    Some(t.text.clone())
-                                } else {
-                                    None
-                                }
-                            }).collect::<Vec<_>>().join("\n")
-                        }
-                    };
-                    result.push(AnthropicToolMessage::user(&text));
-                }
-                ChatCompletionRequestMessage::Tool(tool_msg) => {
-                    // Collect tool results to batch them
-                    let id = tool_msg.tool_call_id.clone();
-                    let content = match &tool_msg.content {
-                        async_openai::types::ChatCompletionRequestToolMessageContent::Text(t) => t.clone(),
-                        async_openai::types::ChatCompletionRequestToolMessageContent::Array(parts) => {
-                            parts.iter().map(|async_openai::types::ChatCompletionRequestToolMessageContentPart::Text(t)| {
-                                t.text.clone()
-                            }).collect::<Vec<_>>().join("\n")
-                        }
-                    };
-                    tool_results.push((id, content));
+        let message_chars = serde_json::to_string(messages).map(|s| s.len()).unwrap_or(0);
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            chaos.before_request(&self.model).await?;
+        }
+        let permit = self.throttle(system_prompt.len() + message_chars).await;
+        let wire_tools: Vec<ToolSchema> = tools.iter().map(|t| ToolSchema { name: wire_name(&t.name), ..t.clone() }).collect();
+        let wire_pending: Option<Vec<ToolCall>> = pending_tool_calls
+            .map(|calls| calls.iter().map(|c| ToolCall { name: wire_name(&c.name), ..c.clone() }).collect());
+        let started = Instant::now();
+        let response = self
+            .provider
+            .chat_with_tools(&self.model, system_prompt, messages, &wire_tools, wire_pending.as_deref())
+            .await;
+        self.finish_call(started, &response);
+        let mut response = response?;
+        if let ChatResponse::ToolCalls { calls, .. } = &mut response {
+            for call in calls {
+                if let Some(tool) = tools.iter().find(|t| wire_name(&t.name) == call.name) {
+                    call.name = tool.name.clone();
                 }
-                _ => {} // Skip system and other message types
             }
         }
-
-        // Flush any remaining tool results
-        if !tool_results.is_empty() {
-            if let Some(calls) = pending_tool_calls {
-                result.push(AnthropicToolMessage::assistant_tool_use(calls));
-            }
-            result.push(AnthropicToolMessage::tool_results(&tool_results));
+        #[cfg(feature = "chaos")]
+        if let Some(chaos) = crate::chaos::FaultInjector::global() {
+            chaos.corrupt_tool_calls(&mut response);
         }
-
-        Ok(result)
+        if let Some(permit) = permit {
+            let metrics = match &response {
+                ChatResponse::Content(r) => &r.metrics,
+                ChatResponse::ToolCalls { metrics, .. } => metrics,
+            };
+            permit.settle(metrics.input_tokens + metrics.output_tokens);
+        }
+        let metrics = match &response {
+            ChatResponse::Content(r) => &r.metrics,
+            ChatResponse::ToolCalls { metrics, .. } => metrics,
+        };
+        self.observe(metrics);
+        record_usage(&Span::current(), metrics.input_tokens, metrics.output_tokens);
+        self.cache_put(key.as_deref(), &response).await;
+        self.record(
+            CallKind::ChatWithTools,
+            key.as_deref(),
+            json!({ "system_prompt": system_prompt, "messages": messages, "tools": tools }),
+            &response,
+        );
+        Ok(response)
     }
 
     /// Helper to create a user message for tool conversations.
@@ -179,3 +501,9 @@ impl UnifiedLlmClient {
         LlmClient::tool_result_message(tool_call_id, content)
     }
 }
+
+/// Adds a call's token usage to its `llm` span.
+fn record_usage(span: &Span, input_tokens: u32, output_tokens: u32) {
+    span.record("gen_ai.usage.input_tokens", input_tokens);
+    span.record("gen_ai.usage.output_tokens", output_tokens);
+}
@@ -0,0 +1,121 @@
+//! Provider usage APIs, used to reconcile provider billing against recorded metrics.
+
+use std::collections::HashMap;
+
+use fissio_core::AgentError;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+const OPENAI_USAGE_URL: &str = "https://api.openai.com/v1/organization/usage/completions";
+
+/// Token usage reported by a provider for a single model over a time window.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    /// Model name as reported by the provider (may include a dated snapshot suffix).
+    pub model: String,
+    /// Input tokens billed.
+    pub input_tokens: u64,
+    /// Output tokens billed.
+    pub output_tokens: u64,
+    /// Number of model requests.
+    pub requests: u64,
+}
+
+#[derive(Deserialize)]
+struct UsagePage {
+    data: Vec<UsageBucket>,
+    #[serde(default)]
+    has_more: bool,
+    #[serde(default)]
+    next_page: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UsageBucket {
+    results: Vec<UsageResult>,
+}
+
+#[derive(Deserialize)]
+struct UsageResult {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    num_model_requests: u64,
+}
+
+/// Fetches per-model completion usage from the OpenAI organization usage API.
+///
+/// `start` and `end` are Unix timestamps in seconds. The endpoint requires an
+/// organization admin key; regular project keys are rejected. Pass `project_id`
+/// to exclude usage from other projects in the same organization.
+pub async fn fetch_openai_usage(
+    admin_key: &str,
+    project_id: Option<&str>,
+    start: i64,
+    end: i64,
+) -> Result<Vec<ProviderUsage>, AgentError> {
+    let client = Client::new();
+    let mut totals: HashMap<String, ProviderUsage> = HashMap::new();
+    let mut page: Option<String> = None;
+
+    loop {
+        let mut query: Vec<(&str, String)> = vec![
+            ("start_time", start.to_string()),
+            ("end_time", end.to_string()),
+            ("bucket_width", "1d".to_string()),
+            ("group_by", "model".to_string()),
+        ];
+        if let Some(project) = project_id {
+            query.push(("project_ids", project.to_string()));
+        }
+        if let Some(ref p) = page {
+            query.push(("page", p.clone()));
+        }
+
+        let response = client
+            .get(OPENAI_USAGE_URL)
+            .bearer_auth(admin_key)
+            .query(&query)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| AgentError::ExternalApi(format!("OpenAI usage request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AgentError::ExternalApi(format!("OpenAI usage API error {}: {}", status, body)));
+        }
+
+        let usage: UsagePage = response
+            .json()
+            .await
+            .map_err(|e| AgentError::ExternalApi(format!("Failed to parse OpenAI usage response: {}", e)))?;
+
+        for result in usage.data.into_iter().flat_map(|b| b.results) {
+            let model = result.model.unwrap_or_else(|| "unknown".to_string());
+            let entry = totals.entry(model.clone()).or_insert_with(|| ProviderUsage {
+                model,
+                ..Default::default()
+            });
+            entry.input_tokens += result.input_tokens;
+            entry.output_tokens += result.output_tokens;
+            entry.requests += result.num_model_requests;
+        }
+
+        match usage.next_page {
+            Some(next) if usage.has_more => page = Some(next),
+            _ => break,
+        }
+    }
+
+    debug!("Fetched OpenAI usage for {} models", totals.len());
+    let mut usage: Vec<ProviderUsage> = totals.into_values().collect();
+    usage.sort_by(|a, b| a.model.cmp(&b.model));
+    Ok(usage)
+}
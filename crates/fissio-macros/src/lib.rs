@@ -0,0 +1,296 @@
+//! Derive macros for fissio.
+//!
+//! - `#[derive(ToolArgs)]` — Tool parameter schema generated from a struct;
+//!   see `fissio_tools::ToolArgs`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitStr, Meta, Path, Token};
+
+/// Implements `ArgSchema` and `ToolArgs` for a struct with named fields.
+///
+/// Each field becomes a property whose schema comes from the field's type and
+/// whose description is its doc comment. `Option` fields and fields with
+/// `#[serde(default)]` aren't required; `#[serde(rename = "...")]`,
+/// `#[serde(skip)]`, and `#[serde(flatten)]` are honored, and so are
+/// `rename_all`, `default`, and `deny_unknown_fields` on the struct. Struct
+/// attributes that replace its deserialization (`transparent`, `from`,
+/// `try_from`) are rejected. The generated code names `::fissio_tools`;
+/// through the `fissio` crate, add `#[tool_args(crate = "fissio::tools")]`.
+#[proc_macro_derive(ToolArgs, attributes(tool_args))]
+pub fn derive_tool_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let krate = crate_path(&input.attrs)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "ToolArgs needs a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "ToolArgs can only be derived for structs")),
+    };
+
+    let container = SerdeContainer::parse(&input.attrs)?;
+    let mut entries = Vec::new();
+    for field in fields {
+        let serde = SerdeField::parse(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let name = match (serde.rename, &container.rename_all) {
+            (Some(name), _) => name,
+            (None, Some(rule)) => rule.apply(&ident.unraw().to_string()),
+            (None, None) => ident.unraw().to_string(),
+        };
+        let description = match doc_comment(&field.attrs) {
+            Some(doc) => quote!(::core::option::Option::Some(#doc)),
+            None => quote!(::core::option::Option::None),
+        };
+        let ty = &field.ty;
+        let defaulted = serde.default || container.default;
+        let flatten = serde.flatten;
+        entries.push(quote! {
+            #krate::__private::ArgField {
+                name: #name,
+                description: #description,
+                schema: <#ty as #krate::ArgSchema>::arg_schema(),
+                required: !#defaulted && !<#ty as #krate::ArgSchema>::OPTIONAL,
+                flatten: #flatten,
+            }
+        });
+    }
+    let deny_unknown_fields = container.deny_unknown_fields;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::ArgSchema for #ident #ty_generics #where_clause {
+            fn arg_schema() -> #krate::__private::Value {
+                #krate::__private::object_schema(::std::vec![#(#entries),*], #deny_unknown_fields)
+            }
+        }
+
+        impl #impl_generics #krate::ToolArgs for #ident #ty_generics #where_clause {}
+    })
+}
+
+/// Path to `fissio_tools` from `#[tool_args(crate = "...")]`.
+fn crate_path(attrs: &[Attribute]) -> syn::Result<Path> {
+    let mut path: Path = syn::parse_quote!(::fissio_tools);
+    for attr in attrs.iter().filter(|a| a.path().is_ident("tool_args")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                path = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown tool_args attribute; expected `crate`"))
+            }
+        })?;
+    }
+    Ok(path)
+}
+
+/// The `#[serde(...)]` struct attributes that change the schema.
+#[derive(Default)]
+struct SerdeContainer {
+    rename_all: Option<RenameRule>,
+    default: bool,
+    deny_unknown_fields: bool,
+}
+
+impl SerdeContainer {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    if let Some(rule) = deserialize_name(&meta)? {
+                        serde.rename_all = Some(RenameRule::parse(&rule)?);
+                    }
+                } else if meta.path.is_ident("default") {
+                    serde.default = true;
+                    skip_value(&meta)?;
+                } else if meta.path.is_ident("deny_unknown_fields") {
+                    serde.deny_unknown_fields = true;
+                } else if ["transparent", "from", "try_from"].iter().any(|name| meta.path.is_ident(name)) {
+                    return Err(meta.error("ToolArgs can't derive a schema for a struct deserialized through another type"));
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// The `#[serde(...)]` field attributes that change the schema.
+#[derive(Default)]
+struct SerdeField {
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+impl SerdeField {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if let Some(name) = deserialize_name(&meta)? {
+                        serde.rename = Some(name.value());
+                    }
+                } else if meta.path.is_ident("default") {
+                    serde.default = true;
+                    skip_value(&meta)?;
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    serde.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    serde.flatten = true;
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// The name a `rename` or `rename_all` attribute gives deserialization:
+/// `= "..."`, or `deserialize = "..."` in parentheses.
+fn deserialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("deserialize") {
+            name = Some(inner.value()?.parse()?);
+        } else {
+            skip_value(&inner)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// A `rename_all` case convention, applied to snake_case field names the way serde does.
+enum RenameRule {
+    Unchanged,
+    Upper,
+    Pascal,
+    Camel,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> syn::Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" | "snake_case" => Self::Unchanged,
+            "UPPERCASE" | "SCREAMING_SNAKE_CASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new_spanned(rule, "unknown rename_all rule")),
+        })
+    }
+
+    fn apply(&self, field: &str) -> String {
+        let pascal = || -> String {
+            field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+                })
+                .collect()
+        };
+        match self {
+            Self::Unchanged => field.to_string(),
+            Self::Upper => field.to_ascii_uppercase(),
+            Self::Pascal => pascal(),
+            Self::Camel => {
+                let pascal = pascal();
+                let mut chars = pascal.chars();
+                chars.next().map(|first| first.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// Consumes the value of a serde attribute this macro doesn't read.
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_value(&inner))?;
+    }
+    Ok(())
+}
+
+/// A field's doc comment as one line.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(rule: &str, field: &str) -> String {
+        RenameRule::parse(&LitStr::new(rule, proc_macro2::Span::call_site())).unwrap().apply(field)
+    }
+
+    #[test]
+    fn test_rename_rules() {
+        assert_eq!(rename("snake_case", "max_results"), "max_results");
+        assert_eq!(rename("SCREAMING_SNAKE_CASE", "max_results"), "MAX_RESULTS");
+        assert_eq!(rename("PascalCase", "max_results"), "MaxResults");
+        assert_eq!(rename("camelCase", "max_results"), "maxResults");
+        assert_eq!(rename("kebab-case", "max_results"), "max-results");
+        assert_eq!(rename("SCREAMING-KEBAB-CASE", "max_results"), "MAX-RESULTS");
+        assert!(RenameRule::parse(&LitStr::new("Title Case", proc_macro2::Span::call_site())).is_err());
+    }
+
+    #[test]
+    fn test_rejects_struct_deserialized_through_another_type() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[serde(try_from = "String")]
+            struct Args { query: String }
+        };
+        assert!(expand(input).is_err());
+
+        let input: DeriveInput = syn::parse_quote! {
+            #[serde(rename_all(serialize = "UPPERCASE", deserialize = "camelCase"))]
+            struct Args { max_results: u32 }
+        };
+        let expanded = expand(input).unwrap().to_string();
+        assert!(expanded.contains("\"maxResults\""));
+    }
+}
@@ -1,11 +1,16 @@
 //! Tracing collector that persists to TraceStore.
 
-use crate::store::TraceStore;
-use crate::trace::{SpanRecord, TraceRecord, TraceStatus};
+use crate::store::{content_hash, TraceStore};
+use crate::trace::{SpanRecord, ToolCallRecord, TraceRecord, TraceStatus};
 use crate::{MetricsCollector, NodeMetrics, PipelineMetrics};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Receives each run a [`TracingCollector`] finalizes, e.g. to export metrics.
+pub trait RunObserver: Send + Sync {
+    fn on_run(&self, trace: &TraceRecord, metrics: &PipelineMetrics);
+}
+
 /// Collector that persists traces and spans to a TraceStore.
 pub struct TracingCollector {
     store: Arc<TraceStore>,
@@ -16,6 +21,8 @@ pub struct TracingCollector {
     start_time: i64,
     metrics: Mutex<Vec<NodeMetrics>>,
     spans: Mutex<Vec<SpanRecord>>,
+    observer: Option<Arc<dyn RunObserver>>,
+    owner: Option<String>,
 }
 
 impl TracingCollector {
@@ -44,7 +51,9 @@ impl TracingCollector {
             total_input_tokens: 0,
             total_output_tokens: 0,
             total_tool_calls: 0,
+            total_cost_usd: None,
             status: TraceStatus::Running,
+            owner: None,
         };
 
         if let Err(e) = store.insert_trace(&trace) {
@@ -60,7 +69,25 @@ impl TracingCollector {
             start_time,
             metrics: Mutex::new(Vec::new()),
             spans: Mutex::new(Vec::new()),
+            observer: None,
+            owner: None,
+        }
+    }
+
+    /// Reports the run to `observer` when it is finalized.
+    pub fn with_observer(mut self, observer: Arc<dyn RunObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Records the trace as owned by `owner`, so it's only listed for that tenant.
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        let owner = owner.into();
+        if let Err(e) = self.store.set_trace_owner(&self.trace_id, &owner) {
+            tracing::warn!("Failed to set trace owner: {}", e);
         }
+        self.owner = Some(owner);
+        self
     }
 
     /// Returns the trace ID.
@@ -68,6 +95,12 @@ impl TracingCollector {
         &self.trace_id
     }
 
+    /// Estimated cost in USD of the nodes recorded so far, or `None` if none had a priced model.
+    pub fn total_cost_usd(&self) -> Option<f64> {
+        let metrics = self.flush();
+        metrics.node_metrics.iter().any(|m| m.estimated_cost_usd.is_some()).then(|| metrics.total_cost())
+    }
+
     /// Finalizes the trace with the given output and status.
     pub fn finalize(&self, output: &str, status: TraceStatus) {
         let elapsed_ms = (now_ms() - self.start_time) as u64;
@@ -84,12 +117,17 @@ impl TracingCollector {
             total_input_tokens: metrics.total_input_tokens,
             total_output_tokens: metrics.total_output_tokens,
             total_tool_calls: metrics.total_tool_calls,
+            total_cost_usd: self.total_cost_usd(),
             status,
+            owner: self.owner.clone(),
         };
 
         if let Err(e) = self.store.update_trace(&trace) {
             tracing::warn!("Failed to update trace: {}", e);
         }
+        if let Some(observer) = &self.observer {
+            observer.on_run(&trace, &metrics);
+        }
     }
 
     /// Marks the trace as successful with the given output.
@@ -101,6 +139,11 @@ impl TracingCollector {
     pub fn error(&self, error: &str) {
         self.finalize(error, TraceStatus::Error);
     }
+
+    /// Marks the trace as finished without a response, recording why.
+    pub fn completed_empty(&self, reason: &str) {
+        self.finalize(reason, TraceStatus::CompletedEmpty);
+    }
 }
 
 impl MetricsCollector for TracingCollector {
@@ -134,19 +177,36 @@ impl MetricsCollector for TracingCollector {
             trace_id: self.trace_id.clone(),
             node_id: node_id.to_string(),
             node_type: node_type.to_string(),
+            model: metrics.model.clone(),
             start_time,
             end_time,
             input: input.to_string(),
             output: output.to_string(),
+            input_hash: Some(content_hash(input)),
+            output_hash: Some(content_hash(output)),
             input_tokens: metrics.input_tokens,
             output_tokens: metrics.output_tokens,
             tool_call_count: metrics.tool_call_count,
             iteration_count: metrics.iteration_count,
+            estimated_cost_usd: metrics.estimated_cost_usd,
+            groundedness: metrics.groundedness,
+            provider_request_ids: metrics.provider_request_ids.clone(),
+            error: metrics.error.clone(),
         };
 
         if let Err(e) = self.store.insert_span(&span) {
             tracing::warn!("Failed to insert span: {}", e);
         }
+        for call in &metrics.tool_calls {
+            let call = ToolCallRecord {
+                call_id: uuid::Uuid::new_v4().to_string(),
+                span_id: span.span_id.clone(),
+                ..call.clone()
+            };
+            if let Err(e) = self.store.insert_tool_call(&call) {
+                tracing::warn!("Failed to insert tool call: {}", e);
+            }
+        }
 
         let Ok(mut spans) = self.spans.lock() else { return };
         spans.push(span);
@@ -205,12 +265,17 @@ mod tests {
 
         collector.record(NodeMetrics {
             node_id: "node1".to_string(),
+            model: None,
             input_tokens: 10,
             output_tokens: 20,
             elapsed_ms: 100,
             tool_call_count: 1,
             iteration_count: 1,
-            estimated_cost_usd: None,
+            estimated_cost_usd: Some(0.002),
+            groundedness: None,
+            provider_request_ids: Vec::new(),
+            tool_calls: Vec::new(),
+            error: None,
         });
 
         collector.success("World");
@@ -220,5 +285,37 @@ mod tests {
         assert_eq!(trace.output, "World");
         assert_eq!(trace.total_input_tokens, 10);
         assert_eq!(trace.total_output_tokens, 20);
+        assert_eq!(trace.total_cost_usd, Some(0.002));
+    }
+
+    #[test]
+    fn test_completed_empty_status() {
+        let store = Arc::new(TraceStore::in_memory().unwrap());
+        let collector = TracingCollector::new(store.clone(), "test-pipe", "Test Pipeline", "Hello");
+
+        collector.completed_empty("output_not_reached");
+
+        let trace = store.get_trace(collector.trace_id()).unwrap().unwrap();
+        assert_eq!(trace.status, TraceStatus::CompletedEmpty);
+        assert_eq!(trace.output, "output_not_reached");
+        assert_eq!(TraceStatus::from_str(TraceStatus::CompletedEmpty.as_str()), TraceStatus::CompletedEmpty);
+    }
+
+    #[test]
+    fn test_observer_sees_finalized_run() {
+        struct Runs(Mutex<Vec<(TraceStatus, u32)>>);
+        impl RunObserver for Runs {
+            fn on_run(&self, trace: &TraceRecord, metrics: &PipelineMetrics) {
+                self.0.lock().unwrap().push((trace.status, metrics.total_tokens()));
+            }
+        }
+
+        let runs = Arc::new(Runs(Mutex::new(Vec::new())));
+        let store = Arc::new(TraceStore::in_memory().unwrap());
+        let collector = TracingCollector::new(store, "test-pipe", "Test Pipeline", "Hello").with_observer(runs.clone());
+        collector.record(NodeMetrics { input_tokens: 5, output_tokens: 7, ..NodeMetrics::new("node1") });
+        collector.error("boom");
+
+        assert_eq!(*runs.0.lock().unwrap(), vec![(TraceStatus::Error, 12)]);
     }
 }
@@ -1,12 +1,15 @@
 //! Observability and metrics collection for fissio pipelines.
 
 mod collector;
+mod reconcile;
 mod store;
 mod trace;
 
-pub use collector::TracingCollector;
-pub use store::{MetricsSummary, StoreError, TraceStore};
-pub use trace::{SpanRecord, ToolCallRecord, TraceQuery, TraceRecord, TraceStatus};
+pub use collector::{RunObserver, TracingCollector};
+pub use reconcile::{reconcile_usage, ModelUsage, ReconciliationReport, UsageDiscrepancy, UsageStatus};
+pub use store::{content_hash, MetricsSummary, PipelineCost, StoreError, TraceStore};
+pub use fissio_core::ModelPricing;
+pub use trace::{ContentRecord, SpanRecord, ToolCallRecord, TraceQuery, TraceRecord, TraceStatus};
 
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
@@ -29,6 +32,10 @@ pub struct ObserveConfig {
     /// Include cost estimation (requires model pricing).
     #[serde(default)]
     pub cost: bool,
+    /// How much of the node's input and output to log and store in spans.
+    /// `None` uses the engine's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<CaptureMode>,
 }
 
 fn default_true() -> bool {
@@ -44,6 +51,7 @@ impl ObserveConfig {
             latency: true,
             tool_calls: true,
             cost: false,
+            capture: None,
         }
     }
 
@@ -67,6 +75,53 @@ impl ObserveConfig {
         self.cost = enabled;
         self
     }
+
+    pub fn with_capture(mut self, capture: CaptureMode) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+}
+
+/// Number of characters kept by [`CaptureMode::Preview`].
+pub const PREVIEW_CHARS: usize = 100;
+
+/// How much prompt and response text is logged and traced for a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureMode {
+    /// Record only the length; use for nodes handling sensitive data.
+    Off,
+    /// Record the first [`PREVIEW_CHARS`] characters.
+    Preview,
+    /// Record the full text.
+    #[default]
+    Full,
+}
+
+impl CaptureMode {
+    /// Returns `text` reduced according to this mode.
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            CaptureMode::Off => format!("[{} chars redacted]", text.chars().count()),
+            CaptureMode::Preview if text.chars().count() > PREVIEW_CHARS => {
+                format!("{}...", text.chars().take(PREVIEW_CHARS).collect::<String>())
+            }
+            CaptureMode::Preview | CaptureMode::Full => text.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for CaptureMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(CaptureMode::Off),
+            "preview" => Ok(CaptureMode::Preview),
+            "full" => Ok(CaptureMode::Full),
+            other => Err(format!("Unknown capture mode: {}", other)),
+        }
+    }
 }
 
 /// Metrics collected from a single node execution.
@@ -74,6 +129,9 @@ impl ObserveConfig {
 pub struct NodeMetrics {
     /// Node identifier.
     pub node_id: String,
+    /// Provider model name used by this node (None for nodes that make no LLM call).
+    #[serde(default)]
+    pub model: Option<String>,
     /// Input tokens consumed.
     pub input_tokens: u32,
     /// Output tokens generated.
@@ -86,6 +144,19 @@ pub struct NodeMetrics {
     pub iteration_count: u32,
     /// Estimated cost in USD (if pricing configured).
     pub estimated_cost_usd: Option<f64>,
+    /// Share of the checked answer supported by its sources (Grounding nodes only).
+    #[serde(default)]
+    pub groundedness: Option<f64>,
+    /// Provider request ids of the node's LLM calls, for support tickets.
+    #[serde(default)]
+    pub provider_request_ids: Vec<String>,
+    /// The node's tool calls in the order they were made. Their `call_id` and
+    /// `span_id` are assigned when the span is recorded.
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// Why the node failed, when the run carried on without its output.
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 impl NodeMetrics {
@@ -136,6 +207,7 @@ pub trait MetricsCollector: Send + Sync {
     /// Record metrics from a node execution.
     fn record(&self, metrics: NodeMetrics);
     /// Record a span with node I/O for detailed tracing.
+    #[allow(clippy::too_many_arguments)]
     fn record_span(
         &self,
         _node_id: &str,
@@ -217,30 +289,6 @@ impl MetricsCollector for InMemoryCollector {
     }
 }
 
-/// Model pricing for cost estimation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ModelPricing {
-    /// Cost per 1K input tokens in USD.
-    pub input_per_1k: f64,
-    /// Cost per 1K output tokens in USD.
-    pub output_per_1k: f64,
-}
-
-impl ModelPricing {
-    pub fn new(input_per_1k: f64, output_per_1k: f64) -> Self {
-        Self {
-            input_per_1k,
-            output_per_1k,
-        }
-    }
-
-    /// Estimate cost for given token counts.
-    pub fn estimate(&self, input_tokens: u32, output_tokens: u32) -> f64 {
-        (input_tokens as f64 / 1000.0) * self.input_per_1k
-            + (output_tokens as f64 / 1000.0) * self.output_per_1k
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +301,17 @@ mod tests {
         assert!(config.latency);
         assert!(config.tool_calls);
         assert!(!config.cost);
+        assert_eq!(config.capture, None);
+    }
+
+    #[test]
+    fn test_capture_mode() {
+        let long = "x".repeat(PREVIEW_CHARS + 20);
+        assert_eq!(CaptureMode::Off.apply("secret"), "[6 chars redacted]");
+        assert_eq!(CaptureMode::Preview.apply("short"), "short");
+        assert_eq!(CaptureMode::Preview.apply(&long).len(), PREVIEW_CHARS + 3);
+        assert_eq!(CaptureMode::Full.apply(&long), long);
+        assert_eq!("OFF".parse::<CaptureMode>(), Ok(CaptureMode::Off));
     }
 
     #[test]
@@ -261,22 +320,32 @@ mod tests {
 
         collector.record(NodeMetrics {
             node_id: "node1".to_string(),
+            model: None,
             input_tokens: 100,
             output_tokens: 50,
             elapsed_ms: 200,
             tool_call_count: 2,
             iteration_count: 1,
             estimated_cost_usd: None,
+            groundedness: None,
+            provider_request_ids: Vec::new(),
+            tool_calls: Vec::new(),
+            error: None,
         });
 
         collector.record(NodeMetrics {
             node_id: "node2".to_string(),
+            model: None,
             input_tokens: 150,
             output_tokens: 75,
             elapsed_ms: 300,
             tool_call_count: 0,
             iteration_count: 1,
             estimated_cost_usd: None,
+            groundedness: None,
+            provider_request_ids: Vec::new(),
+            tool_calls: Vec::new(),
+            error: None,
         });
 
         let metrics = collector.flush();
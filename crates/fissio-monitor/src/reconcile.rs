@@ -0,0 +1,187 @@
+//! Reconciliation of recorded token usage against provider-reported usage.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Token usage for a single model over a time window.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsage {
+    /// Model name.
+    pub model: String,
+    /// Input tokens.
+    pub input_tokens: u64,
+    /// Output tokens.
+    pub output_tokens: u64,
+    /// Number of spans (internal) or requests (provider) contributing to the totals.
+    pub spans: u64,
+}
+
+/// Outcome of comparing one model's recorded and provider-reported usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageStatus {
+    /// Totals agree within tolerance.
+    Match,
+    /// Both sides report usage but totals differ beyond tolerance.
+    Mismatch,
+    /// Provider billed usage that was never recorded internally.
+    MissingInternal,
+    /// Usage was recorded internally but the provider reports none.
+    MissingProvider,
+}
+
+/// Per-model comparison row in a [`ReconciliationReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDiscrepancy {
+    /// Normalized model name (dated snapshot suffixes removed).
+    pub model: String,
+    pub internal_input_tokens: u64,
+    pub internal_output_tokens: u64,
+    pub provider_input_tokens: u64,
+    pub provider_output_tokens: u64,
+    /// Provider total minus internal total (positive means under-recorded).
+    pub token_delta: i64,
+    pub status: UsageStatus,
+}
+
+/// Result of reconciling recorded usage against a provider for a time window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Provider name (e.g., "openai").
+    pub provider: String,
+    /// Window start (Unix milliseconds).
+    pub start_ms: i64,
+    /// Window end (Unix milliseconds).
+    pub end_ms: i64,
+    /// Relative tolerance used to classify matches.
+    pub tolerance: f64,
+    /// One row per model seen on either side.
+    pub models: Vec<UsageDiscrepancy>,
+}
+
+impl ReconciliationReport {
+    /// Returns rows whose status is not [`UsageStatus::Match`].
+    pub fn discrepancies(&self) -> impl Iterator<Item = &UsageDiscrepancy> {
+        self.models.iter().filter(|m| m.status != UsageStatus::Match)
+    }
+}
+
+/// Compares internal and provider usage per model.
+///
+/// Model names are normalized so that dated snapshots reported by the provider
+/// (`gpt-4o-mini-2024-07-18`) line up with configured names (`gpt-4o-mini`).
+/// A model matches when its total tokens differ by at most `tolerance` relative
+/// to the larger side.
+pub fn reconcile_usage(
+    provider: &str,
+    start_ms: i64,
+    end_ms: i64,
+    internal: &[ModelUsage],
+    reported: &[ModelUsage],
+    tolerance: f64,
+) -> ReconciliationReport {
+    let mut rows: BTreeMap<String, (u64, u64, u64, u64)> = BTreeMap::new();
+    for u in internal {
+        let row = rows.entry(normalize_model(&u.model)).or_default();
+        row.0 += u.input_tokens;
+        row.1 += u.output_tokens;
+    }
+    for u in reported {
+        let row = rows.entry(normalize_model(&u.model)).or_default();
+        row.2 += u.input_tokens;
+        row.3 += u.output_tokens;
+    }
+
+    let models = rows
+        .into_iter()
+        .map(|(model, (ii, io, pi, po))| {
+            let internal_total = ii + io;
+            let provider_total = pi + po;
+            let status = match (internal_total, provider_total) {
+                (0, 0) => UsageStatus::Match,
+                (0, _) => UsageStatus::MissingInternal,
+                (_, 0) => UsageStatus::MissingProvider,
+                (i, p) => {
+                    let diff = i.abs_diff(p) as f64;
+                    if diff <= tolerance * i.max(p) as f64 {
+                        UsageStatus::Match
+                    } else {
+                        UsageStatus::Mismatch
+                    }
+                }
+            };
+            UsageDiscrepancy {
+                model,
+                internal_input_tokens: ii,
+                internal_output_tokens: io,
+                provider_input_tokens: pi,
+                provider_output_tokens: po,
+                token_delta: provider_total as i64 - internal_total as i64,
+                status,
+            }
+        })
+        .collect();
+
+    ReconciliationReport {
+        provider: provider.to_string(),
+        start_ms,
+        end_ms,
+        tolerance,
+        models,
+    }
+}
+
+/// Strips a trailing `-YYYY-MM-DD` snapshot suffix from a model name.
+fn normalize_model(model: &str) -> String {
+    let Some(split) = model.len().checked_sub(11).filter(|&i| model.is_char_boundary(i)) else {
+        return model.to_string();
+    };
+    let (base, suffix) = model.split_at(split);
+    let is_date = suffix.bytes().enumerate().all(|(i, c)| match i {
+        0 | 5 | 8 => c == b'-',
+        _ => c.is_ascii_digit(),
+    });
+    if is_date && !base.is_empty() {
+        base.to_string()
+    } else {
+        model.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(model: &str, input: u64, output: u64) -> ModelUsage {
+        ModelUsage {
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+            spans: 1,
+        }
+    }
+
+    #[test]
+    fn test_normalize_model() {
+        assert_eq!(normalize_model("gpt-4o-mini-2024-07-18"), "gpt-4o-mini");
+        assert_eq!(normalize_model("gpt-4o-mini"), "gpt-4o-mini");
+        assert_eq!(normalize_model("gpt-5.2-codex"), "gpt-5.2-codex");
+    }
+
+    #[test]
+    fn test_reconcile_usage() {
+        let internal = vec![usage("gpt-4o-mini", 1000, 500), usage("gpt-4o", 100, 100)];
+        let reported = vec![
+            usage("gpt-4o-mini-2024-07-18", 1010, 500),
+            usage("gpt-4.1", 300, 50),
+        ];
+
+        let report = reconcile_usage("openai", 0, 1, &internal, &reported, 0.05);
+        let status = |m: &str| report.models.iter().find(|r| r.model == m).unwrap().status;
+
+        assert_eq!(status("gpt-4o-mini"), UsageStatus::Match);
+        assert_eq!(status("gpt-4o"), UsageStatus::MissingProvider);
+        assert_eq!(status("gpt-4.1"), UsageStatus::MissingInternal);
+        assert_eq!(report.discrepancies().count(), 2);
+    }
+}
@@ -1,8 +1,10 @@
 //! SQLite-backed trace storage.
 
-use crate::trace::{SpanRecord, ToolCallRecord, TraceQuery, TraceRecord, TraceStatus};
-use rusqlite::{params, Connection};
+use crate::reconcile::ModelUsage;
+use crate::trace::{ContentRecord, SpanRecord, ToolCallRecord, TraceQuery, TraceRecord, TraceStatus};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::path::Path;
 use std::sync::Mutex;
 use thiserror::Error;
@@ -18,7 +20,34 @@ pub enum StoreError {
     Serialization(#[from] serde_json::Error),
 }
 
+/// Span columns, with input and output resolved from the content table.
+///
+/// Spans written before content deduplication keep their text inline.
+const SPAN_SELECT: &str = r#"SELECT s.span_id, s.trace_id, s.node_id, s.node_type, s.start_time, s.end_time,
+   COALESCE(ci.body, s.input), COALESCE(co.body, s.output), s.input_tokens, s.output_tokens,
+   s.tool_call_count, s.iteration_count, s.model, s.input_hash, s.output_hash, s.groundedness,
+   s.provider_request_ids, s.estimated_cost_usd, s.error
+   FROM spans s
+   LEFT JOIN contents ci ON ci.hash = s.input_hash
+   LEFT JOIN contents co ON co.hash = s.output_hash"#;
+
+/// Tool call columns, in the order [`tool_call_from_row`] reads them.
+const TOOL_CALL_SELECT: &str =
+    "SELECT call_id, span_id, tool_name, arguments, result, elapsed_ms, start_time FROM tool_calls";
+
+/// Returns the hex-encoded SHA-256 of `text`, the key spans use to share content.
+pub fn content_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 /// SQLite-backed trace storage.
+///
+/// Span inputs and outputs are stored once per distinct text in a reference-counted
+/// `contents` table, so a large context fanned out to parallel nodes, or repeated
+/// across runs, is kept as a single copy.
 pub struct TraceStore {
     conn: Mutex<Connection>,
 }
@@ -68,6 +97,7 @@ impl TraceStore {
                 trace_id TEXT NOT NULL,
                 node_id TEXT NOT NULL,
                 node_type TEXT NOT NULL,
+                model TEXT,
                 start_time INTEGER NOT NULL,
                 end_time INTEGER NOT NULL,
                 input TEXT NOT NULL,
@@ -79,6 +109,12 @@ impl TraceStore {
                 FOREIGN KEY (trace_id) REFERENCES traces(trace_id)
             );
 
+            CREATE TABLE IF NOT EXISTS contents (
+                hash TEXT PRIMARY KEY,
+                body TEXT NOT NULL,
+                ref_count INTEGER NOT NULL
+            );
+
             CREATE TABLE IF NOT EXISTS tool_calls (
                 call_id TEXT PRIMARY KEY,
                 span_id TEXT NOT NULL,
@@ -96,6 +132,35 @@ impl TraceStore {
             "#,
         )?;
 
+        // Databases created before spans recorded a model, content hashes, a
+        // groundedness score, provider request ids, costs, or errors, or before
+        // tool calls recorded a start time, lack the columns.
+        for (table, column, sql_type) in [
+            ("spans", "model", "TEXT"),
+            ("spans", "input_hash", "TEXT"),
+            ("spans", "output_hash", "TEXT"),
+            ("spans", "groundedness", "REAL"),
+            ("spans", "provider_request_ids", "TEXT"),
+            ("spans", "estimated_cost_usd", "REAL"),
+            ("spans", "error", "TEXT"),
+            ("traces", "total_cost_usd", "REAL"),
+            ("traces", "owner", "TEXT"),
+            ("tool_calls", "start_time", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            let exists: bool = conn
+                .prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")?
+                .exists(params![table, column])?;
+            if !exists {
+                conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, sql_type), [])?;
+            }
+        }
+        conn.execute_batch(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_spans_input_hash ON spans(input_hash);
+            CREATE INDEX IF NOT EXISTS idx_spans_output_hash ON spans(output_hash);
+            "#,
+        )?;
+
         Ok(())
     }
 
@@ -107,8 +172,8 @@ impl TraceStore {
             r#"INSERT INTO traces
                (trace_id, pipeline_id, pipeline_name, timestamp, input, output,
                 total_elapsed_ms, total_input_tokens, total_output_tokens,
-                total_tool_calls, status)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+                total_tool_calls, status, total_cost_usd, owner)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
             params![
                 trace.trace_id,
                 trace.pipeline_id,
@@ -121,6 +186,8 @@ impl TraceStore {
                 trace.total_output_tokens,
                 trace.total_tool_calls,
                 trace.status.as_str(),
+                trace.total_cost_usd,
+                trace.owner,
             ],
         )?;
 
@@ -134,8 +201,8 @@ impl TraceStore {
         conn.execute(
             r#"UPDATE traces SET
                output = ?1, total_elapsed_ms = ?2, total_input_tokens = ?3,
-               total_output_tokens = ?4, total_tool_calls = ?5, status = ?6
-               WHERE trace_id = ?7"#,
+               total_output_tokens = ?4, total_tool_calls = ?5, status = ?6, total_cost_usd = ?7
+               WHERE trace_id = ?8"#,
             params![
                 trace.output,
                 trace.total_elapsed_ms,
@@ -143,6 +210,7 @@ impl TraceStore {
                 trace.total_output_tokens,
                 trace.total_tool_calls,
                 trace.status.as_str(),
+                trace.total_cost_usd,
                 trace.trace_id,
             ],
         )?;
@@ -150,6 +218,13 @@ impl TraceStore {
         Ok(())
     }
 
+    /// Sets the tenant that owns a trace.
+    pub fn set_trace_owner(&self, trace_id: &str, owner: &str) -> Result<(), StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+        conn.execute("UPDATE traces SET owner = ?1 WHERE trace_id = ?2", params![owner, trace_id])?;
+        Ok(())
+    }
+
     /// Retrieves a trace by ID.
     pub fn get_trace(&self, trace_id: &str) -> Result<Option<TraceRecord>, StoreError> {
         let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
@@ -157,7 +232,7 @@ impl TraceStore {
         let mut stmt = conn.prepare(
             r#"SELECT trace_id, pipeline_id, pipeline_name, timestamp, input, output,
                total_elapsed_ms, total_input_tokens, total_output_tokens,
-               total_tool_calls, status
+               total_tool_calls, status, total_cost_usd, owner
                FROM traces WHERE trace_id = ?1"#,
         )?;
 
@@ -173,7 +248,9 @@ impl TraceStore {
                 total_input_tokens: row.get(7)?,
                 total_output_tokens: row.get(8)?,
                 total_tool_calls: row.get(9)?,
+                total_cost_usd: row.get(11)?,
                 status: TraceStatus::from_str(&row.get::<_, String>(10)?),
+                owner: row.get(12)?,
             })
         });
 
@@ -191,7 +268,7 @@ impl TraceStore {
         let mut sql = String::from(
             r#"SELECT trace_id, pipeline_id, pipeline_name, timestamp, input, output,
                total_elapsed_ms, total_input_tokens, total_output_tokens,
-               total_tool_calls, status
+               total_tool_calls, status, total_cost_usd, owner
                FROM traces WHERE 1=1"#,
         );
 
@@ -207,6 +284,21 @@ impl TraceStore {
             params_vec.push(Box::new(status.as_str().to_string()));
         }
 
+        if let Some(start_ms) = query.start_ms {
+            sql.push_str(" AND timestamp >= ?");
+            params_vec.push(Box::new(start_ms));
+        }
+
+        if let Some(end_ms) = query.end_ms {
+            sql.push_str(" AND timestamp < ?");
+            params_vec.push(Box::new(end_ms));
+        }
+
+        if let Some(ref owner) = query.owner {
+            sql.push_str(" AND owner = ?");
+            params_vec.push(Box::new(owner.clone()));
+        }
+
         sql.push_str(" ORDER BY timestamp DESC");
 
         if let Some(limit) = query.limit {
@@ -233,7 +325,9 @@ impl TraceStore {
                 total_input_tokens: row.get(7)?,
                 total_output_tokens: row.get(8)?,
                 total_tool_calls: row.get(9)?,
+                total_cost_usd: row.get(11)?,
                 status: TraceStatus::from_str(&row.get::<_, String>(10)?),
+                owner: row.get(12)?,
             })
         })?;
 
@@ -245,15 +339,25 @@ impl TraceStore {
         Ok(traces)
     }
 
-    /// Inserts a span record.
+    /// Inserts a span record, storing its input and output in the content table.
+    ///
+    /// The span's hash fields are ignored; hashes are always computed from the text.
     pub fn insert_span(&self, span: &SpanRecord) -> Result<(), StoreError> {
-        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+        let mut conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+        let tx = conn.transaction()?;
 
-        conn.execute(
+        let input_hash = put_content(&tx, &span.input)?;
+        let output_hash = put_content(&tx, &span.output)?;
+        let request_ids = (!span.provider_request_ids.is_empty())
+            .then(|| serde_json::to_string(&span.provider_request_ids))
+            .transpose()?;
+
+        tx.execute(
             r#"INSERT INTO spans
                (span_id, trace_id, node_id, node_type, start_time, end_time,
-                input, output, input_tokens, output_tokens, tool_call_count, iteration_count)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"#,
+                input, output, input_tokens, output_tokens, tool_call_count, iteration_count, model,
+                input_hash, output_hash, groundedness, provider_request_ids, estimated_cost_usd, error)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, '', '', ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)"#,
             params![
                 span.span_id,
                 span.trace_id,
@@ -261,15 +365,21 @@ impl TraceStore {
                 span.node_type,
                 span.start_time,
                 span.end_time,
-                span.input,
-                span.output,
                 span.input_tokens,
                 span.output_tokens,
                 span.tool_call_count,
                 span.iteration_count,
+                span.model,
+                input_hash,
+                output_hash,
+                span.groundedness,
+                request_ids,
+                span.estimated_cost_usd,
+                span.error,
             ],
         )?;
 
+        tx.commit()?;
         Ok(())
     }
 
@@ -277,28 +387,50 @@ impl TraceStore {
     pub fn get_spans(&self, trace_id: &str) -> Result<Vec<SpanRecord>, StoreError> {
         let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
 
-        let mut stmt = conn.prepare(
-            r#"SELECT span_id, trace_id, node_id, node_type, start_time, end_time,
-               input, output, input_tokens, output_tokens, tool_call_count, iteration_count
-               FROM spans WHERE trace_id = ?1 ORDER BY start_time"#,
-        )?;
+        let mut stmt = conn.prepare(&format!("{} WHERE s.trace_id = ?1 ORDER BY s.start_time", SPAN_SELECT))?;
+        let rows = stmt.query_map(params![trace_id], span_from_row)?;
 
-        let rows = stmt.query_map(params![trace_id], |row| {
-            Ok(SpanRecord {
-                span_id: row.get(0)?,
-                trace_id: row.get(1)?,
-                node_id: row.get(2)?,
-                node_type: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                input: row.get(6)?,
-                output: row.get(7)?,
-                input_tokens: row.get(8)?,
-                output_tokens: row.get(9)?,
-                tool_call_count: row.get(10)?,
-                iteration_count: row.get(11)?,
-            })
-        })?;
+        let mut spans = Vec::new();
+        for row in rows {
+            spans.push(row?);
+        }
+
+        Ok(spans)
+    }
+
+    /// Gets a stored input or output by its content hash.
+    pub fn get_content(&self, hash: &str) -> Result<Option<ContentRecord>, StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+
+        let content = conn
+            .query_row(
+                "SELECT hash, body, ref_count FROM contents WHERE hash = ?1",
+                params![hash],
+                |row| {
+                    Ok(ContentRecord {
+                        hash: row.get(0)?,
+                        body: row.get(1)?,
+                        ref_count: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(content)
+    }
+
+    /// Gets every span whose input or output has the given hash, across all
+    /// traces owned by `owner` (or across every trace when `owner` is `None`).
+    pub fn spans_with_content(&self, hash: &str, owner: Option<&str>) -> Result<Vec<SpanRecord>, StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+
+        let mut stmt = conn.prepare(&format!(
+            r#"{} WHERE (s.input_hash = ?1 OR s.output_hash = ?1)
+               AND s.trace_id IN (SELECT trace_id FROM traces WHERE ?2 IS NULL OR owner = ?2)
+               ORDER BY s.start_time DESC"#,
+            SPAN_SELECT
+        ))?;
+        let rows = stmt.query_map(params![hash, owner], span_from_row)?;
 
         let mut spans = Vec::new();
         for row in rows {
@@ -313,8 +445,8 @@ impl TraceStore {
         let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
 
         conn.execute(
-            r#"INSERT INTO tool_calls (call_id, span_id, tool_name, arguments, result, elapsed_ms)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+            r#"INSERT INTO tool_calls (call_id, span_id, tool_name, arguments, result, elapsed_ms, start_time)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
             params![
                 call.call_id,
                 call.span_id,
@@ -322,6 +454,7 @@ impl TraceStore {
                 serde_json::to_string(&call.arguments)?,
                 call.result,
                 call.elapsed_ms,
+                call.start_time,
             ],
         )?;
 
@@ -332,22 +465,26 @@ impl TraceStore {
     pub fn get_tool_calls(&self, span_id: &str) -> Result<Vec<ToolCallRecord>, StoreError> {
         let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
 
-        let mut stmt = conn.prepare(
-            r#"SELECT call_id, span_id, tool_name, arguments, result, elapsed_ms
-               FROM tool_calls WHERE span_id = ?1"#,
-        )?;
+        let mut stmt = conn.prepare(&format!("{} WHERE span_id = ?1 ORDER BY start_time", TOOL_CALL_SELECT))?;
+        let rows = stmt.query_map(params![span_id], tool_call_from_row)?;
 
-        let rows = stmt.query_map(params![span_id], |row| {
-            let args_str: String = row.get(3)?;
-            Ok(ToolCallRecord {
-                call_id: row.get(0)?,
-                span_id: row.get(1)?,
-                tool_name: row.get(2)?,
-                arguments: serde_json::from_str(&args_str).unwrap_or(serde_json::Value::Null),
-                result: row.get(4)?,
-                elapsed_ms: row.get(5)?,
-            })
-        })?;
+        let mut calls = Vec::new();
+        for row in rows {
+            calls.push(row?);
+        }
+
+        Ok(calls)
+    }
+
+    /// Gets the tool calls of every span in a trace, in the order they started.
+    pub fn get_trace_tool_calls(&self, trace_id: &str) -> Result<Vec<ToolCallRecord>, StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE span_id IN (SELECT span_id FROM spans WHERE trace_id = ?1) ORDER BY start_time",
+            TOOL_CALL_SELECT
+        ))?;
+        let rows = stmt.query_map(params![trace_id], tool_call_from_row)?;
 
         let mut calls = Vec::new();
         for row in rows {
@@ -358,27 +495,105 @@ impl TraceStore {
     }
 
     /// Deletes a trace and all its spans and tool calls.
+    ///
+    /// Content no longer referenced by any span is removed.
     pub fn delete_trace(&self, trace_id: &str) -> Result<(), StoreError> {
-        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+        let mut conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+        let tx = conn.transaction()?;
 
         // Delete tool calls for all spans in this trace
-        conn.execute(
+        tx.execute(
             r#"DELETE FROM tool_calls WHERE span_id IN
                (SELECT span_id FROM spans WHERE trace_id = ?1)"#,
             params![trace_id],
         )?;
 
+        // Release the spans' content references
+        tx.execute(
+            r#"UPDATE contents SET ref_count = ref_count
+               - (SELECT COUNT(*) FROM spans WHERE trace_id = ?1 AND input_hash = contents.hash)
+               - (SELECT COUNT(*) FROM spans WHERE trace_id = ?1 AND output_hash = contents.hash)
+               WHERE hash IN (SELECT input_hash FROM spans WHERE trace_id = ?1
+                              UNION SELECT output_hash FROM spans WHERE trace_id = ?1)"#,
+            params![trace_id],
+        )?;
+        tx.execute("DELETE FROM contents WHERE ref_count <= 0", [])?;
+
         // Delete spans
-        conn.execute("DELETE FROM spans WHERE trace_id = ?1", params![trace_id])?;
+        tx.execute("DELETE FROM spans WHERE trace_id = ?1", params![trace_id])?;
 
         // Delete trace
-        conn.execute("DELETE FROM traces WHERE trace_id = ?1", params![trace_id])?;
+        tx.execute("DELETE FROM traces WHERE trace_id = ?1", params![trace_id])?;
 
+        tx.commit()?;
         Ok(())
     }
 
-    /// Gets aggregate metrics for the dashboard.
-    pub fn get_metrics_summary(&self) -> Result<MetricsSummary, StoreError> {
+    /// Sums recorded span token usage per model for spans that started in `[start_ms, end_ms)`.
+    pub fn usage_by_model(&self, start_ms: i64, end_ms: i64) -> Result<Vec<ModelUsage>, StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+
+        let mut stmt = conn.prepare(
+            r#"SELECT model,
+               COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0), COUNT(*)
+               FROM spans
+               WHERE model IS NOT NULL AND start_time >= ?1 AND start_time < ?2
+               GROUP BY model ORDER BY model"#,
+        )?;
+
+        let rows = stmt.query_map(params![start_ms, end_ms], |row| {
+            Ok(ModelUsage {
+                model: row.get(0)?,
+                input_tokens: row.get(1)?,
+                output_tokens: row.get(2)?,
+                spans: row.get(3)?,
+            })
+        })?;
+
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+
+        Ok(usage)
+    }
+
+    /// Sums runs, tokens, and estimated cost per pipeline for traces started in
+    /// `[start_ms, end_ms)` and owned by `owner` (any, if `None`), most expensive first.
+    pub fn cost_by_pipeline(&self, start_ms: i64, end_ms: i64, owner: Option<&str>) -> Result<Vec<PipelineCost>, StoreError> {
+        let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
+
+        let mut stmt = conn.prepare(
+            r#"SELECT pipeline_id, MAX(pipeline_name), COUNT(*),
+               COALESCE(SUM(total_input_tokens), 0), COALESCE(SUM(total_output_tokens), 0),
+               COALESCE(SUM(total_cost_usd), 0)
+               FROM traces
+               WHERE timestamp >= ?1 AND timestamp < ?2 AND (?3 IS NULL OR owner = ?3)
+               GROUP BY pipeline_id ORDER BY 6 DESC, pipeline_id"#,
+        )?;
+
+        let rows = stmt.query_map(params![start_ms, end_ms, owner], |row| {
+            Ok(PipelineCost {
+                pipeline_id: row.get(0)?,
+                pipeline_name: row.get(1)?,
+                runs: row.get(2)?,
+                total_input_tokens: row.get(3)?,
+                total_output_tokens: row.get(4)?,
+                total_cost_usd: row.get(5)?,
+            })
+        })?;
+
+        let mut costs = Vec::new();
+        for row in rows {
+            costs.push(row?);
+        }
+
+        Ok(costs)
+    }
+
+    /// Gets aggregate metrics for the dashboard over the traces owned by
+    /// `owner`, or over every trace when `owner` is `None`.
+    pub fn get_metrics_summary(&self, owner: Option<&str>) -> Result<MetricsSummary, StoreError> {
         let conn = self.conn.lock().map_err(|_| StoreError::Lock)?;
 
         let mut stmt = conn.prepare(
@@ -387,17 +602,20 @@ impl TraceStore {
                COALESCE(SUM(total_input_tokens), 0) as total_input_tokens,
                COALESCE(SUM(total_output_tokens), 0) as total_output_tokens,
                COALESCE(SUM(total_tool_calls), 0) as total_tool_calls,
-               COALESCE(AVG(total_elapsed_ms), 0) as avg_latency_ms
-               FROM traces"#,
+               COALESCE(AVG(total_elapsed_ms), 0) as avg_latency_ms,
+               COALESCE(SUM(total_cost_usd), 0) as total_cost_usd
+               FROM traces
+               WHERE ?1 IS NULL OR owner = ?1"#,
         )?;
 
-        let summary = stmt.query_row([], |row| {
+        let summary = stmt.query_row(params![owner], |row| {
             Ok(MetricsSummary {
                 total_traces: row.get(0)?,
                 total_input_tokens: row.get(1)?,
                 total_output_tokens: row.get(2)?,
                 total_tool_calls: row.get(3)?,
                 avg_latency_ms: row.get(4)?,
+                total_cost_usd: row.get(5)?,
             })
         })?;
 
@@ -405,6 +623,59 @@ impl TraceStore {
     }
 }
 
+/// Stores `text` in the content table, or adds a reference if it's already there.
+fn put_content(conn: &Connection, text: &str) -> Result<String, StoreError> {
+    let hash = content_hash(text);
+    conn.execute(
+        r#"INSERT INTO contents (hash, body, ref_count) VALUES (?1, ?2, 1)
+           ON CONFLICT(hash) DO UPDATE SET ref_count = ref_count + 1"#,
+        params![hash, text],
+    )?;
+    Ok(hash)
+}
+
+/// Maps a row selected with [`SPAN_SELECT`].
+fn span_from_row(row: &Row) -> rusqlite::Result<SpanRecord> {
+    Ok(SpanRecord {
+        span_id: row.get(0)?,
+        trace_id: row.get(1)?,
+        node_id: row.get(2)?,
+        node_type: row.get(3)?,
+        model: row.get(12)?,
+        start_time: row.get(4)?,
+        end_time: row.get(5)?,
+        input: row.get(6)?,
+        output: row.get(7)?,
+        input_hash: row.get(13)?,
+        output_hash: row.get(14)?,
+        input_tokens: row.get(8)?,
+        output_tokens: row.get(9)?,
+        tool_call_count: row.get(10)?,
+        iteration_count: row.get(11)?,
+        estimated_cost_usd: row.get(17)?,
+        groundedness: row.get(15)?,
+        provider_request_ids: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|ids| serde_json::from_str(&ids).ok())
+            .unwrap_or_default(),
+        error: row.get(18)?,
+    })
+}
+
+/// Maps a row selected with [`TOOL_CALL_SELECT`].
+fn tool_call_from_row(row: &Row) -> rusqlite::Result<ToolCallRecord> {
+    let args_str: String = row.get(3)?;
+    Ok(ToolCallRecord {
+        call_id: row.get(0)?,
+        span_id: row.get(1)?,
+        tool_name: row.get(2)?,
+        arguments: serde_json::from_str(&args_str).unwrap_or(serde_json::Value::Null),
+        result: row.get(4)?,
+        elapsed_ms: row.get(5)?,
+        start_time: row.get(6)?,
+    })
+}
+
 /// Aggregate metrics summary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsSummary {
@@ -413,6 +684,20 @@ pub struct MetricsSummary {
     pub total_output_tokens: u64,
     pub total_tool_calls: u64,
     pub avg_latency_ms: f64,
+    /// Estimated spend in USD across all runs with priced models.
+    pub total_cost_usd: f64,
+}
+
+/// A pipeline's runs and estimated spend over a time window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineCost {
+    pub pipeline_id: String,
+    pub pipeline_name: String,
+    pub runs: u64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    /// Estimated spend in USD; runs without priced models count as zero.
+    pub total_cost_usd: f64,
 }
 
 #[cfg(test)]
@@ -434,7 +719,9 @@ mod tests {
             total_input_tokens: 10,
             total_output_tokens: 20,
             total_tool_calls: 2,
+            total_cost_usd: Some(0.25),
             status: TraceStatus::Success,
+            owner: Some("acme".to_string()),
         };
 
         store.insert_trace(&trace).unwrap();
@@ -445,6 +732,25 @@ mod tests {
 
         let traces = store.list_traces(&TraceQuery::default()).unwrap();
         assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].total_cost_usd, Some(0.25));
+
+        let window = |start_ms, end_ms| TraceQuery { start_ms, end_ms, ..Default::default() };
+        assert_eq!(store.list_traces(&window(Some(1700000000000), Some(1700000001000))).unwrap().len(), 1);
+        assert!(store.list_traces(&window(None, Some(1700000000000))).unwrap().is_empty());
+
+        let owned_by = |owner: &str| TraceQuery { owner: Some(owner.to_string()), ..Default::default() };
+        assert_eq!(store.list_traces(&owned_by("acme")).unwrap().len(), 1);
+        assert!(store.list_traces(&owned_by("globex")).unwrap().is_empty());
+
+        let costs = store.cost_by_pipeline(1700000000000, 1700000001000, None).unwrap();
+        assert_eq!(costs.len(), 1);
+        assert_eq!((costs[0].runs, costs[0].total_cost_usd), (1, 0.25));
+        assert!(store.cost_by_pipeline(0, 1700000000000, None).unwrap().is_empty());
+        assert_eq!(store.cost_by_pipeline(0, i64::MAX, Some("acme")).unwrap().len(), 1);
+        assert!(store.cost_by_pipeline(0, i64::MAX, Some("globex")).unwrap().is_empty());
+        assert_eq!(store.get_metrics_summary(None).unwrap().total_cost_usd, 0.25);
+        assert_eq!(store.get_metrics_summary(Some("acme")).unwrap().total_traces, 1);
+        assert_eq!(store.get_metrics_summary(Some("globex")).unwrap().total_traces, 0);
     }
 
     #[test]
@@ -462,7 +768,9 @@ mod tests {
             total_input_tokens: 5,
             total_output_tokens: 10,
             total_tool_calls: 1,
+            total_cost_usd: None,
             status: TraceStatus::Success,
+            owner: None,
         };
         store.insert_trace(&trace).unwrap();
 
@@ -471,14 +779,21 @@ mod tests {
             trace_id: "trace-1".to_string(),
             node_id: "node-1".to_string(),
             node_type: "llm".to_string(),
+            model: Some("gpt-4o-mini".to_string()),
             start_time: 1700000000000,
             end_time: 1700000000100,
             input: "Hi".to_string(),
             output: "Hello".to_string(),
+            input_hash: None,
+            output_hash: None,
             input_tokens: 5,
             output_tokens: 10,
             tool_call_count: 1,
             iteration_count: 1,
+            estimated_cost_usd: Some(0.0004),
+            groundedness: Some(0.75),
+            provider_request_ids: vec!["req_123".to_string()],
+            error: None,
         };
         store.insert_span(&span).unwrap();
 
@@ -489,14 +804,94 @@ mod tests {
             arguments: serde_json::json!({"query": "test"}),
             result: "result".to_string(),
             elapsed_ms: 50,
+            start_time: 1700000000020,
         };
         store.insert_tool_call(&tool_call).unwrap();
 
         let spans = store.get_spans("trace-1").unwrap();
         assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].groundedness, Some(0.75));
+        assert_eq!(spans[0].estimated_cost_usd, Some(0.0004));
+        assert_eq!(spans[0].provider_request_ids, vec!["req_123".to_string()]);
 
         let calls = store.get_tool_calls("span-1").unwrap();
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].tool_name, "search");
+        assert_eq!(calls[0].start_time, 1700000000020);
+        assert_eq!(store.get_trace_tool_calls("trace-1").unwrap().len(), 1);
+
+        let usage = store.usage_by_model(1700000000000, 1700000001000).unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].model, "gpt-4o-mini");
+        assert_eq!(usage[0].input_tokens, 5);
+    }
+
+    #[test]
+    fn test_span_content_is_deduplicated() {
+        let store = TraceStore::in_memory().unwrap();
+        let context = "shared context ".repeat(100);
+
+        for (trace_id, span_ids) in [("trace-1", ["a", "b"]), ("trace-2", ["c", "d"])] {
+            store
+                .insert_trace(&TraceRecord {
+                    trace_id: trace_id.to_string(),
+                    pipeline_id: "pipe-1".to_string(),
+                    pipeline_name: "Test".to_string(),
+                    timestamp: 1700000000000,
+                    input: "Hi".to_string(),
+                    output: String::new(),
+                    total_elapsed_ms: 0,
+                    total_input_tokens: 0,
+                    total_output_tokens: 0,
+                    total_tool_calls: 0,
+                    total_cost_usd: None,
+                    status: TraceStatus::Running,
+                    owner: None,
+                })
+                .unwrap();
+            for span_id in span_ids {
+                store
+                    .insert_span(&SpanRecord {
+                        span_id: span_id.to_string(),
+                        trace_id: trace_id.to_string(),
+                        node_id: span_id.to_string(),
+                        node_type: "llm".to_string(),
+                        model: None,
+                        start_time: 1700000000000,
+                        end_time: 1700000000100,
+                        input: context.clone(),
+                        output: format!("answer {}", span_id),
+                        input_hash: None,
+                        output_hash: None,
+                        input_tokens: 0,
+                        output_tokens: 0,
+                        tool_call_count: 0,
+                        iteration_count: 1,
+                        estimated_cost_usd: None,
+                        groundedness: None,
+                        provider_request_ids: Vec::new(),
+                        error: None,
+                    })
+                    .unwrap();
+            }
+        }
+
+        let hash = content_hash(&context);
+        let content = store.get_content(&hash).unwrap().unwrap();
+        assert_eq!(content.body, context);
+        assert_eq!(content.ref_count, 4);
+
+        let spans = store.get_spans("trace-1").unwrap();
+        assert_eq!(spans[0].input, context);
+        assert_eq!(spans[0].input_hash.as_deref(), Some(hash.as_str()));
+        assert_eq!(store.spans_with_content(&hash, None).unwrap().len(), 4);
+        assert!(store.spans_with_content(&hash, Some("acme")).unwrap().is_empty());
+
+        store.delete_trace("trace-1").unwrap();
+        assert_eq!(store.get_content(&hash).unwrap().unwrap().ref_count, 2);
+        assert!(store.get_content(&content_hash("answer a")).unwrap().is_none());
+
+        store.delete_trace("trace-2").unwrap();
+        assert!(store.get_content(&hash).unwrap().is_none());
     }
 }
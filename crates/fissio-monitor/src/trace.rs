@@ -25,8 +25,14 @@ pub struct TraceRecord {
     pub total_output_tokens: u32,
     /// Total tool calls across all spans.
     pub total_tool_calls: u32,
+    /// Estimated cost in USD of the run's priced LLM calls; `None` if none were priced.
+    #[serde(default)]
+    pub total_cost_usd: Option<f64>,
     /// Execution status.
     pub status: TraceStatus,
+    /// Tenant that started the run; `None` when runs aren't owned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
 /// Status of a trace execution.
@@ -39,6 +45,9 @@ pub enum TraceStatus {
     Error,
     /// Execution is still in progress.
     Running,
+    /// Execution finished without producing a response.
+    #[serde(rename = "completed_empty")]
+    CompletedEmpty,
 }
 
 impl TraceStatus {
@@ -47,14 +56,17 @@ impl TraceStatus {
             TraceStatus::Success => "success",
             TraceStatus::Error => "error",
             TraceStatus::Running => "running",
+            TraceStatus::CompletedEmpty => "completed_empty",
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "success" => TraceStatus::Success,
             "error" => TraceStatus::Error,
             "running" => TraceStatus::Running,
+            "completed_empty" => TraceStatus::CompletedEmpty,
             _ => TraceStatus::Error,
         }
     }
@@ -71,6 +83,9 @@ pub struct SpanRecord {
     pub node_id: String,
     /// Node type (e.g., "llm", "worker", "router").
     pub node_type: String,
+    /// Provider model name used by this node, if it made LLM calls.
+    #[serde(default)]
+    pub model: Option<String>,
     /// Unix timestamp (milliseconds) when span started.
     pub start_time: i64,
     /// Unix timestamp (milliseconds) when span ended.
@@ -79,6 +94,12 @@ pub struct SpanRecord {
     pub input: String,
     /// Output from this node.
     pub output: String,
+    /// Content hash of `input`, shared by every span with the same input.
+    #[serde(default)]
+    pub input_hash: Option<String>,
+    /// Content hash of `output`.
+    #[serde(default)]
+    pub output_hash: Option<String>,
     /// Input tokens for this span.
     pub input_tokens: u32,
     /// Output tokens for this span.
@@ -87,6 +108,29 @@ pub struct SpanRecord {
     pub tool_call_count: u32,
     /// Number of agentic loop iterations.
     pub iteration_count: u32,
+    /// Estimated cost in USD, if the node's model has pricing.
+    #[serde(default)]
+    pub estimated_cost_usd: Option<f64>,
+    /// Groundedness score recorded by a Grounding node, from 0.0 to 1.0.
+    #[serde(default)]
+    pub groundedness: Option<f64>,
+    /// Provider request ids of the span's LLM calls.
+    #[serde(default)]
+    pub provider_request_ids: Vec<String>,
+    /// Why the node failed, when the run carried on without its output.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A deduplicated span input or output, stored once per distinct text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentRecord {
+    /// SHA-256 of the text, hex-encoded.
+    pub hash: String,
+    /// The text itself.
+    pub body: String,
+    /// Number of span inputs and outputs referencing this content.
+    pub ref_count: u32,
 }
 
 /// A tool call record within a span.
@@ -104,6 +148,9 @@ pub struct ToolCallRecord {
     pub result: String,
     /// Execution time in milliseconds.
     pub elapsed_ms: u64,
+    /// When the call started (Unix ms); 0 for calls recorded before start times were kept.
+    #[serde(default)]
+    pub start_time: i64,
 }
 
 /// Query parameters for listing traces.
@@ -117,4 +164,13 @@ pub struct TraceQuery {
     pub limit: Option<u32>,
     /// Offset for pagination.
     pub offset: Option<u32>,
+    /// Only traces started at or after this time (Unix ms).
+    #[serde(default)]
+    pub start_ms: Option<i64>,
+    /// Only traces started before this time (Unix ms).
+    #[serde(default)]
+    pub end_ms: Option<i64>,
+    /// Only traces owned by this tenant.
+    #[serde(default)]
+    pub owner: Option<String>,
 }
@@ -0,0 +1,152 @@
+//! Long-term agent memory: facts a pipeline keeps across runs.
+//!
+//! Memories are key/value notes grouped by scope, usually a user or session
+//! ID, so one user's memories never surface in another's runs. Search is by
+//! keyword overlap, which needs no embedding model.
+
+mod sqlite;
+mod tools;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use sqlite::SqliteMemory;
+pub use tools::{MemoryReadTool, MemoryWriteTool};
+
+/// Words too common to say anything about relevance.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "do", "does", "for", "from", "has", "have", "how", "i", "in",
+    "is", "it", "me", "my", "of", "on", "or", "that", "the", "this", "to", "was", "what", "when", "where", "which",
+    "who", "why", "with", "you", "your",
+];
+
+/// Errors from memory stores.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MemoryError {
+    #[error("Memory backend error: {0}")]
+    Backend(String),
+}
+
+/// A remembered fact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub key: String,
+    pub value: String,
+    /// Unix time in seconds of the last write.
+    pub updated_at: u64,
+}
+
+/// Storage for long-term memories, partitioned by scope.
+#[async_trait]
+pub trait Memory: Send + Sync {
+    /// Returns the memory stored under `key`.
+    async fn get(&self, scope: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError>;
+
+    /// Stores `value` under `key`, replacing what was there.
+    async fn put(&self, scope: &str, key: &str, value: &str) -> Result<(), MemoryError>;
+
+    /// Returns up to `limit` memories relevant to `query`, best first.
+    /// An empty query returns the most recently written.
+    async fn search(&self, scope: &str, query: &str, limit: usize) -> Result<Vec<MemoryEntry>, MemoryError>;
+}
+
+/// Memory held in process; contents are lost on restart.
+#[derive(Default)]
+pub struct InMemoryMemory {
+    scopes: RwLock<HashMap<String, HashMap<String, MemoryEntry>>>,
+}
+
+impl InMemoryMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Memory for InMemoryMemory {
+    async fn get(&self, scope: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError> {
+        let scopes = self.scopes.read().map_err(|_| MemoryError::Backend("lock poisoned".into()))?;
+        Ok(scopes.get(scope).and_then(|entries| entries.get(key)).cloned())
+    }
+
+    async fn put(&self, scope: &str, key: &str, value: &str) -> Result<(), MemoryError> {
+        let mut scopes = self.scopes.write().map_err(|_| MemoryError::Backend("lock poisoned".into()))?;
+        let entry = MemoryEntry { key: key.to_string(), value: value.to_string(), updated_at: now_secs() };
+        scopes.entry(scope.to_string()).or_default().insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    async fn search(&self, scope: &str, query: &str, limit: usize) -> Result<Vec<MemoryEntry>, MemoryError> {
+        let scopes = self.scopes.read().map_err(|_| MemoryError::Backend("lock poisoned".into()))?;
+        let entries = scopes.get(scope).map(|e| e.values().cloned().collect()).unwrap_or_default();
+        Ok(rank(entries, query, limit))
+    }
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Lowercased words of `text`, without stopwords or duplicates.
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|t| !t.is_empty() && !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Orders `entries` by the share of the query's terms in their key and value,
+/// newest first among equals, dropping those that share none.
+pub(crate) fn rank(entries: Vec<MemoryEntry>, query: &str, limit: usize) -> Vec<MemoryEntry> {
+    let query_terms = terms(query);
+    let mut scored: Vec<(f32, MemoryEntry)> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            if query_terms.is_empty() {
+                return Some((0.0, entry));
+            }
+            let entry_terms = terms(&format!("{} {}", entry.key, entry.value));
+            let matched = query_terms.iter().filter(|t| entry_terms.contains(*t)).count();
+            (matched > 0).then(|| (matched as f32 / query_terms.len() as f32, entry))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.updated_at.cmp(&a.1.updated_at)).then(a.1.key.cmp(&b.1.key)));
+    scored.into_iter().take(limit).map(|(_, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scopes_are_separate() {
+        let memory = InMemoryMemory::new();
+        memory.put("alice", "diet", "vegetarian").await.unwrap();
+        memory.put("bob", "diet", "no restrictions").await.unwrap();
+
+        assert_eq!(memory.get("alice", "diet").await.unwrap().unwrap().value, "vegetarian");
+        assert!(memory.get("carol", "diet").await.unwrap().is_none());
+        assert_eq!(memory.search("bob", "diet", 5).await.unwrap()[0].value, "no restrictions");
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_shared_terms() {
+        let memory = InMemoryMemory::new();
+        memory.put("u", "home_city", "Lives in Lisbon, Portugal").await.unwrap();
+        memory.put("u", "favorite_food", "Grilled sardines").await.unwrap();
+        memory.put("u", "work", "Backend engineer in Lisbon").await.unwrap();
+
+        let hits = memory.search("u", "What is the weather in Lisbon, Portugal?", 5).await.unwrap();
+        let keys: Vec<_> = hits.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys, ["home_city", "work"]);
+
+        assert_eq!(memory.search("u", "the of and", 5).await.unwrap().len(), 3);
+        assert!(memory.search("u", "quantum physics", 5).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! SQLite memory store.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{now_secs, rank, Memory, MemoryEntry, MemoryError};
+
+/// Memory kept in a SQLite file.
+///
+/// Search loads the scope's memories and ranks them in process, which suits
+/// the hundreds of facts a user or session accumulates.
+pub struct SqliteMemory {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteMemory {
+    /// Opens (or creates) a memory database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MemoryError> {
+        Self::init(Connection::open(path).map_err(backend_err)?)
+    }
+
+    /// Creates a store that lives only as long as this value.
+    pub fn in_memory() -> Result<Self, MemoryError> {
+        Self::init(Connection::open_in_memory().map_err(backend_err)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, MemoryError> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS memories (
+                scope TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (scope, key)
+            );
+            "#,
+        )
+        .map_err(backend_err)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, MemoryError> {
+        self.conn.lock().map_err(|_| MemoryError::Backend("lock poisoned".into()))
+    }
+}
+
+fn backend_err(e: rusqlite::Error) -> MemoryError {
+    MemoryError::Backend(e.to_string())
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<MemoryEntry> {
+    Ok(MemoryEntry { key: row.get(0)?, value: row.get(1)?, updated_at: row.get::<_, i64>(2)? as u64 })
+}
+
+#[async_trait]
+impl Memory for SqliteMemory {
+    async fn get(&self, scope: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError> {
+        self.lock()?
+            .query_row(
+                "SELECT key, value, updated_at FROM memories WHERE scope = ?1 AND key = ?2",
+                params![scope, key],
+                entry_from_row,
+            )
+            .optional()
+            .map_err(backend_err)
+    }
+
+    async fn put(&self, scope: &str, key: &str, value: &str) -> Result<(), MemoryError> {
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO memories (scope, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![scope, key, value, now_secs() as i64],
+            )
+            .map_err(backend_err)?;
+        Ok(())
+    }
+
+    async fn search(&self, scope: &str, query: &str, limit: usize) -> Result<Vec<MemoryEntry>, MemoryError> {
+        let entries = {
+            let conn = self.lock()?;
+            let mut stmt = conn
+                .prepare("SELECT key, value, updated_at FROM memories WHERE scope = ?1")
+                .map_err(backend_err)?;
+            let rows = stmt.query_map(params![scope], entry_from_row).map_err(backend_err)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(backend_err)?
+        };
+        Ok(rank(entries, query, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memories_persist_and_replace() {
+        let memory = SqliteMemory::in_memory().unwrap();
+        memory.put("alice", "editor", "Uses Helix").await.unwrap();
+        memory.put("alice", "editor", "Switched to Zed").await.unwrap();
+        memory.put("bob", "editor", "Uses Vim").await.unwrap();
+
+        assert_eq!(memory.get("alice", "editor").await.unwrap().unwrap().value, "Switched to Zed");
+        assert!(memory.get("alice", "shell").await.unwrap().is_none());
+
+        let hits = memory.search("alice", "which editor?", 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].value, "Switched to Zed");
+    }
+}
@@ -0,0 +1,171 @@
+//! The `memory_read` and `memory_write` tools.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use fissio_tools::{Tool, ToolError, ToolMetadata};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{Memory, MemoryEntry};
+
+/// Memories returned when a read doesn't ask for a number.
+const DEFAULT_LIMIT: usize = 5;
+/// Most memories a single read may return.
+const MAX_LIMIT: usize = 50;
+
+#[derive(Serialize)]
+struct ReadOutput {
+    memories: Vec<MemoryEntry>,
+}
+
+/// The `memory_read` tool: looks up memories of one scope by key or by query.
+pub struct MemoryReadTool {
+    memory: Arc<dyn Memory>,
+    scope: String,
+}
+
+impl MemoryReadTool {
+    /// Creates a tool reading `scope`'s memories, e.g. the current user's.
+    pub fn new(memory: Arc<dyn Memory>, scope: impl Into<String>) -> Self {
+        Self { memory, scope: scope.into() }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryReadTool {
+    fn name(&self) -> &str {
+        "memory_read"
+    }
+
+    fn description(&self) -> &str {
+        "Recall facts saved in earlier conversations with this user. Look one up by its key, or search by topic; \
+         with neither, returns the most recent."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Exact key of the memory to read"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Words describing the memories to find"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": format!("Most memories to return (default {})", DEFAULT_LIMIT)
+                }
+            }
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("memory")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let failed = |e: super::MemoryError| ToolError::ExecutionFailed(format!("memory read failed: {}", e));
+        let memories = match args.get("key").and_then(|v| v.as_str()).filter(|k| !k.trim().is_empty()) {
+            Some(key) => self.memory.get(&self.scope, key).await.map_err(failed)?.into_iter().collect(),
+            None => {
+                let query = args.get("query").and_then(|v| v.as_str()).unwrap_or_default();
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map_or(DEFAULT_LIMIT, |l| (l as usize).clamp(1, MAX_LIMIT));
+                self.memory.search(&self.scope, query, limit).await.map_err(failed)?
+            }
+        };
+        serde_json::to_string_pretty(&ReadOutput { memories })
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+/// The `memory_write` tool: saves a fact to one scope's memories.
+pub struct MemoryWriteTool {
+    memory: Arc<dyn Memory>,
+    scope: String,
+}
+
+impl MemoryWriteTool {
+    /// Creates a tool writing to `scope`'s memories, e.g. the current user's.
+    pub fn new(memory: Arc<dyn Memory>, scope: impl Into<String>) -> Self {
+        Self { memory, scope: scope.into() }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryWriteTool {
+    fn name(&self) -> &str {
+        "memory_write"
+    }
+
+    fn description(&self) -> &str {
+        "Save a fact about the user or task to recall in later conversations, such as a preference or a decision. \
+         Writing an existing key replaces its value."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Short descriptive key, e.g. 'preferred_language'"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "The fact to remember"
+                }
+            },
+            "required": ["key", "value"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("memory")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let field = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| ToolError::InvalidArguments(format!("Missing '{}' parameter", name)))
+        };
+        let (key, value) = (field("key")?, field("value")?);
+        self.memory
+            .put(&self.scope, key, value)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("memory write failed: {}", e)))?;
+        Ok(format!("Saved memory '{}'", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryMemory;
+
+    #[tokio::test]
+    async fn test_written_memories_are_read_back() {
+        let memory: Arc<dyn Memory> = Arc::new(InMemoryMemory::new());
+        let write = MemoryWriteTool::new(Arc::clone(&memory), "alice");
+        let read = MemoryReadTool::new(Arc::clone(&memory), "alice");
+
+        write.execute(json!({"key": "timezone", "value": "Europe/Lisbon"})).await.unwrap();
+        let output: Value = serde_json::from_str(&read.execute(json!({"key": "timezone"})).await.unwrap()).unwrap();
+        assert_eq!(output["memories"][0]["value"], "Europe/Lisbon");
+
+        let other = MemoryReadTool::new(memory, "bob");
+        let output: Value = serde_json::from_str(&other.execute(json!({"query": "timezone"})).await.unwrap()).unwrap();
+        assert!(output["memories"].as_array().unwrap().is_empty());
+
+        assert!(matches!(write.execute(json!({"key": "x"})).await, Err(ToolError::InvalidArguments(_))));
+    }
+}
@@ -0,0 +1,111 @@
+//! Splitting documents into chunks for embedding.
+
+use serde::{Deserialize, Serialize};
+
+/// Chunk size limits, in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    /// Maximum characters per chunk.
+    pub max_chars: usize,
+    /// Characters from the end of each chunk repeated at the start of the next,
+    /// so a sentence split across chunks is still found whole in one of them.
+    pub overlap: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self { max_chars: 1500, overlap: 200 }
+    }
+}
+
+impl ChunkConfig {
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars, overlap: 0 }
+    }
+
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap;
+        self
+    }
+}
+
+/// Splits text into chunks of at most `config.max_chars` characters.
+///
+/// Paragraphs (separated by blank lines) are packed together while they fit;
+/// a paragraph longer than the limit is split at whitespace.
+pub fn chunk_text(text: &str, config: ChunkConfig) -> Vec<String> {
+    let max = config.max_chars.max(1);
+    let overlap = config.overlap.min(max / 2);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for piece in text.split("\n\n").flat_map(|p| split_long(p.trim(), max.saturating_sub(overlap + 2).max(1))) {
+        if piece.is_empty() {
+            continue;
+        }
+        let len = current.chars().count();
+        if len > 0 && len + 2 + piece.chars().count() > max {
+            let tail = tail_chars(&current, overlap);
+            chunks.push(std::mem::replace(&mut current, tail));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(piece);
+    }
+    if !current.trim().is_empty() && (chunks.is_empty() || current.chars().count() > overlap) {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Splits `text` into pieces of at most `max` characters, preferring whitespace.
+fn split_long(text: &str, max: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max {
+        let limit = rest.char_indices().nth(max).map_or(rest.len(), |(i, _)| i);
+        let cut = rest[..limit].rfind(char::is_whitespace).filter(|&i| i > 0).unwrap_or(limit);
+        pieces.push(rest[..cut].trim_end());
+        rest = rest[cut..].trim_start();
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Returns roughly the last `n` characters of `text`, starting at a word boundary.
+fn tail_chars(text: &str, n: usize) -> String {
+    if n == 0 {
+        return String::new();
+    }
+    let count = text.chars().count();
+    let start = text.char_indices().nth(count.saturating_sub(n)).map_or(0, |(i, _)| i);
+    let tail = &text[start..];
+    match tail.find(char::is_whitespace) {
+        Some(i) if start > 0 => tail[i..].trim_start().to_string(),
+        _ => tail.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_respect_limit_and_overlap() {
+        let paragraph = "word ".repeat(60);
+        let text = format!("{}\n\n{}\n\n{}", paragraph, paragraph, "short tail");
+        let chunks = chunk_text(&text, ChunkConfig::new(200).with_overlap(20));
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 200));
+        assert!(chunks.last().unwrap().ends_with("short tail"));
+        assert!(chunks[1].starts_with("word"));
+    }
+
+    #[test]
+    fn test_small_text_is_one_chunk() {
+        assert_eq!(chunk_text("one\n\ntwo", ChunkConfig::default()), vec!["one\n\ntwo"]);
+        assert!(chunk_text("  ", ChunkConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! Finding `[n]` citation markers in answers written from [`format_context`](crate::format_context).
+
+use fissio_core::Citation;
+
+use crate::SearchResult;
+
+/// Longest marker body held back while waiting for its closing `]`, e.g. `12, 13, 14`.
+const MAX_MARKER_CHARS: usize = 16;
+
+/// Finds citation markers in a response as it streams in.
+///
+/// Markers are `[n]` or lists like `[1, 3]` whose numbers are positions in the
+/// sources given to [`format_context`](crate::format_context). A marker split
+/// across chunks is reported once its closing `]` arrives, and numbers with no
+/// matching source are ignored.
+pub struct CitationScanner {
+    sources: Vec<SearchResult>,
+    /// Characters of the response before `pending`.
+    offset: usize,
+    /// Tail of the response that may be the start of a marker.
+    pending: String,
+}
+
+impl CitationScanner {
+    pub fn new(sources: Vec<SearchResult>) -> Self {
+        Self { sources, offset: 0, pending: String::new() }
+    }
+
+    /// Adds the next chunk of the response and returns the markers it completes.
+    pub fn push(&mut self, text: &str) -> Vec<Citation> {
+        self.pending.push_str(text);
+        let chars: Vec<char> = self.pending.chars().collect();
+        let mut citations = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            if chars[i] != '[' {
+                i += 1;
+                continue;
+            }
+            let body: String = chars[i + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit() || matches!(c, ',' | ' '))
+                .take(MAX_MARKER_CHARS + 1)
+                .collect();
+            let end = i + 1 + body.chars().count();
+            if end == chars.len() && body.len() <= MAX_MARKER_CHARS {
+                // Possibly a marker still being streamed
+                break;
+            }
+            if chars.get(end) == Some(&']') {
+                citations.extend(self.cite(&body, self.offset + i));
+            }
+            i += 1;
+        }
+
+        self.offset += i;
+        self.pending = chars[i..].iter().collect();
+        citations
+    }
+
+    fn cite(&self, body: &str, offset: usize) -> Vec<Citation> {
+        body.split(',')
+            .filter_map(|n| n.trim().parse::<usize>().ok())
+            .filter_map(|index| {
+                let source = self.sources.get(index.checked_sub(1)?)?;
+                let meta = |key: &str| source.metadata.get(key).and_then(|v| v.as_str()).map(String::from);
+                Some(Citation {
+                    index,
+                    offset,
+                    source_id: source.id.clone(),
+                    title: meta("title"),
+                    url: meta("url"),
+                    snippet: source.text.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the citation markers in a complete response.
+pub fn find_citations(text: &str, sources: &[SearchResult]) -> Vec<Citation> {
+    CitationScanner::new(sources.to_vec()).push(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+
+    fn source(id: &str) -> SearchResult {
+        let mut metadata = Map::new();
+        metadata.insert("title".into(), json!(format!("Doc {}", id)));
+        SearchResult { id: id.into(), score: 0.0, text: format!("text of {}", id), metadata }
+    }
+
+    #[test]
+    fn test_scanner_reports_markers_split_across_chunks() {
+        let mut scanner = CitationScanner::new(vec![source("a"), source("b")]);
+        assert!(scanner.push("Refunds take 5 days [").is_empty());
+        let citations = scanner.push("1, 2]. See [array] and [9]. Déjà [2]");
+
+        let found: Vec<(usize, usize, &str)> =
+            citations.iter().map(|c| (c.index, c.offset, c.source_id.as_str())).collect();
+        assert_eq!(found, vec![(1, 20, "a"), (2, 20, "b"), (2, 54, "b")]);
+        assert_eq!(citations[0].title.as_deref(), Some("Doc a"));
+        assert_eq!(find_citations("Déjà [2]", &[source("a"), source("b")])[0].offset, 5);
+    }
+}
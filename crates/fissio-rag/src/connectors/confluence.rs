@@ -0,0 +1,119 @@
+//! Pages in Confluence spaces.
+
+use async_trait::async_trait;
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+use super::{connector_err, html_to_text, Connector, Document};
+use crate::store::VectorStoreError;
+
+const PAGE_LIMIT: usize = 50;
+
+enum Auth {
+    /// Confluence Cloud: account email and API token.
+    Basic { email: String, api_token: String },
+    /// Confluence Data Center: personal access token.
+    Bearer(String),
+}
+
+/// Reads the pages of one or more Confluence spaces over the REST API.
+///
+/// `base_url` is the wiki root, e.g. `https://example.atlassian.net/wiki`.
+/// Document IDs are Confluence page IDs, and each chunk carries the page's
+/// `space` in its metadata.
+pub struct ConfluenceConnector {
+    base_url: String,
+    auth: Auth,
+    spaces: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl ConfluenceConnector {
+    /// Connects to Confluence Cloud with an account email and API token.
+    pub fn new(base_url: impl Into<String>, email: impl Into<String>, api_token: impl Into<String>) -> Self {
+        Self::with_auth(base_url.into(), Auth::Basic { email: email.into(), api_token: api_token.into() })
+    }
+
+    /// Connects to Confluence Data Center with a personal access token.
+    pub fn with_personal_token(base_url: impl Into<String>, token: impl Into<String>) -> Self {
+        Self::with_auth(base_url.into(), Auth::Bearer(token.into()))
+    }
+
+    fn with_auth(base_url: String, auth: Auth) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth,
+            spaces: Vec::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Adds a space to read, by key. At least one space is required.
+    pub fn with_space(mut self, key: impl Into<String>) -> Self {
+        self.spaces.push(key.into());
+        self
+    }
+
+    fn authed(&self, req: RequestBuilder) -> RequestBuilder {
+        match &self.auth {
+            Auth::Basic { email, api_token } => req.basic_auth(email, Some(api_token)),
+            Auth::Bearer(token) => req.bearer_auth(token),
+        }
+    }
+
+    async fn space_pages(&self, space: &str) -> Result<Vec<Document>, VectorStoreError> {
+        let mut documents = Vec::new();
+        let mut start = 0;
+        loop {
+            let url = format!("{}/rest/api/content", self.base_url);
+            let query = [
+                ("spaceKey", space.to_string()),
+                ("type", "page".to_string()),
+                ("expand", "body.storage".to_string()),
+                ("limit", PAGE_LIMIT.to_string()),
+                ("start", start.to_string()),
+            ];
+            let res = self.authed(self.client.get(url).query(&query)).send().await.map_err(connector_err)?;
+            let status = res.status();
+            if !status.is_success() {
+                return Err(VectorStoreError::Connector(format!("Confluence space {}: {}", space, status)));
+            }
+            let page: Value = res.json().await.map_err(connector_err)?;
+            let results = page["results"].as_array().cloned().unwrap_or_default();
+
+            for result in &results {
+                let Some(id) = result["id"].as_str() else { continue };
+                let title = result["title"].as_str().unwrap_or(id);
+                let html = result["body"]["storage"]["value"].as_str().unwrap_or_default();
+                let mut doc = Document::new(id, title, html_to_text(html)).with_metadata("space", space);
+                if let Some(webui) = result["_links"]["webui"].as_str() {
+                    doc = doc.with_url(format!("{}{}", self.base_url, webui));
+                }
+                documents.push(doc);
+            }
+
+            if results.len() < PAGE_LIMIT || page["_links"]["next"].is_null() {
+                return Ok(documents);
+            }
+            start += results.len();
+        }
+    }
+}
+
+#[async_trait]
+impl Connector for ConfluenceConnector {
+    fn name(&self) -> &str {
+        "confluence"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError> {
+        if self.spaces.is_empty() {
+            return Err(VectorStoreError::Connector("no Confluence spaces configured".into()));
+        }
+        let mut documents = Vec::new();
+        for space in &self.spaces {
+            documents.extend(self.space_pages(space).await?);
+        }
+        Ok(documents)
+    }
+}
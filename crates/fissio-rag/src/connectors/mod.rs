@@ -0,0 +1,308 @@
+//! Document sources that keep a collection in sync.
+//!
+//! A [`Connector`] lists the current documents from a source; [`ConnectorSync`]
+//! indexes new or changed documents with a [`DocumentIngestor`] and removes
+//! deleted ones.
+//! Built-in connectors (`connectors` feature):
+//!
+//! - `SitemapConnector` — Pages listed in a sitemap, fetched with `fetch_url`
+//! - `RssConnector` — Items of an RSS or Atom feed
+//! - `NotionConnector` — Pages shared with a Notion integration
+//! - `ConfluenceConnector` — Pages in Confluence spaces
+
+#[cfg(feature = "connectors")]
+mod confluence;
+#[cfg(feature = "connectors")]
+mod notion;
+#[cfg(feature = "connectors")]
+mod rss;
+#[cfg(feature = "connectors")]
+mod sitemap;
+
+#[cfg(feature = "connectors")]
+pub use confluence::ConfluenceConnector;
+#[cfg(feature = "connectors")]
+pub use notion::NotionConnector;
+#[cfg(feature = "connectors")]
+pub use rss::RssConnector;
+#[cfg(feature = "connectors")]
+pub use sitemap::SitemapConnector;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::chunk::ChunkConfig;
+use crate::ingest::DocumentIngestor;
+use crate::retriever::Retriever;
+use crate::store::VectorStoreError;
+
+/// A document read from a source, before chunking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    /// Stable ID within the source, such as a URL or page ID.
+    pub id: String,
+    pub title: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Copied onto every chunk for filtering.
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+impl Document {
+    pub fn new(id: impl Into<String>, title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { id: id.into(), title: title.into(), text: text.into(), url: None, metadata: Map::new() }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.title, &self.text, &self.url).hash(&mut hasher);
+        serde_json::to_string(&self.metadata).unwrap_or_default().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A source of documents.
+#[async_trait]
+pub trait Connector: Send + Sync {
+    /// Short name stored as the `source` metadata of every chunk.
+    fn name(&self) -> &str;
+
+    /// Returns every document currently in the source.
+    async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError>;
+}
+
+/// Counts from one [`ConnectorSync::sync_once`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+struct Synced {
+    fingerprint: u64,
+    chunks: usize,
+}
+
+/// Keeps a collection in sync with a [`Connector`].
+///
+/// Chunk IDs are `<document id>#<n>`. Each chunk's metadata holds `doc_id`,
+/// `title`, `url`, `source`, and the document's own metadata. The sync state
+/// lives in memory, so the first run after a restart re-indexes every document.
+pub struct ConnectorSync {
+    connector: Arc<dyn Connector>,
+    ingestor: DocumentIngestor,
+    synced: Mutex<HashMap<String, Synced>>,
+}
+
+impl ConnectorSync {
+    pub fn new(connector: Arc<dyn Connector>, retriever: Arc<Retriever>, collection: impl Into<String>) -> Self {
+        let ingestor = DocumentIngestor::new(retriever, collection).with_source(connector.name());
+        Self { connector, ingestor, synced: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn with_chunking(mut self, chunking: ChunkConfig) -> Self {
+        self.ingestor = self.ingestor.with_chunking(chunking);
+        self
+    }
+
+    /// Fetches the source once and applies its changes to the collection.
+    pub async fn sync_once(&self) -> Result<SyncReport, VectorStoreError> {
+        let documents = self.connector.fetch().await?;
+        let mut synced = self.synced.lock().await;
+        let mut report = SyncReport::default();
+        let mut seen = HashSet::with_capacity(documents.len());
+
+        for doc in documents {
+            seen.insert(doc.id.clone());
+            let fingerprint = doc.fingerprint();
+            let previous = synced.get(&doc.id);
+            if previous.is_some_and(|s| s.fingerprint == fingerprint) {
+                report.unchanged += 1;
+                continue;
+            }
+            match previous {
+                Some(s) => {
+                    self.ingestor.remove(&doc.id, s.chunks).await?;
+                    report.updated += 1;
+                }
+                None => report.added += 1,
+            }
+            let chunks = self.ingestor.ingest(&doc).await?;
+            synced.insert(doc.id, Synced { fingerprint, chunks });
+        }
+
+        let removed: Vec<String> = synced.keys().filter(|id| !seen.contains(*id)).cloned().collect();
+        for id in removed {
+            if let Some(s) = synced.remove(&id) {
+                self.ingestor.remove(&id, s.chunks).await?;
+                report.removed += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Runs [`ConnectorSync::sync_once`] now and then every `interval`, logging
+    /// failures and continuing.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.sync_once().await {
+                    Ok(r) => info!(
+                        "Synced {} into '{}': {} added, {} updated, {} removed, {} unchanged",
+                        self.connector.name(),
+                        self.ingestor.collection(),
+                        r.added,
+                        r.updated,
+                        r.removed,
+                        r.unchanged
+                    ),
+                    Err(e) => {
+                        warn!("Sync of {} into '{}' failed: {}", self.connector.name(), self.ingestor.collection(), e)
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Returns the text inside each `<tag>...</tag>` element, unescaped.
+///
+/// Enough XML for sitemaps and feeds: attributes on the opening tag are
+/// allowed, nesting of the same tag is not.
+#[cfg(feature = "connectors")]
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Skip longer tag names sharing the prefix, e.g. <link> vs <linkedin>
+        if !after.starts_with(['>', ' ', '/', '\t', '\n', '\r']) {
+            rest = after;
+            continue;
+        }
+        let Some(gt) = after.find('>') else { break };
+        if after[..gt].ends_with('/') {
+            rest = &after[gt + 1..];
+            continue;
+        }
+        let body = &after[gt + 1..];
+        let Some(end) = body.find(&close) else { break };
+        values.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    values
+}
+
+/// Returns the text of the first `<tag>` element, with CDATA and entities decoded.
+#[cfg(feature = "connectors")]
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag).first().map(|v| xml_unescape(v))
+}
+
+#[cfg(feature = "connectors")]
+fn xml_unescape(value: &str) -> String {
+    let value = value.trim();
+    if let Some(inner) = value.strip_prefix("<![CDATA[").and_then(|v| v.strip_suffix("]]>")) {
+        return inner.to_string();
+    }
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(feature = "connectors")]
+fn connector_err(e: reqwest::Error) -> VectorStoreError {
+    VectorStoreError::Connector(e.to_string())
+}
+
+/// Converts HTML to plain text for chunking.
+pub(crate) fn html_to_text(html: &str) -> String {
+    html2text::from_read(html.as_bytes(), 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::VectorStore;
+    use crate::{CollectionConfig, Embedder, InMemoryVectorStore, RetrievalConfig};
+    use std::sync::Mutex as StdMutex;
+
+    struct StaticConnector(StdMutex<Vec<Document>>);
+
+    #[async_trait]
+    impl Connector for StaticConnector {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    struct UnitEmbedder;
+
+    #[async_trait]
+    impl Embedder for UnitEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+            Ok(texts.iter().map(|_| vec![1.0]).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_tracks_changes() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        store.create_collection("docs", CollectionConfig::new(1)).await.unwrap();
+        let retriever = Arc::new(Retriever::new(store, Arc::new(UnitEmbedder)));
+        let connector = Arc::new(StaticConnector(StdMutex::new(vec![
+            Document::new("a", "A", "first"),
+            Document::new("b", "B", "second"),
+        ])));
+        let sync = ConnectorSync::new(connector.clone(), Arc::clone(&retriever), "docs");
+
+        let report = sync.sync_once().await.unwrap();
+        assert_eq!(report, SyncReport { added: 2, ..Default::default() });
+
+        *connector.0.lock().unwrap() = vec![Document::new("a", "A", "first, edited")];
+        let report = sync.sync_once().await.unwrap();
+        assert_eq!(report, SyncReport { updated: 1, removed: 1, ..Default::default() });
+
+        let results = retriever.retrieve("anything", &RetrievalConfig::new("docs")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].text, "first, edited");
+        assert_eq!(results[0].metadata["source"], "static");
+    }
+}
@@ -0,0 +1,167 @@
+//! Pages shared with a Notion integration.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use reqwest::{RequestBuilder, Response};
+use serde_json::{json, Value};
+
+use super::{connector_err, Connector, Document};
+use crate::store::VectorStoreError;
+
+const API_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+/// How deep to follow nested blocks (toggles, list children).
+const MAX_BLOCK_DEPTH: usize = 3;
+
+/// Reads every page shared with a Notion integration.
+///
+/// Create an internal integration, share the pages or databases to index with
+/// it, and pass its token. Document IDs are Notion page IDs.
+pub struct NotionConnector {
+    token: String,
+    client: reqwest::Client,
+}
+
+impl NotionConnector {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self { token: token.into(), client: reqwest::Client::new() }
+    }
+
+    fn authed(&self, req: RequestBuilder) -> RequestBuilder {
+        req.bearer_auth(&self.token).header("Notion-Version", NOTION_VERSION)
+    }
+
+    async fn pages(&self) -> Result<Vec<Value>, VectorStoreError> {
+        let mut pages = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut body = json!({ "filter": { "property": "object", "value": "page" }, "page_size": 100 });
+            if let Some(c) = &cursor {
+                body["start_cursor"] = json!(c);
+            }
+            let res = self.authed(self.client.post(format!("{}/search", API_URL))).json(&body).send().await;
+            let page = read_json(res).await?;
+            pages.extend(page["results"].as_array().cloned().unwrap_or_default());
+            cursor = next_cursor(&page);
+            if cursor.is_none() {
+                return Ok(pages);
+            }
+        }
+    }
+
+    /// Appends the text of a block's children to `out`, following nested blocks.
+    fn block_text<'a>(
+        &'a self,
+        block_id: &'a str,
+        depth: usize,
+        out: &'a mut Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), VectorStoreError>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut url = format!("{}/blocks/{}/children?page_size=100", API_URL, block_id);
+                if let Some(c) = &cursor {
+                    url.push_str(&format!("&start_cursor={}", c));
+                }
+                let page = read_json(self.authed(self.client.get(url)).send().await).await?;
+                for block in page["results"].as_array().into_iter().flatten() {
+                    if let Some(line) = block_line(block) {
+                        out.push(line);
+                    }
+                    let id = block["id"].as_str().unwrap_or_default();
+                    if block["has_children"].as_bool() == Some(true) && depth < MAX_BLOCK_DEPTH && !id.is_empty() {
+                        self.block_text(id, depth + 1, out).await?;
+                    }
+                }
+                cursor = next_cursor(&page);
+                if cursor.is_none() {
+                    return Ok(());
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Connector for NotionConnector {
+    fn name(&self) -> &str {
+        "notion"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError> {
+        let mut documents = Vec::new();
+        for page in self.pages().await? {
+            let Some(id) = page["id"].as_str() else { continue };
+            let mut lines = Vec::new();
+            self.block_text(id, 0, &mut lines).await?;
+
+            let mut doc = Document::new(id, page_title(&page), lines.join("\n\n"));
+            if let Some(url) = page["url"].as_str() {
+                doc = doc.with_url(url);
+            }
+            if let Some(edited) = page["last_edited_time"].as_str() {
+                doc = doc.with_metadata("last_edited", edited);
+            }
+            documents.push(doc);
+        }
+        Ok(documents)
+    }
+}
+
+async fn read_json(res: Result<Response, reqwest::Error>) -> Result<Value, VectorStoreError> {
+    let res = res.map_err(connector_err)?;
+    let status = res.status();
+    let body: Value = res.json().await.map_err(connector_err)?;
+    if !status.is_success() {
+        let message = body["message"].as_str().unwrap_or("request failed");
+        return Err(VectorStoreError::Connector(format!("Notion {}: {}", status, message)));
+    }
+    Ok(body)
+}
+
+fn next_cursor(page: &Value) -> Option<String> {
+    match page["has_more"].as_bool() {
+        Some(true) => page["next_cursor"].as_str().map(String::from),
+        _ => None,
+    }
+}
+
+/// Returns the page's title property, or "Untitled".
+fn page_title(page: &Value) -> String {
+    page["properties"]
+        .as_object()
+        .and_then(|props| props.values().find(|p| p["type"] == "title"))
+        .map(|p| plain_text(&p["title"]))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Untitled".to_string())
+}
+
+/// Renders a block's rich text as a line, with Markdown-style heading and list markers.
+fn block_line(block: &Value) -> Option<String> {
+    let kind = block["type"].as_str()?;
+    let text = plain_text(&block[kind]["rich_text"]);
+    if text.is_empty() {
+        return None;
+    }
+    let prefix = match kind {
+        "heading_1" => "# ",
+        "heading_2" => "## ",
+        "heading_3" => "### ",
+        "bulleted_list_item" | "numbered_list_item" => "- ",
+        "to_do" => "- [ ] ",
+        "quote" => "> ",
+        _ => "",
+    };
+    Some(format!("{}{}", prefix, text))
+}
+
+fn plain_text(rich_text: &Value) -> String {
+    rich_text
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t["plain_text"].as_str())
+        .collect()
+}
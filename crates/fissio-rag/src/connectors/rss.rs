@@ -0,0 +1,107 @@
+//! Items of an RSS or Atom feed.
+
+use async_trait::async_trait;
+
+use super::{connector_err, html_to_text, xml_elements, xml_text, xml_unescape, Connector, Document};
+use crate::store::VectorStoreError;
+
+/// Reads the items of an RSS 2.0 or Atom feed.
+///
+/// Document IDs are the item `guid` (Atom `id`), falling back to its link. The
+/// text is the full content when the feed includes it, else the summary.
+pub struct RssConnector {
+    feed_url: String,
+    client: reqwest::Client,
+}
+
+impl RssConnector {
+    pub fn new(feed_url: impl Into<String>) -> Self {
+        Self { feed_url: feed_url.into(), client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl Connector for RssConnector {
+    fn name(&self) -> &str {
+        "rss"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError> {
+        let res = self.client.get(&self.feed_url).send().await.map_err(connector_err)?;
+        if !res.status().is_success() {
+            return Err(VectorStoreError::Connector(format!("GET {}: {}", self.feed_url, res.status())));
+        }
+        let feed = res.text().await.map_err(connector_err)?;
+        Ok(parse_feed(&feed))
+    }
+}
+
+fn parse_feed(feed: &str) -> Vec<Document> {
+    let atom = !feed.contains("<item");
+    let entries = xml_elements(feed, if atom { "entry" } else { "item" });
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let link = if atom { atom_link(entry) } else { xml_text(entry, "link") };
+            let id = xml_text(entry, if atom { "id" } else { "guid" }).or_else(|| link.clone())?;
+            let title = xml_text(entry, "title").unwrap_or_else(|| id.clone());
+            let body = ["content:encoded", "content", "description", "summary"]
+                .iter()
+                .find_map(|tag| xml_text(entry, tag))
+                .unwrap_or_default();
+
+            let mut doc = Document::new(id, title, html_to_text(&body));
+            if let Some(link) = link {
+                doc = doc.with_url(link);
+            }
+            if let Some(published) = ["pubDate", "published", "updated"].iter().find_map(|tag| xml_text(entry, tag)) {
+                doc = doc.with_metadata("published", published);
+            }
+            Some(doc)
+        })
+        .collect()
+}
+
+/// Returns the `href` of an Atom entry's `<link>` (preferring `rel="alternate"`).
+fn atom_link(entry: &str) -> Option<String> {
+    let mut rest = entry;
+    let mut first = None;
+    while let Some(start) = rest.find("<link") {
+        let tag = &rest[start..];
+        let end = tag.find('>')?;
+        let attrs = &tag[..end];
+        let href = attrs.split("href=\"").nth(1).and_then(|h| h.split('"').next()).map(xml_unescape);
+        if attrs.contains("rel=\"alternate\"") {
+            return href;
+        }
+        first = first.or(href);
+        rest = &tag[end..];
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let rss = r#"<rss><channel><title>Blog</title>
+            <item><title>Release 2.0</title><link>https://example.com/2.0</link>
+            <guid isPermaLink="false">post-2</guid><description><![CDATA[<p>New &amp; improved</p>]]></description></item>
+        </channel></rss>"#;
+        let docs = parse_feed(rss);
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].id, "post-2");
+        assert_eq!(docs[0].url.as_deref(), Some("https://example.com/2.0"));
+        assert!(docs[0].text.contains("New & improved"));
+
+        let atom = r#"<feed><entry><id>urn:1</id><title>Hello</title>
+            <link rel="alternate" href="https://example.com/hello"/><summary>Hi there</summary></entry></feed>"#;
+        let docs = parse_feed(atom);
+        assert_eq!(docs[0].title, "Hello");
+        assert_eq!(docs[0].url.as_deref(), Some("https://example.com/hello"));
+        assert_eq!(docs[0].text.trim(), "Hi there");
+    }
+}
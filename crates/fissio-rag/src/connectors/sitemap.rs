@@ -0,0 +1,113 @@
+//! Pages listed in a sitemap.
+
+use async_trait::async_trait;
+use fissio_tools::{FetchUrlTool, Tool};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use super::{connector_err, xml_elements, xml_unescape, Connector, Document};
+use crate::store::VectorStoreError;
+
+const DEFAULT_MAX_PAGES: usize = 500;
+const MAX_PAGE_CHARS: u64 = 200_000;
+
+/// Crawls the pages listed in a sitemap (or sitemap index) with `fetch_url`.
+///
+/// Document IDs are page URLs. Pages that fail to load are skipped with a
+/// warning, so a single broken link doesn't stop the sync.
+pub struct SitemapConnector {
+    sitemap_url: String,
+    client: reqwest::Client,
+    fetcher: FetchUrlTool,
+    include: Vec<String>,
+    max_pages: usize,
+}
+
+impl SitemapConnector {
+    /// Creates a connector for the sitemap at `sitemap_url` (e.g. `https://docs.example.com/sitemap.xml`).
+    pub fn new(sitemap_url: impl Into<String>) -> Self {
+        Self {
+            sitemap_url: sitemap_url.into(),
+            client: reqwest::Client::new(),
+            // A crawler, unlike a one-off fetch for an LLM, should honor robots.txt
+            fetcher: FetchUrlTool::new().with_respect_robots(true),
+            include: Vec::new(),
+            max_pages: DEFAULT_MAX_PAGES,
+        }
+    }
+
+    /// Only crawls URLs starting with one of the given prefixes.
+    pub fn with_include(mut self, prefix: impl Into<String>) -> Self {
+        self.include.push(prefix.into());
+        self
+    }
+
+    /// Caps the number of pages crawled per sync (default 500).
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Returns page URLs, following one level of sitemap index.
+    async fn page_urls(&self) -> Result<Vec<String>, VectorStoreError> {
+        let root = self.get_xml(&self.sitemap_url).await?;
+        let mut urls = Vec::new();
+        if root.contains("<sitemapindex") {
+            for sitemap in xml_elements(&root, "sitemap") {
+                let Some(loc) = xml_elements(sitemap, "loc").first().map(|l| xml_unescape(l)) else {
+                    continue;
+                };
+                match self.get_xml(&loc).await {
+                    Ok(xml) => urls.extend(locs(&xml)),
+                    Err(e) => warn!("Skipping sitemap {}: {}", loc, e),
+                }
+            }
+        } else {
+            urls = locs(&root);
+        }
+
+        urls.retain(|u| self.include.is_empty() || self.include.iter().any(|p| u.starts_with(p)));
+        urls.truncate(self.max_pages);
+        Ok(urls)
+    }
+
+    async fn get_xml(&self, url: &str) -> Result<String, VectorStoreError> {
+        let res = self.client.get(url).send().await.map_err(connector_err)?;
+        if !res.status().is_success() {
+            return Err(VectorStoreError::Connector(format!("GET {}: {}", url, res.status())));
+        }
+        res.text().await.map_err(connector_err)
+    }
+}
+
+#[async_trait]
+impl Connector for SitemapConnector {
+    fn name(&self) -> &str {
+        "sitemap"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Document>, VectorStoreError> {
+        let mut documents = Vec::new();
+        for url in self.page_urls().await? {
+            let page = match self.fetcher.execute(json!({ "url": url, "max_length": MAX_PAGE_CHARS })).await {
+                Ok(page) => page,
+                Err(e) => {
+                    warn!("Skipping page {}: {}", url, e);
+                    continue;
+                }
+            };
+            let page: Value = serde_json::from_str(&page).unwrap_or_default();
+            let text = page.get("content").and_then(Value::as_str).unwrap_or_default();
+            let title = page.get("title").and_then(Value::as_str).unwrap_or(&url);
+            documents.push(Document::new(&url, title, text).with_url(&url));
+        }
+        Ok(documents)
+    }
+}
+
+fn locs(xml: &str) -> Vec<String> {
+    xml_elements(xml, "url")
+        .into_iter()
+        .filter_map(|entry| xml_elements(entry, "loc").first().map(|l| xml_unescape(l)))
+        .collect()
+}
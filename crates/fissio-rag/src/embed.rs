@@ -0,0 +1,30 @@
+//! Turning text into vectors.
+
+use async_trait::async_trait;
+
+use crate::store::VectorStoreError;
+
+/// Produces embeddings for queries and documents.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Embeds each text, returning vectors in the same order.
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError>;
+
+    /// Embeds a single text.
+    async fn embed_one(&self, text: &str) -> Result<Vec<f32>, VectorStoreError> {
+        self.embed(&[text.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| VectorStoreError::Embedding("no embedding returned".into()))
+    }
+}
+
+#[async_trait]
+impl Embedder for fissio_llm::EmbeddingClient {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+        fissio_llm::EmbeddingClient::embed(self, texts)
+            .await
+            .map_err(|e| VectorStoreError::Embedding(e.to_string()))
+    }
+}
@@ -0,0 +1,81 @@
+//! Multi-query expansion: searching for several rewordings of a vague question.
+
+use serde::{Deserialize, Serialize};
+
+/// Default number of reformulations generated per query.
+pub const DEFAULT_QUERY_VARIANTS: usize = 3;
+
+/// Multi-query step of a [`RetrievalConfig`](crate::RetrievalConfig).
+///
+/// A model rewrites the query `queries` ways, the retriever searches for the
+/// original and each rewrite, and the result lists are merged with reciprocal
+/// rank fusion. The Retriever node's own `model` writes the rewrites, so point
+/// it at a cheap model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiQueryConfig {
+    /// Reformulations to generate, in addition to the original query.
+    #[serde(default = "default_queries")]
+    pub queries: usize,
+}
+
+fn default_queries() -> usize {
+    DEFAULT_QUERY_VARIANTS
+}
+
+impl Default for MultiQueryConfig {
+    fn default() -> Self {
+        Self { queries: DEFAULT_QUERY_VARIANTS }
+    }
+}
+
+impl MultiQueryConfig {
+    pub fn new(queries: usize) -> Self {
+        Self { queries }
+    }
+
+    /// System prompt asking a model for reformulations, one per line.
+    pub fn prompt(&self) -> String {
+        format!(
+            "You help a search engine find documents. Rewrite the user's question {} different ways: \
+            use synonyms, spell out what it is vaguely asking, and split compound questions. \
+            Respond with ONLY the rewrites, one per line, no numbering or explanation.",
+            self.queries
+        )
+    }
+
+    /// Reads reformulations from the model's response, dropping list markers,
+    /// blanks, and repeats of the original query.
+    pub fn parse(&self, response: &str, original: &str) -> Vec<String> {
+        let mut variants: Vec<String> = Vec::new();
+        for line in response.lines() {
+            let variant = line
+                .trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | '•'))
+                .trim()
+                .trim_matches('"')
+                .to_string();
+            let repeated = variant.eq_ignore_ascii_case(original.trim()) || variants.contains(&variant);
+            if !variant.is_empty() && !repeated {
+                variants.push(variant);
+            }
+        }
+        variants.truncate(self.queries);
+        variants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_strips_markers_and_repeats() {
+        let config = MultiQueryConfig::new(3);
+        let response = "1. How do I reset my password?\n\n- \"password reset steps\"\nreset password\n2) Forgot login credentials\n3. account recovery";
+        let variants = config.parse(response, "Reset password");
+        assert_eq!(
+            variants,
+            vec!["How do I reset my password?", "password reset steps", "Forgot login credentials"]
+        );
+    }
+}
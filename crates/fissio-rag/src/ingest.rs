@@ -0,0 +1,226 @@
+//! Turning uploaded files into indexed chunks.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{chunk_text, ChunkConfig};
+use crate::connectors::{html_to_text, Document};
+use crate::retriever::Retriever;
+use crate::store::{CollectionConfig, VectorRecord, VectorStoreError};
+
+/// File formats [`extract_text`] understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Text,
+    Markdown,
+    Html,
+    /// Needs the `pdf` feature.
+    Pdf,
+}
+
+impl DocumentFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// Recognizes a MIME type, ignoring parameters such as `charset`.
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let essence = mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "text/plain" => Some(Self::Text),
+            "text/markdown" | "text/x-markdown" => Some(Self::Markdown),
+            "text/html" | "application/xhtml+xml" => Some(Self::Html),
+            "application/pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    /// Recognizes a file extension or format name, such as `md` or `pdf`.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.trim_start_matches('.').to_ascii_lowercase().as_str() {
+            "txt" | "text" => Some(Self::Text),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts plain text from a file's bytes for chunking.
+///
+/// Markdown is kept as written; its headings and lists chunk well as they are.
+pub fn extract_text(bytes: &[u8], format: DocumentFormat) -> Result<String, VectorStoreError> {
+    match format {
+        DocumentFormat::Text | DocumentFormat::Markdown => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        DocumentFormat::Html => Ok(html_to_text(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Pdf => extract_pdf(bytes),
+    }
+}
+
+#[cfg(feature = "pdf")]
+fn extract_pdf(bytes: &[u8]) -> Result<String, VectorStoreError> {
+    pdf_extract::extract_text_from_mem(bytes).map_err(|e| VectorStoreError::Extract(e.to_string()))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn extract_pdf(_bytes: &[u8]) -> Result<String, VectorStoreError> {
+    Err(VectorStoreError::Extract("PDF support requires the `pdf` feature".into()))
+}
+
+/// Chunks, embeds, and indexes documents into one collection.
+///
+/// Chunk IDs are `<document id>#<n>`. Each chunk's metadata holds `doc_id`,
+/// `title`, `url`, `source` (when set), and the document's own metadata. The
+/// collection is created on first use with the embedder's dimension.
+pub struct DocumentIngestor {
+    retriever: Arc<Retriever>,
+    collection: String,
+    chunking: ChunkConfig,
+    source: Option<String>,
+    ready: AtomicBool,
+}
+
+impl DocumentIngestor {
+    pub fn new(retriever: Arc<Retriever>, collection: impl Into<String>) -> Self {
+        Self {
+            retriever,
+            collection: collection.into(),
+            chunking: ChunkConfig::default(),
+            source: None,
+            ready: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_chunking(mut self, chunking: ChunkConfig) -> Self {
+        self.chunking = chunking;
+        self
+    }
+
+    /// Stores `source` in the metadata of every chunk.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    /// Chunks, embeds, and indexes a document, returning the chunk count.
+    ///
+    /// Re-ingesting a document that now has fewer chunks leaves the old tail
+    /// behind; call [`DocumentIngestor::remove`] first when replacing one.
+    pub async fn ingest(&self, doc: &Document) -> Result<usize, VectorStoreError> {
+        let chunks = chunk_text(&doc.text, self.chunking);
+        if chunks.is_empty() {
+            return Ok(0);
+        }
+        let vectors = self.retriever.embedder().embed(&chunks).await?;
+        if !self.ready.load(Ordering::Acquire) {
+            let dimension = vectors.first().map(|v| v.len()).unwrap_or_default();
+            self.retriever
+                .store()
+                .create_collection(&self.collection, CollectionConfig::new(dimension))
+                .await?;
+            self.ready.store(true, Ordering::Release);
+        }
+
+        let records: Vec<VectorRecord> = chunks
+            .into_iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(i, (text, vector))| {
+                let mut record = VectorRecord::new(format!("{}#{}", doc.id, i), vector, text)
+                    .with_metadata("doc_id", doc.id.as_str())
+                    .with_metadata("title", doc.title.as_str());
+                if let Some(source) = &self.source {
+                    record = record.with_metadata("source", source.as_str());
+                }
+                if let Some(url) = &doc.url {
+                    record = record.with_metadata("url", url.as_str());
+                }
+                record.metadata.extend(doc.metadata.clone());
+                record
+            })
+            .collect();
+        let count = records.len();
+        self.retriever.index(&self.collection, records).await?;
+        Ok(count)
+    }
+
+    /// Removes the first `chunks` chunks of a document.
+    pub async fn remove(&self, doc_id: &str, chunks: usize) -> Result<(), VectorStoreError> {
+        if chunks == 0 {
+            return Ok(());
+        }
+        self.retriever.remove(&self.collection, &chunk_ids(doc_id, chunks)).await
+    }
+}
+
+fn chunk_ids(doc_id: &str, count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("{}#{}", doc_id, i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Embedder, InMemoryVectorStore, RetrievalConfig};
+    use async_trait::async_trait;
+
+    struct UnitEmbedder;
+
+    #[async_trait]
+    impl Embedder for UnitEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(DocumentFormat::from_mime("text/html; charset=utf-8"), Some(DocumentFormat::Html));
+        assert_eq!(DocumentFormat::from_mime("application/PDF"), Some(DocumentFormat::Pdf));
+        assert_eq!(DocumentFormat::from_mime("image/png"), None);
+        assert_eq!(DocumentFormat::from_extension(".md"), Some(DocumentFormat::Markdown));
+        assert_eq!(DocumentFormat::from_extension("htm"), Some(DocumentFormat::Html));
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let text = extract_text(b"<h1>Title</h1><p>Body <b>text</b></p>", DocumentFormat::Html).unwrap();
+        assert!(text.contains("Title"));
+        assert!(text.contains("Body"));
+        assert!(!text.contains("<p>"));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_and_remove() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let retriever = Arc::new(Retriever::new(store, Arc::new(UnitEmbedder)));
+        let ingestor = DocumentIngestor::new(Arc::clone(&retriever), "uploads")
+            .with_chunking(ChunkConfig::new(30))
+            .with_source("upload");
+
+        let doc = Document::new("guide", "Guide", "First paragraph here.\n\nSecond paragraph here.");
+        let chunks = ingestor.ingest(&doc).await.unwrap();
+        assert_eq!(chunks, 2);
+
+        let config = RetrievalConfig::new("uploads").with_top_k(10);
+        let results = retriever.retrieve("anything", &config).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].metadata["doc_id"], "guide");
+        assert_eq!(results[0].metadata["source"], "upload");
+
+        ingestor.remove("guide", chunks).await.unwrap();
+        assert!(retriever.retrieve("anything", &config).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! BM25 keyword index backed by tantivy.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde_json::{Map, Value as JsonValue};
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexReader, IndexWriter, TantivyDocument, Term};
+
+use crate::store::{MetadataFilter, SearchResult, VectorRecord, VectorStoreError};
+
+/// Heap given to the index writer.
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// How many extra hits to fetch when results are filtered on metadata afterwards.
+const FILTER_OVERFETCH: usize = 4;
+
+struct Fields {
+    /// `<collection>/<id>`, unique per record, used for replacement and deletion.
+    key: Field,
+    collection: Field,
+    id: Field,
+    text: Field,
+    metadata: Field,
+}
+
+/// Full-text index of record text, ranked by BM25.
+///
+/// Complements vector search for exact terms such as identifiers and error
+/// codes, which embeddings tend to blur. One index holds every collection.
+pub struct KeywordIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+fn index_err(e: tantivy::TantivyError) -> VectorStoreError {
+    VectorStoreError::Index(e.to_string())
+}
+
+impl KeywordIndex {
+    /// Creates an index held in memory.
+    pub fn in_memory() -> Result<Self, VectorStoreError> {
+        let (schema, fields) = schema();
+        Self::from_index(Index::create_in_ram(schema), fields)
+    }
+
+    /// Opens the index in `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VectorStoreError> {
+        std::fs::create_dir_all(path.as_ref()).map_err(|e| VectorStoreError::Index(e.to_string()))?;
+        let dir = MmapDirectory::open(path.as_ref()).map_err(|e| VectorStoreError::Index(e.to_string()))?;
+        let (schema, fields) = schema();
+        Self::from_index(Index::open_or_create(dir, schema).map_err(index_err)?, fields)
+    }
+
+    fn from_index(index: Index, fields: Fields) -> Result<Self, VectorStoreError> {
+        let reader = index.reader().map_err(index_err)?;
+        let writer = index.writer(WRITER_HEAP_BYTES).map_err(index_err)?;
+        Ok(Self { index, reader, writer: Mutex::new(writer), fields })
+    }
+
+    /// Indexes records' text, replacing any with the same ID in the collection.
+    pub fn add(&self, collection: &str, records: &[VectorRecord]) -> Result<(), VectorStoreError> {
+        self.write(|writer, f| {
+            for r in records {
+                writer.delete_term(Term::from_field_text(f.key, &record_key(collection, &r.id)));
+                writer
+                    .add_document(doc!(
+                        f.key => record_key(collection, &r.id),
+                        f.collection => collection,
+                        f.id => r.id.as_str(),
+                        f.text => r.text.as_str(),
+                        f.metadata => JsonValue::Object(r.metadata.clone()).to_string(),
+                    ))
+                    .map_err(index_err)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes records by ID.
+    pub fn delete(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError> {
+        self.write(|writer, f| {
+            for id in ids {
+                writer.delete_term(Term::from_field_text(f.key, &record_key(collection, id)));
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes every record in a collection.
+    pub fn delete_collection(&self, collection: &str) -> Result<(), VectorStoreError> {
+        self.write(|writer, f| {
+            writer.delete_term(Term::from_field_text(f.collection, collection));
+            Ok(())
+        })
+    }
+
+    /// Returns up to `top_k` records in `collection` that best match `query`.
+    pub fn search(
+        &self,
+        collection: &str,
+        query: &str,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let f = &self.fields;
+        let parser = QueryParser::for_index(&self.index, vec![f.text]);
+        let (text_query, _) = parser.parse_query_lenient(query);
+        let in_collection = TermQuery::new(
+            Term::from_field_text(f.collection, collection),
+            IndexRecordOption::Basic,
+        );
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(in_collection) as Box<dyn Query>),
+            (Occur::Must, text_query),
+        ]);
+
+        let filter = filter.filter(|flt| !flt.equals.is_empty());
+        let limit = if filter.is_some() { top_k * FILTER_OVERFETCH } else { top_k };
+        let searcher = self.reader.searcher();
+        let hits = searcher.search(&query, &TopDocs::with_limit(limit.max(1))).map_err(index_err)?;
+
+        let mut results = Vec::new();
+        for (score, address) in hits {
+            let doc: TantivyDocument = searcher.doc(address).map_err(index_err)?;
+            let text_of = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let metadata: Map<String, JsonValue> = serde_json::from_str(&text_of(f.metadata)).unwrap_or_default();
+            if filter.is_some_and(|flt| !flt.matches(&metadata)) {
+                continue;
+            }
+            results.push(SearchResult { id: text_of(f.id), score, text: text_of(f.text), metadata });
+            if results.len() == top_k {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Runs `edit` against the writer, then commits and refreshes the reader.
+    fn write(
+        &self,
+        edit: impl FnOnce(&mut IndexWriter, &Fields) -> Result<(), VectorStoreError>,
+    ) -> Result<(), VectorStoreError> {
+        let mut writer = self.writer.lock().map_err(|_| VectorStoreError::Index("lock poisoned".into()))?;
+        edit(&mut writer, &self.fields)?;
+        writer.commit().map_err(index_err)?;
+        self.reader.reload().map_err(index_err)
+    }
+}
+
+fn record_key(collection: &str, id: &str) -> String {
+    format!("{}/{}", collection, id)
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        key: builder.add_text_field("key", STRING),
+        collection: builder.add_text_field("collection", STRING),
+        id: builder.add_text_field("id", STRING | STORED),
+        text: builder.add_text_field("text", TEXT | STORED),
+        metadata: builder.add_text_field("metadata", STORED),
+    };
+    (builder.build(), fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_search_matches_exact_terms() {
+        let index = KeywordIndex::in_memory().unwrap();
+        index
+            .add(
+                "support",
+                &[
+                    VectorRecord::new("a", vec![], "Error E1042 means the token expired").with_metadata("product", "auth"),
+                    VectorRecord::new("b", vec![], "Tokens can be refreshed from the dashboard").with_metadata("product", "auth"),
+                    VectorRecord::new("c", vec![], "Error E2001 is a billing failure").with_metadata("product", "billing"),
+                ],
+            )
+            .unwrap();
+        index.add("other", &[VectorRecord::new("d", vec![], "E1042 in another collection")]).unwrap();
+
+        let hits = index.search("support", "E1042", 5, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "a");
+
+        let filter = MetadataFilter::new().eq("product", "billing");
+        let hits = index.search("support", "error", 5, Some(&filter)).unwrap();
+        assert_eq!(hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), ["c"]);
+
+        index.delete("support", &["a".to_string()]).unwrap();
+        assert!(index.search("support", "E1042", 5, None).unwrap().is_empty());
+    }
+}
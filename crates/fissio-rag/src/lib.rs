@@ -0,0 +1,172 @@
+//! Retrieval and vector storage for fissio pipelines.
+//!
+//! - [`VectorStore`] — Collections of embedded text chunks searchable by similarity
+//! - [`InMemoryVectorStore`] — In-process store for development and small corpora
+//! - [`SqliteVectorStore`] — Single-file store that survives restarts, without a database server
+//! - `QdrantVectorStore` — Qdrant over its REST API (`qdrant` feature)
+//! - `PgVectorStore` — PostgreSQL with the pgvector extension (`pgvector` feature)
+//! - [`Retriever`] — Vector, keyword (BM25), or hybrid search for a query
+//! - `KeywordIndex` — tantivy full-text index (`keyword` feature)
+//! - [`Reranker`] — Cross-encoder reordering of candidates; `ApiReranker` (`rerank` feature)
+//! - [`MultiQueryConfig`] — Searching for model-written rewordings of a vague query
+//! - [`CitationScanner`] — Finding `[n]` source markers in a streamed answer
+//! - [`VectorSearchTool`] — The `vector_search` tool, for workers that search on their own
+//! - [`DocumentIngestor`] — Chunk, embed, and index text, markdown, HTML, and PDF files
+//! - [`Connector`] / [`ConnectorSync`] — Keep a collection synced with a document source
+//! - [`Memory`] — Long-term facts per user or session; [`SqliteMemory`], [`InMemoryMemory`]
+//! - [`MemoryReadTool`] / [`MemoryWriteTool`] — The `memory_read` and `memory_write` tools
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fissio_rag::{CollectionConfig, InMemoryVectorStore, MetadataFilter, VectorRecord, VectorStore};
+//!
+//! let store = InMemoryVectorStore::new();
+//! store.create_collection("docs", CollectionConfig::new(1536)).await?;
+//! store.upsert("docs", vec![VectorRecord::new("intro", embedding, "Fissio is ...")]).await?;
+//!
+//! let filter = MetadataFilter::new().eq("lang", "en");
+//! let hits = store.search("docs", &query_embedding, 5, Some(&filter)).await?;
+//! ```
+//!
+//! # Embeddings
+//!
+//! [`fissio_llm::EmbeddingClient`] implements [`Embedder`] for OpenAI-compatible
+//! and Ollama embedding endpoints:
+//!
+//! ```rust,ignore
+//! let embedder = Arc::new(EmbeddingClient::ollama("http://localhost:11434", "nomic-embed-text"));
+//! let store = Arc::new(SqliteVectorStore::open("data/vectors.db")?);
+//! let retriever = Arc::new(Retriever::new(store, embedder));
+//! ```
+//!
+//! # Search Tool
+//!
+//! A Retriever node searches once, with its input. [`VectorSearchTool`] lets a
+//! worker search whenever it decides to, with queries it writes:
+//!
+//! ```rust,ignore
+//! registry.register(VectorSearchTool::new(retriever, "docs"));
+//! ```
+//!
+//! # Hybrid Search
+//!
+//! Embeddings miss exact identifiers and error codes. With a [`KeywordIndex`],
+//! [`SearchMode::Hybrid`] runs both searches and merges them with
+//! [`reciprocal_rank_fusion`]:
+//!
+//! ```rust,ignore
+//! let retriever = Retriever::new(store, embedder).with_keyword_index(Arc::new(KeywordIndex::open("data/keywords")?));
+//! retriever.index("docs", records).await?;
+//!
+//! let config = RetrievalConfig::new("docs").with_search(SearchMode::Hybrid);
+//! let chunks = retriever.retrieve("what does E1042 mean?", &config).await?;
+//! ```
+//!
+//! # Reranking
+//!
+//! With a [`Reranker`] and [`RetrievalConfig::with_rerank`], the retriever
+//! fetches `top_k_in` candidates and keeps the `top_k_out` the reranker scores
+//! highest:
+//!
+//! ```rust,ignore
+//! let retriever = Retriever::new(store, embedder)
+//!     .with_reranker(Arc::new(ApiReranker::new("https://api.cohere.com/v2/rerank", "rerank-v3.5").with_api_key(key)));
+//! let config = RetrievalConfig::new("docs").with_rerank(RerankConfig::new(50).with_top_k_out(5));
+//! ```
+//!
+//! # Multi-Query Retrieval
+//!
+//! For vague questions, a model rewrites the query a few ways and
+//! [`Retriever::retrieve_expanded`] searches for all of them, fusing the results.
+//! A Retriever node does this when its config sets `multi_query`:
+//!
+//! ```rust,ignore
+//! let config = RetrievalConfig::new("docs").with_multi_query(MultiQueryConfig::new(4));
+//! let variants = config.multi_query.unwrap().parse(&llm_response, query);
+//! let chunks = retriever.retrieve_expanded(query, &variants, &config).await?;
+//! ```
+//!
+//! # Ingesting Files
+//!
+//! [`extract_text`] pulls plain text out of an uploaded file (PDF needs the
+//! `pdf` feature) and [`DocumentIngestor`] chunks, embeds, and indexes it,
+//! creating the collection on first use:
+//!
+//! ```rust,ignore
+//! let ingestor = DocumentIngestor::new(retriever, "docs").with_chunking(ChunkConfig::new(1000).with_overlap(150));
+//! let text = extract_text(&bytes, DocumentFormat::Pdf)?;
+//! let chunks = ingestor.ingest(&Document::new("handbook", "Employee Handbook", text)).await?;
+//! ingestor.remove("handbook", chunks).await?;
+//! ```
+//!
+//! # Connectors
+//!
+//! [`ConnectorSync`] chunks and indexes a source's documents and keeps them
+//! current, re-indexing changed documents and removing deleted ones:
+//!
+//! ```rust,ignore
+//! let docs = SitemapConnector::new("https://docs.example.com/sitemap.xml").with_include("https://docs.example.com/guides/");
+//! let sync = Arc::new(ConnectorSync::new(Arc::new(docs), retriever, "docs"));
+//! sync.spawn(Duration::from_secs(3600));
+//! ```
+//!
+//! # Memory
+//!
+//! A [`Memory`] keeps facts across runs, scoped to a user or session. Give an
+//! engine one with `with_memory` and its workers get the memory tools, and
+//! relevant memories are added to node prompts:
+//!
+//! ```rust,ignore
+//! let memory: Arc<dyn Memory> = Arc::new(SqliteMemory::open("data/memory.db")?);
+//! memory.put("user-42", "preferred_language", "Answers in Portuguese").await?;
+//! let engine = PipelineEngine::new(config, models, default_model, overrides).with_memory(memory, "user-42");
+//! ```
+
+mod agent_memory;
+mod chunk;
+mod cite;
+mod connectors;
+mod embed;
+mod expand;
+mod ingest;
+#[cfg(feature = "keyword")]
+mod keyword;
+mod memory;
+#[cfg(feature = "pgvector")]
+mod pgvector;
+#[cfg(feature = "qdrant")]
+mod qdrant;
+mod rerank;
+mod retriever;
+mod sqlite;
+mod store;
+mod tool;
+
+pub use agent_memory::{
+    InMemoryMemory, Memory, MemoryEntry, MemoryError, MemoryReadTool, MemoryWriteTool, SqliteMemory,
+};
+pub use chunk::{chunk_text, ChunkConfig};
+pub use cite::{find_citations, CitationScanner};
+#[cfg(feature = "connectors")]
+pub use connectors::{ConfluenceConnector, NotionConnector, RssConnector, SitemapConnector};
+pub use connectors::{Connector, ConnectorSync, Document, SyncReport};
+pub use embed::Embedder;
+pub use expand::{MultiQueryConfig, DEFAULT_QUERY_VARIANTS};
+pub use ingest::{extract_text, DocumentFormat, DocumentIngestor};
+#[cfg(feature = "keyword")]
+pub use keyword::KeywordIndex;
+pub use memory::InMemoryVectorStore;
+#[cfg(feature = "pgvector")]
+pub use pgvector::PgVectorStore;
+#[cfg(feature = "qdrant")]
+pub use qdrant::QdrantVectorStore;
+#[cfg(feature = "rerank")]
+pub use rerank::ApiReranker;
+pub use rerank::{RerankConfig, Reranker, DEFAULT_RERANK_CANDIDATES};
+pub use retriever::{format_context, reciprocal_rank_fusion, RetrievalConfig, Retriever, SearchMode, DEFAULT_RRF_K};
+pub use sqlite::SqliteVectorStore;
+pub use store::{
+    CollectionConfig, Distance, MetadataFilter, SearchResult, VectorRecord, VectorStore, VectorStoreError,
+};
+pub use tool::VectorSearchTool;
@@ -0,0 +1,174 @@
+//! In-process vector store.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::store::{
+    CollectionConfig, Distance, MetadataFilter, SearchResult, VectorRecord, VectorStore, VectorStoreError,
+};
+
+struct Collection {
+    config: CollectionConfig,
+    records: HashMap<String, VectorRecord>,
+}
+
+/// Vector store held in memory with brute-force search.
+///
+/// Suitable for tests and corpora of a few thousand chunks; contents are lost on restart.
+#[derive(Default)]
+pub struct InMemoryVectorStore {
+    collections: RwLock<HashMap<String, Collection>>,
+}
+
+impl InMemoryVectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn lock_err() -> VectorStoreError {
+    VectorStoreError::Backend("lock poisoned".into())
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn create_collection(&self, name: &str, config: CollectionConfig) -> Result<(), VectorStoreError> {
+        let mut collections = self.collections.write().map_err(|_| lock_err())?;
+        collections
+            .entry(name.to_string())
+            .or_insert_with(|| Collection { config, records: HashMap::new() });
+        Ok(())
+    }
+
+    async fn delete_collection(&self, name: &str) -> Result<(), VectorStoreError> {
+        self.collections.write().map_err(|_| lock_err())?.remove(name);
+        Ok(())
+    }
+
+    async fn list_collections(&self) -> Result<Vec<String>, VectorStoreError> {
+        let collections = self.collections.read().map_err(|_| lock_err())?;
+        let mut names: Vec<String> = collections.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn upsert(&self, collection: &str, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        let mut collections = self.collections.write().map_err(|_| lock_err())?;
+        let coll = collections
+            .get_mut(collection)
+            .ok_or_else(|| VectorStoreError::CollectionNotFound(collection.to_string()))?;
+
+        for record in records {
+            check_dimension(coll.config.dimension, record.vector.len())?;
+            coll.records.insert(record.id.clone(), record);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError> {
+        let mut collections = self.collections.write().map_err(|_| lock_err())?;
+        let coll = collections
+            .get_mut(collection)
+            .ok_or_else(|| VectorStoreError::CollectionNotFound(collection.to_string()))?;
+
+        for id in ids {
+            coll.records.remove(id);
+        }
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        collection: &str,
+        vector: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let collections = self.collections.read().map_err(|_| lock_err())?;
+        let coll = collections
+            .get(collection)
+            .ok_or_else(|| VectorStoreError::CollectionNotFound(collection.to_string()))?;
+        check_dimension(coll.config.dimension, vector.len())?;
+
+        let mut results: Vec<SearchResult> = coll
+            .records
+            .values()
+            .filter(|r| filter.is_none_or(|f| f.matches(&r.metadata)))
+            .map(|r| SearchResult {
+                id: r.id.clone(),
+                score: similarity(coll.config.distance, vector, &r.vector),
+                text: r.text.clone(),
+                metadata: r.metadata.clone(),
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(top_k);
+        Ok(results)
+    }
+}
+
+pub(crate) fn check_dimension(expected: usize, actual: usize) -> Result<(), VectorStoreError> {
+    if expected != actual {
+        return Err(VectorStoreError::DimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Scores two vectors so that higher always means more similar.
+pub(crate) fn similarity(distance: Distance, a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    match distance {
+        Distance::Dot => dot,
+        Distance::Cosine => {
+            let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            let denom = norm(a) * norm(b);
+            if denom == 0.0 { 0.0 } else { dot / denom }
+        }
+        Distance::Euclidean => -a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_search_ranks_and_filters() {
+        let store = InMemoryVectorStore::new();
+        store.create_collection("docs", CollectionConfig::new(2)).await.unwrap();
+        store
+            .upsert(
+                "docs",
+                vec![
+                    VectorRecord::new("a", vec![1.0, 0.0], "alpha").with_metadata("lang", "en"),
+                    VectorRecord::new("b", vec![0.7, 0.7], "beta").with_metadata("lang", "de"),
+                    VectorRecord::new("c", vec![0.0, 1.0], "gamma").with_metadata("lang", "en"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let hits = store.search("docs", &[1.0, 0.1], 2, None).await.unwrap();
+        assert_eq!(hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+
+        let filter = MetadataFilter::new().eq("lang", "en");
+        let hits = store.search("docs", &[0.7, 0.7], 5, Some(&filter)).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.metadata["lang"] == "en"));
+
+        store.delete("docs", &["a".to_string()]).await.unwrap();
+        let hits = store.search("docs", &[1.0, 0.0], 5, None).await.unwrap();
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatch() {
+        let store = InMemoryVectorStore::new();
+        store.create_collection("docs", CollectionConfig::new(3)).await.unwrap();
+        let err = store.upsert("docs", vec![VectorRecord::new("a", vec![1.0], "x")]).await.unwrap_err();
+        assert!(matches!(err, VectorStoreError::DimensionMismatch { expected: 3, actual: 1 }));
+    }
+}
@@ -0,0 +1,205 @@
+//! Qdrant vector store over its REST API.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::store::{
+    CollectionConfig, Distance, MetadataFilter, SearchResult, VectorRecord, VectorStore, VectorStoreError,
+};
+
+/// Vector store backed by a Qdrant server.
+///
+/// Record IDs are mapped to deterministic UUIDs, since Qdrant only accepts
+/// integers and UUIDs as point IDs; the original ID is kept in the payload.
+pub struct QdrantVectorStore {
+    client: Client,
+    url: String,
+    api_key: Option<String>,
+    distances: RwLock<HashMap<String, Distance>>,
+}
+
+impl QdrantVectorStore {
+    /// Creates a store for the Qdrant server at `url` (e.g. `http://localhost:6333`).
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            distances: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Sends `api-key` with every request (Qdrant Cloud).
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self.client.request(method, format!("{}{}", self.url, path));
+        match &self.api_key {
+            Some(key) => req.header("api-key", key),
+            None => req,
+        }
+    }
+
+    /// Sends a request and returns the `result` field of the response.
+    async fn send(&self, req: RequestBuilder, collection: &str) -> Result<Value, VectorStoreError> {
+        let res = req.send().await.map_err(backend_err)?;
+        let status = res.status();
+        if status == StatusCode::NOT_FOUND {
+            return Err(VectorStoreError::CollectionNotFound(collection.to_string()));
+        }
+        let body: Value = res.json().await.map_err(backend_err)?;
+        if !status.is_success() {
+            let message = body.pointer("/status/error").and_then(Value::as_str).unwrap_or("request failed");
+            return Err(VectorStoreError::Backend(format!("Qdrant {}: {}", status, message)));
+        }
+        Ok(body.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Returns the collection's distance metric, fetching it once per collection.
+    async fn distance(&self, collection: &str) -> Result<Distance, VectorStoreError> {
+        if let Some(distance) = self.distances.read().ok().and_then(|d| d.get(collection).copied()) {
+            return Ok(distance);
+        }
+        let info = self.send(self.request(Method::GET, &format!("/collections/{}", collection)), collection).await?;
+        let distance = match info.pointer("/config/params/vectors/distance").and_then(Value::as_str) {
+            Some("Dot") => Distance::Dot,
+            Some("Euclid") => Distance::Euclidean,
+            _ => Distance::Cosine,
+        };
+        if let Ok(mut distances) = self.distances.write() {
+            distances.insert(collection.to_string(), distance);
+        }
+        Ok(distance)
+    }
+}
+
+fn backend_err(e: reqwest::Error) -> VectorStoreError {
+    VectorStoreError::Backend(e.to_string())
+}
+
+fn point_id(id: &str) -> String {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, id.as_bytes()).to_string()
+}
+
+fn qdrant_filter(filter: &MetadataFilter) -> Value {
+    let must: Vec<Value> = filter
+        .equals
+        .iter()
+        .map(|(k, v)| json!({ "key": format!("metadata.{}", k), "match": { "value": v } }))
+        .collect();
+    json!({ "must": must })
+}
+
+#[async_trait]
+impl VectorStore for QdrantVectorStore {
+    async fn create_collection(&self, name: &str, config: CollectionConfig) -> Result<(), VectorStoreError> {
+        let exists = self.send(self.request(Method::GET, &format!("/collections/{}/exists", name)), name).await?;
+        if exists.get("exists").and_then(Value::as_bool) == Some(true) {
+            return Ok(());
+        }
+
+        let distance = match config.distance {
+            Distance::Cosine => "Cosine",
+            Distance::Dot => "Dot",
+            Distance::Euclidean => "Euclid",
+        };
+        let body = json!({ "vectors": { "size": config.dimension, "distance": distance } });
+        self.send(self.request(Method::PUT, &format!("/collections/{}", name)).json(&body), name).await?;
+
+        if let Ok(mut distances) = self.distances.write() {
+            distances.insert(name.to_string(), config.distance);
+        }
+        Ok(())
+    }
+
+    async fn delete_collection(&self, name: &str) -> Result<(), VectorStoreError> {
+        match self.send(self.request(Method::DELETE, &format!("/collections/{}", name)), name).await {
+            Ok(_) | Err(VectorStoreError::CollectionNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        if let Ok(mut distances) = self.distances.write() {
+            distances.remove(name);
+        }
+        Ok(())
+    }
+
+    async fn list_collections(&self) -> Result<Vec<String>, VectorStoreError> {
+        let result = self.send(self.request(Method::GET, "/collections"), "").await?;
+        let names = result
+            .get("collections")
+            .and_then(Value::as_array)
+            .map(|cs| cs.iter().filter_map(|c| c.get("name")?.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        Ok(names)
+    }
+
+    async fn upsert(&self, collection: &str, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        let points: Vec<Value> = records
+            .into_iter()
+            .map(|r| {
+                json!({
+                    "id": point_id(&r.id),
+                    "vector": r.vector,
+                    "payload": { "id": r.id, "text": r.text, "metadata": r.metadata },
+                })
+            })
+            .collect();
+
+        let path = format!("/collections/{}/points?wait=true", collection);
+        self.send(self.request(Method::PUT, &path).json(&json!({ "points": points })), collection).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError> {
+        let points: Vec<String> = ids.iter().map(|id| point_id(id)).collect();
+        let path = format!("/collections/{}/points/delete?wait=true", collection);
+        self.send(self.request(Method::POST, &path).json(&json!({ "points": points })), collection).await?;
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        collection: &str,
+        vector: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let distance = self.distance(collection).await?;
+
+        let mut body = json!({ "vector": vector, "limit": top_k, "with_payload": true });
+        if let Some(filter) = filter.filter(|f| !f.equals.is_empty()) {
+            body["filter"] = qdrant_filter(filter);
+        }
+
+        let path = format!("/collections/{}/points/search", collection);
+        let result = self.send(self.request(Method::POST, &path).json(&body), collection).await?;
+
+        let hits = result.as_array().cloned().unwrap_or_default();
+        Ok(hits
+            .into_iter()
+            .map(|hit| {
+                let payload = hit.get("payload").cloned().unwrap_or(Value::Null);
+                let score = hit.get("score").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+                SearchResult {
+                    id: payload.get("id").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    // Qdrant reports Euclidean results as distances, lowest first
+                    score: if distance == Distance::Euclidean { -score } else { score },
+                    text: payload.get("text").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    metadata: payload
+                        .get("metadata")
+                        .and_then(Value::as_object)
+                        .cloned()
+                        .unwrap_or_else(Map::new),
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,134 @@
+//! Reordering retrieved candidates with a cross-encoder.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::store::{SearchResult, VectorStoreError};
+
+/// Default number of candidates passed to the reranker.
+pub const DEFAULT_RERANK_CANDIDATES: usize = 20;
+
+/// Scores candidates against the query and returns the best `top_k`.
+///
+/// Cross-encoders read the query and chunk together, so they rank more
+/// accurately than embedding similarity but are too slow to run over a whole
+/// collection; the retriever passes them a shortlist.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Returns up to `top_k` candidates, best first, with reranker scores.
+    async fn rerank(
+        &self,
+        query: &str,
+        candidates: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError>;
+}
+
+/// Rerank step of a [`RetrievalConfig`](crate::RetrievalConfig).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RerankConfig {
+    /// Candidates fetched from search and passed to the reranker.
+    #[serde(default = "default_top_k_in")]
+    pub top_k_in: usize,
+    /// Chunks kept after reranking; defaults to the retrieval `top_k`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k_out: Option<usize>,
+}
+
+fn default_top_k_in() -> usize {
+    DEFAULT_RERANK_CANDIDATES
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self { top_k_in: DEFAULT_RERANK_CANDIDATES, top_k_out: None }
+    }
+}
+
+impl RerankConfig {
+    pub fn new(top_k_in: usize) -> Self {
+        Self { top_k_in, top_k_out: None }
+    }
+
+    pub fn with_top_k_out(mut self, top_k_out: usize) -> Self {
+        self.top_k_out = Some(top_k_out);
+        self
+    }
+}
+
+/// Reranker for `POST /rerank` APIs in the Cohere format.
+///
+/// Cohere, Jina, and Voyage use this format, as do local rerank servers such
+/// as llama.cpp (`--reranking`), vLLM, and Infinity.
+#[cfg(feature = "rerank")]
+pub struct ApiReranker {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[cfg(feature = "rerank")]
+impl ApiReranker {
+    /// Creates a reranker for the endpoint at `url` (e.g. `https://api.cohere.com/v2/rerank`).
+    pub fn new(url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self { client: reqwest::Client::new(), url: url.into(), model: model.into(), api_key: None }
+    }
+
+    /// Sends `Authorization: Bearer <key>` with every request.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+}
+
+#[cfg(feature = "rerank")]
+#[async_trait]
+impl Reranker for ApiReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        candidates: Vec<SearchResult>,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        use serde_json::{json, Value};
+
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+        let documents: Vec<&str> = candidates.iter().map(|c| c.text.as_str()).collect();
+        let body = json!({ "model": self.model, "query": query, "documents": documents, "top_n": top_k });
+
+        let mut req = self.client.post(&self.url).json(&body);
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+        let res = req.send().await.map_err(rerank_err)?;
+        let status = res.status();
+        let body: Value = res.json().await.map_err(rerank_err)?;
+        if !status.is_success() {
+            return Err(VectorStoreError::Rerank(format!("{}: {}", status, body)));
+        }
+
+        let ranked = body
+            .get("results")
+            .and_then(Value::as_array)
+            .ok_or_else(|| VectorStoreError::Rerank("response has no results".into()))?;
+        let mut results: Vec<SearchResult> = ranked
+            .iter()
+            .filter_map(|r| {
+                let index = r.get("index")?.as_u64()? as usize;
+                let score = r.get("relevance_score")?.as_f64()? as f32;
+                candidates.get(index).map(|c| SearchResult { score, ..c.clone() })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(top_k);
+        Ok(results)
+    }
+}
+
+#[cfg(feature = "rerank")]
+fn rerank_err(e: reqwest::Error) -> VectorStoreError {
+    VectorStoreError::Rerank(e.to_string())
+}
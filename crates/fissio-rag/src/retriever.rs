@@ -0,0 +1,399 @@
+//! Query-time retrieval over a vector store and optional keyword index.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::embed::Embedder;
+use crate::expand::MultiQueryConfig;
+#[cfg(feature = "keyword")]
+use crate::keyword::KeywordIndex;
+use crate::rerank::{RerankConfig, Reranker};
+use crate::store::{MetadataFilter, SearchResult, VectorRecord, VectorStore, VectorStoreError};
+
+/// Standard damping constant for reciprocal rank fusion.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// Candidates fetched from each source per requested result before fusing.
+const HYBRID_CANDIDATES: usize = 4;
+
+/// How a retriever finds candidates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Embedding similarity only.
+    #[default]
+    Vector,
+    /// BM25 keyword matching only (`keyword` feature).
+    Keyword,
+    /// Both, merged with reciprocal rank fusion.
+    Hybrid,
+}
+
+/// Per-query retrieval settings; a Retriever node's `config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievalConfig {
+    /// Collection to search.
+    pub collection: String,
+    /// Number of chunks to return.
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    #[serde(default)]
+    pub search: SearchMode,
+    /// Reciprocal rank fusion constant for hybrid search.
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+    /// Only return chunks whose metadata has these values.
+    #[serde(default)]
+    pub filter: Option<MetadataFilter>,
+    /// Reranks a larger candidate set before returning results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<RerankConfig>,
+    /// Also searches for model-written reformulations of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_query: Option<MultiQueryConfig>,
+}
+
+fn default_top_k() -> usize {
+    5
+}
+
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
+
+impl RetrievalConfig {
+    pub fn new(collection: impl Into<String>) -> Self {
+        Self {
+            collection: collection.into(),
+            top_k: default_top_k(),
+            search: SearchMode::default(),
+            rrf_k: DEFAULT_RRF_K,
+            filter: None,
+            rerank: None,
+            multi_query: None,
+        }
+    }
+
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    pub fn with_search(mut self, search: SearchMode) -> Self {
+        self.search = search;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: MetadataFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_rerank(mut self, rerank: RerankConfig) -> Self {
+        self.rerank = Some(rerank);
+        self
+    }
+
+    pub fn with_multi_query(mut self, multi_query: MultiQueryConfig) -> Self {
+        self.multi_query = Some(multi_query);
+        self
+    }
+}
+
+/// Finds the chunks most relevant to a query.
+pub struct Retriever {
+    store: Arc<dyn VectorStore>,
+    embedder: Arc<dyn Embedder>,
+    reranker: Option<Arc<dyn Reranker>>,
+    #[cfg(feature = "keyword")]
+    keywords: Option<Arc<KeywordIndex>>,
+}
+
+impl Retriever {
+    pub fn new(store: Arc<dyn VectorStore>, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            store,
+            embedder,
+            reranker: None,
+            #[cfg(feature = "keyword")]
+            keywords: None,
+        }
+    }
+
+    /// Enables keyword and hybrid search. Records added through [`Retriever::index`]
+    /// are written to both the vector store and this index.
+    #[cfg(feature = "keyword")]
+    pub fn with_keyword_index(mut self, index: Arc<KeywordIndex>) -> Self {
+        self.keywords = Some(index);
+        self
+    }
+
+    /// Enables the rerank step for configs with `rerank` set.
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.reranker = Some(reranker);
+        self
+    }
+
+    /// The underlying vector store.
+    pub fn store(&self) -> &Arc<dyn VectorStore> {
+        &self.store
+    }
+
+    /// The embedder used for queries.
+    pub fn embedder(&self) -> &Arc<dyn Embedder> {
+        &self.embedder
+    }
+
+    /// Adds embedded records to the vector store and keyword index.
+    pub async fn index(&self, collection: &str, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        #[cfg(feature = "keyword")]
+        if let Some(keywords) = &self.keywords {
+            keywords.add(collection, &records)?;
+        }
+        self.store.upsert(collection, records).await
+    }
+
+    /// Removes records from the vector store and keyword index.
+    pub async fn remove(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError> {
+        #[cfg(feature = "keyword")]
+        if let Some(keywords) = &self.keywords {
+            keywords.delete(collection, ids)?;
+        }
+        self.store.delete(collection, ids).await
+    }
+
+    /// Returns up to `config.top_k` chunks for `query`, best first.
+    ///
+    /// With `config.rerank`, fetches `top_k_in` candidates and returns the
+    /// reranker's best `top_k_out` instead.
+    pub async fn retrieve(&self, query: &str, config: &RetrievalConfig) -> Result<Vec<SearchResult>, VectorStoreError> {
+        self.retrieve_expanded(query, &[], config).await
+    }
+
+    /// Like [`Retriever::retrieve`], but also searches for each of `variants`
+    /// (reformulations of `query`) and merges the result lists with reciprocal
+    /// rank fusion. Reranking scores the merged candidates against `query`.
+    pub async fn retrieve_expanded(
+        &self,
+        query: &str,
+        variants: &[String],
+        config: &RetrievalConfig,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let Some(rerank) = config.rerank else {
+            return self.search_all(query, variants, config, config.top_k).await;
+        };
+        let top_k_out = rerank.top_k_out.unwrap_or(config.top_k);
+        let mut candidates = self.search_all(query, variants, config, rerank.top_k_in.max(top_k_out)).await?;
+        match &self.reranker {
+            Some(reranker) => reranker.rerank(query, candidates, top_k_out).await,
+            None => {
+                warn!("Rerank requested but no reranker configured, using search order");
+                candidates.truncate(top_k_out);
+                Ok(candidates)
+            }
+        }
+    }
+
+    async fn search_all(
+        &self,
+        query: &str,
+        variants: &[String],
+        config: &RetrievalConfig,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        if variants.is_empty() {
+            return self.search(query, config, top_k).await;
+        }
+        let queries = std::iter::once(query).chain(variants.iter().map(String::as_str));
+        let lists = try_join_all(queries.map(|q| self.search(q, config, top_k))).await?;
+        let mut fused = reciprocal_rank_fusion(&lists, config.rrf_k);
+        fused.truncate(top_k);
+        Ok(fused)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        config: &RetrievalConfig,
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let filter = config.filter.as_ref();
+        match config.search {
+            SearchMode::Vector => self.vector_search(query, &config.collection, top_k, filter).await,
+            SearchMode::Keyword => self.keyword_search(query, &config.collection, top_k, filter),
+            SearchMode::Hybrid => {
+                let candidates = top_k * HYBRID_CANDIDATES;
+                let vector = self.vector_search(query, &config.collection, candidates, filter).await?;
+                let keyword = match self.keyword_search(query, &config.collection, candidates, filter) {
+                    Ok(results) => results,
+                    Err(e) => {
+                        warn!("Keyword search unavailable, using vector results only: {}", e);
+                        Vec::new()
+                    }
+                };
+                let mut fused = reciprocal_rank_fusion(&[vector, keyword], config.rrf_k);
+                fused.truncate(top_k);
+                Ok(fused)
+            }
+        }
+    }
+
+    async fn vector_search(
+        &self,
+        query: &str,
+        collection: &str,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let vector = self.embedder.embed_one(query).await?;
+        self.store.search(collection, &vector, top_k, filter).await
+    }
+
+    #[cfg(feature = "keyword")]
+    fn keyword_search(
+        &self,
+        query: &str,
+        collection: &str,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let keywords = self
+            .keywords
+            .as_ref()
+            .ok_or_else(|| VectorStoreError::Index("no keyword index configured".into()))?;
+        keywords.search(collection, query, top_k, filter)
+    }
+
+    #[cfg(not(feature = "keyword"))]
+    fn keyword_search(
+        &self,
+        _query: &str,
+        _collection: &str,
+        _top_k: usize,
+        _filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        Err(VectorStoreError::Index("keyword search requires the `keyword` feature".into()))
+    }
+}
+
+/// Merges ranked lists by reciprocal rank fusion: each result scores
+/// `sum(1 / (k + rank))` over the lists it appears in.
+///
+/// Results are matched by ID; the returned `score` is the fused score.
+pub fn reciprocal_rank_fusion(lists: &[Vec<SearchResult>], k: f32) -> Vec<SearchResult> {
+    let mut fused: HashMap<&str, (f32, &SearchResult)> = HashMap::new();
+    for list in lists {
+        for (rank, result) in list.iter().enumerate() {
+            let entry = fused.entry(result.id.as_str()).or_insert((0.0, result));
+            entry.0 += 1.0 / (k + rank as f32 + 1.0);
+        }
+    }
+
+    let mut results: Vec<SearchResult> = fused
+        .into_values()
+        .map(|(score, result)| SearchResult { score, ..result.clone() })
+        .collect();
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+    results
+}
+
+/// Formats retrieved chunks as numbered context for a prompt: `[1] ...`.
+pub fn format_context(results: &[SearchResult]) -> String {
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("[{}] {}", i + 1, r.text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Map;
+
+    fn result(id: &str) -> SearchResult {
+        SearchResult { id: id.into(), score: 0.0, text: id.into(), metadata: Map::new() }
+    }
+
+    #[test]
+    fn test_rrf_prefers_results_in_both_lists() {
+        let vector = vec![result("a"), result("b"), result("c")];
+        let keyword = vec![result("c"), result("d")];
+        let fused = reciprocal_rank_fusion(&[vector, keyword], DEFAULT_RRF_K);
+        assert_eq!(fused[0].id, "c");
+        assert_eq!(fused.len(), 4);
+    }
+
+    struct FixedEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for FixedEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+    }
+
+    /// Ranks shortest text first.
+    struct ShortestFirst;
+
+    #[async_trait::async_trait]
+    impl Reranker for ShortestFirst {
+        async fn rerank(
+            &self,
+            _query: &str,
+            mut candidates: Vec<SearchResult>,
+            top_k: usize,
+        ) -> Result<Vec<SearchResult>, VectorStoreError> {
+            candidates.sort_by_key(|c| c.text.len());
+            candidates.truncate(top_k);
+            Ok(candidates)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rerank_reorders_candidates() {
+        use crate::{CollectionConfig, InMemoryVectorStore};
+
+        let store = Arc::new(InMemoryVectorStore::new());
+        store.create_collection("docs", CollectionConfig::new(2)).await.unwrap();
+        let retriever = Retriever::new(store, Arc::new(FixedEmbedder)).with_reranker(Arc::new(ShortestFirst));
+        retriever
+            .index(
+                "docs",
+                vec![
+                    VectorRecord::new("long", vec![1.0, 0.0], "a much longer chunk"),
+                    VectorRecord::new("short", vec![0.9, 0.1], "short"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let config = RetrievalConfig::new("docs").with_top_k(1);
+        assert_eq!(retriever.retrieve("q", &config).await.unwrap()[0].id, "long");
+
+        let config = config.with_rerank(RerankConfig::new(10));
+        let results = retriever.retrieve("q", &config).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "short");
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: RetrievalConfig =
+            serde_json::from_value(serde_json::json!({"collection": "docs", "search": "hybrid", "filter": {"lang": "en"}}))
+                .unwrap();
+        assert_eq!(config.top_k, 5);
+        assert_eq!(config.search, SearchMode::Hybrid);
+        assert!(config.filter.unwrap().matches(&serde_json::from_str(r#"{"lang":"en"}"#).unwrap()));
+        assert!(config.rerank.is_none());
+
+        let config: RetrievalConfig =
+            serde_json::from_value(serde_json::json!({"collection": "docs", "rerank": {"top_k_out": 3}})).unwrap();
+        assert_eq!(config.rerank, Some(RerankConfig::default().with_top_k_out(3)));
+    }
+}
@@ -0,0 +1,245 @@
+//! SQLite vector store.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+
+use crate::memory::{check_dimension, similarity};
+use crate::store::{
+    distance_name, parse_distance, CollectionConfig, MetadataFilter, SearchResult, VectorRecord, VectorStore,
+    VectorStoreError,
+};
+
+/// Vector store kept in a SQLite file, with brute-force search.
+///
+/// Vectors are stored as little-endian `f32` blobs and scored in process, so
+/// this suits corpora up to tens of thousands of chunks that should survive a
+/// restart without running a database server.
+pub struct SqliteVectorStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteVectorStore {
+    /// Opens (or creates) a vector database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VectorStoreError> {
+        Self::init(Connection::open(path).map_err(backend_err)?)
+    }
+
+    /// Creates a store that lives only as long as this value.
+    pub fn in_memory() -> Result<Self, VectorStoreError> {
+        Self::init(Connection::open_in_memory().map_err(backend_err)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, VectorStoreError> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS collections (
+                name TEXT PRIMARY KEY,
+                dimension INTEGER NOT NULL,
+                distance TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS records (
+                collection TEXT NOT NULL,
+                id TEXT NOT NULL,
+                vector BLOB NOT NULL,
+                text TEXT NOT NULL,
+                metadata TEXT NOT NULL,
+                PRIMARY KEY (collection, id)
+            );
+            "#,
+        )
+        .map_err(backend_err)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, VectorStoreError> {
+        self.conn.lock().map_err(|_| VectorStoreError::Backend("lock poisoned".into()))
+    }
+}
+
+fn backend_err(e: rusqlite::Error) -> VectorStoreError {
+    VectorStoreError::Backend(e.to_string())
+}
+
+fn config(conn: &Connection, collection: &str) -> Result<CollectionConfig, VectorStoreError> {
+    conn.query_row(
+        "SELECT dimension, distance FROM collections WHERE name = ?1",
+        params![collection],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+    )
+    .optional()
+    .map_err(backend_err)?
+    .map(|(dimension, distance)| CollectionConfig::new(dimension as usize).with_distance(parse_distance(&distance)))
+    .ok_or_else(|| VectorStoreError::CollectionNotFound(collection.to_string()))
+}
+
+fn encode(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[async_trait]
+impl VectorStore for SqliteVectorStore {
+    async fn create_collection(&self, name: &str, config: CollectionConfig) -> Result<(), VectorStoreError> {
+        self.lock()?
+            .execute(
+                "INSERT OR IGNORE INTO collections (name, dimension, distance) VALUES (?1, ?2, ?3)",
+                params![name, config.dimension as i64, distance_name(config.distance)],
+            )
+            .map_err(backend_err)?;
+        Ok(())
+    }
+
+    async fn delete_collection(&self, name: &str) -> Result<(), VectorStoreError> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction().map_err(backend_err)?;
+        tx.execute("DELETE FROM records WHERE collection = ?1", params![name]).map_err(backend_err)?;
+        tx.execute("DELETE FROM collections WHERE name = ?1", params![name]).map_err(backend_err)?;
+        tx.commit().map_err(backend_err)
+    }
+
+    async fn list_collections(&self) -> Result<Vec<String>, VectorStoreError> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT name FROM collections ORDER BY name").map_err(backend_err)?;
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(backend_err)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(backend_err)?;
+        Ok(names)
+    }
+
+    async fn upsert(&self, collection: &str, records: Vec<VectorRecord>) -> Result<(), VectorStoreError> {
+        let mut conn = self.lock()?;
+        let config = config(&conn, collection)?;
+        for record in &records {
+            check_dimension(config.dimension, record.vector.len())?;
+        }
+
+        let tx = conn.transaction().map_err(backend_err)?;
+        for record in records {
+            let metadata = Value::Object(record.metadata).to_string();
+            tx.execute(
+                "INSERT OR REPLACE INTO records (collection, id, vector, text, metadata) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![collection, record.id, encode(&record.vector), record.text, metadata],
+            )
+            .map_err(backend_err)?;
+        }
+        tx.commit().map_err(backend_err)
+    }
+
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError> {
+        let mut conn = self.lock()?;
+        config(&conn, collection)?;
+
+        let tx = conn.transaction().map_err(backend_err)?;
+        for id in ids {
+            tx.execute("DELETE FROM records WHERE collection = ?1 AND id = ?2", params![collection, id])
+                .map_err(backend_err)?;
+        }
+        tx.commit().map_err(backend_err)
+    }
+
+    async fn search(
+        &self,
+        collection: &str,
+        vector: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError> {
+        let conn = self.lock()?;
+        let config = config(&conn, collection)?;
+        check_dimension(config.dimension, vector.len())?;
+
+        let mut stmt = conn
+            .prepare("SELECT id, vector, text, metadata FROM records WHERE collection = ?1")
+            .map_err(backend_err)?;
+        let rows = stmt
+            .query_map(params![collection], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(backend_err)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (id, blob, text, metadata) = row.map_err(backend_err)?;
+            let metadata: Map<String, Value> = serde_json::from_str(&metadata).unwrap_or_default();
+            if filter.is_some_and(|f| !f.matches(&metadata)) {
+                continue;
+            }
+            results.push(SearchResult {
+                id,
+                score: similarity(config.distance, vector, &decode(&blob)),
+                text,
+                metadata,
+            });
+        }
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(top_k);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_persist_and_rank() {
+        let store = SqliteVectorStore::in_memory().unwrap();
+        store.create_collection("docs", CollectionConfig::new(2)).await.unwrap();
+        store
+            .upsert(
+                "docs",
+                vec![
+                    VectorRecord::new("a", vec![1.0, 0.0], "alpha").with_metadata("lang", "en"),
+                    VectorRecord::new("b", vec![0.7, 0.7], "beta").with_metadata("lang", "de"),
+                    VectorRecord::new("c", vec![0.0, 1.0], "gamma").with_metadata("lang", "en"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let hits = store.search("docs", &[1.0, 0.1], 2, None).await.unwrap();
+        assert_eq!(hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(hits[0].text, "alpha");
+
+        let filter = MetadataFilter::new().eq("lang", "en");
+        let hits = store.search("docs", &[0.7, 0.7], 5, Some(&filter)).await.unwrap();
+        assert_eq!(hits.len(), 2);
+
+        store.upsert("docs", vec![VectorRecord::new("a", vec![0.0, 1.0], "alpha v2")]).await.unwrap();
+        store.delete("docs", &["c".to_string()]).await.unwrap();
+        let hits = store.search("docs", &[0.0, 1.0], 5, None).await.unwrap();
+        assert_eq!(hits[0].text, "alpha v2");
+        assert_eq!(hits.len(), 2);
+
+        store.delete_collection("docs").await.unwrap();
+        assert!(store.list_collections().await.unwrap().is_empty());
+        assert!(matches!(
+            store.search("docs", &[1.0, 0.0], 1, None).await,
+            Err(VectorStoreError::CollectionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_vector_encoding_round_trips() {
+        let vector = vec![0.5, -1.25, 3.0e-7];
+        assert_eq!(decode(&encode(&vector)), vector);
+    }
+}
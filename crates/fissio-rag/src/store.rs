@@ -0,0 +1,168 @@
+//! The vector store trait and the records it stores.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Errors from vector store and retrieval operations.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum VectorStoreError {
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(String),
+    #[error("Invalid collection name: {0}")]
+    InvalidCollectionName(String),
+    #[error("Expected {expected} dimensions, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+    #[error("Backend error: {0}")]
+    Backend(String),
+    #[error("Embedding error: {0}")]
+    Embedding(String),
+    #[error("Keyword index error: {0}")]
+    Index(String),
+    #[error("Rerank error: {0}")]
+    Rerank(String),
+    #[error("Connector error: {0}")]
+    Connector(String),
+    #[error("Failed to extract text: {0}")]
+    Extract(String),
+}
+
+/// Similarity measure used to rank search results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distance {
+    #[default]
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+pub(crate) fn distance_name(distance: Distance) -> &'static str {
+    match distance {
+        Distance::Cosine => "cosine",
+        Distance::Dot => "dot",
+        Distance::Euclidean => "euclidean",
+    }
+}
+
+pub(crate) fn parse_distance(name: &str) -> Distance {
+    match name {
+        "dot" => Distance::Dot,
+        "euclidean" => Distance::Euclidean,
+        _ => Distance::Cosine,
+    }
+}
+
+/// Settings for a new collection.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CollectionConfig {
+    /// Length of every vector in the collection.
+    pub dimension: usize,
+    #[serde(default)]
+    pub distance: Distance,
+}
+
+impl CollectionConfig {
+    /// Creates a cosine-distance collection config.
+    pub fn new(dimension: usize) -> Self {
+        Self { dimension, distance: Distance::Cosine }
+    }
+
+    pub fn with_distance(mut self, distance: Distance) -> Self {
+        self.distance = distance;
+        self
+    }
+}
+
+/// An embedded chunk of text with its metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorRecord {
+    pub id: String,
+    pub vector: Vec<f32>,
+    pub text: String,
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+impl VectorRecord {
+    pub fn new(id: impl Into<String>, vector: Vec<f32>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            vector,
+            text: text.into(),
+            metadata: Map::new(),
+        }
+    }
+
+    /// Adds a metadata field that searches can filter on.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// A record returned by a search, best match first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub id: String,
+    /// Similarity score; higher is more similar regardless of distance metric.
+    pub score: f32,
+    pub text: String,
+    pub metadata: Map<String, Value>,
+}
+
+/// Restricts a search to records whose metadata has all the given values.
+///
+/// Serializes as a plain object, e.g. `{"lang": "en"}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MetadataFilter {
+    pub equals: Map<String, Value>,
+}
+
+impl MetadataFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires `metadata[key] == value`.
+    pub fn eq(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.equals.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns true if `metadata` satisfies every condition.
+    pub fn matches(&self, metadata: &Map<String, Value>) -> bool {
+        self.equals.iter().all(|(k, v)| metadata.get(k) == Some(v))
+    }
+}
+
+/// Storage for embedded text, organized into named collections.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// Creates a collection; succeeds if it already exists.
+    async fn create_collection(&self, name: &str, config: CollectionConfig) -> Result<(), VectorStoreError>;
+
+    /// Deletes a collection and all its records.
+    async fn delete_collection(&self, name: &str) -> Result<(), VectorStoreError>;
+
+    /// Lists collection names.
+    async fn list_collections(&self) -> Result<Vec<String>, VectorStoreError>;
+
+    /// Inserts records, replacing any with the same ID.
+    async fn upsert(&self, collection: &str, records: Vec<VectorRecord>) -> Result<(), VectorStoreError>;
+
+    /// Deletes records by ID. Unknown IDs are ignored.
+    async fn delete(&self, collection: &str, ids: &[String]) -> Result<(), VectorStoreError>;
+
+    /// Returns up to `top_k` records most similar to `vector`.
+    async fn search(
+        &self,
+        collection: &str,
+        vector: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<SearchResult>, VectorStoreError>;
+}
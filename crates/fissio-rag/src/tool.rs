@@ -0,0 +1,206 @@
+//! The `vector_search` tool, for agents that decide when to search.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use fissio_tools::{Tool, ToolError, ToolMetadata};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::retriever::{RetrievalConfig, Retriever};
+use crate::store::{MetadataFilter, SearchResult};
+
+/// Most results a single call may ask for.
+const MAX_TOP_K: usize = 50;
+
+/// The `vector_search` tool: returns the chunks of a collection most similar
+/// to a query.
+///
+/// A Retriever node searches once per run with its input; this tool lets a
+/// worker search as often as it needs, with queries it writes itself. The
+/// tool's [`RetrievalConfig`] sets the collection, search mode, and default
+/// number of results; the LLM may ask for a different `top_k` and add metadata
+/// filters, but can't override the config's own filter.
+pub struct VectorSearchTool {
+    retriever: Arc<Retriever>,
+    config: RetrievalConfig,
+    name: String,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct SearchOutput {
+    results: Vec<SearchResult>,
+}
+
+impl VectorSearchTool {
+    /// Creates a tool that searches `collection` with default retrieval settings.
+    pub fn new(retriever: Arc<Retriever>, collection: impl Into<String>) -> Self {
+        Self::with_config(retriever, RetrievalConfig::new(collection))
+    }
+
+    /// Creates a tool that searches with `config`.
+    pub fn with_config(retriever: Arc<Retriever>, config: RetrievalConfig) -> Self {
+        Self {
+            description: format!(
+                "Search the '{}' document collection for passages relevant to a query. Returns the best \
+                matches with their text, similarity score, and metadata.",
+                config.collection
+            ),
+            retriever,
+            config,
+            name: "vector_search".to_string(),
+        }
+    }
+
+    /// Renames the tool, e.g. to offer several collections.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Replaces the description; saying what the collection holds helps the LLM decide when to search.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for VectorSearchTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to search for, in natural language"
+                },
+                "top_k": {
+                    "type": "integer",
+                    "description": format!("Number of results (default {})", self.config.top_k)
+                },
+                "filter": {
+                    "type": "object",
+                    "description": "Only return chunks whose metadata has these exact values, e.g. {\"lang\": \"en\"}"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("rag")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'query' parameter".to_string()))?;
+
+        let mut config = self.config.clone();
+        if let Some(top_k) = args.get("top_k").and_then(|v| v.as_u64()) {
+            config.top_k = (top_k as usize).clamp(1, MAX_TOP_K);
+        }
+        match args.get("filter") {
+            None | Some(Value::Null) => {}
+            Some(Value::Object(equals)) => {
+                let mut filter = MetadataFilter { equals: equals.clone() };
+                // The configured filter wins, so the LLM can't widen the search
+                if let Some(base) = &self.config.filter {
+                    filter.equals.extend(base.equals.clone());
+                }
+                config.filter = Some(filter);
+            }
+            Some(_) => return Err(ToolError::InvalidArguments("'filter' must be an object".into())),
+        }
+
+        let results = self
+            .retriever
+            .retrieve(query, &config)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("search failed: {}", e)))?;
+
+        serde_json::to_string_pretty(&SearchOutput { results })
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CollectionConfig, Embedder, InMemoryVectorStore, VectorRecord, VectorStore, VectorStoreError};
+
+    /// Embeds texts mentioning "rust" along one axis and everything else along the other.
+    struct TopicEmbedder;
+
+    #[async_trait]
+    impl Embedder for TopicEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+            Ok(texts
+                .iter()
+                .map(|t| if t.to_lowercase().contains("rust") { vec![1.0, 0.0] } else { vec![0.0, 1.0] })
+                .collect())
+        }
+    }
+
+    async fn tool() -> VectorSearchTool {
+        let store = Arc::new(InMemoryVectorStore::new());
+        store.create_collection("docs", CollectionConfig::new(2)).await.unwrap();
+        let retriever = Arc::new(Retriever::new(store, Arc::new(TopicEmbedder)));
+        retriever
+            .index(
+                "docs",
+                vec![
+                    VectorRecord::new("rust", vec![1.0, 0.0], "Rust has no garbage collector").with_metadata("lang", "en"),
+                    VectorRecord::new("rust-de", vec![0.9, 0.1], "Rust hat keinen Garbage Collector")
+                        .with_metadata("lang", "de"),
+                    VectorRecord::new("go", vec![0.0, 1.0], "Go is garbage collected").with_metadata("lang", "en"),
+                ],
+            )
+            .await
+            .unwrap();
+        VectorSearchTool::new(retriever, "docs")
+    }
+
+    fn ids(output: &str) -> Vec<String> {
+        let value: Value = serde_json::from_str(output).unwrap();
+        value["results"].as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_similarity() {
+        let tool = tool().await;
+        let output = tool.execute(json!({"query": "memory in Rust", "top_k": 2})).await.unwrap();
+        assert_eq!(ids(&output), ["rust", "rust-de"]);
+
+        let output = tool.execute(json!({"query": "memory in Rust", "filter": {"lang": "en"}})).await.unwrap();
+        assert_eq!(ids(&output), ["rust", "go"]);
+    }
+
+    #[tokio::test]
+    async fn test_configured_filter_cannot_be_widened() {
+        let tool = tool().await;
+        let config = RetrievalConfig::new("docs").with_filter(MetadataFilter::new().eq("lang", "de"));
+        let tool = VectorSearchTool::with_config(tool.retriever.clone(), config);
+
+        let output = tool.execute(json!({"query": "Rust", "filter": {"lang": "en"}})).await.unwrap();
+        assert_eq!(ids(&output), ["rust-de"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_query() {
+        let tool = tool().await;
+        assert!(matches!(tool.execute(json!({})).await, Err(ToolError::InvalidArguments(_))));
+    }
+}
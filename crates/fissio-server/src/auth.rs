@@ -0,0 +1,440 @@
+//! API key and JWT bearer authentication.
+//!
+//! Requests carry a token in `Authorization: Bearer <token>` or `X-API-Key`.
+//! A token is either one of the static keys in `API_KEYS` or a JWT signed with
+//! `AUTH_JWT_SECRET` (HS256) or the key in `AUTH_JWT_PUBLIC_KEY` (RS256/ES256).
+//! With neither configured, authentication is off.
+//!
+//! Any valid token can read, except under `/admin`; changes need a scope:
+//!
+//! - `run` — chat, sessions, evaluations, and comparisons
+//! - `edit` — saving pipelines, registering tools, uploading documents, schedules
+//! - `models` — waking and unloading models
+//! - `admin` — viewing and changing usage policies under `/admin`
+//!
+//! `/health` is always open.
+//!
+//! Each principal belongs to a tenant, which owns the pipelines, sessions,
+//! schedules, runs, and traces it creates; see [`Tenant`].
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{FromRequestParts, MatchedPath, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+
+/// What a token may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Run pipelines and chats.
+    Run,
+    /// Change pipelines, tools, documents, and schedules.
+    Edit,
+    /// Load and unload models.
+    Models,
+    /// Manage other keys' usage policies.
+    Admin,
+}
+
+impl Scope {
+    const ALL: [Scope; 4] = [Scope::Run, Scope::Edit, Scope::Models, Scope::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Run => "run",
+            Scope::Edit => "edit",
+            Scope::Models => "models",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "run" => Ok(Scope::Run),
+            "edit" => Ok(Scope::Edit),
+            "models" => Ok(Scope::Models),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!("unknown scope '{}' (expected run, edit, models, or admin)", other)),
+        }
+    }
+}
+
+/// Who made a request, added to the request's extensions once authenticated.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// The API key's name or the JWT's `sub`.
+    pub id: String,
+    /// The part of an API key's name before `/`, or the JWT's `tenant` claim,
+    /// defaulting to the whole name or `sub`.
+    pub tenant: String,
+    pub scopes: HashSet<Scope>,
+}
+
+impl Principal {
+    pub fn has(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// The tenant a request acts for, or `None` when authentication is off and
+/// everything is shared.
+///
+/// Resources record their owner; those saved without one (before
+/// authentication was enabled, or seeded examples) have none.
+#[derive(Debug, Clone, Default)]
+pub struct Tenant(pub Option<String>);
+
+impl Tenant {
+    pub fn id(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+
+    /// Whether this tenant may see and change a resource owned by `owner`.
+    pub fn owns(&self, owner: Option<&str>) -> bool {
+        self.0.is_none() || self.id() == owner
+    }
+
+    /// Whether this tenant may see a resource owned by `owner`; unowned
+    /// resources are visible to everyone.
+    pub fn can_read(&self, owner: Option<&str>) -> bool {
+        owner.is_none() || self.owns(owner)
+    }
+
+    /// Prefixes `key` with the tenant, for keys like run IDs and memory
+    /// scopes that are stored without an owner.
+    pub fn scope(&self, key: &str) -> String {
+        match &self.0 {
+            Some(tenant) => format!("{}/{}", tenant, key),
+            None => key.to_string(),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Tenant(parts.extensions.get::<Principal>().map(|p| p.tenant.clone())))
+    }
+}
+
+/// A static API key.
+#[derive(Clone)]
+struct ApiKey {
+    name: String,
+    key: String,
+    scopes: HashSet<Scope>,
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey").field("name", &self.name).field("scopes", &self.scopes).finish_non_exhaustive()
+    }
+}
+
+/// How JWTs are verified.
+#[derive(Clone)]
+struct JwtConfig {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+/// Accepted API keys and JWT settings.
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    keys: Vec<ApiKey>,
+    jwt: Option<Arc<JwtConfig>>,
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthConfig").field("keys", &self.keys).field("jwt", &self.jwt.is_some()).finish()
+    }
+}
+
+/// Claims read from a JWT; `exp` is checked by the validator.
+#[derive(Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(default)]
+    tenant: Option<String>,
+    /// Space-separated, as in OAuth 2.0.
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+impl AuthConfig {
+    /// Reads `API_KEYS` and the `AUTH_JWT_*` variables.
+    ///
+    /// `API_KEYS` is a comma-separated list of `name:key` or
+    /// `name:key:scope+scope` entries; a key without scopes has all of them.
+    /// Keys named `tenant/name` share that tenant's resources.
+    /// JWTs are verified with `AUTH_JWT_SECRET`, or the PEM file at
+    /// `AUTH_JWT_PUBLIC_KEY` (`AUTH_JWT_ALGORITHM`, default `RS256`), and must
+    /// match `AUTH_JWT_ISSUER` and `AUTH_JWT_AUDIENCE` when those are set.
+    /// Misconfiguration is an error, so the server doesn't start unprotected.
+    pub fn from_env() -> Result<Self> {
+        let keys = match std::env::var("API_KEYS") {
+            Ok(value) => parse_keys(&value)?,
+            Err(_) => Vec::new(),
+        };
+        let jwt = jwt_from_env()?.map(Arc::new);
+        let config = Self { keys, jwt };
+        if config.enabled() {
+            info!(
+                "Authentication enabled ({} API keys{})",
+                config.keys.len(),
+                if config.jwt.is_some() { ", JWT" } else { "" }
+            );
+        } else {
+            warn!("Authentication disabled: set API_KEYS or AUTH_JWT_SECRET / AUTH_JWT_PUBLIC_KEY");
+        }
+        Ok(config)
+    }
+
+    /// True when requests must carry a token.
+    pub fn enabled(&self) -> bool {
+        !self.keys.is_empty() || self.jwt.is_some()
+    }
+
+    /// Returns who a token belongs to, or why it's refused.
+    pub fn authenticate(&self, token: &str) -> Result<Principal, String> {
+        if let Some(key) = self.keys.iter().find(|k| constant_time_eq(k.key.as_bytes(), token.as_bytes())) {
+            let tenant = key.name.split_once('/').map_or(key.name.as_str(), |(tenant, _)| tenant);
+            return Ok(Principal { id: key.name.clone(), tenant: tenant.to_string(), scopes: key.scopes.clone() });
+        }
+        let Some(jwt) = &self.jwt else {
+            return Err("invalid API key".into());
+        };
+        let claims = jsonwebtoken::decode::<Claims>(token, &jwt.key, &jwt.validation)
+            .map_err(|e| format!("invalid token: {}", e))?
+            .claims;
+        let scopes = claims
+            .scope
+            .iter()
+            .flat_map(|s| s.split_whitespace())
+            .chain(claims.scopes.iter().map(String::as_str))
+            .filter_map(|s| s.parse().ok())
+            .collect();
+        let id = claims.sub.unwrap_or_default();
+        let tenant = claims.tenant.unwrap_or_else(|| id.clone());
+        if tenant.is_empty() {
+            return Err("token has neither a sub nor a tenant claim".into());
+        }
+        Ok(Principal { id, tenant, scopes })
+    }
+}
+
+fn parse_keys(value: &str) -> Result<Vec<ApiKey>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            let (Some(name), Some(key)) = (parts.next(), parts.next()) else {
+                return Err(anyhow!("API_KEYS entry must be name:key[:scopes]"));
+            };
+            if name.is_empty() || key.is_empty() {
+                return Err(anyhow!("API_KEYS entry '{}' has an empty name or key", name));
+            }
+            let scopes = match parts.next() {
+                Some(scopes) => scopes
+                    .split('+')
+                    .map(|s| s.trim().parse::<Scope>().map_err(|e| anyhow!("API key '{}': {}", name, e)))
+                    .collect::<Result<_>>()?,
+                None => Scope::ALL.into_iter().collect(),
+            };
+            Ok(ApiKey { name: name.to_string(), key: key.to_string(), scopes })
+        })
+        .collect()
+}
+
+fn jwt_from_env() -> Result<Option<JwtConfig>> {
+    let (key, algorithm) = if let Ok(secret) = std::env::var("AUTH_JWT_SECRET") {
+        (DecodingKey::from_secret(secret.as_bytes()), Algorithm::HS256)
+    } else if let Ok(path) = std::env::var("AUTH_JWT_PUBLIC_KEY") {
+        let pem = std::fs::read(&path).with_context(|| format!("failed to read AUTH_JWT_PUBLIC_KEY '{}'", path))?;
+        let algorithm: Algorithm = std::env::var("AUTH_JWT_ALGORITHM")
+            .unwrap_or_else(|_| "RS256".into())
+            .parse()
+            .context("invalid AUTH_JWT_ALGORITHM")?;
+        let key = match algorithm {
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(&pem),
+            _ => DecodingKey::from_rsa_pem(&pem),
+        }
+        .with_context(|| format!("invalid public key in '{}'", path))?;
+        (key, algorithm)
+    } else {
+        return Ok(None);
+    };
+
+    let mut validation = Validation::new(algorithm);
+    if let Ok(issuer) = std::env::var("AUTH_JWT_ISSUER") {
+        validation.set_issuer(&[issuer]);
+    }
+    match std::env::var("AUTH_JWT_AUDIENCE") {
+        Ok(audience) => validation.set_audience(&[audience]),
+        Err(_) => validation.validate_aud = false,
+    }
+    Ok(Some(JwtConfig { key, validation }))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The scope a route needs beyond a valid token, if any.
+fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    if path.starts_with("/admin") {
+        return Some(Scope::Admin);
+    }
+    if path.starts_with("/chat") || path.starts_with("/sessions") || path == "/compare" || path.ends_with("/evaluate") {
+        return Some(Scope::Run);
+    }
+    if method == Method::GET {
+        return None;
+    }
+    if path.starts_with("/models") {
+        return Some(Scope::Models);
+    }
+    Some(Scope::Edit)
+}
+
+/// Middleware that authenticates each request and checks its route's scope.
+pub async fn require_auth(State(auth): State<Arc<AuthConfig>>, mut req: Request, next: Next) -> Result<Response, AppError> {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| req.headers().get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .ok_or_else(|| AppError::Unauthorized("missing API key or bearer token".into()))?;
+    let principal = auth.authenticate(token).map_err(AppError::Unauthorized)?;
+
+    let path = match req.extensions().get::<MatchedPath>() {
+        Some(matched) => matched.as_str().to_string(),
+        None => req.uri().path().to_string(),
+    };
+    if let Some(scope) = required_scope(req.method(), &path) {
+        if !principal.has(scope) {
+            return Err(AppError::Forbidden(format!("{} {} requires the '{}' scope", req.method(), path, scope)));
+        }
+    }
+    debug!("Authenticated {} for {} {}", principal.id, req.method(), path);
+
+    req.extensions_mut().insert(principal);
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::ServiceExt;
+
+    fn config() -> AuthConfig {
+        let keys = parse_keys("reader:reader-key:run, editor:editor-key:edit, acme/ops:ops-key").unwrap();
+        AuthConfig { keys, jwt: None }
+    }
+
+    /// Status of `method path` through the auth middleware, with `token` if any.
+    async fn status(config: AuthConfig, method: Method, path: &str, token: Option<&str>) -> StatusCode {
+        let ok = || async { "ok" };
+        let app = Router::new()
+            .route("/chat", post(ok))
+            .route("/pipelines", get(ok))
+            .route("/pipelines/save", post(ok))
+            .route("/models/{id}/wake", post(ok))
+            .route("/admin/quotas", get(ok))
+            .route_layer(axum::middleware::from_fn_with_state(Arc::new(config), require_auth));
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_requires_a_valid_token() {
+        assert_eq!(status(config(), Method::GET, "/pipelines", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(config(), Method::GET, "/pipelines", Some("wrong-key")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(config(), Method::GET, "/pipelines", Some("reader-key")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_routes_outside_the_keys_scopes() {
+        let cases = [
+            (Method::POST, "/chat", "reader-key", StatusCode::OK),
+            (Method::POST, "/pipelines/save", "reader-key", StatusCode::FORBIDDEN),
+            (Method::POST, "/models/llama/wake", "reader-key", StatusCode::FORBIDDEN),
+            (Method::GET, "/admin/quotas", "reader-key", StatusCode::FORBIDDEN),
+            (Method::POST, "/chat", "editor-key", StatusCode::FORBIDDEN),
+            (Method::POST, "/pipelines/save", "editor-key", StatusCode::OK),
+            (Method::GET, "/admin/quotas", "ops-key", StatusCode::OK),
+            (Method::POST, "/models/llama/wake", "ops-key", StatusCode::OK),
+        ];
+        for (method, path, token, expected) in cases {
+            assert_eq!(status(config(), method.clone(), path, Some(token)).await, expected, "{} {} with {}", method, path, token);
+        }
+    }
+
+    #[test]
+    fn test_key_names_set_the_tenant() {
+        let config = config();
+        assert_eq!(config.authenticate("ops-key").unwrap().tenant, "acme");
+        assert_eq!(config.authenticate("reader-key").unwrap().tenant, "reader");
+        assert!(parse_keys("bot:bot-key:run+deploy").is_err());
+        assert!(parse_keys("bot:").is_err());
+    }
+
+    #[test]
+    fn test_jwt_scopes_and_tenant() {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_aud = false;
+        let jwt = JwtConfig { key: DecodingKey::from_secret(b"secret"), validation };
+        let config = AuthConfig { keys: Vec::new(), jwt: Some(Arc::new(jwt)) };
+        let token = |claims: serde_json::Value| {
+            jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(b"secret")).unwrap()
+        };
+
+        let principal = config
+            .authenticate(&token(serde_json::json!({ "sub": "ci", "tenant": "acme", "scope": "run models", "exp": 4102444800u64 })))
+            .unwrap();
+        assert_eq!(principal.tenant, "acme");
+        assert!(principal.has(Scope::Run) && principal.has(Scope::Models) && !principal.has(Scope::Edit));
+
+        let expired = token(serde_json::json!({ "sub": "ci", "scope": "run", "exp": 1 }));
+        assert!(config.authenticate(&expired).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! Server configuration read from a TOML file and the environment.
+//!
+//! Settings come from `fissio.toml` (or the file named by `FISSIO_CONFIG`),
+//! and environment variables override the file:
+//!
+//! ```toml
+//! bind_addr = "0.0.0.0:8000"
+//! ollama_hosts = ["http://gpu-1:11434", "http://gpu-2:11434"]
+//! presets_dir = "presets"
+//! database_url = "data/pipelines.db"
+//!
+//! [cors]
+//! allowed_origins = ["https://agents.example.com"]
+//!
+//! [[models]]
+//! id = "openai-gpt5"
+//! name = "GPT-5.2 (OpenAI)"
+//! model = "gpt-5.2-2025-12-11"
+//! provider = "openai"
+//! pricing = { input_per_1k = 0.00175, output_per_1k = 0.014 }
+//! ```
+//!
+//! `models` replaces the built-in cloud model catalog.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::extract::{Request, State};
+use axum::http::header::{STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS};
+use axum::http::{HeaderName, HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use fissio_core::ModelConfig;
+use serde::Deserialize;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tracing::{info, warn};
+
+use crate::auth::AuthConfig;
+use crate::tls::TlsConfig;
+
+const DEFAULT_CONFIG_PATH: &str = "fissio.toml";
+const DEFAULT_BIND_ADDR: &str = "0.0.0.0:8000";
+const DEFAULT_OLLAMA_HOST: &str = "http://host.docker.internal:11434";
+const DEFAULT_PRESETS_DIR: &str = "presets";
+const DEFAULT_DATABASE_URL: &str = "data/pipelines.db";
+const DEFAULT_HSTS_MAX_AGE: u64 = 31_536_000;
+
+/// Top-level server settings.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_addr: SocketAddr,
+    /// Ollama servers models are discovered on; empty disables Ollama.
+    pub ollama_hosts: Vec<String>,
+    pub presets_dir: PathBuf,
+    pub database_url: String,
+    /// Cloud models to offer; `None` offers the built-in catalog.
+    pub cloud_models: Option<Vec<ModelConfig>>,
+    pub model_pool: ModelPoolConfig,
+    pub cors: CorsConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub tls: Option<TlsConfig>,
+    pub auth: AuthConfig,
+}
+
+/// The settings file, every field optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    bind_addr: Option<String>,
+    ollama_hosts: Option<Vec<String>>,
+    presets_dir: Option<PathBuf>,
+    database_url: Option<String>,
+    cors: FileCorsConfig,
+    models: Option<Vec<ModelConfig>>,
+    model_pool: FileModelPoolConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileModelPoolConfig {
+    keep_alive_secs: Option<u64>,
+    prewarm: Option<bool>,
+    keep_alive_secs_by_model: HashMap<String, u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileCorsConfig {
+    allowed_origins: Option<Vec<String>>,
+    allowed_methods: Option<Vec<String>>,
+    allowed_headers: Option<Vec<String>>,
+}
+
+impl FileConfig {
+    /// Reads the file named by `FISSIO_CONFIG`, or `fissio.toml` if it exists.
+    fn load() -> anyhow::Result<Self> {
+        let (path, required) = match std::env::var("FISSIO_CONFIG") {
+            Ok(path) => (PathBuf::from(path), true),
+            Err(_) => (PathBuf::from(DEFAULT_CONFIG_PATH), false),
+        };
+        if !required && !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config file '{}' (FISSIO_CONFIG)", path.display()))?;
+        let config = toml::from_str(&content).with_context(|| format!("invalid config file '{}'", path.display()))?;
+        info!("Loaded server config from {}", path.display());
+        Ok(config)
+    }
+}
+
+impl ServerConfig {
+    /// Reads the settings file and environment, falling back to defaults.
+    /// Fails with every problem found if a setting is invalid.
+    pub fn load() -> anyhow::Result<Self> {
+        let file = FileConfig::load()?;
+        let mut problems = Vec::new();
+
+        let bind_addr = env_or("BIND_ADDR", file.bind_addr).unwrap_or_else(|| DEFAULT_BIND_ADDR.to_string());
+        let bind_addr = bind_addr.parse().unwrap_or_else(|e| {
+            problems.push(format!("bind_addr '{}' is not a socket address (like 0.0.0.0:8000): {}", bind_addr, e));
+            DEFAULT_BIND_ADDR.parse().expect("default bind address is valid")
+        });
+
+        let ollama_hosts = env_csv("OLLAMA_HOSTS")
+            .or(file.ollama_hosts)
+            .unwrap_or_else(|| vec![DEFAULT_OLLAMA_HOST.to_string()]);
+        for host in &ollama_hosts {
+            if !host.starts_with("http://") && !host.starts_with("https://") {
+                problems.push(format!("Ollama host '{}' must start with http:// or https://", host));
+            }
+        }
+
+        let presets_dir = env_or("PRESETS_DIR", file.presets_dir.map(|p| p.display().to_string()))
+            .map_or_else(|| PathBuf::from(DEFAULT_PRESETS_DIR), PathBuf::from);
+        if presets_dir.exists() && !presets_dir.is_dir() {
+            problems.push(format!("presets_dir '{}' is not a directory", presets_dir.display()));
+        }
+
+        let database_url = env_or("DATABASE_URL", file.database_url).unwrap_or_else(|| DEFAULT_DATABASE_URL.to_string());
+        if database_url.trim().is_empty() {
+            problems.push("database_url must not be empty".to_string());
+        }
+
+        if let Some(models) = &file.models {
+            problems.extend(model_problems(models));
+        }
+
+        let auth = AuthConfig::from_env();
+        if let Err(e) = &auth {
+            problems.push(format!("{:#}", e));
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("invalid server configuration:\n  - {}", problems.join("\n  - "));
+        }
+        Ok(Self {
+            bind_addr,
+            ollama_hosts,
+            presets_dir,
+            database_url,
+            cloud_models: file.models,
+            cors: CorsConfig::load(file.cors),
+            model_pool: ModelPoolConfig::load(file.model_pool),
+            security_headers: SecurityHeadersConfig::from_env(),
+            tls: TlsConfig::from_env(),
+            auth: auth?,
+        })
+    }
+
+    /// The Ollama server used for embeddings and GPU memory scheduling.
+    pub fn primary_ollama_host(&self) -> Option<&str> {
+        self.ollama_hosts.first().map(String::as_str)
+    }
+}
+
+/// How long local models stay loaded, and whether runs load theirs up front.
+#[derive(Debug, Clone, Default)]
+pub struct ModelPoolConfig {
+    /// Idle time after which a local model is unloaded; `None` leaves it to Ollama.
+    pub keep_alive: Option<Duration>,
+    /// Keep-alive for particular models, by model ID.
+    pub model_keep_alive: HashMap<String, Duration>,
+    /// Whether a pipeline run loads the local models it uses before it starts.
+    pub prewarm: bool,
+}
+
+impl ModelPoolConfig {
+    /// Reads `MODEL_KEEP_ALIVE_SECS` and `MODEL_PREWARM` (default `true`), falling
+    /// back to the file's `[model_pool]` table.
+    fn load(file: FileModelPoolConfig) -> Self {
+        let keep_alive = std::env::var("MODEL_KEEP_ALIVE_SECS").ok().and_then(|v| v.parse().ok()).or(file.keep_alive_secs);
+        let prewarm = std::env::var("MODEL_PREWARM").ok().map(|v| v != "false" && v != "0").or(file.prewarm);
+        Self {
+            keep_alive: keep_alive.map(Duration::from_secs),
+            model_keep_alive: file
+                .keep_alive_secs_by_model
+                .into_iter()
+                .map(|(id, secs)| (id, Duration::from_secs(secs)))
+                .collect(),
+            prewarm: prewarm.unwrap_or(true),
+        }
+    }
+
+    /// The keep-alive for a model: its own, or else the default.
+    pub fn keep_alive_for(&self, model_id: &str) -> Option<Duration> {
+        self.model_keep_alive.get(model_id).copied().or(self.keep_alive)
+    }
+}
+
+/// Checks a model catalog for missing fields, duplicate IDs, and negative prices.
+fn model_problems(models: &[ModelConfig]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut ids = HashSet::new();
+    for (i, model) in models.iter().enumerate() {
+        let label = if model.id.is_empty() { format!("models[{}]", i) } else { format!("model '{}'", model.id) };
+        if model.id.trim().is_empty() {
+            problems.push(format!("{} has no id", label));
+        } else if !ids.insert(model.id.as_str()) {
+            problems.push(format!("{} is listed twice", label));
+        }
+        if model.model.trim().is_empty() {
+            problems.push(format!("{} has no model name", label));
+        }
+        if model.pricing.is_some_and(|p| p.input_per_1k < 0.0 || p.output_per_1k < 0.0) {
+            problems.push(format!("{} has a negative price", label));
+        }
+    }
+    problems
+}
+
+/// Allowed CORS origins, methods, and headers. `*` allows any.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// Reads `CORS_ALLOWED_ORIGINS`, `CORS_ALLOWED_METHODS`, and `CORS_ALLOWED_HEADERS`
+    /// (comma-separated), falling back to the file's `[cors]` table and then `*`.
+    fn load(file: FileCorsConfig) -> Self {
+        let list = |var: &str, file: Option<Vec<String>>| {
+            env_csv(var).or(file).unwrap_or_else(|| vec!["*".to_string()])
+        };
+        Self {
+            allowed_origins: list("CORS_ALLOWED_ORIGINS", file.allowed_origins),
+            allowed_methods: list("CORS_ALLOWED_METHODS", file.allowed_methods),
+            allowed_headers: list("CORS_ALLOWED_HEADERS", file.allowed_headers),
+        }
+    }
+
+    /// Builds the CORS layer. Entries that fail to parse are skipped with a warning.
+    pub fn layer(&self) -> CorsLayer {
+        let origins = if is_any(&self.allowed_origins) {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(parse_all::<HeaderValue>(&self.allowed_origins, "origin"))
+        };
+        let methods = if is_any(&self.allowed_methods) {
+            AllowMethods::any()
+        } else {
+            AllowMethods::list(parse_all::<Method>(&self.allowed_methods, "method"))
+        };
+        let headers = if is_any(&self.allowed_headers) {
+            AllowHeaders::any()
+        } else {
+            AllowHeaders::list(parse_all::<HeaderName>(&self.allowed_headers, "header"))
+        };
+
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+    }
+}
+
+/// Standard security headers added to every response.
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    /// `Strict-Transport-Security` max-age in seconds; 0 omits the header.
+    pub hsts_max_age: u64,
+}
+
+impl SecurityHeadersConfig {
+    /// Reads `SECURITY_HEADERS` (default `true`) and `HSTS_MAX_AGE` (default one year).
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("SECURITY_HEADERS").map(|v| v != "false" && v != "0").unwrap_or(true),
+            hsts_max_age: std::env::var("HSTS_MAX_AGE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_HSTS_MAX_AGE),
+        }
+    }
+
+    /// Returns the headers to add, or none when disabled.
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut headers = vec![
+            (X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+            (X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+        ];
+        if self.hsts_max_age > 0 {
+            let hsts = format!("max-age={}; includeSubDomains", self.hsts_max_age);
+            if let Ok(value) = HeaderValue::from_str(&hsts) {
+                headers.push((STRICT_TRANSPORT_SECURITY, value));
+            }
+        }
+        headers
+    }
+}
+
+/// Middleware that adds the given security headers unless a handler already set them.
+pub async fn apply_security_headers(
+    State(headers): State<Vec<(HeaderName, HeaderValue)>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut res = next.run(req).await;
+    for (name, value) in headers {
+        res.headers_mut().entry(name).or_insert(value);
+    }
+    res
+}
+
+/// An environment variable if set, else the file's value.
+fn env_or(var: &str, file: Option<String>) -> Option<String> {
+    std::env::var(var).ok().or(file)
+}
+
+/// A comma-separated environment variable, if set.
+fn env_csv(var: &str) -> Option<Vec<String>> {
+    let value = std::env::var(var).ok()?;
+    Some(value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+}
+
+fn is_any(values: &[String]) -> bool {
+    values.is_empty() || values.iter().any(|v| v == "*")
+}
+
+fn parse_all<T: std::str::FromStr>(values: &[String], kind: &str) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|v| {
+            let parsed = v.parse().ok();
+            if parsed.is_none() {
+                warn!("Ignoring invalid CORS {} '{}'", kind, v);
+            }
+            parsed
+        })
+        .collect()
+}
@@ -1,16 +1,24 @@
 //! SQLite persistence layer for user-saved pipeline configurations.
 //!
-//! Provides CRUD operations for pipeline configs and seeds example data on first run.
+//! Provides CRUD operations for pipeline configs and uploaded documents, and
+//! seeds example data on first run.
 
 use std::fs;
 use std::path::Path;
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use tracing::{error, info};
 
 use std::collections::HashMap;
-use crate::dto::{EdgeInfo, NodeInfo, PipelineInfo, Position, SavePipelineRequest};
+use fissio_config::{ModelLifecycle, PipelineConfig, ResidencyPolicy};
+use fissio_core::{Message, MessageRole};
+
+use crate::dto::{
+    DailyUsage, EdgeInfo, KeyPolicy, NodeInfo, PipelineInfo, PipelineVersionInfo, Position, SavePipelineRequest,
+    ScheduleInfo, ScheduleRunInfo, SessionInfo,
+};
+use crate::services::pipeline::pipeline_config_to_info;
 
 /// Initializes the database, creating tables if needed.
 pub fn init_db(path: &str) -> Result<Connection> {
@@ -26,15 +34,129 @@ pub fn init_db(path: &str) -> Result<Connection> {
             config_json TEXT NOT NULL,
             created_at TEXT NOT NULL DEFAULT (datetime('now')),
             updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS pipeline_versions (
+            pipeline_id TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            config_json TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (pipeline_id, version)
+        );
+        CREATE TABLE IF NOT EXISTS queued_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            request_json TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS documents (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            format TEXT NOT NULL,
+            chunks INTEGER NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS registered_tools (
+            name TEXT PRIMARY KEY,
+            definition_json TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS session_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_messages_session ON session_messages(session_id, id);
+        CREATE TABLE IF NOT EXISTS schedules (
+            id TEXT PRIMARY KEY,
+            pipeline_id TEXT NOT NULL,
+            cron TEXT NOT NULL,
+            input TEXT NOT NULL,
+            model_id TEXT,
+            webhook_url TEXT,
+            next_run_at TEXT NOT NULL,
+            last_run_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS schedule_runs (
+            run_id TEXT PRIMARY KEY,
+            schedule_id TEXT NOT NULL,
+            pipeline_id TEXT NOT NULL,
+            status TEXT NOT NULL,
+            output TEXT NOT NULL DEFAULT '',
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            started_at TEXT NOT NULL DEFAULT (datetime('now')),
+            finished_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON schedule_runs(schedule_id, started_at);
+        CREATE TABLE IF NOT EXISTS key_policies (
+            key TEXT PRIMARY KEY,
+            models_json TEXT,
+            daily_token_limit INTEGER,
+            daily_cost_limit_usd REAL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS key_usage (
+            key TEXT NOT NULL,
+            day TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0,
+            PRIMARY KEY (key, day)
         );"
     ).context("failed to create table")?;
+    migrate_pipeline_versions(&conn)?;
+    migrate_owners(&conn)?;
     info!("Database initialized at {}", path);
     Ok(conn)
 }
 
+/// Adds `user_pipelines.version` to databases created before versioning and
+/// records each pipeline saved back then as its version 1.
+fn migrate_pipeline_versions(conn: &Connection) -> Result<()> {
+    let has_version: bool = conn
+        .query_row("SELECT COUNT(*) FROM pragma_table_info('user_pipelines') WHERE name = 'version'", [], |r| r.get(0))
+        .context("failed to inspect user_pipelines")?;
+    if !has_version {
+        conn.execute("ALTER TABLE user_pipelines ADD COLUMN version INTEGER NOT NULL DEFAULT 1", [])
+            .context("failed to add pipeline version column")?;
+    }
+    conn.execute(
+        "INSERT INTO pipeline_versions (pipeline_id, version, name, description, config_json, created_at)
+         SELECT id, version, name, description, config_json, updated_at FROM user_pipelines p
+         WHERE NOT EXISTS (SELECT 1 FROM pipeline_versions v WHERE v.pipeline_id = p.id)",
+        [],
+    ).context("failed to backfill pipeline versions")?;
+    Ok(())
+}
+
+/// Adds the `owner` column, the tenant a row belongs to, to databases created
+/// before tenants. Existing rows keep no owner.
+fn migrate_owners(conn: &Connection) -> Result<()> {
+    for table in ["user_pipelines", "pipeline_versions", "sessions", "schedules"] {
+        let has_owner: bool = conn
+            .query_row("SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = 'owner'", params![table], |r| r.get(0))
+            .with_context(|| format!("failed to inspect {}", table))?;
+        if !has_owner {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN owner TEXT", table), [])
+                .with_context(|| format!("failed to add owner column to {}", table))?;
+        }
+    }
+    Ok(())
+}
+
 /// Lists all user-saved pipeline configurations.
 pub fn list_user_pipelines(conn: &Connection) -> Vec<PipelineInfo> {
-    let mut stmt = match conn.prepare("SELECT id, name, description, config_json FROM user_pipelines") {
+    let mut stmt = match conn.prepare("SELECT id, name, description, config_json, owner FROM user_pipelines") {
         Ok(s) => s,
         Err(e) => {
             error!("Failed to prepare list query: {}", e);
@@ -47,7 +169,8 @@ pub fn list_user_pipelines(conn: &Connection) -> Vec<PipelineInfo> {
         let name: String = row.get(1)?;
         let description: String = row.get(2)?;
         let config_json: String = row.get(3)?;
-        Ok((id, name, description, config_json))
+        let owner: Option<String> = row.get(4)?;
+        Ok((id, name, description, config_json, owner))
     }) {
         Ok(r) => r,
         Err(e) => {
@@ -57,7 +180,7 @@ pub fn list_user_pipelines(conn: &Connection) -> Vec<PipelineInfo> {
     };
 
     rows.filter_map(|row| {
-        let (id, name, description, config_json) = row.ok()?;
+        let (id, name, description, config_json, owner) = row.ok()?;
         let config: StoredConfig = serde_json::from_str(&config_json).ok()?;
         Some(PipelineInfo {
             id,
@@ -66,28 +189,50 @@ pub fn list_user_pipelines(conn: &Connection) -> Vec<PipelineInfo> {
             nodes: config.nodes,
             edges: config.edges,
             layout: config.layout,
+            max_concurrency: config.max_concurrency,
+            residency: config.residency,
+            model_lifecycle: config.model_lifecycle,
+            owner,
         })
     }).collect()
 }
 
-/// Saves or updates a pipeline configuration.
-pub fn save_pipeline(conn: &Connection, req: &SavePipelineRequest) -> Result<()> {
+/// Saves or updates a pipeline configuration as its next version, owned by
+/// `owner`, returning the version number.
+pub fn save_pipeline(conn: &Connection, req: &SavePipelineRequest, owner: Option<&str>) -> Result<i64> {
     let config = StoredConfig {
         nodes: req.nodes.clone(),
         edges: req.edges.clone(),
         layout: req.layout.clone(),
+        max_concurrency: req.max_concurrency,
+        residency: req.residency.clone(),
+        model_lifecycle: req.model_lifecycle.clone(),
     };
     let config_json = serde_json::to_string(&config).context("failed to serialize config")?;
-    conn.execute(
-        "INSERT OR REPLACE INTO user_pipelines (id, name, description, config_json, updated_at)
-         VALUES (?1, ?2, ?3, ?4, datetime('now'))",
-        params![req.id, req.name, req.description, config_json],
+
+    let tx = conn.unchecked_transaction().context("failed to start transaction")?;
+    let version: i64 = tx.query_row(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM pipeline_versions WHERE pipeline_id = ?1",
+        params![req.id],
+        |r| r.get(0),
+    )?;
+    tx.execute(
+        "INSERT INTO pipeline_versions (pipeline_id, version, name, description, config_json, owner)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![req.id, version, req.name, req.description, config_json, owner],
+    ).context("failed to save pipeline version")?;
+    tx.execute(
+        "INSERT OR REPLACE INTO user_pipelines (id, name, description, config_json, version, owner, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+        params![req.id, req.name, req.description, config_json, version, owner],
     ).context("failed to save pipeline")?;
-    info!("Saved pipeline config: {} ({})", req.name, req.id);
-    Ok(())
+    tx.commit().context("failed to save pipeline")?;
+    info!("Saved pipeline config: {} ({}) v{}", req.name, req.id, version);
+    Ok(version)
 }
 
-/// Deletes a pipeline configuration by ID.
+/// Deletes a pipeline configuration by ID. Its versions are kept, so a
+/// rollback can restore it.
 pub fn delete_pipeline(conn: &Connection, id: &str) -> Result<()> {
     conn.execute("DELETE FROM user_pipelines WHERE id = ?1", params![id])
         .context("failed to delete pipeline")?;
@@ -95,26 +240,463 @@ pub fn delete_pipeline(conn: &Connection, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Returns the owner of a pipeline ID from its newest version: `None` if the
+/// ID was never saved, `Some(None)` if it was saved without an owner. IDs
+/// stay with their owner after the pipeline is deleted.
+pub fn pipeline_owner(conn: &Connection, id: &str) -> Result<Option<Option<String>>> {
+    conn.query_row(
+        "SELECT owner FROM pipeline_versions WHERE pipeline_id = ?1 ORDER BY version DESC LIMIT 1",
+        params![id],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to read pipeline owner")
+}
+
+/// Lists a pipeline's saved versions, newest first.
+pub fn list_pipeline_versions(conn: &Connection, id: &str) -> Result<Vec<PipelineVersionInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT version, name, description, created_at FROM pipeline_versions
+         WHERE pipeline_id = ?1 ORDER BY version DESC",
+    )?;
+    let versions = stmt
+        .query_map(params![id], |row| {
+            Ok(PipelineVersionInfo {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(versions)
+}
+
+/// Returns one saved version of a pipeline as a save request, or `None` if
+/// there is no such version.
+pub fn get_pipeline_version(conn: &Connection, id: &str, version: i64) -> Result<Option<SavePipelineRequest>> {
+    let row: Option<(String, String, String)> = conn
+        .query_row(
+            "SELECT name, description, config_json FROM pipeline_versions WHERE pipeline_id = ?1 AND version = ?2",
+            params![id, version],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .context("failed to read pipeline version")?;
+    let Some((name, description, config_json)) = row else { return Ok(None) };
+    let config: StoredConfig = serde_json::from_str(&config_json).context("failed to parse stored config")?;
+    Ok(Some(SavePipelineRequest {
+        id: id.to_string(),
+        name,
+        description,
+        nodes: config.nodes,
+        edges: config.edges,
+        layout: config.layout,
+        max_concurrency: config.max_concurrency,
+        residency: config.residency,
+        model_lifecycle: config.model_lifecycle,
+    }))
+}
+
+/// Returns the chunk count of an uploaded document, or `None` if it isn't stored.
+pub fn document_chunks(conn: &Connection, id: &str) -> Result<Option<usize>> {
+    let chunks = conn
+        .query_row("SELECT chunks FROM documents WHERE id = ?1", params![id], |row| row.get::<_, i64>(0))
+        .optional()
+        .context("failed to read document")?;
+    Ok(chunks.map(|c| c as usize))
+}
+
+/// Records an uploaded document and how many chunks it was indexed as.
+pub fn save_document(conn: &Connection, id: &str, title: &str, format: &str, chunks: usize) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO documents (id, title, format, chunks) VALUES (?1, ?2, ?3, ?4)",
+        params![id, title, format, chunks as i64],
+    ).context("failed to save document")?;
+    Ok(())
+}
+
+/// Deletes an uploaded document's record by ID.
+pub fn delete_document(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM documents WHERE id = ?1", params![id])
+        .context("failed to delete document")?;
+    Ok(())
+}
+
+/// Returns the tool definitions registered through POST /tools/register, oldest first.
+pub fn list_registered_tools(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT name, definition_json FROM registered_tools ORDER BY created_at, name")?;
+    let tools = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+    Ok(tools)
+}
+
+/// Returns whether a tool definition with this name was registered.
+pub fn has_registered_tool(conn: &Connection, name: &str) -> Result<bool> {
+    let found = conn
+        .query_row("SELECT 1 FROM registered_tools WHERE name = ?1", params![name], |_| Ok(()))
+        .optional()
+        .context("failed to read registered tool")?;
+    Ok(found.is_some())
+}
+
+/// Saves a tool definition (as JSON), replacing one with the same name.
+pub fn save_registered_tool(conn: &Connection, name: &str, definition_json: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO registered_tools (name, definition_json) VALUES (?1, ?2)",
+        params![name, definition_json],
+    ).context("failed to save registered tool")?;
+    Ok(())
+}
+
+const SESSION_COLUMNS: &str = "s.id, s.title, s.created_at, s.updated_at,
+    (SELECT COUNT(*) FROM session_messages m WHERE m.session_id = s.id)";
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<SessionInfo> {
+    Ok(SessionInfo {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        message_count: row.get::<_, i64>(4)? as usize,
+    })
+}
+
+/// Creates an empty session owned by `owner` unless one with this ID exists.
+/// Returns whether it was created.
+pub fn create_session(conn: &Connection, id: &str, title: &str, owner: Option<&str>) -> Result<bool> {
+    let created = conn
+        .execute("INSERT OR IGNORE INTO sessions (id, title, owner) VALUES (?1, ?2, ?3)", params![id, title, owner])
+        .context("failed to create session")?;
+    Ok(created > 0)
+}
+
+/// Lists the sessions owned by `owner` (all of them for `None`), most recently updated first.
+pub fn list_sessions(conn: &Connection, owner: Option<&str>) -> Result<Vec<SessionInfo>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sessions s WHERE ?1 IS NULL OR s.owner = ?1 ORDER BY s.updated_at DESC, s.id",
+        SESSION_COLUMNS
+    ))?;
+    let sessions = stmt.query_map(params![owner], session_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(sessions)
+}
+
+/// Returns a session by ID, or `None` if it doesn't exist or isn't owned by `owner`.
+pub fn get_session(conn: &Connection, id: &str, owner: Option<&str>) -> Result<Option<SessionInfo>> {
+    conn.query_row(
+        &format!("SELECT {} FROM sessions s WHERE s.id = ?1 AND (?2 IS NULL OR s.owner = ?2)", SESSION_COLUMNS),
+        params![id, owner],
+        session_from_row,
+    )
+    .optional()
+    .context("failed to read session")
+}
+
+/// Returns a session's messages, oldest first.
+pub fn session_messages(conn: &Connection, id: &str) -> Result<Vec<Message>> {
+    let mut stmt = conn.prepare("SELECT role, content FROM session_messages WHERE session_id = ?1 ORDER BY id")?;
+    let messages = stmt
+        .query_map(params![id], |row| {
+            let role: String = row.get(0)?;
+            let role = if role == "assistant" { MessageRole::Assistant } else { MessageRole::User };
+            Ok(Message { role, content: row.get(1)? })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(messages)
+}
+
+/// Appends messages to a session, titling it after the first user message if it has no title.
+pub fn append_session_messages(conn: &Connection, id: &str, messages: &[Message], title: &str) -> Result<()> {
+    let tx = conn.unchecked_transaction().context("failed to start transaction")?;
+    for message in messages {
+        tx.execute(
+            "INSERT INTO session_messages (session_id, role, content) VALUES (?1, ?2, ?3)",
+            params![id, message.role.as_str(), message.content],
+        ).context("failed to save session message")?;
+    }
+    tx.execute(
+        "UPDATE sessions SET updated_at = datetime('now'), title = CASE WHEN title = '' THEN ?2 ELSE title END WHERE id = ?1",
+        params![id, title],
+    ).context("failed to update session")?;
+    tx.commit().context("failed to save session messages")?;
+    Ok(())
+}
+
+/// Deletes a session owned by `owner` and its messages. Returns whether it existed.
+pub fn delete_session(conn: &Connection, id: &str, owner: Option<&str>) -> Result<bool> {
+    if get_session(conn, id, owner)?.is_none() {
+        return Ok(false);
+    }
+    conn.execute("DELETE FROM session_messages WHERE session_id = ?1", params![id])
+        .context("failed to delete session messages")?;
+    let deleted = conn.execute("DELETE FROM sessions WHERE id = ?1", params![id]).context("failed to delete session")?;
+    Ok(deleted > 0)
+}
+
+/// Queues a chat request (as JSON) to replay when providers recover. Returns its ID.
+pub fn enqueue_request(conn: &Connection, request_json: &str) -> Result<i64> {
+    conn.execute("INSERT INTO queued_requests (request_json) VALUES (?1)", params![request_json])
+        .context("failed to queue request")?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Removes and returns all queued requests, oldest first.
+pub fn take_queued_requests(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT id, request_json FROM queued_requests ORDER BY id")?;
+    let queued = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(i64, String)>>>()?;
+    if let Some((last_id, _)) = queued.last() {
+        conn.execute("DELETE FROM queued_requests WHERE id <= ?1", params![last_id])?;
+    }
+    Ok(queued)
+}
+
+const SCHEDULE_COLUMNS: &str =
+    "id, pipeline_id, cron, input, model_id, webhook_url, next_run_at, last_run_at, created_at, owner";
+
+fn schedule_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScheduleInfo> {
+    Ok(ScheduleInfo {
+        id: row.get(0)?,
+        pipeline_id: row.get(1)?,
+        cron: row.get(2)?,
+        input: row.get(3)?,
+        model_id: row.get(4)?,
+        webhook_url: row.get(5)?,
+        next_run_at: row.get(6)?,
+        last_run_at: row.get(7)?,
+        created_at: row.get(8)?,
+        owner: row.get(9)?,
+    })
+}
+
+/// Saves a schedule.
+pub fn save_schedule(conn: &Connection, schedule: &ScheduleInfo) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schedules (id, pipeline_id, cron, input, model_id, webhook_url, next_run_at, owner)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            schedule.id,
+            schedule.pipeline_id,
+            schedule.cron,
+            schedule.input,
+            schedule.model_id,
+            schedule.webhook_url,
+            schedule.next_run_at,
+            schedule.owner
+        ],
+    ).context("failed to save schedule")?;
+    Ok(())
+}
+
+/// Lists the schedules owned by `owner` (all of them for `None`), oldest first.
+pub fn list_schedules(conn: &Connection, owner: Option<&str>) -> Result<Vec<ScheduleInfo>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM schedules WHERE ?1 IS NULL OR owner = ?1 ORDER BY created_at, id",
+        SCHEDULE_COLUMNS
+    ))?;
+    let schedules = stmt.query_map(params![owner], schedule_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(schedules)
+}
+
+/// Returns a schedule by ID, or `None` if it doesn't exist or isn't owned by `owner`.
+pub fn get_schedule(conn: &Connection, id: &str, owner: Option<&str>) -> Result<Option<ScheduleInfo>> {
+    conn.query_row(
+        &format!("SELECT {} FROM schedules WHERE id = ?1 AND (?2 IS NULL OR owner = ?2)", SCHEDULE_COLUMNS),
+        params![id, owner],
+        schedule_from_row,
+    )
+    .optional()
+    .context("failed to read schedule")
+}
+
+/// Returns the schedules whose next run is at or before `now`.
+pub fn due_schedules(conn: &Connection, now: &str) -> Result<Vec<ScheduleInfo>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM schedules WHERE next_run_at <= ?1", SCHEDULE_COLUMNS))?;
+    let schedules = stmt.query_map(params![now], schedule_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(schedules)
+}
+
+/// Records that a schedule ran at `last_run_at` and moves it to its next run.
+pub fn set_schedule_run_times(conn: &Connection, id: &str, last_run_at: &str, next_run_at: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE schedules SET last_run_at = ?2, next_run_at = ?3 WHERE id = ?1",
+        params![id, last_run_at, next_run_at],
+    ).context("failed to update schedule")?;
+    Ok(())
+}
+
+/// Deletes a schedule owned by `owner` and its run history. Returns whether it existed.
+pub fn delete_schedule(conn: &Connection, id: &str, owner: Option<&str>) -> Result<bool> {
+    if get_schedule(conn, id, owner)?.is_none() {
+        return Ok(false);
+    }
+    conn.execute("DELETE FROM schedule_runs WHERE schedule_id = ?1", params![id])
+        .context("failed to delete schedule runs")?;
+    let deleted = conn.execute("DELETE FROM schedules WHERE id = ?1", params![id]).context("failed to delete schedule")?;
+    Ok(deleted > 0)
+}
+
+/// Records a scheduled run as started.
+pub fn start_schedule_run(conn: &Connection, run_id: &str, schedule_id: &str, pipeline_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schedule_runs (run_id, schedule_id, pipeline_id, status) VALUES (?1, ?2, ?3, 'running')",
+        params![run_id, schedule_id, pipeline_id],
+    ).context("failed to save schedule run")?;
+    Ok(())
+}
+
+/// Records a scheduled run's outcome and returns the stored run.
+pub fn finish_schedule_run(
+    conn: &Connection,
+    run_id: &str,
+    status: &str,
+    output: &str,
+    input_tokens: u32,
+    output_tokens: u32,
+) -> Result<Option<ScheduleRunInfo>> {
+    conn.execute(
+        "UPDATE schedule_runs SET status = ?2, output = ?3, input_tokens = ?4, output_tokens = ?5, finished_at = datetime('now')
+         WHERE run_id = ?1",
+        params![run_id, status, output, input_tokens, output_tokens],
+    ).context("failed to update schedule run")?;
+    conn.query_row(&format!("SELECT {} FROM schedule_runs WHERE run_id = ?1", SCHEDULE_RUN_COLUMNS), params![run_id], schedule_run_from_row)
+        .optional()
+        .context("failed to read schedule run")
+}
+
+/// Lists a schedule's most recent runs, newest first.
+pub fn list_schedule_runs(conn: &Connection, schedule_id: &str, limit: usize) -> Result<Vec<ScheduleRunInfo>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM schedule_runs WHERE schedule_id = ?1 ORDER BY started_at DESC, rowid DESC LIMIT ?2",
+        SCHEDULE_RUN_COLUMNS
+    ))?;
+    let runs = stmt
+        .query_map(params![schedule_id, limit as i64], schedule_run_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(runs)
+}
+
+const SCHEDULE_RUN_COLUMNS: &str =
+    "run_id, schedule_id, pipeline_id, status, output, input_tokens, output_tokens, started_at, finished_at";
+
+fn schedule_run_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScheduleRunInfo> {
+    Ok(ScheduleRunInfo {
+        run_id: row.get(0)?,
+        schedule_id: row.get(1)?,
+        pipeline_id: row.get(2)?,
+        status: row.get(3)?,
+        output: row.get(4)?,
+        input_tokens: row.get(5)?,
+        output_tokens: row.get(6)?,
+        started_at: row.get(7)?,
+        finished_at: row.get(8)?,
+    })
+}
+
+const KEY_POLICY_COLUMNS: &str = "key, models_json, daily_token_limit, daily_cost_limit_usd";
+
+fn key_policy_from_row(row: &rusqlite::Row) -> rusqlite::Result<(String, KeyPolicy)> {
+    let models_json: Option<String> = row.get(1)?;
+    let policy = KeyPolicy {
+        models: models_json.and_then(|json| serde_json::from_str(&json).ok()),
+        daily_token_limit: row.get(2)?,
+        daily_cost_limit_usd: row.get(3)?,
+    };
+    Ok((row.get(0)?, policy))
+}
+
+/// Returns a key's own policy, or `None` if it has none.
+pub fn get_key_policy(conn: &Connection, key: &str) -> Result<Option<KeyPolicy>> {
+    conn.query_row(
+        &format!("SELECT {} FROM key_policies WHERE key = ?1", KEY_POLICY_COLUMNS),
+        params![key],
+        key_policy_from_row,
+    )
+    .optional()
+    .map(|row| row.map(|(_, policy)| policy))
+    .context("failed to read key policy")
+}
+
+/// Lists every key's policy, by key.
+pub fn list_key_policies(conn: &Connection) -> Result<Vec<(String, KeyPolicy)>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM key_policies ORDER BY key", KEY_POLICY_COLUMNS))?;
+    let policies = stmt.query_map([], key_policy_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(policies)
+}
+
+/// Saves or replaces a key's policy.
+pub fn save_key_policy(conn: &Connection, key: &str, policy: &KeyPolicy) -> Result<()> {
+    let models_json = policy.models.as_ref().map(serde_json::to_string).transpose()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO key_policies (key, models_json, daily_token_limit, daily_cost_limit_usd, updated_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+        params![key, models_json, policy.daily_token_limit, policy.daily_cost_limit_usd],
+    ).context("failed to save key policy")?;
+    Ok(())
+}
+
+/// Deletes a key's policy. Returns whether it existed.
+pub fn delete_key_policy(conn: &Connection, key: &str) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM key_policies WHERE key = ?1", params![key])
+        .context("failed to delete key policy")?;
+    Ok(deleted > 0)
+}
+
+/// Returns a key's usage on `day` (`YYYY-MM-DD`).
+pub fn key_usage(conn: &Connection, key: &str, day: &str) -> Result<DailyUsage> {
+    let usage = conn
+        .query_row(
+            "SELECT input_tokens, output_tokens, cost_usd FROM key_usage WHERE key = ?1 AND day = ?2",
+            params![key, day],
+            |row| Ok(DailyUsage { input_tokens: row.get(0)?, output_tokens: row.get(1)?, cost_usd: row.get(2)? }),
+        )
+        .optional()
+        .context("failed to read key usage")?;
+    Ok(usage.unwrap_or_default())
+}
+
+/// Adds to a key's usage on `day`.
+pub fn add_key_usage(conn: &Connection, key: &str, day: &str, usage: &DailyUsage) -> Result<()> {
+    conn.execute(
+        "INSERT INTO key_usage (key, day, input_tokens, output_tokens, cost_usd) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (key, day) DO UPDATE SET
+            input_tokens = input_tokens + excluded.input_tokens,
+            output_tokens = output_tokens + excluded.output_tokens,
+            cost_usd = cost_usd + excluded.cost_usd",
+        params![key, day, usage.input_tokens, usage.output_tokens, usage.cost_usd],
+    ).context("failed to record key usage")?;
+    Ok(())
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct StoredConfig {
     nodes: Vec<NodeInfo>,
     edges: Vec<EdgeInfo>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     layout: Option<HashMap<String, Position>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    residency: Option<ResidencyPolicy>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    model_lifecycle: HashMap<String, ModelLifecycle>,
 }
 
 /// Example pipeline definition loaded from JSON.
+///
+/// Uses the preset schema (`PipelineConfig`), plus an optional editor layout.
 #[derive(serde::Deserialize)]
 struct ExamplePipeline {
-    id: String,
-    name: String,
-    description: String,
-    nodes: Vec<NodeInfo>,
-    edges: Vec<EdgeInfo>,
+    #[serde(flatten)]
+    config: PipelineConfig,
+    #[serde(default)]
+    layout: Option<HashMap<String, Position>>,
 }
 
 /// Seed example configs if the database is empty.
-/// Loads examples from examples.json file.
+/// Loads examples from examples.json file, written in the same schema as presets.
 pub fn seed_examples(conn: &Connection) -> Result<()> {
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM user_pipelines", [], |r| r.get(0))?;
     if count > 0 {
@@ -133,14 +715,27 @@ pub fn seed_examples(conn: &Connection) -> Result<()> {
 
     let example_count = examples.len();
     for ex in examples {
-        let config = StoredConfig { nodes: ex.nodes, edges: ex.edges, layout: None };
+        let info = pipeline_config_to_info(&ex.config, ex.layout);
+        let config = StoredConfig {
+            nodes: info.nodes,
+            edges: info.edges,
+            layout: info.layout,
+            max_concurrency: info.max_concurrency,
+            residency: info.residency,
+            model_lifecycle: info.model_lifecycle,
+        };
         let config_json = serde_json::to_string(&config)?;
 
         conn.execute(
             "INSERT INTO user_pipelines (id, name, description, config_json) VALUES (?1, ?2, ?3, ?4)",
-            params![ex.id, ex.name, ex.description, config_json],
+            params![info.id, info.name, info.description, config_json],
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO pipeline_versions (pipeline_id, version, name, description, config_json)
+             VALUES (?1, 1, ?2, ?3, ?4)",
+            params![info.id, info.name, info.description, config_json],
         )?;
-        info!("  Seeded: {}", ex.name);
+        info!("  Seeded: {}", info.name);
     }
 
     info!("Seeded {} example configs", example_count);
@@ -3,9 +3,14 @@
 use std::collections::HashMap;
 use std::fmt;
 
-use fissio_core::ModelConfig;
+use fissio_config::{FailurePolicy, ModelLifecycle, PipelineConfig, ResidencyPolicy};
+use fissio_core::{Message, ModelConfig};
+use fissio_eval::EvalSuite;
+use fissio_tools::{HttpToolSpec, McpTransport, ToolMetadata};
 use serde::{Deserialize, Serialize};
 
+use crate::services::model_health::ModelHealth;
+
 // === Model Management Types ===
 
 /// Response from model warmup.
@@ -19,12 +24,61 @@ pub struct WakeResponse {
 #[derive(Debug, Serialize)]
 pub struct UnloadResponse {
     pub success: bool,
+    /// The model is in use by a pipeline run and will be unloaded when the run finishes.
+    pub deferred: bool,
+}
+
+/// Latest health check of every registered model.
+#[derive(Debug, Serialize)]
+pub struct ModelHealthResponse {
+    /// Consecutive failed checks after which a model is unhealthy.
+    pub failure_threshold: u32,
+    pub models: HashMap<String, ModelHealth>,
+}
+
+/// The local models the server has loaded and the runs using them.
+#[derive(Debug, Serialize)]
+pub struct ModelPoolResponse {
+    /// GPU memory budget from `OLLAMA_VRAM_GB`, if set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vram_budget_bytes: Option<u64>,
+    /// Memory the loaded models take, as far as their sizes are known.
+    pub loaded_bytes: u64,
+    pub models: Vec<PooledModelInfo>,
+}
+
+/// A local model in the pool.
+#[derive(Debug, Serialize)]
+pub struct PooledModelInfo {
+    pub id: String,
+    pub name: String,
+    pub loaded: bool,
+    /// Pipeline runs using the model now.
+    pub runs: usize,
+    /// Seconds since the model was last used, if it has been.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+/// A change to the model catalog after Ollama discovery was re-run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelsUpdated {
+    /// IDs of models that appeared.
+    pub added: Vec<String>,
+    /// IDs of models that are gone.
+    pub removed: Vec<String>,
+    /// The full catalog after the change.
+    pub models: Vec<ModelConfig>,
 }
 
 // === Runtime Pipeline Config Types ===
 
 /// Runtime node configuration from the frontend.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeNodeConfig {
     pub id: String,
     #[serde(rename = "type")]
@@ -32,22 +86,32 @@ pub struct RuntimeNodeConfig {
     #[serde(default)]
     pub model: Option<String>,
     #[serde(default)]
+    pub fallback_models: Vec<String>,
+    #[serde(default)]
     pub prompt: Option<String>,
     #[serde(default)]
     pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
 }
 
 /// Runtime edge configuration from the frontend.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeEdgeConfig {
     pub from: serde_json::Value,
     pub to: serde_json::Value,
     #[serde(default)]
     pub edge_type: Option<String>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    #[serde(default)]
+    pub on_failure: Option<FailurePolicy>,
+    #[serde(default)]
+    pub default_output: Option<String>,
 }
 
 /// Complete runtime pipeline configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimePipelineConfig {
     #[serde(default)]
     pub id: Option<String>,
@@ -55,6 +119,12 @@ pub struct RuntimePipelineConfig {
     pub name: Option<String>,
     pub nodes: Vec<RuntimeNodeConfig>,
     pub edges: Vec<RuntimeEdgeConfig>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    #[serde(default)]
+    pub residency: Option<ResidencyPolicy>,
+    #[serde(default)]
+    pub model_lifecycle: HashMap<String, ModelLifecycle>,
 }
 
 // === Pipeline Info Types ===
@@ -65,9 +135,13 @@ pub struct NodeInfo {
     pub id: String,
     pub node_type: String,
     pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_models: Vec<String>,
     pub prompt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -81,6 +155,12 @@ pub struct EdgeInfo {
     pub to: serde_json::Value,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub edge_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<FailurePolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_output: Option<String>,
 }
 
 /// Position for layout storage.
@@ -100,6 +180,15 @@ pub struct PipelineInfo {
     pub edges: Vec<EdgeInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<HashMap<String, Position>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residency: Option<ResidencyPolicy>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_lifecycle: HashMap<String, ModelLifecycle>,
+    /// Tenant that saved the pipeline; unowned pipelines are shared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
 // === Pipeline CRUD Types ===
@@ -115,6 +204,12 @@ pub struct SavePipelineRequest {
     pub edges: Vec<EdgeInfo>,
     #[serde(default)]
     pub layout: Option<HashMap<String, Position>>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    #[serde(default)]
+    pub residency: Option<ResidencyPolicy>,
+    #[serde(default)]
+    pub model_lifecycle: HashMap<String, ModelLifecycle>,
 }
 
 /// Response from saving a pipeline.
@@ -122,6 +217,17 @@ pub struct SavePipelineRequest {
 pub struct SavePipelineResponse {
     pub success: bool,
     pub id: String,
+    /// Version the save created.
+    pub version: i64,
+}
+
+/// A saved version of a pipeline, without its config.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineVersionInfo {
+    pub version: i64,
+    pub name: String,
+    pub description: String,
+    pub created_at: String,
 }
 
 /// Request to delete a pipeline.
@@ -130,15 +236,260 @@ pub struct DeletePipelineRequest {
     pub id: String,
 }
 
+/// A pipeline exported with what it needs to run on another server.
+///
+/// The config's fields sit at the top level, so a bundle also loads as a
+/// preset file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PipelineBundle {
+    #[serde(flatten)]
+    pub pipeline: PipelineConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<HashMap<String, Position>>,
+    /// Model IDs the nodes name, fallbacks included.
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Tool selections the nodes list, e.g. `web_search` or `memory/*`.
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+/// Response from importing a pipeline bundle.
+#[derive(Debug, Serialize)]
+pub struct ImportPipelineResponse {
+    pub success: bool,
+    pub id: String,
+    pub version: i64,
+    /// Models and tools the pipeline needs that this server doesn't have.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Request to run a test suite against a pipeline.
+#[derive(Debug, Deserialize)]
+pub struct EvaluatePipelineRequest {
+    pub suite: EvalSuite,
+    /// Model for nodes that don't pin one; defaults to the first configured model.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub node_models: HashMap<String, String>,
+    /// Model that grades `judge` rubrics; defaults to `model_id`'s.
+    #[serde(default)]
+    pub judge_model_id: Option<String>,
+}
+
+/// Request to run one input through two pipeline variants side by side.
+#[derive(Debug, Deserialize)]
+pub struct ComparePipelinesRequest {
+    pub input: String,
+    pub a: CompareVariant,
+    pub b: CompareVariant,
+    /// Model that picks the better answer; no verdict without one.
+    #[serde(default)]
+    pub judge_model_id: Option<String>,
+    /// What the judge should prefer, e.g. "cites a source".
+    #[serde(default)]
+    pub criteria: Option<String>,
+}
+
+/// One side of a comparison: a saved or preset pipeline by ID, or an
+/// unsaved config, with the models to run it on.
+#[derive(Debug, Deserialize)]
+pub struct CompareVariant {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+    /// Takes precedence over `pipeline_id`.
+    #[serde(default)]
+    pub pipeline_config: Option<RuntimePipelineConfig>,
+    /// Model for nodes that don't pin one; defaults to the first configured model.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub node_models: HashMap<String, String>,
+}
+
+// === Session Types ===
+
+/// A stored conversation, without its messages.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    /// Set on creation, or taken from the first message.
+    pub title: String,
+    pub message_count: usize,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A session with its messages, oldest first.
+#[derive(Debug, Serialize)]
+pub struct SessionDetail {
+    #[serde(flatten)]
+    pub session: SessionInfo,
+    pub messages: Vec<Message>,
+}
+
+/// Request to create a session.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateSessionRequest {
+    /// Defaults to a new UUID.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Request to create a schedule.
+#[derive(Debug, Deserialize)]
+pub struct CreateScheduleRequest {
+    pub pipeline_id: String,
+    /// Five-field cron expression (minute hour day month weekday), in UTC.
+    pub cron: String,
+    /// Message the pipeline runs on; `{{date}}` and `{{datetime}}` are filled in per run.
+    pub input: String,
+    /// Model for nodes that don't pin one; defaults to the first configured model.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// URL each run's result is POSTed to.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+/// A recurring pipeline run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleInfo {
+    pub id: String,
+    pub pipeline_id: String,
+    pub cron: String,
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// UTC, as `YYYY-MM-DD HH:MM:SS`.
+    pub next_run_at: String,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+    /// Tenant that created the schedule; its runs are owned by the same tenant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// One run of a schedule; also the body POSTed to its webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRunInfo {
+    /// Also the chat run ID, so its events can be replayed from `/chat/runs/{run_id}/events`.
+    pub run_id: String,
+    pub schedule_id: String,
+    pub pipeline_id: String,
+    /// `running`, `completed`, or `failed`.
+    pub status: String,
+    pub output: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+// === Usage Policy Types ===
+
+/// Models an API key may use and its daily quotas; also the body of
+/// `PUT /admin/quotas/{key}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyPolicy {
+    /// Model IDs the key may use; every model when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<String>>,
+    /// Input plus output tokens per UTC day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_token_limit: Option<u64>,
+    /// Estimated spend per UTC day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_cost_limit_usd: Option<f64>,
+}
+
+/// A key's usage so far today (UTC).
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct DailyUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl DailyUsage {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// A key's policy with today's usage.
+#[derive(Debug, Serialize)]
+pub struct KeyQuotaInfo {
+    pub key: String,
+    pub policy: KeyPolicy,
+    /// True when the key has no policy of its own and the `*` policy applies.
+    pub inherited: bool,
+    pub usage: DailyUsage,
+}
+
+/// Tool schema for API responses.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+    /// Namespace, tags, cost hint, and approval requirement, for the editor.
+    pub metadata: ToolMetadata,
+}
+
+/// A tool definition posted to /tools/register.
+///
+/// ```json
+/// { "type": "http", "spec": { "name": "get_weather", "url": "https://api.example.com/weather?city={city}" } }
+/// { "type": "mcp", "name": "docs", "server": { "url": "http://localhost:8000/sse" } }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolDefinition {
+    /// A tool declared as an HTTP request.
+    Http {
+        spec: HttpToolSpec,
+        #[serde(default)]
+        metadata: Option<ToolMetadata>,
+    },
+    /// An MCP server; each of its tools is registered as `<name>.<tool>`.
+    Mcp {
+        name: String,
+        server: McpTransport,
+        /// Applied to every tool the server offers.
+        #[serde(default)]
+        metadata: Option<ToolMetadata>,
+    },
+}
+
+impl ToolDefinition {
+    /// The name the definition is stored under: the tool's or the MCP server's.
+    pub fn name(&self) -> &str {
+        match self {
+            ToolDefinition::Http { spec, .. } => &spec.name,
+            ToolDefinition::Mcp { name, .. } => name,
+        }
+    }
+}
+
 /// Response sent on WebSocket connection init.
 #[derive(Debug, Serialize)]
 pub struct InitResponse {
     pub models: Vec<ModelConfig>,
     pub templates: Vec<PipelineInfo>,
     pub configs: Vec<PipelineInfo>,
+    pub tools: Vec<ToolInfo>,
 }
 
-/// Metadata about an LLM response (timing, tokens).
+/// Metadata about an LLM response (timing, tokens, estimated cost).
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct WsMetadata {
     pub input_tokens: u32,
@@ -152,6 +503,8 @@ pub struct WsMetadata {
     pub eval_ms: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tokens_per_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
 }
 
 impl fmt::Display for WsMetadata {
@@ -160,6 +513,9 @@ impl fmt::Display for WsMetadata {
         if let Some(tps) = self.tokens_per_sec {
             write!(f, ", {:.1} tok/s", tps)?;
         }
+        if let Some(cost) = self.estimated_cost_usd {
+            write!(f, ", ${:.4}", cost)?;
+        }
         Ok(())
     }
 }
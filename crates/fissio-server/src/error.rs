@@ -1,17 +1,31 @@
 //! Application error types and Axum response conversion.
 
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
+use std::fmt;
+
+use crate::services::policy::PolicyViolation;
 
 /// Application-level errors with HTTP status code mapping.
 #[derive(Debug)]
 pub enum AppError {
     Internal(String),
     NotFound(String),
+    BadRequest(String),
+    /// A feature the request needs isn't configured on this server.
+    Unavailable(String),
+    /// The request has no valid API key or token.
+    Unauthorized(String),
+    /// The request's key or token lacks a scope the route needs.
+    Forbidden(String),
+    /// The request clashes with an earlier one, such as a reused idempotency key.
+    Conflict(String),
+    /// The key's usage policy refused the request.
+    Policy(PolicyViolation),
 }
 
 impl AppError {
@@ -21,6 +35,21 @@ impl AppError {
     }
 }
 
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Internal(msg)
+            | AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Unavailable(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg) => f.write_str(msg),
+            AppError::Policy(violation) => f.write_str(&violation.message()),
+        }
+    }
+}
+
 impl From<rusqlite::Error> for AppError {
     fn from(e: rusqlite::Error) -> Self {
         AppError::Internal(e.to_string())
@@ -44,11 +73,36 @@ struct ErrorResponse {
     error: String,
 }
 
+/// Error body for a policy refusal: the message plus the violation's `code` and details.
+#[derive(Serialize)]
+struct PolicyErrorResponse {
+    error: String,
+    #[serde(flatten)]
+    violation: PolicyViolation,
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::Unauthorized(msg) => {
+                let body = Json(ErrorResponse { error: msg });
+                return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response();
+            }
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::Policy(violation) => {
+                let status = violation.status();
+                let retry_after = violation.retry_after();
+                let mut res = (status, Json(PolicyErrorResponse { error: violation.message(), violation })).into_response();
+                if let Some(seconds) = retry_after {
+                    res.headers_mut().insert(header::RETRY_AFTER, seconds.into());
+                }
+                return res;
+            }
         };
         (status, Json(ErrorResponse { error: message })).into_response()
     }
@@ -1,32 +1,90 @@
 //! SSE-based chat streaming handler.
+//!
+//! Every run starts with a `run` event carrying its ID, and each event is
+//! appended to the shared event log with a sequence number sent as the SSE
+//! event ID. A client that loses its connection can resume from any replica
+//! via `GET /chat/runs/{run_id}/events` with `Last-Event-ID`.
+//!
+//! When the final node runs tools, each call is bracketed by
+//! `tool_call_started` and `tool_call_finished` events with short previews of
+//! its arguments and result. A tool call that needs approval emits an
+//! `approval` event; the client answers it with `POST /chat/approvals/{approval_id}`.
+//!
+//! A request with an `idempotency_key` (or `Idempotency-Key` header) runs at
+//! most once per tenant: a retry with the same key, while the run is going or
+//! after it ended, gets the first run's events replayed instead of a new run.
+//!
+//! A request with a `session_id` uses the session's stored history and
+//! appends the message and response to it, so clients don't resend history.
+//! Pipeline runs remember facts across conversations in the scope of the
+//! request's `user_id`, or of its session when no user is given.
+//!
+//! With authentication on, runs, sessions, memory, and traces belong to the
+//! caller's tenant: another tenant can't resume a run or use a session. The
+//! caller's usage policy is checked before the stream opens, and the run's
+//! tokens count toward its daily quota.
+//!
+//! A voice message is sent as a `multipart/form-data` body with the recording
+//! in an `audio` part and the rest of the request as JSON in a `request` part.
+//! The recording is transcribed before the run starts, the transcript becomes
+//! the message (after any `message` in the request), and a `transcript` event
+//! follows the `run` event.
+//!
+//! A request with `audio_output` also gets its answer spoken: an `audio` event
+//! before `end` carries the URL of the clip, served from `GET /audio/{id}`.
+//!
+//! Reasoning that a model streams before its answer arrives in `thinking`
+//! events, separate from the answer's `stream` chunks.
+//!
+//! A run that fails sends an `error` event before `end`, with a `code`, a
+//! short `message`, the provider's `detail`, and the `node_id` of the pipeline
+//! node that failed. Text streamed before a mid-stream failure stays valid.
+//!
+//! When the answer cites retrieved sources, a `citation` event follows the
+//! `stream` chunk that completes each `[n]` marker, with the marker's character
+//! offset in the full response and the cited chunk's ID, title, URL, and text.
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use axum::{
-    extract::State,
+    body::Body,
+    extract::{Extension, FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, HeaderMap},
     response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     Json,
 };
-use fissio_core::Message as CoreMessage;
-use fissio_engine::EngineOutput;
-use fissio_monitor::{MetricsCollector, NodeMetrics, TracingCollector};
+use fissio_core::{AgentError, Citation, Message as CoreMessage};
+use async_trait::async_trait;
+use fissio_engine::{ApprovalRequest, ApprovalResponse, EmptyReason, EngineOutput, ToolApprover};
+use fissio_llm::FittedContext;
+use fissio_monitor::{MetricsCollector, NodeMetrics, TraceStatus, TracingCollector};
+use fissio_tools::MAX_AUDIO_BYTES;
 use futures::stream::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::auth::{Principal, Tenant};
 use crate::dto::{RuntimePipelineConfig, WsMetadata};
 use crate::services::chat::{
     build_metadata, execute_direct_chat, execute_ollama_stream,
-    execute_pipeline, runtime_to_pipeline_config, PipelineResult, StreamResult,
+    execute_pipeline, runtime_to_pipeline_config, PipelineResult, PipelineRunOptions, StreamResult,
 };
+use crate::error::AppError;
+use crate::services::approval::ApprovalBroker;
+use crate::services::degraded::DegradedMode;
+use crate::services::policy;
+use crate::services::sessions;
+use crate::services::speech;
+use crate::shared::{EventLog, RunEvent, IDEMPOTENCY_TTL, RUN_EVENT_TTL};
 use crate::ServerState;
 
 /// Request body for chat endpoint.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatRequest {
     pub message: String,
     #[serde(default)]
@@ -41,62 +99,529 @@ pub struct ChatRequest {
     pub pipeline_config: Option<RuntimePipelineConfig>,
     #[serde(default)]
     pub system_prompt: Option<String>,
+    /// Takes the history from this session instead of `history`, and saves the
+    /// turn to it. An unknown ID starts a new session.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Scopes the pipeline's long-term memory to this user across sessions.
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Speaks the final answer and sends its URL in an `audio` event.
+    #[serde(default)]
+    pub audio_output: bool,
+    /// Client-chosen key identifying this request across retries; a request
+    /// whose key already started a run replays that run's events.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Tenant the request runs for; always set by the server from the
+    /// caller's credentials, and kept so queued requests replay as it.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+impl ChatRequest {
+    fn tenant(&self) -> Tenant {
+        Tenant(self.tenant.clone())
+    }
+
+    /// Scope of the pipeline's long-term memory: the user, else the session,
+    /// within the tenant.
+    fn memory_scope(&self) -> Option<String> {
+        let scope = self.user_id.as_deref().or(self.session_id.as_deref())?;
+        Some(self.tenant().scope(scope))
+    }
 }
 
 /// SSE event data types.
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 enum SseData {
+    #[serde(rename = "run")]
+    Run { run_id: String },
+    #[serde(rename = "transcript")]
+    Transcript { text: String },
     #[serde(rename = "stream")]
     Stream { content: String },
+    #[serde(rename = "thinking")]
+    Thinking { content: String },
+    #[serde(rename = "progress")]
+    Progress { message: String },
+    #[serde(rename = "tool_call_started")]
+    ToolCallStarted { call_id: String, name: String, args_preview: String },
+    #[serde(rename = "tool_call_finished")]
+    ToolCallFinished { call_id: String, name: String, duration_ms: u64, result_preview: String },
+    #[serde(rename = "approval")]
+    Approval { approval_id: String, node_id: String, tool: String, arguments: serde_json::Value },
+    #[serde(rename = "citation")]
+    Citation(Citation),
+    #[serde(rename = "warning")]
+    Warning { status: &'static str, reason: EmptyReason, message: String },
+    #[serde(rename = "error")]
+    Error {
+        code: &'static str,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        node_id: Option<String>,
+    },
+    #[serde(rename = "audio")]
+    Audio { url: String, mime_type: &'static str },
     #[serde(rename = "end")]
     End { metadata: WsMetadata },
 }
 
+impl SseData {
+    /// SSE event name, matching the `type` tag.
+    fn name(&self) -> &'static str {
+        match self {
+            SseData::Run { .. } => "run",
+            SseData::Transcript { .. } => "transcript",
+            SseData::Stream { .. } => "stream",
+            SseData::Thinking { .. } => "thinking",
+            SseData::Progress { .. } => "progress",
+            SseData::ToolCallStarted { .. } => "tool_call_started",
+            SseData::ToolCallFinished { .. } => "tool_call_finished",
+            SseData::Approval { .. } => "approval",
+            SseData::Citation(_) => "citation",
+            SseData::Warning { .. } => "warning",
+            SseData::Error { .. } => "error",
+            SseData::Audio { .. } => "audio",
+            SseData::End { .. } => "end",
+        }
+    }
+}
+
 const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant.";
 
-type EventSender = mpsc::Sender<Result<Event, std::convert::Infallible>>;
+/// Message of the `error` event for a failed model call or pipeline node.
+const GENERATION_FAILED: &str = "Error generating response.";
+
+/// Largest POST /chat body: a voice message plus room for the request part.
+pub const MAX_CHAT_BYTES: usize = MAX_AUDIO_BYTES + 1024 * 1024;
+
+/// Longest idempotency key accepted.
+const MAX_IDEMPOTENCY_KEY_CHARS: usize = 255;
+
+/// How often a resumed stream polls the event log for new events.
+const RESUME_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+type SseSender = mpsc::Sender<Result<Event, std::convert::Infallible>>;
+
+/// Sends a run's events to its client and appends them to the event log
+/// under the run's tenant-scoped key.
+#[derive(Clone)]
+struct EventSender {
+    tx: SseSender,
+    run: Option<(String, Arc<dyn EventLog>)>,
+}
+
+impl EventSender {
+    async fn send(&self, data: &SseData) {
+        let Ok(json) = serde_json::to_string(data) else {
+            return;
+        };
+        let mut event = Event::default().event(data.name()).data(&json);
+        if let Some((run_id, log)) = &self.run {
+            match log.append(run_id, data.name(), &json).await {
+                Ok(seq) => event = event.id(seq.to_string()),
+                Err(e) => warn!("Failed to log event for run {}: {}", run_id, e),
+            }
+        }
+        // The run keeps going if the client disconnects; it can resume from the log.
+        let _ = self.tx.send(Ok(event)).await;
+    }
+}
+
+/// Asks the run's client to approve tool calls through `approval` events.
+struct SseApprover {
+    tx: EventSender,
+    broker: Arc<ApprovalBroker>,
+}
+
+#[async_trait]
+impl ToolApprover for SseApprover {
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalResponse {
+        let (approval_id, rx) = self.broker.register();
+        info!("Awaiting approval {} for {} in {}", approval_id, request.tool, request.node_id);
+        self.tx
+            .send(&SseData::Approval {
+                approval_id: approval_id.clone(),
+                node_id: request.node_id.clone(),
+                tool: request.tool.clone(),
+                arguments: request.arguments.clone(),
+            })
+            .await;
+        self.broker.wait(&approval_id, rx).await
+    }
+}
+
+/// Request body answering an `approval` event.
+#[derive(Debug, Deserialize)]
+pub struct ApprovalAnswer {
+    pub approved: bool,
+    /// Passed to the LLM when denied.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Approves or denies a tool call waiting in a chat run.
+pub async fn approve(
+    State(state): State<Arc<ServerState>>,
+    Path(approval_id): Path<String>,
+    Json(answer): Json<ApprovalAnswer>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let response = match answer.approved {
+        true => ApprovalResponse::Approved,
+        false => ApprovalResponse::Denied(answer.reason.unwrap_or_else(|| "the user denied this call".into())),
+    };
+    if !state.approvals.resolve(&approval_id, response) {
+        return Err(AppError::NotFound(format!("approval {} not found", approval_id)));
+    }
+    info!("Approval {} answered: {}", approval_id, if answer.approved { "approved" } else { "denied" });
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// A recording uploaded with a voice message.
+pub struct AudioUpload {
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A chat request sent as JSON, or as a multipart form with a voice message.
+pub struct ChatInput {
+    pub request: ChatRequest,
+    pub audio: Option<AudioUpload>,
+}
+
+impl<S: Send + Sync> FromRequest<S> for ChatInput {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let multipart = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+        if !multipart {
+            let Json(request) = Json::<ChatRequest>::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+            return Ok(Self { request, audio: None });
+        }
+
+        let bad_request = |message: String| AppError::BadRequest(message).into_response();
+        let mut form = Multipart::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+        let mut fields = serde_json::Map::new();
+        let mut audio = None;
+        while let Some(field) = form.next_field().await.map_err(IntoResponse::into_response)? {
+            match field.name() {
+                Some("request") => {
+                    let text = field.text().await.map_err(IntoResponse::into_response)?;
+                    fields = serde_json::from_str(&text).map_err(|e| bad_request(format!("invalid request part: {}", e)))?;
+                }
+                Some("audio") => {
+                    let file_name = field.file_name().unwrap_or("audio.webm").to_string();
+                    let bytes = field.bytes().await.map_err(IntoResponse::into_response)?.to_vec();
+                    audio = Some(AudioUpload { file_name, bytes });
+                }
+                _ => {}
+            }
+        }
+        if audio.is_none() {
+            return Err(bad_request("multipart chat requests need an 'audio' part".into()));
+        }
+        fields.entry("message").or_insert_with(|| "".into());
+        let request = serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| bad_request(format!("invalid request part: {}", e)))?;
+        Ok(Self { request, audio })
+    }
+}
+
+/// Transcribes a voice message and adds the transcript to the request's message.
+async fn transcribe(state: &ServerState, req: &mut ChatRequest, audio: AudioUpload) -> Result<String, AppError> {
+    let transcriber = state.transcriber.as_ref().ok_or_else(|| {
+        AppError::Unavailable("voice messages are disabled; set WHISPER_CPP_URL or OPENAI_API_KEY".into())
+    })?;
+    let text = transcriber
+        .transcribe(audio.bytes, &audio.file_name, None)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if text.is_empty() {
+        return Err(AppError::BadRequest("no speech found in the recording".into()));
+    }
+    info!("Transcribed voice message {} ({} chars)", audio.file_name, text.len());
+    req.message = match req.message.trim() {
+        "" => text.clone(),
+        typed => format!("{}\n\n{}", typed, text),
+    };
+    Ok(text)
+}
+
+/// The request's idempotency key, from the body or the `Idempotency-Key` header.
+fn idempotency_key(req: &ChatRequest, headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let header = headers.get("idempotency-key").and_then(|v| v.to_str().ok());
+    let Some(key) = req.idempotency_key.as_deref().or(header).map(str::trim) else {
+        return Ok(None);
+    };
+    if key.is_empty() || key.chars().count() > MAX_IDEMPOTENCY_KEY_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "idempotency_key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_CHARS
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Claims the request's idempotency key for `run_id`, returning the run it
+/// already started, if any.
+async fn claim_run(state: &ServerState, tenant: &Tenant, key: &str, run_id: &str) -> Result<Option<String>, AppError> {
+    let scoped = tenant.scope(&format!("idempotency/{}", key));
+    state.idempotency_keys.claim(&scoped, run_id).await.map_err(AppError::internal)
+}
 
 /// SSE chat streaming endpoint.
 pub async fn chat(
     State(state): State<Arc<ServerState>>,
-    Json(req): Json<ChatRequest>,
-) -> Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>> {
+    tenant: Tenant,
+    principal: Option<Extension<Principal>>,
+    headers: HeaderMap,
+    input: ChatInput,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
+    let ChatInput { request: mut req, audio } = input;
+    req.tenant = tenant.0.clone();
+    req.idempotency_key = idempotency_key(&req, &headers)?;
     let model_id = req.model_id.as_deref().unwrap_or("");
     let model = state.get_model(model_id);
+    if let Some(Extension(principal)) = &principal {
+        policy::check(&state, principal, &request_models(&state, &tenant, &req, &model.id).await)?;
+    }
+    if req.audio_output && state.speech.is_none() {
+        return Err(AppError::Unavailable("audio output is disabled; set OPENAI_API_KEY or TTS_API_BASE".into()));
+    }
+    let transcript = match audio {
+        Some(audio) => Some(transcribe(&state, &mut req, audio).await?),
+        None => None,
+    };
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel::<Result<Event, std::convert::Infallible>>(100);
+    let tx = EventSender { tx, run: Some((tenant.scope(&run_id), state.event_log.clone())) };
+    // Logged before the idempotency key is claimed, so a retry that finds the claim finds the run.
+    tx.send(&SseData::Run { run_id: run_id.clone() }).await;
+
+    if let Some(key) = &req.idempotency_key {
+        if let Some(existing) = claim_run(&state, &tenant, key, &run_id).await? {
+            info!("Replaying run {} for idempotency key {}", existing, key);
+            let key = tenant.scope(&existing);
+            let first = state.event_log.read_from(&key, 0).await.map_err(AppError::internal)?.ok_or_else(|| {
+                AppError::Conflict(format!("idempotency key already ran as run {}, whose events have expired", existing))
+            })?;
+            let rx = follow_run(state, key, existing, 0, first);
+            return Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()));
+        }
+    }
 
     info!(
-        "Chat request (model: {}): {}...",
+        "Chat request {} (model: {}): {}...",
+        run_id,
         model.name,
         req.message.get(..50).unwrap_or(&req.message)
     );
 
-    let (tx, rx) = mpsc::channel::<Result<Event, std::convert::Infallible>>(100);
-
     tokio::spawn(async move {
+        if let Some(text) = transcript {
+            tx.send(&SseData::Transcript { text }).await;
+        }
+
         let start = Instant::now();
-        let result = execute_chat(&tx, &req, &state).await;
+        let result = execute_turn(&tx, &req, &state).await;
+        if let Some(Extension(principal)) = &principal {
+            let (input, output) = (result.input_tokens as u64, result.output_tokens as u64);
+            policy::record(&state, principal, input, output, result.estimated_cost_usd);
+        }
+        if req.audio_output && !result.response.is_empty() {
+            match speech::speak(&state, &tenant, &result.response).await {
+                Ok((id, format)) => {
+                    let url = format!("/audio/{}", id);
+                    tx.send(&SseData::Audio { url, mime_type: format.mime_type() }).await;
+                }
+                Err(e) => error!("Failed to synthesize the answer to run {}: {}", run_id, e),
+            }
+        }
         let metadata = build_metadata(&result, start.elapsed().as_millis() as u64);
 
-        let end_data = SseData::End { metadata };
-        if let Ok(event) = Event::default().event("end").json_data(&end_data) {
-            let _ = tx.send(Ok(event)).await;
+        tx.send(&SseData::End { metadata }).await;
+        // A retry with the same key replays the run for as long as the key lasts
+        if req.idempotency_key.is_some() {
+            if let Err(e) = state.event_log.keep_for(&tenant.scope(&run_id), IDEMPOTENCY_TTL).await {
+                warn!("Failed to keep events of run {}: {}", run_id, e);
+            }
+        }
+    });
+
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
+
+/// The models a request may run on: its pipeline's, including the pipelines
+/// it nests, or the model it chats with directly.
+async fn request_models(state: &ServerState, tenant: &Tenant, req: &ChatRequest, default_model: &str) -> Vec<String> {
+    let config = match (&req.pipeline_config, req.pipeline_id.as_deref()) {
+        (Some(runtime), _) => Some(runtime_to_pipeline_config(runtime)),
+        (None, Some(id)) => state.presets.read().await.get(id).cloned(),
+        (None, None) => None,
+    };
+    match config {
+        Some(config) => {
+            let pipelines = state.pipeline_catalog(tenant).await;
+            policy::pipeline_models(&config, default_model, &req.node_models, &pipelines)
+        }
+        None => vec![default_model.to_string()],
+    }
+}
+
+/// Serves a spoken answer from an `audio` event.
+pub async fn audio(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    let clip = state
+        .audio_clips
+        .get(&tenant, &id)
+        .ok_or_else(|| AppError::NotFound(format!("audio {} not found", id)))?;
+    Ok(([(header::CONTENT_TYPE, clip.format.mime_type())], Body::from(clip.bytes)).into_response())
+}
+
+/// Query parameters for resuming a run's stream.
+#[derive(Debug, Deserialize)]
+pub struct ResumeQuery {
+    /// Last event ID received; alternative to the `Last-Event-ID` header.
+    #[serde(default)]
+    pub after: Option<u64>,
+}
+
+/// Resumes a chat run's stream after the last event the client received.
+///
+/// Replays logged events, then follows the log until the run's `end` event.
+pub async fn resume(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(run_id): Path<String>,
+    Query(query): Query<ResumeQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
+    let after = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .or(query.after);
+    let next = after.map_or(0, |seq| seq + 1);
+    let key = tenant.scope(&run_id);
+
+    let first = state
+        .event_log
+        .read_from(&key, next)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound(format!("run {} not found", run_id)))?;
+    info!("Resuming run {} from event {}", run_id, next);
+
+    let rx = follow_run(state, key, run_id, next, first);
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
+
+/// Sends a run's logged events, `first` being those from `next` on, then
+/// follows the log under its scoped `key` until the run's `end` event.
+fn follow_run(
+    state: Arc<ServerState>,
+    key: String,
+    run_id: String,
+    mut next: u64,
+    first: Vec<RunEvent>,
+) -> mpsc::Receiver<Result<Event, std::convert::Infallible>> {
+    let (tx, rx) = mpsc::channel::<Result<Event, std::convert::Infallible>>(100);
+
+    tokio::spawn(async move {
+        let mut events = first;
+        let mut last_progress = Instant::now();
+        loop {
+            let mut finished = false;
+            if !events.is_empty() {
+                last_progress = Instant::now();
+            }
+            for event in events {
+                next = event.seq + 1;
+                finished |= event.event == "end";
+                if tx.send(Ok(to_sse(event))).await.is_err() {
+                    return;
+                }
+            }
+            if finished || last_progress.elapsed() > RUN_EVENT_TTL {
+                return;
+            }
+
+            tokio::time::sleep(RESUME_POLL_INTERVAL).await;
+            events = match state.event_log.read_from(&key, next).await {
+                Ok(Some(events)) => events,
+                Ok(None) => return,
+                Err(e) => {
+                    error!("Failed to read events for run {}: {}", run_id, e);
+                    return;
+                }
+            };
         }
     });
 
-    Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default())
+    rx
+}
+
+fn to_sse(event: RunEvent) -> Event {
+    Event::default().event(event.event).id(event.seq.to_string()).data(event.data)
 }
 
 async fn send_chunk(tx: &EventSender, content: &str) {
-    let data = SseData::Stream { content: content.to_string() };
-    if let Ok(event) = Event::default().event("stream").json_data(&data) {
-        let _ = tx.send(Ok(event)).await;
+    tx.send(&SseData::Stream { content: content.to_string() }).await;
+}
+
+/// Tells the client the run failed for a reason of its own, such as a refused model.
+async fn send_error(tx: &EventSender, code: &'static str, message: String) {
+    tx.send(&SseData::Error { code, message, detail: None, node_id: None }).await;
+}
+
+/// Tells the client a model call or pipeline node failed, and which node.
+async fn send_agent_error(tx: &EventSender, error: &AgentError) {
+    tx.send(&SseData::Error {
+        code: error.code(),
+        message: GENERATION_FAILED.to_string(),
+        detail: Some(error.root().to_string()),
+        node_id: error.node().map(String::from),
+    })
+    .await;
+}
+
+/// Tells the client the run finished without a response, rather than ending on an empty message.
+async fn send_empty(tx: &EventSender, reason: EmptyReason, collector: Option<Arc<TracingCollector>>) {
+    warn!("Pipeline finished without a response ({})", reason.as_str());
+    tx.send(&SseData::Warning {
+        status: TraceStatus::CompletedEmpty.as_str(),
+        reason,
+        message: reason.message().to_string(),
+    })
+    .await;
+    if let Some(coll) = collector {
+        coll.completed_empty(reason.message());
     }
 }
 
-/// Consumes a stream and sends chunks to the SSE channel.
-/// Returns (full_response, input_tokens, output_tokens).
-async fn stream_to_sse_with_response(tx: &EventSender, stream: fissio_llm::LlmStream) -> (String, u32, u32) {
+/// What a stream sent before it ended, and the error that ended it early.
+struct StreamedResponse {
+    response: String,
+    input_tokens: u32,
+    output_tokens: u32,
+    error: Option<AgentError>,
+}
+
+/// Consumes a stream and sends chunks to the SSE channel, stopping with an
+/// `error` event if the stream fails.
+async fn stream_to_sse_with_response(tx: &EventSender, stream: fissio_llm::LlmStream) -> StreamedResponse {
     let mut full_response = String::new();
     let mut input_tokens = 0u32;
     let mut output_tokens = 0u32;
@@ -112,40 +637,251 @@ async fn stream_to_sse_with_response(tx: &EventSender, stream: fissio_llm::LlmSt
                 input_tokens = i;
                 output_tokens = o;
             }
+            Ok(fissio_llm::StreamChunk::Reasoning(content)) => {
+                tx.send(&SseData::Thinking { content }).await;
+            }
+            Ok(fissio_llm::StreamChunk::Progress(message)) => {
+                tx.send(&SseData::Progress { message }).await;
+            }
+            Ok(fissio_llm::StreamChunk::ToolCallStarted { call_id, name, args_preview }) => {
+                tx.send(&SseData::ToolCallStarted { call_id, name, args_preview }).await;
+            }
+            Ok(fissio_llm::StreamChunk::ToolCallFinished { call_id, name, duration_ms, result_preview }) => {
+                tx.send(&SseData::ToolCallFinished { call_id, name, duration_ms, result_preview }).await;
+            }
+            Ok(fissio_llm::StreamChunk::Citation(citation)) => {
+                tx.send(&SseData::Citation(citation)).await;
+            }
+            Ok(_) => {}
             Err(e) => {
                 error!("Stream error: {}", e);
+                send_agent_error(tx, &e).await;
+                return StreamedResponse { response: full_response, input_tokens, output_tokens, error: Some(e) };
             }
         }
     }
 
-    (full_response, input_tokens, output_tokens)
+    StreamedResponse { response: full_response, input_tokens, output_tokens, error: None }
+}
+
+/// Runs a request queued during degraded mode; output is recorded in traces only.
+///
+/// Takes the request by value, so the health monitor's spawned task doesn't
+/// hold a borrowed history across the run.
+pub async fn replay(req: ChatRequest, state: &ServerState) {
+    let (tx, rx) = mpsc::channel(1);
+    drop(rx);
+    execute_turn(&EventSender { tx, run: None }, &req, state).await;
+}
+
+/// Runs a scheduled pipeline for `tenant` with no client attached. Its events
+/// are logged under `run_id`, so the run can be followed from
+/// `GET /chat/runs/{run_id}/events`.
+pub async fn run_scheduled(
+    state: &ServerState,
+    tenant: &Tenant,
+    run_id: &str,
+    config: &fissio_config::PipelineConfig,
+    message: &str,
+    model_id: Option<&str>,
+) -> StreamResult {
+    let (tx, rx) = mpsc::channel(1);
+    drop(rx);
+    let tx = EventSender { tx, run: Some((tenant.scope(run_id), state.event_log.clone())) };
+    tx.send(&SseData::Run { run_id: run_id.to_string() }).await;
+
+    let start = Instant::now();
+    let model = state.get_model(model_id.unwrap_or(""));
+    let result = execute_pipeline_chat(&tx, config, message, &[], None, state, &model, HashMap::new(), tenant, None).await;
+    let metadata = build_metadata(&result, start.elapsed().as_millis() as u64);
+    tx.send(&SseData::End { metadata }).await;
+    result
+}
+
+/// Runs a request, reading history from and saving the turn to its session if it names one.
+async fn execute_turn(tx: &EventSender, req: &ChatRequest, state: &ServerState) -> StreamResult {
+    let Some(session_id) = req.session_id.as_deref() else {
+        return execute_chat(tx, req, state).await;
+    };
+    let mut req = req.clone();
+    match sessions::load_history(state, session_id, req.tenant.as_deref()) {
+        Ok(Some(history)) => req.history = history,
+        Ok(None) => {
+            warn!("Session {} belongs to another tenant", session_id);
+            send_error(tx, "session_not_found", format!("Session {} not found.", session_id)).await;
+            return StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None };
+        }
+        Err(e) => error!("Failed to load session {}: {}", session_id, e),
+    }
+
+    let result = execute_chat(tx, &req, state).await;
+    if !result.response.is_empty() {
+        if let Err(e) = sessions::append_turn(state, session_id, &req.message, &result.response) {
+            error!("Failed to save turn to session {}: {}", session_id, e);
+        }
+    }
+    result
 }
 
 async fn execute_chat(tx: &EventSender, req: &ChatRequest, state: &ServerState) -> StreamResult {
     let model_id = req.model_id.as_deref().unwrap_or("");
     let model = state.get_model(model_id);
     let system_prompt = req.system_prompt.as_deref().unwrap_or(DEFAULT_SYSTEM_PROMPT);
+    let tenant = req.tenant();
+
+    // All cloud providers down: apply the configured degraded behavior
+    if model.api_base.is_none() && state.provider_health.is_degraded().await {
+        if let Some(result) = execute_degraded(tx, req, state, system_prompt).await {
+            return result;
+        }
+    }
+
+    let direct = req.pipeline_config.is_none() && req.pipeline_id.is_none();
+    if (direct || model.api_base.is_some()) && !model_allowed(tx, state, &model).await {
+        return StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None };
+    }
 
     // Use native Ollama API for local models (provides rich metrics)
     if model.api_base.is_some() {
-        return execute_ollama_chat(tx, &model, &req.history, &req.message, system_prompt, state).await;
+        return execute_ollama_chat(tx, &model, &req.history, &req.message, system_prompt, state, &tenant).await;
     }
 
     // Runtime pipeline config from frontend
     if let Some(ref runtime_config) = req.pipeline_config {
         let config = runtime_to_pipeline_config(runtime_config);
         info!("Using runtime pipeline config ({} nodes)", config.nodes.len());
-        return execute_pipeline_chat(tx, &config, &req.message, &req.history, state, &model, req.node_models.clone()).await;
+        return execute_pipeline_chat(tx, &config, &req.message, &req.history, req.memory_scope().as_deref(), state, &model, req.node_models.clone(), &tenant, req.idempotency_key.as_deref()).await;
     }
 
     // Preset pipeline by ID
-    if let Some(config) = req.pipeline_id.as_deref().and_then(|id| state.presets.get(id)) {
+    let preset = match req.pipeline_id.as_deref() {
+        Some(id) => state.presets.read().await.get(id).cloned(),
+        None => None,
+    };
+    if let Some(config) = &preset {
         info!("Using pipeline preset: {}", config.name);
-        return execute_pipeline_chat(tx, config, &req.message, &req.history, state, &model, req.node_models.clone()).await;
+        return execute_pipeline_chat(tx, config, &req.message, &req.history, req.memory_scope().as_deref(), state, &model, req.node_models.clone(), &tenant, req.idempotency_key.as_deref()).await;
     }
 
     // Direct chat
-    execute_direct(tx, &model, &req.history, &req.message, system_prompt, state).await
+    execute_direct(tx, &model, &req.history, &req.message, system_prompt, state, &tenant).await
+}
+
+/// Checks a directly called model against the server's residency policy,
+/// telling the client why it is refused.
+async fn model_allowed(tx: &EventSender, state: &ServerState, model: &fissio_core::ModelConfig) -> bool {
+    let Err(reason) = state.residency.check(&model.provider_name(), model.region.as_deref()) else {
+        return true;
+    };
+    warn!("Refusing model {}: {}", model.id, reason);
+    send_error(tx, "residency_violation", format!("{} is not allowed by the residency policy: {}.", model.name, reason)).await;
+    false
+}
+
+/// Handles a cloud-bound request while all providers are unavailable.
+/// Returns None in `error` mode so the request runs (and fails) as usual.
+async fn execute_degraded(
+    tx: &EventSender,
+    req: &ChatRequest,
+    state: &ServerState,
+    system_prompt: &str,
+) -> Option<StreamResult> {
+    let empty = StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None };
+
+    match &state.provider_health.mode {
+        DegradedMode::Error => None,
+        DegradedMode::Canned(message) => {
+            info!("Degraded mode: sending canned response");
+            send_chunk(tx, message).await;
+            Some(empty)
+        }
+        DegradedMode::Queue => {
+            let queued = match serde_json::to_string(req) {
+                Ok(json) => state.enqueue_request(json).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match queued {
+                Ok(id) => {
+                    info!("Degraded mode: queued request #{}", id);
+                    send_chunk(tx, &format!(
+                        "Our AI providers are temporarily unavailable. Your request has been queued (#{}) \
+                         and will run automatically once service is restored; its result will appear in traces.",
+                        id
+                    )).await;
+                }
+                Err(e) => {
+                    error!("Failed to queue request: {}", e);
+                    send_error(tx, "queue_failed", "Providers are unavailable and the request couldn't be queued.".into()).await;
+                }
+            }
+            Some(empty)
+        }
+        DegradedMode::Local(local_id) => {
+            let local = state.get_model(local_id);
+            info!("Degraded mode: routing to local model {}", local.name);
+
+            let config = match req.pipeline_config {
+                Some(ref runtime_config) => Some(runtime_to_pipeline_config(runtime_config)),
+                None => match req.pipeline_id.as_deref() {
+                    Some(id) => state.presets.read().await.get(id).cloned(),
+                    None => None,
+                },
+            };
+            let tenant = req.tenant();
+            let Some(config) = config else {
+                return Some(execute_ollama_chat(tx, &local, &req.history, &req.message, system_prompt, state, &tenant).await);
+            };
+
+            let overrides = config.nodes.iter().map(|n| (n.id.clone(), local.id.clone())).collect();
+            Some(execute_pipeline_chat(tx, &config, &req.message, &req.history, req.memory_scope().as_deref(), state, &local, overrides, &tenant, req.idempotency_key.as_deref()).await)
+        }
+    }
+}
+
+/// Fits the history into the model's context window, recording any summary call in the trace.
+async fn fit_history(
+    state: &ServerState,
+    collector: &TracingCollector,
+    model: &fissio_core::ModelConfig,
+    system_prompt: &str,
+    history: &[CoreMessage],
+    message: &str,
+) -> FittedContext {
+    let fitted = state.context.fit(model, system_prompt, history, message).await;
+    if let Some((summary_model, metrics)) = &fitted.summary {
+        let pricing = state.models().iter().find(|m| &m.model == summary_model).and_then(|m| m.pricing);
+        collector.record(NodeMetrics {
+            node_id: "context_summary".to_string(),
+            model: Some(summary_model.clone()),
+            input_tokens: metrics.input_tokens,
+            output_tokens: metrics.output_tokens,
+            elapsed_ms: metrics.elapsed_ms,
+            tool_call_count: 0,
+            iteration_count: 1,
+            estimated_cost_usd: pricing.map(|p| p.estimate(metrics.input_tokens, metrics.output_tokens)),
+            groundedness: None,
+            provider_request_ids: metrics.request_id.iter().cloned().collect(),
+            tool_calls: Vec::new(),
+            error: None,
+        });
+    }
+    fitted
+}
+
+/// Traces a direct chat under the caller's tenant.
+fn direct_collector(
+    state: &ServerState,
+    model: &fissio_core::ModelConfig,
+    message: &str,
+    tenant: &Tenant,
+) -> TracingCollector {
+    let collector =
+        TracingCollector::new(state.trace_store.clone(), "direct", format!("Direct Chat ({})", model.name), message)
+            .with_observer(state.metrics.clone());
+    match tenant.id() {
+        Some(owner) => collector.with_owner(owner),
+        None => collector,
+    }
 }
 
 async fn execute_ollama_chat(
@@ -155,21 +891,19 @@ async fn execute_ollama_chat(
     message: &str,
     system_prompt: &str,
     state: &ServerState,
+    tenant: &Tenant,
 ) -> StreamResult {
-    let collector = TracingCollector::new(
-        state.trace_store.clone(),
-        "direct",
-        format!("Direct Chat ({})", model.name),
-        message,
-    );
+    let collector = direct_collector(state, model, message, tenant);
     let start_time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_millis() as i64)
         .unwrap_or(0);
 
-    match execute_ollama_stream(model, history, message, system_prompt).await {
+    let fitted = fit_history(state, &collector, model, system_prompt, history, message).await;
+
+    match execute_ollama_stream(model, &fitted.history, message, &fitted.system_prompt).await {
         Ok((stream, metrics)) => {
-            let (response, input_tokens, output_tokens) = stream_to_sse_with_response(tx, stream).await;
+            let StreamedResponse { response, input_tokens, output_tokens, error } = stream_to_sse_with_response(tx, stream).await;
             let end_time = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_millis() as i64)
@@ -177,25 +911,34 @@ async fn execute_ollama_chat(
 
             let node_metrics = NodeMetrics {
                 node_id: "llm".to_string(),
+                model: Some(model.model.clone()),
                 input_tokens,
                 output_tokens,
                 elapsed_ms: (end_time - start_time) as u64,
                 tool_call_count: 0,
                 iteration_count: 1,
-                estimated_cost_usd: None,
+                estimated_cost_usd: model.pricing.map(|p| p.estimate(input_tokens, output_tokens)),
+                groundedness: None,
+                provider_request_ids: Vec::new(),
+                tool_calls: Vec::new(),
+                error: error.as_ref().map(ToString::to_string),
             };
             collector.record(node_metrics.clone());
             collector.record_span("llm", "llm", start_time, end_time, message, &response, &node_metrics);
-            collector.success(&response);
+            match &error {
+                Some(e) => collector.error(&e.to_string()),
+                None => collector.success(&response),
+            }
 
             info!("Direct chat: {}ms, tokens: {}/{}", end_time - start_time, input_tokens, output_tokens);
-            StreamResult { input_tokens, output_tokens, ollama_metrics: Some(metrics) }
+            let estimated_cost_usd = collector.total_cost_usd();
+            StreamResult { response, input_tokens, output_tokens, ollama_metrics: Some(metrics), estimated_cost_usd }
         }
         Err(e) => {
             error!("Ollama error: {}", e);
             collector.error(&e.to_string());
-            send_chunk(tx, "Error generating response.").await;
-            StreamResult { input_tokens: 0, output_tokens: 0, ollama_metrics: None }
+            send_agent_error(tx, &e).await;
+            StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None }
         }
     }
 }
@@ -207,21 +950,19 @@ async fn execute_direct(
     message: &str,
     system_prompt: &str,
     state: &ServerState,
+    tenant: &Tenant,
 ) -> StreamResult {
-    let collector = TracingCollector::new(
-        state.trace_store.clone(),
-        "direct",
-        format!("Direct Chat ({})", model.name),
-        message,
-    );
+    let collector = direct_collector(state, model, message, tenant);
     let start_time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_millis() as i64)
         .unwrap_or(0);
 
-    match execute_direct_chat(model, history, message, system_prompt).await {
+    let fitted = fit_history(state, &collector, model, system_prompt, history, message).await;
+
+    match execute_direct_chat(model, &fitted.history, message, &fitted.system_prompt).await {
         Ok(stream) => {
-            let (response, input_tokens, output_tokens) = stream_to_sse_with_response(tx, stream).await;
+            let StreamedResponse { response, input_tokens, output_tokens, error } = stream_to_sse_with_response(tx, stream).await;
             let end_time = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_millis() as i64)
@@ -229,59 +970,163 @@ async fn execute_direct(
 
             let node_metrics = NodeMetrics {
                 node_id: "llm".to_string(),
+                model: Some(model.model.clone()),
                 input_tokens,
                 output_tokens,
                 elapsed_ms: (end_time - start_time) as u64,
                 tool_call_count: 0,
                 iteration_count: 1,
-                estimated_cost_usd: None,
+                estimated_cost_usd: model.pricing.map(|p| p.estimate(input_tokens, output_tokens)),
+                groundedness: None,
+                provider_request_ids: Vec::new(),
+                tool_calls: Vec::new(),
+                error: error.as_ref().map(ToString::to_string),
             };
             collector.record(node_metrics.clone());
             collector.record_span("llm", "llm", start_time, end_time, message, &response, &node_metrics);
-            collector.success(&response);
+            match &error {
+                Some(e) => collector.error(&e.to_string()),
+                None => collector.success(&response),
+            }
 
             info!("Direct chat: {}ms, tokens: {}/{}", end_time - start_time, input_tokens, output_tokens);
-            StreamResult { input_tokens, output_tokens, ollama_metrics: None }
+            let estimated_cost_usd = collector.total_cost_usd();
+            StreamResult { response, input_tokens, output_tokens, ollama_metrics: None, estimated_cost_usd }
         }
         Err(e) => {
             error!("Chat error: {}", e);
             collector.error(&e.to_string());
-            send_chunk(tx, "Error generating response.").await;
-            StreamResult { input_tokens: 0, output_tokens: 0, ollama_metrics: None }
+            send_agent_error(tx, &e).await;
+            StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn execute_pipeline_chat(
     tx: &EventSender,
     config: &fissio_config::PipelineConfig,
     message: &str,
     history: &[CoreMessage],
+    memory_scope: Option<&str>,
     state: &ServerState,
     default_model: &fissio_core::ModelConfig,
     node_overrides: HashMap<String, String>,
+    tenant: &Tenant,
+    idempotency_key: Option<&str>,
 ) -> StreamResult {
-    let trace_store = Some(state.trace_store.clone());
+    let config = match state.resolve_secrets(config.clone()) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            error!("Pipeline {} can't run: {}", config.id, e);
+            send_error(tx, "config_error", format!("{} can't run: {}.", config.name, e)).await;
+            return StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None };
+        }
+    };
+    let config = &config;
+    let memory = state.memory.clone().zip(memory_scope.map(String::from));
+    // Only a keyed request runs again as the same request, after a restart or from the degraded-mode queue
+    let ledger = state.side_effects.clone().zip(idempotency_key.map(|key| tenant.scope(&format!("side_effects/{}", key))));
+    let _lease = crate::services::model::start_run(state, config, default_model, &node_overrides).await;
+    // Replayed requests have no client to ask, so approval-gated calls are refused
+    let approver = tx.run.is_some().then(|| {
+        Arc::new(SseApprover { tx: tx.clone(), broker: state.approvals.clone() }) as Arc<dyn ToolApprover>
+    });
+
+    let models = state.models();
+    let options = PipelineRunOptions {
+        models: &models,
+        default_model,
+        node_overrides,
+        pipelines: state.pipeline_catalog(tenant).await,
+        tools: state.tool_registry.read().await.clone(),
+        llm_cache: state.llm_cache.clone(),
+        trace_store: Some(state.trace_store.clone()),
+        run_observer: Some(state.metrics.clone()),
+        owner: tenant.id(),
+        capture: state.trace_capture,
+        pretty_logs: state.pretty_logs,
+        tool_policy: &state.tool_policy,
+        residency: &state.residency,
+        local_hosts: state.local_hosts.as_ref(),
+        vram: state.vram.clone(),
+        unhealthy_models: state.model_health.unhealthy(),
+        memory,
+        ledger,
+        approver,
+    };
 
-    match execute_pipeline(config, message, history, &state.models, default_model, node_overrides, trace_store).await {
+    match execute_pipeline(config, message, history, options).await {
         Ok(PipelineResult { output: EngineOutput::Stream(stream), collector }) => {
-            let (response, input_tokens, output_tokens) = stream_to_sse_with_response(tx, stream).await;
-            if let Some(coll) = collector {
+            let StreamedResponse { response, input_tokens, output_tokens, error } = stream_to_sse_with_response(tx, stream).await;
+            let estimated_cost_usd = collector.as_ref().and_then(|c| c.total_cost_usd());
+            if let Some(e) = &error {
+                if let Some(coll) = collector {
+                    coll.error(&e.to_string());
+                }
+            } else if response.trim().is_empty() {
+                send_empty(tx, EmptyReason::EmptyResult, collector).await;
+            } else if let Some(coll) = collector {
                 coll.success(&response);
             }
-            StreamResult { input_tokens, output_tokens, ollama_metrics: None }
+            StreamResult { response, input_tokens, output_tokens, ollama_metrics: None, estimated_cost_usd }
         }
         Ok(PipelineResult { output: EngineOutput::Complete(response), collector }) => {
             send_chunk(tx, &response).await;
+            let estimated_cost_usd = collector.as_ref().and_then(|c| c.total_cost_usd());
             if let Some(coll) = collector {
                 coll.success(&response);
             }
-            StreamResult { input_tokens: 0, output_tokens: 0, ollama_metrics: None }
+            StreamResult { response, input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd }
+        }
+        Ok(PipelineResult { output: EngineOutput::Empty(reason), collector }) => {
+            let estimated_cost_usd = collector.as_ref().and_then(|c| c.total_cost_usd());
+            send_empty(tx, reason, collector).await;
+            StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd }
+        }
+        Ok(PipelineResult { collector, .. }) => {
+            send_empty(tx, EmptyReason::EmptyResult, collector).await;
+            StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None }
         }
         Err(e) => {
             error!("Engine error: {}", e);
-            send_chunk(tx, "Error generating response.").await;
-            StreamResult { input_tokens: 0, output_tokens: 0, ollama_metrics: None }
+            send_agent_error(tx, &e).await;
+            StreamResult { response: String::new(), input_tokens: 0, output_tokens: 0, ollama_metrics: None, estimated_cost_usd: None }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::InMemoryEventLog;
+    use fissio_rag::{InMemoryMemory, Memory};
+
+    fn request(tenant: &str) -> ChatRequest {
+        let mut req: ChatRequest = serde_json::from_value(serde_json::json!({ "message": "hi", "user_id": "sam" })).unwrap();
+        req.tenant = Some(tenant.to_string());
+        req
+    }
+
+    #[tokio::test]
+    async fn test_tenants_cannot_read_each_others_runs() {
+        let log = InMemoryEventLog::default();
+        let (acme, globex) = (request("acme").tenant(), request("globex").tenant());
+        log.append(&acme.scope("run-1"), "chunk", "{}").await.unwrap();
+
+        assert!(log.read_from(&acme.scope("run-1"), 0).await.unwrap().is_some());
+        assert!(log.read_from(&globex.scope("run-1"), 0).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tenants_cannot_read_each_others_memories() {
+        let memory = InMemoryMemory::new();
+        let (acme, globex) = (request("acme").memory_scope().unwrap(), request("globex").memory_scope().unwrap());
+        assert_ne!(acme, globex);
+        memory.put(&acme, "plan", "enterprise").await.unwrap();
+
+        assert!(memory.get(&acme, "plan").await.unwrap().is_some());
+        assert!(memory.get(&globex, "plan").await.unwrap().is_none());
+        assert!(memory.search(&globex, "", 10).await.unwrap().is_empty());
+    }
+}
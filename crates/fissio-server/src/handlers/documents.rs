@@ -0,0 +1,105 @@
+//! Document upload HTTP handlers.
+
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap};
+use axum::Json;
+use fissio_rag::{extract_text, Document, DocumentFormat, DocumentIngestor};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::db;
+use crate::error::AppError;
+use crate::ServerState;
+
+/// Largest upload accepted by POST /documents.
+pub const MAX_UPLOAD_BYTES: usize = 32 * 1024 * 1024;
+
+/// Query parameters for uploading a document.
+#[derive(Debug, Deserialize, Default)]
+pub struct UploadQuery {
+    /// Document ID; uploading an existing ID replaces that document.
+    pub id: Option<String>,
+    pub title: Option<String>,
+    /// `text`, `markdown`, `html`, or `pdf`; read from `Content-Type` when absent.
+    pub format: Option<String>,
+}
+
+/// Response for an indexed document.
+#[derive(Serialize)]
+pub struct DocumentResponse {
+    pub id: String,
+    pub title: String,
+    pub format: DocumentFormat,
+    pub chunks: usize,
+    pub collection: String,
+}
+
+fn ingestor(state: &ServerState) -> Result<&Arc<DocumentIngestor>, AppError> {
+    state
+        .documents
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("document ingestion is disabled; set EMBEDDING_MODEL".into()))
+}
+
+/// POST /documents - Extract, chunk, embed, and index an uploaded file.
+pub async fn upload(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<UploadQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<DocumentResponse>, AppError> {
+    let ingestor = ingestor(&state)?;
+    let format = match &params.format {
+        Some(name) => DocumentFormat::from_extension(name)
+            .ok_or_else(|| AppError::BadRequest(format!("unsupported format: {}", name)))?,
+        None => {
+            let mime = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("text/plain");
+            DocumentFormat::from_mime(mime)
+                .ok_or_else(|| AppError::BadRequest(format!("unsupported content type: {}", mime)))?
+        }
+    };
+
+    let text = tokio::task::spawn_blocking(move || extract_text(&body, format))
+        .await
+        .map_err(AppError::internal)?
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if text.trim().is_empty() {
+        return Err(AppError::BadRequest("document has no text".into()));
+    }
+
+    let id = params.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let title = params.title.unwrap_or_else(|| id.clone());
+    let previous = db::document_chunks(&state.db.lock().unwrap(), &id).map_err(AppError::internal)?;
+    if let Some(chunks) = previous {
+        ingestor.remove(&id, chunks).await.map_err(AppError::internal)?;
+    }
+
+    let chunks = ingestor.ingest(&Document::new(&id, &title, text)).await.map_err(|e| {
+        error!("Failed to ingest document {}: {}", id, e);
+        AppError::internal(e)
+    })?;
+    db::save_document(&state.db.lock().unwrap(), &id, &title, format.as_str(), chunks).map_err(AppError::internal)?;
+    info!("Indexed document {} ({} chunks) into '{}'", id, chunks, ingestor.collection());
+
+    Ok(Json(DocumentResponse { id, title, format, chunks, collection: ingestor.collection().to_string() }))
+}
+
+/// DELETE /documents/{id} - Remove a document's chunks from the collection.
+pub async fn delete(
+    State(state): State<Arc<ServerState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let ingestor = ingestor(&state)?;
+    let chunks = db::document_chunks(&state.db.lock().unwrap(), &id)
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound(format!("document not found: {}", id)))?;
+
+    ingestor.remove(&id, chunks).await.map_err(AppError::internal)?;
+    db::delete_document(&state.db.lock().unwrap(), &id).map_err(AppError::internal)?;
+    info!("Removed document {} from '{}'", id, ingestor.collection());
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
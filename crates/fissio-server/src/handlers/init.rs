@@ -1,17 +1,34 @@
-//! Init endpoint returning models, templates, and configs.
+//! Init endpoint returning models, templates, configs, and tools.
 
 use std::sync::Arc;
 
-use axum::{extract::State, Json};
+use axum::extract::State;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 
-use crate::dto::InitResponse;
+use crate::error::AppError;
+use crate::services::init as init_service;
 use crate::ServerState;
 
 /// Returns initialization data for the frontend.
-pub async fn init(State(state): State<Arc<ServerState>>) -> Json<InitResponse> {
-    Json(InitResponse {
-        models: state.models.clone(),
-        templates: state.templates.clone(),
-        configs: state.configs.read().await.clone(),
-    })
+///
+/// Serves the cached payload with an ETag; a matching `If-None-Match` gets 304.
+pub async fn init(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let payload = init_service::payload(&state).await?;
+    let cache_headers = [(ETAG, payload.etag.clone()), (CACHE_CONTROL, "no-cache".to_string())];
+
+    if payload.matches(&headers) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        cache_headers,
+        [(CONTENT_TYPE, "application/json")],
+        payload.body.clone(),
+    )
+        .into_response())
 }
@@ -1,13 +1,52 @@
 //! HTTP route handlers for the agent server.
 
 pub mod chat;
+pub mod documents;
 pub mod init;
 pub mod model;
 pub mod pipeline;
+pub mod quotas;
+pub mod schedules;
+pub mod sessions;
 pub mod tools;
 pub mod traces;
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Json;
+use fissio_llm::CloudProvider;
+use serde::Serialize;
+
+use crate::services::degraded::DegradedMode;
+use crate::ServerState;
+
 /// Health check endpoint.
 pub async fn health() -> &'static str {
     "OK"
 }
+
+/// GET /metrics - Prometheus metrics in the text exposition format.
+pub async fn metrics(State(state): State<Arc<ServerState>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], state.metrics.render())
+}
+
+/// Cloud provider health and degraded-mode status.
+#[derive(Serialize)]
+pub struct ProviderHealthResponse {
+    pub degraded: bool,
+    pub degraded_mode: DegradedMode,
+    pub providers: HashMap<CloudProvider, bool>,
+}
+
+/// GET /api/providers/health - Latest provider health check results.
+pub async fn provider_health(State(state): State<Arc<ServerState>>) -> Json<ProviderHealthResponse> {
+    Json(ProviderHealthResponse {
+        degraded: state.provider_health.is_degraded().await,
+        degraded_mode: state.provider_health.mode.clone(),
+        providers: state.provider_health.snapshot().await,
+    })
+}
@@ -1,14 +1,18 @@
-//! Model management HTTP handlers (wake/unload).
+//! Model management HTTP handlers (wake/unload/health/pool/refresh).
 
+use std::convert::Infallible;
 use std::sync::Arc;
 
 use axum::{
     extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
     Json,
 };
+use futures::Stream;
 use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
 
-use crate::dto::{UnloadResponse, WakeResponse};
+use crate::dto::{ModelHealthResponse, ModelPoolResponse, ModelsUpdated, UnloadResponse, WakeResponse};
 use crate::error::AppError;
 use crate::services;
 use crate::ServerState;
@@ -33,11 +37,47 @@ pub async fn wake(
     }))
 }
 
-/// Unloads a model from GPU memory.
+/// Unloads a model from GPU memory, or once the pipeline runs using it finish.
 pub async fn unload(
     State(state): State<Arc<ServerState>>,
     Path(model_id): Path<String>,
 ) -> Result<Json<UnloadResponse>, AppError> {
-    services::model::unload(&state, &model_id).await?;
-    Ok(Json(UnloadResponse { success: true }))
+    let deferred = services::model::unload(&state, &model_id).await?;
+    Ok(Json(UnloadResponse { success: true, deferred }))
+}
+
+/// GET /models/health - Latest health check of every registered model.
+pub async fn health(State(state): State<Arc<ServerState>>) -> Json<ModelHealthResponse> {
+    Json(ModelHealthResponse {
+        failure_threshold: state.model_health.failure_threshold(),
+        models: state.model_health.snapshot(),
+    })
+}
+
+/// GET /models/pool - Local models the server has loaded, with their runs and keep-alive.
+pub async fn pool(State(state): State<Arc<ServerState>>) -> Json<ModelPoolResponse> {
+    Json(state.model_pool.snapshot())
+}
+
+/// POST /models/refresh - Re-runs Ollama discovery and returns what changed.
+pub async fn refresh(State(state): State<Arc<ServerState>>) -> Result<Json<ModelsUpdated>, AppError> {
+    Ok(Json(services::model::refresh(&state).await?))
+}
+
+/// GET /models/events - Streams a `models_updated` event whenever the model catalog changes.
+pub async fn events(State(state): State<Arc<ServerState>>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(state.model_events.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(update) => {
+                    let event = Event::default().event("models_updated").json_data(&update).unwrap_or_default();
+                    return Some((Ok(event), rx));
+                }
+                // A slow client only needs the latest catalog
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
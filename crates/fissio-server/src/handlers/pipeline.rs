@@ -1,50 +1,223 @@
-//! Pipeline CRUD HTTP handlers.
+//! Pipeline CRUD, planning, evaluation, and comparison HTTP handlers.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::{extract::State, Json};
-use tracing::{error, info};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Json;
+use fissio_engine::ExecutionPlan;
+use fissio_eval::{ComparisonReport, EvalReport};
+use serde::Deserialize;
+use tracing::{error, info, warn};
 
-use crate::dto::{DeletePipelineRequest, PipelineInfo, SavePipelineRequest, SavePipelineResponse};
+use crate::auth::{Principal, Tenant};
+use crate::dto::{
+    ComparePipelinesRequest, DeletePipelineRequest, EvaluatePipelineRequest, ImportPipelineResponse, PipelineBundle,
+    PipelineInfo, PipelineVersionInfo, SavePipelineRequest, SavePipelineResponse,
+};
 use crate::error::AppError;
+use crate::services;
 use crate::services::pipeline as pipeline_service;
 use crate::ServerState;
 
-/// Lists all saved pipeline configurations.
+/// Lists the saved pipeline configurations the caller can see.
 pub async fn list(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
 ) -> Json<Vec<PipelineInfo>> {
     let configs = state.configs.read().await;
-    Json(configs.clone())
+    Json(configs.iter().filter(|p| tenant.can_read(p.owner.as_deref())).cloned().collect())
 }
 
 /// Saves a pipeline configuration.
 pub async fn save(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
     Json(req): Json<SavePipelineRequest>,
 ) -> Result<Json<SavePipelineResponse>, AppError> {
     info!("Saving pipeline config: {} ({})", req.name, req.id);
 
-    pipeline_service::save_pipeline(&state, &req).await.map_err(|e| {
+    let (_, version) = pipeline_service::save_pipeline(&state, &tenant, &req).await.map_err(|e| {
         error!("Failed to save pipeline: {:?}", e);
         e
     })?;
 
-    info!("Pipeline config saved successfully: {}", req.id);
-    Ok(Json(SavePipelineResponse { success: true, id: req.id }))
+    info!("Pipeline config saved successfully: {} v{}", req.id, version);
+    Ok(Json(SavePipelineResponse { success: true, id: req.id, version }))
 }
 
 /// Deletes a pipeline configuration.
 pub async fn delete(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
     Json(req): Json<DeletePipelineRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     info!("Deleting pipeline config: {}", req.id);
 
-    pipeline_service::delete_pipeline(&state, &req.id).await.map_err(|e| {
+    pipeline_service::delete_pipeline(&state, &tenant, &req.id).await.map_err(|e| {
         error!("Failed to delete pipeline: {:?}", e);
         e
     })?;
 
     Ok(Json(serde_json::json!({ "success": true })))
 }
+
+/// GET /pipelines/{id}/versions - Lists a saved pipeline's versions, newest first.
+pub async fn versions(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<PipelineVersionInfo>>, AppError> {
+    pipeline_service::list_versions(&state, &tenant, &id).map(Json)
+}
+
+/// POST /pipelines/{id}/rollback/{version} - Restores a saved version as the
+/// pipeline's newest one.
+pub async fn rollback(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path((id, version)): Path<(String, i64)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    info!("Rolling back pipeline {} to v{}", id, version);
+    let (pipeline, new_version) = pipeline_service::rollback_pipeline(&state, &tenant, &id, version).await.map_err(|e| {
+        error!("Failed to roll back pipeline: {:?}", e);
+        e
+    })?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "version": new_version,
+        "restored_from": version,
+        "pipeline": pipeline,
+    })))
+}
+
+/// GET /pipelines/{id}/export - A saved pipeline or preset as a portable bundle.
+pub async fn export(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<PipelineBundle>, AppError> {
+    pipeline_service::export_pipeline(&state, &tenant, &id).await.map(Json)
+}
+
+/// Query parameters for a pipeline's graph.
+#[derive(Debug, Deserialize, Default)]
+pub struct GraphQuery {
+    /// `mermaid` (default) or `dot`.
+    pub format: Option<String>,
+}
+
+/// GET /pipelines/{id}/graph - The pipeline's graph as a Mermaid flowchart or Graphviz DOT.
+pub async fn graph(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Query(params): Query<GraphQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let format = params.format.as_deref().unwrap_or("mermaid");
+    let graph = pipeline_service::pipeline_graph(&state, &tenant, &id, format).await?;
+    let content_type = if format == "dot" { "text/vnd.graphviz" } else { "text/plain; charset=utf-8" };
+    Ok(([(header::CONTENT_TYPE, content_type)], graph))
+}
+
+/// POST /pipelines/import - Saves a bundle from GET /pipelines/{id}/export.
+pub async fn import(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Json(bundle): Json<serde_json::Value>,
+) -> Result<Json<ImportPipelineResponse>, AppError> {
+    let (pipeline, version, warnings) = pipeline_service::import_pipeline(&state, &tenant, bundle).await.map_err(|e| {
+        error!("Failed to import pipeline: {:?}", e);
+        e
+    })?;
+    for warning in &warnings {
+        warn!("Imported pipeline {}: {}", pipeline.id, warning);
+    }
+    info!("Imported pipeline {} v{}", pipeline.id, version);
+    Ok(Json(ImportPipelineResponse { success: true, id: pipeline.id, version, warnings }))
+}
+
+/// POST /presets/reload - Reloads the preset files from disk.
+pub async fn reload_presets(State(state): State<Arc<ServerState>>) -> Result<Json<serde_json::Value>, AppError> {
+    let count = services::presets::reload(&state).await.map_err(|e| {
+        error!("Failed to reload presets: {}", e);
+        AppError::internal(e)
+    })?;
+    Ok(Json(serde_json::json!({ "success": true, "presets": count })))
+}
+
+/// POST /pipelines/{id}/evaluate - Runs a test suite against a pipeline and
+/// returns the scored report.
+pub async fn evaluate(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    principal: Option<Extension<Principal>>,
+    Path(id): Path<String>,
+    Json(req): Json<EvaluatePipelineRequest>,
+) -> Result<Json<EvalReport>, AppError> {
+    info!("Evaluating pipeline {} ({} cases)", id, req.suite.cases.len());
+    let report = pipeline_service::evaluate_pipeline(&state, &tenant, principal.as_deref(), &id, req).await?;
+    info!(
+        "Pipeline {} evaluated: {:.0}% passed, score {:.2}",
+        id,
+        report.scores.pass_rate * 100.0,
+        report.scores.avg_score
+    );
+    Ok(Json(report))
+}
+
+/// Query parameters for a pipeline's execution plan.
+#[derive(Debug, Deserialize, Default)]
+pub struct PlanQuery {
+    /// Model for nodes that don't pin one (default: the first configured model).
+    pub model_id: Option<String>,
+    /// Node model overrides as `node=model` pairs, comma-separated.
+    pub node_models: Option<String>,
+}
+
+/// GET /pipelines/{id}/plan - What a run would execute, in order, with each
+/// node's model and tools, without calling any LLM.
+pub async fn plan(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Query(params): Query<PlanQuery>,
+) -> Result<Json<ExecutionPlan>, AppError> {
+    let node_models: HashMap<String, String> = params
+        .node_models
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(node, model)| (node.trim().to_string(), model.trim().to_string()))
+                .ok_or_else(|| AppError::BadRequest(format!("Invalid node_models entry '{}', expected node=model", pair)))
+        })
+        .collect::<Result<_, _>>()?;
+    let plan = pipeline_service::plan_pipeline(&state, &tenant, &id, params.model_id.as_deref(), node_models).await?;
+    Ok(Json(plan))
+}
+
+/// POST /compare - Runs one input through two pipeline variants and reports
+/// their outputs, latency, and tokens side by side, with an optional judge verdict.
+pub async fn compare(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    principal: Option<Extension<Principal>>,
+    Json(req): Json<ComparePipelinesRequest>,
+) -> Result<Json<ComparisonReport>, AppError> {
+    info!("Comparing pipeline variants");
+    let report = pipeline_service::compare_pipelines(&state, &tenant, principal.as_deref(), req).await?;
+    info!(
+        "Compared {} ({}ms) and {} ({}ms), winner: {:?}",
+        report.a.label,
+        report.a.elapsed_ms,
+        report.b.label,
+        report.b.elapsed_ms,
+        report.verdict.as_ref().map(|v| v.winner)
+    );
+    Ok(Json(report))
+}
@@ -0,0 +1,46 @@
+//! Usage policy admin HTTP handlers.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::Json;
+use tracing::info;
+
+use crate::dto::{KeyPolicy, KeyQuotaInfo};
+use crate::error::AppError;
+use crate::services::policy;
+use crate::ServerState;
+
+/// GET /admin/quotas - Every saved policy with today's usage.
+pub async fn list(State(state): State<Arc<ServerState>>) -> Result<Json<Vec<KeyQuotaInfo>>, AppError> {
+    Ok(Json(policy::quotas(&state)?))
+}
+
+/// GET /admin/quotas/{key} - The policy that applies to a key, with today's usage.
+pub async fn get(
+    State(state): State<Arc<ServerState>>,
+    Path(key): Path<String>,
+) -> Result<Json<KeyQuotaInfo>, AppError> {
+    Ok(Json(policy::quota(&state, &key)?))
+}
+
+/// PUT /admin/quotas/{key} - Set a key's allowed models and daily limits; `*` sets the default.
+pub async fn set(
+    State(state): State<Arc<ServerState>>,
+    Path(key): Path<String>,
+    Json(policy): Json<KeyPolicy>,
+) -> Result<Json<KeyQuotaInfo>, AppError> {
+    let quota = policy::set_policy(&state, &key, policy)?;
+    info!("Set usage policy for {}", key);
+    Ok(Json(quota))
+}
+
+/// DELETE /admin/quotas/{key} - Remove a key's policy, so the default applies.
+pub async fn delete(
+    State(state): State<Arc<ServerState>>,
+    Path(key): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    policy::delete_policy(&state, &key)?;
+    info!("Deleted usage policy for {}", key);
+    Ok(Json(serde_json::json!({ "success": true })))
+}
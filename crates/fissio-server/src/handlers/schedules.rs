@@ -0,0 +1,53 @@
+//! Scheduled pipeline run HTTP handlers.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::Json;
+use tracing::info;
+
+use crate::auth::Tenant;
+use crate::db;
+use crate::dto::{CreateScheduleRequest, ScheduleInfo, ScheduleRunInfo};
+use crate::error::AppError;
+use crate::services::schedules;
+use crate::ServerState;
+
+/// GET /schedules - List the caller's schedules, oldest first.
+pub async fn list(State(state): State<Arc<ServerState>>, tenant: Tenant) -> Result<Json<Vec<ScheduleInfo>>, AppError> {
+    let conn = state.db_lock()?;
+    let schedules = db::list_schedules(&conn, tenant.id()).map_err(AppError::internal)?;
+    Ok(Json(schedules))
+}
+
+/// POST /schedules - Run a pipeline on a cron schedule.
+pub async fn create(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Json(req): Json<CreateScheduleRequest>,
+) -> Result<Json<ScheduleInfo>, AppError> {
+    Ok(Json(schedules::create(&state, &tenant, req).await?))
+}
+
+/// DELETE /schedules/{id} - Delete a schedule and its run history.
+pub async fn delete(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let conn = state.db_lock()?;
+    if !db::delete_schedule(&conn, &id, tenant.id()).map_err(AppError::internal)? {
+        return Err(AppError::NotFound(format!("schedule not found: {}", id)));
+    }
+    info!("Deleted schedule {}", id);
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// GET /schedules/{id}/runs - A schedule's most recent runs, newest first.
+pub async fn runs(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ScheduleRunInfo>>, AppError> {
+    Ok(Json(schedules::runs(&state, &tenant, &id)?))
+}
@@ -0,0 +1,69 @@
+//! Conversation session HTTP handlers.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::Json;
+use tracing::info;
+
+use crate::auth::Tenant;
+use crate::db;
+use crate::dto::{CreateSessionRequest, SessionDetail, SessionInfo};
+use crate::error::AppError;
+use crate::ServerState;
+
+/// GET /sessions - List the caller's sessions, most recently updated first.
+pub async fn list(State(state): State<Arc<ServerState>>, tenant: Tenant) -> Result<Json<Vec<SessionInfo>>, AppError> {
+    let conn = state.db_lock()?;
+    let sessions = db::list_sessions(&conn, tenant.id()).map_err(AppError::internal)?;
+    Ok(Json(sessions))
+}
+
+/// POST /sessions - Create an empty session.
+pub async fn create(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Json(req): Json<CreateSessionRequest>,
+) -> Result<Json<SessionInfo>, AppError> {
+    let id = req.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    if id.trim().is_empty() {
+        return Err(AppError::BadRequest("session id must not be empty".into()));
+    }
+    let conn = state.db_lock()?;
+    if !db::create_session(&conn, &id, req.title.as_deref().unwrap_or_default(), tenant.id()).map_err(AppError::internal)? {
+        return Err(AppError::BadRequest(format!("session {} already exists", id)));
+    }
+    let session = db::get_session(&conn, &id, tenant.id())
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::Internal(format!("session {} was not saved", id)))?;
+    info!("Created session {}", id);
+    Ok(Json(session))
+}
+
+/// GET /sessions/{id} - A session with its messages.
+pub async fn get(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<SessionDetail>, AppError> {
+    let conn = state.db_lock()?;
+    let session = db::get_session(&conn, &id, tenant.id())
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound(format!("session not found: {}", id)))?;
+    let messages = db::session_messages(&conn, &id).map_err(AppError::internal)?;
+    Ok(Json(SessionDetail { session, messages }))
+}
+
+/// DELETE /sessions/{id} - Delete a session and its messages.
+pub async fn delete(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let conn = state.db_lock()?;
+    if !db::delete_session(&conn, &id, tenant.id()).map_err(AppError::internal)? {
+        return Err(AppError::NotFound(format!("session not found: {}", id)));
+    }
+    info!("Deleted session {}", id);
+    Ok(Json(serde_json::json!({ "success": true })))
+}
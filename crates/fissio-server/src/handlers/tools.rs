@@ -3,28 +3,59 @@
 use std::sync::Arc;
 
 use axum::{extract::State, Json};
-use serde::Serialize;
+use tracing::info;
 
+use crate::db;
+use crate::dto::{ToolDefinition, ToolInfo};
+use crate::error::AppError;
+use crate::services::{init, tools};
 use crate::ServerState;
 
-/// Tool schema for API responses.
-#[derive(Debug, Serialize)]
-pub struct ToolInfo {
-    pub name: String,
-    pub description: String,
-    pub parameters: serde_json::Value,
-}
-
 /// Lists all available tools.
 pub async fn list(State(state): State<Arc<ServerState>>) -> Json<Vec<ToolInfo>> {
-    let tools = state.tool_registry.list()
-        .into_iter()
-        .map(|s| ToolInfo {
-            name: s.name,
-            description: s.description,
-            parameters: s.parameters,
-        })
-        .collect();
-
-    Json(tools)
+    Json(state.tool_infos().await)
+}
+
+/// POST /tools/register - Add an HTTP or MCP tool definition to the live registry and save it.
+///
+/// Posting a definition with a registered name replaces it. Returns the tools added.
+pub async fn register(
+    State(state): State<Arc<ServerState>>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Json<Vec<ToolInfo>>, AppError> {
+    let definition: ToolDefinition = serde_json::from_value(body.clone())
+        .map_err(|e| AppError::BadRequest(format!("invalid tool definition: {}", e)))?;
+    tools::validate(&definition, state.local_hosts.as_ref()).map_err(AppError::BadRequest)?;
+    let name = definition.name().to_string();
+    let replacing = {
+        let conn = state.db_lock()?;
+        db::has_registered_tool(&conn, &name).map_err(AppError::internal)?
+    };
+
+    let built = tools::build(&definition, state.local_hosts.as_ref()).await.map_err(AppError::BadRequest)?;
+    let added = built.tool_names();
+    if added.is_empty() {
+        return Err(AppError::BadRequest(format!("'{}' provides no tools", name)));
+    }
+
+    {
+        let mut registry = state.tool_registry.write().await;
+        let previous = if replacing { tools::registered_names(&definition, &registry) } else { Vec::new() };
+        if let Some(taken) = added.iter().find(|n| registry.has(n) && !previous.contains(n)) {
+            return Err(AppError::BadRequest(format!("a tool named '{}' is already registered", taken)));
+        }
+        {
+            let conn = state.db_lock()?;
+            db::save_registered_tool(&conn, &name, &body.to_string()).map_err(AppError::internal)?;
+        }
+        for stale in &previous {
+            registry.unregister(stale);
+        }
+        registry.extend(built);
+    }
+    init::invalidate(&state).await;
+    info!("Registered tool definition '{}' ({} tools)", name, added.len());
+
+    let infos = state.tool_infos().await.into_iter().filter(|t| added.contains(&t.name)).collect();
+    Ok(Json(infos))
 }
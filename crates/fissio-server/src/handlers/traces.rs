@@ -4,10 +4,12 @@ use std::sync::Arc;
 
 use axum::extract::{Path, Query, State};
 use axum::Json;
-use fissio_monitor::{SpanRecord, TraceQuery, TraceRecord, TraceStatus};
+use fissio_monitor::{ContentRecord, SpanRecord, ToolCallRecord, TraceQuery, TraceRecord, TraceStatus, TraceStore};
 use serde::{Deserialize, Serialize};
 
+use crate::auth::Tenant;
 use crate::error::AppError;
+use crate::services::usage::{previous_day_window, reconcile_openai, UsageApiConfig};
 use crate::ServerState;
 
 /// Response for listing traces.
@@ -16,10 +18,29 @@ pub struct TracesListResponse {
     pub traces: Vec<TraceRecord>,
 }
 
-/// Response for a single trace with spans.
+/// Response for a single trace with its timeline.
 #[derive(Serialize)]
 pub struct TraceDetailResponse {
     pub trace: TraceRecord,
+    /// Node spans in the order they started.
+    pub spans: Vec<TimelineSpan>,
+}
+
+/// A node span placed on its run's timeline, with the tool calls it made.
+#[derive(Serialize)]
+pub struct TimelineSpan {
+    #[serde(flatten)]
+    pub span: SpanRecord,
+    /// Milliseconds from the start of the run to the start of the span.
+    pub offset_ms: i64,
+    pub duration_ms: i64,
+    pub tool_calls: Vec<ToolCallRecord>,
+}
+
+/// Response for a stored input/output and the spans that share it.
+#[derive(Serialize)]
+pub struct ContentDetailResponse {
+    pub content: ContentRecord,
     pub spans: Vec<SpanRecord>,
 }
 
@@ -30,21 +51,19 @@ pub struct ListTracesQuery {
     pub status: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// Only runs started at or after this time (Unix ms).
+    pub start: Option<i64>,
+    /// Only runs started before this time (Unix ms).
+    pub end: Option<i64>,
 }
 
-/// GET /api/traces - List traces with optional filtering.
+/// GET /api/traces - List the caller's traces with optional filtering.
 pub async fn list(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
     Query(params): Query<ListTracesQuery>,
 ) -> Result<Json<TracesListResponse>, AppError> {
-    let query = TraceQuery {
-        pipeline_id: params.pipeline_id,
-        status: params.status.as_deref().map(TraceStatus::from_str),
-        limit: params.limit.or(Some(50)),
-        offset: params.offset,
-    };
-
-    let traces = state.trace_store.list_traces(&query).map_err(|e| {
+    let traces = state.trace_store.list_traces(&trace_query(params, tenant)).map_err(|e| {
         tracing::error!("Failed to list traces: {}", e);
         AppError::Internal("failed to list traces".into())
     })?;
@@ -52,24 +71,49 @@ pub async fn list(
     Ok(Json(TracesListResponse { traces }))
 }
 
-/// GET /api/traces/:id - Get a single trace with its spans.
+/// The store query for a tenant's listing; only the tenant's own traces match.
+fn trace_query(params: ListTracesQuery, tenant: Tenant) -> TraceQuery {
+    TraceQuery {
+        pipeline_id: params.pipeline_id,
+        status: params.status.as_deref().map(TraceStatus::from_str),
+        limit: params.limit.or(Some(50)),
+        offset: params.offset,
+        start_ms: params.start,
+        end_ms: params.end,
+        owner: tenant.0,
+    }
+}
+
+/// GET /api/traces/:id - Get a single trace with its node and tool call timeline.
 pub async fn get(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
     Path(trace_id): Path<String>,
 ) -> Result<Json<TraceDetailResponse>, AppError> {
-    let trace = state
-        .trace_store
-        .get_trace(&trace_id)
-        .map_err(|e| {
-            tracing::error!("Failed to get trace: {}", e);
-            AppError::Internal("failed to get trace".into())
-        })?
-        .ok_or_else(|| AppError::NotFound("trace not found".into()))?;
+    let trace = owned_trace(&state.trace_store, &tenant, &trace_id)?;
 
     let spans = state.trace_store.get_spans(&trace_id).map_err(|e| {
         tracing::error!("Failed to get spans: {}", e);
         AppError::Internal("failed to get spans".into())
     })?;
+    let mut tool_calls = state.trace_store.get_trace_tool_calls(&trace_id).map_err(|e| {
+        tracing::error!("Failed to get tool calls: {}", e);
+        AppError::Internal("failed to get tool calls".into())
+    })?;
+
+    let spans = spans
+        .into_iter()
+        .map(|span| {
+            let (own, rest) = tool_calls.drain(..).partition(|c| c.span_id == span.span_id);
+            tool_calls = rest;
+            TimelineSpan {
+                offset_ms: span.start_time - trace.timestamp,
+                duration_ms: span.end_time - span.start_time,
+                tool_calls: own,
+                span,
+            }
+        })
+        .collect();
 
     Ok(Json(TraceDetailResponse { trace, spans }))
 }
@@ -77,8 +121,10 @@ pub async fn get(
 /// DELETE /api/traces/:id - Delete a trace.
 pub async fn delete(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
     Path(trace_id): Path<String>,
 ) -> Result<Json<()>, AppError> {
+    owned_trace(&state.trace_store, &tenant, &trace_id)?;
     state.trace_store.delete_trace(&trace_id).map_err(|e| {
         tracing::error!("Failed to delete trace: {}", e);
         AppError::Internal("failed to delete trace".into())
@@ -87,14 +133,160 @@ pub async fn delete(
     Ok(Json(()))
 }
 
-/// GET /api/metrics/summary - Get aggregate metrics.
+/// Looks up a trace, treating one owned by another tenant as missing.
+fn owned_trace(store: &TraceStore, tenant: &Tenant, trace_id: &str) -> Result<TraceRecord, AppError> {
+    store
+        .get_trace(trace_id)
+        .map_err(|e| {
+            tracing::error!("Failed to get trace: {}", e);
+            AppError::Internal("failed to get trace".into())
+        })?
+        .filter(|trace| tenant.owns(trace.owner.as_deref()))
+        .ok_or_else(|| AppError::NotFound("trace not found".into()))
+}
+
+/// GET /api/contents/:hash - Get a span input/output by hash, with every span
+/// of the caller's traces that used it.
+pub async fn content(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Path(hash): Path<String>,
+) -> Result<Json<ContentDetailResponse>, AppError> {
+    let spans = state.trace_store.spans_with_content(&hash, tenant.id()).map_err(|e| {
+        tracing::error!("Failed to get spans for content: {}", e);
+        AppError::Internal("failed to get spans".into())
+    })?;
+    // Content is shared across tenants by hash; only show it to one whose traces used it
+    if tenant.id().is_some() && spans.is_empty() {
+        return Err(AppError::NotFound("content not found".into()));
+    }
+
+    let content = state
+        .trace_store
+        .get_content(&hash)
+        .map_err(|e| {
+            tracing::error!("Failed to get content: {}", e);
+            AppError::Internal("failed to get content".into())
+        })?
+        .ok_or_else(|| AppError::NotFound("content not found".into()))?;
+
+    Ok(Json(ContentDetailResponse { content, spans }))
+}
+
+/// GET /api/metrics/summary - Get aggregate metrics over the caller's traces.
 pub async fn metrics_summary(
     State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
 ) -> Result<Json<fissio_monitor::MetricsSummary>, AppError> {
-    let summary = state.trace_store.get_metrics_summary().map_err(|e| {
+    let summary = state.trace_store.get_metrics_summary(tenant.id()).map_err(|e| {
         tracing::error!("Failed to get metrics summary: {}", e);
         AppError::Internal("failed to get metrics".into())
     })?;
 
     Ok(Json(summary))
 }
+
+/// Query parameters for the per-pipeline cost report.
+#[derive(Debug, Deserialize, Default)]
+pub struct CostQuery {
+    /// Start of the window, Unix milliseconds (default: all time).
+    pub start: Option<i64>,
+    /// End of the window, Unix milliseconds, exclusive (default: now).
+    pub end: Option<i64>,
+}
+
+/// GET /api/metrics/costs - Runs, tokens, and estimated spend per pipeline for the caller's traces.
+pub async fn costs(
+    State(state): State<Arc<ServerState>>,
+    tenant: Tenant,
+    Query(params): Query<CostQuery>,
+) -> Result<Json<Vec<fissio_monitor::PipelineCost>>, AppError> {
+    let costs = state
+        .trace_store
+        .cost_by_pipeline(params.start.unwrap_or(0), params.end.unwrap_or(i64::MAX), tenant.id())
+        .map_err(|e| {
+            tracing::error!("Failed to get pipeline costs: {}", e);
+            AppError::Internal("failed to get costs".into())
+        })?;
+
+    Ok(Json(costs))
+}
+
+/// Query parameters for the usage reconciliation report.
+#[derive(Debug, Deserialize, Default)]
+pub struct ReconciliationQuery {
+    /// Re-run reconciliation for the previous UTC day instead of returning the cached report.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// GET /api/usage/reconciliation - Latest recorded-vs-provider token usage report.
+pub async fn usage_reconciliation(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<ReconciliationQuery>,
+) -> Result<Json<fissio_monitor::ReconciliationReport>, AppError> {
+    if !params.refresh {
+        if let Some(report) = state.usage_report.read().await.clone() {
+            return Ok(Json(report));
+        }
+    }
+
+    let api = UsageApiConfig::from_env()
+        .ok_or_else(|| AppError::NotFound("usage reconciliation requires OPENAI_ADMIN_KEY".into()))?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let (start, end) = previous_day_window(now);
+
+    let report = reconcile_openai(&state, &api, start, end).await.map_err(|e| {
+        tracing::error!("Usage reconciliation failed: {}", e);
+        AppError::Internal("usage reconciliation failed".into())
+    })?;
+
+    *state.usage_report.write().await = Some(report.clone());
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(id: &str, owner: Option<&str>) -> TraceRecord {
+        TraceRecord {
+            trace_id: id.to_string(),
+            pipeline_id: "support".to_string(),
+            pipeline_name: "Support".to_string(),
+            timestamp: 1700000000000,
+            input: "hello".to_string(),
+            output: "hi".to_string(),
+            total_elapsed_ms: 10,
+            total_input_tokens: 1,
+            total_output_tokens: 1,
+            total_tool_calls: 0,
+            total_cost_usd: None,
+            status: TraceStatus::Success,
+            owner: owner.map(String::from),
+        }
+    }
+
+    fn tenant(id: &str) -> Tenant {
+        Tenant(Some(id.to_string()))
+    }
+
+    #[test]
+    fn test_tenants_only_list_and_read_their_own_traces() {
+        let store = TraceStore::in_memory().unwrap();
+        store.insert_trace(&trace("acme-run", Some("acme"))).unwrap();
+        store.insert_trace(&trace("globex-run", Some("globex"))).unwrap();
+
+        let listed = store.list_traces(&trace_query(ListTracesQuery::default(), tenant("acme"))).unwrap();
+        assert_eq!(listed.iter().map(|t| t.trace_id.as_str()).collect::<Vec<_>>(), ["acme-run"]);
+
+        assert!(owned_trace(&store, &tenant("acme"), "acme-run").is_ok());
+        assert!(matches!(owned_trace(&store, &tenant("acme"), "globex-run"), Err(AppError::NotFound(_))));
+        // Without authentication everything is shared
+        assert!(owned_trace(&store, &Tenant(None), "globex-run").is_ok());
+    }
+}
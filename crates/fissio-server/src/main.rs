@@ -1,96 +1,424 @@
 //! HTTP server entry point and Axum router setup.
 //!
-//! Initializes the server state (models, presets, database), configures routes,
-//! and starts the Axum server on port 8000.
+//! Loads the server configuration, initializes the server state (models,
+//! presets, database), configures routes, and starts the Axum server.
 
+mod auth;
+mod config;
 mod db;
 mod dto;
 mod error;
 mod handlers;
+#[cfg(feature = "otel")]
+mod otel;
 mod services;
+mod shared;
+mod tls;
 
-use std::path::Path;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
-use fissio_config::PresetRegistry;
-use fissio_core::ModelConfig;
-use fissio_llm::discover_models;
-use fissio_monitor::TraceStore;
-use fissio_tools::ToolRegistry;
+use fissio_config::{ConfigError, PipelineConfig, PresetRegistry, ResidencyPolicy, SecretResolver};
+use fissio_core::{AzureOpenAiConfig, ModelConfig, ModelPricing, Provider, RateLimitConfig};
+use fissio_engine::{SideEffectLedger, SqliteLedger, ToolPolicy, VramScheduler};
+use fissio_llm::{
+    discover_models_on, model_sizes, ContextManager, EmbeddingClient, InMemoryLlmCache, LlmCache, SpeechClient, SqliteLlmCache,
+    UnifiedLlmClient, DEFAULT_SPEECH_MODEL,
+};
+use fissio_monitor::{CaptureMode, ReconciliationReport, TraceStore};
+use fissio_rag::{DocumentIngestor, Memory, Retriever, SqliteMemory, SqliteVectorStore, VectorSearchTool};
+use fissio_tools::{CommandTool, HostAllowlist, McpToolProvider, PythonTool, SendEmailTool, ToolRegistry, Transcriber};
 
-use crate::dto::{EdgeInfo, NodeInfo, PipelineInfo};
+use crate::config::ServerConfig;
+use crate::dto::{ModelsUpdated, PipelineInfo, ToolDefinition, ToolInfo};
+use crate::services::approval::ApprovalBroker;
+use crate::services::init::InitPayload;
+use crate::services::metrics::ServerMetrics;
+use crate::services::model::ModelPoolManager;
+use crate::services::model_health::ModelHealthMonitor;
+use crate::services::speech::AudioClips;
+use crate::services::degraded::{DegradedMode, ProviderHealth};
+use crate::shared::{EventLog, IdempotencyKeys, RequestQueue, IDEMPOTENCY_TTL};
 use anyhow::Result;
 use axum::body::Body;
+use axum::extract::DefaultBodyLimit;
 use axum::http::{Request, Response};
 use axum::routing::{get, post};
 use axum::Router;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing::{info, warn};
 
-const OLLAMA_HOST: &str = "http://host.docker.internal:11434";
+const AZURE_API_VERSION: &str = "2024-10-21";
 
-/// Returns the list of cloud-hosted models (e.g., OpenAI).
-fn cloud_models() -> Vec<ModelConfig> {
+/// Reads `<PROVIDER>_RPM` and `<PROVIDER>_TPM` into a rate limit, if either is set.
+fn rate_limit_from_env(provider: &str) -> Option<RateLimitConfig> {
+    let read = |suffix: &str| {
+        std::env::var(format!("{}_{}", provider, suffix))
+            .ok()
+            .and_then(|v| v.parse().ok())
+    };
+    let limit = RateLimitConfig {
+        requests_per_minute: read("RPM"),
+        tokens_per_minute: read("TPM"),
+    };
+    (limit != RateLimitConfig::default()).then_some(limit)
+}
+
+/// Reads `<PROVIDER>_REGION`, the region the provider's models are hosted in.
+fn region_from_env(provider: &str) -> Option<String> {
+    std::env::var(format!("{}_REGION", provider)).ok().filter(|r| !r.is_empty())
+}
+
+/// Reads the server-wide residency policy from `RESIDENCY_ALLOW_PROVIDERS`,
+/// `RESIDENCY_DENY_PROVIDERS`, and `RESIDENCY_ALLOW_REGIONS` (comma-separated).
+fn residency_from_env() -> ResidencyPolicy {
+    let list = |var: &str| -> Option<Vec<String>> {
+        let value = std::env::var(var).ok()?;
+        Some(value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+    };
+    let policy = ResidencyPolicy {
+        allow_providers: list("RESIDENCY_ALLOW_PROVIDERS"),
+        deny_providers: list("RESIDENCY_DENY_PROVIDERS").unwrap_or_default(),
+        allow_regions: list("RESIDENCY_ALLOW_REGIONS"),
+    };
+    if !policy.is_empty() {
+        info!("Residency policy: {:?}", policy);
+    }
+    policy
+}
+
+/// Reads `LOCAL_ONLY`. When set, returns the hosts tools may still reach:
+/// `LOCAL_ONLY_ALLOWED_HOSTS` (comma-separated), or loopback only.
+fn local_only_from_env() -> Option<HostAllowlist> {
+    let enabled = std::env::var("LOCAL_ONLY").is_ok_and(|v| matches!(v.as_str(), "1" | "true"));
+    if !enabled {
+        return None;
+    }
+    let hosts = match std::env::var("LOCAL_ONLY_ALLOWED_HOSTS") {
+        Ok(hosts) => HostAllowlist::new(hosts.split(',').map(str::trim).filter(|h| !h.is_empty())),
+        Err(_) => HostAllowlist::loopback(),
+    };
+    info!("Local-only mode: cloud providers disabled, tools may reach {:?}", hosts.hosts());
+    Some(hosts)
+}
+
+/// Creates the GPU memory scheduler for Ollama models when `OLLAMA_VRAM_GB` is set.
+async fn vram_scheduler_from_env(ollama_host: Option<&str>) -> Option<Arc<VramScheduler>> {
+    let gb: f64 = std::env::var("OLLAMA_VRAM_GB").ok()?.parse().ok()?;
+    let budget_bytes = (gb * 1024.0 * 1024.0 * 1024.0) as u64;
+    let Some(ollama_host) = ollama_host else {
+        warn!("OLLAMA_VRAM_GB is set but no Ollama host is configured");
+        return None;
+    };
+    match VramScheduler::from_ollama(ollama_host, budget_bytes).await {
+        Ok(scheduler) => Some(Arc::new(scheduler)),
+        Err(e) => {
+            warn!("VRAM scheduling disabled: {}", e);
+            None
+        }
+    }
+}
+
+/// Creates the model pool, evicting idle models to stay within the VRAM budget if one is set.
+async fn model_pool(config: &ServerConfig, vram: Option<&VramScheduler>) -> ModelPoolManager {
+    let pool = ModelPoolManager::new(config.model_pool.clone());
+    let (Some(vram), Some(host)) = (vram, config.primary_ollama_host()) else {
+        return pool;
+    };
+    match model_sizes(host).await {
+        Ok(sizes) => pool.with_vram_budget(vram.budget_bytes(), sizes),
+        Err(e) => {
+            warn!("Model pool can't evict by size: {}", e);
+            pool
+        }
+    }
+}
+
+/// Returns the cloud-hosted models: the settings file's catalog, or else the
+/// built-in one, plus any Azure OpenAI deployments.
+fn cloud_models(configured: Option<&[ModelConfig]>) -> Vec<ModelConfig> {
+    let mut models = match configured {
+        Some(models) => models.iter().cloned().map(with_provider_env).collect(),
+        None => builtin_cloud_models(),
+    };
+    models.extend(azure_models());
+    models
+}
+
+/// Fills a configured model's unset rate limit and region from its provider's
+/// `<PROVIDER>_RPM`, `_TPM`, and `_REGION` variables.
+fn with_provider_env(mut model: ModelConfig) -> ModelConfig {
+    let prefix = match &model.provider {
+        _ if model.azure.is_some() => "AZURE_OPENAI",
+        Some(Provider::OpenAI) => "OPENAI",
+        Some(Provider::Anthropic) => "ANTHROPIC",
+        _ => return model,
+    };
+    model.rate_limit = model.rate_limit.or_else(|| rate_limit_from_env(prefix));
+    model.region = model.region.or_else(|| region_from_env(prefix));
+    model
+}
+
+/// The cloud models offered when the settings file doesn't list any.
+fn builtin_cloud_models() -> Vec<ModelConfig> {
     vec![
         ModelConfig {
             id: "openai-gpt5".into(),
             name: "GPT-5.2 (OpenAI)".into(),
             model: "gpt-5.2-2025-12-11".into(),
             api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::OpenAI),
+            rate_limit: rate_limit_from_env("OPENAI"),
+            azure: None,
+            region: region_from_env("OPENAI"),
+            context_window: Some(400_000),
+            pricing: Some(ModelPricing::new(0.00175, 0.014)),
         },
         ModelConfig {
             id: "openai-codex".into(),
             name: "GPT-5.2 Codex (OpenAI)".into(),
             model: "gpt-5.2-codex".into(),
             api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::OpenAI),
+            rate_limit: rate_limit_from_env("OPENAI"),
+            azure: None,
+            region: region_from_env("OPENAI"),
+            context_window: Some(400_000),
+            pricing: Some(ModelPricing::new(0.00175, 0.014)),
         },
         ModelConfig {
             id: "anthropic-opus".into(),
             name: "Claude Opus 4.5 (Anthropic)".into(),
             model: "claude-opus-4-5-20251101".into(),
             api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::Anthropic),
+            rate_limit: rate_limit_from_env("ANTHROPIC"),
+            azure: None,
+            region: region_from_env("ANTHROPIC"),
+            context_window: Some(200_000),
+            pricing: Some(ModelPricing::new(0.005, 0.025)),
         },
         ModelConfig {
             id: "anthropic-sonnet".into(),
             name: "Claude Sonnet 4.5 (Anthropic)".into(),
             model: "claude-sonnet-4-5-20250929".into(),
             api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::Anthropic),
+            rate_limit: rate_limit_from_env("ANTHROPIC"),
+            azure: None,
+            region: region_from_env("ANTHROPIC"),
+            context_window: Some(200_000),
+            pricing: Some(ModelPricing::new(0.003, 0.015)),
         },
         ModelConfig {
             id: "anthropic-haiku".into(),
             name: "Claude Haiku 4.5 (Anthropic)".into(),
             model: "claude-haiku-4-5-20251001".into(),
             api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::Anthropic),
+            rate_limit: rate_limit_from_env("ANTHROPIC"),
+            azure: None,
+            region: region_from_env("ANTHROPIC"),
+            context_window: Some(200_000),
+            pricing: Some(ModelPricing::new(0.001, 0.005)),
         },
     ]
 }
 
+/// Returns one model per deployment in `AZURE_OPENAI_DEPLOYMENTS`.
+///
+/// Requires `AZURE_OPENAI_ENDPOINT`; `AZURE_OPENAI_API_VERSION` defaults to a current GA version.
+fn azure_models() -> Vec<ModelConfig> {
+    let Ok(endpoint) = std::env::var("AZURE_OPENAI_ENDPOINT") else {
+        return Vec::new();
+    };
+    let api_version =
+        std::env::var("AZURE_OPENAI_API_VERSION").unwrap_or_else(|_| AZURE_API_VERSION.to_string());
+    std::env::var("AZURE_OPENAI_DEPLOYMENTS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|deployment| ModelConfig {
+            id: format!("azure-{}", deployment),
+            name: format!("{} (Azure OpenAI)", deployment),
+            model: deployment.to_string(),
+            api_base: None,
+            mirrors: Vec::new(),
+            provider: Some(Provider::OpenAI),
+            rate_limit: rate_limit_from_env("AZURE_OPENAI"),
+            azure: Some(AzureOpenAiConfig {
+                endpoint: endpoint.clone(),
+                deployment: deployment.to_string(),
+                api_version: api_version.clone(),
+            }),
+            region: region_from_env("AZURE_OPENAI"),
+            context_window: None,
+            pricing: None,
+        })
+        .collect()
+}
+
 /// Shared server state accessible from all handlers.
 pub struct ServerState {
-    pub models: Vec<ModelConfig>,
-    pub presets: PresetRegistry,
-    pub templates: Vec<PipelineInfo>,
+    /// Cloud models and those discovered on `ollama_hosts`, replaced when discovery is refreshed.
+    pub models: std::sync::RwLock<Arc<Vec<ModelConfig>>>,
+    pub ollama_hosts: Vec<String>,
+    /// Announces catalog changes to clients following GET /models/events.
+    pub model_events: broadcast::Sender<ModelsUpdated>,
+    /// Pipeline presets, reloaded when files in `presets_dir` change.
+    pub presets: RwLock<PresetRegistry>,
+    pub templates: RwLock<Vec<PipelineInfo>>,
+    pub presets_dir: PathBuf,
     pub configs: RwLock<Vec<PipelineInfo>>,
     pub db: Mutex<rusqlite::Connection>,
-    pub tool_registry: ToolRegistry,
+    /// Built-in and configured tools, plus those added through POST /tools/register.
+    pub tool_registry: RwLock<ToolRegistry>,
     pub trace_store: Arc<TraceStore>,
+    pub usage_report: RwLock<Option<ReconciliationReport>>,
+    pub provider_health: ProviderHealth,
+    /// Health checks of each model, so nodes with fallbacks skip models that are down.
+    pub model_health: ModelHealthMonitor,
+    pub init_cache: RwLock<Option<Arc<InitPayload>>>,
+    pub llm_cache: Option<Arc<dyn LlmCache>>,
+    /// Results of tool calls with side effects, by idempotency key, so a retried request doesn't repeat them.
+    pub side_effects: Option<Arc<dyn SideEffectLedger>>,
+    /// Shared degraded-mode queue; `None` uses the local SQLite queue.
+    pub request_queue: Option<Arc<dyn RequestQueue>>,
+    /// Events streamed by each chat run, for clients resuming after a disconnect.
+    pub event_log: Arc<dyn EventLog>,
+    /// The run each idempotency key started, so retried requests don't run twice.
+    pub idempotency_keys: Arc<dyn IdempotencyKeys>,
+    /// Capture mode for nodes that don't set `observe.capture`.
+    pub trace_capture: CaptureMode,
+    /// Whether engines log box-drawn banners rather than structured events.
+    pub pretty_logs: bool,
+    /// Tool access rules applied to every pipeline run.
+    pub tool_policy: ToolPolicy,
+    /// Tool calls waiting for a user's approval.
+    pub approvals: Arc<ApprovalBroker>,
+    /// Providers and regions every model call must keep to.
+    pub residency: ResidencyPolicy,
+    /// Hosts tools may reach when `LOCAL_ONLY` is set; `None` allows cloud providers and all hosts.
+    pub local_hosts: Option<HostAllowlist>,
+    /// Loaded local models and the runs using each, so models aren't unloaded mid-run.
+    pub model_pool: Arc<ModelPoolManager>,
+    /// Keeps parallel nodes within the GPU memory budget for Ollama models.
+    pub vram: Option<Arc<VramScheduler>>,
+    /// Indexes uploads from POST /documents; `None` when no embedding model is configured.
+    pub documents: Option<Arc<DocumentIngestor>>,
+    /// Transcribes voice messages sent to POST /chat; `None` when no backend is configured.
+    pub transcriber: Option<Transcriber>,
+    /// Reads answers aloud for chats that set `audio_output`; `None` when no speech endpoint is configured.
+    pub speech: Option<SpeechClient>,
+    /// Spoken answers waiting to be fetched from GET /audio/{id}.
+    pub audio_clips: AudioClips,
+    /// Summarizes older history in direct chats that outgrow the model's context window.
+    pub context: ContextManager,
+    /// Long-term memory for pipeline runs, scoped by user or session.
+    pub memory: Option<Arc<dyn Memory>>,
+    /// Prometheus counters and histograms served on GET /metrics.
+    pub metrics: Arc<ServerMetrics>,
+    /// Fills `${VAR}` placeholders in pipelines before they run; `None` leaves them as written.
+    pub secrets: Option<SecretResolver>,
 }
 
 impl ServerState {
     /// Gets a model by ID, falling back to the first available model.
     pub fn get_model(&self, model_id: &str) -> ModelConfig {
-        self.models
+        let models = self.models();
+        models
             .iter()
             .find(|m| m.id == model_id)
-            .or_else(|| self.models.first())
+            .or_else(|| models.first())
             .cloned()
             .expect("at least one model must be configured")
     }
 
+    /// The current model catalog.
+    pub fn models(&self) -> Arc<Vec<ModelConfig>> {
+        self.models.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns every pipeline a `Pipeline` node may reference: presets and the
+    /// saved configs `tenant` can see.
+    ///
+    /// Placeholders are filled in; a pipeline whose placeholders can't be is
+    /// returned as written.
+    pub async fn pipeline_catalog(&self, tenant: &auth::Tenant) -> Vec<PipelineConfig> {
+        let saved = self.configs.read().await;
+        self.presets
+            .read()
+            .await
+            .list()
+            .into_iter()
+            .cloned()
+            .chain(
+                saved
+                    .iter()
+                    .filter(|p| tenant.can_read(p.owner.as_deref()))
+                    .map(services::chat::pipeline_info_to_config),
+            )
+            .map(|config| {
+                self.resolve_secrets(config.clone()).unwrap_or_else(|e| {
+                    warn!("Pipeline {}: {}", config.id, e);
+                    config
+                })
+            })
+            .collect()
+    }
+
+    /// Fills a pipeline's `${VAR}` placeholders from the variables `PIPELINE_ENV_ALLOWLIST` allows.
+    pub fn resolve_secrets(&self, mut config: PipelineConfig) -> Result<PipelineConfig, ConfigError> {
+        if let Some(secrets) = &self.secrets {
+            secrets.resolve(&mut config)?;
+        }
+        Ok(config)
+    }
+
+    /// Returns the schemas and metadata of every registered tool.
+    pub async fn tool_infos(&self) -> Vec<ToolInfo> {
+        let registry = self.tool_registry.read().await;
+        registry
+            .list()
+            .into_iter()
+            .map(|s| ToolInfo {
+                metadata: registry.metadata(&s.name).unwrap_or_default(),
+                name: s.name,
+                description: s.description,
+                parameters: s.parameters,
+            })
+            .collect()
+    }
+
+    /// Queues a chat request (as JSON) to replay when providers recover. Returns its ID.
+    pub async fn enqueue_request(&self, request_json: String) -> Result<i64> {
+        if let Some(queue) = &self.request_queue {
+            return queue.enqueue(request_json).await;
+        }
+        let db = self.db.lock().map_err(|_| anyhow::anyhow!("database lock error"))?;
+        db::enqueue_request(&db, &request_json)
+    }
+
+    /// Removes and returns all queued requests, oldest first.
+    pub async fn take_queued_requests(&self) -> Result<Vec<(i64, String)>> {
+        if let Some(queue) = &self.request_queue {
+            return queue.take_all().await;
+        }
+        let db = self.db.lock().map_err(|_| anyhow::anyhow!("database lock error"))?;
+        db::take_queued_requests(&db)
+    }
+
     /// Acquires the database lock, converting poison errors to AppError.
     pub fn db_lock(&self) -> Result<std::sync::MutexGuard<'_, rusqlite::Connection>, error::AppError> {
         self.db.lock().map_err(|e| {
@@ -104,21 +432,39 @@ impl ServerState {
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
 
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "info".parse().unwrap()),
-        )
-        .compact()
-        .init();
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".parse().unwrap());
+    // Events logged inside a node carry the node span's fields under `span`
+    let (fmt, json) = if json_logs_from_env() {
+        (None, Some(tracing_subscriber::fmt::layer().json().with_current_span(true).with_span_list(false)))
+    } else {
+        (Some(tracing_subscriber::fmt::layer().with_target(false).compact()), None)
+    };
+    #[cfg(feature = "otel")]
+    let (otel, _otel_guard) = otel::layer_from_env().unzip();
+    #[cfg(not(feature = "otel"))]
+    let otel: Option<tracing_subscriber::layer::Identity> = None;
+    tracing_subscriber::registry().with(filter).with(fmt).with(json).with(otel).init();
 
-    let state = Arc::new(init_server_state().await);
+    let server_config = ServerConfig::load()?;
+    let state = Arc::new(init_server_state(&server_config).await);
+    if let Err(e) = services::init::payload(&state).await {
+        warn!("Failed to warm /init cache: {:?}", e);
+    }
+    services::usage::spawn_daily_reconciliation(state.clone());
+    services::degraded::spawn_health_monitor(state.clone());
+    services::model_health::spawn_model_health_monitor(state.clone());
+    services::model::spawn_keep_alive_sweeper(state.clone());
+    services::model::spawn_model_refresh(state.clone());
+    services::presets::spawn_preset_watcher(state.clone());
+    services::schedules::spawn_scheduler(state.clone());
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = server_config.cors.layer();
+    let security_headers = axum::middleware::from_fn_with_state(
+        server_config.security_headers.headers(),
+        config::apply_security_headers,
+    );
+    let request_metrics =
+        axum::middleware::from_fn_with_state(state.metrics.clone(), services::metrics::track_requests);
 
     let trace_layer = TraceLayer::new_for_http()
         .make_span_with(|req: &Request<Body>| {
@@ -138,45 +484,399 @@ async fn main() -> Result<()> {
         });
 
     let logged_routes = Router::new()
-        .route("/chat", post(handlers::chat::chat))
+        .route("/chat", post(handlers::chat::chat).layer(DefaultBodyLimit::max(handlers::chat::MAX_CHAT_BYTES)))
+        .route("/chat/runs/{run_id}/events", get(handlers::chat::resume))
+        .route("/chat/approvals/{approval_id}", post(handlers::chat::approve))
+        .route("/audio/{id}", get(handlers::chat::audio))
         .route("/init", get(handlers::init::init))
+        .route("/models/health", get(handlers::model::health))
+        .route("/models/pool", get(handlers::model::pool))
+        .route("/models/refresh", post(handlers::model::refresh))
+        .route("/models/events", get(handlers::model::events))
         .route("/models/{id}/wake", post(handlers::model::wake))
         .route("/models/{id}", axum::routing::delete(handlers::model::unload))
         .route("/pipelines", get(handlers::pipeline::list))
         .route("/pipelines/save", post(handlers::pipeline::save))
         .route("/pipelines/delete", post(handlers::pipeline::delete))
+        .route("/pipelines/import", post(handlers::pipeline::import))
+        .route("/pipelines/{id}/export", get(handlers::pipeline::export))
+        .route("/pipelines/{id}/graph", get(handlers::pipeline::graph))
+        .route("/pipelines/{id}/versions", get(handlers::pipeline::versions))
+        .route("/pipelines/{id}/rollback/{version}", post(handlers::pipeline::rollback))
+        .route("/pipelines/{id}/plan", get(handlers::pipeline::plan))
+        .route("/pipelines/{id}/evaluate", post(handlers::pipeline::evaluate))
+        .route("/presets/reload", post(handlers::pipeline::reload_presets))
+        .route("/compare", post(handlers::pipeline::compare))
+        .route("/schedules", get(handlers::schedules::list).post(handlers::schedules::create))
+        .route("/schedules/{id}", axum::routing::delete(handlers::schedules::delete))
+        .route("/schedules/{id}/runs", get(handlers::schedules::runs))
+        .route("/sessions", get(handlers::sessions::list).post(handlers::sessions::create))
+        .route("/sessions/{id}", get(handlers::sessions::get).delete(handlers::sessions::delete))
         .route("/tools", get(handlers::tools::list))
+        .route("/tools/register", post(handlers::tools::register))
+        .route(
+            "/documents",
+            post(handlers::documents::upload).layer(DefaultBodyLimit::max(handlers::documents::MAX_UPLOAD_BYTES)),
+        )
+        .route("/documents/{id}", axum::routing::delete(handlers::documents::delete))
         .route("/api/traces", get(handlers::traces::list))
         .route("/api/traces/{id}", get(handlers::traces::get))
         .route("/api/traces/{id}", axum::routing::delete(handlers::traces::delete))
+        .route("/api/contents/{hash}", get(handlers::traces::content))
         .route("/api/metrics/summary", get(handlers::traces::metrics_summary))
+        .route("/api/metrics/costs", get(handlers::traces::costs))
+        .route("/api/usage/reconciliation", get(handlers::traces::usage_reconciliation))
+        .route("/api/providers/health", get(handlers::provider_health))
+        .route("/admin/quotas", get(handlers::quotas::list))
+        .route(
+            "/admin/quotas/{key}",
+            get(handlers::quotas::get).put(handlers::quotas::set).delete(handlers::quotas::delete),
+        )
+        .route_layer(request_metrics)
         .layer(trace_layer);
 
-    let app = Router::new()
-        .merge(logged_routes)
+    let mut app = Router::new().merge(logged_routes).route("/metrics", get(handlers::metrics));
+    if server_config.auth.enabled() {
+        let auth = Arc::new(server_config.auth.clone());
+        app = app.route_layer(axum::middleware::from_fn_with_state(auth, auth::require_auth));
+    }
+    let app = app
         .route("/health", get(handlers::health))
         .layer(cors)
+        .layer(security_headers)
         .with_state(state);
 
-    let addr = "0.0.0.0:8000";
-    info!("Starting server on {}", addr);
+    let addr = server_config.bind_addr;
+    if let Some(tls_config) = server_config.tls {
+        return tls::serve(addr, app, tls_config).await;
+    }
 
+    info!("Starting server on {}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
 
     Ok(())
 }
 
+/// Opens the side effect ledger at `SIDE_EFFECT_LEDGER_PATH`.
+fn side_effect_ledger_from_env() -> Option<Arc<dyn SideEffectLedger>> {
+    let path = std::env::var("SIDE_EFFECT_LEDGER_PATH").unwrap_or_else(|_| "data/side_effects.db".into());
+    match SqliteLedger::new(&path) {
+        Ok(ledger) => {
+            let ledger = ledger.with_ttl(IDEMPOTENCY_TTL);
+            info!("Side effect ledger at {}", path);
+            Some(Arc::new(ledger))
+        }
+        Err(e) => {
+            warn!("Failed to open side effect ledger at {}; tools with side effects may repeat: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Creates the LLM response cache selected by `LLM_CACHE` (`memory` or `sqlite`).
+fn llm_cache_from_env() -> Option<Arc<dyn LlmCache>> {
+    match std::env::var("LLM_CACHE").as_deref() {
+        Ok("memory") => {
+            info!("LLM response cache enabled (in-memory)");
+            Some(Arc::new(InMemoryLlmCache::new()))
+        }
+        Ok("sqlite") => {
+            let path = std::env::var("LLM_CACHE_PATH").unwrap_or_else(|_| "data/llm_cache.db".into());
+            match SqliteLlmCache::new(&path) {
+                Ok(cache) => {
+                    info!("LLM response cache enabled at {}", path);
+                    Some(Arc::new(cache))
+                }
+                Err(e) => {
+                    warn!("Failed to open LLM cache at {}: {}", path, e);
+                    None
+                }
+            }
+        }
+        Ok("") | Ok("off") | Err(_) => None,
+        Ok(other) => {
+            warn!("Unknown LLM_CACHE '{}', caching disabled", other);
+            None
+        }
+    }
+}
+
+/// Registers the tools of every MCP server in the `mcpServers` file at `path`.
+/// A server that fails to start is skipped so the others stay available.
+async fn register_mcp_servers(registry: &mut ToolRegistry, path: &str) {
+    let providers = match McpToolProvider::from_config_file(path) {
+        Ok(providers) => providers,
+        Err(e) => {
+            warn!("Failed to load MCP servers: {}", e);
+            return;
+        }
+    };
+    for provider in providers {
+        if let Err(e) = provider.register_into(registry).await {
+            warn!("MCP server '{}' unavailable: {}", provider.name(), e);
+        }
+    }
+}
+
+/// Registers the tool definitions saved through POST /tools/register.
+/// A definition that fails to load is skipped so the others stay available.
+async fn register_saved_tools(registry: &mut ToolRegistry, saved: Vec<(String, String)>, local_hosts: Option<&HostAllowlist>) {
+    for (name, json) in saved {
+        let definition = match serde_json::from_str::<ToolDefinition>(&json) {
+            Ok(definition) => definition,
+            Err(e) => {
+                warn!("Registered tool '{}' is invalid: {}", name, e);
+                continue;
+            }
+        };
+        let built = match services::tools::validate(&definition, local_hosts) {
+            Ok(()) => services::tools::build(&definition, local_hosts).await,
+            Err(e) => Err(e),
+        };
+        match built {
+            Ok(tools) => registry.extend(tools),
+            Err(e) => warn!("Registered tool '{}' unavailable: {}", name, e),
+        }
+    }
+}
+
+/// Registers the WASM tools listed in the JSON file at `path`.
+/// A module that fails to load is skipped so the others stay available.
+#[cfg(feature = "wasm")]
+fn register_wasm_tools(registry: &mut ToolRegistry, path: &str) {
+    let specs = match fissio_tools::WasmToolSpec::from_config_file(path) {
+        Ok(specs) => specs,
+        Err(e) => {
+            warn!("Failed to load WASM tools: {}", e);
+            return;
+        }
+    };
+    for spec in specs {
+        let name = spec.name.clone();
+        match fissio_tools::WasmTool::new(spec) {
+            Ok(tool) => registry.register(tool),
+            Err(e) => warn!("WASM tool '{}' unavailable: {}", name, e),
+        }
+    }
+}
+
+/// Builds the `sql_query` tool when `SQL_TOOL_URL` names a database.
+#[cfg(feature = "sql")]
+fn sql_tool_from_env() -> Option<fissio_tools::SqlQueryTool> {
+    let url = std::env::var("SQL_TOOL_URL").ok().filter(|v| !v.trim().is_empty())?;
+    let mut tool = fissio_tools::SqlQueryTool::new(fissio_tools::SqlConnection::from_url(url.trim()));
+    if let Some(rows) = std::env::var("SQL_TOOL_MAX_ROWS").ok().and_then(|v| v.parse().ok()) {
+        tool = tool.with_max_rows(rows);
+    }
+    if let Ok(description) = std::env::var("SQL_TOOL_DESCRIPTION") {
+        tool = tool.with_description(description);
+    }
+    Some(tool)
+}
+
+/// Builds the `run_command` tool when `RUN_COMMAND_ALLOW` lists commands it may run.
+fn command_tool_from_env() -> Option<CommandTool> {
+    let allowed = std::env::var("RUN_COMMAND_ALLOW").ok().filter(|v| !v.trim().is_empty())?;
+    let root = std::env::var("RUN_COMMAND_DIR").unwrap_or_else(|_| ".".into());
+    let mut tool = CommandTool::new(&root);
+    for command in allowed.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        tool = tool.allow(command);
+    }
+    if let Ok(names) = std::env::var("RUN_COMMAND_ENV") {
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            tool = tool.pass_env(name);
+        }
+    }
+    if let Some(secs) = std::env::var("RUN_COMMAND_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()) {
+        tool = tool.with_timeout(Duration::from_secs(secs));
+    }
+    info!("run_command enabled in {} for: {}", root, allowed);
+    Some(tool)
+}
+
+/// Builds the `python_exec` tool when `PYTHON_EXEC_DIR` names its working directory.
+fn python_tool_from_env() -> Option<PythonTool> {
+    let dir = std::env::var("PYTHON_EXEC_DIR").ok().filter(|v| !v.trim().is_empty())?;
+    let mut tool = PythonTool::new(&dir);
+    if let Ok(interpreter) = std::env::var("PYTHON_EXEC_INTERPRETER") {
+        tool = tool.with_interpreter(interpreter);
+    }
+    if let Some(secs) = std::env::var("PYTHON_EXEC_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()) {
+        tool = tool.with_timeout(Duration::from_secs(secs));
+    }
+    if let Some(mb) = std::env::var("PYTHON_EXEC_MEMORY_MB").ok().and_then(|v| v.parse::<u64>().ok()) {
+        tool = tool.with_memory_limit(mb * 1024 * 1024);
+    }
+    info!("python_exec enabled in {}", dir);
+    Some(tool)
+}
+
+/// Builds the transcriber for voice messages and `transcribe_audio` from
+/// `WHISPER_CPP_URL` or `OPENAI_API_KEY`. Local-only mode allows whisper.cpp only.
+fn transcriber_from_env(local_only: bool) -> Option<Transcriber> {
+    let transcriber = Transcriber::from_env()?;
+    if local_only && !transcriber.is_local() {
+        warn!("LOCAL_ONLY is set, so OpenAI transcription is disabled; set WHISPER_CPP_URL");
+        return None;
+    }
+    info!("Transcribing audio with {}", if transcriber.is_local() { "whisper.cpp" } else { "OpenAI" });
+    Some(transcriber)
+}
+
+/// Builds the speech client for `audio_output` from `TTS_MODEL` and `TTS_VOICE`,
+/// calling `TTS_API_BASE` if set, else OpenAI with `OPENAI_API_KEY`. In local-only
+/// mode, `TTS_API_BASE` must be an allowed host.
+fn speech_from_env(local_hosts: Option<&HostAllowlist>) -> Option<SpeechClient> {
+    let model = std::env::var("TTS_MODEL").ok().filter(|m| !m.is_empty());
+    let mut client = SpeechClient::openai(model.as_deref().unwrap_or(DEFAULT_SPEECH_MODEL));
+    if let Some(voice) = std::env::var("TTS_VOICE").ok().filter(|v| !v.is_empty()) {
+        client = client.with_voice(voice);
+    }
+    match std::env::var("TTS_API_BASE").ok().filter(|u| !u.is_empty()) {
+        Some(base) => {
+            if let Some(Err(e)) = local_hosts.map(|hosts| hosts.check(&base)) {
+                warn!("LOCAL_ONLY is set, so TTS_API_BASE is disabled: {}", e);
+                return None;
+            }
+            client = client.with_base_url(base);
+        }
+        None if local_hosts.is_some() => return None,
+        None if std::env::var("OPENAI_API_KEY").map_or(true, |k| k.is_empty()) => return None,
+        None => {}
+    }
+    info!("Synthesizing speech with {} at {}", client.model(), client.base_url());
+    Some(client)
+}
+
+/// Builds a retriever over the SQLite vector store at `VECTOR_STORE_PATH` when
+/// `EMBEDDING_MODEL` names a model, from OpenAI or (with `EMBEDDING_PROVIDER=ollama`)
+/// the first Ollama host.
+fn retriever_from_env(local_only: bool, ollama_host: Option<&str>) -> Option<Arc<Retriever>> {
+    let model = std::env::var("EMBEDDING_MODEL").ok().filter(|v| !v.trim().is_empty())?;
+    let embedder = match std::env::var("EMBEDDING_PROVIDER").as_deref() {
+        Ok("ollama") => match ollama_host {
+            Some(host) => EmbeddingClient::ollama(host, model.trim()),
+            None => {
+                warn!("EMBEDDING_PROVIDER=ollama but no Ollama host is configured");
+                return None;
+            }
+        },
+        _ if local_only => {
+            warn!("LOCAL_ONLY is set, so OpenAI embeddings are disabled; set EMBEDDING_PROVIDER=ollama");
+            return None;
+        }
+        _ => EmbeddingClient::openai(model.trim()),
+    };
+    let path = std::env::var("VECTOR_STORE_PATH").unwrap_or_else(|_| "data/vectors.db".into());
+    let store = match SqliteVectorStore::open(&path) {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Vector store unavailable at {}: {}", path, e);
+            return None;
+        }
+    };
+    info!("Vector store at {} (embeddings: {})", path, model.trim());
+    Some(Arc::new(Retriever::new(Arc::new(store), Arc::new(embedder))))
+}
+
+/// Reads `VECTOR_SEARCH_COLLECTION`, the collection searched and uploaded to (default `docs`).
+fn vector_collection_from_env() -> String {
+    std::env::var("VECTOR_SEARCH_COLLECTION").map(|c| c.trim().to_string()).unwrap_or_else(|_| "docs".into())
+}
+
+/// Builds the `vector_search` tool over the configured collection.
+fn vector_search_tool(retriever: &Arc<Retriever>) -> VectorSearchTool {
+    let mut tool = VectorSearchTool::new(retriever.clone(), vector_collection_from_env());
+    if let Ok(description) = std::env::var("VECTOR_SEARCH_DESCRIPTION") {
+        tool = tool.with_description(description);
+    }
+    tool
+}
+
+/// Builds the history manager for direct chats. `CONTEXT_SUMMARY_MODEL` names the
+/// model that summarizes older turns; without it, each chat's own model does.
+fn context_manager_from_env(models: &[ModelConfig], residency: &ResidencyPolicy) -> ContextManager {
+    let context = ContextManager::new();
+    let Ok(id) = std::env::var("CONTEXT_SUMMARY_MODEL") else {
+        return context;
+    };
+    let Some(model) = models.iter().find(|m| m.id == id) else {
+        warn!("CONTEXT_SUMMARY_MODEL '{}' is not a configured model; chats summarize with their own model", id);
+        return context;
+    };
+    if let Err(reason) = residency.check(&model.provider_name(), model.region.as_deref()) {
+        warn!("CONTEXT_SUMMARY_MODEL '{}' is not allowed by the residency policy ({}); chats summarize with their own model", id, reason);
+        return context;
+    }
+    info!("Summarizing long chat histories with {}", model.name);
+    context.with_summarizer(UnifiedLlmClient::from_config(model))
+}
+
+/// Opens the pipelines' long-term memory at `MEMORY_DATABASE_URL` (default `data/memory.db`).
+fn memory_from_env() -> Option<Arc<dyn Memory>> {
+    let path = std::env::var("MEMORY_DATABASE_URL").unwrap_or_else(|_| "data/memory.db".into());
+    match SqliteMemory::open(&path) {
+        Ok(memory) => {
+            info!("Agent memory at {}", path);
+            Some(Arc::new(memory))
+        }
+        Err(e) => {
+            warn!("Agent memory unavailable at {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Reads `LOG_FORMAT`: `json` for JSON log lines and structured engine events,
+/// anything else for the readable default.
+fn json_logs_from_env() -> bool {
+    std::env::var("LOG_FORMAT").is_ok_and(|v| v.eq_ignore_ascii_case("json"))
+}
+
+/// Reads `TRACE_CAPTURE` (`off`, `preview`, or `full`), defaulting to full capture.
+fn trace_capture_from_env() -> CaptureMode {
+    match std::env::var("TRACE_CAPTURE") {
+        Ok(value) if !value.is_empty() => value.parse().unwrap_or_else(|e| {
+            warn!("{}, capturing full node I/O", e);
+            CaptureMode::default()
+        }),
+        _ => CaptureMode::default(),
+    }
+}
+
+/// Reads `PIPELINE_ENV_ALLOWLIST`, the comma-separated environment variables
+/// (or `PREFIX_*` patterns) pipeline placeholders may read.
+fn secrets_from_env() -> Option<SecretResolver> {
+    let allowed: Vec<String> = std::env::var("PIPELINE_ENV_ALLOWLIST")
+        .ok()?
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if allowed.is_empty() {
+        return None;
+    }
+    info!("Pipeline placeholders may read: {}", allowed.join(", "));
+    Some(SecretResolver::new(allowed))
+}
+
 /// Initializes the server state: discovers models, loads presets, and seeds the database.
-async fn init_server_state() -> ServerState {
-    let discovery_future = discover_models(OLLAMA_HOST);
+async fn init_server_state(config: &ServerConfig) -> ServerState {
+    let discovery_future = discover_models_on(&config.ollama_hosts);
 
-    let mut models = cloud_models();
+    let local_hosts = local_only_from_env();
+    let mut residency = residency_from_env();
+    let mut models = Vec::new();
+    match local_hosts {
+        Some(_) => residency.allow_regions = Some(vec!["local".into()]),
+        None => models.extend(cloud_models(config.cloud_models.as_deref())),
+    }
     match discovery_future.await {
         Ok(ollama_models) => {
             info!("Found {} local Ollama models", ollama_models.len());
             for m in &ollama_models {
-                info!("  - {} ({})", m.name, m.id);
+                info!("  - {} ({}, {} hosts)", m.name, m.id, m.mirrors.len() + 1);
             }
             models.extend(ollama_models);
         }
@@ -186,66 +886,113 @@ async fn init_server_state() -> ServerState {
     }
 
     // Load pipeline presets
-    let presets_dir = Path::new("presets");
-    let presets = PresetRegistry::load_from_dir(presets_dir).unwrap_or_else(|e| {
+    let presets_dir = config.presets_dir.clone();
+    let presets = PresetRegistry::load_from_dir(&presets_dir).unwrap_or_else(|e| {
         warn!("Failed to load presets: {}", e);
         PresetRegistry::new()
     });
 
-    let templates: Vec<PipelineInfo> = presets
-        .list()
-        .iter()
-        .map(|p| PipelineInfo {
-            id: p.id.clone(),
-            name: p.name.clone(),
-            description: p.description.clone(),
-            nodes: p.nodes.iter().map(|n| NodeInfo {
-                id: n.id.clone(),
-                node_type: n.node_type.to_string(),
-                model: n.model.clone(),
-                prompt: n.prompt.clone(),
-                tools: if n.tools.is_empty() { None } else { Some(n.tools.clone()) },
-                x: None,
-                y: None,
-            }).collect(),
-            edges: p.edges.iter().map(|e| EdgeInfo {
-                from: serde_json::Value::from(&e.from),
-                to: serde_json::Value::from(&e.to),
-                edge_type: if e.edge_type == fissio_config::EdgeType::Direct {
-                    None
-                } else {
-                    Some(e.edge_type.to_string())
-                },
-            }).collect(),
-            layout: None,
-        })
-        .collect();
+    let templates = services::presets::templates(&presets);
 
     info!("Loaded {} pipeline templates", templates.len());
     for p in &templates {
         info!("  - {} ({})", p.name, p.id);
     }
 
-    let db_path = std::env::var("DATABASE_URL").unwrap_or_else(|_| "data/pipelines.db".into());
-    let conn = db::init_db(&db_path).expect("failed to initialize database");
+    let conn = db::init_db(&config.database_url).expect("failed to initialize database");
     db::seed_examples(&conn).expect("failed to seed examples");
     let configs = db::list_user_pipelines(&conn);
     info!("Loaded {} saved configs", configs.len());
 
-    let tool_registry = ToolRegistry::with_defaults();
+    let mut tool_registry = ToolRegistry::with_defaults();
+    if let Ok(path) = std::env::var("MCP_CONFIG") {
+        register_mcp_servers(&mut tool_registry, &path).await;
+    }
+    #[cfg(feature = "wasm")]
+    if let Ok(path) = std::env::var("WASM_TOOLS") {
+        register_wasm_tools(&mut tool_registry, &path);
+    }
+    if let Some(tool) = command_tool_from_env() {
+        tool_registry.register(tool);
+    }
+    if let Some(tool) = python_tool_from_env() {
+        tool_registry.register(tool);
+    }
+    #[cfg(feature = "sql")]
+    if let Some(tool) = sql_tool_from_env() {
+        tool_registry.register(tool);
+    }
+    let transcriber = transcriber_from_env(local_hosts.is_some());
+    if transcriber.is_none() {
+        // with_defaults registers it whenever OPENAI_API_KEY is set, including in local-only mode
+        tool_registry.unregister("transcribe_audio");
+    }
+    if let (Some(hosts), Some(email)) = (&local_hosts, SendEmailTool::from_env()) {
+        if let Err(e) = email.check_hosts(hosts) {
+            warn!("LOCAL_ONLY is set, so send_email is disabled: {}", e);
+            tool_registry.unregister("send_email");
+        }
+    }
+    let speech = speech_from_env(local_hosts.as_ref());
+    let retriever = retriever_from_env(local_hosts.is_some(), config.primary_ollama_host());
+    if let Some(retriever) = &retriever {
+        tool_registry.register(vector_search_tool(retriever));
+    }
+    match db::list_registered_tools(&conn) {
+        Ok(saved) => register_saved_tools(&mut tool_registry, saved, local_hosts.as_ref()).await,
+        Err(e) => warn!("Failed to load registered tools: {}", e),
+    }
+    let documents = retriever.map(|r| Arc::new(DocumentIngestor::new(r, vector_collection_from_env())));
     info!("Registered {} tools", tool_registry.list().len());
 
     let trace_db_path = std::env::var("TRACE_DATABASE_URL").unwrap_or_else(|_| "data/traces.db".into());
     let trace_store = Arc::new(TraceStore::new(&trace_db_path).expect("failed to initialize trace store"));
     info!("Trace store initialized at {}", trace_db_path);
 
+    let provider_health = ProviderHealth::new(DegradedMode::from_env(&models));
+    let model_health = ModelHealthMonitor::from_env(&models);
+    let context = context_manager_from_env(&models, &residency);
+    let backends = shared::init_from_env().await;
+    let metrics = Arc::new(ServerMetrics::new());
+    fissio_llm::set_call_observer(metrics.clone());
+    let vram = vram_scheduler_from_env(config.primary_ollama_host()).await;
+    let model_pool = Arc::new(model_pool(config, vram.as_deref()).await);
+
     ServerState {
-        models,
-        presets,
-        templates,
+        models: std::sync::RwLock::new(Arc::new(models)),
+        ollama_hosts: config.ollama_hosts.clone(),
+        model_events: broadcast::channel(16).0,
+        presets: RwLock::new(presets),
+        templates: RwLock::new(templates),
+        presets_dir,
         configs: RwLock::new(configs),
         db: Mutex::new(conn),
-        tool_registry,
+        tool_registry: RwLock::new(tool_registry),
         trace_store,
+        usage_report: RwLock::new(None),
+        provider_health,
+        model_health,
+        init_cache: RwLock::new(None),
+        llm_cache: llm_cache_from_env(),
+        side_effects: side_effect_ledger_from_env(),
+        request_queue: backends.request_queue,
+        event_log: backends.event_log,
+        idempotency_keys: backends.idempotency_keys,
+        trace_capture: trace_capture_from_env(),
+        pretty_logs: !json_logs_from_env(),
+        tool_policy: services::approval::tool_policy_from_env(),
+        approvals: Arc::new(ApprovalBroker::from_env()),
+        residency,
+        local_hosts,
+        model_pool,
+        vram,
+        documents,
+        transcriber,
+        speech,
+        audio_clips: AudioClips::default(),
+        context,
+        memory: memory_from_env(),
+        metrics,
+        secrets: secrets_from_env(),
     }
 }
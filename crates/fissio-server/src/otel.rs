@@ -0,0 +1,51 @@
+//! Optional OpenTelemetry export.
+//!
+//! Built with the `otel` feature and given `OTEL_EXPORTER_OTLP_ENDPOINT`, the
+//! server sends its tracing spans over OTLP/gRPC, so pipeline runs appear in
+//! Jaeger, Tempo, Datadog, or any OpenTelemetry collector.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Flushes spans still buffered for export when dropped.
+pub struct OtelGuard(TracerProvider);
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            eprintln!("Failed to flush OpenTelemetry spans: {}", e);
+        }
+    }
+}
+
+/// Builds the export layer from `OTEL_EXPORTER_OTLP_ENDPOINT` and
+/// `OTEL_SERVICE_NAME` (default `fissio-server`); `None` if no endpoint is set.
+///
+/// Runs before logging is initialized, so problems are printed to stderr.
+pub fn layer_from_env<S>() -> Option<(OpenTelemetryLayer<S, Tracer>, OtelGuard)>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().filter(|e| !e.is_empty())?;
+    let exporter = match opentelemetry_otlp::SpanExporter::builder().with_tonic().with_endpoint(&endpoint).build() {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("OpenTelemetry export disabled: {}", e);
+            return None;
+        }
+    };
+    let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "fissio-server".into());
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", service)]))
+        .build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("fissio"));
+    Some((layer, OtelGuard(provider)))
+}
@@ -0,0 +1,79 @@
+//! Tool access policy and human approval of tool calls.
+//!
+//! A chat run that reaches a tool marked `require_approval` sends an `approval`
+//! event to its client and waits until `POST /chat/approvals/{id}` answers it,
+//! or denies the call once the timeout passes. Pending approvals are held in
+//! memory, so the answer must reach the replica running the request.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use fissio_engine::{ApprovalResponse, ToolPolicy};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+const DEFAULT_APPROVAL_TIMEOUT_SECS: u64 = 300;
+
+/// Tool calls waiting for a user's answer.
+pub struct ApprovalBroker {
+    pending: Mutex<HashMap<String, oneshot::Sender<ApprovalResponse>>>,
+    timeout: Duration,
+}
+
+impl ApprovalBroker {
+    /// Reads `TOOL_APPROVAL_TIMEOUT_SECS` (default 300).
+    pub fn from_env() -> Self {
+        let secs = std::env::var("TOOL_APPROVAL_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_APPROVAL_TIMEOUT_SECS);
+        Self { pending: Mutex::new(HashMap::new()), timeout: Duration::from_secs(secs) }
+    }
+
+    /// Registers a pending approval and returns its ID and the receiver for the answer.
+    pub fn register(&self) -> (String, oneshot::Receiver<ApprovalResponse>) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id.clone(), tx);
+        }
+        (id, rx)
+    }
+
+    /// Waits for the answer to a registered approval, denying it on timeout.
+    pub async fn wait(&self, id: &str, rx: oneshot::Receiver<ApprovalResponse>) -> ApprovalResponse {
+        let response = match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => ApprovalResponse::Denied("approval was cancelled".into()),
+            Err(_) => {
+                warn!("Approval {} timed out after {:?}", id, self.timeout);
+                ApprovalResponse::Denied("no approval was given in time".into())
+            }
+        };
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(id);
+        }
+        response
+    }
+
+    /// Answers a pending approval. Returns false if it is unknown or already answered.
+    pub fn resolve(&self, id: &str, response: ApprovalResponse) -> bool {
+        let sender = self.pending.lock().ok().and_then(|mut pending| pending.remove(id));
+        sender.is_some_and(|tx| tx.send(response).is_ok())
+    }
+}
+
+/// Reads the server-wide tool policy from `TOOL_ALLOW`, `TOOL_DENY`, and
+/// `TOOL_REQUIRE_APPROVAL` (comma-separated tool names).
+pub fn tool_policy_from_env() -> ToolPolicy {
+    let list = |var: &str| -> Option<Vec<String>> {
+        let value = std::env::var(var).ok()?;
+        Some(value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+    };
+    ToolPolicy {
+        allow: list("TOOL_ALLOW"),
+        deny: list("TOOL_DENY").unwrap_or_default(),
+        require_approval: list("TOOL_REQUIRE_APPROVAL").unwrap_or_default(),
+    }
+}
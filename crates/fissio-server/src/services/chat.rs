@@ -1,23 +1,30 @@
 //! Chat execution service - business logic for chat streaming.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use fissio_config::{EdgeConfig, EdgeEndpoint, EdgeType, NodeConfig, NodeType, PipelineConfig};
-use fissio_core::{Message as CoreMessage, ModelConfig};
-use fissio_engine::{EngineOutput, PipelineEngine};
-use fissio_llm::{LlmStream, OllamaClient, OllamaMetrics, StreamChunk, UnifiedLlmClient};
-use fissio_monitor::{ObserveConfig, TraceStore, TracingCollector};
-use futures::StreamExt;
-use tracing::{error, info};
+use fissio_config::{
+    EdgeConfig, EdgeEndpoint, EdgeType, NodeConfig, NodeType, PipelineConfig, ResidencyPolicy, SCHEMA_VERSION,
+};
+use fissio_core::{AgentError, Message as CoreMessage, ModelConfig};
+use fissio_engine::{EngineOutput, PipelineEngine, SideEffectLedger, ToolApprover, ToolPolicy, VramScheduler};
+use fissio_llm::{LlmCache, LlmStream, OllamaClient, OllamaMetrics, UnifiedLlmClient};
+use fissio_monitor::{CaptureMode, ObserveConfig, RunObserver, TraceStore, TracingCollector};
+use fissio_rag::Memory;
+use fissio_tools::{HostAllowlist, ToolRegistry};
+use tracing::info;
 
-use crate::dto::{RuntimePipelineConfig, WsMetadata};
+use crate::dto::{EdgeInfo, PipelineInfo, RuntimePipelineConfig, WsMetadata};
 
 /// Result of a streaming chat operation.
 pub struct StreamResult {
+    /// Response text sent to the client; empty if the run failed or produced none.
+    pub response: String,
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub ollama_metrics: Option<OllamaMetrics>,
+    /// Estimated cost in USD of the run's LLM calls, if their models have pricing.
+    pub estimated_cost_usd: Option<f64>,
 }
 
 /// Converts a runtime config from the frontend to a PipelineConfig.
@@ -26,7 +33,8 @@ pub fn runtime_to_pipeline_config(runtime: &RuntimePipelineConfig) -> PipelineCo
         id: n.id.clone(),
         node_type: n.node_type.parse().unwrap_or(NodeType::Llm),
         model: n.model.clone(),
-        config: serde_json::Value::Null,
+        fallback_models: n.fallback_models.clone(),
+        config: n.config.clone().unwrap_or_default(),
         prompt: n.prompt.clone(),
         tools: n.tools.clone().unwrap_or_default(),
         observe: Some(ObserveConfig::new()),
@@ -38,14 +46,60 @@ pub fn runtime_to_pipeline_config(runtime: &RuntimePipelineConfig) -> PipelineCo
         edge_type: e.edge_type.as_deref()
             .and_then(|t| t.parse().ok())
             .unwrap_or(EdgeType::Direct),
+        max_concurrency: e.max_concurrency,
+        on_failure: e.on_failure.unwrap_or_default(),
+        default_output: e.default_output.clone(),
     }).collect();
 
     PipelineConfig {
+        schema_version: SCHEMA_VERSION,
         id: runtime.id.clone().unwrap_or_else(|| "runtime".to_string()),
         name: runtime.name.clone().unwrap_or_else(|| "Runtime Config".to_string()),
         description: String::new(),
         nodes,
         edges,
+        max_concurrency: runtime.max_concurrency,
+        residency: runtime.residency.clone(),
+        model_lifecycle: runtime.model_lifecycle.clone(),
+    }
+}
+
+/// Converts a saved pipeline back to a PipelineConfig (so it can run as a sub-pipeline).
+pub fn pipeline_info_to_config(info: &PipelineInfo) -> PipelineConfig {
+    let nodes = info.nodes.iter().map(|n| NodeConfig {
+        id: n.id.clone(),
+        node_type: n.node_type.parse().unwrap_or(NodeType::Llm),
+        model: n.model.clone(),
+        fallback_models: n.fallback_models.clone(),
+        config: n.config.clone().unwrap_or_default(),
+        prompt: n.prompt.clone(),
+        tools: n.tools.clone().unwrap_or_default(),
+        observe: Some(ObserveConfig::new()),
+    }).collect();
+
+    PipelineConfig {
+        schema_version: SCHEMA_VERSION,
+        id: info.id.clone(),
+        name: info.name.clone(),
+        description: info.description.clone(),
+        nodes,
+        edges: info.edges.iter().map(edge_info_to_config).collect(),
+        max_concurrency: info.max_concurrency,
+        residency: info.residency.clone(),
+        model_lifecycle: info.model_lifecycle.clone(),
+    }
+}
+
+fn edge_info_to_config(e: &EdgeInfo) -> EdgeConfig {
+    EdgeConfig {
+        from: EdgeEndpoint::from(&e.from),
+        to: EdgeEndpoint::from(&e.to),
+        edge_type: e.edge_type.as_deref()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(EdgeType::Direct),
+        max_concurrency: e.max_concurrency,
+        on_failure: e.on_failure.unwrap_or_default(),
+        default_output: e.default_output.clone(),
     }
 }
 
@@ -55,15 +109,12 @@ pub async fn execute_ollama_stream(
     history: &[CoreMessage],
     message: &str,
     system_prompt: &str,
-) -> Result<(LlmStream, OllamaMetrics), String> {
-    let api_base = model.api_base.as_ref().ok_or("ollama requires api_base")?;
+) -> Result<(LlmStream, OllamaMetrics), AgentError> {
+    let api_base = model.api_base.as_ref().ok_or_else(|| AgentError::LlmError("ollama requires api_base".into()))?;
     let client = OllamaClient::new(&model.model, api_base);
     info!("Using native Ollama API for verbose metrics");
 
-    let (stream, metrics_collector) = client
-        .chat_stream_with_metrics(system_prompt, history, message)
-        .await
-        .map_err(|e| e.to_string())?;
+    let (stream, metrics_collector) = client.chat_stream_with_metrics(system_prompt, history, message).await?;
 
     Ok((stream, metrics_collector.get_metrics()))
 }
@@ -74,12 +125,8 @@ pub async fn execute_direct_chat(
     history: &[CoreMessage],
     message: &str,
     system_prompt: &str,
-) -> Result<LlmStream, String> {
-    let client = UnifiedLlmClient::new(&model.model, model.api_base.as_deref());
-    client
-        .chat_stream(system_prompt, history, message)
-        .await
-        .map_err(|e| e.to_string())
+) -> Result<LlmStream, AgentError> {
+    UnifiedLlmClient::from_config(model).chat_stream(system_prompt, history, message).await
 }
 
 /// Result of pipeline execution with optional tracing collector.
@@ -88,70 +135,126 @@ pub struct PipelineResult {
     pub collector: Option<Arc<TracingCollector>>,
 }
 
+/// How a pipeline run is executed, besides its config and input.
+pub struct PipelineRunOptions<'a> {
+    pub models: &'a [ModelConfig],
+    pub default_model: &'a ModelConfig,
+    pub node_overrides: HashMap<String, String>,
+    /// Configs that `Pipeline` nodes may reference by ID.
+    pub pipelines: Vec<PipelineConfig>,
+    /// The server's registry, including tools registered at runtime.
+    pub tools: ToolRegistry,
+    /// Answers repeated LLM requests from the cache.
+    pub llm_cache: Option<Arc<dyn LlmCache>>,
+    pub trace_store: Option<Arc<TraceStore>>,
+    /// Told about the run when its trace is finalized.
+    pub run_observer: Option<Arc<dyn RunObserver>>,
+    /// The tenant the run is for, who owns its trace.
+    pub owner: Option<&'a str>,
+    /// Applies to nodes that don't set `observe.capture`.
+    pub capture: CaptureMode,
+    /// Banners or structured events for the engine's logs.
+    pub pretty_logs: bool,
+    /// Applies to every node.
+    pub tool_policy: &'a ToolPolicy,
+    /// Restricts every node's model on top of the pipeline's own policy.
+    pub residency: &'a ResidencyPolicy,
+    /// Enables local-only mode, limiting tools to those hosts.
+    pub local_hosts: Option<&'a HostAllowlist>,
+    /// The server-wide GPU memory scheduler for Ollama models.
+    pub vram: Option<Arc<VramScheduler>>,
+    /// Nodes whose model is in the set run on a healthy fallback if they have one.
+    pub unhealthy_models: HashSet<String>,
+    /// Long-term memory and the scope it is read and written under.
+    pub memory: Option<(Arc<dyn Memory>, String)>,
+    /// Ledger and run key under which tools with side effects run at most once.
+    pub ledger: Option<(Arc<dyn SideEffectLedger>, String)>,
+    /// Answers `require_approval` calls.
+    pub approver: Option<Arc<dyn ToolApprover>>,
+}
+
 /// Executes a pipeline and returns the output stream.
 pub async fn execute_pipeline(
     config: &PipelineConfig,
     message: &str,
     history: &[CoreMessage],
-    models: &[ModelConfig],
-    default_model: &ModelConfig,
-    node_overrides: HashMap<String, String>,
-    trace_store: Option<Arc<TraceStore>>,
-) -> Result<PipelineResult, String> {
+    options: PipelineRunOptions<'_>,
+) -> Result<PipelineResult, AgentError> {
+    let PipelineRunOptions {
+        models,
+        default_model,
+        node_overrides,
+        pipelines,
+        tools,
+        llm_cache,
+        trace_store,
+        run_observer,
+        owner,
+        capture,
+        pretty_logs,
+        tool_policy,
+        residency,
+        local_hosts,
+        vram,
+        unhealthy_models,
+        memory,
+        ledger,
+        approver,
+    } = options;
     let collector = trace_store.map(|store| {
-        Arc::new(TracingCollector::new(
-            store,
-            &config.id,
-            &config.name,
-            message,
-        ))
+        let mut collector = TracingCollector::new(store, &config.id, &config.name, message);
+        if let Some(observer) = run_observer {
+            collector = collector.with_observer(observer);
+        }
+        if let Some(owner) = owner {
+            collector = collector.with_owner(owner);
+        }
+        Arc::new(collector)
     });
 
-    let mut engine = PipelineEngine::new(
+    let mut engine = PipelineEngine::with_tools(
         config.clone(),
         models.to_vec(),
         default_model.clone(),
         node_overrides,
-    );
+        tools,
+    )
+    .with_pipelines(pipelines)
+    .with_default_capture(capture)
+    .with_pretty_logs(pretty_logs)
+    .with_tool_policy(tool_policy.clone())
+    .with_unhealthy_models(unhealthy_models);
 
     if let Some(ref coll) = collector {
         engine = engine.with_collector(coll.clone());
     }
+    if let Some(cache) = llm_cache {
+        engine = engine.with_llm_cache(cache);
+    }
+    if let Some(approver) = approver {
+        engine = engine.with_approver(approver);
+    }
+    if !residency.is_empty() {
+        engine = engine.with_residency(residency.clone());
+    }
+    if let Some(hosts) = local_hosts {
+        engine = engine.with_local_only(hosts.clone());
+    }
+    if let Some(scheduler) = vram {
+        engine = engine.with_vram_scheduler(scheduler);
+    }
+    if let Some((memory, scope)) = memory {
+        engine = engine.with_memory(memory, scope);
+    }
+    if let Some((ledger, run_key)) = ledger {
+        engine = engine.with_side_effect_ledger(ledger, run_key);
+    }
 
-    let output = engine
-        .execute_stream(message, history)
-        .await
-        .map_err(|e| e.to_string())?;
+    let output = engine.execute_stream(message, history).await?;
 
     Ok(PipelineResult { output, collector })
 }
 
-/// Consumes an LLM stream, calling the sender for each content chunk.
-/// Returns token counts.
-pub async fn consume_stream<F>(mut stream: LlmStream, on_chunk: F) -> (u32, u32)
-where
-    F: Fn(&str),
-{
-    let mut input_tokens = 0u32;
-    let mut output_tokens = 0u32;
-
-    while let Some(chunk_result) = stream.next().await {
-        match chunk_result {
-            Ok(StreamChunk::Content(chunk)) => on_chunk(&chunk),
-            Ok(StreamChunk::Usage { input_tokens: i, output_tokens: o }) => {
-                input_tokens = i;
-                output_tokens = o;
-            }
-            Err(e) => {
-                error!("Stream error: {}", e);
-                break;
-            }
-        }
-    }
-
-    (input_tokens, output_tokens)
-}
-
 /// Builds metadata from stream result.
 pub fn build_metadata(result: &StreamResult, elapsed_ms: u64) -> WsMetadata {
     match &result.ollama_metrics {
@@ -170,12 +273,14 @@ pub fn build_metadata(result: &StreamResult, elapsed_ms: u64) -> WsMetadata {
                 prompt_eval_ms: Some(m.prompt_eval_ms()),
                 eval_ms: Some(m.eval_ms()),
                 tokens_per_sec: Some(m.tokens_per_sec()),
+                estimated_cost_usd: result.estimated_cost_usd,
             }
         }
         None => WsMetadata {
             input_tokens: result.input_tokens,
             output_tokens: result.output_tokens,
             elapsed_ms,
+            estimated_cost_usd: result.estimated_cost_usd,
             ..Default::default()
         },
     }
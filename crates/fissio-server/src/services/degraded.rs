@@ -0,0 +1,168 @@
+//! Degraded-mode handling for when every configured cloud provider is down.
+//!
+//! A background monitor pings each provider that has an API key configured.
+//! While all of them fail, chat requests follow `DEGRADED_MODE` instead of
+//! erroring one by one:
+//!
+//! - `error` (default) — Requests run normally and fail as before
+//! - `canned` — Reply with `DEGRADED_MESSAGE`
+//! - `queue` — Persist the request and replay it once a provider recovers
+//! - `local` — Run every node on `DEGRADED_MODEL` (default: first local model)
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fissio_core::ModelConfig;
+use fissio_llm::CloudProvider;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::ServerState;
+
+const DEFAULT_MESSAGE: &str =
+    "Our AI providers are temporarily unavailable. Please try again in a few minutes.";
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Behavior while all cloud providers are unavailable.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "mode", content = "value", rename_all = "lowercase")]
+pub enum DegradedMode {
+    Error,
+    Canned(String),
+    Queue,
+    Local(String),
+}
+
+impl DegradedMode {
+    /// Reads the mode from `DEGRADED_MODE`, `DEGRADED_MESSAGE`, and `DEGRADED_MODEL`.
+    pub fn from_env(models: &[ModelConfig]) -> Self {
+        match std::env::var("DEGRADED_MODE").as_deref() {
+            Ok("canned") => Self::Canned(
+                std::env::var("DEGRADED_MESSAGE").unwrap_or_else(|_| DEFAULT_MESSAGE.to_string()),
+            ),
+            Ok("queue") => Self::Queue,
+            Ok("local") => {
+                let model = std::env::var("DEGRADED_MODEL")
+                    .ok()
+                    .or_else(|| models.iter().find(|m| m.api_base.is_some()).map(|m| m.id.clone()));
+                match model {
+                    Some(id) => Self::Local(id),
+                    None => {
+                        warn!("DEGRADED_MODE=local but no local model is available, using 'error'");
+                        Self::Error
+                    }
+                }
+            }
+            Ok("error") | Err(_) => Self::Error,
+            Ok(other) => {
+                warn!("Unknown DEGRADED_MODE '{}', using 'error'", other);
+                Self::Error
+            }
+        }
+    }
+}
+
+/// Latest health check result per configured cloud provider.
+pub struct ProviderHealth {
+    pub mode: DegradedMode,
+    status: RwLock<HashMap<CloudProvider, bool>>,
+}
+
+impl ProviderHealth {
+    /// Creates a tracker that assumes every configured provider is healthy until checked.
+    pub fn new(mode: DegradedMode) -> Self {
+        let status = CloudProvider::configured().into_iter().map(|p| (p, true)).collect();
+        Self { mode, status: RwLock::new(status) }
+    }
+
+    /// Returns true when at least one provider is configured and all of them are failing.
+    pub async fn is_degraded(&self) -> bool {
+        let status = self.status.read().await;
+        !status.is_empty() && status.values().all(|healthy| !healthy)
+    }
+
+    /// Returns the current per-provider health.
+    pub async fn snapshot(&self) -> HashMap<CloudProvider, bool> {
+        self.status.read().await.clone()
+    }
+
+    /// Re-checks every configured provider.
+    async fn refresh(&self) {
+        let providers: Vec<CloudProvider> = self.status.read().await.keys().copied().collect();
+        for provider in providers {
+            let healthy = match provider.check().await {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("{}", e);
+                    false
+                }
+            };
+            self.status.write().await.insert(provider, healthy);
+        }
+    }
+}
+
+/// Spawns the provider health monitor if any cloud provider is configured.
+///
+/// Interval is `PROVIDER_HEALTH_INTERVAL_SECS` (default 60). When providers
+/// recover in `queue` mode, queued requests are replayed.
+pub fn spawn_health_monitor(state: Arc<ServerState>) {
+    if state.local_hosts.is_some() {
+        info!("Local-only mode, provider health monitor disabled");
+        return;
+    }
+    if CloudProvider::configured().is_empty() {
+        info!("No cloud providers configured, provider health monitor disabled");
+        return;
+    }
+
+    let interval = std::env::var("PROVIDER_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        loop {
+            let was_degraded = state.provider_health.is_degraded().await;
+            state.provider_health.refresh().await;
+            let degraded = state.provider_health.is_degraded().await;
+
+            if degraded && !was_degraded {
+                warn!("All cloud providers unavailable, entering degraded mode ({:?})", state.provider_health.mode);
+            } else if !degraded && was_degraded {
+                info!("Cloud providers recovered, leaving degraded mode");
+            }
+
+            if !degraded && matches!(state.provider_health.mode, DegradedMode::Queue) {
+                replay_queued(&state).await;
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+/// Replays requests queued while degraded, oldest first.
+async fn replay_queued(state: &ServerState) {
+    let queued = match state.take_queued_requests().await {
+        Ok(queued) => queued,
+        Err(e) => {
+            error!("Failed to read queued requests: {}", e);
+            return;
+        }
+    };
+
+    if queued.is_empty() {
+        return;
+    }
+    info!("Replaying {} queued requests", queued.len());
+
+    for (id, request_json) in queued {
+        match serde_json::from_str(&request_json) {
+            Ok(req) => crate::handlers::chat::replay(req, state).await,
+            Err(e) => error!("Dropping unreadable queued request #{}: {}", id, e),
+        }
+    }
+}
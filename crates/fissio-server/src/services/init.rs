@@ -0,0 +1,73 @@
+//! Cached `/init` payload shared by every editor connection.
+//!
+//! The payload is serialized once and reused until pipeline configs change,
+//! so connecting editors skip rebuilding and re-serializing the response.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::http::header::IF_NONE_MATCH;
+use axum::http::HeaderMap;
+use tracing::debug;
+
+use crate::dto::InitResponse;
+use crate::error::AppError;
+use crate::ServerState;
+
+/// Serialized `/init` response and its entity tag.
+pub struct InitPayload {
+    pub body: Bytes,
+    pub etag: String,
+}
+
+impl InitPayload {
+    /// Returns true if the request's `If-None-Match` header matches this payload.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(IF_NONE_MATCH)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.split(',').map(str::trim).any(|tag| tag == self.etag || tag == "*"))
+    }
+}
+
+/// Returns the cached payload, building it on first use or after invalidation.
+pub async fn payload(state: &ServerState) -> Result<Arc<InitPayload>, AppError> {
+    if let Some(payload) = state.init_cache.read().await.clone() {
+        return Ok(payload);
+    }
+
+    let mut cache = state.init_cache.write().await;
+    if let Some(payload) = cache.clone() {
+        return Ok(payload);
+    }
+
+    let payload = Arc::new(build(state).await?);
+    debug!("Built /init payload ({} bytes, etag {})", payload.body.len(), payload.etag);
+    *cache = Some(payload.clone());
+    Ok(payload)
+}
+
+/// Drops the cached payload so the next request rebuilds it.
+///
+/// Callers must not hold the `configs` lock, which building the payload reads.
+pub async fn invalidate(state: &ServerState) {
+    *state.init_cache.write().await = None;
+}
+
+async fn build(state: &ServerState) -> Result<InitPayload, AppError> {
+    let response = InitResponse {
+        models: state.models().to_vec(),
+        templates: state.templates.read().await.clone(),
+        configs: state.configs.read().await.clone(),
+        tools: state.tool_infos().await,
+    };
+    let body = serde_json::to_vec(&response)?;
+
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+
+    Ok(InitPayload { body: Bytes::from(body), etag })
+}
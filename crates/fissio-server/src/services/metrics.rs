@@ -0,0 +1,150 @@
+//! Prometheus metrics for GET /metrics.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use fissio_core::AgentError;
+use fissio_llm::CallObserver;
+use fissio_monitor::{PipelineMetrics, RunObserver, TraceRecord};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use tracing::warn;
+
+/// Buckets in seconds, from a fast tool node to a long agentic loop.
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Counters and histograms for HTTP requests, pipeline runs, and provider calls.
+///
+/// Node series are labeled by `pipeline_id`, `node_id`, and `model`; direct
+/// chats report under the pipeline ID `direct`.
+pub struct ServerMetrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    runs: IntCounterVec,
+    node_duration: HistogramVec,
+    node_tokens: IntCounterVec,
+    node_tool_calls: IntCounterVec,
+    provider_requests: IntCounterVec,
+    provider_errors: IntCounterVec,
+    provider_duration: HistogramVec,
+}
+
+impl ServerMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let metric = IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter");
+            registry.register(Box::new(metric.clone())).expect("unique metric name");
+            metric
+        };
+        let histogram = |name: &str, help: &str, labels: &[&str]| {
+            let opts = HistogramOpts::new(name, help).buckets(LATENCY_BUCKETS.to_vec());
+            let metric = HistogramVec::new(opts, labels).expect("valid histogram");
+            registry.register(Box::new(metric.clone())).expect("unique metric name");
+            metric
+        };
+
+        Self {
+            http_requests: counter(
+                "fissio_http_requests_total",
+                "HTTP requests handled",
+                &["method", "route", "status"],
+            ),
+            http_duration: histogram(
+                "fissio_http_request_duration_seconds",
+                "Time to produce an HTTP response; for chat streams, until the stream starts",
+                &["method", "route"],
+            ),
+            runs: counter("fissio_pipeline_runs_total", "Pipeline runs by final status", &["pipeline_id", "status"]),
+            node_duration: histogram(
+                "fissio_node_duration_seconds",
+                "Node execution time",
+                &["pipeline_id", "node_id", "model"],
+            ),
+            node_tokens: counter(
+                "fissio_node_tokens_total",
+                "Tokens used by nodes; direction is input or output",
+                &["pipeline_id", "node_id", "model", "direction"],
+            ),
+            node_tool_calls: counter(
+                "fissio_node_tool_calls_total",
+                "Tool calls made by nodes",
+                &["pipeline_id", "node_id", "model"],
+            ),
+            provider_requests: counter(
+                "fissio_provider_requests_total",
+                "Requests sent to LLM providers",
+                &["provider", "model"],
+            ),
+            provider_errors: counter(
+                "fissio_provider_errors_total",
+                "LLM provider requests that failed",
+                &["provider", "model"],
+            ),
+            provider_duration: histogram(
+                "fissio_provider_request_duration_seconds",
+                "LLM provider response time; for streams, until the stream opens",
+                &["provider", "model"],
+            ),
+            registry,
+        }
+    }
+
+    fn record_http(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        self.http_requests.with_label_values(&[method, route, &status.to_string()]).inc();
+        self.http_duration.with_label_values(&[method, route]).observe(elapsed.as_secs_f64());
+    }
+
+    /// All metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buf = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buf) {
+            warn!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buf).unwrap_or_default()
+    }
+}
+
+impl Default for ServerMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunObserver for ServerMetrics {
+    fn on_run(&self, trace: &TraceRecord, metrics: &PipelineMetrics) {
+        let pipeline = trace.pipeline_id.as_str();
+        self.runs.with_label_values(&[pipeline, trace.status.as_str()]).inc();
+        for node in &metrics.node_metrics {
+            let labels = [pipeline, node.node_id.as_str(), node.model.as_deref().unwrap_or("")];
+            self.node_duration.with_label_values(&labels).observe(node.elapsed_ms as f64 / 1000.0);
+            self.node_tool_calls.with_label_values(&labels).inc_by(node.tool_call_count.into());
+            let [pipeline, node_id, model] = labels;
+            self.node_tokens.with_label_values(&[pipeline, node_id, model, "input"]).inc_by(node.input_tokens.into());
+            self.node_tokens.with_label_values(&[pipeline, node_id, model, "output"]).inc_by(node.output_tokens.into());
+        }
+    }
+}
+
+impl CallObserver for ServerMetrics {
+    fn on_call(&self, provider: &str, model: &str, elapsed: Duration, error: Option<&AgentError>) {
+        self.provider_requests.with_label_values(&[provider, model]).inc();
+        self.provider_duration.with_label_values(&[provider, model]).observe(elapsed.as_secs_f64());
+        if error.is_some() {
+            self.provider_errors.with_label_values(&[provider, model]).inc();
+        }
+    }
+}
+
+/// Middleware counting requests by route template, so path IDs don't become labels.
+pub async fn track_requests(State(metrics): State<Arc<ServerMetrics>>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req.extensions().get::<MatchedPath>().map_or("unmatched", |p| p.as_str()).to_string();
+    let start = Instant::now();
+    let response = next.run(req).await;
+    metrics.record_http(&method, &route, response.status().as_u16(), start.elapsed());
+    response
+}
@@ -1,5 +1,17 @@
 //! Business logic services.
 
+pub mod approval;
 pub mod chat;
+pub mod degraded;
+pub mod init;
+pub mod metrics;
 pub mod model;
+pub mod model_health;
 pub mod pipeline;
+pub mod policy;
+pub mod presets;
+pub mod schedules;
+pub mod sessions;
+pub mod speech;
+pub mod tools;
+pub mod usage;
@@ -1,16 +1,384 @@
-//! Model warmup and unload service.
+//! Model warmup, unload, and warm pool service.
 //!
 //! Handles pre-loading models into GPU memory for faster first responses,
 //! and unloading to free memory when switching models.
+//!
+//! Pipeline runs lease the models they use. An unload, whether requested by a
+//! pipeline's `model_lifecycle` or through the API, waits until no run holds a
+//! lease on the model, so one run never pulls a model out from under another.
+//!
+//! The [`ModelPoolManager`] also tracks which local models are loaded. A run
+//! loads its local models before it starts, a model idle longer than its
+//! keep-alive is unloaded, and when loading a model would exceed the GPU
+//! memory budget the least recently used idle models are unloaded first.
+//!
+//! Ollama discovery is re-run periodically and on request, so models pulled
+//! after startup can be used without a restart.
 
-use fissio_core::ModelConfig;
-use fissio_llm::{unload_model, LlmClient};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use fissio_config::{PipelineConfig, UnloadPolicy};
+use fissio_core::{ModelConfig, Provider};
+use fissio_llm::{discover_models_on, load_model, unload_model, LlmClient};
 use futures::StreamExt;
-use tracing::info;
+use tracing::{debug, info, warn};
 
+use crate::config::ModelPoolConfig;
+use crate::dto::{ModelPoolResponse, ModelsUpdated, PooledModelInfo};
+use crate::services;
 use crate::error::AppError;
 use crate::ServerState;
 
+/// How often idle models are checked against their keep-alive.
+const KEEP_ALIVE_SWEEP_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// Tracks the loaded local models and the runs using each model.
+pub struct ModelPoolManager {
+    config: ModelPoolConfig,
+    vram_budget: Option<u64>,
+    /// Model sizes in bytes, keyed by Ollama model name.
+    sizes: HashMap<String, u64>,
+    models: Mutex<HashMap<String, PooledModel>>,
+}
+
+struct PooledModel {
+    model: ModelConfig,
+    runs: usize,
+    unload_when_idle: bool,
+    loaded: bool,
+    last_used: Option<Instant>,
+}
+
+impl PooledModel {
+    fn new(model: &ModelConfig) -> Self {
+        Self { model: model.clone(), runs: 0, unload_when_idle: false, loaded: false, last_used: None }
+    }
+}
+
+impl ModelPoolManager {
+    pub fn new(config: ModelPoolConfig) -> Self {
+        Self { config, vram_budget: None, sizes: HashMap::new(), models: Mutex::new(HashMap::new()) }
+    }
+
+    /// Evicts idle models to keep loaded ones within `budget_bytes`, using
+    /// `sizes` (keyed by Ollama model name) to account for each.
+    pub fn with_vram_budget(mut self, budget_bytes: u64, sizes: HashMap<String, u64>) -> Self {
+        self.vram_budget = Some(budget_bytes);
+        self.sizes = sizes;
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PooledModel>> {
+        self.models.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn size_of(&self, model: &ModelConfig) -> Option<u64> {
+        self.sizes.get(&model.model).copied()
+    }
+
+    /// Leases `models` for a run. Those listed in `unload_after` are unloaded
+    /// once the last run using them finishes.
+    pub fn acquire(self: &Arc<Self>, models: Vec<ModelConfig>, unload_after: &[String]) -> RunLease {
+        let mut pool = self.lock();
+        let now = Instant::now();
+        for model in &models {
+            let entry = pool.entry(model.id.clone()).or_insert_with(|| PooledModel::new(model));
+            entry.runs += 1;
+            entry.unload_when_idle |= unload_after.contains(&model.id);
+            entry.loaded |= model.api_base.is_some();
+            entry.last_used = Some(now);
+        }
+        RunLease { pool: Arc::clone(self), models }
+    }
+
+    /// Marks a model for unloading. Returns true if no run is using it, so the
+    /// caller should unload it now; otherwise the last run to finish unloads it.
+    pub fn request_unload(&self, model_id: &str) -> bool {
+        match self.lock().get_mut(model_id) {
+            Some(entry) if entry.runs > 0 => {
+                entry.unload_when_idle = true;
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether the pool last saw `model_id` loaded.
+    pub fn is_loaded(&self, model_id: &str) -> bool {
+        self.lock().get(model_id).is_some_and(|entry| entry.loaded)
+    }
+
+    fn mark_loaded(&self, model: &ModelConfig) {
+        let mut pool = self.lock();
+        let entry = pool.entry(model.id.clone()).or_insert_with(|| PooledModel::new(model));
+        entry.loaded = true;
+        entry.last_used = Some(Instant::now());
+    }
+
+    fn mark_unloaded(&self, model_id: &str) {
+        if let Some(entry) = self.lock().get_mut(model_id) {
+            entry.loaded = false;
+            entry.unload_when_idle = false;
+        }
+    }
+
+    /// Releases one run's leases, returning the models now due to be unloaded.
+    fn release(&self, models: &[ModelConfig]) -> Vec<ModelConfig> {
+        let mut pool = self.lock();
+        let now = Instant::now();
+        let mut idle = Vec::new();
+        for model in models {
+            let Some(entry) = pool.get_mut(&model.id) else { continue };
+            entry.runs = entry.runs.saturating_sub(1);
+            entry.last_used = Some(now);
+            if entry.runs == 0 && entry.unload_when_idle {
+                entry.unload_when_idle = false;
+                entry.loaded = false;
+                idle.push(model.clone());
+            }
+        }
+        idle
+    }
+
+    /// Picks the idle models to unload so `model` fits in the GPU memory
+    /// budget, least recently used first, and marks them unloaded.
+    fn evictions_for(&self, model: &ModelConfig) -> Vec<ModelConfig> {
+        let (Some(budget), Some(needed)) = (self.vram_budget, self.size_of(model)) else {
+            return Vec::new();
+        };
+        let mut pool = self.lock();
+        let mut used: u64 = pool
+            .values()
+            .filter(|entry| entry.loaded && entry.model.id != model.id)
+            .filter_map(|entry| self.size_of(&entry.model))
+            .sum();
+        let mut candidates: Vec<&mut PooledModel> = pool
+            .values_mut()
+            .filter(|entry| entry.loaded && entry.runs == 0 && entry.model.id != model.id)
+            .collect();
+        candidates.sort_by_key(|entry| entry.last_used);
+
+        let mut evicted = Vec::new();
+        for entry in candidates {
+            if used + needed <= budget {
+                break;
+            }
+            used = used.saturating_sub(self.size_of(&entry.model).unwrap_or(0));
+            entry.loaded = false;
+            evicted.push(entry.model.clone());
+        }
+        if used + needed > budget {
+            warn!("Model {} doesn't fit in the GPU memory budget beside the models in use", model.name);
+        }
+        evicted
+    }
+
+    /// Marks the idle models whose keep-alive has run out as unloaded and returns them.
+    fn expired(&self, now: Instant) -> Vec<ModelConfig> {
+        let mut pool = self.lock();
+        pool.values_mut()
+            .filter(|entry| entry.loaded && entry.runs == 0)
+            .filter(|entry| {
+                let keep_alive = self.config.keep_alive_for(&entry.model.id);
+                matches!((keep_alive, entry.last_used), (Some(keep_alive), Some(used)) if now.duration_since(used) >= keep_alive)
+            })
+            .map(|entry| {
+                entry.loaded = false;
+                entry.model.clone()
+            })
+            .collect()
+    }
+
+    /// The pool's models, loaded ones first.
+    pub fn snapshot(&self) -> ModelPoolResponse {
+        let pool = self.lock();
+        let mut models: Vec<PooledModelInfo> = pool
+            .values()
+            .filter(|entry| entry.model.api_base.is_some())
+            .map(|entry| PooledModelInfo {
+                id: entry.model.id.clone(),
+                name: entry.model.name.clone(),
+                loaded: entry.loaded,
+                runs: entry.runs,
+                idle_secs: entry.last_used.filter(|_| entry.runs == 0).map(|used| used.elapsed().as_secs()),
+                keep_alive_secs: self.config.keep_alive_for(&entry.model.id).map(|d| d.as_secs()),
+                size_bytes: self.size_of(&entry.model),
+            })
+            .collect();
+        models.sort_by(|a, b| b.loaded.cmp(&a.loaded).then_with(|| a.id.cmp(&b.id)));
+        ModelPoolResponse {
+            vram_budget_bytes: self.vram_budget,
+            loaded_bytes: models.iter().filter(|m| m.loaded).filter_map(|m| m.size_bytes).sum(),
+            models,
+        }
+    }
+}
+
+/// The models one run is using; dropping it ends the run's leases.
+pub struct RunLease {
+    pool: Arc<ModelPoolManager>,
+    models: Vec<ModelConfig>,
+}
+
+impl Drop for RunLease {
+    fn drop(&mut self) {
+        for model in self.pool.release(&self.models) {
+            tokio::spawn(async move {
+                if let Err(e) = unload_config(&model).await {
+                    warn!("Failed to unload idle model {}: {:?}", model.name, e);
+                }
+            });
+        }
+    }
+}
+
+/// Leases the models a pipeline run will use and loads them before it
+/// starts: local models not yet loaded (unless prewarming is off) and those
+/// its `model_lifecycle` marks `prewarm`. Hold the lease until the run finishes.
+pub async fn start_run(
+    state: &ServerState,
+    config: &PipelineConfig,
+    default_model: &ModelConfig,
+    node_overrides: &HashMap<String, String>,
+) -> RunLease {
+    let models = run_models(state, config, default_model, node_overrides);
+    let unload_after: Vec<String> = config
+        .model_lifecycle
+        .iter()
+        .filter(|(_, lifecycle)| lifecycle.unload == UnloadPolicy::WhenIdle)
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    let pool = &state.model_pool;
+    let prewarm: Vec<ModelConfig> = models
+        .iter()
+        .filter(|m| m.api_base.is_some())
+        .filter(|m| {
+            config.model_lifecycle.get(&m.id).is_some_and(|l| l.prewarm)
+                || (pool.config.prewarm && !pool.is_loaded(&m.id))
+        })
+        .cloned()
+        .collect();
+
+    // Lease first so loading one of the run's models can't evict another
+    let lease = pool.acquire(models, &unload_after);
+    let results = futures::future::join_all(prewarm.iter().map(|m| load(state, m))).await;
+    for (model, result) in prewarm.iter().zip(results) {
+        match result {
+            Ok(()) => info!("Prewarmed model {}", model.name),
+            Err(e) => warn!("Failed to prewarm model {}: {:?}", model.name, e),
+        }
+    }
+    lease
+}
+
+/// Unloads idle local models whose keep-alive has run out.
+pub fn spawn_keep_alive_sweeper(state: Arc<ServerState>) {
+    let pool = &state.model_pool.config;
+    if pool.keep_alive.is_none() && pool.model_keep_alive.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(KEEP_ALIVE_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            for model in state.model_pool.expired(Instant::now()) {
+                info!("Model {} idle past its keep-alive", model.name);
+                if let Err(e) = unload_config(&model).await {
+                    warn!("Failed to unload idle model {}: {:?}", model.name, e);
+                }
+            }
+        }
+    });
+}
+
+/// Re-runs Ollama discovery and swaps the discovered models into the catalog,
+/// keeping cloud models as they are. Clients following GET /models/events are
+/// told when the catalog changed.
+pub async fn refresh(state: &ServerState) -> Result<ModelsUpdated, AppError> {
+    let discovered = discover_models_on(&state.ollama_hosts).await?;
+    let current = state.models();
+    let mut models: Vec<ModelConfig> = current.iter().filter(|m| m.provider != Some(Provider::Ollama)).cloned().collect();
+    models.extend(discovered);
+
+    let ids_missing_from = |from: &[ModelConfig], of: &[ModelConfig]| -> Vec<String> {
+        of.iter().filter(|m| !from.iter().any(|f| f.id == m.id)).map(|m| m.id.clone()).collect()
+    };
+    let update = ModelsUpdated {
+        added: ids_missing_from(&current, &models),
+        removed: ids_missing_from(&models, &current),
+        models,
+    };
+    let hosts_changed = update.models.iter().any(|m| {
+        current.iter().any(|c| c.id == m.id && (c.api_base != m.api_base || c.mirrors != m.mirrors))
+    });
+    if update.added.is_empty() && update.removed.is_empty() && !hosts_changed {
+        return Ok(update);
+    }
+
+    *state.models.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(update.models.clone());
+    state.model_health.track(&update.models);
+    services::init::invalidate(state).await;
+    info!("Model catalog updated: {} added, {} removed", update.added.len(), update.removed.len());
+    // Nobody may be listening, which is fine
+    let _ = state.model_events.send(update.clone());
+    Ok(update)
+}
+
+/// Spawns the periodic model discovery refresh.
+///
+/// Interval is `MODEL_REFRESH_INTERVAL_SECS` (default 60; `0` turns it off).
+pub fn spawn_model_refresh(state: Arc<ServerState>) {
+    let interval = std::env::var("MODEL_REFRESH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS);
+    if interval == 0 || state.ollama_hosts.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if let Err(e) = refresh(&state).await {
+                debug!("Model discovery refresh failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Models a run of `config` uses: each node's model after overrides, the
+/// default for nodes without one, and any named in `model_lifecycle`.
+fn run_models(
+    state: &ServerState,
+    config: &PipelineConfig,
+    default_model: &ModelConfig,
+    node_overrides: &HashMap<String, String>,
+) -> Vec<ModelConfig> {
+    let known = state.models();
+    let mut models: Vec<ModelConfig> = Vec::new();
+    let mut add = |model: &ModelConfig| {
+        if !models.iter().any(|m| m.id == model.id) {
+            models.push(model.clone());
+        }
+    };
+    for node in &config.nodes {
+        let id = node_overrides.get(&node.id).or(node.model.as_ref());
+        match id.and_then(|id| known.iter().find(|m| &m.id == id)) {
+            Some(model) => add(model),
+            None => add(default_model),
+        }
+    }
+    for id in config.model_lifecycle.keys() {
+        if let Some(model) = known.iter().find(|m| &m.id == id) {
+            add(model);
+        }
+    }
+    models
+}
+
 /// Warms up a model by running a minimal chat request.
 /// Optionally unloads the previous model first (in parallel).
 pub async fn warmup(
@@ -23,7 +391,7 @@ pub async fn warmup(
 
     let (_, warmup_result) = tokio::join!(
         unload_previous(state, previous_model_id),
-        do_warmup(&model)
+        load(state, &model)
     );
     warmup_result?;
 
@@ -31,9 +399,28 @@ pub async fn warmup(
     Ok(model)
 }
 
+/// Loads a model, unloading idle ones first if it wouldn't fit in GPU memory.
+///
+/// Local models are loaded with their keep-alive; others get a minimal request.
+async fn load(state: &ServerState, model: &ModelConfig) -> Result<(), AppError> {
+    let Some(api_base) = &model.api_base else {
+        return do_warmup(model).await;
+    };
+    for evicted in state.model_pool.evictions_for(model) {
+        info!("Unloading {} to make room for {}", evicted.name, model.name);
+        if let Err(e) = unload_config(&evicted).await {
+            warn!("Failed to unload model {}: {:?}", evicted.name, e);
+        }
+    }
+    let keep_alive = state.model_pool.config.keep_alive_for(&model.id);
+    load_model(api_base.trim_end_matches("/v1"), &model.model, keep_alive).await?;
+    state.model_pool.mark_loaded(model);
+    Ok(())
+}
+
 /// Runs a minimal request to load the model into memory.
 async fn do_warmup(model: &ModelConfig) -> Result<(), AppError> {
-    let client = LlmClient::new(&model.model, model.api_base.as_deref());
+    let client = LlmClient::from_config(model);
     let mut stream = client
         .chat_stream("You are a helpful assistant.", &[], "hi")
         .await?;
@@ -43,9 +430,21 @@ async fn do_warmup(model: &ModelConfig) -> Result<(), AppError> {
 }
 
 /// Unloads a model from GPU memory (Ollama only).
-pub async fn unload(state: &ServerState, model_id: &str) -> Result<(), AppError> {
+///
+/// Returns true if a pipeline run is still using the model; it is then
+/// unloaded when the last such run finishes.
+pub async fn unload(state: &ServerState, model_id: &str) -> Result<bool, AppError> {
     let model = state.get_model(model_id);
+    if !state.model_pool.request_unload(&model.id) {
+        info!("Model {} is in use, unloading when its runs finish", model.name);
+        return Ok(true);
+    }
+    unload_config(&model).await?;
+    state.model_pool.mark_unloaded(&model.id);
+    Ok(false)
+}
 
+async fn unload_config(model: &ModelConfig) -> Result<(), AppError> {
     let Some(api_base) = &model.api_base else {
         return Ok(()); // Not a local model
     };
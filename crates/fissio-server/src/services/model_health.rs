@@ -0,0 +1,186 @@
+//! Per-model health checks, so runs stop sending nodes to a model that is down.
+//!
+//! A background monitor pings every registered model's provider: cloud
+//! providers through their model listing, Ollama through `/api/tags`, which
+//! must also list the model. After `MODEL_HEALTH_FAILURES` consecutive failed
+//! checks (default 3) a model is unhealthy until a check passes again. Nodes
+//! whose model is unhealthy run on their first healthy fallback instead.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use fissio_core::ModelConfig;
+use fissio_llm::CloudProvider;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::ServerState;
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Latest health check result for one model.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelHealth {
+    pub healthy: bool,
+    /// Failed checks since the last one that passed.
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When the model was last checked, in Unix milliseconds; `None` before the first check.
+    pub last_checked_ms: Option<i64>,
+}
+
+/// Tracks the health of every registered model.
+pub struct ModelHealthMonitor {
+    failure_threshold: u32,
+    status: RwLock<HashMap<String, ModelHealth>>,
+}
+
+impl ModelHealthMonitor {
+    /// Creates a monitor that assumes every model is healthy until checked.
+    pub fn new(models: &[ModelConfig], failure_threshold: u32) -> Self {
+        let status = models
+            .iter()
+            .map(|m| {
+                let health = ModelHealth { healthy: true, consecutive_failures: 0, last_error: None, last_checked_ms: None };
+                (m.id.clone(), health)
+            })
+            .collect();
+        Self { failure_threshold: failure_threshold.max(1), status: RwLock::new(status) }
+    }
+
+    /// Reads the failure threshold from `MODEL_HEALTH_FAILURES` (default 3).
+    pub fn from_env(models: &[ModelConfig]) -> Self {
+        let threshold = std::env::var("MODEL_HEALTH_FAILURES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_FAILURE_THRESHOLD);
+        Self::new(models, threshold)
+    }
+
+    /// Starts tracking models new to the catalog and stops tracking removed ones.
+    pub fn track(&self, models: &[ModelConfig]) {
+        let Ok(mut status) = self.status.write() else { return };
+        status.retain(|id, _| models.iter().any(|m| &m.id == id));
+        for model in models {
+            status.entry(model.id.clone()).or_insert_with(|| ModelHealth {
+                healthy: true,
+                consecutive_failures: 0,
+                last_error: None,
+                last_checked_ms: None,
+            });
+        }
+    }
+
+    /// Consecutive failed checks after which a model is unhealthy.
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    /// Returns the current per-model health.
+    pub fn snapshot(&self) -> HashMap<String, ModelHealth> {
+        self.status.read().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    /// IDs of the models currently marked unhealthy.
+    pub fn unhealthy(&self) -> HashSet<String> {
+        let Ok(status) = self.status.read() else { return HashSet::new() };
+        status.iter().filter(|(_, health)| !health.healthy).map(|(id, _)| id.clone()).collect()
+    }
+
+    /// Records a check of `model_id`, marking it unhealthy once it has failed
+    /// the threshold's worth of checks in a row.
+    pub fn record(&self, model_id: &str, result: Result<(), String>) {
+        let Ok(mut status) = self.status.write() else { return };
+        let Some(health) = status.get_mut(model_id) else { return };
+        health.last_checked_ms = Some(now_ms());
+        match result {
+            Ok(()) => {
+                if !health.healthy {
+                    info!("Model {} is healthy again", model_id);
+                }
+                health.healthy = true;
+                health.consecutive_failures = 0;
+                health.last_error = None;
+            }
+            Err(e) => {
+                health.consecutive_failures += 1;
+                if health.healthy && health.consecutive_failures >= self.failure_threshold {
+                    warn!("Model {} failed {} health checks, marking it unhealthy: {}", model_id, health.consecutive_failures, e);
+                    health.healthy = false;
+                }
+                health.last_error = Some(e);
+            }
+        }
+    }
+
+    /// Checks every model, pinging each provider and Ollama host once.
+    async fn refresh(&self, models: &[ModelConfig]) {
+        let mut cloud: HashMap<CloudProvider, Result<(), String>> = HashMap::new();
+        let mut ollama: HashMap<String, Result<HashSet<String>, String>> = HashMap::new();
+
+        for model in models {
+            let result = match (model.provider_name().as_str(), model.api_base.as_deref()) {
+                ("ollama", Some(api_base)) => {
+                    let host = api_base.trim_end_matches("/v1").to_string();
+                    if !ollama.contains_key(&host) {
+                        let listed = fissio_llm::model_sizes(&host)
+                            .await
+                            .map(|sizes| sizes.into_keys().collect())
+                            .map_err(|e| e.to_string());
+                        ollama.insert(host.clone(), listed);
+                    }
+                    match &ollama[&host] {
+                        Ok(names) if names.contains(&model.model) => Ok(()),
+                        Ok(_) => Err(format!("Ollama at {} doesn't have {}", host, model.model)),
+                        Err(e) => Err(e.clone()),
+                    }
+                }
+                ("openai", None) => check_cloud(&mut cloud, CloudProvider::OpenAI).await,
+                ("anthropic", None) => check_cloud(&mut cloud, CloudProvider::Anthropic).await,
+                // No cheap check for Azure deployments, custom providers, or
+                // OpenAI-compatible servers; their failures surface in runs
+                _ => continue,
+            };
+            self.record(&model.id, result);
+        }
+    }
+}
+
+async fn check_cloud(results: &mut HashMap<CloudProvider, Result<(), String>>, provider: CloudProvider) -> Result<(), String> {
+    if let Some(result) = results.get(&provider) {
+        return result.clone();
+    }
+    let result = provider.check().await.map_err(|e| e.to_string());
+    results.insert(provider, result.clone());
+    result
+}
+
+/// Spawns the model health monitor.
+///
+/// Interval is `MODEL_HEALTH_INTERVAL_SECS` (default 60).
+pub fn spawn_model_health_monitor(state: Arc<ServerState>) {
+    if state.models().is_empty() {
+        return;
+    }
+    let interval = std::env::var("MODEL_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        loop {
+            state.model_health.refresh(&state.models()).await;
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
@@ -1,18 +1,48 @@
-//! Pipeline configuration persistence service.
+//! Pipeline configuration persistence, evaluation, and comparison service.
 
-use crate::dto::{PipelineInfo, SavePipelineRequest};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use fissio_config::{EdgeType, FailurePolicy, PipelineConfig};
+use fissio_core::ModelConfig;
+use fissio_engine::{ExecutionPlan, PipelineEngine};
+use fissio_eval::{Comparer, ComparisonReport, EngineSetup, EvalError, EvalReport, Evaluator, Variant};
+
+use crate::auth::{Principal, Tenant};
+use crate::dto::{
+    ComparePipelinesRequest, CompareVariant, EdgeInfo, EvaluatePipelineRequest, NodeInfo, PipelineBundle, PipelineInfo,
+    PipelineVersionInfo, Position, SavePipelineRequest,
+};
 use crate::error::AppError;
+use crate::services::chat::{pipeline_info_to_config, runtime_to_pipeline_config};
+use crate::services::policy;
 use crate::ServerState;
 
-/// Saves a pipeline to the database and updates the in-memory cache.
-pub async fn save_pipeline(state: &ServerState, req: &SavePipelineRequest) -> Result<PipelineInfo, AppError> {
+/// Saves a pipeline to the database as a new version and updates the
+/// in-memory cache. Returns the pipeline and its version number.
+///
+/// A new ID is owned by `tenant`; an ID already saved can only be saved
+/// again by its owner, and keeps that owner.
+pub async fn save_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    req: &SavePipelineRequest,
+) -> Result<(PipelineInfo, i64), AppError> {
     // Persist to database
-    {
+    let (version, owner) = {
         let db = state.db_lock()?;
-        crate::db::save_pipeline(&db, req).map_err(|e| {
+        let owner = match crate::db::pipeline_owner(&db, &req.id).map_err(AppError::internal)? {
+            Some(owner) if !tenant.owns(owner.as_deref()) => {
+                return Err(AppError::Forbidden(format!("Pipeline ID '{}' belongs to another tenant", req.id)));
+            }
+            Some(owner) => owner,
+            None => tenant.id().map(String::from),
+        };
+        let version = crate::db::save_pipeline(&db, req, owner.as_deref()).map_err(|e| {
             AppError::Internal(format!("save failed: {}", e))
         })?;
-    }
+        (version, owner)
+    };
 
     // Build the new PipelineInfo
     let info = PipelineInfo {
@@ -22,21 +52,40 @@ pub async fn save_pipeline(state: &ServerState, req: &SavePipelineRequest) -> Re
         nodes: req.nodes.clone(),
         edges: req.edges.clone(),
         layout: req.layout.clone(),
+        max_concurrency: req.max_concurrency,
+        residency: req.residency.clone(),
+        model_lifecycle: req.model_lifecycle.clone(),
+        owner,
     };
 
     // Update in-memory cache
-    let mut configs = state.configs.write().await;
-    if let Some(idx) = configs.iter().position(|p| p.id == info.id) {
-        configs[idx] = info.clone();
-    } else {
-        configs.push(info.clone());
+    {
+        let mut configs = state.configs.write().await;
+        if let Some(idx) = configs.iter().position(|p| p.id == info.id) {
+            configs[idx] = info.clone();
+        } else {
+            configs.push(info.clone());
+        }
     }
+    crate::services::init::invalidate(state).await;
 
-    Ok(info)
+    Ok((info, version))
 }
 
 /// Deletes a pipeline from the database and removes from in-memory cache.
-pub async fn delete_pipeline(state: &ServerState, id: &str) -> Result<(), AppError> {
+///
+/// Only the owner may delete a pipeline; another tenant's is reported as missing.
+pub async fn delete_pipeline(state: &ServerState, tenant: &Tenant, id: &str) -> Result<(), AppError> {
+    let owner = state.configs.read().await.iter().find(|p| p.id == id).map(|p| p.owner.clone());
+    if let Some(owner) = owner {
+        if !tenant.can_read(owner.as_deref()) {
+            return Err(AppError::NotFound(format!("Pipeline '{}' not found", id)));
+        }
+        if !tenant.owns(owner.as_deref()) {
+            return Err(AppError::Forbidden(format!("Pipeline '{}' is shared and can't be deleted", id)));
+        }
+    }
+
     // Delete from database
     {
         let db = state.db_lock()?;
@@ -46,8 +95,367 @@ pub async fn delete_pipeline(state: &ServerState, id: &str) -> Result<(), AppErr
     }
 
     // Remove from in-memory cache
-    let mut configs = state.configs.write().await;
-    configs.retain(|p| p.id != id);
+    state.configs.write().await.retain(|p| p.id != id);
+    crate::services::init::invalidate(state).await;
 
     Ok(())
 }
+
+/// Lists a pipeline's saved versions, newest first.
+pub fn list_versions(state: &ServerState, tenant: &Tenant, id: &str) -> Result<Vec<PipelineVersionInfo>, AppError> {
+    let db = state.db_lock()?;
+    if let Some(owner) = crate::db::pipeline_owner(&db, id).map_err(AppError::internal)? {
+        if !tenant.can_read(owner.as_deref()) {
+            return Err(AppError::NotFound(format!("no saved versions of pipeline: {}", id)));
+        }
+    }
+    let versions = crate::db::list_pipeline_versions(&db, id).map_err(AppError::internal)?;
+    if versions.is_empty() {
+        return Err(AppError::NotFound(format!("no saved versions of pipeline: {}", id)));
+    }
+    Ok(versions)
+}
+
+/// Restores a saved version of a pipeline, recreating it if it was deleted.
+///
+/// The restored config is saved as a new version, so a rollback can itself
+/// be undone. Returns the pipeline and its new version number.
+pub async fn rollback_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    id: &str,
+    version: i64,
+) -> Result<(PipelineInfo, i64), AppError> {
+    let req = {
+        let db = state.db_lock()?;
+        match crate::db::pipeline_owner(&db, id).map_err(AppError::internal)? {
+            Some(owner) if !tenant.can_read(owner.as_deref()) => None,
+            _ => crate::db::get_pipeline_version(&db, id, version).map_err(AppError::internal)?,
+        }
+    };
+    let req = req.ok_or_else(|| AppError::NotFound(format!("pipeline {} has no version {}", id, version)))?;
+    save_pipeline(state, tenant, &req).await
+}
+
+/// Exports a saved pipeline or preset as a bundle, with the models and tools
+/// it needs. Saved pipelines take precedence and keep their editor layout.
+pub async fn export_pipeline(state: &ServerState, tenant: &Tenant, id: &str) -> Result<PipelineBundle, AppError> {
+    let (pipeline, layout) = find_pipeline(state, tenant, id).await?;
+
+    let mut models = BTreeSet::new();
+    let mut tools = BTreeSet::new();
+    for node in &pipeline.nodes {
+        models.extend(node.model.iter().chain(&node.fallback_models).cloned());
+        tools.extend(node.tools.iter().cloned());
+    }
+    Ok(PipelineBundle { pipeline, layout, models: models.into_iter().collect(), tools: tools.into_iter().collect() })
+}
+
+/// Renders a saved pipeline or preset as a Mermaid flowchart or Graphviz DOT.
+pub async fn pipeline_graph(state: &ServerState, tenant: &Tenant, id: &str, format: &str) -> Result<String, AppError> {
+    let (pipeline, _) = find_pipeline(state, tenant, id).await?;
+    match format {
+        "mermaid" => Ok(pipeline.to_mermaid()),
+        "dot" => Ok(pipeline.to_dot()),
+        other => Err(AppError::BadRequest(format!("Unknown graph format '{}', expected mermaid or dot", other))),
+    }
+}
+
+/// Looks up a saved pipeline `tenant` can see, with its editor layout, or else a preset.
+pub async fn find_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    id: &str,
+) -> Result<(PipelineConfig, Option<HashMap<String, Position>>), AppError> {
+    let saved = state
+        .configs
+        .read()
+        .await
+        .iter()
+        .find(|p| p.id == id && tenant.can_read(p.owner.as_deref()))
+        .cloned();
+    if let Some(info) = saved {
+        return Ok((pipeline_info_to_config(&info), info.layout));
+    }
+    let preset = state.presets.read().await.get(id).cloned();
+    preset
+        .map(|preset| (preset, None))
+        .ok_or_else(|| AppError::NotFound(format!("Pipeline '{}' not found", id)))
+}
+
+/// Imports a bundle as a saved pipeline, upgrading bundles from older
+/// releases. An existing pipeline with the same ID gets a new version.
+///
+/// Models and tools this server lacks don't stop the import; they come back
+/// as warnings, since they may be added before the pipeline runs.
+pub async fn import_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    mut bundle: serde_json::Value,
+) -> Result<(PipelineInfo, i64, Vec<String>), AppError> {
+    PipelineConfig::migrate(&mut bundle).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let bundle: PipelineBundle =
+        serde_json::from_value(bundle).map_err(|e| AppError::BadRequest(format!("Invalid pipeline bundle: {}", e)))?;
+    bundle.pipeline.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut warnings = Vec::new();
+    {
+        let registry = state.tool_registry.read().await;
+        for node in &bundle.pipeline.nodes {
+            for model in node.model.iter().chain(&node.fallback_models) {
+                if !state.models().iter().any(|m| &m.id == model) {
+                    warnings.push(format!("node '{}' uses model '{}', which isn't registered", node.id, model));
+                }
+            }
+            for selection in &node.tools {
+                if !registry.resolve(std::slice::from_ref(selection)).iter().any(|name| registry.has(name)) {
+                    warnings.push(format!("node '{}' lists tool '{}', which isn't registered", node.id, selection));
+                }
+            }
+        }
+    }
+
+    let info = pipeline_config_to_info(&bundle.pipeline, bundle.layout);
+    let req = SavePipelineRequest {
+        id: info.id,
+        name: info.name,
+        description: info.description,
+        nodes: info.nodes,
+        edges: info.edges,
+        layout: info.layout,
+        max_concurrency: info.max_concurrency,
+        residency: info.residency,
+        model_lifecycle: info.model_lifecycle,
+    };
+    let (info, version) = save_pipeline(state, tenant, &req).await?;
+    Ok((info, version, warnings))
+}
+
+/// Runs a test suite against a preset or saved pipeline under the server's
+/// tool, residency, and local-only policies, and the caller's usage policy.
+/// Calls needing approval are refused.
+pub async fn evaluate_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    principal: Option<&Principal>,
+    id: &str,
+    req: EvaluatePipelineRequest,
+) -> Result<EvalReport, AppError> {
+    let pipelines = state.pipeline_catalog(tenant).await;
+    let config = pipelines
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Pipeline '{}' not found", id)))?;
+    let model = match &req.model_id {
+        Some(model_id) => find_model(state, model_id)?,
+        None => state.get_model(""),
+    };
+    if let Some(principal) = principal {
+        let mut models = policy::pipeline_models(&config, &model.id, &req.node_models, &pipelines);
+        models.extend(req.judge_model_id.iter().cloned());
+        policy::check(state, principal, &models)?;
+    }
+
+    let mut evaluator = Evaluator::new(config, state.models().to_vec(), model)
+        .with_node_overrides(req.node_models)
+        .with_tools(state.tool_registry.read().await.clone())
+        .with_pipelines(pipelines)
+        .with_engine_setup(engine_setup(state));
+    if let Some(judge_id) = &req.judge_model_id {
+        evaluator = evaluator.with_judge(find_model(state, judge_id)?);
+    }
+
+    let report = evaluator.run(&req.suite).await.map_err(|e| match e {
+        EvalError::InvalidSuite(_) => AppError::BadRequest(e.to_string()),
+        e => AppError::internal(e),
+    })?;
+    if let Some(principal) = principal {
+        // The report totals tokens without splitting input from output
+        policy::record(state, principal, report.scores.total_tokens, 0, None);
+    }
+    Ok(report)
+}
+
+/// Plans a run of a preset or saved pipeline under the server's policies.
+pub async fn plan_pipeline(
+    state: &ServerState,
+    tenant: &Tenant,
+    id: &str,
+    model_id: Option<&str>,
+    node_models: HashMap<String, String>,
+) -> Result<ExecutionPlan, AppError> {
+    let pipelines = state.pipeline_catalog(tenant).await;
+    let config = pipelines
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Pipeline '{}' not found", id)))?;
+    let model = match model_id {
+        Some(model_id) => find_model(state, model_id)?,
+        None => state.get_model(""),
+    };
+
+    let tools = state.tool_registry.read().await.clone();
+    let engine = PipelineEngine::with_tools(config, state.models().to_vec(), model, node_models, tools)
+        .with_pipelines(pipelines);
+    Ok(engine_setup(state)(engine).plan())
+}
+
+/// Runs one input through two pipeline variants and reports them side by side.
+///
+/// Both variants count toward the caller's usage policy.
+pub async fn compare_pipelines(
+    state: &ServerState,
+    tenant: &Tenant,
+    principal: Option<&Principal>,
+    req: ComparePipelinesRequest,
+) -> Result<ComparisonReport, AppError> {
+    if req.input.trim().is_empty() {
+        return Err(AppError::BadRequest("input is required".into()));
+    }
+    let pipelines = state.pipeline_catalog(tenant).await;
+    let a = variant(state, &pipelines, "a", req.a)?;
+    let b = variant(state, &pipelines, "b", req.b)?;
+    if let Some(principal) = principal {
+        let default_model = state.get_model("");
+        let mut models: Vec<String> = [&a, &b]
+            .into_iter()
+            .flat_map(|v| {
+                let model = v.default_model.as_ref().unwrap_or(&default_model);
+                policy::pipeline_models(&v.config, &model.id, &v.node_overrides, &pipelines)
+            })
+            .collect();
+        models.extend(req.judge_model_id.iter().cloned());
+        policy::check(state, principal, &models)?;
+    }
+
+    let mut comparer = Comparer::new(state.models().to_vec(), state.get_model(""))
+        .with_tools(state.tool_registry.read().await.clone())
+        .with_pipelines(pipelines)
+        .with_engine_setup(engine_setup(state));
+    if let Some(judge_id) = &req.judge_model_id {
+        comparer = comparer.with_judge(find_model(state, judge_id)?);
+    }
+    if let Some(criteria) = req.criteria {
+        comparer = comparer.with_criteria(criteria);
+    }
+
+    let report = comparer.compare(&a, &b, &req.input).await;
+    if let Some(principal) = principal {
+        let input = report.a.input_tokens + report.b.input_tokens;
+        let output = report.a.output_tokens + report.b.output_tokens;
+        policy::record(state, principal, input, output, None);
+    }
+    Ok(report)
+}
+
+fn variant(
+    state: &ServerState,
+    pipelines: &[PipelineConfig],
+    label: &str,
+    spec: CompareVariant,
+) -> Result<Variant, AppError> {
+    let config = match (&spec.pipeline_id, &spec.pipeline_config) {
+        (_, Some(runtime)) => state
+            .resolve_secrets(runtime_to_pipeline_config(runtime))
+            .map_err(|e| AppError::BadRequest(e.to_string()))?,
+        (Some(id), None) => pipelines
+            .iter()
+            .find(|p| &p.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Pipeline '{}' not found", id)))?,
+        (None, None) => {
+            return Err(AppError::BadRequest(format!("Variant {} needs a pipeline_id or pipeline_config", label)))
+        }
+    };
+    let mut variant = Variant::new(spec.label.unwrap_or_else(|| label.to_string()), config)
+        .with_node_overrides(spec.node_models);
+    if let Some(model_id) = &spec.model_id {
+        variant = variant.with_default_model(find_model(state, model_id)?);
+    }
+    Ok(variant)
+}
+
+/// Applies the server's tool policy, residency, model health, scheduling, and
+/// caching to engines built outside `execute_pipeline`.
+fn engine_setup(state: &ServerState) -> EngineSetup {
+    let tool_policy = state.tool_policy.clone();
+    let residency = state.residency.clone();
+    let local_hosts = state.local_hosts.clone();
+    let vram = state.vram.clone();
+    let llm_cache = state.llm_cache.clone();
+    let unhealthy = state.model_health.unhealthy();
+    let (capture, pretty_logs) = (state.trace_capture, state.pretty_logs);
+    Arc::new(move |engine: PipelineEngine| {
+        let mut engine = engine
+            .with_tool_policy(tool_policy.clone())
+            .with_default_capture(capture)
+            .with_pretty_logs(pretty_logs)
+            .with_unhealthy_models(unhealthy.clone());
+        if !residency.is_empty() {
+            engine = engine.with_residency(residency.clone());
+        }
+        if let Some(hosts) = &local_hosts {
+            engine = engine.with_local_only(hosts.clone());
+        }
+        if let Some(scheduler) = &vram {
+            engine = engine.with_vram_scheduler(Arc::clone(scheduler));
+        }
+        if let Some(cache) = &llm_cache {
+            engine = engine.with_llm_cache(Arc::clone(cache));
+        }
+        engine
+    })
+}
+
+fn find_model(state: &ServerState, id: &str) -> Result<ModelConfig, AppError> {
+    state
+        .models()
+        .iter()
+        .find(|m| m.id == id)
+        .cloned()
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown model '{}'", id)))
+}
+
+/// Converts a pipeline config into its API representation.
+///
+/// Shared by preset templates and seeded examples so both reach the editor in the same shape.
+pub fn pipeline_config_to_info(
+    config: &PipelineConfig,
+    layout: Option<HashMap<String, Position>>,
+) -> PipelineInfo {
+    PipelineInfo {
+        id: config.id.clone(),
+        name: config.name.clone(),
+        description: config.description.clone(),
+        nodes: config.nodes.iter().map(|n| NodeInfo {
+            id: n.id.clone(),
+            node_type: n.node_type.to_string(),
+            model: n.model.clone(),
+            fallback_models: n.fallback_models.clone(),
+            prompt: n.prompt.clone(),
+            tools: if n.tools.is_empty() { None } else { Some(n.tools.clone()) },
+            config: if n.config.is_null() { None } else { Some(n.config.clone()) },
+            x: None,
+            y: None,
+        }).collect(),
+        edges: config.edges.iter().map(|e| EdgeInfo {
+            from: serde_json::Value::from(&e.from),
+            to: serde_json::Value::from(&e.to),
+            edge_type: if e.edge_type == EdgeType::Direct {
+                None
+            } else {
+                Some(e.edge_type.to_string())
+            },
+            max_concurrency: e.max_concurrency,
+            on_failure: (e.on_failure != FailurePolicy::FailFast).then_some(e.on_failure),
+            default_output: e.default_output.clone(),
+        }).collect(),
+        layout,
+        max_concurrency: config.max_concurrency,
+        residency: config.residency.clone(),
+        model_lifecycle: config.model_lifecycle.clone(),
+        owner: None,
+    }
+}
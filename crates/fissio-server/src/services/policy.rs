@@ -0,0 +1,228 @@
+//! Per-key model allowlists and daily usage quotas.
+//!
+//! A policy names the models a principal (an API key's name or a JWT's `sub`)
+//! may use and caps its tokens and estimated spend per UTC day. The policy
+//! saved for `*` applies to principals without one of their own. Usage is
+//! counted when a run finishes, so a run that starts under a quota may end
+//! over it; later requests are refused until the day ends.
+
+use std::collections::{BTreeSet, HashMap};
+
+use axum::http::StatusCode;
+use fissio_config::PipelineConfig;
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::auth::Principal;
+use crate::db;
+use crate::dto::{DailyUsage, KeyPolicy, KeyQuotaInfo};
+use crate::error::AppError;
+use crate::services::schedules::{format_utc, unix_now};
+use crate::ServerState;
+
+/// Key whose policy applies to principals without their own.
+pub const DEFAULT_POLICY_KEY: &str = "*";
+
+/// Why a policy refused a request; serialized into the error body after `error`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum PolicyViolation {
+    ModelNotAllowed { model: String, allowed: Vec<String> },
+    TokenQuotaExceeded { limit: u64, used: u64, resets_at: String },
+    CostQuotaExceeded { limit_usd: f64, used_usd: f64, resets_at: String },
+}
+
+impl PolicyViolation {
+    /// `403` for a model outside the allowlist, `429` for a spent quota.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            PolicyViolation::ModelNotAllowed { .. } => StatusCode::FORBIDDEN,
+            _ => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Seconds until the quota resets, for `Retry-After`.
+    pub fn retry_after(&self) -> Option<i64> {
+        match self {
+            PolicyViolation::ModelNotAllowed { .. } => None,
+            _ => Some(86_400 - unix_now().rem_euclid(86_400)),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            PolicyViolation::ModelNotAllowed { model, .. } => format!("this key may not use model '{}'", model),
+            PolicyViolation::TokenQuotaExceeded { limit, .. } => format!("daily token quota of {} reached", limit),
+            PolicyViolation::CostQuotaExceeded { limit_usd, .. } => format!("daily cost quota of ${:.2} reached", limit_usd),
+        }
+    }
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`, the day usage is counted under.
+fn today() -> String {
+    format_utc(unix_now())[..10].to_string()
+}
+
+fn resets_at() -> String {
+    format_utc(unix_now().div_euclid(86_400) * 86_400 + 86_400)
+}
+
+/// A key's own policy, or else the `*` policy. The flag is true for the latter.
+fn effective_policy(conn: &rusqlite::Connection, key: &str) -> anyhow::Result<(KeyPolicy, bool)> {
+    if let Some(policy) = db::get_key_policy(conn, key)? {
+        return Ok((policy, false));
+    }
+    Ok((db::get_key_policy(conn, DEFAULT_POLICY_KEY)?.unwrap_or_default(), true))
+}
+
+/// Every model a pipeline's nodes may run on, including fallbacks and the
+/// nodes of the `pipelines` its `Pipeline` nodes reference.
+pub fn pipeline_models(
+    config: &PipelineConfig,
+    default_model: &str,
+    overrides: &HashMap<String, String>,
+    pipelines: &[PipelineConfig],
+) -> Vec<String> {
+    let mut models = BTreeSet::new();
+    let mut ancestors = vec![config.id.as_str()];
+    collect_models(config, default_model, overrides, pipelines, &mut ancestors, &mut models);
+    models.into_iter().collect()
+}
+
+fn collect_models<'a>(
+    config: &'a PipelineConfig,
+    default_model: &str,
+    overrides: &HashMap<String, String>,
+    pipelines: &'a [PipelineConfig],
+    ancestors: &mut Vec<&'a str>,
+    models: &mut BTreeSet<String>,
+) {
+    for node in &config.nodes {
+        let model = overrides.get(&node.id).or(node.model.as_ref()).map_or(default_model, String::as_str);
+        models.insert(model.to_string());
+        models.extend(node.fallback_models.iter().cloned());
+
+        // The engine refuses a pipeline that references one running above it, so skip those too
+        let Some(sub) = node
+            .pipeline_ref()
+            .filter(|id| !ancestors.contains(id))
+            .and_then(|id| pipelines.iter().find(|p| p.id == id))
+        else {
+            continue;
+        };
+        // Overrides reach nested nodes as `<node_id>/<inner_id>`
+        let prefix = format!("{}/", node.id);
+        let nested = overrides
+            .iter()
+            .filter_map(|(k, v)| k.strip_prefix(&prefix).map(|inner| (inner.to_string(), v.clone())))
+            .collect();
+        ancestors.push(sub.id.as_str());
+        collect_models(sub, default_model, &nested, pipelines, ancestors, models);
+        ancestors.pop();
+    }
+}
+
+/// Checks that `principal` may use `models` and has quota left today.
+pub fn check(state: &ServerState, principal: &Principal, models: &[String]) -> Result<(), AppError> {
+    let conn = state.db_lock()?;
+    let (policy, _) = effective_policy(&conn, &principal.id).map_err(AppError::internal)?;
+
+    if let Some(allowed) = &policy.models {
+        if let Some(model) = models.iter().find(|m| !allowed.contains(m)) {
+            warn!("Refusing model {} for {}", model, principal.id);
+            return Err(AppError::Policy(PolicyViolation::ModelNotAllowed {
+                model: model.clone(),
+                allowed: allowed.clone(),
+            }));
+        }
+    }
+    if policy.daily_token_limit.is_none() && policy.daily_cost_limit_usd.is_none() {
+        return Ok(());
+    }
+
+    let usage = db::key_usage(&conn, &principal.id, &today()).map_err(AppError::internal)?;
+    if let Some(limit) = policy.daily_token_limit {
+        if usage.tokens() >= limit {
+            return Err(AppError::Policy(PolicyViolation::TokenQuotaExceeded {
+                limit,
+                used: usage.tokens(),
+                resets_at: resets_at(),
+            }));
+        }
+    }
+    if let Some(limit_usd) = policy.daily_cost_limit_usd {
+        if usage.cost_usd >= limit_usd {
+            return Err(AppError::Policy(PolicyViolation::CostQuotaExceeded {
+                limit_usd,
+                used_usd: usage.cost_usd,
+                resets_at: resets_at(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Adds a finished run's usage to the principal's count for today.
+pub fn record(state: &ServerState, principal: &Principal, input_tokens: u64, output_tokens: u64, cost_usd: Option<f64>) {
+    let usage = DailyUsage { input_tokens, output_tokens, cost_usd: cost_usd.unwrap_or(0.0) };
+    let recorded = state
+        .db
+        .lock()
+        .map_err(|_| anyhow::anyhow!("database lock error"))
+        .and_then(|conn| db::add_key_usage(&conn, &principal.id, &today(), &usage));
+    if let Err(e) = recorded {
+        error!("Failed to record usage for {}: {}", principal.id, e);
+    }
+}
+
+/// A key's effective policy and today's usage.
+pub fn quota(state: &ServerState, key: &str) -> Result<KeyQuotaInfo, AppError> {
+    let conn = state.db_lock()?;
+    let (policy, inherited) = effective_policy(&conn, key).map_err(AppError::internal)?;
+    let usage = db::key_usage(&conn, key, &today()).map_err(AppError::internal)?;
+    Ok(KeyQuotaInfo { key: key.to_string(), policy, inherited, usage })
+}
+
+/// Every saved policy with its key's usage today.
+pub fn quotas(state: &ServerState) -> Result<Vec<KeyQuotaInfo>, AppError> {
+    let conn = state.db_lock()?;
+    let day = today();
+    db::list_key_policies(&conn)
+        .map_err(AppError::internal)?
+        .into_iter()
+        .map(|(key, policy)| {
+            let usage = db::key_usage(&conn, &key, &day).map_err(AppError::internal)?;
+            Ok(KeyQuotaInfo { key, policy, inherited: false, usage })
+        })
+        .collect()
+}
+
+/// Saves a key's policy after checking its models and limits.
+pub fn set_policy(state: &ServerState, key: &str, policy: KeyPolicy) -> Result<KeyQuotaInfo, AppError> {
+    if key.trim().is_empty() {
+        return Err(AppError::BadRequest("key must not be empty".into()));
+    }
+    for model in policy.models.iter().flatten() {
+        if !state.models().iter().any(|m| &m.id == model) {
+            return Err(AppError::BadRequest(format!("Unknown model '{}'", model)));
+        }
+    }
+    if policy.daily_cost_limit_usd.is_some_and(|limit| limit < 0.0) {
+        return Err(AppError::BadRequest("daily_cost_limit_usd must not be negative".into()));
+    }
+    {
+        // Released before `quota` locks the database again
+        let conn = state.db_lock()?;
+        db::save_key_policy(&conn, key, &policy).map_err(AppError::internal)?;
+    }
+    quota(state, key)
+}
+
+/// Deletes a key's policy, so the `*` policy applies to it again.
+pub fn delete_policy(state: &ServerState, key: &str) -> Result<(), AppError> {
+    let conn = state.db_lock()?;
+    if !db::delete_key_policy(&conn, key).map_err(AppError::internal)? {
+        return Err(AppError::NotFound(format!("no policy for key: {}", key)));
+    }
+    Ok(())
+}
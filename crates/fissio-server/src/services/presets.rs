@@ -0,0 +1,84 @@
+//! Reloading pipeline presets from disk while the server runs.
+//!
+//! A watcher on the presets directory reloads every preset when a JSON file
+//! in it changes, so prompt edits take effect on the next run. A directory
+//! that no longer parses keeps the presets loaded before, so a half-saved
+//! file can't take presets away.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use fissio_config::{ConfigError, PresetRegistry};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tracing::{info, warn};
+
+use crate::dto::PipelineInfo;
+use crate::services;
+use crate::ServerState;
+
+/// How long to wait after a change for an editor to finish writing.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Converts presets into the templates the editor lists.
+pub fn templates(presets: &PresetRegistry) -> Vec<PipelineInfo> {
+    presets
+        .list()
+        .into_iter()
+        .map(|p| services::pipeline::pipeline_config_to_info(p, None))
+        .collect()
+}
+
+/// Reloads every preset from the presets directory, returning how many loaded.
+pub async fn reload(state: &ServerState) -> Result<usize, ConfigError> {
+    let presets = PresetRegistry::load_from_dir(&state.presets_dir)?;
+    let count = presets.list().len();
+    *state.templates.write().await = templates(&presets);
+    *state.presets.write().await = presets;
+    services::init::invalidate(state).await;
+    info!("Reloaded {} presets from {}", count, state.presets_dir.display());
+    Ok(count)
+}
+
+/// Spawns a watcher that reloads presets when a JSON file in the presets
+/// directory changes. Disabled with `PRESETS_WATCH=false`.
+pub fn spawn_preset_watcher(state: Arc<ServerState>) {
+    if std::env::var("PRESETS_WATCH").is_ok_and(|v| v == "false") {
+        info!("Preset watcher disabled");
+        return;
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+            if event.paths.iter().any(|p| p.extension().is_some_and(|ext| ext == "json")) {
+                let _ = tx.send(());
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Preset watcher error: {}", e),
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("Failed to start preset watcher: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&state.presets_dir, RecursiveMode::NonRecursive) {
+        warn!("Failed to watch {}: {}", state.presets_dir.display(), e);
+        return;
+    }
+    info!("Watching {} for preset changes", state.presets_dir.display());
+
+    tokio::spawn(async move {
+        // Dropping the watcher stops it, so the task keeps it alive
+        let _watcher = watcher;
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+            if let Err(e) = reload(&state).await {
+                warn!("Keeping previous presets: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,340 @@
+//! Recurring pipeline runs.
+//!
+//! A schedule runs a pipeline on a fixed input whenever its cron expression
+//! matches, in UTC. The scheduler wakes at the start of every minute and runs
+//! each schedule that is due; a schedule missed while the server was down runs
+//! once when it comes back. Each run is logged like a chat run, recorded in
+//! the database, and POSTed to the schedule's webhook if it has one.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{error, info, warn};
+
+use crate::auth::Tenant;
+use crate::db;
+use crate::dto::{CreateScheduleRequest, ScheduleInfo, ScheduleRunInfo};
+use crate::error::AppError;
+use crate::services::pipeline::find_pipeline;
+use crate::ServerState;
+
+/// Runs returned by GET /schedules/{id}/runs.
+const RUN_HISTORY_LIMIT: usize = 100;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `next_run_at` of a schedule whose cron expression never matches again.
+const NEVER: &str = "9999-12-31 23:59:59";
+
+/// A parsed five-field cron expression: minute, hour, day of month, month,
+/// and day of week (0 or 7 is Sunday).
+///
+/// Fields take `*`, values, ranges (`1-5`), lists (`1,15`), and steps
+/// (`*/15`, `0-30/10`). `@hourly`, `@daily`, `@weekly`, `@monthly`, and
+/// `@yearly` are shorthands. As in cron, when both day fields are restricted
+/// a day matching either one matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for Cron {
+    type Err = String;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expr => expr,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).map_err(|e| format!("day of week: {}", e))?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Cron {
+            minutes: parse_field(minute, 0, 59).map_err(|e| format!("minute: {}", e))?,
+            hours: parse_field(hour, 0, 23).map_err(|e| format!("hour: {}", e))?,
+            days: parse_field(day, 1, 31).map_err(|e| format!("day of month: {}", e))?,
+            months: parse_field(month, 1, 12).map_err(|e| format!("month: {}", e))?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+/// Parses one cron field into a bitmask of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be positive".into());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let value = |v: &str| -> Result<u32, String> {
+            let v: u32 = v.parse().map_err(|_| format!("invalid value '{}'", v))?;
+            if v < min || v > max {
+                return Err(format!("{} is outside {}-{}", v, min, max));
+            }
+            Ok(v)
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` means from 5 to the end
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range {}-{} is backwards", start, end));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl Cron {
+    /// The first matching minute after `after` (Unix seconds), or `None` if
+    /// nothing matches within five years (e.g. `0 0 31 2 *`).
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        let limit = after + 5 * 366 * 86_400;
+        let mut t = (after.div_euclid(60) + 1) * 60;
+        while t <= limit {
+            let day = t.div_euclid(86_400);
+            if !self.matches_day(day) {
+                t = (day + 1) * 86_400;
+                continue;
+            }
+            let seconds = t - day * 86_400;
+            let (hour, minute) = (seconds / 3600, seconds % 3600 / 60);
+            if self.hours & (1 << hour) == 0 {
+                t = day * 86_400 + (hour + 1) * 3600;
+                continue;
+            }
+            if self.minutes & (1 << minute) == 0 {
+                t += 60;
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn matches_day(&self, day: i64) -> bool {
+        let (_, month, day_of_month) = civil_from_days(day);
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let weekday = (day + 4).rem_euclid(7); // 1970-01-01 was a Thursday
+        let day_ok = self.days & (1 << day_of_month) != 0;
+        let weekday_ok = self.weekdays & (1 << weekday) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day_ok || weekday_ok,
+            _ => day_ok && weekday_ok,
+        }
+    }
+}
+
+/// Year, month, and day of a day count since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats Unix seconds as `YYYY-MM-DD HH:MM:SS`, SQLite's `datetime()` format.
+pub(crate) fn format_utc(seconds: i64) -> String {
+    let day = seconds.div_euclid(86_400);
+    let (year, month, day_of_month) = civil_from_days(day);
+    let s = seconds.rem_euclid(86_400);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day_of_month, s / 3600, s % 3600 / 60, s % 60)
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+/// Fills a schedule's input template for a run starting at `now`.
+fn render_input(template: &str, now: i64) -> String {
+    let datetime = format_utc(now);
+    template.replace("{{datetime}}", &datetime).replace("{{date}}", &datetime[..10])
+}
+
+/// Creates a schedule owned by `tenant` after checking its cron expression,
+/// pipeline, model, and webhook.
+pub async fn create(state: &ServerState, tenant: &Tenant, req: CreateScheduleRequest) -> Result<ScheduleInfo, AppError> {
+    let cron: Cron = req.cron.parse().map_err(|e| AppError::BadRequest(format!("Invalid cron expression: {}", e)))?;
+    let next = cron
+        .next_after(unix_now())
+        .ok_or_else(|| AppError::BadRequest(format!("Cron expression '{}' never matches", req.cron)))?;
+    find_pipeline(state, tenant, &req.pipeline_id).await?;
+    if let Some(model_id) = &req.model_id {
+        if !state.models().iter().any(|m| &m.id == model_id) {
+            return Err(AppError::BadRequest(format!("Unknown model '{}'", model_id)));
+        }
+    }
+    if let Some(url) = &req.webhook_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::BadRequest("webhook_url must be an http(s) URL".into()));
+        }
+        if let Some(hosts) = &state.local_hosts {
+            hosts.check(url).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        }
+    }
+
+    let schedule = ScheduleInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        pipeline_id: req.pipeline_id,
+        cron: req.cron.trim().to_string(),
+        input: req.input,
+        model_id: req.model_id,
+        webhook_url: req.webhook_url,
+        next_run_at: format_utc(next),
+        last_run_at: None,
+        created_at: format_utc(unix_now()),
+        owner: tenant.id().map(String::from),
+    };
+    {
+        let conn = state.db_lock()?;
+        db::save_schedule(&conn, &schedule).map_err(AppError::internal)?;
+    }
+    info!("Scheduled pipeline {} ({}), next run {}", schedule.pipeline_id, schedule.cron, schedule.next_run_at);
+    Ok(schedule)
+}
+
+/// Returns a schedule's most recent runs, newest first.
+pub fn runs(state: &ServerState, tenant: &Tenant, id: &str) -> Result<Vec<ScheduleRunInfo>, AppError> {
+    let conn = state.db_lock()?;
+    if db::get_schedule(&conn, id, tenant.id()).map_err(AppError::internal)?.is_none() {
+        return Err(AppError::NotFound(format!("schedule not found: {}", id)));
+    }
+    db::list_schedule_runs(&conn, id, RUN_HISTORY_LIMIT).map_err(AppError::internal)
+}
+
+/// Spawns the scheduler, which runs due schedules at the start of every minute.
+pub fn spawn_scheduler(state: Arc<ServerState>) {
+    tokio::spawn(async move {
+        loop {
+            let now = unix_now();
+            tokio::time::sleep(Duration::from_secs((60 - now.rem_euclid(60)) as u64)).await;
+            match take_due(&state, unix_now()) {
+                Ok(due) => {
+                    for schedule in due {
+                        tokio::spawn(run_schedule(state.clone(), schedule));
+                    }
+                }
+                Err(e) => error!("Failed to read due schedules: {}", e),
+            }
+        }
+    });
+}
+
+/// Returns the schedules due at `now`, moving each to its next run.
+fn take_due(state: &ServerState, now: i64) -> anyhow::Result<Vec<ScheduleInfo>> {
+    let conn = state.db.lock().map_err(|_| anyhow::anyhow!("database lock error"))?;
+    let due = db::due_schedules(&conn, &format_utc(now))?;
+    for schedule in &due {
+        let next = match schedule.cron.parse::<Cron>().ok().and_then(|c| c.next_after(now)) {
+            Some(next) => format_utc(next),
+            None => {
+                warn!("Schedule {} has no next run for '{}'", schedule.id, schedule.cron);
+                NEVER.to_string()
+            }
+        };
+        db::set_schedule_run_times(&conn, &schedule.id, &format_utc(now), &next)?;
+    }
+    Ok(due)
+}
+
+/// Runs a schedule once as its owner, records the result, and delivers it to the webhook.
+async fn run_schedule(state: Arc<ServerState>, schedule: ScheduleInfo) {
+    let tenant = Tenant(schedule.owner.clone());
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let input = render_input(&schedule.input, unix_now());
+    info!("Running schedule {} (pipeline {}) as run {}", schedule.id, schedule.pipeline_id, run_id);
+
+    let started = state
+        .db
+        .lock()
+        .map_err(|_| anyhow::anyhow!("database lock error"))
+        .and_then(|conn| db::start_schedule_run(&conn, &run_id, &schedule.id, &schedule.pipeline_id));
+    if let Err(e) = started {
+        error!("Failed to record run of schedule {}: {}", schedule.id, e);
+    }
+
+    let (status, output, input_tokens, output_tokens) = match find_pipeline(&state, &tenant, &schedule.pipeline_id).await {
+        Ok((config, _)) => {
+            let model_id = schedule.model_id.as_deref();
+            let result = crate::handlers::chat::run_scheduled(&state, &tenant, &run_id, &config, &input, model_id).await;
+            let status = if result.response.trim().is_empty() { "failed" } else { "completed" };
+            (status, result.response, result.input_tokens, result.output_tokens)
+        }
+        Err(e) => {
+            warn!("Schedule {} can't run: {}", schedule.id, e);
+            ("failed", e.to_string(), 0, 0)
+        }
+    };
+
+    let run = state
+        .db
+        .lock()
+        .map_err(|_| anyhow::anyhow!("database lock error"))
+        .and_then(|conn| db::finish_schedule_run(&conn, &run_id, status, &output, input_tokens, output_tokens));
+    let run = match run {
+        Ok(Some(run)) => run,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to record result of run {}: {}", run_id, e);
+            return;
+        }
+    };
+    info!("Schedule {} run {} {}", schedule.id, run_id, status);
+
+    if let Some(url) = &schedule.webhook_url {
+        deliver(url, &run).await;
+    }
+}
+
+/// POSTs a run's result to a webhook. Failures are logged, not retried.
+async fn deliver(url: &str, run: &ScheduleRunInfo) {
+    let sent = reqwest::Client::new().post(url).timeout(WEBHOOK_TIMEOUT).json(run).send().await;
+    match sent.and_then(|r| r.error_for_status()) {
+        Ok(_) => info!("Delivered run {} to {}", run.run_id, url),
+        Err(e) => warn!("Failed to deliver run {} to {}: {}", run.run_id, url, e),
+    }
+}
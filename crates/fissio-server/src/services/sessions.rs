@@ -0,0 +1,41 @@
+//! Conversation history kept on the server, per session.
+
+use anyhow::Result;
+use fissio_core::Message;
+
+use crate::db;
+use crate::ServerState;
+
+/// Longest title taken from a session's first message.
+const TITLE_CHARS: usize = 60;
+
+/// Returns a session's messages, creating the session for `owner` if it
+/// doesn't exist, or `None` if it belongs to another tenant.
+pub fn load_history(state: &ServerState, session_id: &str, owner: Option<&str>) -> Result<Option<Vec<Message>>> {
+    let conn = state.db.lock().map_err(|_| anyhow::anyhow!("database lock error"))?;
+    db::create_session(&conn, session_id, "", owner)?;
+    if db::get_session(&conn, session_id, owner)?.is_none() {
+        return Ok(None);
+    }
+    db::session_messages(&conn, session_id).map(Some)
+}
+
+/// Appends a user message and the response to it to a session.
+pub fn append_turn(state: &ServerState, session_id: &str, message: &str, response: &str) -> Result<()> {
+    let conn = state.db.lock().map_err(|_| anyhow::anyhow!("database lock error"))?;
+    db::append_session_messages(
+        &conn,
+        session_id,
+        &[Message::user(message), Message::assistant(response)],
+        &title_for(message),
+    )
+}
+
+/// A session title from its first message: the first line, shortened.
+fn title_for(message: &str) -> String {
+    let line = message.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    match line.char_indices().nth(TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", line[..end].trim_end()),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,64 @@
+//! Spoken answers for chats that set `audio_output`.
+//!
+//! The final response is synthesized after the run and kept in memory for
+//! [`RUN_EVENT_TTL`], as long as the run's events, so the `audio` event's URL
+//! stays valid for clients resuming the stream. Clips are held per replica:
+//! fetching one needs the replica that ran the chat.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use axum::body::Bytes;
+use fissio_llm::AudioFormat;
+use tracing::info;
+
+use crate::auth::Tenant;
+use crate::error::AppError;
+use crate::shared::RUN_EVENT_TTL;
+use crate::ServerState;
+
+/// A synthesized answer.
+#[derive(Clone)]
+pub struct AudioClip {
+    pub format: AudioFormat,
+    pub bytes: Bytes,
+}
+
+/// Synthesized answers by tenant-scoped clip ID.
+#[derive(Default)]
+pub struct AudioClips {
+    clips: Mutex<HashMap<String, (Instant, AudioClip)>>,
+}
+
+impl AudioClips {
+    /// Stores a clip, dropping expired ones, and returns its ID.
+    fn insert(&self, tenant: &Tenant, clip: AudioClip) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        if let Ok(mut clips) = self.clips.lock() {
+            clips.retain(|_, (created, _)| created.elapsed() < RUN_EVENT_TTL);
+            clips.insert(tenant.scope(&id), (Instant::now(), clip));
+        }
+        id
+    }
+
+    /// A clip of `tenant`'s that hasn't expired.
+    pub fn get(&self, tenant: &Tenant, id: &str) -> Option<AudioClip> {
+        let clips = self.clips.lock().ok()?;
+        let (created, clip) = clips.get(&tenant.scope(id))?;
+        (created.elapsed() < RUN_EVENT_TTL).then(|| clip.clone())
+    }
+}
+
+/// Synthesizes `text` and stores it for `tenant`, returning the clip's ID and format.
+pub async fn speak(state: &ServerState, tenant: &Tenant, text: &str) -> Result<(String, AudioFormat), AppError> {
+    let speech = state
+        .speech
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("audio output is disabled; set OPENAI_API_KEY or TTS_API_BASE".into()))?;
+    let bytes = speech.synthesize(text).await.map_err(AppError::internal)?;
+    info!("Synthesized {} bytes of {} for a chat answer", bytes.len(), speech.format().as_str());
+    let format = speech.format();
+    let id = state.audio_clips.insert(tenant, AudioClip { format, bytes: Bytes::from(bytes) });
+    Ok((id, format))
+}
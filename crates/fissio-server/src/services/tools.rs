@@ -0,0 +1,84 @@
+//! Tools added at runtime through POST /tools/register.
+
+use fissio_tools::{HostAllowlist, HttpTool, McpToolProvider, McpTransport, ToolRegistry};
+
+use crate::dto::ToolDefinition;
+
+/// Whether `MCP_ALLOW_STDIO_REGISTRATION` lets definitions launch MCP servers as local processes.
+pub fn stdio_registration_allowed() -> bool {
+    std::env::var("MCP_ALLOW_STDIO_REGISTRATION").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// Checks a definition before any connection is made or process started.
+///
+/// Names must be non-empty and free of `/`, which selects namespaces, and `.`,
+/// which separates an MCP server from its tools. Stdio MCP servers need
+/// `MCP_ALLOW_STDIO_REGISTRATION`, and in local-only mode SSE servers must be
+/// on an allowed host.
+pub fn validate(definition: &ToolDefinition, local_hosts: Option<&HostAllowlist>) -> Result<(), String> {
+    let name = definition.name();
+    if name.trim().is_empty() {
+        return Err("tool name must not be empty".to_string());
+    }
+    if name.contains(['/', '.']) {
+        return Err(format!("tool name '{}' must not contain '/' or '.'", name));
+    }
+    match definition {
+        ToolDefinition::Http { spec, .. } if spec.url.trim().is_empty() => {
+            Err(format!("HTTP tool '{}' has no URL", name))
+        }
+        ToolDefinition::Mcp { server: McpTransport::Stdio { .. }, .. } if !stdio_registration_allowed() => Err(
+            "registering MCP servers that run a command is disabled; set MCP_ALLOW_STDIO_REGISTRATION=true to allow it"
+                .to_string(),
+        ),
+        ToolDefinition::Mcp { server: McpTransport::Sse { url, .. }, .. } => match local_hosts {
+            Some(hosts) => hosts.check(url).map_err(|e| e.to_string()),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Builds the tools a definition declares, connecting to its MCP server if it has one.
+///
+/// The definition's metadata, if any, is set on every tool built.
+pub async fn build(definition: &ToolDefinition, local_hosts: Option<&HostAllowlist>) -> Result<ToolRegistry, String> {
+    let mut registry = ToolRegistry::new();
+    let metadata = match definition {
+        ToolDefinition::Http { spec, metadata } => {
+            let tool = HttpTool::new(spec.clone());
+            match local_hosts {
+                Some(hosts) => registry.register(tool.with_allowed_hosts(hosts.clone())),
+                None => registry.register(tool),
+            }
+            metadata
+        }
+        ToolDefinition::Mcp { name, server, metadata } => {
+            McpToolProvider::new(name.clone(), server.clone())
+                .register_into(&mut registry)
+                .await
+                .map_err(|e| format!("MCP server '{}' unavailable: {}", name, e))?;
+            metadata
+        }
+    };
+    if let Some(metadata) = metadata {
+        for name in registry.tool_names() {
+            registry.set_metadata(name, metadata.clone());
+        }
+    }
+    Ok(registry)
+}
+
+/// Names of the tools a definition registered, as found in `registry`.
+///
+/// An HTTP definition registers its own name; an MCP definition registers
+/// `<name>.<tool>` for each of its server's tools.
+pub fn registered_names(definition: &ToolDefinition, registry: &ToolRegistry) -> Vec<String> {
+    match definition {
+        ToolDefinition::Http { spec, .. } => registry.tool_names().into_iter().filter(|n| *n == spec.name).collect(),
+        ToolDefinition::Mcp { name, .. } => {
+            let prefix = format!("{}.", name);
+            registry.tool_names().into_iter().filter(|n| n.starts_with(&prefix)).collect()
+        }
+    }
+}
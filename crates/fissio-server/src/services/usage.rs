@@ -0,0 +1,143 @@
+//! Usage reconciliation service - compares recorded tokens with provider billing.
+//!
+//! Runs once a day when `OPENAI_ADMIN_KEY` is set. Each run covers the previous
+//! UTC day (the granularity of the provider's usage buckets) and logs every model
+//! whose recorded totals diverge from what the provider billed.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fissio_llm::fetch_openai_usage;
+use fissio_monitor::{reconcile_usage, ModelUsage, ReconciliationReport};
+use tracing::{error, info, warn};
+
+use crate::ServerState;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Delay after UTC midnight before reconciling, giving the provider time to settle usage.
+const SETTLE_DELAY_MS: i64 = 60 * 60 * 1000;
+
+/// Relative difference in total tokens tolerated before a model is flagged.
+const TOLERANCE: f64 = 0.05;
+
+/// Credentials for the OpenAI organization usage API.
+#[derive(Clone)]
+pub struct UsageApiConfig {
+    pub admin_key: String,
+    pub project_id: Option<String>,
+}
+
+impl UsageApiConfig {
+    /// Reads `OPENAI_ADMIN_KEY` and optional `OPENAI_PROJECT_ID`.
+    pub fn from_env() -> Option<Self> {
+        let admin_key = std::env::var("OPENAI_ADMIN_KEY").ok().filter(|k| !k.is_empty())?;
+        Some(Self {
+            admin_key,
+            project_id: std::env::var("OPENAI_PROJECT_ID").ok().filter(|p| !p.is_empty()),
+        })
+    }
+}
+
+/// Reconciles recorded OpenAI usage against the provider for `[start_ms, end_ms)`.
+pub async fn reconcile_openai(
+    state: &ServerState,
+    api: &UsageApiConfig,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<ReconciliationReport, String> {
+    // Only cloud OpenAI models are billed by the usage API.
+    let models = state.models();
+    let openai_models: HashSet<&str> = models
+        .iter()
+        .filter(|m| m.api_base.is_none() && !m.model.starts_with("claude-"))
+        .map(|m| m.model.as_str())
+        .collect();
+
+    let internal: Vec<ModelUsage> = state.trace_store
+        .usage_by_model(start_ms, end_ms)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|u| openai_models.contains(u.model.as_str()))
+        .collect();
+
+    let reported: Vec<ModelUsage> = fetch_openai_usage(
+        &api.admin_key,
+        api.project_id.as_deref(),
+        start_ms / 1000,
+        end_ms / 1000,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .map(|u| ModelUsage {
+        model: u.model,
+        input_tokens: u.input_tokens,
+        output_tokens: u.output_tokens,
+        spans: u.requests,
+    })
+    .collect();
+
+    let report = reconcile_usage("openai", start_ms, end_ms, &internal, &reported, TOLERANCE);
+
+    for d in report.discrepancies() {
+        warn!(
+            model = %d.model,
+            status = ?d.status,
+            internal_tokens = d.internal_input_tokens + d.internal_output_tokens,
+            provider_tokens = d.provider_input_tokens + d.provider_output_tokens,
+            delta = d.token_delta,
+            "Token usage discrepancy"
+        );
+    }
+    info!(
+        "Usage reconciliation: {} models, {} discrepancies",
+        report.models.len(),
+        report.discrepancies().count()
+    );
+
+    Ok(report)
+}
+
+/// Returns the previous full UTC day as `(start_ms, end_ms)`.
+pub fn previous_day_window(now_ms: i64) -> (i64, i64) {
+    let end = now_ms - now_ms.rem_euclid(DAY_MS);
+    (end - DAY_MS, end)
+}
+
+/// Spawns the daily reconciliation job if usage API credentials are configured.
+pub fn spawn_daily_reconciliation(state: Arc<ServerState>) {
+    if state.local_hosts.is_some() {
+        info!("Local-only mode, usage reconciliation disabled");
+        return;
+    }
+    let Some(api) = UsageApiConfig::from_env() else {
+        info!("OPENAI_ADMIN_KEY not set, usage reconciliation disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        loop {
+            let now = now_ms();
+            let mut next_run = now - now.rem_euclid(DAY_MS) + SETTLE_DELAY_MS;
+            if next_run <= now {
+                next_run += DAY_MS;
+            }
+            tokio::time::sleep(Duration::from_millis((next_run - now) as u64)).await;
+
+            let (start, end) = previous_day_window(now_ms());
+            match reconcile_openai(&state, &api, start, end).await {
+                Ok(report) => *state.usage_report.write().await = Some(report),
+                Err(e) => error!("Usage reconciliation failed: {}", e),
+            }
+        }
+    });
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,347 @@
+//! State shared between server replicas.
+//!
+//! A single instance keeps everything in process memory and SQLite. When
+//! `REDIS_URL` is set (requires the `redis` feature), the degraded-mode
+//! request queue, chat run event logs, idempotency keys, and LLM rate limit
+//! windows move to Redis so replicas behind a load balancer enforce one
+//! budget, replay each queued request exactly once, run each retried request
+//! once, and let clients resume a run's stream from any replica.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// How long a run's events stay available for resuming after its last event.
+pub const RUN_EVENT_TTL: Duration = Duration::from_secs(600);
+
+/// How long an idempotency key keeps pointing at the run it started. The
+/// run's events are kept as long once it ends, so a late retry can replay them.
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Queue of chat requests (as JSON) waiting for providers to recover.
+#[async_trait]
+pub trait RequestQueue: Send + Sync {
+    /// Queues a request and returns its ID.
+    async fn enqueue(&self, request_json: String) -> Result<i64>;
+
+    /// Removes and returns all queued requests, oldest first.
+    async fn take_all(&self) -> Result<Vec<(i64, String)>>;
+}
+
+/// An SSE event emitted by a chat run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+    /// Position in the run, starting at 0; sent as the SSE event ID.
+    pub seq: u64,
+    /// SSE event name.
+    pub event: String,
+    /// JSON payload.
+    pub data: String,
+}
+
+/// Append-only log of the events each chat run has streamed.
+#[async_trait]
+pub trait EventLog: Send + Sync {
+    /// Appends an event to a run and returns its sequence number.
+    async fn append(&self, run_id: &str, event: &str, data: &str) -> Result<u64>;
+
+    /// Returns the run's events starting at `from`, or `None` if the run is unknown or expired.
+    async fn read_from(&self, run_id: &str, from: u64) -> Result<Option<Vec<RunEvent>>>;
+
+    /// Keeps a finished run's events for `ttl` from now instead of [`RUN_EVENT_TTL`].
+    async fn keep_for(&self, run_id: &str, ttl: Duration) -> Result<()>;
+}
+
+/// Event log kept in process memory; runs expire [`RUN_EVENT_TTL`] after
+/// their last event, unless kept longer with [`EventLog::keep_for`].
+#[derive(Default)]
+pub struct InMemoryEventLog {
+    runs: Mutex<HashMap<String, LoggedRun>>,
+}
+
+struct LoggedRun {
+    updated: Instant,
+    /// How long the run lasts after `updated`.
+    ttl: Duration,
+    events: Vec<RunEvent>,
+}
+
+#[async_trait]
+impl EventLog for InMemoryEventLog {
+    async fn append(&self, run_id: &str, event: &str, data: &str) -> Result<u64> {
+        let mut runs = self.runs.lock().map_err(|_| anyhow::anyhow!("event log lock error"))?;
+        let now = Instant::now();
+        runs.retain(|_, run| now.duration_since(run.updated) < run.ttl);
+
+        let run = runs
+            .entry(run_id.to_string())
+            .or_insert_with(|| LoggedRun { updated: now, ttl: RUN_EVENT_TTL, events: Vec::new() });
+        run.updated = now;
+        let seq = run.events.len() as u64;
+        run.events.push(RunEvent { seq, event: event.to_string(), data: data.to_string() });
+        Ok(seq)
+    }
+
+    async fn read_from(&self, run_id: &str, from: u64) -> Result<Option<Vec<RunEvent>>> {
+        let runs = self.runs.lock().map_err(|_| anyhow::anyhow!("event log lock error"))?;
+        Ok(runs
+            .get(run_id)
+            .map(|run| run.events.iter().skip(from as usize).cloned().collect()))
+    }
+
+    async fn keep_for(&self, run_id: &str, ttl: Duration) -> Result<()> {
+        let mut runs = self.runs.lock().map_err(|_| anyhow::anyhow!("event log lock error"))?;
+        if let Some(run) = runs.get_mut(run_id) {
+            run.updated = Instant::now();
+            run.ttl = ttl;
+        }
+        Ok(())
+    }
+}
+
+/// Runs started by requests carrying an idempotency key.
+#[async_trait]
+pub trait IdempotencyKeys: Send + Sync {
+    /// Records `run_id` as the key's run unless the key already has one, which
+    /// is returned instead. Of concurrent claims, exactly one gets `None`.
+    async fn claim(&self, key: &str, run_id: &str) -> Result<Option<String>>;
+}
+
+/// Idempotency keys kept in process memory; each expires [`IDEMPOTENCY_TTL`] after it is claimed.
+#[derive(Default)]
+pub struct InMemoryIdempotencyKeys {
+    keys: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+#[async_trait]
+impl IdempotencyKeys for InMemoryIdempotencyKeys {
+    async fn claim(&self, key: &str, run_id: &str) -> Result<Option<String>> {
+        let mut keys = self.keys.lock().map_err(|_| anyhow::anyhow!("idempotency key lock error"))?;
+        keys.retain(|_, (claimed, _)| claimed.elapsed() < IDEMPOTENCY_TTL);
+        if let Some((_, existing)) = keys.get(key) {
+            return Ok(Some(existing.clone()));
+        }
+        keys.insert(key.to_string(), (Instant::now(), run_id.to_string()));
+        Ok(None)
+    }
+}
+
+/// Backends for state that must be consistent across replicas.
+pub struct SharedBackends {
+    /// Shared degraded-mode queue; `None` uses the local SQLite queue.
+    pub request_queue: Option<Arc<dyn RequestQueue>>,
+    pub event_log: Arc<dyn EventLog>,
+    pub idempotency_keys: Arc<dyn IdempotencyKeys>,
+}
+
+impl SharedBackends {
+    fn local() -> Self {
+        Self {
+            request_queue: None,
+            event_log: Arc::new(InMemoryEventLog::default()),
+            idempotency_keys: Arc::new(InMemoryIdempotencyKeys::default()),
+        }
+    }
+}
+
+/// Connects to Redis if `REDIS_URL` is set and installs the shared rate limit backend.
+///
+/// Falls back to process-local state when Redis is not configured or unreachable.
+pub async fn init_from_env() -> SharedBackends {
+    match std::env::var("REDIS_URL").ok().filter(|u| !u.is_empty()) {
+        Some(url) => connect(&url).await.unwrap_or_else(SharedBackends::local),
+        None => SharedBackends::local(),
+    }
+}
+
+#[cfg(feature = "redis")]
+async fn connect(url: &str) -> Option<SharedBackends> {
+    use tracing::info;
+
+    let connected = async {
+        let backend = fissio_llm::RedisRateLimitBackend::connect(url).await?;
+        let conn = redis_state::connect(url).await?;
+        anyhow::Ok((backend, conn))
+    };
+    let (backend, conn) = match connected.await {
+        Ok(connected) => connected,
+        Err(e) => {
+            warn!("Failed to connect to Redis, using local state: {}", e);
+            return None;
+        }
+    };
+    fissio_llm::set_rate_limit_backend(Arc::new(backend));
+
+    info!("Shared state enabled via Redis");
+    Some(SharedBackends {
+        request_queue: Some(Arc::new(redis_state::RedisRequestQueue::new(conn.clone()))),
+        event_log: Arc::new(redis_state::RedisEventLog::new(conn.clone())),
+        idempotency_keys: Arc::new(redis_state::RedisIdempotencyKeys::new(conn)),
+    })
+}
+
+#[cfg(not(feature = "redis"))]
+async fn connect(_url: &str) -> Option<SharedBackends> {
+    warn!("REDIS_URL is set but fissio-server was built without the `redis` feature, using local state");
+    None
+}
+
+#[cfg(feature = "redis")]
+mod redis_state {
+    use std::time::Duration;
+
+    use anyhow::{Context, Result};
+    use async_trait::async_trait;
+    use redis::aio::ConnectionManager;
+    use redis::AsyncCommands;
+
+    use super::{EventLog, IdempotencyKeys, RequestQueue, RunEvent, IDEMPOTENCY_TTL, RUN_EVENT_TTL};
+
+    const QUEUE_KEY: &str = "fissio:queue:requests";
+    const SEQ_KEY: &str = "fissio:queue:seq";
+    const RUN_KEY_PREFIX: &str = "fissio:run:";
+    const IDEMPOTENCY_KEY_PREFIX: &str = "fissio:idempotency:";
+
+    pub async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url).context("invalid REDIS_URL")?;
+        ConnectionManager::new(client).await.context("failed to connect to Redis")
+    }
+
+    /// Request queue stored in a Redis list. Entries are `{id}:{json}`.
+    pub struct RedisRequestQueue {
+        conn: ConnectionManager,
+    }
+
+    impl RedisRequestQueue {
+        pub fn new(conn: ConnectionManager) -> Self {
+            Self { conn }
+        }
+    }
+
+    #[async_trait]
+    impl RequestQueue for RedisRequestQueue {
+        async fn enqueue(&self, request_json: String) -> Result<i64> {
+            let mut conn = self.conn.clone();
+            let id: i64 = conn.incr(SEQ_KEY, 1).await.context("failed to queue request")?;
+            let _: () = conn
+                .rpush(QUEUE_KEY, format!("{}:{}", id, request_json))
+                .await
+                .context("failed to queue request")?;
+            Ok(id)
+        }
+
+        async fn take_all(&self) -> Result<Vec<(i64, String)>> {
+            let mut conn = self.conn.clone();
+            // Read and clear in one transaction so only one replica replays each request.
+            let (entries, _): (Vec<String>, i64) = redis::pipe()
+                .atomic()
+                .lrange(QUEUE_KEY, 0, -1)
+                .del(QUEUE_KEY)
+                .query_async(&mut conn)
+                .await
+                .context("failed to read queued requests")?;
+
+            Ok(entries
+                .into_iter()
+                .filter_map(|entry| {
+                    let (id, json) = entry.split_once(':')?;
+                    Some((id.parse().ok()?, json.to_string()))
+                })
+                .collect())
+        }
+    }
+
+    /// Run events stored in one Redis list per run, indexed by sequence number.
+    pub struct RedisEventLog {
+        conn: ConnectionManager,
+    }
+
+    impl RedisEventLog {
+        pub fn new(conn: ConnectionManager) -> Self {
+            Self { conn }
+        }
+    }
+
+    #[async_trait]
+    impl EventLog for RedisEventLog {
+        async fn append(&self, run_id: &str, event: &str, data: &str) -> Result<u64> {
+            let key = format!("{}{}", RUN_KEY_PREFIX, run_id);
+            let entry = serde_json::to_string(&(event, data))?;
+            let (len, _): (u64, bool) = redis::pipe()
+                .atomic()
+                .rpush(&key, entry)
+                .expire(&key, RUN_EVENT_TTL.as_secs() as i64)
+                .query_async(&mut self.conn.clone())
+                .await
+                .context("failed to append run event")?;
+            Ok(len - 1)
+        }
+
+        async fn read_from(&self, run_id: &str, from: u64) -> Result<Option<Vec<RunEvent>>> {
+            let key = format!("{}{}", RUN_KEY_PREFIX, run_id);
+            let (exists, entries): (bool, Vec<String>) = redis::pipe()
+                .exists(&key)
+                .lrange(&key, from as isize, -1)
+                .query_async(&mut self.conn.clone())
+                .await
+                .context("failed to read run events")?;
+            if !exists {
+                return Ok(None);
+            }
+
+            let events = entries
+                .into_iter()
+                .zip(from..)
+                .filter_map(|(entry, seq)| {
+                    let (event, data) = serde_json::from_str::<(String, String)>(&entry).ok()?;
+                    Some(RunEvent { seq, event, data })
+                })
+                .collect();
+            Ok(Some(events))
+        }
+
+        async fn keep_for(&self, run_id: &str, ttl: Duration) -> Result<()> {
+            let key = format!("{}{}", RUN_KEY_PREFIX, run_id);
+            let mut conn = self.conn.clone();
+            let _: bool = conn.expire(&key, ttl.as_secs() as i64).await.context("failed to keep run events")?;
+            Ok(())
+        }
+    }
+
+    /// Idempotency keys stored as Redis strings holding the run ID.
+    pub struct RedisIdempotencyKeys {
+        conn: ConnectionManager,
+    }
+
+    impl RedisIdempotencyKeys {
+        pub fn new(conn: ConnectionManager) -> Self {
+            Self { conn }
+        }
+    }
+
+    #[async_trait]
+    impl IdempotencyKeys for RedisIdempotencyKeys {
+        async fn claim(&self, key: &str, run_id: &str) -> Result<Option<String>> {
+            let key = format!("{}{}", IDEMPOTENCY_KEY_PREFIX, key);
+            let mut conn = self.conn.clone();
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(run_id)
+                .arg("NX")
+                .arg("EX")
+                .arg(IDEMPOTENCY_TTL.as_secs())
+                .query_async(&mut conn)
+                .await
+                .context("failed to claim idempotency key")?;
+            if claimed.is_some() {
+                return Ok(None);
+            }
+            conn.get(&key).await.context("failed to read idempotency key")
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Optional HTTPS termination.
+//!
+//! Built with the `tls` feature, the server serves HTTPS from PEM certificate
+//! and key files. The `acme` feature adds automatic certificates from Let's
+//! Encrypt via the TLS-ALPN-01 challenge, which requires the server to be
+//! reachable on port 443 for every configured domain.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use axum::Router;
+
+/// How the server obtains its certificate.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "tls"), allow(dead_code))]
+pub enum TlsConfig {
+    /// PEM certificate chain and private key on disk.
+    Files { cert: PathBuf, key: PathBuf },
+    /// Certificates issued and renewed automatically.
+    Acme(AcmeSettings),
+}
+
+/// Let's Encrypt settings for the `acme` feature.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "acme"), allow(dead_code))]
+pub struct AcmeSettings {
+    pub domains: Vec<String>,
+    pub contact: Vec<String>,
+    pub cache_dir: PathBuf,
+    /// Use the production directory instead of staging.
+    pub production: bool,
+}
+
+impl TlsConfig {
+    /// Reads `TLS_CERT_PATH` + `TLS_KEY_PATH`, or `ACME_DOMAINS` (with `ACME_CONTACT`,
+    /// `ACME_CACHE_DIR`, `ACME_PRODUCTION`). Returns `None` when neither is set.
+    pub fn from_env() -> Option<Self> {
+        if let (Ok(cert), Ok(key)) = (std::env::var("TLS_CERT_PATH"), std::env::var("TLS_KEY_PATH")) {
+            return Some(Self::Files { cert: cert.into(), key: key.into() });
+        }
+
+        let domains = split_list(&std::env::var("ACME_DOMAINS").ok()?);
+        if domains.is_empty() {
+            return None;
+        }
+        Some(Self::Acme(AcmeSettings {
+            domains,
+            contact: split_list(&std::env::var("ACME_CONTACT").unwrap_or_default()),
+            cache_dir: std::env::var("ACME_CACHE_DIR").unwrap_or_else(|_| "data/acme".into()).into(),
+            production: std::env::var("ACME_PRODUCTION").is_ok_and(|v| v == "true" || v == "1"),
+        }))
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+/// Serves `app` over HTTPS on `addr`.
+#[cfg(feature = "tls")]
+pub async fn serve(addr: SocketAddr, app: Router, tls: TlsConfig) -> Result<()> {
+    use anyhow::Context;
+    use axum_server::tls_rustls::RustlsConfig;
+    use tracing::info;
+
+    match tls {
+        TlsConfig::Files { cert, key } => {
+            let config = RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .with_context(|| format!("failed to load TLS certificate {} / key {}", cert.display(), key.display()))?;
+            info!("Starting HTTPS server on {}", addr);
+            axum_server::bind_rustls(addr, config)
+                .serve(app.into_make_service())
+                .await?;
+            Ok(())
+        }
+        TlsConfig::Acme(acme) => serve_acme(addr, app, acme).await,
+    }
+}
+
+#[cfg(not(feature = "tls"))]
+pub async fn serve(_addr: SocketAddr, _app: Router, _tls: TlsConfig) -> Result<()> {
+    anyhow::bail!("TLS is configured but fissio-server was built without the `tls` feature")
+}
+
+#[cfg(feature = "acme")]
+async fn serve_acme(addr: SocketAddr, app: Router, acme: AcmeSettings) -> Result<()> {
+    use futures::StreamExt;
+    use rustls_acme::caches::DirCache;
+    use rustls_acme::AcmeConfig;
+    use tracing::{error, info};
+
+    info!(
+        "Requesting certificates for {:?} from Let's Encrypt ({})",
+        acme.domains,
+        if acme.production { "production" } else { "staging" }
+    );
+    let mut state = AcmeConfig::new(acme.domains)
+        .contact(acme.contact.iter().map(|c| format!("mailto:{}", c)))
+        .cache(DirCache::new(acme.cache_dir))
+        .directory_lets_encrypt(acme.production)
+        .state();
+    let acceptor = state.axum_acceptor(state.default_rustls_config());
+
+    tokio::spawn(async move {
+        while let Some(event) = state.next().await {
+            match event {
+                Ok(ok) => info!("ACME: {:?}", ok),
+                Err(e) => error!("ACME error: {:?}", e),
+            }
+        }
+    });
+
+    info!("Starting HTTPS server on {}", addr);
+    axum_server::bind(addr)
+        .acceptor(acceptor)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
+
+#[cfg(all(feature = "tls", not(feature = "acme")))]
+async fn serve_acme(_addr: SocketAddr, _app: Router, _acme: AcmeSettings) -> Result<()> {
+    anyhow::bail!("ACME is configured but fissio-server was built without the `acme` feature")
+}
@@ -0,0 +1,142 @@
+//! The clock: the `current_time` tool, and the [`Timezone`] that the engine
+//! also uses to fill `{{now}}` in node prompts.
+
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// A timezone from the IANA database, such as `Europe/Berlin`, or `UTC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timezone(Tz);
+
+impl Timezone {
+    pub const UTC: Timezone = Timezone(Tz::UTC);
+
+    /// `FISSIO_TIMEZONE`, or UTC when it is unset or not a known timezone.
+    pub fn from_env() -> Self {
+        match std::env::var("FISSIO_TIMEZONE").ok().filter(|v| !v.is_empty()) {
+            Some(name) => name.parse().unwrap_or_else(|e| {
+                tracing::warn!("Ignoring FISSIO_TIMEZONE: {}", e);
+                Self::UTC
+            }),
+            None => Self::UTC,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.0)
+    }
+
+    /// The current date and time for a prompt, e.g. `Wednesday, October 14, 2026, 09:30 EDT (America/New_York)`.
+    pub fn describe_now(&self) -> String {
+        format!("{} ({})", self.now().format("%A, %B %-d, %Y, %H:%M %Z"), self.name())
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self::UTC
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = ToolError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        name.trim()
+            .parse::<Tz>()
+            .map(Timezone)
+            .map_err(|_| ToolError::InvalidArguments(format!("unknown timezone '{}'; use an IANA name such as 'Europe/Berlin'", name)))
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Serialize)]
+struct CurrentTime {
+    /// RFC 3339, with the timezone's offset.
+    datetime: String,
+    date: String,
+    time: String,
+    weekday: String,
+    timezone: &'static str,
+    utc_offset: String,
+    unix: i64,
+}
+
+/// The `current_time` tool: the current date and time in the configured
+/// timezone, or in one the LLM names.
+pub struct CurrentTimeTool {
+    timezone: Timezone,
+}
+
+impl CurrentTimeTool {
+    pub fn new(timezone: Timezone) -> Self {
+        Self { timezone }
+    }
+
+    /// Reports times in `FISSIO_TIMEZONE`, else UTC.
+    pub fn from_env() -> Self {
+        Self::new(Timezone::from_env())
+    }
+}
+
+#[async_trait]
+impl Tool for CurrentTimeTool {
+    fn name(&self) -> &str {
+        "current_time"
+    }
+
+    fn description(&self) -> &str {
+        "Get the current date, time, and weekday. Use it for anything that depends on today's date."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "timezone": {
+                    "type": "string",
+                    "description": format!("IANA timezone such as 'Asia/Tokyo'; defaults to {}", self.timezone)
+                }
+            }
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("time")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let timezone = match args.get("timezone").and_then(|v| v.as_str()).filter(|s| !s.is_empty()) {
+            Some(name) => name.parse()?,
+            None => self.timezone,
+        };
+        let now = timezone.now();
+        let time = CurrentTime {
+            datetime: now.to_rfc3339(),
+            date: now.format("%Y-%m-%d").to_string(),
+            time: now.format("%H:%M:%S").to_string(),
+            weekday: now.format("%A").to_string(),
+            timezone: timezone.name(),
+            utc_offset: now.format("%:z").to_string(),
+            unix: now.timestamp(),
+        };
+        serde_json::to_string(&time).map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+    }
+}
@@ -0,0 +1,327 @@
+//! Opt-in tool that runs allow-listed commands, e.g. tests or linters in an agentic loop.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// How long a command may run when the tool doesn't set a timeout.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+/// Bytes kept from each of stdout and stderr when the tool doesn't set a limit.
+pub const DEFAULT_COMMAND_OUTPUT_BYTES: usize = 64 * 1024;
+/// Environment variables every command receives, when set on the server.
+const DEFAULT_ENV: [&str; 5] = ["PATH", "HOME", "LANG", "LC_ALL", "TMPDIR"];
+
+/// The `run_command` tool.
+///
+/// Commands run directly, without a shell, so pipes, redirects, and `$VAR`s are
+/// passed to the program as literal arguments. A command is allowed when its
+/// words start with an allow-listed entry: `cargo test` allows
+/// `cargo test --lib` but not `cargo publish`. Commands run in the tool's root
+/// directory or a directory under it, with a cleared environment plus `PATH`,
+/// `HOME`, locale, `TMPDIR`, and any variables passed with
+/// [`pass_env`](Self::pass_env).
+///
+/// The working directory is confined, not the command: an allowed program can
+/// still read paths outside the root that it is given, so keep the allow-list
+/// narrow.
+pub struct CommandTool {
+    root: PathBuf,
+    allowed: Vec<Vec<String>>,
+    env: Vec<String>,
+    timeout: Duration,
+    max_output_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandOutput {
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    truncated: bool,
+}
+
+impl CommandTool {
+    /// Creates a tool that runs commands under `root`. Nothing is allowed until
+    /// commands are added with [`allow`](Self::allow).
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            allowed: Vec::new(),
+            env: DEFAULT_ENV.iter().map(|s| s.to_string()).collect(),
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+            max_output_bytes: DEFAULT_COMMAND_OUTPUT_BYTES,
+        }
+    }
+
+    /// Allows a program, or a program with leading arguments such as `cargo test`.
+    pub fn allow(mut self, command: &str) -> Self {
+        if let Ok(words) = split_words(command) {
+            if !words.is_empty() {
+                self.allowed.push(words);
+            }
+        }
+        self
+    }
+
+    /// Passes an environment variable through to commands.
+    pub fn pass_env(mut self, name: impl Into<String>) -> Self {
+        self.env.push(name.into());
+        self
+    }
+
+    /// Kills commands that run longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keeps at most `bytes` of each of stdout and stderr.
+    pub fn with_max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
+    fn is_allowed(&self, words: &[String]) -> bool {
+        self.allowed.iter().any(|prefix| words.starts_with(prefix))
+    }
+
+    /// Resolves `cwd` under the root, refusing anything that leaves it.
+    fn working_dir(&self, cwd: Option<&str>) -> Result<PathBuf, ToolError> {
+        let root = self.root.canonicalize().map_err(|e| {
+            ToolError::ExecutionFailed(format!("command root {} is unavailable: {}", self.root.display(), e))
+        })?;
+        let Some(cwd) = cwd.filter(|c| !c.is_empty()) else {
+            return Ok(root);
+        };
+        let dir = root
+            .join(Path::new(cwd))
+            .canonicalize()
+            .map_err(|e| ToolError::InvalidArguments(format!("invalid cwd '{}': {}", cwd, e)))?;
+        if !dir.starts_with(&root) {
+            return Err(ToolError::InvalidArguments(format!("cwd '{}' is outside the command root", cwd)));
+        }
+        Ok(dir)
+    }
+}
+
+#[async_trait]
+impl Tool for CommandTool {
+    fn name(&self) -> &str {
+        "run_command"
+    }
+
+    fn description(&self) -> &str {
+        "Run an allow-listed command (no shell) and return its exit code, stdout, and stderr. \
+        Use it to run tests, linters, or builds."
+    }
+
+    fn parameters(&self) -> Value {
+        let allowed: Vec<String> = self.allowed.iter().map(|words| words.join(" ")).collect();
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": format!("Command line to run; must start with one of: {}", allowed.join(", "))
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run in, relative to the project root (default: the root)"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("system").with_tag("dangerous")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let command = args
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'command' parameter".to_string()))?;
+        let words = split_words(command)?;
+        if words.is_empty() {
+            return Err(ToolError::InvalidArguments("command is empty".into()));
+        }
+        if !self.is_allowed(&words) {
+            return Err(ToolError::InvalidArguments(format!("command '{}' is not allowed", command)));
+        }
+        let dir = self.working_dir(args.get("cwd").and_then(|v| v.as_str()))?;
+
+        let mut process = Command::new(&words[0]);
+        process
+            .args(&words[1..])
+            .current_dir(&dir)
+            .env_clear()
+            .envs(self.env.iter().filter_map(|name| std::env::var(name).ok().map(|value| (name, value))))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = process
+            .spawn()
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to start '{}': {}", words[0], e)))?;
+
+        let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+            return Err(ToolError::ExecutionFailed("command output was not captured".into()));
+        };
+        let limit = self.max_output_bytes;
+        let run = async {
+            tokio::join!(read_capped(stdout, limit), read_capped(stderr, limit), child.wait())
+        };
+        let (stdout, stderr, status) = match tokio::time::timeout(self.timeout, run).await {
+            Ok(finished) => finished,
+            Err(_) => {
+                let _ = child.kill().await;
+                return Err(ToolError::Timeout(self.timeout.as_millis() as u64));
+            }
+        };
+        let failed = |e: std::io::Error| ToolError::ExecutionFailed(format!("'{}' failed: {}", command, e));
+        let (stdout, stdout_cut) = stdout.map_err(failed)?;
+        let (stderr, stderr_cut) = stderr.map_err(failed)?;
+        let status = status.map_err(failed)?;
+
+        let output = CommandOutput {
+            exit_code: status.code(),
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            truncated: stdout_cut || stderr_cut,
+        };
+        serde_json::to_string_pretty(&output)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+/// Reads a stream to the end, keeping at most `limit` bytes. The rest is
+/// drained so the command never blocks on a full pipe.
+pub(crate) async fn read_capped<R: AsyncRead + Unpin>(mut reader: R, limit: usize) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            return Ok((kept, truncated));
+        }
+        let room = limit.saturating_sub(kept.len());
+        truncated |= n > room;
+        kept.extend_from_slice(&buf[..n.min(room)]);
+    }
+}
+
+/// Splits a command line into words, honouring single and double quotes and
+/// backslash escapes. Nothing else is interpreted.
+fn split_words(line: &str) -> Result<Vec<String>, ToolError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                let escaped = chars
+                    .next()
+                    .ok_or_else(|| ToolError::InvalidArguments("command ends with a backslash".into()))?;
+                word.push(escaped);
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(ToolError::InvalidArguments("command has an unclosed quote".into()));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool() -> CommandTool {
+        CommandTool::new(std::env::temp_dir()).allow("echo").allow("cargo test")
+    }
+
+    fn output(result: &str) -> Value {
+        serde_json::from_str(result).unwrap()
+    }
+
+    #[test]
+    fn test_allow_list_matches_leading_words() {
+        let tool = tool();
+        let words = |line: &str| split_words(line).unwrap();
+        assert!(tool.is_allowed(&words("cargo test --lib")));
+        assert!(!tool.is_allowed(&words("cargo publish")));
+        assert!(!tool.is_allowed(&words("cargo")));
+        assert!(!tool.is_allowed(&words("echoes hi")));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_command_not_on_allow_list() {
+        let result = tool().execute(json!({ "command": "rm -rf /tmp/anything" })).await;
+        assert!(matches!(result, Err(ToolError::InvalidArguments(message)) if message.contains("not allowed")));
+        assert!(matches!(tool().execute(json!({ "command": "  " })).await, Err(ToolError::InvalidArguments(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_runs_without_a_shell() {
+        let result = tool().execute(json!({ "command": "echo 'a b' ; rm -rf $HOME" })).await.unwrap();
+        let output = output(&result);
+        assert_eq!(output["exit_code"], 0);
+        assert_eq!(output["stdout"], "a b ; rm -rf $HOME\n");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_cwd_outside_root() {
+        let result = tool().execute(json!({ "command": "echo hi", "cwd": ".." })).await;
+        assert!(matches!(result, Err(ToolError::InvalidArguments(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_and_output_limit() {
+        let tool = CommandTool::new(std::env::temp_dir()).allow("sleep").with_timeout(Duration::from_millis(100));
+        assert!(matches!(tool.execute(json!({ "command": "sleep 5" })).await, Err(ToolError::Timeout(100))));
+
+        let tool = CommandTool::new(std::env::temp_dir()).allow("echo").with_max_output_bytes(3);
+        let output = output(&tool.execute(json!({ "command": "echo hello" })).await.unwrap());
+        assert_eq!((output["stdout"].as_str(), output["truncated"].as_bool()), (Some("hel"), Some(true)));
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words(r#"git commit -m "a \"b\" c" 'd e'"#).unwrap(), ["git", "commit", "-m", "a \"b\" c", "d e"]);
+        assert!(split_words("echo 'open").is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! The `send_email` tool: sends email through SendGrid or an SMTP server.
+//!
+//! In dry-run mode the tool validates the message and reports what it would
+//! have sent without sending it, so notification pipelines can be tried out
+//! safely. Either way the tool asks for approval before each call.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::{CostHint, HostAllowlist, Tool, ToolError, ToolMetadata};
+
+/// SendGrid's v3 API.
+pub const SENDGRID_API_BASE: &str = "https://api.sendgrid.com/v3";
+
+/// SMTP submission port used when none is configured.
+pub const DEFAULT_SMTP_PORT: u16 = 587;
+
+/// Most recipients (`to` and `cc` together) one call may address.
+const MAX_RECIPIENTS: usize = 50;
+
+/// Where email is sent.
+#[derive(Clone)]
+pub enum EmailBackend {
+    /// SendGrid's mail send API, or a compatible endpoint at `api_base`.
+    SendGrid { api_base: String, api_key: String },
+    /// An SMTP server, using STARTTLS (or TLS on port 465) unless `tls` is off.
+    #[cfg(feature = "smtp")]
+    Smtp { host: String, port: u16, username: Option<String>, password: Option<String>, tls: bool },
+}
+
+/// A message the LLM asked to send.
+#[derive(Debug, Serialize)]
+struct Email {
+    to: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cc: Vec<String>,
+    subject: String,
+    #[serde(skip)]
+    body: String,
+    #[serde(skip)]
+    html: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SendReport<'a> {
+    sent: bool,
+    dry_run: bool,
+    #[serde(flatten)]
+    email: &'a Email,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<String>,
+}
+
+/// The `send_email` tool.
+///
+/// ```rust,ignore
+/// use fissio_tools::{EmailBackend, SendEmailTool, SENDGRID_API_BASE};
+///
+/// let tool = SendEmailTool::new(
+///     EmailBackend::SendGrid { api_base: SENDGRID_API_BASE.into(), api_key },
+///     "alerts@example.com",
+/// );
+/// let preview = SendEmailTool::dry_run("alerts@example.com");
+/// ```
+pub struct SendEmailTool {
+    /// `None` in a dry run without a backend.
+    backend: Option<EmailBackend>,
+    from: String,
+    dry_run: bool,
+    client: reqwest::Client,
+}
+
+impl SendEmailTool {
+    /// Sends from `from` through `backend`.
+    pub fn new(backend: EmailBackend, from: impl Into<String>) -> Self {
+        Self { backend: Some(backend), from: from.into(), dry_run: false, client: reqwest::Client::new() }
+    }
+
+    /// Reports what would be sent from `from` without sending anything.
+    pub fn dry_run(from: impl Into<String>) -> Self {
+        Self { backend: None, from: from.into(), dry_run: true, client: reqwest::Client::new() }
+    }
+
+    /// Reports messages instead of sending them, while keeping the backend.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run || self.backend.is_none();
+        self
+    }
+
+    /// Sends from `EMAIL_FROM` through SendGrid if `SENDGRID_API_KEY` is set,
+    /// else through `SMTP_HOST` (`smtp` feature) with `SMTP_PORT`,
+    /// `SMTP_USERNAME`, `SMTP_PASSWORD`, and `SMTP_TLS`. `EMAIL_DRY_RUN=true`
+    /// only reports messages, and needs no backend. Returns `None` when
+    /// `EMAIL_FROM` or a backend is missing.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let from = var("EMAIL_FROM")?;
+        let dry_run = var("EMAIL_DRY_RUN").is_some_and(|v| v == "true" || v == "1");
+
+        let backend = match var("SENDGRID_API_KEY") {
+            Some(api_key) => Some(EmailBackend::SendGrid { api_base: SENDGRID_API_BASE.to_string(), api_key }),
+            None => smtp_from_env(),
+        };
+        match backend {
+            Some(backend) => Some(Self::new(backend, from).with_dry_run(dry_run)),
+            None if dry_run => Some(Self::dry_run(from)),
+            None => None,
+        }
+    }
+
+    pub fn backend(&self) -> Option<&EmailBackend> {
+        self.backend.as_ref()
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Returns an error unless the backend's host is allowed. A dry run reaches no host.
+    pub fn check_hosts(&self, hosts: &HostAllowlist) -> Result<(), ToolError> {
+        match &self.backend {
+            None => Ok(()),
+            Some(_) if self.dry_run => Ok(()),
+            Some(EmailBackend::SendGrid { api_base, .. }) => hosts.check(api_base),
+            #[cfg(feature = "smtp")]
+            Some(EmailBackend::Smtp { host, .. }) => hosts.check(&format!("smtp://{}", host)),
+        }
+    }
+
+    /// Sends through the backend, returning the provider's message ID if it gives one.
+    async fn send(&self, email: &Email) -> Result<Option<String>, ToolError> {
+        match &self.backend {
+            None => Err(ToolError::ExecutionFailed("no email backend is configured".into())),
+            Some(EmailBackend::SendGrid { api_base, api_key }) => self.send_sendgrid(api_base, api_key, email).await,
+            #[cfg(feature = "smtp")]
+            Some(EmailBackend::Smtp { host, port, username, password, tls }) => {
+                let credentials = username.clone().zip(password.clone());
+                smtp::send(host, *port, credentials, *tls, &self.from, email).await.map(|_| None)
+            }
+        }
+    }
+
+    async fn send_sendgrid(&self, api_base: &str, api_key: &str, email: &Email) -> Result<Option<String>, ToolError> {
+        let addresses = |list: &[String]| list.iter().map(|a| json!({ "email": a })).collect::<Vec<_>>();
+        let mut personalization = json!({ "to": addresses(&email.to) });
+        if !email.cc.is_empty() {
+            personalization["cc"] = json!(addresses(&email.cc));
+        }
+        let content_type = if email.html { "text/html" } else { "text/plain" };
+        let body = json!({
+            "personalizations": [personalization],
+            "from": { "email": self.from },
+            "subject": email.subject,
+            "content": [{ "type": content_type, "value": email.body }],
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/mail/send", api_base.trim_end_matches('/')))
+            .bearer_auth(api_key)
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(ToolError::ExecutionFailed(format!("SendGrid returned {}: {}", status, detail)));
+        }
+        let message_id = response.headers().get("x-message-id").and_then(|v| v.to_str().ok()).map(String::from);
+        Ok(message_id)
+    }
+}
+
+#[cfg(feature = "smtp")]
+fn smtp_from_env() -> Option<EmailBackend> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    Some(EmailBackend::Smtp {
+        host: var("SMTP_HOST")?,
+        port: var("SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or(DEFAULT_SMTP_PORT),
+        username: var("SMTP_USERNAME"),
+        password: var("SMTP_PASSWORD"),
+        tls: !matches!(var("SMTP_TLS").as_deref(), Some("false" | "0")),
+    })
+}
+
+#[cfg(not(feature = "smtp"))]
+fn smtp_from_env() -> Option<EmailBackend> {
+    if std::env::var("SMTP_HOST").is_ok_and(|h| !h.is_empty()) {
+        tracing::warn!("SMTP_HOST is set but fissio-tools was built without the `smtp` feature; send_email needs SENDGRID_API_KEY");
+    }
+    None
+}
+
+#[cfg(feature = "smtp")]
+mod smtp {
+    use lettre::message::header::ContentType;
+    use lettre::message::Mailbox;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    use super::Email;
+    use crate::ToolError;
+
+    /// Port on which SMTP servers expect TLS from the start rather than STARTTLS.
+    const IMPLICIT_TLS_PORT: u16 = 465;
+
+    pub async fn send(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        tls: bool,
+        from: &str,
+        email: &Email,
+    ) -> Result<(), ToolError> {
+        let mailbox = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| ToolError::InvalidArguments(format!("invalid address '{}': {}", address, e)))
+        };
+        let mut builder = Message::builder().from(mailbox(from)?).subject(email.subject.as_str());
+        for address in &email.to {
+            builder = builder.to(mailbox(address)?);
+        }
+        for address in &email.cc {
+            builder = builder.cc(mailbox(address)?);
+        }
+        let content_type = if email.html { ContentType::TEXT_HTML } else { ContentType::TEXT_PLAIN };
+        let message = builder
+            .header(content_type)
+            .body(email.body.clone())
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+
+        let failed = |e: lettre::transport::smtp::Error| ToolError::ExecutionFailed(format!("SMTP {}: {}", host, e));
+        let mut transport = match (tls, port) {
+            (false, _) => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            (true, IMPLICIT_TLS_PORT) => AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(failed)?,
+            (true, _) => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(failed)?,
+        }
+        .port(port);
+        if let Some((username, password)) = credentials {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+        transport.build().send(message).await.map_err(failed)?;
+        Ok(())
+    }
+}
+
+/// Reads a list of addresses given as an array or a comma-separated string.
+fn addresses(args: &Value, field: &str) -> Result<Vec<String>, ToolError> {
+    let list: Vec<String> = match args.get(field) {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(s)) => s.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+        Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).map(|a| a.trim().to_string()).collect(),
+        Some(_) => return Err(ToolError::InvalidArguments(format!("'{}' must be a list of addresses", field))),
+    };
+    // A newline would let an address inject extra headers
+    match list.iter().find(|a| !a.contains('@') || a.contains(['\r', '\n'])) {
+        Some(bad) => Err(ToolError::InvalidArguments(format!("invalid address in '{}': {}", field, bad))),
+        None => Ok(list),
+    }
+}
+
+fn parse_email(args: &Value) -> Result<Email, ToolError> {
+    let text = |name: &str| args.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    let to = addresses(args, "to")?;
+    if to.is_empty() {
+        return Err(ToolError::InvalidArguments("Missing 'to' parameter".into()));
+    }
+    let cc = addresses(args, "cc")?;
+    if to.len() + cc.len() > MAX_RECIPIENTS {
+        return Err(ToolError::InvalidArguments(format!("at most {} recipients are allowed", MAX_RECIPIENTS)));
+    }
+    let subject = text("subject").filter(|s| !s.trim().is_empty());
+    let subject = subject.ok_or_else(|| ToolError::InvalidArguments("Missing 'subject' parameter".into()))?;
+    if subject.contains(['\r', '\n']) {
+        return Err(ToolError::InvalidArguments("'subject' must be a single line".into()));
+    }
+    let body = text("body").ok_or_else(|| ToolError::InvalidArguments("Missing 'body' parameter".into()))?;
+    let html = args.get("html").and_then(|v| v.as_bool()).unwrap_or(false);
+    Ok(Email { to, cc, subject, body, html })
+}
+
+#[async_trait]
+impl Tool for SendEmailTool {
+    fn name(&self) -> &str {
+        "send_email"
+    }
+
+    fn description(&self) -> &str {
+        "Send an email. Use only when the user asked for a message to be sent."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "to": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Recipient email addresses"
+                },
+                "cc": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Addresses to copy"
+                },
+                "subject": {
+                    "type": "string",
+                    "description": "Subject line"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Message text"
+                },
+                "html": {
+                    "type": "boolean",
+                    "description": "Whether body is HTML rather than plain text (default false)"
+                }
+            },
+            "required": ["to", "subject", "body"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new()
+            .with_namespace("email")
+            .with_tag("network")
+            .with_cost(CostHint::Low)
+            .with_requires_approval(true)
+            .with_side_effects(true)
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let email = parse_email(&args)?;
+        let message_id = match self.dry_run {
+            true => {
+                info!("Dry run: not sending '{}' to {}", email.subject, email.to.join(", "));
+                None
+            }
+            false => {
+                let id = self.send(&email).await?;
+                info!("Sent '{}' to {} recipients", email.subject, email.to.len() + email.cc.len());
+                id
+            }
+        };
+        let report = SendReport { sent: !self.dry_run, dry_run: self.dry_run, email: &email, message_id };
+        serde_json::to_string(&report).map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+    }
+}
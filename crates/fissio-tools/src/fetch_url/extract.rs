@@ -0,0 +1,246 @@
+//! Metadata and main-content extraction from HTML pages.
+//!
+//! The main content is found the way readability tools do it: paragraphs
+//! score their parent and grandparent by length, the best-scoring container
+//! (discounted by how much of its text is links) is taken as the article,
+//! and navigation, headers, footers, and sidebars are skipped.
+
+use std::collections::{HashMap, HashSet};
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Content shorter than this is treated as a failed extraction, and the whole
+/// page is converted to text instead.
+const MIN_CONTENT_CHARS: usize = 200;
+/// Most links returned per page.
+const MAX_LINKS: usize = 25;
+
+/// Elements that never hold the main content.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside", "form", "button",
+];
+/// `class` and `id` fragments marking page furniture rather than content.
+const UNLIKELY_MARKERS: &[&str] = &[
+    "comment", "sidebar", "footer", "menu", "navbar", "share", "social", "related", "promo", "cookie", "banner", "advert",
+];
+/// Elements whose text becomes a block of the extracted content.
+const BLOCK_TAGS: &[&str] = &[
+    "p", "h1", "h2", "h3", "h4", "h5", "h6", "li", "pre", "blockquote", "td", "th", "dt", "dd", "figcaption",
+];
+
+/// A link found in the page's content.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+/// What was read from an HTML page.
+#[derive(Debug, Default)]
+pub(crate) struct Extracted {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub published: Option<String>,
+    pub author: Option<String>,
+    pub content: String,
+    pub links: Vec<Link>,
+}
+
+/// Extracts metadata, main content, and links from `html`, resolving URLs against `base`.
+pub(crate) fn extract(html: &str, base: &Url) -> Extracted {
+    let document = Html::parse_document(html);
+    let article = main_content(&document);
+
+    let content = article.map(render_text).filter(|text| text.chars().count() >= MIN_CONTENT_CHARS);
+    let links = match article {
+        Some(article) => links(article, base),
+        None => links(document.root_element(), base),
+    };
+
+    Extracted {
+        title: meta(&document, &[("property", "og:title")]).or_else(|| first_text(&document, "title")),
+        description: meta(&document, &[("name", "description"), ("property", "og:description")]),
+        canonical_url: first_attr(&document, "link[rel=\"canonical\"]", "href")
+            .and_then(|href| base.join(&href).ok())
+            .map(String::from),
+        published: published(&document),
+        author: meta(&document, &[("name", "author"), ("property", "article:author")]),
+        content: content.unwrap_or_else(|| html2text::from_read(html.as_bytes(), 80)),
+        links,
+    }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("valid built-in selector")
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn meta(document: &Html, keys: &[(&str, &str)]) -> Option<String> {
+    keys.iter().find_map(|(attr, value)| first_attr(document, &format!("meta[{}=\"{}\"]", attr, value), "content"))
+}
+
+fn first_attr(document: &Html, css: &str, attr: &str) -> Option<String> {
+    document
+        .select(&selector(css))
+        .filter_map(|el| el.value().attr(attr))
+        .map(str::trim)
+        .find(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn first_text(document: &Html, css: &str) -> Option<String> {
+    document.select(&selector(css)).map(|el| normalize(&el.text().collect::<String>())).find(|t| !t.is_empty())
+}
+
+/// The publication date from meta tags, JSON-LD, or the first `<time datetime>`.
+fn published(document: &Html) -> Option<String> {
+    meta(
+        document,
+        &[
+            ("property", "article:published_time"),
+            ("name", "date"),
+            ("name", "pubdate"),
+            ("name", "publish-date"),
+            ("name", "dc.date"),
+            ("itemprop", "datePublished"),
+        ],
+    )
+    .or_else(|| {
+        document
+            .select(&selector("script[type=\"application/ld+json\"]"))
+            .filter_map(|el| serde_json::from_str::<Value>(&el.text().collect::<String>()).ok())
+            .find_map(|json| find_key(&json, "datePublished"))
+    })
+    .or_else(|| first_attr(document, "time[datetime]", "datetime"))
+}
+
+fn find_key(value: &Value, key: &str) -> Option<String> {
+    match value {
+        Value::Object(map) => map
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        Value::Array(items) => items.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}
+
+/// Whether the element is, or sits inside, page furniture.
+fn is_skipped(el: ElementRef) -> bool {
+    std::iter::once(el).chain(el.ancestors().filter_map(ElementRef::wrap)).any(|el| {
+        let element = el.value();
+        if SKIPPED_TAGS.contains(&element.name()) {
+            return true;
+        }
+        // Sites put layout classes on <body> and <html>; only judge containers
+        if matches!(element.name(), "html" | "body" | "article" | "main") {
+            return false;
+        }
+        let marks = format!("{} {}", element.attr("class").unwrap_or_default(), element.id().unwrap_or_default())
+            .to_ascii_lowercase();
+        UNLIKELY_MARKERS.iter().any(|m| marks.contains(m))
+    })
+}
+
+/// Picks the container most likely to hold the article.
+fn main_content(document: &Html) -> Option<ElementRef<'_>> {
+    let mut scores: HashMap<_, f64> = HashMap::new();
+    for paragraph in document.select(&selector("p, pre, td, blockquote")) {
+        let text = normalize(&paragraph.text().collect::<String>());
+        if text.len() < 25 || is_skipped(paragraph) {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_default() += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_default() += score / 2.0;
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = document.tree.get(id).and_then(ElementRef::wrap)?;
+            Some((el, score * (1.0 - link_density(el))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+}
+
+/// Share of the element's text inside links.
+fn link_density(el: ElementRef) -> f64 {
+    let total = el.text().map(str::len).sum::<usize>();
+    if total == 0 {
+        return 1.0;
+    }
+    let linked: usize = el.select(&selector("a")).flat_map(|a| a.text()).map(str::len).sum();
+    linked as f64 / total as f64
+}
+
+/// Renders the container's block elements as paragraphs, headings as `#` lines
+/// and list items as `-` lines.
+fn render_text(article: ElementRef) -> String {
+    let blocks = selector(&BLOCK_TAGS.join(", "));
+    let mut out = Vec::new();
+    for el in article.select(&blocks) {
+        // Nested blocks (a <p> in an <li>) are rendered with their outer block
+        let nested = el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .take_while(|a| a.id() != article.id())
+            .any(|a| BLOCK_TAGS.contains(&a.value().name()));
+        if nested || is_skipped(el) {
+            continue;
+        }
+        let name = el.value().name();
+        let raw: String = el.text().collect();
+        let text = if name == "pre" { raw.trim_matches('\n').to_string() } else { normalize(&raw) };
+        if text.is_empty() {
+            continue;
+        }
+        out.push(match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                format!("{} {}", "#".repeat(level), text)
+            }
+            "li" => format!("- {}", text),
+            _ => text,
+        });
+    }
+    out.join("\n\n")
+}
+
+/// Links in `scope` to other http(s) pages, deduplicated, resolved against `base`.
+fn links(scope: ElementRef, base: &Url) -> Vec<Link> {
+    let mut seen = HashSet::new();
+    scope
+        .select(&selector("a[href]"))
+        .filter(|a| !is_skipped(*a))
+        .filter_map(|a| {
+            let href = a.value().attr("href")?.trim();
+            if href.starts_with('#') {
+                return None;
+            }
+            let mut url = base.join(href).ok()?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return None;
+            }
+            url.set_fragment(None);
+            let text = normalize(&a.text().collect::<String>());
+            Some(Link { text, url: url.into() })
+        })
+        .filter(|link| seen.insert(link.url.clone()))
+        .take(MAX_LINKS)
+        .collect()
+}
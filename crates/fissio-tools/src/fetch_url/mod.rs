@@ -0,0 +1,276 @@
+//! The `fetch_url` tool: fetches a page and returns its main content and metadata.
+
+mod extract;
+mod robots;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use reqwest::header::LOCATION;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::{extract_html, HostAllowlist, Tool, ToolError, ToolMetadata};
+use extract::Link;
+use robots::Robots;
+
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; AgentBot/1.0)";
+/// The product token matched against robots.txt `User-agent` lines.
+const ROBOTS_AGENT: &str = "AgentBot";
+const MAX_REDIRECTS: usize = 10;
+const DEFAULT_MAX_LENGTH: usize = 8000;
+
+/// Fetch URL tool - retrieves and extracts structured content from web pages
+///
+/// Follows up to 10 redirects, checking each hop against the allowed hosts
+/// and, when enabled, the site's robots.txt. HTML pages are reduced to their
+/// main content, with the title, description, canonical URL, publication
+/// date, author, and content links returned alongside.
+pub struct FetchUrlTool {
+    client: reqwest::Client,
+    respect_robots: bool,
+    allowed_hosts: Option<HostAllowlist>,
+    /// Parsed robots.txt per origin.
+    robots: Mutex<HashMap<String, Arc<Robots>>>,
+}
+
+impl FetchUrlTool {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent(USER_AGENT)
+                .timeout(std::time::Duration::from_secs(30))
+                // Redirects are followed by hand so each hop is checked
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default(),
+            respect_robots: false,
+            allowed_hosts: None,
+            robots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates the tool, honoring robots.txt if `FETCH_URL_RESPECT_ROBOTS` is `true`.
+    pub fn from_env() -> Self {
+        let respect = std::env::var("FETCH_URL_RESPECT_ROBOTS").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+        Self::new().with_respect_robots(respect)
+    }
+
+    /// Refuses pages the site's robots.txt disallows for this tool.
+    pub fn with_respect_robots(mut self, respect: bool) -> Self {
+        self.respect_robots = respect;
+        self
+    }
+
+    /// Refuses requests, including redirects, to hosts not in `hosts`.
+    pub fn with_allowed_hosts(mut self, hosts: HostAllowlist) -> Self {
+        self.allowed_hosts = Some(hosts);
+        self
+    }
+
+    /// Requests `url`, following redirects. Returns the final URL and response.
+    async fn get(&self, url: &str) -> Result<(Url, reqwest::Response), ToolError> {
+        let mut url = Url::parse(url).map_err(|e| ToolError::InvalidArguments(format!("invalid URL '{}': {}", url, e)))?;
+        for _ in 0..=MAX_REDIRECTS {
+            if let Some(hosts) = &self.allowed_hosts {
+                hosts.check(url.as_str())?;
+            }
+            if self.respect_robots && !self.robots_for(&url).await.allows(&path_of(&url)) {
+                return Err(ToolError::ExecutionFailed(format!("robots.txt disallows fetching {}", url)));
+            }
+
+            let response = self.client.get(url.clone()).send().await?;
+            if !response.status().is_redirection() {
+                return Ok((url, response));
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| ToolError::ExecutionFailed(format!("HTTP {} without a Location header", response.status())))?;
+            let next = url
+                .join(location)
+                .map_err(|e| ToolError::ExecutionFailed(format!("invalid redirect to '{}': {}", location, e)))?;
+            debug!("fetch_url: {} redirected to {}", url, next);
+            url = next;
+        }
+        Err(ToolError::ExecutionFailed(format!("more than {} redirects", MAX_REDIRECTS)))
+    }
+
+    /// Returns the robots.txt rules for the URL's origin, fetching them on first use.
+    ///
+    /// A missing or unreadable robots.txt allows everything.
+    async fn robots_for(&self, url: &Url) -> Arc<Robots> {
+        let origin = url.origin().ascii_serialization();
+        let cached = self.robots.lock().ok().and_then(|cache| cache.get(&origin).cloned());
+        if let Some(robots) = cached {
+            return robots;
+        }
+
+        let robots_url = format!("{}/robots.txt", origin);
+        let robots = match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(text) => Robots::parse(&text, ROBOTS_AGENT),
+                Err(e) => {
+                    warn!("Failed to read {}: {}", robots_url, e);
+                    Robots::default()
+                }
+            },
+            Ok(_) => Robots::default(),
+            Err(e) => {
+                warn!("Failed to fetch {}: {}", robots_url, e);
+                Robots::default()
+            }
+        };
+        let robots = Arc::new(robots);
+        if let Ok(mut cache) = self.robots.lock() {
+            cache.insert(origin, Arc::clone(&robots));
+        }
+        robots
+    }
+}
+
+impl Default for FetchUrlTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The path and query robots.txt rules are matched against.
+fn path_of(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PageContent {
+    url: String,
+    /// Where the request ended up, when redirected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    final_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canonical_url: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    content: String,
+    content_type: String,
+    truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<Link>,
+}
+
+#[async_trait]
+impl Tool for FetchUrlTool {
+    fn name(&self) -> &str {
+        "fetch_url"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch and parse content from a URL. Returns the page's main text content with its title, description, \
+         canonical URL, publication date, author, and links."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The URL to fetch content from"
+                },
+                "max_length": {
+                    "type": "integer",
+                    "description": "Maximum characters for content (default: 8000)",
+                    "default": DEFAULT_MAX_LENGTH
+                },
+                "selector": {
+                    "type": "string",
+                    "description": "CSS selector for the part of an HTML page to return instead of the detected main content"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("web").with_tag("network")
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<String, ToolError> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'url' parameter".to_string()))?;
+
+        let max_length = args
+            .get("max_length")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(DEFAULT_MAX_LENGTH);
+
+        let selector = args.get("selector").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty());
+
+        let (final_url, response) = self.get(url).await?;
+
+        if !response.status().is_success() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
+        }
+
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/plain")
+            .to_string();
+
+        let is_html = content_type.contains("text/html") || content_type.contains("application/xhtml");
+        let body = response.text().await?;
+
+        let mut page = extract::Extracted::default();
+        if is_html {
+            page = extract::extract(&body, &final_url);
+            if let Some(selector) = selector {
+                page.content = extract_html(&body, selector)?;
+            }
+        } else {
+            page.content = body;
+        }
+
+        // Truncate if needed
+        let truncated = page.content.chars().count() > max_length;
+        if truncated {
+            page.content = page.content.chars().take(max_length).collect();
+        }
+
+        let redirected = Url::parse(url).is_ok_and(|requested| requested != final_url);
+        let page = PageContent {
+            url: url.to_string(),
+            final_url: redirected.then(|| final_url.to_string()),
+            canonical_url: page.canonical_url,
+            title: page.title,
+            description: page.description,
+            published: page.published,
+            author: page.author,
+            content: page.content,
+            content_type,
+            truncated,
+            links: page.links,
+        };
+
+        serde_json::to_string_pretty(&page).map_err(|e| {
+            ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e))
+        })
+    }
+}
@@ -0,0 +1,89 @@
+//! robots.txt rules for the user agent `fetch_url` identifies as.
+
+/// The rules of a robots.txt that apply to one user agent.
+///
+/// The longest matching pattern decides; an `Allow` wins a tie. `*` matches
+/// any characters and a trailing `$` anchors the pattern to the end of the path.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Robots {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Robots {
+    /// Reads the groups for `agent`, or the `*` groups if none names it.
+    pub(crate) fn parse(text: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut named = Vec::new();
+        let mut wildcard = Vec::new();
+        // User agents of the group being read, and whether its rules have started
+        let mut group: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match field.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        group.clear();
+                        in_rules = false;
+                    }
+                    group.push(value.to_ascii_lowercase());
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty Disallow allows everything, which is the default
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = Rule { allow: field == "allow", pattern: value.to_string() };
+                    if group.iter().any(|ua| ua != "*" && agent.contains(ua.as_str())) {
+                        named.push(rule);
+                    } else if group.iter().any(|ua| ua == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { rules: if named.is_empty() { wildcard } else { named } }
+    }
+
+    /// Whether the rules allow fetching `path` (path and query, starting with `/`).
+    pub(crate) fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(mut rest) = path.strip_prefix(parts[0]) else {
+        return false;
+    };
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
@@ -0,0 +1,349 @@
+//! Tools that read, write, and list files under a sandbox root directory.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// Characters `read_file` returns when the call doesn't set `max_length`.
+const DEFAULT_READ_CHARS: usize = 20_000;
+
+/// A directory the filesystem tools are confined to.
+///
+/// Paths given to the tools are relative to the root. Absolute paths and `..`
+/// are refused, and so are symlinks that lead out of the root.
+#[derive(Debug, Clone)]
+pub struct FsRoot {
+    root: PathBuf,
+}
+
+impl FsRoot {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolves a path relative to the root, refusing anything that escapes it.
+    /// The path itself doesn't have to exist.
+    pub(crate) fn resolve(&self, relative: &str) -> Result<PathBuf, ToolError> {
+        let relative = Path::new(relative);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(ToolError::InvalidArguments(format!(
+                "path '{}' must be relative to the root and must not contain '..'",
+                relative.display()
+            )));
+        }
+        let root = self.root.canonicalize().map_err(|e| {
+            ToolError::ExecutionFailed(format!("filesystem root {} is unavailable: {}", self.root.display(), e))
+        })?;
+        let path = root.join(relative);
+
+        // A symlink anywhere along the existing part of the path could point out of the root
+        let existing = path.ancestors().find(|p| p.symlink_metadata().is_ok()).unwrap_or(&root);
+        let real = existing
+            .canonicalize()
+            .map_err(|e| ToolError::ExecutionFailed(format!("{}: {}", relative.display(), e)))?;
+        if !real.starts_with(&root) {
+            return Err(ToolError::InvalidArguments(format!("path '{}' leads outside the root", relative.display())));
+        }
+        Ok(path)
+    }
+}
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str, ToolError> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvalidArguments(format!("Missing '{}' parameter", name)))
+}
+
+fn io_error(path: &str, e: std::io::Error) -> ToolError {
+    ToolError::ExecutionFailed(format!("{}: {}", path, e))
+}
+
+/// The `read_file` tool: reads a text file under the root.
+pub struct FileReadTool {
+    root: FsRoot,
+}
+
+impl FileReadTool {
+    pub fn new(root: FsRoot) -> Self {
+        Self { root }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileContent {
+    path: String,
+    content: String,
+    truncated: bool,
+}
+
+#[async_trait]
+impl Tool for FileReadTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file. Paths are relative to the working directory."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to read, relative to the working directory"
+                },
+                "max_length": {
+                    "type": "integer",
+                    "description": "Maximum characters to return (default: 20000)",
+                    "default": DEFAULT_READ_CHARS
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("fs")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let relative = str_arg(&args, "path")?;
+        let max_length = args
+            .get("max_length")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(DEFAULT_READ_CHARS);
+
+        let path = self.root.resolve(relative)?;
+        // A character is at most 4 bytes, so that many bytes (and one more, to
+        // tell whether there is more) are all that can be returned
+        let cap = max_length.saturating_mul(4);
+        let file = tokio::fs::File::open(&path).await.map_err(|e| io_error(relative, e))?;
+        let mut bytes = Vec::new();
+        file.take(cap.saturating_add(1) as u64)
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| io_error(relative, e))?;
+        let text = String::from_utf8_lossy(&bytes);
+        let truncated = bytes.len() > cap || text.chars().count() > max_length;
+
+        let file = FileContent {
+            path: relative.to_string(),
+            content: text.chars().take(max_length).collect(),
+            truncated,
+        };
+        serde_json::to_string_pretty(&file)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+/// The `write_file` tool: creates, replaces, or appends to a file under the root.
+pub struct FileWriteTool {
+    root: FsRoot,
+}
+
+impl FileWriteTool {
+    pub fn new(root: FsRoot) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait]
+impl Tool for FileWriteTool {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn description(&self) -> &str {
+        "Write text to a file, creating it and any missing directories. Replaces the file unless append is true."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to write, relative to the working directory"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to write"
+                },
+                "append": {
+                    "type": "boolean",
+                    "description": "Add to the end of the file instead of replacing it",
+                    "default": false
+                }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("fs").with_tag("dangerous")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let relative = str_arg(&args, "path")?;
+        let content = str_arg(&args, "content")?;
+        let append = args.get("append").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let path = self.root.resolve(relative)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| io_error(relative, e))?;
+        }
+        if append {
+            use tokio::io::AsyncWriteExt;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .map_err(|e| io_error(relative, e))?;
+            file.write_all(content.as_bytes()).await.map_err(|e| io_error(relative, e))?;
+        } else {
+            tokio::fs::write(&path, content).await.map_err(|e| io_error(relative, e))?;
+        }
+        Ok(format!("Wrote {} bytes to {}", content.len(), relative))
+    }
+}
+
+/// The `list_dir` tool: lists a directory under the root.
+pub struct ListDirTool {
+    root: FsRoot,
+}
+
+impl ListDirTool {
+    pub fn new(root: FsRoot) -> Self {
+        Self { root }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DirEntry {
+    name: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    size: u64,
+}
+
+#[async_trait]
+impl Tool for ListDirTool {
+    fn name(&self) -> &str {
+        "list_dir"
+    }
+
+    fn description(&self) -> &str {
+        "List the files and directories in a directory, with their sizes in bytes."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory to list, relative to the working directory (default: the working directory)"
+                }
+            }
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("fs")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let relative = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let path = self.root.resolve(relative)?;
+
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(&path).await.map_err(|e| io_error(relative, e))?;
+        while let Some(entry) = dir.next_entry().await.map_err(|e| io_error(relative, e))? {
+            let metadata = entry.metadata().await.map_err(|e| io_error(relative, e))?;
+            let kind = match metadata.file_type() {
+                t if t.is_dir() => "dir",
+                t if t.is_symlink() => "symlink",
+                _ => "file",
+            };
+            entries.push(DirEntry { name: entry.file_name().to_string_lossy().into_owned(), kind, size: metadata.len() });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        serde_json::to_string_pretty(&entries)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh root directory under the system temp dir.
+    fn temp_root(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fissio-fs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve_rejects_parent_and_absolute_paths() {
+        let dir = temp_root("escape");
+        let root = FsRoot::new(&dir);
+        assert!(matches!(root.resolve("../secret"), Err(ToolError::InvalidArguments(_))));
+        assert!(matches!(root.resolve("notes/../../secret"), Err(ToolError::InvalidArguments(_))));
+        assert!(matches!(root.resolve("/etc/passwd"), Err(ToolError::InvalidArguments(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_symlink_out_of_root() {
+        let dir = temp_root("symlink");
+        let outside = temp_root("symlink-target");
+        std::os::unix::fs::symlink(&outside, dir.join("link")).unwrap();
+
+        let root = FsRoot::new(&dir);
+        assert!(matches!(root.resolve("link"), Err(ToolError::InvalidArguments(_))));
+        assert!(matches!(root.resolve("link/new.txt"), Err(ToolError::InvalidArguments(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_accepts_new_nested_path() {
+        let dir = temp_root("nested");
+        let path = FsRoot::new(&dir).resolve("./notes/2024/today.md").unwrap();
+        assert!(path.starts_with(dir.canonicalize().unwrap()));
+        assert!(path.ends_with("notes/2024/today.md"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_file_truncates_to_max_length() {
+        let dir = temp_root("read");
+        std::fs::write(dir.join("long.txt"), "é".repeat(100)).unwrap();
+        std::fs::write(dir.join("short.txt"), "hi").unwrap();
+        let tool = FileReadTool::new(FsRoot::new(&dir));
+
+        let long: Value = serde_json::from_str(&tool.execute(json!({ "path": "long.txt", "max_length": 10 })).await.unwrap()).unwrap();
+        assert_eq!(long["content"], "é".repeat(10));
+        assert_eq!(long["truncated"], true);
+
+        let short: Value = serde_json::from_str(&tool.execute(json!({ "path": "short.txt", "max_length": 2 })).await.unwrap()).unwrap();
+        assert_eq!((short["content"].as_str(), short["truncated"].as_bool()), (Some("hi"), Some(false)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
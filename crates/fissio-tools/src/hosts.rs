@@ -0,0 +1,46 @@
+//! Hosts that network tools may reach.
+
+use crate::ToolError;
+
+/// Hosts a tool may send requests to, for deployments where nothing may leave
+/// the machine or network. Names compare case-insensitively; ports are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostAllowlist {
+    hosts: Vec<String>,
+}
+
+impl HostAllowlist {
+    pub fn new<I, S>(hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { hosts: hosts.into_iter().map(Into::into).collect() }
+    }
+
+    /// Allows only the loopback interface.
+    pub fn loopback() -> Self {
+        Self::new(["localhost", "127.0.0.1", "::1"])
+    }
+
+    /// Adds a host.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.hosts.push(host.into());
+        self
+    }
+
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    /// Returns an error unless the URL's host is allowed.
+    pub fn check(&self, url: &str) -> Result<(), ToolError> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| ToolError::InvalidArguments(format!("invalid URL '{}': {}", url, e)))?;
+        let host = parsed.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+        if self.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+            Ok(())
+        } else {
+            Err(ToolError::InvalidArguments(format!("host '{}' is not in the allowed hosts {:?}", host, self.hosts)))
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Tools declared as HTTP requests instead of written in Rust.
+
+mod openapi;
+
+use async_trait::async_trait;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::{HostAllowlist, Tool, ToolError, ToolMetadata};
+
+/// How much of an error response body to include in the tool error.
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// A tool that makes one HTTP request.
+///
+/// `url`, `headers`, and `query` are templates: `{name}` is replaced with the
+/// call's `name` argument and `${VAR}` with the environment variable `VAR`, so
+/// secrets stay out of pipeline JSON. A `query` or `headers` entry that is only
+/// a placeholder is left out when its argument isn't given.
+///
+/// `body` is a JSON template in which a string that is only `{name}` becomes
+/// the argument's JSON value. Without one, POST, PUT, and PATCH requests send
+/// the arguments not used elsewhere as a JSON object.
+///
+/// When `parameters` is omitted, every placeholder becomes a required string.
+///
+/// ```json
+/// {
+///   "name": "create_ticket",
+///   "description": "Opens a support ticket",
+///   "method": "POST",
+///   "url": "https://api.example.com/projects/{project}/tickets",
+///   "headers": { "Authorization": "Bearer ${TICKETS_TOKEN}" },
+///   "body": { "title": "{title}", "priority": "{priority}" }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpToolSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// JSON Schema for the arguments.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub parameters: Value,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl HttpToolSpec {
+    pub fn new(name: impl Into<String>, method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            method: method.into(),
+            url: url.into(),
+            headers: BTreeMap::new(),
+            query: BTreeMap::new(),
+            body: None,
+            parameters: Value::Null,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, template: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), template.into());
+        self
+    }
+
+    pub fn with_query(mut self, name: impl Into<String>, template: impl Into<String>) -> Self {
+        self.query.insert(name.into(), template.into());
+        self
+    }
+
+    pub fn with_body(mut self, template: Value) -> Self {
+        self.body = Some(template);
+        self
+    }
+
+    pub fn with_parameters(mut self, schema: Value) -> Self {
+        self.parameters = schema;
+        self
+    }
+
+    /// Builds a spec for each operation in an OpenAPI 3 document.
+    ///
+    /// Tools are named by `operationId`. Path, query, and header parameters
+    /// become arguments, and a JSON request body becomes the `body` argument.
+    /// `base_url` overrides the document's first server URL.
+    pub fn from_openapi(document: &Value, base_url: Option<&str>) -> Result<Vec<Self>, ToolError> {
+        openapi::specs(document, base_url)
+    }
+
+    /// Names of the `{name}` placeholders used by the URL, query, and headers.
+    fn placeholders(&self) -> Vec<String> {
+        let templates = std::iter::once(&self.url).chain(self.query.values()).chain(self.headers.values());
+        let mut names: Vec<String> = Vec::new();
+        for template in templates {
+            for name in placeholders(template) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+}
+
+/// A tool built from an [`HttpToolSpec`].
+pub struct HttpTool {
+    spec: HttpToolSpec,
+    parameters: Value,
+    client: reqwest::Client,
+    allowed_hosts: Option<HostAllowlist>,
+}
+
+impl HttpTool {
+    pub fn new(spec: HttpToolSpec) -> Self {
+        let parameters = match &spec.parameters {
+            Value::Null => inferred_parameters(&spec),
+            schema => schema.clone(),
+        };
+        Self { spec, parameters, client: reqwest::Client::new(), allowed_hosts: None }
+    }
+
+    /// Refuses requests to hosts not in `hosts`.
+    pub fn with_allowed_hosts(mut self, hosts: HostAllowlist) -> Self {
+        self.allowed_hosts = Some(hosts);
+        self
+    }
+
+    pub fn spec(&self) -> &HttpToolSpec {
+        &self.spec
+    }
+}
+
+#[async_trait]
+impl Tool for HttpTool {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn description(&self) -> &str {
+        &self.spec.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        // POST and PATCH aren't idempotent; the other methods are safe to repeat
+        let side_effects = matches!(self.spec.method.to_uppercase().as_str(), "POST" | "PATCH");
+        ToolMetadata::new().with_namespace("http").with_tag("network").with_side_effects(side_effects)
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let empty = Map::new();
+        let args = args.as_object().unwrap_or(&empty);
+        let method = Method::from_bytes(self.spec.method.to_uppercase().as_bytes())
+            .map_err(|_| ToolError::InvalidArguments(format!("invalid HTTP method: {}", self.spec.method)))?;
+
+        let url = render(&self.spec.url, args, true)?;
+        if let Some(hosts) = &self.allowed_hosts {
+            hosts.check(&url)?;
+        }
+        let mut request = self.client.request(method.clone(), &url);
+
+        let mut query = Vec::new();
+        for (name, template) in &self.spec.query {
+            if let Some(value) = render_optional(template, args)? {
+                query.push((name.as_str(), value));
+            }
+        }
+        if !query.is_empty() {
+            request = request.query(&query);
+        }
+        for (name, template) in &self.spec.headers {
+            if let Some(value) = render_optional(template, args)? {
+                request = request.header(name.as_str(), value);
+            }
+        }
+
+        let body = match &self.spec.body {
+            Some(template) => Some(render_value(template, args)?),
+            None if matches!(method, Method::POST | Method::PUT | Method::PATCH) => {
+                let used = self.spec.placeholders();
+                let rest: Map<String, Value> = args.iter().filter(|(k, _)| !used.contains(k)).map(|(k, v)| (k.clone(), v.clone())).collect();
+                (!rest.is_empty()).then_some(Value::Object(rest))
+            }
+            None => None,
+        };
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            let detail: String = text.chars().take(MAX_ERROR_BODY_CHARS).collect();
+            return Err(ToolError::ExecutionFailed(format!("{} {} returned {}: {}", method, url, status, detail)));
+        }
+        Ok(text)
+    }
+}
+
+/// A schema with each placeholder as a required string argument.
+fn inferred_parameters(spec: &HttpToolSpec) -> Value {
+    let mut names = spec.placeholders();
+    if let Some(body) = &spec.body {
+        for name in body_placeholders(body) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    let properties: Map<String, Value> = names.iter().map(|n| (n.clone(), json!({ "type": "string" }))).collect();
+    json!({ "type": "object", "properties": properties, "required": names })
+}
+
+/// Returns the `{name}` placeholders in a template, skipping `${VAR}` references.
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let is_env = start > 0 && rest.as_bytes()[start - 1] == b'$';
+        if !is_env {
+            names.push(rest[start + 1..start + len].to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    names
+}
+
+fn body_placeholders(template: &Value) -> Vec<String> {
+    match template {
+        Value::String(s) => placeholders(s),
+        Value::Array(items) => items.iter().flat_map(body_placeholders).collect(),
+        Value::Object(map) => map.values().flat_map(body_placeholders).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fills a template's placeholders. With `encode`, arguments are percent-encoded for use in a URL path.
+fn render(template: &str, args: &Map<String, Value>, encode: bool) -> Result<String, ToolError> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = &rest[start + 1..start + len];
+        if start > 0 && rest.as_bytes()[start - 1] == b'$' {
+            out.push_str(&rest[..start - 1]);
+            let value = std::env::var(name)
+                .map_err(|_| ToolError::ExecutionFailed(format!("environment variable {} is not set", name)))?;
+            out.push_str(&value);
+        } else {
+            out.push_str(&rest[..start]);
+            let value = args
+                .get(name)
+                .map(argument_text)
+                .ok_or_else(|| ToolError::InvalidArguments(format!("missing argument: {}", name)))?;
+            out.push_str(&if encode { encode_component(&value) } else { value });
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Renders a query or header template, or returns `None` if it is a single
+/// placeholder whose argument wasn't given.
+fn render_optional(template: &str, args: &Map<String, Value>) -> Result<Option<String>, ToolError> {
+    if let Some(name) = sole_placeholder(template) {
+        if args.get(name).is_none_or(Value::is_null) {
+            return Ok(None);
+        }
+    }
+    render(template, args, false).map(Some)
+}
+
+/// Renders a body template. A string that is a single placeholder takes the
+/// argument's JSON value, and object fields whose argument is missing are dropped.
+fn render_value(template: &Value, args: &Map<String, Value>) -> Result<Value, ToolError> {
+    Ok(match template {
+        Value::String(s) => match sole_placeholder(s) {
+            Some(name) => args.get(name).cloned().unwrap_or(Value::Null),
+            None => Value::String(render(s, args, false)?),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, args)).collect::<Result<_, _>>()?),
+        Value::Object(map) => {
+            let mut out = Map::new();
+            for (key, value) in map {
+                let rendered = render_value(value, args)?;
+                let omitted = rendered.is_null() && value.as_str().and_then(sole_placeholder).is_some();
+                if !omitted {
+                    out.insert(key.clone(), rendered);
+                }
+            }
+            Value::Object(out)
+        }
+        other => other.clone(),
+    })
+}
+
+/// Returns `name` if the template is exactly `{name}`.
+fn sole_placeholder(template: &str) -> Option<&str> {
+    let name = template.strip_prefix('{')?.strip_suffix('}')?;
+    (!name.is_empty() && !name.contains(['{', '}'])).then_some(name)
+}
+
+fn argument_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn encode_component(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,133 @@
+//! Builds HTTP tool specs from an OpenAPI 3 document.
+
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use super::HttpToolSpec;
+use crate::ToolError;
+
+const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+/// How deep `$ref`s are inlined; deeper (usually recursive) schemas accept any value.
+const MAX_REF_DEPTH: usize = 8;
+
+pub(super) fn specs(document: &Value, base_url: Option<&str>) -> Result<Vec<HttpToolSpec>, ToolError> {
+    let base = base_url
+        .or_else(|| document["servers"][0]["url"].as_str())
+        .ok_or_else(|| ToolError::InvalidArguments("OpenAPI document has no server URL; pass a base URL".into()))?
+        .trim_end_matches('/');
+    let paths = document["paths"]
+        .as_object()
+        .ok_or_else(|| ToolError::InvalidArguments("OpenAPI document has no paths".into()))?;
+
+    let mut specs = Vec::new();
+    for (path, item) in paths {
+        let shared_params = item["parameters"].as_array().cloned().unwrap_or_default();
+        for method in METHODS {
+            let operation = &item[method];
+            if !operation.is_object() {
+                continue;
+            }
+            let params = shared_params.iter().chain(operation["parameters"].as_array().into_iter().flatten());
+            specs.push(operation_spec(document, base, path, method, operation, params));
+        }
+    }
+    Ok(specs)
+}
+
+fn operation_spec<'a>(
+    document: &Value,
+    base: &str,
+    path: &str,
+    method: &str,
+    operation: &Value,
+    params: impl Iterator<Item = &'a Value>,
+) -> HttpToolSpec {
+    let name = match operation["operationId"].as_str() {
+        Some(id) => tool_name(id),
+        None => tool_name(&format!("{}_{}", method, path)),
+    };
+    let description = [&operation["summary"], &operation["description"]]
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut query = BTreeMap::new();
+    let mut headers = BTreeMap::new();
+
+    for param in params {
+        let param = inline_refs(document, param, 0);
+        let Some(param_name) = param["name"].as_str() else { continue };
+        match param["in"].as_str() {
+            Some("query") => {
+                query.insert(param_name.to_string(), format!("{{{}}}", param_name));
+            }
+            Some("header") => {
+                headers.insert(param_name.to_string(), format!("{{{}}}", param_name));
+            }
+            Some("path") => {}
+            _ => continue,
+        }
+        let mut schema = match &param["schema"] {
+            Value::Object(schema) => schema.clone(),
+            _ => Map::new(),
+        };
+        if let Some(text) = param["description"].as_str() {
+            schema.insert("description".into(), json!(text));
+        }
+        properties.insert(param_name.to_string(), Value::Object(schema));
+        if param["required"].as_bool() == Some(true) || param["in"] == "path" {
+            required.push(json!(param_name));
+        }
+    }
+
+    let request_body = inline_refs(document, &operation["requestBody"], 0);
+    let body_schema = &request_body["content"]["application/json"]["schema"];
+    let body = body_schema.is_object().then(|| {
+        properties.insert("body".into(), body_schema.clone());
+        if request_body["required"].as_bool() == Some(true) {
+            required.push(json!("body"));
+        }
+        json!("{body}")
+    });
+
+    HttpToolSpec {
+        name,
+        description,
+        method: method.to_uppercase(),
+        url: format!("{}{}", base, path),
+        headers,
+        query,
+        body,
+        parameters: json!({ "type": "object", "properties": properties, "required": required }),
+    }
+}
+
+/// Replaces local `$ref`s (`#/components/...`) with what they point to.
+fn inline_refs(document: &Value, value: &Value, depth: usize) -> Value {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                let target = reference.strip_prefix('#').and_then(|pointer| document.pointer(pointer));
+                return match target {
+                    Some(target) if depth < MAX_REF_DEPTH => inline_refs(document, target, depth + 1),
+                    _ => json!({}),
+                };
+            }
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), inline_refs(document, v, depth))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| inline_refs(document, v, depth)).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Makes a tool name that LLM providers accept: letters, digits, `_`, and `-`.
+fn tool_name(raw: &str) -> String {
+    let name: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    name.trim_matches('_').to_string()
+}
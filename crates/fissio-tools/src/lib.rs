@@ -5,8 +5,24 @@
 //! - [`Tool`] — Trait for implementing custom tools
 //! - [`ToolRegistry`] — Registry for managing available tools
 //! - [`ToolSchema`] — JSON schema for tool parameters
-//! - [`FetchUrlTool`] — Built-in HTTP fetch tool
-//! - [`WebSearchTool`] — Built-in web search (requires Tavily API key)
+//! - [`ToolMetadata`] — Namespace, tags, cost hint, and approval requirement of a tool
+//! - [`ToolArgs`] — Parameter schema and parsing derived from an argument struct; [`typed_tool!`] and [`FnTool`] build a tool from a closure over it
+//! - [`FetchUrlTool`] — Built-in HTTP fetch tool with main-content extraction
+//! - [`WebSearchTool`] — Built-in web search through a [`SearchProvider`]: Tavily, Brave, SerpAPI, or DuckDuckGo
+//! - [`FileReadTool`], [`FileWriteTool`], [`ListDirTool`] — Files under a sandbox root ([`FsRoot`])
+//! - [`CommandTool`] — Opt-in `run_command` tool for allow-listed commands
+//! - [`PythonTool`] — Opt-in `python_exec` tool for Python snippets under resource limits
+//! - [`CurrentTimeTool`] — `current_time` in a configured [`Timezone`]
+//! - [`SendEmailTool`] — `send_email` through SendGrid or SMTP (`smtp` feature), with a dry-run mode
+//! - [`TranscribeAudioTool`] — Speech-to-text through a [`Transcriber`]: OpenAI Whisper or a whisper.cpp server
+//! - [`MockTool`] — Canned responses for deterministic tests
+//! - [`ToolExecutionPolicy`] — Timeout and result truncation around tool calls
+//! - [`PostProcessor`] — Summarize, extract, or JSON-path steps on tool output
+//! - [`McpToolProvider`] — Tools served by MCP servers over stdio or SSE
+//! - [`HttpTool`] — Tools declared as HTTP requests, by hand or from an OpenAPI document
+//! - [`HostAllowlist`] — Hosts network tools may reach
+//! - `WasmTool` — Tools implemented as sandboxed WebAssembly modules (`wasm` feature)
+//! - `SqlQueryTool` — Read-only SQL queries against SQLite or Postgres (`sql` feature)
 //!
 //! # Implementing a Custom Tool
 //!
@@ -36,12 +52,32 @@
 //! }
 //! ```
 //!
+//! Or derive the schema from an argument struct, and skip the `Tool` impl
+//! for a tool that is just a function:
+//!
+//! ```rust,ignore
+//! use fissio_tools::{typed_tool, ToolArgs};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, ToolArgs)]
+//! struct CalculatorArgs {
+//!     /// Arithmetic expression, e.g. "6 * 7"
+//!     expression: String,
+//! }
+//!
+//! let calculator = typed_tool!("calculator", "Performs math calculations", |args: CalculatorArgs| {
+//!     Ok(evaluate(&args.expression)?.to_string())
+//! });
+//! registry.register(calculator);
+//! ```
+//!
 //! # Using the Registry
 //!
 //! ```rust,ignore
 //! use fissio_tools::ToolRegistry;
 //!
-//! // Create with defaults (includes fetch_url, web_search if TAVILY_API_KEY set)
+//! // Create with defaults (includes fetch_url, web_search if a search provider is
+//! // configured, file tools if FISSIO_FS_ROOT set)
 //! let registry = ToolRegistry::with_defaults();
 //!
 //! // Or build manually
@@ -50,13 +86,64 @@
 //!
 //! // Get schemas for LLM
 //! let schemas = registry.schemas_for(&["fetch_url".to_string()]);
+//!
+//! // Bound every call: 15s timeout, results cut to 8000 chars
+//! let registry = registry.with_policy(
+//!     ToolExecutionPolicy::new()
+//!         .with_timeout(Duration::from_secs(15))
+//!         .with_max_result_chars(8000),
+//! );
 //! ```
 
+mod clock;
+mod command;
+mod email;
 mod fetch_url;
+mod fs;
+mod hosts;
+mod http;
+mod mcp;
+mod metadata;
+mod mock;
+mod policy;
+mod postprocess;
+mod python;
+#[cfg(feature = "sql")]
+mod sql;
+mod transcribe;
+mod typed;
+#[cfg(feature = "wasm")]
+mod wasm;
 mod web_search;
 
+pub use clock::{CurrentTimeTool, Timezone};
+pub use command::{CommandTool, DEFAULT_COMMAND_OUTPUT_BYTES, DEFAULT_COMMAND_TIMEOUT};
+pub use email::{EmailBackend, SendEmailTool, DEFAULT_SMTP_PORT, SENDGRID_API_BASE};
 pub use fetch_url::FetchUrlTool;
-pub use web_search::WebSearchTool;
+pub use fs::{FileReadTool, FileWriteTool, FsRoot, ListDirTool};
+pub use hosts::HostAllowlist;
+pub use http::{HttpTool, HttpToolSpec};
+pub use mcp::{McpTool, McpToolProvider, McpTransport};
+pub use metadata::{CostHint, ToolMetadata};
+pub use mock::{MockResponse, MockTool, ToolMock};
+pub use policy::{truncate_middle, ToolExecutionPolicy};
+pub use postprocess::{extract_html, select_json, PostProcessor};
+pub use python::{PythonTool, DEFAULT_PYTHON_MEMORY_BYTES, DEFAULT_PYTHON_TIMEOUT};
+#[cfg(feature = "sql")]
+pub use sql::{SqlConnection, SqlQueryTool, DEFAULT_SQL_MAX_BYTES, DEFAULT_SQL_MAX_ROWS};
+#[doc(hidden)]
+pub use typed::__private;
+pub use typed::{ArgSchema, FnTool, ToolArgs};
+pub use fissio_macros::ToolArgs;
+pub use transcribe::{
+    TranscribeAudioTool, Transcriber, TranscriptionBackend, DEFAULT_TRANSCRIPTION_MODEL, MAX_AUDIO_BYTES,
+};
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmTool, WasmToolSpec, DEFAULT_WASM_FUEL, DEFAULT_WASM_MEMORY_BYTES};
+pub use web_search::{
+    BraveSearch, DuckDuckGoSearch, SearchHit, SearchProvider, SearchProviderKind, SearchResults, SerpApiSearch,
+    TavilySearch, WebSearchTool,
+};
 
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -67,6 +154,7 @@ pub use fissio_core::{ToolCall, ToolResult, ToolSchema};
 
 /// Errors that can occur during tool execution.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ToolError {
     /// Tool execution failed with a message.
     #[error("Tool execution failed: {0}")]
@@ -83,6 +171,10 @@ pub enum ToolError {
     /// Requested tool was not found in the registry.
     #[error("Tool not found: {0}")]
     NotFound(String),
+
+    /// Tool call exceeded the policy timeout (milliseconds).
+    #[error("Tool timed out after {0}ms")]
+    Timeout(u64),
 }
 
 /// Trait for implementing tools that can be called by LLMs.
@@ -109,6 +201,11 @@ pub trait Tool: Send + Sync {
     /// The tool's output as a string, or an error.
     async fn execute(&self, args: serde_json::Value) -> Result<String, ToolError>;
 
+    /// Returns the tool's namespace, tags, and hints (none by default).
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::default()
+    }
+
     /// Generates the schema for this tool (default implementation).
     fn schema(&self) -> ToolSchema {
         ToolSchema {
@@ -122,8 +219,12 @@ pub trait Tool: Send + Sync {
 /// Registry of tools available to pipeline nodes.
 ///
 /// The registry manages tool instances and provides schemas for LLM function calling.
+#[derive(Clone)]
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
+    /// Metadata set on the registry, replacing what the tool reports.
+    metadata: HashMap<String, ToolMetadata>,
+    policy: ToolExecutionPolicy,
 }
 
 impl Default for ToolRegistry {
@@ -137,21 +238,62 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            metadata: HashMap::new(),
+            policy: ToolExecutionPolicy::default(),
         }
     }
 
+    /// Sets the default execution policy for calls to this registry's tools.
+    pub fn with_policy(mut self, policy: ToolExecutionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the default execution policy.
+    pub fn policy(&self) -> &ToolExecutionPolicy {
+        &self.policy
+    }
+
     /// Creates a registry with default built-in tools.
     ///
     /// Includes:
-    /// - `fetch_url` — Always available
-    /// - `web_search` — Available if `TAVILY_API_KEY` env var is set
+    /// - `fetch_url` — Always available; honors robots.txt if `FETCH_URL_RESPECT_ROBOTS=true`
+    /// - `current_time` — Always available; in `FISSIO_TIMEZONE`, else UTC
+    /// - `web_search` — Available if `WEB_SEARCH_PROVIDER` or a search API key
+    ///   (`TAVILY_API_KEY`, `BRAVE_API_KEY`, `SERPAPI_API_KEY`) is set
+    /// - `read_file`, `write_file`, `list_dir` — Available if `FISSIO_FS_ROOT` is set,
+    ///   confined to that directory
+    /// - `transcribe_audio` — Available if `WHISPER_CPP_URL` or `OPENAI_API_KEY` is set;
+    ///   reads recordings under `FISSIO_FS_ROOT` too when that is set
+    /// - `send_email` — Available if `EMAIL_FROM` and `SENDGRID_API_KEY` or `SMTP_HOST`
+    ///   are set, or with `EMAIL_DRY_RUN=true`; see [`SendEmailTool::from_env`]
     pub fn with_defaults() -> Self {
         let mut registry = Self::new();
 
-        registry.register(FetchUrlTool::new());
+        registry.register(FetchUrlTool::from_env());
+        registry.register(CurrentTimeTool::from_env());
+
+        if let Some(tool) = WebSearchTool::from_env() {
+            registry.register(tool);
+        }
 
-        if let Ok(api_key) = std::env::var("TAVILY_API_KEY") {
-            registry.register(WebSearchTool::new(api_key));
+        let fs_root = std::env::var("FISSIO_FS_ROOT").ok().map(FsRoot::new);
+        if let Some(root) = &fs_root {
+            registry.register(FileReadTool::new(root.clone()));
+            registry.register(FileWriteTool::new(root.clone()));
+            registry.register(ListDirTool::new(root.clone()));
+        }
+
+        if let Some(transcriber) = Transcriber::from_env() {
+            let tool = TranscribeAudioTool::new(transcriber);
+            registry.register(match fs_root {
+                Some(root) => tool.with_root(root),
+                None => tool,
+            });
+        }
+
+        if let Some(tool) = SendEmailTool::from_env() {
+            registry.register(tool);
         }
 
         registry
@@ -164,6 +306,27 @@ impl ToolRegistry {
         self.tools.insert(tool.name().to_string(), Arc::new(tool));
     }
 
+    /// Adds every tool from `other`, with its metadata overrides, replacing tools of the same name.
+    pub fn extend(&mut self, other: ToolRegistry) {
+        self.tools.extend(other.tools);
+        self.metadata.extend(other.metadata);
+    }
+
+    /// Removes a tool and its metadata override. Returns whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.metadata.remove(name);
+        self.tools.remove(name).is_some()
+    }
+
+    /// Replaces a tool with canned responses, keeping its schema if it is registered.
+    pub fn mock(&mut self, name: &str, mock: ToolMock) {
+        let tool = match self.tools.get(name) {
+            Some(existing) => MockTool::replacing(existing.as_ref(), mock),
+            None => MockTool::new(name, mock),
+        };
+        self.register(tool);
+    }
+
     /// Gets a tool by name.
     pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
         self.tools.get(name).cloned()
@@ -193,4 +356,70 @@ impl ToolRegistry {
     pub fn tool_names(&self) -> Vec<String> {
         self.tools.keys().cloned().collect()
     }
+
+    /// Sets a tool's metadata, replacing what the tool reports. Use it to tag
+    /// tools that don't describe themselves, such as MCP or HTTP tools.
+    pub fn set_metadata(&mut self, name: impl Into<String>, metadata: ToolMetadata) {
+        self.metadata.insert(name.into(), metadata);
+    }
+
+    /// Returns a registered tool's metadata.
+    pub fn metadata(&self, name: &str) -> Option<ToolMetadata> {
+        let tool = self.tools.get(name)?;
+        Some(self.metadata.get(name).cloned().unwrap_or_else(|| tool.metadata()))
+    }
+
+    /// Returns the names of tools tagged `tag`, sorted.
+    pub fn tools_with_tag(&self, tag: &str) -> Vec<String> {
+        self.names_where(|m| m.has_tag(tag))
+    }
+
+    /// Returns the names of tools in `namespace`, sorted.
+    pub fn tools_in_namespace(&self, namespace: &str) -> Vec<String> {
+        self.names_where(|m| m.namespace.as_deref() == Some(namespace))
+    }
+
+    fn names_where(&self, predicate: impl Fn(&ToolMetadata) -> bool) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .tools
+            .keys()
+            .filter(|name| self.metadata(name).is_some_and(|m| predicate(&m)))
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Expands a node's tool selections into tool names, keeping their order
+    /// and dropping duplicates.
+    ///
+    /// - `*` selects every tool
+    /// - `web/*` selects the tools in namespace `web`
+    /// - `web/fetch_url` selects `fetch_url` if it is in namespace `web`
+    /// - anything else is a tool name, kept even if unregistered
+    pub fn resolve(&self, selections: &[String]) -> Vec<String> {
+        let mut names = Vec::new();
+        for selection in selections {
+            let expanded = match selection.split_once('/') {
+                _ if selection == "*" => {
+                    let mut all = self.tool_names();
+                    all.sort();
+                    all
+                }
+                Some((namespace, "*")) => self.tools_in_namespace(namespace),
+                Some((namespace, name)) => self
+                    .metadata(name)
+                    .filter(|m| m.namespace.as_deref() == Some(namespace))
+                    .map(|_| vec![name.to_string()])
+                    .unwrap_or_default(),
+                None => vec![selection.clone()],
+            };
+            for name in expanded {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
 }
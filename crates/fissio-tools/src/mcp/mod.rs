@@ -0,0 +1,398 @@
+//! Tools served by MCP (Model Context Protocol) servers.
+
+mod sse;
+mod stdio;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+use crate::{Tool, ToolError, ToolMetadata, ToolRegistry};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How to reach an MCP server.
+///
+/// Deserializes from the `mcpServers` entries used by MCP clients: an entry
+/// with `command` is launched over stdio, one with `url` is reached over SSE.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum McpTransport {
+    /// Launches the server as a child process and talks JSON-RPC over its stdin and stdout.
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    /// Connects to the server's SSE endpoint and posts requests to the endpoint it announces.
+    Sse {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// Connects to an MCP server and registers its tools.
+///
+/// Each tool is registered as `<server>.<tool>`, e.g. `github.create_issue`.
+/// The connection stays open while any of its tools is registered; if the
+/// server exits, calls to its tools fail until it is registered again.
+///
+/// ```rust,ignore
+/// let github = McpToolProvider::stdio("github", "npx", ["-y", "@modelcontextprotocol/server-github"])
+///     .with_env("GITHUB_PERSONAL_ACCESS_TOKEN", token);
+/// github.register_into(&mut registry).await?;
+/// ```
+pub struct McpToolProvider {
+    name: String,
+    transport: McpTransport,
+    timeout: Duration,
+}
+
+impl McpToolProvider {
+    pub fn new(name: impl Into<String>, transport: McpTransport) -> Self {
+        Self { name: name.into(), transport, timeout: DEFAULT_TIMEOUT }
+    }
+
+    /// A server launched as `command args…`.
+    pub fn stdio<I, S>(name: impl Into<String>, command: impl Into<String>, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let transport = McpTransport::Stdio {
+            command: command.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            env: HashMap::new(),
+        };
+        Self::new(name, transport)
+    }
+
+    /// A server reached at its SSE endpoint, e.g. `http://localhost:8000/sse`.
+    pub fn sse(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self::new(name, McpTransport::Sse { url: url.into(), headers: HashMap::new() })
+    }
+
+    /// Sets an environment variable for a stdio server.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        if let McpTransport::Stdio { env, .. } = &mut self.transport {
+            env.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Adds a header to every request to an SSE server, e.g. `Authorization`.
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        if let McpTransport::Sse { headers, .. } = &mut self.transport {
+            headers.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    /// Sets how long to wait for each response from the server (default 60s).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reads providers from a JSON file in the common `mcpServers` format:
+    ///
+    /// ```json
+    /// { "mcpServers": {
+    ///     "github": { "command": "npx", "args": ["-y", "@modelcontextprotocol/server-github"] },
+    ///     "docs": { "url": "http://localhost:8000/sse" }
+    /// } }
+    /// ```
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Vec<Self>, ToolError> {
+        #[derive(Deserialize)]
+        struct ConfigFile {
+            #[serde(rename = "mcpServers")]
+            servers: HashMap<String, McpTransport>,
+        }
+
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to read {}: {}", path.display(), e)))?;
+        let config: ConfigFile = serde_json::from_str(&text)
+            .map_err(|e| ToolError::InvalidArguments(format!("invalid MCP config {}: {}", path.display(), e)))?;
+        Ok(config.servers.into_iter().map(|(name, transport)| Self::new(name, transport)).collect())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Connects, performs the MCP handshake, and returns the server's tools.
+    pub async fn connect(&self) -> Result<Vec<McpTool>, ToolError> {
+        let session = Arc::new(Session::connect(&self.name, &self.transport, self.timeout).await?);
+        session
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "fissio", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        session.notify("notifications/initialized").await?;
+
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let page = session.request("tools/list", params).await?;
+            for tool in page["tools"].as_array().into_iter().flatten() {
+                let Some(remote_name) = tool["name"].as_str() else { continue };
+                tools.push(McpTool {
+                    name: format!("{}.{}", self.name, remote_name),
+                    remote_name: remote_name.to_string(),
+                    description: tool["description"].as_str().unwrap_or_default().to_string(),
+                    parameters: match &tool["inputSchema"] {
+                        Value::Null => json!({ "type": "object", "properties": {} }),
+                        schema => schema.clone(),
+                    },
+                    session: Arc::clone(&session),
+                });
+            }
+            cursor = page["nextCursor"].as_str().map(String::from);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        info!("MCP server '{}': {} tools", self.name, tools.len());
+        Ok(tools)
+    }
+
+    /// Connects and registers the server's tools, returning how many were registered.
+    pub async fn register_into(&self, registry: &mut ToolRegistry) -> Result<usize, ToolError> {
+        let tools = self.connect().await?;
+        let count = tools.len();
+        for tool in tools {
+            registry.register(tool);
+        }
+        Ok(count)
+    }
+}
+
+/// A tool served by an MCP server, registered as `<server>.<tool>`.
+pub struct McpTool {
+    name: String,
+    remote_name: String,
+    description: String,
+    parameters: Value,
+    session: Arc<Session>,
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("mcp")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let arguments = if args.is_null() { json!({}) } else { args };
+        let result = self
+            .session
+            .request("tools/call", json!({ "name": self.remote_name, "arguments": arguments }))
+            .await?;
+        let text = content_text(&result["content"]);
+        if result["isError"].as_bool() == Some(true) {
+            return Err(ToolError::ExecutionFailed(text));
+        }
+        Ok(text)
+    }
+}
+
+/// Joins the text of a `tools/call` result's content blocks.
+fn content_text(content: &Value) -> String {
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|block| match block["type"].as_str() {
+            Some("text") => block["text"].as_str().unwrap_or_default().to_string(),
+            Some("resource") => match block["resource"]["text"].as_str() {
+                Some(text) => text.to_string(),
+                None => format!("[resource: {}]", block["resource"]["uri"].as_str().unwrap_or("unknown")),
+            },
+            Some(kind) => format!("[{}: {}]", kind, block["mimeType"].as_str().unwrap_or("unknown")),
+            None => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, ToolError>>>>>;
+
+/// Where JSON-RPC messages to the server are written.
+#[derive(Clone)]
+enum Outbound {
+    Stdio(mpsc::UnboundedSender<String>),
+    Http { client: reqwest::Client, endpoint: String, headers: reqwest::header::HeaderMap },
+}
+
+impl Outbound {
+    async fn send(&self, message: &Value) -> Result<(), ToolError> {
+        match self {
+            Outbound::Stdio(tx) => tx
+                .send(message.to_string())
+                .map_err(|_| ToolError::ExecutionFailed("MCP server process has exited".into())),
+            Outbound::Http { client, endpoint, headers } => {
+                let res = client.post(endpoint).headers(headers.clone()).json(message).send().await?;
+                if !res.status().is_success() {
+                    return Err(ToolError::ExecutionFailed(format!("MCP server returned {}", res.status())));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An open connection to an MCP server, matching responses to requests by ID.
+struct Session {
+    server: String,
+    outbound: Outbound,
+    pending: Pending,
+    next_id: AtomicU64,
+    timeout: Duration,
+    tasks: Vec<JoinHandle<()>>,
+    /// Killed when the session is dropped.
+    _child: Option<Child>,
+}
+
+impl Session {
+    async fn connect(server: &str, transport: &McpTransport, timeout: Duration) -> Result<Self, ToolError> {
+        let pending: Pending = Arc::default();
+        let (outbound, tasks, child) = match transport {
+            McpTransport::Stdio { command, args, env } => {
+                let (outbound, tasks, child) = stdio::spawn(command, args, env, Arc::clone(&pending))?;
+                (outbound, tasks, Some(child))
+            }
+            McpTransport::Sse { url, headers } => {
+                let (outbound, task) = sse::connect(url, headers, Arc::clone(&pending), timeout).await?;
+                (outbound, vec![task], None)
+            }
+        };
+        Ok(Self {
+            server: server.to_string(),
+            outbound,
+            pending,
+            next_id: AtomicU64::new(1),
+            timeout,
+            tasks,
+            _child: child,
+        })
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, ToolError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id, tx);
+        }
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = self.outbound.send(&message).await {
+            self.forget(id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(ToolError::ExecutionFailed(format!("MCP server '{}' disconnected", self.server))),
+            Err(_) => {
+                self.forget(id);
+                Err(ToolError::Timeout(self.timeout.as_millis() as u64))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str) -> Result<(), ToolError> {
+        self.outbound.send(&json!({ "jsonrpc": "2.0", "method": method })).await
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Handles a message from the server: completes the matching request, or
+/// answers the server's own requests (only `ping` is supported).
+async fn dispatch(pending: &Pending, outbound: &Outbound, message: Value) {
+    let method = message["method"].as_str();
+    let id = &message["id"];
+
+    if let Some(method) = method {
+        if id.is_null() {
+            debug!("MCP notification: {}", method);
+            return;
+        }
+        let reply = match method {
+            "ping" => json!({ "jsonrpc": "2.0", "id": id, "result": {} }),
+            _ => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("method not supported: {}", method) },
+            }),
+        };
+        let _ = outbound.send(&reply).await;
+        return;
+    }
+
+    let Some(id) = id.as_u64() else { return };
+    let Some(tx) = pending.lock().ok().and_then(|mut p| p.remove(&id)) else { return };
+    let result = match message.get("error") {
+        Some(error) => Err(ToolError::ExecutionFailed(
+            error["message"].as_str().unwrap_or("MCP request failed").to_string(),
+        )),
+        None => Ok(message["result"].clone()),
+    };
+    let _ = tx.send(result);
+}
+
+/// Fails every waiting request once the server's output closes.
+fn fail_pending(pending: &Pending, reason: &str) {
+    let Ok(mut pending) = pending.lock() else { return };
+    for (_, tx) in pending.drain() {
+        let _ = tx.send(Err(ToolError::ExecutionFailed(reason.to_string())));
+    }
+}
@@ -0,0 +1,110 @@
+//! MCP over HTTP with server-sent events.
+//!
+//! The client holds a GET stream open; the server's first `endpoint` event gives
+//! the URL to POST requests to, and responses arrive as `message` events.
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use super::{dispatch, fail_pending, Outbound, Pending};
+use crate::ToolError;
+
+/// Opens the event stream and waits for the server to announce its message endpoint.
+pub(super) async fn connect(
+    url: &str,
+    headers: &HashMap<String, String>,
+    pending: Pending,
+    timeout: Duration,
+) -> Result<(Outbound, JoinHandle<()>), ToolError> {
+    let headers = header_map(headers)?;
+    let client = reqwest::Client::new();
+    let mut res = client.get(url).headers(headers.clone()).header(ACCEPT, "text/event-stream").send().await?;
+    if !res.status().is_success() {
+        return Err(ToolError::ExecutionFailed(format!("MCP server {} returned {}", url, res.status())));
+    }
+
+    let base = res.url().clone();
+    let (endpoint_tx, endpoint_rx) = oneshot::channel();
+    let stream_client = client.clone();
+    let stream_headers = headers.clone();
+    let task = tokio::spawn(async move {
+        let mut endpoint_tx = Some(endpoint_tx);
+        let mut outbound: Option<Outbound> = None;
+        let mut buffer = String::new();
+        let mut bytes = Vec::new();
+
+        while let Ok(Some(chunk)) = res.chunk().await {
+            bytes.extend_from_slice(&chunk);
+            // Keep an incomplete UTF-8 sequence for the next chunk
+            let valid = match std::str::from_utf8(&bytes) {
+                Ok(text) => text.len(),
+                Err(e) => e.valid_up_to(),
+            };
+            buffer.push_str(&String::from_utf8_lossy(&bytes[..valid]).replace('\r', ""));
+            bytes.drain(..valid);
+
+            while let Some(end) = buffer.find("\n\n") {
+                let block: String = buffer.drain(..end + 2).collect();
+                let (event, data) = parse_event(&block);
+                match event.as_str() {
+                    "endpoint" => {
+                        let Ok(endpoint) = base.join(data.trim()) else { continue };
+                        let http = Outbound::Http {
+                            client: stream_client.clone(),
+                            endpoint: endpoint.to_string(),
+                            headers: stream_headers.clone(),
+                        };
+                        outbound = Some(http.clone());
+                        if let Some(tx) = endpoint_tx.take() {
+                            let _ = tx.send(http);
+                        }
+                    }
+                    "message" => match (&outbound, serde_json::from_str(&data)) {
+                        (Some(out), Ok(message)) => dispatch(&pending, out, message).await,
+                        _ => debug!("Ignoring MCP event: {}", data),
+                    },
+                    _ => {}
+                }
+            }
+        }
+        fail_pending(&pending, "MCP server closed the event stream");
+    });
+
+    match tokio::time::timeout(timeout, endpoint_rx).await {
+        Ok(Ok(outbound)) => Ok((outbound, task)),
+        _ => {
+            task.abort();
+            Err(ToolError::ExecutionFailed(format!("MCP server {} did not announce an endpoint", url)))
+        }
+    }
+}
+
+/// Returns an event block's type (default `message`) and its data lines joined by newlines.
+fn parse_event(block: &str) -> (String, String) {
+    let mut event = "message".to_string();
+    let mut data = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    (event, data.join("\n"))
+}
+
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, ToolError> {
+    let mut map = HeaderMap::new();
+    for (key, value) in headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|e| ToolError::InvalidArguments(format!("invalid header {}: {}", key, e)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ToolError::InvalidArguments(format!("invalid value for header {}: {}", key, e)))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
@@ -0,0 +1,65 @@
+//! MCP over a child process's stdin and stdout, one JSON message per line.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use super::{dispatch, fail_pending, Outbound, Pending};
+use crate::ToolError;
+
+/// Launches the server and starts tasks that write requests and read responses.
+///
+/// The server's stderr is inherited, so its logs appear alongside ours.
+pub(super) fn spawn(
+    command: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    pending: Pending,
+) -> Result<(Outbound, Vec<JoinHandle<()>>, Child), ToolError> {
+    let mut child = Command::new(command)
+        .args(args)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ToolError::ExecutionFailed(format!("failed to start MCP server '{}': {}", command, e)))?;
+
+    let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(ToolError::ExecutionFailed("MCP server has no stdio".into()));
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let writer = tokio::spawn(async move {
+        while let Some(line) = rx.recv().await {
+            let written = async {
+                stdin.write_all(line.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+                stdin.flush().await
+            };
+            if written.await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let outbound = Outbound::Stdio(tx);
+    let replies = outbound.clone();
+    let reader = tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str(&line) {
+                Ok(message) => dispatch(&pending, &replies, message).await,
+                Err(_) => debug!("MCP server wrote a non-JSON line: {}", line),
+            }
+        }
+        fail_pending(&pending, "MCP server process has exited");
+    });
+
+    Ok((outbound, vec![writer, reader], child))
+}
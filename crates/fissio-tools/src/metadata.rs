@@ -0,0 +1,85 @@
+//! Namespaces, tags, and hints describing a tool beyond its schema.
+
+use serde::{Deserialize, Serialize};
+
+/// Rough cost of one call, shown in the editor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostHint {
+    /// Local work or a free API.
+    #[default]
+    Free,
+    /// A metered API, priced per call.
+    Low,
+    Medium,
+    /// An expensive API, or a call that runs an LLM.
+    High,
+}
+
+/// What the registry knows about a tool besides its schema.
+///
+/// Tools describe themselves with [`Tool::metadata`](crate::Tool::metadata);
+/// [`ToolRegistry::set_metadata`](crate::ToolRegistry::set_metadata) overrides it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolMetadata {
+    /// Group the tool belongs to, e.g. `web` or `fs`. A node selects all of
+    /// a namespace's tools with `web/*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Labels such as `network` or `dangerous`, for [`ToolRegistry::tools_with_tag`](crate::ToolRegistry::tools_with_tag).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub cost: CostHint,
+    /// Calls wait for a `ToolApprover`, as if the tool policy required approval.
+    #[serde(default)]
+    pub requires_approval: bool,
+    /// Calls per minute the tool's backend allows. Advisory; not enforced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_minute: Option<u32>,
+    /// Calls change something outside the run, such as sending an email, so
+    /// repeating one isn't safe. The engine runs each such call at most once
+    /// per run when given a side-effect ledger.
+    #[serde(default)]
+    pub side_effects: bool,
+}
+
+impl ToolMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_cost(mut self, cost: CostHint) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn with_requires_approval(mut self, required: bool) -> Self {
+        self.requires_approval = required;
+        self
+    }
+
+    pub fn with_rate_limit_per_minute(mut self, calls: u32) -> Self {
+        self.rate_limit_per_minute = Some(calls);
+        self
+    }
+
+    pub fn with_side_effects(mut self, side_effects: bool) -> Self {
+        self.side_effects = side_effects;
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
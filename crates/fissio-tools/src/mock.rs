@@ -0,0 +1,118 @@
+//! Declarative tool stubs for deterministic pipeline tests.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// Canned responses for one tool.
+///
+/// Deserializes from a plain string (always returned) or a list of
+/// [`MockResponse`]s tried in order:
+///
+/// ```json
+/// {"web_search": "Rust 1.0 was released in May 2015."}
+/// {"web_search": [{"when": {"query": "rust"}, "output": "..."}, {"error": "rate limited"}]}
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ToolMock {
+    Output(String),
+    Responses(Vec<MockResponse>),
+}
+
+/// A canned tool response, optionally restricted to matching arguments.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockResponse {
+    /// Only answer calls whose arguments contain all of these fields with equal values.
+    #[serde(default)]
+    pub when: Option<Map<String, Value>>,
+    /// Output returned to the model.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Error returned instead of output.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl MockResponse {
+    fn matches(&self, args: &Value) -> bool {
+        let Some(when) = &self.when else {
+            return true;
+        };
+        when.iter().all(|(key, expected)| args.get(key) == Some(expected))
+    }
+}
+
+/// A tool that answers from a [`ToolMock`] instead of running.
+///
+/// Keeps the name, description, and parameters of the tool it replaces so the
+/// model sees the same schema as in production.
+pub struct MockTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    metadata: ToolMetadata,
+    mock: ToolMock,
+}
+
+impl MockTool {
+    /// Creates a mock with a permissive schema, for tools not otherwise registered.
+    pub fn new(name: impl Into<String>, mock: ToolMock) -> Self {
+        let name = name.into();
+        Self {
+            description: format!("Mocked tool '{}'", name),
+            name,
+            parameters: json!({ "type": "object", "additionalProperties": true }),
+            metadata: ToolMetadata::default(),
+            mock,
+        }
+    }
+
+    /// Creates a mock that presents the same schema as `tool`.
+    pub fn replacing(tool: &dyn Tool, mock: ToolMock) -> Self {
+        Self {
+            name: tool.name().to_string(),
+            description: tool.description().to_string(),
+            parameters: tool.parameters(),
+            metadata: tool.metadata(),
+            mock,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for MockTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        self.metadata.clone()
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let responses = match &self.mock {
+            ToolMock::Output(output) => return Ok(output.clone()),
+            ToolMock::Responses(responses) => responses,
+        };
+        let response = responses.iter().find(|r| r.matches(&args)).ok_or_else(|| {
+            ToolError::ExecutionFailed(format!("no mock response for {} with arguments {}", self.name, args))
+        })?;
+
+        match (&response.error, &response.output) {
+            (Some(error), _) => Err(ToolError::ExecutionFailed(error.clone())),
+            (None, Some(output)) => Ok(output.clone()),
+            (None, None) => Ok(String::new()),
+        }
+    }
+}
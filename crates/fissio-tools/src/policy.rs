@@ -0,0 +1,104 @@
+//! Limits applied around every tool call in the agentic loop.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{Tool, ToolError};
+
+/// Timeout, output size, and error handling for tool calls.
+///
+/// Set a default with [`ToolRegistry::with_policy`](crate::ToolRegistry::with_policy);
+/// a node overrides it with a `tool_policy` object in its `config`:
+///
+/// ```json
+/// { "tool_policy": { "timeout_ms": 15000, "max_result_chars": 8000, "errors_as_results": true } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolExecutionPolicy {
+    /// Fails a call that runs longer than this many milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Truncates longer results by removing their middle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_result_chars: Option<usize>,
+    /// Returns failures to the LLM as the tool result instead of failing the node.
+    #[serde(default)]
+    pub errors_as_results: bool,
+}
+
+impl ToolExecutionPolicy {
+    /// Creates a policy with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the per-call timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Sets the maximum result length in characters.
+    pub fn with_max_result_chars(mut self, max: usize) -> Self {
+        self.max_result_chars = Some(max);
+        self
+    }
+
+    /// Returns tool errors to the LLM as results.
+    pub fn with_errors_as_results(mut self, enabled: bool) -> Self {
+        self.errors_as_results = enabled;
+        self
+    }
+
+    /// Runs the tool under this policy.
+    ///
+    /// With `errors_as_results`, a failure or timeout becomes an `Ok` result
+    /// starting with `Error:` so the LLM can retry or work around it.
+    pub async fn execute(&self, tool: &dyn Tool, args: serde_json::Value) -> Result<String, ToolError> {
+        let result = self.run(tool, args).await;
+        self.finish(result)
+    }
+
+    /// Runs the tool with this policy's timeout, leaving its result as is.
+    ///
+    /// Pass the result to [`finish`](Self::finish) after any processing of
+    /// the raw output.
+    pub async fn run(&self, tool: &dyn Tool, args: serde_json::Value) -> Result<String, ToolError> {
+        match self.timeout_ms {
+            Some(ms) => tokio::time::timeout(Duration::from_millis(ms), tool.execute(args))
+                .await
+                .unwrap_or(Err(ToolError::Timeout(ms))),
+            None => tool.execute(args).await,
+        }
+    }
+
+    /// Applies `errors_as_results` and `max_result_chars` to a call's result.
+    pub fn finish(&self, result: Result<String, ToolError>) -> Result<String, ToolError> {
+        let output = match result {
+            Ok(output) => output,
+            Err(e) if self.errors_as_results => format!("Error: {}", e),
+            Err(e) => return Err(e),
+        };
+
+        Ok(match self.max_result_chars {
+            Some(max) => truncate_middle(&output, max),
+            None => output,
+        })
+    }
+}
+
+/// Shortens `text` to about `max` characters by keeping its start and end.
+///
+/// The removed middle is replaced with a marker giving the number of characters
+/// dropped, so the LLM knows the result is incomplete.
+pub fn truncate_middle(text: &str, max: usize) -> String {
+    let total = text.chars().count();
+    if total <= max {
+        return text.to_string();
+    }
+    let head = max / 2;
+    let tail = max - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(total - tail).collect();
+    format!("{}\n[... {} chars truncated ...]\n{}", start, total - max, end)
+}
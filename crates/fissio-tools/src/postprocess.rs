@@ -0,0 +1,199 @@
+//! Steps applied to a tool's output before it goes back to the LLM.
+
+use std::fmt;
+use std::str::FromStr;
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ToolError;
+
+/// A post-processing step for a tool's output, configured per tool on a node:
+///
+/// ```json
+/// { "tool_post_process": { "fetch_url": "summarize", "get_issues": ["json-path:$..title"] } }
+/// ```
+///
+/// Steps run in order, before the node's `max_result_chars` truncation. A
+/// step that fails leaves the output as it was.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PostProcessor {
+    /// `summarize`: replaces the output with an LLM summary, made with the node's model.
+    Summarize,
+    /// `extract:<css selector>`: keeps the text of the matching HTML elements.
+    Extract(String),
+    /// `json-path:<path>`: keeps the JSON values matching the path, e.g. `$.items[*].name`.
+    JsonPath(String),
+}
+
+impl FromStr for PostProcessor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (s, None),
+        };
+        match (name, arg) {
+            ("summarize", None) => Ok(Self::Summarize),
+            ("extract", Some(selector)) if !selector.is_empty() => {
+                Selector::parse(selector).map_err(|e| format!("invalid selector '{}': {}", selector, e))?;
+                Ok(Self::Extract(selector.to_string()))
+            }
+            ("json-path", Some(path)) if !path.is_empty() => {
+                parse_path(path)?;
+                Ok(Self::JsonPath(path.to_string()))
+            }
+            _ => Err(format!(
+                "unknown post-processor '{}' (expected summarize, extract:<selector>, or json-path:<path>)",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for PostProcessor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for PostProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Summarize => f.write_str("summarize"),
+            Self::Extract(selector) => write!(f, "extract:{}", selector),
+            Self::JsonPath(path) => write!(f, "json-path:{}", path),
+        }
+    }
+}
+
+impl From<PostProcessor> for String {
+    fn from(p: PostProcessor) -> Self {
+        p.to_string()
+    }
+}
+
+/// Returns the text of the elements in `html` matching `selector`, one block per element.
+pub fn extract_html(html: &str, selector: &str) -> Result<String, ToolError> {
+    let selector = Selector::parse(selector)
+        .map_err(|e| ToolError::InvalidArguments(format!("invalid selector '{}': {}", selector, e)))?;
+    let document = Html::parse_document(html);
+    let blocks: Vec<String> = document
+        .select(&selector)
+        .map(|el| el.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .collect();
+    if blocks.is_empty() {
+        return Err(ToolError::ExecutionFailed("selector matched no elements".to_string()));
+    }
+    Ok(blocks.join("\n\n"))
+}
+
+/// Returns the values in the JSON document `json` matching `path`.
+///
+/// Supports `$`, `.key`, `['key']`, `[index]` (negative counts from the end),
+/// `*`, and `..` for recursive descent. A single string match is returned
+/// as-is; anything else as JSON.
+pub fn select_json(json: &str, path: &str) -> Result<String, ToolError> {
+    let segments = parse_path(path).map_err(ToolError::InvalidArguments)?;
+    let document: Value = serde_json::from_str(json)
+        .map_err(|e| ToolError::ExecutionFailed(format!("output is not JSON: {}", e)))?;
+
+    let mut matches = vec![&document];
+    for segment in &segments {
+        matches = matches.into_iter().flat_map(|value| segment.select(value)).collect();
+    }
+
+    let rendered = match matches.as_slice() {
+        [] => return Err(ToolError::ExecutionFailed(format!("'{}' matched nothing", path))),
+        [Value::String(s)] => return Ok(s.clone()),
+        [value] => serde_json::to_string_pretty(value),
+        values => serde_json::to_string_pretty(values),
+    };
+    rendered.map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize matches: {}", e)))
+}
+
+/// One step of a JSON path.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    /// The value and all its descendants; `..` before the next step.
+    Descendants,
+}
+
+impl Segment {
+    fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match (self, value) {
+            (Self::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+            (Self::Index(i), Value::Array(items)) => {
+                let index = if *i < 0 { items.len() as i64 + i } else { *i };
+                usize::try_from(index).ok().and_then(|i| items.get(i)).into_iter().collect()
+            }
+            (Self::Wildcard, Value::Object(map)) => map.values().collect(),
+            (Self::Wildcard, Value::Array(items)) => items.iter().collect(),
+            (Self::Descendants, _) => {
+                let mut all = vec![value];
+                collect_descendants(value, &mut all);
+                all
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn collect_descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    let children: Box<dyn Iterator<Item = &'a Value>> = match value {
+        Value::Object(map) => Box::new(map.values()),
+        Value::Array(items) => Box::new(items.iter()),
+        _ => return,
+    };
+    for child in children {
+        out.push(child);
+        collect_descendants(child, out);
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = |reason: &str| format!("invalid JSON path '{}': {}", path, reason);
+    let mut rest = path.trim().strip_prefix('$').ok_or_else(|| invalid("must start with $"))?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            segments.push(Segment::Descendants);
+            // `..key` and `..*` name the next step without a dot
+            rest = if after.starts_with('[') { after } else { &rest[1..] };
+        }
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return Err(invalid("empty key"));
+            }
+            segments.push(if key == "*" { Segment::Wildcard } else { Segment::Key(key.to_string()) });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed ["))?;
+            let inner = after[..end].trim();
+            segments.push(match inner {
+                "*" => Segment::Wildcard,
+                _ if inner.len() >= 2 && (inner.starts_with('\'') || inner.starts_with('"')) => {
+                    Segment::Key(inner[1..inner.len() - 1].to_string())
+                }
+                _ => Segment::Index(inner.parse().map_err(|_| invalid("expected an index, *, or a quoted key in []"))?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid("expected . or ["));
+        }
+    }
+    Ok(segments)
+}
@@ -0,0 +1,301 @@
+//! Opt-in tool that runs Python snippets in a resource-limited subprocess.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::command::read_capped;
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// How long a snippet may run when the tool doesn't set a timeout.
+pub const DEFAULT_PYTHON_TIMEOUT: Duration = Duration::from_secs(30);
+/// Address space a snippet may use when the tool doesn't set a limit.
+pub const DEFAULT_PYTHON_MEMORY_BYTES: u64 = 512 * 1024 * 1024;
+/// Bytes kept from each of stdout and stderr.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+/// Largest file a snippet may write.
+const MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+/// Environment variables every snippet receives, when set on the server.
+const DEFAULT_ENV: [&str; 5] = ["PATH", "HOME", "LANG", "LC_ALL", "TMPDIR"];
+
+/// Applies the limits, then runs the snippet read from stdin as `__main__`.
+/// `resource` only exists on Unix; elsewhere only the timeout applies.
+const LAUNCHER: &str = r#"
+import sys
+try:
+    import resource
+except ImportError:
+    resource = None
+if resource is not None:
+    def limit(kind, value):
+        hard = resource.getrlimit(kind)[1]
+        if hard != resource.RLIM_INFINITY:
+            value = min(value, hard)
+        resource.setrlimit(kind, (value, value))
+    memory, cpu, fsize = (int(v) for v in sys.argv[1:4])
+    limit(resource.RLIMIT_AS, memory)
+    limit(resource.RLIMIT_CPU, cpu)
+    limit(resource.RLIMIT_FSIZE, fsize)
+del sys.argv[1:4]
+source = sys.stdin.read()
+exec(compile(source, "<snippet>", "exec"), {"__name__": "__main__"})
+"#;
+
+/// The `python_exec` tool: runs a Python snippet and returns its output.
+///
+/// Each call starts a fresh interpreter in isolated mode (`-I`) in the tool's
+/// working directory, with a cleared environment plus `PATH`, `HOME`, locale,
+/// and `TMPDIR`. Memory, CPU time, and file size are capped with rlimits, and
+/// the process is killed at the timeout. Files the snippet creates stay in the
+/// working directory and are listed in the result, so charts saved with
+/// matplotlib (`MPLBACKEND=Agg` is set) can be picked up by later steps.
+///
+/// This limits resources; it is not an isolation boundary. A snippet can read
+/// anything the server user can and open network connections, so only enable
+/// it where that is acceptable, or run the server in a container.
+pub struct PythonTool {
+    work_dir: PathBuf,
+    interpreter: String,
+    env: Vec<String>,
+    timeout: Duration,
+    memory_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct PythonOutput {
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    truncated: bool,
+    /// Files in the working directory created or changed by the snippet.
+    files: Vec<String>,
+}
+
+impl PythonTool {
+    /// Creates a tool that runs snippets in `work_dir` with `python3`.
+    pub fn new(work_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            work_dir: work_dir.into(),
+            interpreter: "python3".to_string(),
+            env: DEFAULT_ENV.iter().map(|s| s.to_string()).collect(),
+            timeout: DEFAULT_PYTHON_TIMEOUT,
+            memory_bytes: DEFAULT_PYTHON_MEMORY_BYTES,
+        }
+    }
+
+    /// Runs snippets with another interpreter, such as a virtualenv's
+    /// `bin/python` with pandas installed.
+    pub fn with_interpreter(mut self, interpreter: impl Into<String>) -> Self {
+        self.interpreter = interpreter.into();
+        self
+    }
+
+    /// Passes an environment variable through to snippets.
+    pub fn pass_env(mut self, name: impl Into<String>) -> Self {
+        self.env.push(name.into());
+        self
+    }
+
+    /// Kills snippets that run longer than `timeout`; CPU time is capped to match.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Caps the interpreter's address space at `bytes`.
+    pub fn with_memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_bytes = bytes;
+        self
+    }
+
+    /// Modification times of the files directly in the working directory.
+    async fn snapshot(&self) -> HashMap<String, SystemTime> {
+        let mut files = HashMap::new();
+        let Ok(mut dir) = tokio::fs::read_dir(&self.work_dir).await else {
+            return files;
+        };
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            let Ok(metadata) = entry.metadata().await else { continue };
+            if metadata.is_file() {
+                if let Ok(modified) = metadata.modified() {
+                    files.insert(entry.file_name().to_string_lossy().into_owned(), modified);
+                }
+            }
+        }
+        files
+    }
+}
+
+#[async_trait]
+impl Tool for PythonTool {
+    fn name(&self) -> &str {
+        "python_exec"
+    }
+
+    fn description(&self) -> &str {
+        "Run a Python 3 snippet and return its stdout, stderr, and the files it wrote. Print the results you \
+        need; save charts and data to files in the current directory."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "Python source to run"
+                }
+            },
+            "required": ["code"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("system").with_tag("dangerous")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let code = args
+            .get("code")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'code' parameter".to_string()))?;
+
+        tokio::fs::create_dir_all(&self.work_dir).await.map_err(|e| {
+            ToolError::ExecutionFailed(format!("working directory {} is unavailable: {}", self.work_dir.display(), e))
+        })?;
+        let before = self.snapshot().await;
+
+        let cpu_secs = self.timeout.as_secs().max(1);
+        let mut process = Command::new(&self.interpreter);
+        process
+            .args(["-I", "-c", LAUNCHER])
+            .args([self.memory_bytes.to_string(), cpu_secs.to_string(), MAX_FILE_BYTES.to_string()])
+            .current_dir(&self.work_dir)
+            .env_clear()
+            .envs(self.env.iter().filter_map(|name| std::env::var(name).ok().map(|value| (name, value))))
+            .env("MPLBACKEND", "Agg")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = process
+            .spawn()
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to start {}: {}", self.interpreter, e)))?;
+
+        let (Some(mut stdin), Some(stdout), Some(stderr)) = (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(ToolError::ExecutionFailed("interpreter I/O was not captured".into()));
+        };
+        let source = code.to_string();
+        let run = async {
+            let write = async move {
+                // Closing stdin marks the end of the snippet
+                let _ = stdin.write_all(source.as_bytes()).await;
+            };
+            let (_, stdout, stderr, status) = tokio::join!(
+                write,
+                read_capped(stdout, MAX_OUTPUT_BYTES),
+                read_capped(stderr, MAX_OUTPUT_BYTES),
+                child.wait()
+            );
+            (stdout, stderr, status)
+        };
+        let (stdout, stderr, status) = match tokio::time::timeout(self.timeout, run).await {
+            Ok(finished) => finished,
+            Err(_) => {
+                let _ = child.kill().await;
+                return Err(ToolError::Timeout(self.timeout.as_millis() as u64));
+            }
+        };
+        let failed = |e: std::io::Error| ToolError::ExecutionFailed(format!("python_exec failed: {}", e));
+        let (stdout, stdout_cut) = stdout.map_err(failed)?;
+        let (stderr, stderr_cut) = stderr.map_err(failed)?;
+        let status = status.map_err(failed)?;
+
+        let mut files: Vec<String> = self
+            .snapshot()
+            .await
+            .into_iter()
+            .filter(|(name, modified)| before.get(name) != Some(modified))
+            .map(|(name, _)| name)
+            .collect();
+        files.sort();
+
+        let output = PythonOutput {
+            exit_code: status.code(),
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            truncated: stdout_cut || stderr_cut,
+            files,
+        };
+        serde_json::to_string_pretty(&output)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tool with its own working directory, or `None` where there's no `python3`.
+    fn python(name: &str) -> Option<PythonTool> {
+        std::process::Command::new("python3").arg("--version").output().ok()?;
+        let dir = std::env::temp_dir().join(format!("fissio-python-{}-{}", name, std::process::id()));
+        Some(PythonTool::new(dir))
+    }
+
+    async fn run(tool: &PythonTool, code: &str) -> Value {
+        serde_json::from_str(&tool.execute(json!({ "code": code })).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_runs_snippet_and_lists_files() {
+        let Some(tool) = python("files") else { return };
+        let output = run(&tool, "open('out.txt', 'w').write('x')\nprint(6 * 7)").await;
+        assert_eq!(output["exit_code"], 0);
+        assert_eq!(output["stdout"], "42\n");
+        assert_eq!(output["files"], json!(["out.txt"]));
+        std::fs::remove_dir_all(&tool.work_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_kills_snippet_at_timeout() {
+        let Some(tool) = python("timeout") else { return };
+        let tool = tool.with_timeout(Duration::from_millis(300));
+        let result = tool.execute(json!({ "code": "import time\ntime.sleep(10)" })).await;
+        assert!(matches!(result, Err(ToolError::Timeout(300))));
+        std::fs::remove_dir_all(&tool.work_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_caps_output() {
+        let Some(tool) = python("output") else { return };
+        let output = run(&tool, "print('x' * 200000)").await;
+        assert_eq!(output["stdout"].as_str().unwrap().len(), MAX_OUTPUT_BYTES);
+        assert_eq!(output["truncated"], true);
+        std::fs::remove_dir_all(&tool.work_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_caps_memory() {
+        let Some(tool) = python("memory") else { return };
+        let tool = tool.with_memory_limit(256 * 1024 * 1024);
+        let output = run(&tool, "data = bytearray(1024 * 1024 * 1024)").await;
+        assert_ne!(output["exit_code"], 0);
+        assert!(output["stderr"].as_str().unwrap().contains("MemoryError"));
+        std::fs::remove_dir_all(&tool.work_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_code_is_invalid() {
+        let tool = PythonTool::new(std::env::temp_dir());
+        assert!(matches!(tool.execute(json!({})).await, Err(ToolError::InvalidArguments(_))));
+    }
+}
@@ -0,0 +1,396 @@
+//! Read-only SQL queries against SQLite or Postgres (`sql` feature).
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// Rows a query returns when the tool doesn't set a limit.
+pub const DEFAULT_SQL_MAX_ROWS: usize = 100;
+/// Bytes of JSON row data a query returns when the tool doesn't set a limit.
+pub const DEFAULT_SQL_MAX_BYTES: usize = 64 * 1024;
+
+/// A database [`SqlQueryTool`] queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlConnection {
+    /// A SQLite database file, opened read-only.
+    Sqlite(PathBuf),
+    /// A Postgres connection string; queries run in read-only transactions.
+    Postgres(String),
+}
+
+impl SqlConnection {
+    /// Parses `postgres://…` / `postgresql://…` URLs and `sqlite://path` or
+    /// `sqlite:path`; anything else is taken as a SQLite file path.
+    pub fn from_url(url: &str) -> Self {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            return Self::Postgres(url.to_string());
+        }
+        let path = url.strip_prefix("sqlite://").or_else(|| url.strip_prefix("sqlite:")).unwrap_or(url);
+        Self::Sqlite(PathBuf::from(path))
+    }
+}
+
+/// The `sql_query` tool: runs one parameterized, read-only query and returns
+/// its rows as JSON.
+///
+/// Placeholders are `?1`, `?2`, … for SQLite and `$1`, `$2`, … for Postgres,
+/// bound from the `params` array. SQLite databases are opened read-only;
+/// Postgres queries run in a `READ ONLY` transaction, so writes fail either
+/// way, and a query holding more than one statement is refused. Results stop at the row and byte limits, with `truncated` set.
+///
+/// Postgres parameters bind to booleans, integers, floats, `json`/`jsonb`, and
+/// text; cast other types in SQL (e.g. `$1::text::date`).
+pub struct SqlQueryTool {
+    connection: SqlConnection,
+    name: String,
+    description: String,
+    max_rows: usize,
+    max_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct QueryResult {
+    rows: Vec<Map<String, Value>>,
+    truncated: bool,
+}
+
+impl SqlQueryTool {
+    pub fn new(connection: SqlConnection) -> Self {
+        let dialect = match connection {
+            SqlConnection::Sqlite(_) => "SQLite",
+            SqlConnection::Postgres(_) => "Postgres",
+        };
+        Self {
+            connection,
+            name: "sql_query".to_string(),
+            description: format!(
+                "Run a read-only {} query and return the rows as JSON. Pass values through params instead of \
+                writing them into the query.",
+                dialect
+            ),
+            max_rows: DEFAULT_SQL_MAX_ROWS,
+            max_bytes: DEFAULT_SQL_MAX_BYTES,
+        }
+    }
+
+    /// Renames the tool, e.g. to offer several databases.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Replaces the description; describing the schema helps the LLM write queries.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = rows;
+        self
+    }
+
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Keeps rows until either limit is reached. `rows` may hold one row past
+    /// `max_rows`, fetched only to tell whether there were more.
+    fn capped(&self, rows: Vec<Map<String, Value>>) -> QueryResult {
+        let mut truncated = rows.len() > self.max_rows;
+        let mut bytes = 0;
+        let mut kept = Vec::new();
+        for row in rows.into_iter().take(self.max_rows) {
+            bytes += Value::Object(row.clone()).to_string().len();
+            if bytes > self.max_bytes {
+                truncated = true;
+                break;
+            }
+            kept.push(row);
+        }
+        QueryResult { rows: kept, truncated }
+    }
+}
+
+#[async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        let placeholder = match self.connection {
+            SqlConnection::Sqlite(_) => "?1, ?2, …",
+            SqlConnection::Postgres(_) => "$1, $2, …",
+        };
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": format!("A single SELECT statement, using {} for values", placeholder)
+                },
+                "params": {
+                    "type": "array",
+                    "description": "Values for the query's placeholders, in order",
+                    "items": {}
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("data").with_tag("network")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'query' parameter".to_string()))?
+            .trim()
+            .trim_end_matches(';')
+            .to_string();
+        let params = match args.get("params") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(values)) => values.clone(),
+            Some(_) => return Err(ToolError::InvalidArguments("'params' must be an array".into())),
+        };
+
+        // One more row than the limit shows whether the result was cut
+        let fetch = self.max_rows + 1;
+        let rows = match &self.connection {
+            SqlConnection::Sqlite(path) => {
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || sqlite::query(&path, &query, &params, fetch))
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("query panicked: {}", e)))??
+            }
+            SqlConnection::Postgres(url) => postgres::query(url, &query, &params, fetch).await?,
+        };
+
+        serde_json::to_string(&self.capped(rows))
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to serialize response: {}", e)))
+    }
+}
+
+fn query_failed(e: impl std::fmt::Display) -> ToolError {
+    ToolError::ExecutionFailed(format!("query failed: {}", e))
+}
+
+mod sqlite {
+    use rusqlite::types::{Value as SqlValue, ValueRef};
+    use rusqlite::{params_from_iter, Batch, Connection, OpenFlags};
+    use serde_json::{Map, Value};
+    use std::path::Path;
+
+    use super::query_failed;
+    use crate::ToolError;
+
+    pub(super) fn query(path: &Path, query: &str, params: &[Value], limit: usize) -> Result<Vec<Map<String, Value>>, ToolError> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(path, flags)
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to open {}: {}", path.display(), e)))?;
+        conn.pragma_update(None, "query_only", true).map_err(query_failed)?;
+
+        // `prepare` would silently drop whatever follows the first statement
+        let mut statements = Batch::new(&conn, query);
+        let mut statement = statements
+            .next()
+            .map_err(query_failed)?
+            .ok_or_else(|| ToolError::InvalidArguments("query is empty".into()))?;
+        if statements.next().map_err(query_failed)?.is_some() {
+            return Err(ToolError::InvalidArguments("query must be a single statement".into()));
+        }
+        let columns: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+        let mut rows = statement.query(params_from_iter(params.iter().map(to_sql))).map_err(query_failed)?;
+
+        let mut out = Vec::new();
+        while out.len() < limit {
+            let Some(row) = rows.next().map_err(query_failed)? else { break };
+            let mut object = Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                let value = row.get_ref(i).map_err(query_failed)?;
+                object.insert(column.clone(), to_json(value));
+            }
+            out.push(object);
+        }
+        Ok(out)
+    }
+
+    fn to_sql(value: &Value) -> SqlValue {
+        match value {
+            Value::Null => SqlValue::Null,
+            Value::Bool(b) => SqlValue::Integer(*b as i64),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => SqlValue::Integer(i),
+                None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+            },
+            Value::String(s) => SqlValue::Text(s.clone()),
+            other => SqlValue::Text(other.to_string()),
+        }
+    }
+
+    fn to_json(value: ValueRef<'_>) -> Value {
+        match value {
+            ValueRef::Null => Value::Null,
+            ValueRef::Integer(i) => Value::from(i),
+            ValueRef::Real(f) => Value::from(f),
+            ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+            ValueRef::Blob(blob) => Value::String(format!("<{} byte blob>", blob.len())),
+        }
+    }
+}
+
+mod postgres {
+    use serde_json::{Map, Value};
+    use tokio_postgres::types::{ToSql, Type};
+    use tokio_postgres::NoTls;
+
+    use super::query_failed;
+    use crate::ToolError;
+
+    type Param = Box<dyn ToSql + Sync + Send>;
+
+    pub(super) async fn query(url: &str, query: &str, params: &[Value], limit: usize) -> Result<Vec<Map<String, Value>>, ToolError> {
+        let (mut client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to connect to Postgres: {}", e)))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::warn!("Postgres connection error: {}", e);
+            }
+        });
+
+        let transaction = client.build_transaction().read_only(true).start().await.map_err(query_failed)?;
+        // Postgres turns each row into JSON, so every column type comes back intact
+        let wrapped = format!("SELECT row_to_json(q)::text FROM ({}) q LIMIT {}", query, limit);
+        let statement = transaction.prepare(&wrapped).await.map_err(query_failed)?;
+        if statement.params().len() != params.len() {
+            return Err(ToolError::InvalidArguments(format!(
+                "query has {} placeholders but {} params were given",
+                statement.params().len(),
+                params.len()
+            )));
+        }
+        let bound: Vec<Param> = statement
+            .params()
+            .iter()
+            .zip(params)
+            .enumerate()
+            .map(|(i, (ty, value))| to_sql(i + 1, ty, value))
+            .collect::<Result<_, _>>()?;
+        let refs: Vec<&(dyn ToSql + Sync)> = bound.iter().map(|p| p.as_ref() as &(dyn ToSql + Sync)).collect();
+
+        let rows = transaction.query(&statement, &refs).await.map_err(query_failed)?;
+        transaction.rollback().await.map_err(query_failed)?;
+
+        rows.iter()
+            .map(|row| {
+                let text: String = row.try_get(0).map_err(query_failed)?;
+                match serde_json::from_str(&text).map_err(query_failed)? {
+                    Value::Object(object) => Ok(object),
+                    other => Err(query_failed(format!("unexpected row {}", other))),
+                }
+            })
+            .collect()
+    }
+
+    /// Converts a JSON value to the parameter type Postgres inferred.
+    fn to_sql(position: usize, ty: &Type, value: &Value) -> Result<Param, ToolError> {
+        let mismatch = || ToolError::InvalidArguments(format!("parameter ${} expects {}, got {}", position, ty, value));
+        let null = value.is_null();
+        let param: Param = if *ty == Type::BOOL {
+            Box::new(if null { None } else { Some(value.as_bool().ok_or_else(mismatch)?) })
+        } else if *ty == Type::INT2 {
+            Box::new(if null { None } else { Some(value.as_i64().and_then(|n| i16::try_from(n).ok()).ok_or_else(mismatch)?) })
+        } else if *ty == Type::INT4 {
+            Box::new(if null { None } else { Some(value.as_i64().and_then(|n| i32::try_from(n).ok()).ok_or_else(mismatch)?) })
+        } else if *ty == Type::INT8 {
+            Box::new(if null { None } else { Some(value.as_i64().ok_or_else(mismatch)?) })
+        } else if *ty == Type::FLOAT4 {
+            Box::new(if null { None } else { Some(value.as_f64().ok_or_else(mismatch)? as f32) })
+        } else if *ty == Type::FLOAT8 {
+            Box::new(if null { None } else { Some(value.as_f64().ok_or_else(mismatch)?) })
+        } else if *ty == Type::JSON || *ty == Type::JSONB {
+            Box::new(if null { None } else { Some(value.clone()) })
+        } else {
+            let text = match value {
+                Value::Null => None,
+                Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            };
+            Box::new(text)
+        };
+        Ok(param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A SQLite database with a `users` table of two rows.
+    fn database(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("fissio-sql-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE users (id INTEGER, name TEXT); INSERT INTO users VALUES (1, 'ada'), (2, 'grace');")
+            .unwrap();
+        path
+    }
+
+    fn user_count(path: &PathBuf) -> i64 {
+        let conn = rusqlite::Connection::open(path).unwrap();
+        conn.query_row("SELECT count(*) FROM users", [], |row| row.get(0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_select_with_params_and_row_limit() {
+        let path = database("select");
+        let tool = SqlQueryTool::new(SqlConnection::Sqlite(path.clone()));
+        let result = tool.execute(json!({ "query": "SELECT name FROM users WHERE id = ?1;", "params": [2] })).await.unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&result).unwrap(), json!({ "rows": [{ "name": "grace" }], "truncated": false }));
+
+        let tool = tool.with_max_rows(1);
+        let result: Value = serde_json::from_str(&tool.execute(json!({ "query": "SELECT * FROM users" })).await.unwrap()).unwrap();
+        assert_eq!((result["rows"].as_array().unwrap().len(), result["truncated"].as_bool()), (1, Some(true)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_writes() {
+        let path = database("writes");
+        let tool = SqlQueryTool::new(SqlConnection::Sqlite(path.clone()));
+        for query in [
+            "INSERT INTO users VALUES (3, 'linus')",
+            "DELETE FROM users",
+            "DROP TABLE users",
+            "SELECT 1; DROP TABLE users",
+            "SELECT 1; INSERT INTO users VALUES (3, 'linus')",
+        ] {
+            assert!(tool.execute(json!({ "query": query })).await.is_err(), "{} was not refused", query);
+        }
+        assert_eq!(user_count(&path), 2);
+
+        let stacked = tool.execute(json!({ "query": "SELECT 1; SELECT 2" })).await;
+        assert!(matches!(stacked, Err(ToolError::InvalidArguments(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_connection_from_url() {
+        assert_eq!(SqlConnection::from_url("postgres://db/app"), SqlConnection::Postgres("postgres://db/app".into()));
+        assert_eq!(SqlConnection::from_url("sqlite://data/app.db"), SqlConnection::Sqlite("data/app.db".into()));
+        assert_eq!(SqlConnection::from_url("data/app.db"), SqlConnection::Sqlite("data/app.db".into()));
+    }
+}
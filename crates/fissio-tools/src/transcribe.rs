@@ -0,0 +1,208 @@
+//! Speech-to-text: the `transcribe_audio` tool and the [`Transcriber`] behind it.
+//!
+//! Audio is sent to OpenAI's transcription API (Whisper) or to a whisper.cpp
+//! server, which keeps recordings on the machine.
+
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{CostHint, FsRoot, Tool, ToolError, ToolMetadata};
+
+const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
+
+/// Transcription model used with OpenAI when none is configured.
+pub const DEFAULT_TRANSCRIPTION_MODEL: &str = "whisper-1";
+
+/// Largest recording accepted, matching OpenAI's upload limit.
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+/// Where recordings are transcribed.
+#[derive(Debug, Clone)]
+pub enum TranscriptionBackend {
+    /// OpenAI's `/audio/transcriptions`, or a compatible endpoint at `api_base`.
+    OpenAi { api_base: String, api_key: String, model: String },
+    /// A whisper.cpp server's `/inference` endpoint at `url`.
+    WhisperCpp { url: String },
+}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Turns recordings into text through a [`TranscriptionBackend`].
+#[derive(Debug, Clone)]
+pub struct Transcriber {
+    backend: TranscriptionBackend,
+    client: reqwest::Client,
+}
+
+impl Transcriber {
+    pub fn new(backend: TranscriptionBackend) -> Self {
+        Self { backend, client: reqwest::Client::new() }
+    }
+
+    /// Transcribes with OpenAI's [`DEFAULT_TRANSCRIPTION_MODEL`].
+    pub fn openai(api_key: impl Into<String>) -> Self {
+        Self::new(TranscriptionBackend::OpenAi {
+            api_base: OPENAI_API_BASE.to_string(),
+            api_key: api_key.into(),
+            model: DEFAULT_TRANSCRIPTION_MODEL.to_string(),
+        })
+    }
+
+    /// Transcribes with the whisper.cpp server at `url`, e.g. `http://localhost:8080`.
+    pub fn whisper_cpp(url: impl Into<String>) -> Self {
+        Self::new(TranscriptionBackend::WhisperCpp { url: url.into() })
+    }
+
+    /// Uses `WHISPER_CPP_URL` if set, else OpenAI with `OPENAI_API_KEY` and
+    /// `TRANSCRIPTION_MODEL`. Returns `None` when neither is configured.
+    pub fn from_env() -> Option<Self> {
+        if let Some(url) = std::env::var("WHISPER_CPP_URL").ok().filter(|u| !u.is_empty()) {
+            return Some(Self::whisper_cpp(url));
+        }
+        let api_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty())?;
+        let model = std::env::var("TRANSCRIPTION_MODEL").unwrap_or_else(|_| DEFAULT_TRANSCRIPTION_MODEL.into());
+        Some(Self::new(TranscriptionBackend::OpenAi { api_base: OPENAI_API_BASE.to_string(), api_key, model }))
+    }
+
+    pub fn backend(&self) -> &TranscriptionBackend {
+        &self.backend
+    }
+
+    /// Whether recordings stay on the machine, i.e. the backend is whisper.cpp.
+    pub fn is_local(&self) -> bool {
+        matches!(self.backend, TranscriptionBackend::WhisperCpp { .. })
+    }
+
+    /// Transcribes a recording. `file_name` tells the backend the audio format
+    /// by its extension; `language` is an ISO-639-1 code, detected when absent.
+    pub async fn transcribe(&self, audio: Vec<u8>, file_name: &str, language: Option<&str>) -> Result<String, ToolError> {
+        if audio.len() > MAX_AUDIO_BYTES {
+            return Err(ToolError::InvalidArguments(format!(
+                "audio is {} bytes; the limit is {}",
+                audio.len(),
+                MAX_AUDIO_BYTES
+            )));
+        }
+        let mut form = Form::new()
+            .part("file", Part::bytes(audio).file_name(file_name.to_string()))
+            .text("response_format", "json");
+        if let Some(language) = language {
+            form = form.text("language", language.to_string());
+        }
+
+        let request = match &self.backend {
+            TranscriptionBackend::OpenAi { api_base, api_key, model } => self
+                .client
+                .post(format!("{}/audio/transcriptions", api_base.trim_end_matches('/')))
+                .bearer_auth(api_key)
+                .multipart(form.text("model", model.clone())),
+            TranscriptionBackend::WhisperCpp { url } => self
+                .client
+                .post(format!("{}/inference", url.trim_end_matches('/')))
+                .multipart(form),
+        };
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ToolError::ExecutionFailed(format!("transcription failed ({}): {}", status, body)));
+        }
+        let transcript: TranscriptionResponse = response
+            .json()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("unreadable transcription response: {}", e)))?;
+        debug!("Transcribed {} to {} chars", file_name, transcript.text.len());
+        Ok(transcript.text.trim().to_string())
+    }
+}
+
+/// The `transcribe_audio` tool: transcribes a recording from a URL or from a
+/// file under the sandbox root.
+pub struct TranscribeAudioTool {
+    transcriber: Transcriber,
+    root: Option<FsRoot>,
+    client: reqwest::Client,
+}
+
+impl TranscribeAudioTool {
+    /// Creates a tool that accepts URLs only.
+    pub fn new(transcriber: Transcriber) -> Self {
+        Self { transcriber, root: None, client: reqwest::Client::new() }
+    }
+
+    /// Also accepts `path`s relative to `root`.
+    pub fn with_root(mut self, root: FsRoot) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Reads the recording named by the call, returning its bytes and file name.
+    async fn load(&self, args: &Value) -> Result<(Vec<u8>, String), ToolError> {
+        let arg = |name: &str| args.get(name).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+        match (arg("url"), arg("path")) {
+            (Some(url), _) => {
+                let response = self.client.get(url).send().await?.error_for_status()?;
+                let name = url.rsplit('/').next().filter(|n| n.contains('.')).unwrap_or("audio.mp3").to_string();
+                Ok((response.bytes().await?.to_vec(), name))
+            }
+            (None, Some(path)) => {
+                let root = self.root.as_ref().ok_or_else(|| {
+                    ToolError::InvalidArguments("reading files needs FISSIO_FS_ROOT; pass a url instead".into())
+                })?;
+                let full = root.resolve(path)?;
+                let bytes = tokio::fs::read(&full).await.map_err(|e| ToolError::ExecutionFailed(format!("{}: {}", path, e)))?;
+                let name = full.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.to_string());
+                Ok((bytes, name))
+            }
+            (None, None) => Err(ToolError::InvalidArguments("Missing 'url' or 'path' parameter".into())),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for TranscribeAudioTool {
+    fn name(&self) -> &str {
+        "transcribe_audio"
+    }
+
+    fn description(&self) -> &str {
+        "Transcribe speech in an audio file (mp3, wav, m4a, webm, ogg, flac) to text."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "URL of the recording"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Recording to read, relative to the working directory"
+                },
+                "language": {
+                    "type": "string",
+                    "description": "ISO-639-1 code of the spoken language, e.g. 'en'; detected when omitted"
+                }
+            }
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        let cost = if self.transcriber.is_local() { CostHint::Free } else { CostHint::Low };
+        ToolMetadata::new().with_namespace("audio").with_tag("network").with_cost(cost)
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let (audio, file_name) = self.load(&args).await?;
+        let language = args.get("language").and_then(|v| v.as_str());
+        self.transcriber.transcribe(audio, &file_name, language).await
+    }
+}
@@ -0,0 +1,340 @@
+//! Tools with typed arguments: the parameter schema is generated from a Rust
+//! struct with `#[derive(ToolArgs)]`, and the call's JSON arguments are
+//! deserialized into it before the tool runs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// JSON Schema of an argument type.
+///
+/// Implemented for strings, numbers, booleans, `Option`, `Vec`, string-keyed
+/// maps, and `serde_json::Value`; `#[derive(ToolArgs)]` implements it for a
+/// struct, so structs nest. Implement it by hand for other types, such as an
+/// enum serialized as a string.
+pub trait ArgSchema {
+    /// The property may be left out of the arguments.
+    const OPTIONAL: bool = false;
+
+    fn arg_schema() -> Value;
+}
+
+/// A tool's arguments as a struct. Derive it together with `Deserialize`:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, ToolArgs)]
+/// struct WeatherArgs {
+///     /// City name, e.g. "Lisbon"
+///     city: String,
+///     /// Days to forecast; defaults to 1
+///     days: Option<u8>,
+/// }
+/// ```
+pub trait ToolArgs: ArgSchema + DeserializeOwned {
+    /// The schema to return from [`Tool::parameters`].
+    fn parameters() -> Value {
+        Self::arg_schema()
+    }
+
+    /// Deserializes a call's arguments.
+    fn parse(args: Value) -> Result<Self, ToolError> {
+        serde_json::from_value(args).map_err(|e| ToolError::InvalidArguments(e.to_string()))
+    }
+}
+
+macro_rules! arg_schema {
+    ($schema:tt => $($ty:ty),+) => {
+        $(impl ArgSchema for $ty {
+            fn arg_schema() -> Value {
+                json!($schema)
+            }
+        })+
+    };
+}
+
+arg_schema!({ "type": "string" } => String, char);
+arg_schema!({ "type": "boolean" } => bool);
+arg_schema!({ "type": "integer" } => i8, i16, i32, i64, isize);
+arg_schema!({ "type": "integer", "minimum": 0 } => u8, u16, u32, u64, usize);
+arg_schema!({ "type": "number" } => f32, f64);
+arg_schema!({} => Value);
+
+impl<T: ArgSchema> ArgSchema for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn arg_schema() -> Value {
+        T::arg_schema()
+    }
+}
+
+impl<T: ArgSchema> ArgSchema for Vec<T> {
+    fn arg_schema() -> Value {
+        json!({ "type": "array", "items": T::arg_schema() })
+    }
+}
+
+impl<T: ArgSchema> ArgSchema for HashMap<String, T> {
+    fn arg_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::arg_schema() })
+    }
+}
+
+impl<T: ArgSchema> ArgSchema for BTreeMap<String, T> {
+    fn arg_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::arg_schema() })
+    }
+}
+
+/// A tool made from an async function of its typed arguments, usually
+/// through [`typed_tool!`](crate::typed_tool).
+pub struct FnTool<A, F> {
+    name: String,
+    description: String,
+    metadata: ToolMetadata,
+    f: F,
+    args: PhantomData<fn(A)>,
+}
+
+impl<A, F, Fut> FnTool<A, F>
+where
+    A: ToolArgs,
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = Result<String, ToolError>>,
+{
+    pub fn new(name: impl Into<String>, description: impl Into<String>, f: F) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            metadata: ToolMetadata::default(),
+            f,
+            args: PhantomData,
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: ToolMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+#[async_trait]
+impl<A, F, Fut> Tool for FnTool<A, F>
+where
+    A: ToolArgs + Send,
+    F: Fn(A) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, ToolError>> + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        A::parameters()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        self.metadata.clone()
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        (self.f)(A::parse(args)?).await
+    }
+}
+
+/// Builds a [`FnTool`] from a name, a description, and a closure over typed
+/// arguments whose body is async:
+///
+/// ```rust,ignore
+/// let tool = typed_tool!("weather", "Forecast for a city", |args: WeatherArgs| {
+///     let forecast = fetch_forecast(&args.city, args.days.unwrap_or(1)).await?;
+///     Ok(forecast.summary)
+/// });
+/// registry.register(tool);
+/// ```
+///
+/// The body runs in an `async move` block, so state it uses must be cheap to
+/// clone per call; for an `Arc`, use [`FnTool::new`] and clone it in the closure.
+#[macro_export]
+macro_rules! typed_tool {
+    ($name:expr, $description:expr, |$args:ident : $ty:ty| $body:expr $(,)?) => {
+        $crate::FnTool::new($name, $description, |$args: $ty| async move {
+            let result: ::core::result::Result<::std::string::String, $crate::ToolError> = $body;
+            result
+        })
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::Value;
+
+    /// One property of a derived struct's schema.
+    pub struct ArgField {
+        pub name: &'static str,
+        pub description: Option<&'static str>,
+        pub schema: Value,
+        pub required: bool,
+        /// `#[serde(flatten)]`: the field's own properties belong to the struct.
+        pub flatten: bool,
+    }
+
+    pub fn object_schema(fields: Vec<ArgField>, deny_unknown_fields: bool) -> Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        let mut additional = None;
+        for field in fields {
+            let mut schema = field.schema;
+            if field.flatten {
+                if let Some(Value::Object(inner)) = schema.get_mut("properties").map(Value::take) {
+                    properties.extend(inner);
+                }
+                if let (true, Some(Value::Array(inner))) = (field.required, schema.get_mut("required").map(Value::take)) {
+                    required.extend(inner);
+                }
+                // A flattened map takes the properties no other field names
+                additional = schema.get_mut("additionalProperties").map(Value::take).or(additional);
+                continue;
+            }
+            if let (Some(description), Some(object)) = (field.description, schema.as_object_mut()) {
+                object.insert("description".into(), description.into());
+            }
+            if field.required {
+                required.push(Value::from(field.name));
+            }
+            properties.insert(field.name.to_string(), schema);
+        }
+        let mut schema = serde_json::json!({ "type": "object", "properties": properties, "required": required });
+        if let Some(additional) = additional.or(deny_unknown_fields.then_some(Value::Bool(false))) {
+            schema["additionalProperties"] = additional;
+        }
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, crate::ToolArgs)]
+    #[tool_args(crate = "crate")]
+    struct Forecast {
+        /// City name,
+        /// e.g. "Lisbon"
+        city: String,
+        /// Days to forecast
+        days: Option<u8>,
+        #[serde(default)]
+        units: String,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+        #[serde(skip)]
+        cached: bool,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, crate::ToolArgs)]
+    #[tool_args(crate = "crate")]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    struct Search {
+        query_text: String,
+        max_results: Option<u32>,
+    }
+
+    #[derive(Debug, Default, Deserialize, crate::ToolArgs)]
+    #[tool_args(crate = "crate")]
+    #[serde(default)]
+    struct Paging {
+        page: u32,
+        per_page: u32,
+    }
+
+    #[derive(Debug, Deserialize, crate::ToolArgs)]
+    #[tool_args(crate = "crate")]
+    struct Listing {
+        collection: String,
+        #[serde(flatten)]
+        paging: Paging,
+    }
+
+    #[test]
+    fn test_derived_schema() {
+        assert_eq!(
+            Forecast::parameters(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string", "description": "City name, e.g. \"Lisbon\"" },
+                    "days": { "type": "integer", "minimum": 0, "description": "Days to forecast" },
+                    "units": { "type": "string" },
+                    "type": { "type": "string" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                },
+                "required": ["city", "tags"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = Forecast::parse(json!({ "city": "Lisbon", "type": "daily", "tags": [] })).unwrap();
+        assert_eq!((args.city.as_str(), args.days, args.kind.as_deref()), ("Lisbon", None, Some("daily")));
+        assert!(args.units.is_empty() && args.tags.is_empty() && !args.cached);
+        assert!(matches!(Forecast::parse(json!({ "days": 2 })), Err(ToolError::InvalidArguments(_))));
+    }
+
+    #[test]
+    fn test_rename_all_and_deny_unknown_fields() {
+        let schema = Search::parameters();
+        assert_eq!(schema["required"], json!(["queryText"]));
+        assert!(schema["properties"].get("maxResults").is_some());
+        assert_eq!(schema["additionalProperties"], json!(false));
+
+        let args = Search::parse(json!({ "queryText": "rust", "maxResults": 3 })).unwrap();
+        assert_eq!((args.query_text.as_str(), args.max_results), ("rust", Some(3)));
+        assert!(Search::parse(json!({ "queryText": "rust", "limit": 3 })).is_err());
+    }
+
+    #[test]
+    fn test_container_default_and_flatten() {
+        assert_eq!(Paging::parameters()["required"], json!([]));
+
+        let schema = Listing::parameters();
+        let properties: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(properties, ["collection", "page", "per_page"]);
+        assert_eq!(schema["required"], json!(["collection"]));
+
+        let args = Listing::parse(json!({ "collection": "docs", "page": 2 })).unwrap();
+        assert_eq!((args.collection.as_str(), args.paging.page, args.paging.per_page), ("docs", 2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_typed_tool() {
+        let tool = typed_tool!("search", "Searches the docs", |args: Search| {
+            Ok(format!("{} (up to {})", args.query_text, args.max_results.unwrap_or(10)))
+        });
+        assert_eq!((tool.name(), tool.description()), ("search", "Searches the docs"));
+        assert_eq!(tool.parameters(), Search::parameters());
+
+        assert_eq!(tool.execute(json!({ "queryText": "rust" })).await.unwrap(), "rust (up to 10)");
+        assert!(matches!(tool.execute(json!({ "query": "rust" })).await, Err(ToolError::InvalidArguments(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fn_tool_metadata() {
+        let tool = FnTool::new("page", "Pages through results", |args: Paging| async move { Ok(args.page.to_string()) })
+            .with_metadata(ToolMetadata::new().with_namespace("docs"));
+        assert_eq!(tool.metadata().namespace.as_deref(), Some("docs"));
+        assert_eq!(tool.execute(json!({})).await.unwrap(), "0");
+    }
+}
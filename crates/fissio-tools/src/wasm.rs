@@ -0,0 +1,249 @@
+//! Tools implemented as sandboxed WebAssembly modules (`wasm` feature).
+//!
+//! # Guest ABI
+//!
+//! A module exports its `memory` and two functions:
+//!
+//! - `fissio_alloc(len: i32) -> i32` — returns a pointer to `len` free bytes
+//! - `fissio_call(ptr: i32, len: i32) -> i64` — reads the call's arguments as
+//!   UTF-8 JSON from `ptr..ptr + len` and returns where its result is, packed as
+//!   `(result_ptr << 32) | result_len`
+//!
+//! The result is a UTF-8 JSON object, either `{"output": ...}` or
+//! `{"error": "message"}`. A string output is returned as is; anything else is
+//! returned as JSON text.
+//!
+//! Modules get no imports: no WASI, filesystem, clock, or network. A module that
+//! imports anything is rejected when loaded, so build tools for
+//! `wasm32-unknown-unknown`.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+use crate::{Tool, ToolError, ToolMetadata};
+
+/// Fuel for one call when the spec doesn't set it; roughly one unit per instruction.
+pub const DEFAULT_WASM_FUEL: u64 = 100_000_000;
+/// Linear memory limit for one call when the spec doesn't set it.
+pub const DEFAULT_WASM_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// A tool backed by a WebAssembly module, as declared in JSON.
+///
+/// ```json
+/// {
+///   "name": "slugify",
+///   "description": "Turns a title into a URL slug",
+///   "module": "tools/slugify.wasm",
+///   "parameters": { "type": "object", "properties": { "title": { "type": "string" } }, "required": ["title"] },
+///   "fuel": 10000000
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WasmToolSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Path to the `.wasm` file.
+    pub module: PathBuf,
+    /// JSON Schema for the arguments.
+    #[serde(default = "default_parameters")]
+    pub parameters: Value,
+    /// Fuel for each call; a call that uses it all fails.
+    #[serde(default = "default_fuel")]
+    pub fuel: u64,
+    /// Largest linear memory a call may grow to.
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: usize,
+}
+
+fn default_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+fn default_fuel() -> u64 {
+    DEFAULT_WASM_FUEL
+}
+
+fn default_max_memory_bytes() -> usize {
+    DEFAULT_WASM_MEMORY_BYTES
+}
+
+impl WasmToolSpec {
+    pub fn new(name: impl Into<String>, module: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            module: module.into(),
+            parameters: default_parameters(),
+            fuel: DEFAULT_WASM_FUEL,
+            max_memory_bytes: DEFAULT_WASM_MEMORY_BYTES,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_parameters(mut self, schema: Value) -> Self {
+        self.parameters = schema;
+        self
+    }
+
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    pub fn with_max_memory_bytes(mut self, bytes: usize) -> Self {
+        self.max_memory_bytes = bytes;
+        self
+    }
+
+    /// Reads a JSON array of specs. Relative `module` paths are resolved
+    /// against the file's directory.
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Vec<Self>, ToolError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to read {}: {}", path.display(), e)))?;
+        let mut specs: Vec<Self> = serde_json::from_str(&text)
+            .map_err(|e| ToolError::InvalidArguments(format!("invalid WASM tool file {}: {}", path.display(), e)))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for spec in &mut specs {
+            if spec.module.is_relative() {
+                spec.module = dir.join(&spec.module);
+            }
+        }
+        Ok(specs)
+    }
+}
+
+/// State of one call's store.
+struct Guest {
+    limits: StoreLimits,
+}
+
+/// A tool built from a [`WasmToolSpec`].
+///
+/// The module is compiled once; every call runs in a fresh instance with the
+/// spec's fuel and memory limits, so calls share no state.
+#[derive(Clone)]
+pub struct WasmTool {
+    spec: WasmToolSpec,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmTool {
+    /// Compiles the spec's module and checks that it needs no imports.
+    pub fn new(spec: WasmToolSpec) -> Result<Self, ToolError> {
+        let bytes = std::fs::read(&spec.module).map_err(|e| {
+            ToolError::ExecutionFailed(format!("failed to read WASM module {}: {}", spec.module.display(), e))
+        })?;
+        Self::from_bytes(spec, &bytes)
+    }
+
+    /// Like [`new`](Self::new), with the module's bytes (binary or text format) given directly.
+    pub fn from_bytes(spec: WasmToolSpec, bytes: &[u8]) -> Result<Self, ToolError> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        let module = Module::new(&engine, bytes)
+            .map_err(|e| ToolError::InvalidArguments(format!("WASM tool '{}' has an invalid module: {}", spec.name, e)))?;
+
+        if let Some(import) = module.imports().next() {
+            return Err(ToolError::InvalidArguments(format!(
+                "WASM tool '{}' imports {}::{}; tools run without host functions",
+                spec.name,
+                import.module(),
+                import.name()
+            )));
+        }
+        Ok(Self { spec, engine, module })
+    }
+
+    pub fn spec(&self) -> &WasmToolSpec {
+        &self.spec
+    }
+
+    /// Runs one call to completion on the current thread.
+    ///
+    /// Fuel bounds how long a call can run; the guest cannot be interrupted otherwise.
+    fn call(&self, args: &[u8]) -> Result<Vec<u8>, ToolError> {
+        let limits = StoreLimitsBuilder::new().memory_size(self.spec.max_memory_bytes).instances(1).build();
+        let mut store = Store::new(&self.engine, Guest { limits });
+        store.limiter(|guest| &mut guest.limits);
+        store.set_fuel(self.spec.fuel).map_err(|e| self.failed(e))?;
+
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(|e| self.failed(e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| ToolError::ExecutionFailed(format!("WASM tool '{}' does not export memory", self.spec.name)))?;
+        let alloc = instance.get_typed_func::<u32, u32>(&mut store, "fissio_alloc").map_err(|e| self.failed(e))?;
+        let call = instance.get_typed_func::<(u32, u32), u64>(&mut store, "fissio_call").map_err(|e| self.failed(e))?;
+
+        let len = u32::try_from(args.len())
+            .map_err(|_| ToolError::InvalidArguments(format!("arguments for '{}' are too large", self.spec.name)))?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| self.failed(e))?;
+        memory.write(&mut store, ptr as usize, args).map_err(|e| self.failed(e.into()))?;
+
+        let packed = call.call(&mut store, (ptr, len)).map_err(|e| self.failed(e))?;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut out = vec![0; out_len];
+        memory.read(&store, out_ptr, &mut out).map_err(|e| self.failed(e.into()))?;
+        Ok(out)
+    }
+
+    fn failed(&self, e: wasmtime::Error) -> ToolError {
+        if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+            return ToolError::ExecutionFailed(format!("WASM tool '{}' ran out of fuel ({})", self.spec.name, self.spec.fuel));
+        }
+        ToolError::ExecutionFailed(format!("WASM tool '{}' failed: {}", self.spec.name, e))
+    }
+}
+
+#[async_trait]
+impl Tool for WasmTool {
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn description(&self) -> &str {
+        &self.spec.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.spec.parameters.clone()
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("wasm")
+    }
+
+    async fn execute(&self, args: Value) -> Result<String, ToolError> {
+        let args = args.to_string().into_bytes();
+        let tool = self.clone();
+        let out = tokio::task::spawn_blocking(move || tool.call(&args))
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("WASM tool '{}' panicked: {}", self.spec.name, e)))??;
+
+        let result: Value = serde_json::from_slice(&out).map_err(|e| {
+            ToolError::ExecutionFailed(format!("WASM tool '{}' returned invalid JSON: {}", self.spec.name, e))
+        })?;
+        if let Some(message) = result.get("error") {
+            let message = message.as_str().map(String::from).unwrap_or_else(|| message.to_string());
+            return Err(ToolError::ExecutionFailed(message));
+        }
+        match result.get("output") {
+            Some(Value::String(text)) => Ok(text.clone()),
+            Some(output) => Ok(output.to_string()),
+            None => Err(ToolError::ExecutionFailed(format!(
+                "WASM tool '{}' returned neither output nor error",
+                self.spec.name
+            ))),
+        }
+    }
+}
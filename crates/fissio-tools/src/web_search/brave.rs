@@ -0,0 +1,77 @@
+//! Brave Search API.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{check_status, parse_json, strip_tags, SearchHit, SearchProvider, SearchResults};
+use crate::ToolError;
+
+const BRAVE_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+/// Most results Brave returns per request.
+const BRAVE_MAX_COUNT: usize = 20;
+
+/// Searches with the Brave Search API's web results.
+pub struct BraveSearch {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl BraveSearch {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveResponse {
+    #[serde(default)]
+    web: Option<BraveWeb>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveWeb {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &str {
+        "Brave"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResults, ToolError> {
+        let count = max_results.min(BRAVE_MAX_COUNT).to_string();
+        let response = self
+            .client
+            .get(BRAVE_URL)
+            .query(&[("q", query), ("count", count.as_str())])
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .send()
+            .await?;
+        let response: BraveResponse = parse_json(self.name(), check_status(self.name(), response).await?).await?;
+
+        let hits = response
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .take(max_results)
+            // Brave marks query terms with <strong> in titles and descriptions
+            .map(|r| SearchHit { title: strip_tags(&r.title), url: r.url, snippet: strip_tags(&r.description) })
+            .collect();
+        Ok(SearchResults { answer: None, hits })
+    }
+}
@@ -0,0 +1,96 @@
+//! DuckDuckGo's HTML results page, for searching without an API key.
+
+use async_trait::async_trait;
+use reqwest::Url;
+
+use super::{check_status, strip_tags, SearchHit, SearchProvider, SearchResults};
+use crate::ToolError;
+
+const DUCKDUCKGO_URL: &str = "https://html.duckduckgo.com/html/";
+
+/// Searches DuckDuckGo by reading its no-JavaScript results page.
+///
+/// Needs no key, but the page is not an API: DuckDuckGo may rate limit or
+/// change the markup, so prefer a keyed provider for production pipelines.
+pub struct DuckDuckGoSearch {
+    client: reqwest::Client,
+}
+
+impl DuckDuckGoSearch {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent("Mozilla/5.0 (compatible; AgentBot/1.0)")
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for DuckDuckGoSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoSearch {
+    fn name(&self) -> &str {
+        "DuckDuckGo"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResults, ToolError> {
+        let response = self.client.post(DUCKDUCKGO_URL).form(&[("q", query)]).send().await?;
+        let html = check_status(self.name(), response).await?.text().await?;
+        Ok(SearchResults { answer: None, hits: parse_results(&html, max_results) })
+    }
+}
+
+/// Reads results from the page: each title is an `a.result__a` link, followed
+/// by an `a.result__snippet` holding the excerpt.
+fn parse_results(html: &str, max_results: usize) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+    let mut rest = html;
+    while hits.len() < max_results {
+        let Some((href, title, after)) = next_link(rest, "result__a") else { break };
+        rest = after;
+        // The snippet belongs to this result only if it comes before the next title
+        let next_title = rest.find("class=\"result__a\"").unwrap_or(rest.len());
+        let snippet = match next_link(&rest[..next_title], "result__snippet") {
+            Some((_, snippet, _)) => strip_tags(snippet),
+            None => String::new(),
+        };
+        let Some(url) = result_url(href) else { continue };
+        hits.push(SearchHit { title: strip_tags(title), url, snippet });
+    }
+    hits
+}
+
+/// Finds the next `<a>` with `class` and returns its `href`, inner HTML, and the text after it.
+fn next_link<'a>(html: &'a str, class: &str) -> Option<(&'a str, &'a str, &'a str)> {
+    let marker = format!("class=\"{}\"", class);
+    let at = html.find(&marker)?;
+    let open = html[..at].rfind("<a")?;
+    let tag_end = at + html[at..].find('>')?;
+    let tag = &html[open..tag_end];
+    let href = tag.split("href=\"").nth(1)?.split('"').next()?;
+    let body = &html[tag_end + 1..];
+    let close = body.find("</a>")?;
+    Some((href, &body[..close], &body[close + 4..]))
+}
+
+/// Resolves DuckDuckGo's redirect links (`//duckduckgo.com/l/?uddg=<url>`) to the target URL.
+fn result_url(href: &str) -> Option<String> {
+    let href = href.replace("&amp;", "&");
+    let absolute = if href.starts_with("//") { format!("https:{}", href) } else { href };
+    let url = Url::parse(&absolute).ok()?;
+    if url.path().starts_with("/l/") {
+        return url.query_pairs().find(|(k, _)| k == "uddg").map(|(_, v)| v.into_owned());
+    }
+    // Ads point back at duckduckgo.com/y.js; skip them
+    if url.host_str().is_some_and(|h| h.ends_with("duckduckgo.com")) {
+        return None;
+    }
+    Some(absolute)
+}
@@ -0,0 +1,288 @@
+//! The `web_search` tool and the search APIs behind it.
+
+mod brave;
+mod duckduckgo;
+mod serpapi;
+mod tavily;
+
+pub use brave::BraveSearch;
+pub use duckduckgo::DuckDuckGoSearch;
+pub use serpapi::SerpApiSearch;
+pub use tavily::TavilySearch;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::warn;
+
+use crate::{CostHint, Tool, ToolError, ToolMetadata};
+
+/// Results returned when the caller doesn't ask for a number.
+const DEFAULT_MAX_RESULTS: usize = 5;
+/// Most results a single call may ask for.
+const MAX_RESULTS: usize = 20;
+
+/// One web search result, in the same shape whichever provider returned it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub title: String,
+    pub url: String,
+    /// Plain-text excerpt; providers' highlighting markup is removed.
+    pub snippet: String,
+}
+
+/// What a [`SearchProvider`] found for a query.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchResults {
+    /// A direct answer, for providers that compose one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    pub hits: Vec<SearchHit>,
+}
+
+/// A web search API.
+///
+/// Implementations translate the provider's response into [`SearchResults`],
+/// so pipelines see the same output whichever API answers.
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Short name used in error messages, e.g. `brave`.
+    fn name(&self) -> &str;
+
+    /// Returns up to `max_results` results for `query`.
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResults, ToolError>;
+}
+
+/// The built-in search providers, as named in `WEB_SEARCH_PROVIDER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProviderKind {
+    Tavily,
+    Brave,
+    SerpApi,
+    DuckDuckGo,
+}
+
+impl SearchProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tavily => "tavily",
+            Self::Brave => "brave",
+            Self::SerpApi => "serpapi",
+            Self::DuckDuckGo => "duckduckgo",
+        }
+    }
+
+    /// The environment variable holding the provider's API key; DuckDuckGo needs none.
+    pub fn api_key_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Tavily => Some("TAVILY_API_KEY"),
+            Self::Brave => Some("BRAVE_API_KEY"),
+            Self::SerpApi => Some("SERPAPI_API_KEY"),
+            Self::DuckDuckGo => None,
+        }
+    }
+}
+
+impl fmt::Display for SearchProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SearchProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "tavily" => Ok(Self::Tavily),
+            "brave" => Ok(Self::Brave),
+            "serpapi" => Ok(Self::SerpApi),
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            other => Err(format!(
+                "unknown search provider '{}' (expected tavily, brave, serpapi, or duckduckgo)",
+                other
+            )),
+        }
+    }
+}
+
+/// Web search tool, backed by any [`SearchProvider`].
+pub struct WebSearchTool {
+    provider: Arc<dyn SearchProvider>,
+}
+
+impl WebSearchTool {
+    /// Creates a tool that searches with Tavily.
+    pub fn new(api_key: String) -> Self {
+        Self::with_provider(Arc::new(TavilySearch::new(api_key)))
+    }
+
+    /// Creates a tool that searches with `provider`.
+    pub fn with_provider(provider: Arc<dyn SearchProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Creates a tool for a built-in provider; `api_key` is ignored by DuckDuckGo.
+    pub fn for_kind(kind: SearchProviderKind, api_key: Option<String>) -> Result<Self, ToolError> {
+        let key = || {
+            api_key.clone().filter(|k| !k.is_empty()).ok_or_else(|| {
+                ToolError::InvalidArguments(format!("the {} search provider needs an API key", kind))
+            })
+        };
+        let provider: Arc<dyn SearchProvider> = match kind {
+            SearchProviderKind::Tavily => Arc::new(TavilySearch::new(key()?)),
+            SearchProviderKind::Brave => Arc::new(BraveSearch::new(key()?)),
+            SearchProviderKind::SerpApi => Arc::new(SerpApiSearch::new(key()?)),
+            SearchProviderKind::DuckDuckGo => Arc::new(DuckDuckGoSearch::new()),
+        };
+        Ok(Self::with_provider(provider))
+    }
+
+    /// Creates a tool from `WEB_SEARCH_PROVIDER` and the provider's API key variable.
+    ///
+    /// Without `WEB_SEARCH_PROVIDER`, uses the first of Tavily, Brave, and
+    /// SerpAPI whose key is set. DuckDuckGo needs no key but is only used
+    /// when named, since it reads the HTML results page rather than an API.
+    pub fn from_env() -> Option<Self> {
+        let key_for = |kind: SearchProviderKind| kind.api_key_var().and_then(|var| std::env::var(var).ok());
+        let kind = match std::env::var("WEB_SEARCH_PROVIDER") {
+            Ok(name) if !name.trim().is_empty() => match name.parse() {
+                Ok(kind) => kind,
+                Err(e) => {
+                    warn!("{}; web_search is disabled", e);
+                    return None;
+                }
+            },
+            _ => [SearchProviderKind::Tavily, SearchProviderKind::Brave, SearchProviderKind::SerpApi]
+                .into_iter()
+                .find(|kind| key_for(*kind).is_some_and(|k| !k.is_empty()))?,
+        };
+        match Self::for_kind(kind, key_for(kind)) {
+            Ok(tool) => Some(tool),
+            Err(e) => {
+                warn!("{}; web_search is disabled", e);
+                None
+            }
+        }
+    }
+
+    /// The provider answering searches.
+    pub fn provider(&self) -> &str {
+        self.provider.name()
+    }
+}
+
+#[async_trait]
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web for information. Returns relevant results with titles, URLs, and content snippets."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "The search query"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum number of results to return (default: 5)",
+                    "default": 5
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata::new().with_namespace("web").with_tag("network").with_cost(CostHint::Low)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<String, ToolError> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArguments("Missing 'query' parameter".to_string()))?;
+
+        let max_results = args
+            .get("max_results")
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as usize).clamp(1, MAX_RESULTS))
+            .unwrap_or(DEFAULT_MAX_RESULTS);
+
+        let results = self.provider.search(query, max_results).await?;
+        Ok(format_results(&results))
+    }
+}
+
+/// Formats results as readable text.
+fn format_results(results: &SearchResults) -> String {
+    let mut output = String::new();
+
+    if let Some(answer) = &results.answer {
+        output.push_str(&format!("**Summary:** {}\n\n", answer));
+    }
+
+    output.push_str("**Search Results:**\n\n");
+
+    for (i, hit) in results.hits.iter().enumerate() {
+        output.push_str(&format!("{}. **{}**\n   URL: {}\n   {}\n\n", i + 1, hit.title, hit.url, hit.snippet));
+    }
+
+    output
+}
+
+/// Returns the response if it succeeded, or an error naming the provider and status.
+async fn check_status(provider: &str, response: reqwest::Response) -> Result<reqwest::Response, ToolError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_else(|e| {
+        warn!("Failed to read error response body: {}", e);
+        String::new()
+    });
+    Err(ToolError::ExecutionFailed(format!("{} API error: {} - {}", provider, status, body)))
+}
+
+/// Parses a provider's JSON response.
+async fn parse_json<T: serde::de::DeserializeOwned>(provider: &str, response: reqwest::Response) -> Result<T, ToolError> {
+    response
+        .json()
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("Failed to parse {} response: {}", provider, e)))
+}
+
+/// Removes HTML tags and decodes common entities in a snippet.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
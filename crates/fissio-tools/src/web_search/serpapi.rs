@@ -0,0 +1,91 @@
+//! SerpAPI (Google results).
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{check_status, parse_json, SearchHit, SearchProvider, SearchResults};
+use crate::ToolError;
+
+const SERPAPI_URL: &str = "https://serpapi.com/search.json";
+
+/// Searches Google through SerpAPI.
+pub struct SerpApiSearch {
+    api_key: String,
+    engine: String,
+    client: reqwest::Client,
+}
+
+impl SerpApiSearch {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            engine: "google".to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Uses another SerpAPI engine, such as `bing` or `duckduckgo`.
+    pub fn with_engine(mut self, engine: impl Into<String>) -> Self {
+        self.engine = engine.into();
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpApiResponse {
+    #[serde(default)]
+    organic_results: Vec<SerpApiResult>,
+    #[serde(default)]
+    answer_box: Option<SerpApiAnswerBox>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpApiResult {
+    title: String,
+    link: String,
+    #[serde(default)]
+    snippet: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpApiAnswerBox {
+    #[serde(default)]
+    answer: Option<String>,
+    #[serde(default)]
+    snippet: Option<String>,
+}
+
+#[async_trait]
+impl SearchProvider for SerpApiSearch {
+    fn name(&self) -> &str {
+        "SerpAPI"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResults, ToolError> {
+        let num = max_results.to_string();
+        let response = self
+            .client
+            .get(SERPAPI_URL)
+            .query(&[("engine", self.engine.as_str()), ("q", query), ("num", num.as_str()), ("api_key", self.api_key.as_str())])
+            .send()
+            .await?;
+        let response: SerpApiResponse = parse_json(self.name(), check_status(self.name(), response).await?).await?;
+
+        // SerpAPI reports some failures, such as an exhausted plan, with status 200
+        if let Some(error) = response.error {
+            return Err(ToolError::ExecutionFailed(format!("SerpAPI error: {}", error)));
+        }
+
+        Ok(SearchResults {
+            answer: response.answer_box.and_then(|b| b.answer.or(b.snippet)),
+            hits: response
+                .organic_results
+                .into_iter()
+                .take(max_results)
+                .map(|r| SearchHit { title: r.title, url: r.link, snippet: r.snippet })
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,74 @@
+//! Tavily search API.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{check_status, parse_json, SearchHit, SearchProvider, SearchResults};
+use crate::ToolError;
+
+const TAVILY_URL: &str = "https://api.tavily.com/search";
+
+/// Searches with Tavily, which also composes a short answer.
+pub struct TavilySearch {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl TavilySearch {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TavilyRequest<'a> {
+    api_key: &'a str,
+    query: &'a str,
+    max_results: usize,
+    search_depth: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct TavilyResponse {
+    results: Vec<TavilyResult>,
+    #[serde(default)]
+    answer: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TavilyResult {
+    title: String,
+    url: String,
+    content: String,
+}
+
+#[async_trait]
+impl SearchProvider for TavilySearch {
+    fn name(&self) -> &str {
+        "Tavily"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResults, ToolError> {
+        let request = TavilyRequest {
+            api_key: &self.api_key,
+            query,
+            max_results,
+            search_depth: "basic",
+        };
+
+        let response = self.client.post(TAVILY_URL).json(&request).send().await?;
+        let response: TavilyResponse = parse_json(self.name(), check_status(self.name(), response).await?).await?;
+
+        Ok(SearchResults {
+            answer: response.answer.filter(|a| !a.is_empty()),
+            hits: response
+                .results
+                .into_iter()
+                .map(|r| SearchHit { title: r.title, url: r.url, snippet: r.content })
+                .collect(),
+        })
+    }
+}
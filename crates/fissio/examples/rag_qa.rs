@@ -0,0 +1,104 @@
+//! Runs the RAG QA preset end to end on the mock provider: handbook pages are
+//! ingested into an in-memory store, a retriever node finds the relevant
+//! chunks, and the answer node cites them.
+//!
+//! Run with: cargo run --example rag_qa --features testing
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use fissio::prelude::*;
+use fissio::{Document, DocumentIngestor, Embedder, InMemoryVectorStore, MockProvider, Retriever, VectorStoreError};
+use futures::StreamExt;
+
+const PRESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/presets/rag-qa.json");
+
+const HANDBOOK: [(&str, &str, &str); 3] = [
+    ("vacation", "Vacation policy", "Full-time employees receive 25 vacation days per year, accrued monthly."),
+    ("expenses", "Expense policy", "Submit expense reports within 30 days with itemized receipts attached."),
+    ("remote", "Remote work", "Employees may work remotely up to three days per week with manager approval."),
+];
+
+const ANSWER: &str = "Full-time employees get 25 vacation days per year [1].";
+
+/// Hashes words into a small vector, so texts sharing words score as similar.
+struct WordEmbedder;
+
+#[async_trait]
+impl Embedder for WordEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VectorStoreError> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let mut vector = vec![0.0; 64];
+                for word in text.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() > 3) {
+                    let mut hasher = DefaultHasher::new();
+                    word.hash(&mut hasher);
+                    vector[(hasher.finish() % 64) as usize] += 1.0;
+                }
+                vector
+            })
+            .collect())
+    }
+}
+
+fn mock() -> Arc<MockProvider> {
+    MockProvider::new().reply("numbered sources", ANSWER).install("mock")
+}
+
+async fn run(question: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let retriever = Arc::new(Retriever::new(Arc::new(InMemoryVectorStore::new()), Arc::new(WordEmbedder)));
+    let ingestor = DocumentIngestor::new(Arc::clone(&retriever), "handbook");
+    for (id, title, text) in HANDBOOK {
+        ingestor.ingest(&Document::new(id, title, text)).await?;
+    }
+
+    let config = PipelineConfig::from_file(PRESET)?;
+    let model = MockProvider::model("mock", "qa-model");
+    let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), ToolRegistry::new())
+        .with_retriever(retriever);
+
+    let mut text = String::new();
+    match engine.execute_stream(question, &[]).await? {
+        EngineOutput::Complete(complete) => text = complete,
+        EngineOutput::Stream(mut stream) => {
+            while let Some(chunk) = stream.next().await {
+                match chunk? {
+                    StreamChunk::Content(content) => text.push_str(&content),
+                    StreamChunk::Citation(citation) => println!("cited: {}", citation.source_id),
+                    _ => {}
+                }
+            }
+        }
+        EngineOutput::Empty(reason) => text = reason.message().to_string(),
+        _ => {}
+    }
+    Ok(text)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    mock();
+    println!("{}", run("How many vacation days do full-time employees get?").await?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rag_qa() {
+        let mock = mock();
+        let answer = run("How many vacation days do full-time employees get?").await.unwrap();
+        assert_eq!(answer, ANSWER);
+
+        // The answer node was given the vacation chunk as source [1]
+        let requests = mock.requests();
+        let prompt = requests.iter().find(|r| r.system_prompt.contains("numbered sources")).unwrap();
+        assert!(prompt.input.contains("25 vacation days"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Runs the research preset end to end on the mock provider: a worker calls
+//! `fetch_url` (mocked) and a second node summarizes what it found.
+//!
+//! Run with: cargo run --example research_pipeline --features testing
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use fissio::prelude::*;
+use fissio::{MockProvider, ToolMock};
+use futures::StreamExt;
+use serde_json::json;
+
+const PRESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/presets/research.json");
+
+const PAGE: &str = "Rust 1.0 was released on May 15, 2015. It guarantees memory safety without a garbage collector.";
+const SUMMARY: &str = "Rust reached 1.0 in May 2015. It is memory safe without a garbage collector.";
+
+/// Scripts the models behind the preset's two nodes.
+fn mock() -> Arc<MockProvider> {
+    MockProvider::new()
+        .tool_call("research assistant", "fetch_url", json!({ "url": "https://www.rust-lang.org/" }))
+        .reply("research assistant", "Findings: Rust 1.0 shipped in May 2015; memory safe, no GC.")
+        .reply("Summarize", SUMMARY)
+        .install("mock")
+}
+
+async fn run(question: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let config = PipelineConfig::from_file(PRESET)?;
+    let model = MockProvider::model("mock", "research-model");
+
+    let mut tools = ToolRegistry::new();
+    tools.mock("fetch_url", ToolMock::Output(PAGE.to_string()));
+
+    let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), tools);
+    Ok(collect(engine.execute_stream(question, &[]).await?).await?)
+}
+
+async fn collect(output: EngineOutput) -> Result<String, AgentError> {
+    match output {
+        EngineOutput::Complete(text) => Ok(text),
+        EngineOutput::Stream(mut stream) => {
+            let mut text = String::new();
+            while let Some(chunk) = stream.next().await {
+                if let StreamChunk::Content(content) = chunk? {
+                    text.push_str(&content);
+                }
+            }
+            Ok(text)
+        }
+        EngineOutput::Empty(reason) => Ok(reason.message().to_string()),
+        _ => Ok(String::new()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mock = mock();
+    println!("{}", run("When was Rust 1.0 released?").await?);
+    println!("({} model calls)", mock.requests().len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_research_pipeline() {
+        let mock = mock();
+        let answer = run("When was Rust 1.0 released?").await.unwrap();
+        assert_eq!(answer, SUMMARY);
+
+        let requests = mock.requests();
+        // The worker saw the fetched page after its tool call
+        assert!(requests.iter().any(|r| r.system_prompt.contains("research assistant") && r.input.contains(PAGE)));
+        // The summarizer received the worker's findings
+        let summarizer = requests.iter().find(|r| r.system_prompt.contains("Summarize")).unwrap();
+        assert!(summarizer.input.contains("Findings:"));
+    }
+}
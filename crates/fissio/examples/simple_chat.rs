@@ -13,6 +13,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         name: "GPT-4".into(),
         model: "gpt-4-turbo".into(),
         api_base: None, // Uses OPENAI_API_KEY env var
+        mirrors: Vec::new(),
+        provider: None,
+        rate_limit: None,
+        azure: None,
+        region: None,
+        context_window: None,
+        pricing: None,
     };
 
     // Build a simple pipeline with one LLM node
@@ -41,6 +48,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // For streaming, you would consume the stream
             println!("(Streaming not shown in this example)");
         }
+        EngineOutput::Empty(reason) => eprintln!("{}", reason.message()),
+        _ => {}
     }
 
     Ok(())
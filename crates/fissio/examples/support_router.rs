@@ -0,0 +1,75 @@
+//! Runs the support router preset end to end on the mock provider: the router
+//! classifies each message and only the chosen handler answers.
+//!
+//! Run with: cargo run --example support_router --features testing
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use fissio::prelude::*;
+use fissio::MockProvider;
+use futures::StreamExt;
+
+const PRESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/presets/support-router.json");
+
+fn mock() -> Arc<MockProvider> {
+    // Rules match in order, so handler prompts come before the router's keywords
+    MockProvider::new()
+        .reply("billing specialist", "I've reopened the invoice so you can retry the payment.")
+        .reply("technical support", "Please update to version 2.4, which fixes that crash.")
+        .reply("customer service", "Our office is open 9am to 5pm on weekdays.")
+        .reply("payment", "billing")
+        .reply("crash", "technical")
+        .reply("routing classifier", "general")
+        .install("mock")
+}
+
+async fn run(message: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let config = PipelineConfig::from_file(PRESET)?;
+    let model = MockProvider::model("mock", "support-model");
+    let engine = PipelineEngine::with_tools(config, vec![model.clone()], model, HashMap::new(), ToolRegistry::new());
+
+    let mut text = String::new();
+    match engine.execute_stream(message, &[]).await? {
+        EngineOutput::Complete(complete) => text = complete,
+        EngineOutput::Stream(mut stream) => {
+            while let Some(chunk) = stream.next().await {
+                if let StreamChunk::Content(content) = chunk? {
+                    text.push_str(&content);
+                }
+            }
+        }
+        EngineOutput::Empty(reason) => text = reason.message().to_string(),
+        _ => {}
+    }
+    Ok(text)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    mock();
+    for message in ["My payment failed twice", "The app crashes on launch", "When are you open?"] {
+        println!("> {}\n{}\n", message, run(message).await?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_routes_to_one_handler() {
+        let mock = mock();
+        assert_eq!(run("My payment failed twice").await.unwrap(), "I've reopened the invoice so you can retry the payment.");
+        assert_eq!(run("The app crashes on launch").await.unwrap(), "Please update to version 2.4, which fixes that crash.");
+        assert_eq!(run("When are you open?").await.unwrap(), "Our office is open 9am to 5pm on weekdays.");
+
+        // Three router calls and three handler calls; no handler ran for another's message
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 6);
+        let billing: Vec<_> = requests.iter().filter(|r| r.system_prompt.contains("billing specialist")).collect();
+        assert_eq!(billing.len(), 1);
+        assert!(billing[0].input.contains("payment"));
+    }
+}
@@ -44,7 +44,8 @@
 //! | [`fissio_config`] | Pipeline schema, node/edge types |
 //! | [`fissio_core`] | Error types, messages, model config |
 //! | [`fissio_engine`] | DAG execution engine |
-//! | [`fissio_llm`] | LLM providers (OpenAI, Anthropic, Ollama) |
+//! | [`fissio_llm`] | LLM providers (OpenAI, Anthropic, Ollama) and embeddings |
+//! | [`fissio_rag`] | Vector stores (in-memory, SQLite, Qdrant, pgvector), hybrid retrieval, the `vector_search` tool, document ingestion, connectors, and agent memory |
 //! | [`fissio_tools`] | Tool registry and built-in tools |
 //!
 //! ## Node Types
@@ -63,30 +64,82 @@
 //! - `Parallel` — Concurrent execution
 //! - `Conditional` — Router chooses path
 //! - `Dynamic` — Orchestrator picks targets
+//!
+//! ## Stability
+//!
+//! The [`prelude`] and the items re-exported here are the supported API;
+//! items marked `#[doc(hidden)]` are internal. Enums that gain variants as
+//! features land ([`NodeType`], [`EdgeType`], [`FailurePolicy`], [`AgentError`],
+//! [`ToolError`], [`ConfigError`], [`VectorStoreError`], [`StreamChunk`],
+//! [`EngineOutput`], [`EmptyReason`]) are `#[non_exhaustive]`, so matches on
+//! them need a `_` arm.
+//!
+//! Patch releases don't break this API. Items are deprecated for at least one
+//! minor release before they are removed.
 
 // Re-export config types
 pub use fissio_config::{
-    ConfigError, EdgeConfig, EdgeEndpoint, EdgeType, NodeConfig, NodeType, PipelineConfig,
-    PresetRegistry,
+    ConfigError, EdgeConfig, EdgeEndpoint, EdgeType, FailurePolicy, NodeConfig, NodeType, PipelineConfig,
+    PresetRegistry, ResidencyPolicy, SecretResolver, SCHEMA_VERSION,
 };
 
 // Re-export builders
 pub use fissio_config::{NodeBuilder, PipelineBuilder};
 
 // Re-export core types
-pub use fissio_core::{AgentError, Message, MessageRole, ModelConfig};
+pub use fissio_core::{
+    AgentError, AzureOpenAiConfig, Citation, Message, MessageRole, ModelConfig, ModelPricing, OutputSchema, Provider,
+    RateLimitConfig,
+};
 
 // Re-export engine
-pub use fissio_engine::{EngineOutput, ModelResolver, NodeInput, NodeOutput, PipelineEngine};
+pub use fissio_engine::{
+    ApprovalRequest, ApprovalResponse, EmptyReason, EngineOutput, ExecutionMetrics, InMemoryLedger, ModelResolver, NodeInput,
+    NodeOutput, PipelineEngine, PipelineTool, SideEffectLedger, SqliteLedger, ToolApprover, ToolDecision, ToolPolicy,
+    VramScheduler,
+};
 
 // Re-export LLM clients
 pub use fissio_llm::{
-    ChatResponse, LlmClient, LlmMetrics, LlmResponse, LlmStream, StreamChunk, ToolCall, ToolSchema,
-    UnifiedLlmClient,
+    estimate_tokens, AudioFormat, ChatResponse, ContextManager, EmbeddingClient, EmbeddingProvider, FittedContext, HostBalancer,
+    InMemoryLlmCache, LlmCache, LlmClient, LlmMetrics, LlmProvider, LlmResponse, LlmStream, ProviderRegistry, RateLimitStatus,
+    RateLimiter, ResponseHeaders, SpeechClient, SqliteLlmCache, StreamChunk, ToolCall, ToolSchema, UnifiedLlmClient,
 };
+#[cfg(feature = "testing")]
+pub use fissio_llm::{MockProvider, MockRequest};
+
+// Re-export vector stores and retrieval
+pub use fissio_rag::{
+    extract_text, ChunkConfig, CitationScanner, CollectionConfig, Connector, ConnectorSync, Distance, Document,
+    DocumentFormat, DocumentIngestor, Embedder, InMemoryMemory, InMemoryVectorStore, Memory, MemoryEntry, MemoryError,
+    MemoryReadTool, MemoryWriteTool, MetadataFilter, MultiQueryConfig, RerankConfig, Reranker, RetrievalConfig, Retriever,
+    SearchMode, SearchResult, SqliteMemory, SqliteVectorStore, SyncReport, VectorRecord, VectorSearchTool, VectorStore,
+    VectorStoreError,
+};
+#[cfg(feature = "connectors")]
+pub use fissio_rag::{ConfluenceConnector, NotionConnector, RssConnector, SitemapConnector};
+#[cfg(feature = "rerank")]
+pub use fissio_rag::ApiReranker;
+#[cfg(feature = "keyword")]
+pub use fissio_rag::KeywordIndex;
+#[cfg(feature = "pgvector")]
+pub use fissio_rag::PgVectorStore;
+#[cfg(feature = "qdrant")]
+pub use fissio_rag::QdrantVectorStore;
 
 // Re-export tools
-pub use fissio_tools::{FetchUrlTool, Tool, ToolError, ToolRegistry, WebSearchTool};
+pub use fissio_tools::{
+    typed_tool, ArgSchema, CommandTool, CostHint, CurrentTimeTool, FetchUrlTool, FileReadTool, FileWriteTool, FnTool, FsRoot,
+    HostAllowlist, HttpTool, HttpToolSpec, ListDirTool, McpTool, McpToolProvider, McpTransport, MockTool, PostProcessor,
+    PythonTool, SearchHit, SearchProvider, SearchProviderKind, SearchResults, Timezone, Tool, ToolArgs, ToolError,
+    ToolExecutionPolicy, ToolMetadata, ToolMock, ToolRegistry, WebSearchTool,
+};
+/// The tools crate, for `#[tool_args(crate = "fissio::tools")]` on a `#[derive(ToolArgs)]` struct.
+pub use fissio_tools as tools;
+#[cfg(feature = "sql")]
+pub use fissio_tools::{SqlConnection, SqlQueryTool};
+#[cfg(feature = "wasm")]
+pub use fissio_tools::{WasmTool, WasmToolSpec};
 
 // Re-export editor (optional feature)
 #[cfg(feature = "editor")]
@@ -95,8 +148,8 @@ pub use fissio_editor as editor;
 // Provider-specific clients (hidden by default, use UnifiedLlmClient instead)
 #[doc(hidden)]
 pub use fissio_llm::{
-    discover_models, unload_model, AnthropicClient, OllamaClient, OllamaMetrics,
-    OllamaMetricsCollector,
+    discover_models, discover_models_on, load_model, loaded_models, model_sizes, unload_model, AnthropicClient, OllamaClient, OllamaLoadedModel,
+    OllamaMetrics, OllamaMetricsCollector,
 };
 
 
@@ -107,6 +160,8 @@ pub use fissio_llm::{
 /// ```rust,ignore
 /// use fissio::prelude::*;
 /// ```
+///
+/// Items are only added here, never removed without a deprecation period.
 pub mod prelude {
     // Core types
     pub use crate::{AgentError, Message, MessageRole, ModelConfig};
@@ -115,11 +170,11 @@ pub mod prelude {
     pub use crate::{EdgeConfig, EdgeEndpoint, EdgeType, NodeConfig, NodeType, PipelineConfig};
 
     // Engine
-    pub use crate::{EngineOutput, PipelineEngine};
+    pub use crate::{EmptyReason, EngineOutput, PipelineEngine};
 
     // LLM
     pub use crate::{ChatResponse, LlmResponse, LlmStream, StreamChunk, UnifiedLlmClient};
 
     // Tools
-    pub use crate::{Tool, ToolError, ToolRegistry, ToolSchema};
+    pub use crate::{typed_tool, Tool, ToolArgs, ToolError, ToolRegistry, ToolSchema};
 }